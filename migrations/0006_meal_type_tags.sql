@@ -0,0 +1,20 @@
+-- Meal classification (meal type + free-form tags)
+ALTER TABLE meals
+ADD COLUMN IF NOT EXISTS meal_type TEXT;
+
+ALTER TABLE meals
+ADD COLUMN IF NOT EXISTS tags TEXT[] NOT NULL DEFAULT '{}';
+
+DO $$
+BEGIN
+    IF NOT EXISTS (
+        SELECT 1 FROM pg_constraint
+        WHERE conname = 'meals_meal_type_valid'
+    ) THEN
+        ALTER TABLE meals
+        ADD CONSTRAINT meals_meal_type_valid
+        CHECK (meal_type IS NULL OR meal_type IN ('breakfast', 'lunch', 'dinner', 'snack'));
+    END IF;
+END$$;
+
+CREATE INDEX IF NOT EXISTS idx_meals_tags ON meals USING GIN (tags);
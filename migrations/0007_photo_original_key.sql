@@ -0,0 +1,3 @@
+-- Original upload kept next to a transcoded photo (e.g. HEIC source of a JPEG)
+ALTER TABLE photos
+ADD COLUMN IF NOT EXISTS original_s3_key TEXT;
@@ -0,0 +1,6 @@
+-- Stored object metadata, filled at upload time (NULL for older photos)
+ALTER TABLE photos
+ADD COLUMN IF NOT EXISTS content_type TEXT,
+ADD COLUMN IF NOT EXISTS size_bytes BIGINT,
+ADD COLUMN IF NOT EXISTS width INTEGER,
+ADD COLUMN IF NOT EXISTS height INTEGER;
@@ -0,0 +1,7 @@
+-- SHA-256 of the uploaded bytes, used to reuse storage objects for repeat uploads
+ALTER TABLE photos
+ADD COLUMN IF NOT EXISTS content_hash TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_photos_user_content_hash
+ON photos (user_id, content_hash)
+WHERE content_hash IS NOT NULL;
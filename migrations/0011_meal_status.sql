@@ -0,0 +1,19 @@
+-- Analysis progress of a meal
+ALTER TABLE meals
+ADD COLUMN IF NOT EXISTS status TEXT NOT NULL DEFAULT 'pending';
+
+DO $$
+BEGIN
+    IF NOT EXISTS (
+        SELECT 1 FROM pg_constraint
+        WHERE conname = 'meals_status_valid'
+    ) THEN
+        ALTER TABLE meals
+        ADD CONSTRAINT meals_status_valid
+        CHECK (status IN ('pending', 'analyzing', 'done', 'failed'));
+    END IF;
+END$$;
+
+-- Meals that already have nutrition were analyzed before status existed
+UPDATE meals SET status = 'done'
+WHERE status = 'pending' AND id IN (SELECT meal_id FROM meal_nutrition);
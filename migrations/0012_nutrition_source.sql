@@ -0,0 +1,16 @@
+-- Where nutrition values came from; manual entries are never overwritten by AI
+ALTER TABLE meal_nutrition
+ADD COLUMN IF NOT EXISTS source TEXT NOT NULL DEFAULT 'ai',
+ADD COLUMN IF NOT EXISTS updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW();
+
+DO $$
+BEGIN
+    IF NOT EXISTS (
+        SELECT 1 FROM pg_constraint
+        WHERE conname = 'meal_nutrition_source_valid'
+    ) THEN
+        ALTER TABLE meal_nutrition
+        ADD CONSTRAINT meal_nutrition_source_valid
+        CHECK (source IN ('ai', 'manual'));
+    END IF;
+END$$;
@@ -0,0 +1,13 @@
+-- Per-user daily nutrition goals (1:1 with users); NULL falls back to the
+-- default target
+CREATE TABLE IF NOT EXISTS goals (
+    user_id UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    daily_calories_kcal NUMERIC(10,2),
+    protein_g NUMERIC(10,2),
+    fat_g NUMERIC(10,2),
+    carbs_g NUMERIC(10,2),
+    fiber_g NUMERIC(10,2),
+    sugar_max_g NUMERIC(10,2),
+    sodium_max_mg NUMERIC(10,2),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,11 @@
+-- Weigh-ins
+CREATE TABLE IF NOT EXISTS weights (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    weight_kg NUMERIC(6,2) NOT NULL CHECK (weight_kg > 0),
+    measured_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    note TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_weights_user_measured_at ON weights(user_id, measured_at);
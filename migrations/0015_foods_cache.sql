@@ -0,0 +1,9 @@
+-- Normalized lookups from external food databases. A NULL data column
+-- records that the source doesn't know the id.
+CREATE TABLE IF NOT EXISTS foods_cache (
+    source TEXT NOT NULL,
+    external_id TEXT NOT NULL,
+    data JSONB,
+    fetched_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (source, external_id)
+);
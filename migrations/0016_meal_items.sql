@@ -0,0 +1,44 @@
+-- Ingredients of a meal, either referencing an external food or free text.
+-- Nutrition is stored for the item's quantity.
+CREATE TABLE IF NOT EXISTS meal_items (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    meal_id UUID NOT NULL REFERENCES meals(id) ON DELETE CASCADE,
+    food_source TEXT,
+    food_id TEXT,
+    name TEXT NOT NULL,
+    quantity NUMERIC(10,2) NOT NULL CHECK (quantity > 0),
+    unit TEXT NOT NULL CHECK (unit IN ('g', 'ml', 'serving')),
+    total_calories_kcal NUMERIC(10,2),
+    protein_g NUMERIC(10,2),
+    fat_g NUMERIC(10,2),
+    carbs_g NUMERIC(10,2),
+    sodium_mg NUMERIC(10,2),
+    sugar_g NUMERIC(10,2),
+    fiber_g NUMERIC(10,2),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_meal_items_meal_id ON meal_items(meal_id);
+
+-- The AI or manual estimate before items are added; the value columns hold
+-- the totals. NULL for rows that only sum items.
+ALTER TABLE meal_nutrition
+ADD COLUMN IF NOT EXISTS base JSONB;
+
+UPDATE meal_nutrition
+SET base = jsonb_build_object(
+    'total_calories_kcal', total_calories_kcal,
+    'protein_g', protein_g,
+    'fat_g', fat_g,
+    'carbs_g', carbs_g,
+    'sodium_mg', sodium_mg,
+    'sugar_g', sugar_g,
+    'fiber_g', fiber_g
+)
+WHERE base IS NULL;
+
+ALTER TABLE meal_nutrition DROP CONSTRAINT IF EXISTS meal_nutrition_source_valid;
+ALTER TABLE meal_nutrition
+ADD CONSTRAINT meal_nutrition_source_valid
+CHECK (source IN ('ai', 'manual', 'items'));
@@ -0,0 +1,20 @@
+-- User-defined foods, with nutrition per serving.
+CREATE TABLE IF NOT EXISTS custom_foods (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    brand TEXT,
+    serving_size_g NUMERIC(10,2) CHECK (serving_size_g > 0),
+    serving_description TEXT,
+    total_calories_kcal NUMERIC(10,2),
+    protein_g NUMERIC(10,2),
+    fat_g NUMERIC(10,2),
+    carbs_g NUMERIC(10,2),
+    sodium_mg NUMERIC(10,2),
+    sugar_g NUMERIC(10,2),
+    fiber_g NUMERIC(10,2),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_custom_foods_user_id ON custom_foods(user_id, lower(name));
@@ -0,0 +1,32 @@
+CREATE TABLE IF NOT EXISTS recipes (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    servings NUMERIC(10,2) NOT NULL CHECK (servings > 0),
+    instructions TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_recipes_user_id ON recipes(user_id, lower(name));
+
+-- Same shape as meal_items; nutrition is for the ingredient's quantity in the
+-- whole recipe.
+CREATE TABLE IF NOT EXISTS recipe_ingredients (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    recipe_id UUID NOT NULL REFERENCES recipes(id) ON DELETE CASCADE,
+    position INT NOT NULL,
+    food_source TEXT,
+    food_id TEXT,
+    name TEXT NOT NULL,
+    quantity NUMERIC(10,2) NOT NULL CHECK (quantity > 0),
+    unit TEXT NOT NULL CHECK (unit IN ('g', 'ml', 'serving')),
+    total_calories_kcal NUMERIC(10,2),
+    protein_g NUMERIC(10,2),
+    fat_g NUMERIC(10,2),
+    carbs_g NUMERIC(10,2),
+    sodium_mg NUMERIC(10,2),
+    sugar_g NUMERIC(10,2),
+    fiber_g NUMERIC(10,2),
+    UNIQUE (recipe_id, position)
+);
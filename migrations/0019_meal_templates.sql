@@ -0,0 +1,36 @@
+-- Saved meals that can be logged again in one step. Nutrition is the AI or
+-- manual base of the source meal; items are snapshots of its meal items.
+CREATE TABLE IF NOT EXISTS meal_templates (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    title TEXT,
+    notes TEXT,
+    meal_type TEXT,
+    tags TEXT[] NOT NULL DEFAULT '{}',
+    nutrition JSONB,
+    micros JSONB,
+    items JSONB NOT NULL DEFAULT '[]',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_meal_templates_user_id ON meal_templates(user_id, created_at);
+
+-- Photos kept with a template. They share storage objects with the source
+-- meal's photos, so object deletes check this table too.
+CREATE TABLE IF NOT EXISTS template_photos (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    template_id UUID NOT NULL REFERENCES meal_templates(id) ON DELETE CASCADE,
+    s3_key TEXT NOT NULL,
+    original_s3_key TEXT,
+    content_type TEXT,
+    size_bytes BIGINT,
+    width INTEGER,
+    height INTEGER,
+    content_hash TEXT,
+    taken_at TIMESTAMPTZ,
+    position INT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_template_photos_template_id ON template_photos(template_id);
+CREATE INDEX IF NOT EXISTS idx_template_photos_s3_key ON template_photos(s3_key);
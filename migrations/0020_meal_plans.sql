@@ -0,0 +1,21 @@
+-- Weekly meal plans; week_start is always a Monday.
+CREATE TABLE IF NOT EXISTS meal_plans (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    week_start DATE NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (user_id, week_start)
+);
+
+-- One recipe or template per day and meal type.
+CREATE TABLE IF NOT EXISTS meal_plan_slots (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    plan_id UUID NOT NULL REFERENCES meal_plans(id) ON DELETE CASCADE,
+    date DATE NOT NULL,
+    meal_type TEXT NOT NULL,
+    recipe_id UUID REFERENCES recipes(id) ON DELETE CASCADE,
+    template_id UUID REFERENCES meal_templates(id) ON DELETE CASCADE,
+    servings NUMERIC(10,2) NOT NULL DEFAULT 1 CHECK (servings > 0),
+    CONSTRAINT meal_plan_slots_one_source CHECK ((recipe_id IS NULL) <> (template_id IS NULL)),
+    UNIQUE (plan_id, date, meal_type)
+);
@@ -0,0 +1,5 @@
+-- Nutrition totals brought in from another app's CSV export
+ALTER TABLE meal_nutrition DROP CONSTRAINT IF EXISTS meal_nutrition_source_valid;
+ALTER TABLE meal_nutrition
+ADD CONSTRAINT meal_nutrition_source_valid
+CHECK (source IN ('ai', 'manual', 'items', 'import'));
@@ -0,0 +1,10 @@
+-- Public read-only links to a meal; revoking or expiring a row kills its token.
+CREATE TABLE IF NOT EXISTS meal_shares (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    meal_id UUID NOT NULL REFERENCES meals(id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ,
+    revoked_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_meal_shares_meal_id ON meal_shares(meal_id);
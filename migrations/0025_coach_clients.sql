@@ -0,0 +1,12 @@
+-- Coaches get read-only access to a client's meals once the client accepts.
+CREATE TABLE IF NOT EXISTS coach_clients (
+    coach_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    client_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    status TEXT NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'active')),
+    invited_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    accepted_at TIMESTAMPTZ,
+    PRIMARY KEY (coach_id, client_id),
+    CHECK (coach_id <> client_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_coach_clients_client_id ON coach_clients(client_id);
@@ -0,0 +1,10 @@
+-- Last run of each scheduled (cron) job, shared by all instances: the one
+-- that moves `next_run_at` forward runs the job.
+CREATE TABLE IF NOT EXISTS job_runs (
+    name TEXT PRIMARY KEY,
+    next_run_at TIMESTAMPTZ NOT NULL,
+    last_started_at TIMESTAMPTZ,
+    last_finished_at TIMESTAMPTZ,
+    last_status TEXT CHECK (last_status IN ('running', 'succeeded', 'failed')),
+    last_error TEXT
+);
@@ -0,0 +1,24 @@
+-- Push tokens of the user's phones.
+CREATE TABLE IF NOT EXISTS devices (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    platform TEXT NOT NULL CHECK (platform IN ('android', 'ios')),
+    -- A token identifies one app install; it moves with re-registration.
+    token TEXT NOT NULL UNIQUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_seen_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_devices_user_id ON devices(user_id);
+
+-- Missing rows mean the defaults below.
+CREATE TABLE IF NOT EXISTS notification_preferences (
+    user_id UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    analysis_complete BOOLEAN NOT NULL DEFAULT TRUE,
+    meal_reminders BOOLEAN NOT NULL DEFAULT FALSE,
+    reminder_hour SMALLINT NOT NULL DEFAULT 20 CHECK (reminder_hour BETWEEN 0 AND 23),
+    timezone TEXT NOT NULL DEFAULT 'UTC',
+    -- Local date of the last reminder, so a day never gets two.
+    last_reminded_on DATE,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
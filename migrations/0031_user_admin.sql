@@ -0,0 +1,4 @@
+-- Grants access to /admin endpoints; set by hand, e.g.
+-- UPDATE users SET is_admin = TRUE WHERE email = 'ops@example.com';
+ALTER TABLE users
+ADD COLUMN IF NOT EXISTS is_admin BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,14 @@
+-- Gradual rollout of features. A flag without a row uses its default from
+-- the code; flags only known here are still reported to clients.
+CREATE TABLE IF NOT EXISTS feature_flags (
+    name TEXT PRIMARY KEY,
+    -- Off for everyone when false, whatever the rollout.
+    enabled BOOLEAN NOT NULL DEFAULT FALSE,
+    -- Share of users (by a stable hash of flag and user) who get the flag.
+    rollout_percent SMALLINT NOT NULL DEFAULT 100
+        CHECK (rollout_percent BETWEEN 0 AND 100),
+    -- Always get the flag while it is enabled.
+    user_ids UUID[] NOT NULL DEFAULT '{}',
+    description TEXT,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,19 @@
+-- Account-wide settings. Missing rows mean the defaults below.
+CREATE TABLE IF NOT EXISTS user_preferences (
+    user_id UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    units TEXT NOT NULL DEFAULT 'metric' CHECK (units IN ('metric', 'imperial')),
+    locale TEXT NOT NULL DEFAULT 'en' CHECK (locale IN ('en', 'de', 'ru')),
+    -- IANA name; days in summaries, reminders and streaks are local days.
+    timezone TEXT NOT NULL DEFAULT 'UTC',
+    week_start TEXT NOT NULL DEFAULT 'monday'
+        CHECK (week_start IN ('monday', 'saturday', 'sunday')),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- The time zone used to be a notification setting; it now applies to the
+-- whole account.
+INSERT INTO user_preferences (user_id, timezone)
+SELECT user_id, timezone FROM notification_preferences
+ON CONFLICT (user_id) DO NOTHING;
+
+ALTER TABLE notification_preferences DROP COLUMN IF EXISTS timezone;
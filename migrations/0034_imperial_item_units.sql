@@ -0,0 +1,8 @@
+-- Quantities keep the unit they were entered in, imperial ones included.
+ALTER TABLE meal_items DROP CONSTRAINT IF EXISTS meal_items_unit_check;
+ALTER TABLE meal_items ADD CONSTRAINT meal_items_unit_check
+    CHECK (unit IN ('g', 'ml', 'serving', 'oz', 'lb', 'fl_oz', 'cup', 'tbsp', 'tsp'));
+
+ALTER TABLE recipe_ingredients DROP CONSTRAINT IF EXISTS recipe_ingredients_unit_check;
+ALTER TABLE recipe_ingredients ADD CONSTRAINT recipe_ingredients_unit_check
+    CHECK (unit IN ('g', 'ml', 'serving', 'oz', 'lb', 'fl_oz', 'cup', 'tbsp', 'tsp'));
@@ -0,0 +1,35 @@
+-- Tenants such as a clinic running MealMind for its patients. Users outside
+-- any organization keep working as before.
+CREATE TABLE IF NOT EXISTS organizations (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+ALTER TABLE users
+ADD COLUMN IF NOT EXISTS org_id UUID REFERENCES organizations(id) ON DELETE RESTRICT,
+-- Sees the organization's members and statistics under /org.
+ADD COLUMN IF NOT EXISTS org_admin BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE INDEX IF NOT EXISTS idx_users_org_id ON users(org_id) WHERE org_id IS NOT NULL;
+
+-- Copied from the owner so tenant-wide queries don't need the join; kept in
+-- step when a user moves between organizations.
+ALTER TABLE meals
+ADD COLUMN IF NOT EXISTS org_id UUID REFERENCES organizations(id) ON DELETE RESTRICT;
+
+CREATE INDEX IF NOT EXISTS idx_meals_org_created
+    ON meals(org_id, created_at DESC) WHERE org_id IS NOT NULL;
+
+-- Meals are inserted from several places (API, imports, seeding); the
+-- trigger keeps any of them from forgetting the owner's organization.
+CREATE OR REPLACE FUNCTION meals_set_org_id() RETURNS TRIGGER AS $$
+BEGIN
+    NEW.org_id := (SELECT org_id FROM users WHERE id = NEW.user_id);
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS meals_org_id ON meals;
+CREATE TRIGGER meals_org_id BEFORE INSERT ON meals
+    FOR EACH ROW EXECUTE FUNCTION meals_set_org_id();
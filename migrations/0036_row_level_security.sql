@@ -0,0 +1,32 @@
+-- Row-level security on the tables a single user owns. Requests scoped with
+-- DB_ROW_LEVEL_SECURITY set `app.current_user_id` for their transaction and
+-- then only see and write that user's rows. Without the setting (background
+-- jobs, admin and shared views, or the option turned off) the policies let
+-- everything through, so they change nothing until the option is enabled.
+-- Superusers and roles with BYPASSRLS skip the policies altogether.
+CREATE OR REPLACE FUNCTION app_current_user_id() RETURNS UUID AS $$
+    SELECT NULLIF(current_setting('app.current_user_id', TRUE), '')::UUID;
+$$ LANGUAGE sql STABLE;
+
+DO $$
+DECLARE
+    t TEXT;
+BEGIN
+    FOREACH t IN ARRAY ARRAY[
+        'custom_foods', 'data_exports', 'devices', 'goals', 'meal_plans',
+        'meal_templates', 'meals', 'notification_preferences', 'photos',
+        'recipes', 'user_preferences', 'webhooks', 'weights'
+    ] LOOP
+        EXECUTE format('DROP POLICY IF EXISTS owner_rows ON %I', t);
+        EXECUTE format(
+            'CREATE POLICY owner_rows ON %I
+                USING (app_current_user_id() IS NULL OR user_id = app_current_user_id())
+                WITH CHECK (app_current_user_id() IS NULL OR user_id = app_current_user_id())',
+            t
+        );
+        EXECUTE format('ALTER TABLE %I ENABLE ROW LEVEL SECURITY', t);
+        -- The application usually owns its tables; owners skip RLS otherwise.
+        EXECUTE format('ALTER TABLE %I FORCE ROW LEVEL SECURITY', t);
+    END LOOP;
+END;
+$$;
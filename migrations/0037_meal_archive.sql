@@ -0,0 +1,35 @@
+-- Meals older than MEAL_ARCHIVE_AFTER_DAYS, moved out of `meals` by the
+-- `meal_archival` job. Nutrition, items and photo metadata are kept as JSON
+-- snapshots; the photo objects stay in storage under `photo_keys`.
+CREATE TABLE IF NOT EXISTS meal_archive (
+    id UUID PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    org_id UUID REFERENCES organizations(id) ON DELETE RESTRICT,
+    household_id UUID,
+    title TEXT,
+    notes TEXT,
+    meal_type TEXT,
+    tags TEXT[] NOT NULL DEFAULT '{}',
+    status TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL,
+    nutrition JSONB,
+    items JSONB NOT NULL DEFAULT '[]',
+    photos JSONB NOT NULL DEFAULT '[]',
+    photo_keys TEXT[] NOT NULL DEFAULT '{}',
+    archived_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_meal_archive_user_created
+    ON meal_archive(user_id, created_at DESC);
+
+-- Deleting a photo elsewhere must not release an object an archived meal
+-- still uses.
+CREATE INDEX IF NOT EXISTS idx_meal_archive_photo_keys
+    ON meal_archive USING GIN (photo_keys);
+
+DROP POLICY IF EXISTS owner_rows ON meal_archive;
+CREATE POLICY owner_rows ON meal_archive
+    USING (app_current_user_id() IS NULL OR user_id = app_current_user_id())
+    WITH CHECK (app_current_user_id() IS NULL OR user_id = app_current_user_id());
+ALTER TABLE meal_archive ENABLE ROW LEVEL SECURITY;
+ALTER TABLE meal_archive FORCE ROW LEVEL SECURITY;
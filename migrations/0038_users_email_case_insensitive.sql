@@ -0,0 +1,19 @@
+-- Emails are stored lowercased by every path that creates users today, but
+-- nothing in the schema enforced it. Lowercase the stragglers that don't
+-- collide, then make uniqueness case-insensitive.
+UPDATE users u
+SET email = lower(u.email)
+WHERE u.email <> lower(u.email)
+  AND NOT EXISTS (
+      SELECT 1 FROM users o WHERE o.id <> u.id AND lower(o.email) = lower(u.email)
+  );
+
+DO $$
+BEGIN
+    IF EXISTS (SELECT 1 FROM users GROUP BY lower(email) HAVING COUNT(*) > 1) THEN
+        RAISE EXCEPTION 'users whose emails differ only in case exist; merge or rename them first: SELECT lower(email), array_agg(id) FROM users GROUP BY 1 HAVING COUNT(*) > 1';
+    END IF;
+END;
+$$;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_users_email_lower ON users (lower(email));
@@ -0,0 +1,117 @@
+-- Change tracking for the offline sync protocol (`/sync`). Every change to
+-- a meal, its nutrition or its photos gives the meal a new `sync_version`
+-- from one sequence, and deleted meals leave a tombstone numbered from the
+-- same sequence, so "everything after cursor N" is a single range.
+CREATE SEQUENCE IF NOT EXISTS meal_sync_version_seq;
+
+ALTER TABLE meals
+ADD COLUMN IF NOT EXISTS updated_at TIMESTAMPTZ,
+ADD COLUMN IF NOT EXISTS sync_version BIGINT;
+
+UPDATE meals
+SET updated_at = created_at,
+    sync_version = nextval('meal_sync_version_seq')
+WHERE sync_version IS NULL;
+
+ALTER TABLE meals
+ALTER COLUMN updated_at SET DEFAULT NOW(),
+ALTER COLUMN updated_at SET NOT NULL,
+ALTER COLUMN sync_version SET DEFAULT nextval('meal_sync_version_seq'),
+ALTER COLUMN sync_version SET NOT NULL;
+
+CREATE INDEX IF NOT EXISTS idx_meals_user_sync ON meals(user_id, sync_version);
+
+CREATE TABLE IF NOT EXISTS meal_tombstones (
+    meal_id UUID PRIMARY KEY,
+    -- No foreign key: tombstones are also written while a user is deleted.
+    user_id UUID NOT NULL,
+    deleted_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    sync_version BIGINT NOT NULL DEFAULT nextval('meal_sync_version_seq')
+);
+
+CREATE INDEX IF NOT EXISTS idx_meal_tombstones_user_sync
+    ON meal_tombstones(user_id, sync_version);
+
+-- Highest tombstone version pruned so far; older cursors must resync.
+CREATE TABLE IF NOT EXISTS meal_sync_horizon (
+    id BOOLEAN PRIMARY KEY DEFAULT TRUE CHECK (id),
+    pruned_through BIGINT NOT NULL DEFAULT 0
+);
+
+INSERT INTO meal_sync_horizon (id) VALUES (TRUE) ON CONFLICT DO NOTHING;
+
+-- A version is drawn while holding a shared lock on the user's sync key
+-- until the writing transaction ends. Pulls take the key exclusively before
+-- reading, so they never see version N+1 committed while N is still in
+-- flight and would be skipped by the returned cursor.
+CREATE OR REPLACE FUNCTION meal_sync_lock(p_user_id UUID) RETURNS VOID AS $$
+    SELECT pg_advisory_xact_lock_shared(1907, hashtext(p_user_id::text));
+$$ LANGUAGE sql;
+
+CREATE OR REPLACE FUNCTION meals_touch() RETURNS TRIGGER AS $$
+BEGIN
+    PERFORM meal_sync_lock(NEW.user_id);
+    NEW.sync_version := nextval('meal_sync_version_seq');
+    -- Only edits of user-facing fields count for conflict resolution, and a
+    -- sync push sets `updated_at` to the client's edit time itself.
+    IF TG_OP = 'UPDATE'
+       AND NEW.updated_at IS NOT DISTINCT FROM OLD.updated_at
+       AND (NEW.title, NEW.notes, NEW.meal_type, NEW.tags, NEW.household_id)
+           IS DISTINCT FROM (OLD.title, OLD.notes, OLD.meal_type, OLD.tags, OLD.household_id)
+    THEN
+        NEW.updated_at := NOW();
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS meals_touch ON meals;
+CREATE TRIGGER meals_touch
+    BEFORE INSERT OR UPDATE ON meals
+    FOR EACH ROW EXECUTE FUNCTION meals_touch();
+
+CREATE OR REPLACE FUNCTION meals_record_tombstone() RETURNS TRIGGER AS $$
+BEGIN
+    PERFORM meal_sync_lock(OLD.user_id);
+    INSERT INTO meal_tombstones (meal_id, user_id)
+    VALUES (OLD.id, OLD.user_id)
+    ON CONFLICT (meal_id) DO UPDATE
+    SET deleted_at = NOW(), sync_version = nextval('meal_sync_version_seq');
+    RETURN OLD;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS meals_record_tombstone ON meals;
+CREATE TRIGGER meals_record_tombstone
+    AFTER DELETE ON meals
+    FOR EACH ROW EXECUTE FUNCTION meals_record_tombstone();
+
+-- Nutrition and photos are part of what a pull returns for a meal.
+CREATE OR REPLACE FUNCTION meals_bump_sync_version() RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP <> 'DELETE' AND NEW.meal_id IS NOT NULL THEN
+        UPDATE meals SET sync_version = 0 WHERE id = NEW.meal_id;
+    END IF;
+    IF TG_OP <> 'INSERT' AND OLD.meal_id IS NOT NULL THEN
+        UPDATE meals SET sync_version = 0 WHERE id = OLD.meal_id;
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS meal_nutrition_bump_sync ON meal_nutrition;
+CREATE TRIGGER meal_nutrition_bump_sync
+    AFTER INSERT OR UPDATE OR DELETE ON meal_nutrition
+    FOR EACH ROW EXECUTE FUNCTION meals_bump_sync_version();
+
+DROP TRIGGER IF EXISTS photos_bump_sync ON photos;
+CREATE TRIGGER photos_bump_sync
+    AFTER INSERT OR UPDATE OF meal_id OR DELETE ON photos
+    FOR EACH ROW EXECUTE FUNCTION meals_bump_sync_version();
+
+DROP POLICY IF EXISTS owner_rows ON meal_tombstones;
+CREATE POLICY owner_rows ON meal_tombstones
+    USING (app_current_user_id() IS NULL OR user_id = app_current_user_id())
+    WITH CHECK (app_current_user_id() IS NULL OR user_id = app_current_user_id());
+ALTER TABLE meal_tombstones ENABLE ROW LEVEL SECURITY;
+ALTER TABLE meal_tombstones FORCE ROW LEVEL SECURITY;
@@ -0,0 +1,24 @@
+-- Analysis names the meal when the user didn't, and lists the dish
+-- components it recognized. `title_generated` marks an AI title; it is
+-- cleared once the user sets their own.
+ALTER TABLE meals
+ADD COLUMN IF NOT EXISTS title_generated BOOLEAN NOT NULL DEFAULT FALSE,
+ADD COLUMN IF NOT EXISTS detected_items JSONB;
+
+-- Same as in migration 39, except that an AI title is not a user edit and
+-- must not win sync conflicts against edits made offline before it.
+CREATE OR REPLACE FUNCTION meals_touch() RETURNS TRIGGER AS $$
+BEGIN
+    PERFORM meal_sync_lock(NEW.user_id);
+    NEW.sync_version := nextval('meal_sync_version_seq');
+    IF TG_OP = 'UPDATE'
+       AND NEW.updated_at IS NOT DISTINCT FROM OLD.updated_at
+       AND (CASE WHEN NEW.title_generated THEN OLD.title ELSE NEW.title END,
+            NEW.notes, NEW.meal_type, NEW.tags, NEW.household_id)
+           IS DISTINCT FROM (OLD.title, OLD.notes, OLD.meal_type, OLD.tags, OLD.household_id)
+    THEN
+        NEW.updated_at := NOW();
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
@@ -0,0 +1,8 @@
+-- Allergens and diets users declare, and the ones the analysis found a meal
+-- likely contains; meal details warn about the overlap.
+ALTER TABLE user_preferences
+ADD COLUMN IF NOT EXISTS allergens TEXT[] NOT NULL DEFAULT '{}',
+ADD COLUMN IF NOT EXISTS diets TEXT[] NOT NULL DEFAULT '{}';
+
+ALTER TABLE meals
+ADD COLUMN IF NOT EXISTS contains TEXT[] NOT NULL DEFAULT '{}';
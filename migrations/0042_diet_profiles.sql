@@ -0,0 +1,5 @@
+-- The diet a user follows; meal details and daily summaries rate how well
+-- meals fit it.
+ALTER TABLE user_preferences
+ADD COLUMN IF NOT EXISTS diet_profile TEXT
+    CHECK (diet_profile IN ('keto', 'vegan', 'mediterranean', 'low_sodium'));
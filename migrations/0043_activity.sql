@@ -0,0 +1,62 @@
+-- Burned calories and workouts from health apps, and the OAuth connections
+-- they are pulled through. The daily summary adds a share of the burned
+-- calories to the calorie budget.
+CREATE TABLE IF NOT EXISTS activity (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    source TEXT NOT NULL CHECK (source IN ('apple_health', 'google_fit', 'manual')),
+    -- The source's own id; importing it again updates the entry.
+    external_id TEXT,
+    name TEXT,
+    started_at TIMESTAMPTZ NOT NULL,
+    duration_minutes DOUBLE PRECISION CHECK (duration_minutes >= 0),
+    calories_kcal DOUBLE PRECISION NOT NULL CHECK (calories_kcal >= 0),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (user_id, source, external_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_activity_user_started_at ON activity(user_id, started_at);
+
+-- Percentage of the burned calories added to the day's calorie budget.
+ALTER TABLE user_preferences
+ADD COLUMN IF NOT EXISTS activity_eat_back_percent SMALLINT NOT NULL DEFAULT 50
+    CHECK (activity_eat_back_percent BETWEEN 0 AND 100);
+
+CREATE TABLE IF NOT EXISTS integrations (
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    provider TEXT NOT NULL CHECK (provider IN ('google_fit')),
+    access_token TEXT NOT NULL,
+    refresh_token TEXT,
+    expires_at TIMESTAMPTZ NOT NULL,
+    -- Data up to here has been pulled.
+    synced_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, provider)
+);
+
+-- Pending authorizations: the `state` of an OAuth redirect names the user,
+-- as the provider's callback carries no token of ours.
+CREATE TABLE IF NOT EXISTS integration_states (
+    state TEXT PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    provider TEXT NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL
+);
+
+DO $$
+DECLARE
+    t TEXT;
+BEGIN
+    FOREACH t IN ARRAY ARRAY['activity', 'integrations'] LOOP
+        EXECUTE format('DROP POLICY IF EXISTS owner_rows ON %I', t);
+        EXECUTE format(
+            'CREATE POLICY owner_rows ON %I
+                USING (app_current_user_id() IS NULL OR user_id = app_current_user_id())
+                WITH CHECK (app_current_user_id() IS NULL OR user_id = app_current_user_id())',
+            t
+        );
+        EXECUTE format('ALTER TABLE %I ENABLE ROW LEVEL SECURITY', t);
+        EXECUTE format('ALTER TABLE %I FORCE ROW LEVEL SECURITY', t);
+    END LOOP;
+END;
+$$;
@@ -0,0 +1,15 @@
+-- Fitbit connections, pulling activity and weigh-ins. Pulled weigh-ins keep
+-- the provider's id so a later pull updates them instead of adding copies;
+-- weigh-ins logged in the app have neither.
+ALTER TABLE activity DROP CONSTRAINT IF EXISTS activity_source_check;
+ALTER TABLE activity ADD CONSTRAINT activity_source_check
+    CHECK (source IN ('apple_health', 'google_fit', 'fitbit', 'manual'));
+
+ALTER TABLE integrations DROP CONSTRAINT IF EXISTS integrations_provider_check;
+ALTER TABLE integrations ADD CONSTRAINT integrations_provider_check
+    CHECK (provider IN ('google_fit', 'fitbit'));
+
+ALTER TABLE weights ADD COLUMN IF NOT EXISTS source TEXT;
+ALTER TABLE weights ADD COLUMN IF NOT EXISTS external_id TEXT;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_weights_user_source_external_id
+    ON weights(user_id, source, external_id);
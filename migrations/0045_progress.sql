@@ -0,0 +1,48 @@
+-- Body measurements and progress photos. Progress photos are stored under
+-- their own prefix and never belong to a meal, so the photo orphan cleanup
+-- and meal archive don't see them.
+CREATE TABLE IF NOT EXISTS body_measurements (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    waist_cm NUMERIC(5,1) CHECK (waist_cm > 0),
+    body_fat_percent NUMERIC(4,1) CHECK (body_fat_percent > 0 AND body_fat_percent < 100),
+    measured_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    note TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK (waist_cm IS NOT NULL OR body_fat_percent IS NOT NULL)
+);
+
+CREATE INDEX IF NOT EXISTS idx_body_measurements_user_measured_at
+    ON body_measurements(user_id, measured_at);
+
+CREATE TABLE IF NOT EXISTS progress_photos (
+    id UUID PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    s3_key TEXT NOT NULL,
+    content_type TEXT NOT NULL,
+    size_bytes BIGINT NOT NULL,
+    taken_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    note TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_progress_photos_user_taken_at
+    ON progress_photos(user_id, taken_at);
+
+DO $$
+DECLARE
+    t TEXT;
+BEGIN
+    FOREACH t IN ARRAY ARRAY['body_measurements', 'progress_photos'] LOOP
+        EXECUTE format('DROP POLICY IF EXISTS owner_rows ON %I', t);
+        EXECUTE format(
+            'CREATE POLICY owner_rows ON %I
+                USING (app_current_user_id() IS NULL OR user_id = app_current_user_id())
+                WITH CHECK (app_current_user_id() IS NULL OR user_id = app_current_user_id())',
+            t
+        );
+        EXECUTE format('ALTER TABLE %I ENABLE ROW LEVEL SECURITY', t);
+        EXECUTE format('ALTER TABLE %I FORCE ROW LEVEL SECURITY', t);
+    END LOOP;
+END;
+$$;
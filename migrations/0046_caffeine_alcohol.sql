@@ -0,0 +1,7 @@
+-- Caffeine and alcohol per meal, with daily limits next to the sugar and
+-- sodium ones. NULL limits fall back to the defaults.
+ALTER TABLE meal_nutrition ADD COLUMN IF NOT EXISTS caffeine_mg NUMERIC(10,2);
+ALTER TABLE meal_nutrition ADD COLUMN IF NOT EXISTS alcohol_g NUMERIC(10,2);
+
+ALTER TABLE goals ADD COLUMN IF NOT EXISTS caffeine_max_mg NUMERIC(10,2);
+ALTER TABLE goals ADD COLUMN IF NOT EXISTS alcohol_max_g NUMERIC(10,2);
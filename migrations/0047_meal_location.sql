@@ -0,0 +1,16 @@
+-- Where a meal was eaten. Coordinates come as a pair; `place_name` is sent
+-- by the client or filled in by reverse geocoding.
+ALTER TABLE meals ADD COLUMN IF NOT EXISTS latitude DOUBLE PRECISION;
+ALTER TABLE meals ADD COLUMN IF NOT EXISTS longitude DOUBLE PRECISION;
+ALTER TABLE meals ADD COLUMN IF NOT EXISTS place_name TEXT;
+
+ALTER TABLE meals ADD CONSTRAINT meals_location_check CHECK (
+    (latitude IS NULL) = (longitude IS NULL)
+    AND (latitude IS NULL OR latitude BETWEEN -90 AND 90)
+    AND (longitude IS NULL OR longitude BETWEEN -180 AND 180)
+);
+
+-- `GET /meals?near=` first narrows to a bounding box on these.
+CREATE INDEX IF NOT EXISTS meals_user_location_idx
+    ON meals (user_id, latitude, longitude)
+    WHERE latitude IS NOT NULL;
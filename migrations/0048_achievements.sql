@@ -0,0 +1,17 @@
+-- Badges a user has unlocked. One row per badge; unlocking again is a no-op.
+CREATE TABLE IF NOT EXISTS user_achievements (
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    badge TEXT NOT NULL,
+    unlocked_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, badge)
+);
+
+ALTER TABLE notification_preferences
+    ADD COLUMN IF NOT EXISTS achievements BOOLEAN NOT NULL DEFAULT TRUE;
+
+DROP POLICY IF EXISTS owner_rows ON user_achievements;
+CREATE POLICY owner_rows ON user_achievements
+    USING (app_current_user_id() IS NULL OR user_id = app_current_user_id())
+    WITH CHECK (app_current_user_id() IS NULL OR user_id = app_current_user_id());
+ALTER TABLE user_achievements ENABLE ROW LEVEL SECURITY;
+ALTER TABLE user_achievements FORCE ROW LEVEL SECURITY;
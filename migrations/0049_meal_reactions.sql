@@ -0,0 +1,14 @@
+-- Emoji reactions of household members on each other's meals. One row per
+-- user and reaction, so reacting twice is a no-op.
+CREATE TABLE IF NOT EXISTS meal_reactions (
+    meal_id UUID NOT NULL REFERENCES meals(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    reaction TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (meal_id, user_id, reaction)
+);
+
+CREATE INDEX IF NOT EXISTS idx_meal_reactions_user ON meal_reactions(user_id);
+
+ALTER TABLE notification_preferences
+    ADD COLUMN IF NOT EXISTS reactions BOOLEAN NOT NULL DEFAULT TRUE;
@@ -0,0 +1,44 @@
+-- Product analytics from the apps (screen views, feature usage). Partitioned
+-- by month of arrival so old months are dropped whole; the
+-- `analytics_partitions` cron job creates upcoming months ahead of time and
+-- the default partition catches anything it missed.
+CREATE TABLE IF NOT EXISTS analytics_events (
+    id UUID NOT NULL DEFAULT gen_random_uuid(),
+    -- NULL for users who send analytics anonymously.
+    user_id UUID REFERENCES users(id) ON DELETE CASCADE,
+    kind TEXT NOT NULL,
+    name TEXT NOT NULL,
+    properties JSONB NOT NULL DEFAULT '{}',
+    session_id TEXT,
+    app_version TEXT,
+    occurred_at TIMESTAMPTZ NOT NULL,
+    received_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (id, received_at)
+) PARTITION BY RANGE (received_at);
+
+CREATE TABLE IF NOT EXISTS analytics_events_default PARTITION OF analytics_events DEFAULT;
+
+CREATE INDEX IF NOT EXISTS idx_analytics_events_name
+    ON analytics_events (name, received_at);
+CREATE INDEX IF NOT EXISTS idx_analytics_events_user
+    ON analytics_events (user_id) WHERE user_id IS NOT NULL;
+
+DO $$
+DECLARE
+    month DATE;
+BEGIN
+    FOREACH month IN ARRAY ARRAY[
+        date_trunc('month', NOW())::date,
+        (date_trunc('month', NOW()) + INTERVAL '1 month')::date
+    ] LOOP
+        EXECUTE format(
+            'CREATE TABLE IF NOT EXISTS %I PARTITION OF analytics_events FOR VALUES FROM (%L) TO (%L)',
+            'analytics_events_p' || to_char(month, 'YYYYMM'),
+            month,
+            (month + INTERVAL '1 month')::date
+        );
+    END LOOP;
+END $$;
+
+ALTER TABLE user_preferences
+    ADD COLUMN IF NOT EXISTS analytics TEXT NOT NULL DEFAULT 'full';
@@ -0,0 +1,18 @@
+-- Anonymized meal datasets for internal model training, built by the
+-- `research_export` job and uploaded to storage.
+CREATE TABLE IF NOT EXISTS research_exports (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    requested_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    k INTEGER NOT NULL CHECK (k >= 2),
+    status TEXT NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'running', 'done', 'failed')),
+    s3_key TEXT,
+    size_bytes BIGINT,
+    meal_count INTEGER,
+    user_count INTEGER,
+    suppressed_count INTEGER,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    completed_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_research_exports_created ON research_exports(created_at DESC);
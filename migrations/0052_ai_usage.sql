@@ -0,0 +1,23 @@
+-- Tokens and cost of every AI analysis call, and per-user monthly budgets
+-- that pause automatic analysis once spent.
+CREATE TABLE IF NOT EXISTS ai_usage (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    meal_id UUID REFERENCES meals(id) ON DELETE SET NULL,
+    provider TEXT NOT NULL,
+    model TEXT NOT NULL,
+    input_tokens BIGINT NOT NULL,
+    output_tokens BIGINT NOT NULL,
+    cost_usd NUMERIC(12,6) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_ai_usage_user_created ON ai_usage(user_id, created_at);
+CREATE INDEX IF NOT EXISTS idx_ai_usage_created ON ai_usage(created_at);
+
+-- Overrides of ANALYZER_MONTHLY_BUDGET_USD for single users.
+CREATE TABLE IF NOT EXISTS ai_budgets (
+    user_id UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    monthly_budget_usd NUMERIC(12,2) NOT NULL CHECK (monthly_budget_usd >= 0),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,23 @@
+-- Plan tiers. `plan` is what the API enforces; the `stripe_*` and
+-- `plan_status` columns mirror the user's Stripe subscription.
+ALTER TABLE users
+    ADD COLUMN IF NOT EXISTS plan TEXT NOT NULL DEFAULT 'free' CHECK (plan IN ('free', 'pro')),
+    ADD COLUMN IF NOT EXISTS plan_status TEXT,
+    ADD COLUMN IF NOT EXISTS plan_renews_at TIMESTAMPTZ,
+    ADD COLUMN IF NOT EXISTS stripe_customer_id TEXT,
+    ADD COLUMN IF NOT EXISTS stripe_subscription_id TEXT,
+    -- Creation time of the last Stripe event applied, so older events
+    -- arriving late don't undo newer ones.
+    ADD COLUMN IF NOT EXISTS plan_event_at TIMESTAMPTZ;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_users_stripe_customer
+    ON users(stripe_customer_id) WHERE stripe_customer_id IS NOT NULL;
+
+-- Meals queued for analysis per user and UTC month, counted against the
+-- free plan's allowance. Kept when meals are deleted.
+CREATE TABLE IF NOT EXISTS analysis_quota (
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    month DATE NOT NULL,
+    analyses INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (user_id, month)
+);
@@ -0,0 +1,15 @@
+-- Every Stripe webhook event received, by Stripe's event id. Redeliveries
+-- of an event already processed are acknowledged without applying it again.
+CREATE TABLE IF NOT EXISTS stripe_events (
+    id TEXT PRIMARY KEY,
+    type TEXT NOT NULL,
+    -- When Stripe created the event.
+    created_at TIMESTAMPTZ NOT NULL,
+    payload JSONB NOT NULL,
+    received_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    deliveries INTEGER NOT NULL DEFAULT 1,
+    processed_at TIMESTAMPTZ,
+    last_error TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_stripe_events_received ON stripe_events(received_at);
@@ -0,0 +1,24 @@
+-- In-app notification center. Rows come from outbox events; `event_id`
+-- makes redelivered events a no-op.
+CREATE TABLE IF NOT EXISTS notifications (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    event_id BIGINT NOT NULL UNIQUE,
+    kind TEXT NOT NULL,
+    meal_id UUID REFERENCES meals(id) ON DELETE CASCADE,
+    badge TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    read_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_notifications_user_created
+    ON notifications(user_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_notifications_unread
+    ON notifications(user_id) WHERE read_at IS NULL;
+
+DROP POLICY IF EXISTS owner_rows ON notifications;
+CREATE POLICY owner_rows ON notifications
+    USING (app_current_user_id() IS NULL OR user_id = app_current_user_id())
+    WITH CHECK (app_current_user_id() IS NULL OR user_id = app_current_user_id());
+ALTER TABLE notifications ENABLE ROW LEVEL SECURITY;
+ALTER TABLE notifications FORCE ROW LEVEL SECURITY;
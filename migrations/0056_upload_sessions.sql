@@ -0,0 +1,31 @@
+-- Upload sessions: a meal's photos uploaded one at a time before the meal
+-- exists, then attached to it by POST /meals. Session photos are photo rows
+-- without a meal, tagged with their session and position.
+CREATE TABLE IF NOT EXISTS upload_sessions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    -- The id the meal gets; photo objects are stored under it.
+    meal_id UUID NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL,
+    finalized_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_upload_sessions_user ON upload_sessions(user_id);
+CREATE INDEX IF NOT EXISTS idx_upload_sessions_expires ON upload_sessions(expires_at);
+
+ALTER TABLE photos
+    ADD COLUMN IF NOT EXISTS upload_session_id UUID
+        REFERENCES upload_sessions(id) ON DELETE SET NULL,
+    ADD COLUMN IF NOT EXISTS upload_position INT;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_photos_upload_position
+    ON photos(upload_session_id, upload_position)
+    WHERE upload_session_id IS NOT NULL;
+
+DROP POLICY IF EXISTS owner_rows ON upload_sessions;
+CREATE POLICY owner_rows ON upload_sessions
+    USING (app_current_user_id() IS NULL OR user_id = app_current_user_id())
+    WITH CHECK (app_current_user_id() IS NULL OR user_id = app_current_user_id());
+ALTER TABLE upload_sessions ENABLE ROW LEVEL SECURITY;
+ALTER TABLE upload_sessions FORCE ROW LEVEL SECURITY;
@@ -0,0 +1,36 @@
+-- Chunked uploads: one large session photo sent in parts, each written
+-- straight to a storage multipart upload. Rows track the storage upload and
+-- its parts until the photo is completed; uploads whose session closed are
+-- aborted by the orphan photo cleanup.
+CREATE TABLE IF NOT EXISTS chunked_uploads (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    session_id UUID REFERENCES upload_sessions(id) ON DELETE SET NULL,
+    position INT NOT NULL,
+    -- The photo row the upload becomes, and the object it is written to.
+    photo_id UUID NOT NULL,
+    s3_key TEXT NOT NULL,
+    storage_upload_id TEXT NOT NULL,
+    content_type TEXT NOT NULL,
+    size_bytes BIGINT NOT NULL,
+    taken_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_chunked_uploads_user ON chunked_uploads(user_id);
+CREATE INDEX IF NOT EXISTS idx_chunked_uploads_session ON chunked_uploads(session_id);
+
+CREATE TABLE IF NOT EXISTS chunked_upload_parts (
+    upload_id UUID NOT NULL REFERENCES chunked_uploads(id) ON DELETE CASCADE,
+    part_number INT NOT NULL,
+    etag TEXT NOT NULL,
+    size_bytes BIGINT NOT NULL,
+    PRIMARY KEY (upload_id, part_number)
+);
+
+DROP POLICY IF EXISTS owner_rows ON chunked_uploads;
+CREATE POLICY owner_rows ON chunked_uploads
+    USING (app_current_user_id() IS NULL OR user_id = app_current_user_id())
+    WITH CHECK (app_current_user_id() IS NULL OR user_id = app_current_user_id());
+ALTER TABLE chunked_uploads ENABLE ROW LEVEL SECURITY;
+ALTER TABLE chunked_uploads FORCE ROW LEVEL SECURITY;
@@ -0,0 +1,8 @@
+-- Perceptual fingerprints (64-bit difference hashes) of meal photos, used to
+-- flag near-identical photos logged again shortly after. NULL when duplicate
+-- detection is off or the photo couldn't be fingerprinted.
+ALTER TABLE photos ADD COLUMN IF NOT EXISTS perceptual_hash BIGINT;
+
+CREATE INDEX IF NOT EXISTS idx_photos_user_fingerprinted
+    ON photos(user_id, created_at)
+    WHERE perceptual_hash IS NOT NULL;
@@ -0,0 +1,5 @@
+-- Meals whose photos don't look like food are left for the user to review
+-- instead of being analyzed.
+ALTER TABLE meals DROP CONSTRAINT IF EXISTS meals_status_valid;
+ALTER TABLE meals ADD CONSTRAINT meals_status_valid
+    CHECK (status IN ('pending', 'analyzing', 'done', 'failed', 'needs_review'));
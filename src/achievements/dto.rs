@@ -0,0 +1,171 @@
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+use crate::stats::dto::Streaks;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+pub enum Badge {
+    /// Meals logged on 7 consecutive days.
+    WeekStreak,
+    HundredMeals,
+    /// The daily protein goal met on 5 consecutive days.
+    ProteinStreak,
+}
+
+impl Badge {
+    pub const ALL: [Badge; 3] = [Badge::WeekStreak, Badge::HundredMeals, Badge::ProteinStreak];
+
+    /// Matches the serialized form.
+    pub fn name(self) -> &'static str {
+        match self {
+            Badge::WeekStreak => "week_streak",
+            Badge::HundredMeals => "hundred_meals",
+            Badge::ProteinStreak => "protein_streak",
+        }
+    }
+
+    pub fn title(self) -> &'static str {
+        match self {
+            Badge::WeekStreak => "7-day streak",
+            Badge::HundredMeals => "100 meals",
+            Badge::ProteinStreak => "Protein streak",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Badge::WeekStreak => "Logged meals 7 days in a row.",
+            Badge::HundredMeals => "Logged 100 meals.",
+            Badge::ProteinStreak => "Hit the protein goal 5 days in a row.",
+        }
+    }
+
+    /// Days or meals needed to unlock the badge.
+    pub fn target(self) -> i64 {
+        match self {
+            Badge::WeekStreak => 7,
+            Badge::HundredMeals => 100,
+            Badge::ProteinStreak => 5,
+        }
+    }
+}
+
+/// What the badges are measured on, in the user's time zone.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BadgeProgress {
+    pub logging: Streaks,
+    pub meals: i64,
+    pub protein: Streaks,
+}
+
+impl BadgeProgress {
+    /// Streak badges count the longest run ever, so breaking a streak later
+    /// doesn't take a badge away.
+    pub fn earned(&self, badge: Badge) -> bool {
+        let value = match badge {
+            Badge::WeekStreak => i64::from(self.logging.longest),
+            Badge::HundredMeals => self.meals,
+            Badge::ProteinStreak => i64::from(self.protein.longest),
+        };
+        value >= badge.target()
+    }
+
+    /// Progress shown for a locked badge; streaks show the current run.
+    pub fn current(&self, badge: Badge) -> i64 {
+        match badge {
+            Badge::WeekStreak => i64::from(self.logging.current),
+            Badge::HundredMeals => self.meals,
+            Badge::ProteinStreak => i64::from(self.protein.current),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Achievement {
+    pub badge: Badge,
+    pub title: String,
+    pub description: String,
+    pub unlocked: bool,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub unlocked_at: Option<OffsetDateTime>,
+    /// Towards `target`; equal to it once unlocked.
+    pub progress: i64,
+    pub target: i64,
+}
+
+impl Achievement {
+    /// `title` and `description` are left in English for the caller to
+    /// translate.
+    pub fn new(
+        badge: Badge,
+        progress: &BadgeProgress,
+        unlocked_at: Option<OffsetDateTime>,
+    ) -> Self {
+        let target = badge.target();
+        Self {
+            badge,
+            title: badge.title().to_string(),
+            description: badge.description().to_string(),
+            unlocked: unlocked_at.is_some(),
+            unlocked_at,
+            progress: match unlocked_at {
+                Some(_) => target,
+                None => progress.current(badge).min(target),
+            },
+            target,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct AchievementsResponse {
+    /// Every badge, unlocked or not, in a fixed order.
+    pub achievements: Vec<Achievement>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn progress() -> BadgeProgress {
+        BadgeProgress {
+            logging: Streaks {
+                current: 2,
+                longest: 7,
+            },
+            meals: 140,
+            protein: Streaks {
+                current: 3,
+                longest: 4,
+            },
+        }
+    }
+
+    #[test]
+    fn streak_badges_use_the_longest_run() {
+        let progress = progress();
+        assert!(progress.earned(Badge::WeekStreak));
+        assert!(progress.earned(Badge::HundredMeals));
+        assert!(!progress.earned(Badge::ProteinStreak));
+    }
+
+    #[test]
+    fn locked_badges_show_the_current_run() {
+        let achievement = Achievement::new(Badge::ProteinStreak, &progress(), None);
+        assert!(!achievement.unlocked);
+        assert_eq!((achievement.progress, achievement.target), (3, 5));
+
+        let unlocked = Achievement::new(
+            Badge::WeekStreak,
+            &progress(),
+            Some(OffsetDateTime::UNIX_EPOCH),
+        );
+        assert_eq!(unlocked.progress, 7);
+        assert_eq!(
+            serde_json::to_value(&unlocked).unwrap()["badge"],
+            Badge::WeekStreak.name()
+        );
+    }
+}
@@ -0,0 +1,8 @@
+//! Badges for logging habits. The [`services::AchievementSubscriber`]
+//! re-checks a user's badges on meal events and pushes the ones that get
+//! unlocked; `GET /me/achievements` lists them with the progress towards
+//! the rest.
+
+pub mod dto;
+pub mod repo;
+pub mod services;
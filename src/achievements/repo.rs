@@ -0,0 +1,71 @@
+use sqlx::{PgExecutor, PgPool};
+use time::{Date, OffsetDateTime};
+use uuid::Uuid;
+
+use crate::achievements::dto::Badge;
+
+pub async fn list_unlocked(
+    db: &PgPool,
+    user_id: Uuid,
+) -> anyhow::Result<Vec<(Badge, OffsetDateTime)>> {
+    let rows = sqlx::query_as::<_, (Badge, OffsetDateTime)>(
+        r#"
+        SELECT badge, unlocked_at
+        FROM user_achievements
+        WHERE user_id = $1
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(db)
+    .await?;
+    Ok(rows)
+}
+
+/// Records the badges as unlocked; returns those that weren't already.
+pub async fn unlock(
+    db: impl PgExecutor<'_>,
+    user_id: Uuid,
+    badges: &[Badge],
+) -> anyhow::Result<Vec<Badge>> {
+    let names: Vec<&str> = badges.iter().map(|b| b.name()).collect();
+    let unlocked = sqlx::query_scalar::<_, Badge>(
+        r#"
+        INSERT INTO user_achievements (user_id, badge)
+        SELECT $1, unnest($2::text[])
+        ON CONFLICT (user_id, badge) DO NOTHING
+        RETURNING badge
+        "#,
+    )
+    .bind(user_id)
+    .bind(&names)
+    .fetch_all(db)
+    .await?;
+    Ok(unlocked)
+}
+
+/// Local days in `tz` whose meals add up to at least `protein_g`, oldest
+/// first.
+pub async fn protein_goal_days(
+    db: &PgPool,
+    user_id: Uuid,
+    tz: &str,
+    protein_g: f64,
+) -> anyhow::Result<Vec<Date>> {
+    let days = sqlx::query_scalar::<_, Date>(
+        r#"
+        SELECT (m.created_at AT TIME ZONE $2)::date AS day
+        FROM meals m
+        JOIN meal_nutrition n ON n.meal_id = m.id
+        WHERE m.user_id = $1
+        GROUP BY 1
+        HAVING SUM(n.protein_g)::float8 >= $3
+        ORDER BY 1
+        "#,
+    )
+    .bind(user_id)
+    .bind(tz)
+    .bind(protein_g)
+    .fetch_all(db)
+    .await?;
+    Ok(days)
+}
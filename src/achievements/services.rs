@@ -0,0 +1,117 @@
+use std::collections::HashMap;
+
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    achievements::{
+        dto::{Achievement, AchievementsResponse, Badge, BadgeProgress},
+        repo,
+    },
+    db::AppState,
+    events::{repo as events_repo, DomainEvent, EventSubscriber, OutboxEvent},
+    goals::repo as goals_repo,
+    i18n,
+    preferences::services as preferences_services,
+    push::services::{self as push, Notification},
+    stats::{repo as stats_repo, services::streaks},
+};
+
+/// Streaks and counts behind the badges, with days in the user's time zone.
+pub async fn progress(state: &AppState, user_id: Uuid) -> anyhow::Result<BadgeProgress> {
+    let tz = preferences_services::timezone(state, user_id).await?;
+    let today = stats_repo::today_in(&state.db, &tz).await?;
+    let logged = stats_repo::logged_days(&state.db, user_id, &tz).await?;
+    let meals = stats_repo::count_meals(&state.db, user_id).await?;
+    let protein_g = goals_repo::find_goals(&state.db, user_id)
+        .await?
+        .targets()
+        .protein_g;
+    let protein = repo::protein_goal_days(&state.db, user_id, &tz, protein_g).await?;
+    Ok(BadgeProgress {
+        logging: streaks(&logged, today),
+        meals,
+        protein: streaks(&protein, today),
+    })
+}
+
+/// Every badge with its unlock time or progress, in the request's locale.
+pub async fn achievements(state: &AppState, user_id: Uuid) -> anyhow::Result<AchievementsResponse> {
+    let progress = progress(state, user_id).await?;
+    let unlocked: HashMap<Badge, _> = repo::list_unlocked(&state.db, user_id)
+        .await?
+        .into_iter()
+        .collect();
+    let locale = i18n::current();
+    let achievements = Badge::ALL
+        .into_iter()
+        .map(|badge| {
+            let mut achievement = Achievement::new(badge, &progress, unlocked.get(&badge).copied());
+            achievement.title = i18n::translate_to(locale, &achievement.title);
+            achievement.description = i18n::translate_to(locale, &achievement.description);
+            achievement
+        })
+        .collect();
+    Ok(AchievementsResponse { achievements })
+}
+
+/// Unlocks the badges the user has earned, records an `achievement_unlocked`
+/// event for each new one and pushes it. Safe to repeat: a badge is only
+/// unlocked, and pushed, once.
+pub async fn check_achievements(state: &AppState, user_id: Uuid) -> anyhow::Result<Vec<Badge>> {
+    let progress = progress(state, user_id).await?;
+    let earned: Vec<Badge> = Badge::ALL
+        .into_iter()
+        .filter(|badge| progress.earned(*badge))
+        .collect();
+    if earned.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut tx = state.db.begin().await?;
+    let unlocked = repo::unlock(&mut *tx, user_id, &earned).await?;
+    for badge in &unlocked {
+        let event = DomainEvent::AchievementUnlocked { badge: *badge };
+        events_repo::record(&mut *tx, user_id, &event).await?;
+    }
+    tx.commit().await?;
+    for badge in &unlocked {
+        info!(user_id = %user_id, badge = badge.name(), "achievement unlocked");
+        push::notify(
+            state,
+            user_id,
+            &Notification::AchievementUnlocked { badge: *badge },
+        )
+        .await?;
+    }
+    Ok(unlocked)
+}
+
+/// Re-checks badges whenever a meal is logged or its nutrition is ready.
+pub struct AchievementSubscriber {
+    state: AppState,
+}
+
+impl AchievementSubscriber {
+    pub fn new(state: AppState) -> Self {
+        Self { state }
+    }
+}
+
+#[axum::async_trait]
+impl EventSubscriber for AchievementSubscriber {
+    fn name(&self) -> &'static str {
+        "achievements"
+    }
+
+    async fn handle(&self, event: &OutboxEvent) -> anyhow::Result<()> {
+        match event.event {
+            DomainEvent::MealCreated { .. } | DomainEvent::NutritionReady { .. } => {
+                check_achievements(&self.state, event.user_id).await?;
+                Ok(())
+            }
+            DomainEvent::UserRegistered
+            | DomainEvent::MealReaction { .. }
+            | DomainEvent::AchievementUnlocked { .. } => Ok(()),
+        }
+    }
+}
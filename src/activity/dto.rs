@@ -0,0 +1,192 @@
+use serde::{Deserialize, Serialize};
+use time::{Duration, OffsetDateTime};
+
+pub const MAX_ACTIVITY_ENTRIES: usize = 500;
+pub const MAX_ACTIVITY_KCAL: f64 = 10_000.0;
+pub const MAX_DURATION_MINUTES: f64 = 24.0 * 60.0;
+pub const MAX_NAME_LEN: usize = 100;
+pub const MAX_EXTERNAL_ID_LEN: usize = 200;
+/// Default share of burned calories added to the calorie budget; trackers
+/// tend to overestimate them.
+pub const DEFAULT_EAT_BACK_PERCENT: i16 = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+pub enum ActivitySource {
+    AppleHealth,
+    GoogleFit,
+    Fitbit,
+    Manual,
+}
+
+/// A workout, or calories burned over some period.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct NewActivity {
+    /// The source's id of the entry; sending it again updates the entry.
+    pub external_id: Option<String>,
+    /// E.g. `Running`.
+    pub name: Option<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub started_at: OffsetDateTime,
+    pub duration_minutes: Option<f64>,
+    /// Active calories only, without the basal metabolic rate.
+    pub calories_kcal: f64,
+}
+
+impl NewActivity {
+    /// Trims text fields and checks ranges; entries can't be in the future.
+    pub fn normalized(self) -> Result<Self, String> {
+        if !self.calories_kcal.is_finite()
+            || !(0.0..=MAX_ACTIVITY_KCAL).contains(&self.calories_kcal)
+        {
+            return Err(format!(
+                "calories_kcal must be between 0 and {}",
+                MAX_ACTIVITY_KCAL
+            ));
+        }
+        if self
+            .duration_minutes
+            .is_some_and(|d| !d.is_finite() || !(0.0..=MAX_DURATION_MINUTES).contains(&d))
+        {
+            return Err(format!(
+                "duration_minutes must be between 0 and {}",
+                MAX_DURATION_MINUTES
+            ));
+        }
+        if self.started_at > OffsetDateTime::now_utc() + Duration::minutes(5) {
+            return Err("started_at can't be in the future".into());
+        }
+        let trimmed = |value: Option<String>, field: &str, max: usize| {
+            let value = value
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty());
+            match value {
+                Some(v) if v.chars().count() > max => {
+                    Err(format!("{} must be at most {} characters", field, max))
+                }
+                value => Ok(value),
+            }
+        };
+        Ok(Self {
+            external_id: trimmed(self.external_id, "external_id", MAX_EXTERNAL_ID_LEN)?,
+            name: trimmed(self.name, "name", MAX_NAME_LEN)?,
+            ..self
+        })
+    }
+}
+
+/// Body of `POST /integrations/activity`.
+#[derive(Debug, Deserialize)]
+pub struct ImportActivityRequest {
+    pub source: ActivitySource,
+    pub entries: Vec<NewActivity>,
+}
+
+impl ImportActivityRequest {
+    /// The normalized entries; the first invalid one fails the request.
+    pub fn normalized(self) -> Result<(ActivitySource, Vec<NewActivity>), String> {
+        if self.entries.len() > MAX_ACTIVITY_ENTRIES {
+            return Err(format!(
+                "at most {} entries per request",
+                MAX_ACTIVITY_ENTRIES
+            ));
+        }
+        let entries = self
+            .entries
+            .into_iter()
+            .enumerate()
+            .map(|(i, entry)| {
+                entry
+                    .normalized()
+                    .map_err(|e| format!("entries[{}]: {}", i, e))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok((self.source, entries))
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImportActivityResponse {
+    pub imported: usize,
+}
+
+/// Activity of one day.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ActivityTotals {
+    pub burned_kcal: f64,
+    pub entries: i64,
+}
+
+/// The calorie goal plus the eaten-back share of the burned calories.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CalorieBudget {
+    pub goal_kcal: f64,
+    pub burned_kcal: f64,
+    pub eat_back_percent: i16,
+    pub budget_kcal: f64,
+    /// Negative once the budget is exceeded.
+    pub remaining_kcal: f64,
+}
+
+impl CalorieBudget {
+    pub fn new(goal_kcal: f64, burned_kcal: f64, eat_back_percent: i16, eaten_kcal: f64) -> Self {
+        let round1 = |v: f64| (v * 10.0).round() / 10.0;
+        let budget_kcal = goal_kcal + burned_kcal * f64::from(eat_back_percent) / 100.0;
+        Self {
+            goal_kcal: round1(goal_kcal),
+            burned_kcal: round1(burned_kcal),
+            eat_back_percent,
+            budget_kcal: round1(budget_kcal),
+            remaining_kcal: round1(budget_kcal - eaten_kcal),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(calories_kcal: f64) -> NewActivity {
+        NewActivity {
+            external_id: Some(" abc ".into()),
+            name: Some("".into()),
+            started_at: OffsetDateTime::now_utc() - Duration::hours(1),
+            duration_minutes: Some(30.0),
+            calories_kcal,
+        }
+    }
+
+    #[test]
+    fn normalizes_and_validates_entries() {
+        let normalized = entry(250.0).normalized().unwrap();
+        assert_eq!(normalized.external_id.as_deref(), Some("abc"));
+        assert_eq!(normalized.name, None);
+        assert!(entry(-1.0).normalized().is_err());
+        let future = NewActivity {
+            started_at: OffsetDateTime::now_utc() + Duration::hours(1),
+            ..entry(100.0)
+        };
+        assert!(future.normalized().is_err());
+
+        let request = ImportActivityRequest {
+            source: ActivitySource::AppleHealth,
+            entries: vec![entry(100.0), entry(f64::NAN)],
+        };
+        assert_eq!(
+            request.normalized().unwrap_err(),
+            "entries[1]: calories_kcal must be between 0 and 10000"
+        );
+    }
+
+    #[test]
+    fn budget_adds_the_eaten_back_share() {
+        let budget = CalorieBudget::new(2000.0, 400.0, 50, 1500.0);
+        assert_eq!(budget.budget_kcal, 2200.0);
+        assert_eq!(budget.remaining_kcal, 700.0);
+        assert_eq!(
+            CalorieBudget::new(2000.0, 400.0, 0, 0.0).budget_kcal,
+            2000.0
+        );
+    }
+}
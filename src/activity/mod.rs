@@ -0,0 +1,5 @@
+//! Burned calories and workouts, imported from health apps or pulled from
+//! connected services, and the calorie budget they add to.
+
+pub mod dto;
+pub mod repo;
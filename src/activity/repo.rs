@@ -0,0 +1,61 @@
+use sqlx::{PgExecutor, PgPool};
+use time::Date;
+use uuid::Uuid;
+
+use crate::activity::dto::{ActivitySource, ActivityTotals, NewActivity};
+
+/// Inserts the entry, or updates the one with the same `external_id`.
+pub async fn upsert_activity(
+    db: impl PgExecutor<'_>,
+    user_id: Uuid,
+    source: ActivitySource,
+    entry: &NewActivity,
+) -> anyhow::Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO activity (user_id, source, external_id, name, started_at, duration_minutes,
+                              calories_kcal)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        ON CONFLICT (user_id, source, external_id) DO UPDATE
+        SET name = EXCLUDED.name,
+            started_at = EXCLUDED.started_at,
+            duration_minutes = EXCLUDED.duration_minutes,
+            calories_kcal = EXCLUDED.calories_kcal
+        "#,
+    )
+    .bind(user_id)
+    .bind(source)
+    .bind(&entry.external_id)
+    .bind(&entry.name)
+    .bind(entry.started_at)
+    .bind(entry.duration_minutes)
+    .bind(entry.calories_kcal)
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+/// Activity started on `date` in time zone `tz`.
+pub async fn day_totals(
+    db: &PgPool,
+    user_id: Uuid,
+    date: Date,
+    tz: &str,
+) -> anyhow::Result<ActivityTotals> {
+    let totals = sqlx::query_as::<_, ActivityTotals>(
+        r#"
+        SELECT COALESCE(SUM(calories_kcal), 0)::float8 AS burned_kcal,
+               COUNT(*) AS entries
+        FROM activity
+        WHERE user_id = $1
+          AND started_at >= $2::date::timestamp AT TIME ZONE $3
+          AND started_at < ($2::date + 1)::timestamp AT TIME ZONE $3
+        "#,
+    )
+    .bind(user_id)
+    .bind(date)
+    .bind(tz)
+    .fetch_one(db)
+    .await?;
+    Ok(totals)
+}
@@ -0,0 +1,111 @@
+use serde::{Deserialize, Serialize};
+use time::{Date, OffsetDateTime};
+
+time::serde::format_description!(iso_date, Date, "[year]-[month]-[day]");
+
+pub const DEFAULT_STATS_DAYS: i32 = 30;
+pub const MAX_STATS_DAYS: i32 = 365;
+
+#[derive(Debug, Deserialize)]
+pub struct AdminStatsQuery {
+    /// Length of the reporting window in UTC days, ending today.
+    pub days: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct UserCounts {
+    pub total: i64,
+    /// Registered within the window.
+    pub new: i64,
+    /// Logged at least one meal within the window.
+    pub active: i64,
+}
+
+/// One UTC day of the window. Users count as active on days they log a meal.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct DailyActivity {
+    #[serde(with = "iso_date")]
+    pub date: Date,
+    pub active_users: i64,
+    pub meals: i64,
+    pub new_users: i64,
+}
+
+/// Bytes in object storage as recorded in the database. Photos sharing a
+/// deduplicated object are counted once.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct StorageUsage {
+    pub photo_objects: i64,
+    pub photo_bytes: i64,
+    pub export_bytes: i64,
+    pub total_bytes: i64,
+}
+
+/// `analyze_meal` jobs created within the window.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct AnalysisStats {
+    pub succeeded: i64,
+    pub failed: i64,
+    /// Queued or running, including failed attempts awaiting a retry.
+    pub pending: i64,
+    /// `succeeded / (succeeded + failed)`; `null` when neither happened.
+    pub success_rate: Option<f64>,
+    /// AI nutrition estimates stored within the window.
+    pub estimates_stored: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AdminStats {
+    #[serde(with = "time::serde::rfc3339")]
+    pub generated_at: OffsetDateTime,
+    pub days: i32,
+    pub users: UserCounts,
+    pub daily: Vec<DailyActivity>,
+    pub storage: StorageUsage,
+    pub analysis: AnalysisStats,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MigrationState {
+    Applied,
+    Pending,
+    /// Started but didn't finish; the schema may be half-changed.
+    Failed,
+    /// Applied, but the file changed since.
+    Modified,
+    /// Recorded in the database but not part of this build, e.g. applied
+    /// by a newer release.
+    Unknown,
+}
+
+/// A row of `_sqlx_migrations`.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct AppliedMigration {
+    pub version: i64,
+    pub description: String,
+    pub installed_on: OffsetDateTime,
+    pub success: bool,
+    pub checksum: Vec<u8>,
+    pub execution_time: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MigrationInfo {
+    pub version: i64,
+    pub description: String,
+    pub state: MigrationState,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub installed_on: Option<OffsetDateTime>,
+    pub execution_time_ms: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MigrationsReport {
+    /// Whether every migration of this build is applied and nothing else is.
+    pub up_to_date: bool,
+    pub applied: usize,
+    pub pending: usize,
+    /// In version order.
+    pub migrations: Vec<MigrationInfo>,
+}
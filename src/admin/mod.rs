@@ -0,0 +1,4 @@
+pub mod dto;
+pub mod ops;
+pub mod repo;
+pub mod services;
@@ -0,0 +1,111 @@
+//! Operations behind `mealmind-admin`, for running against a deployment
+//! without going through the HTTP API.
+
+use anyhow::{bail, Context};
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    admin::repo,
+    auth::{password, repo as users_repo},
+    db::{AppState, RepoError, User},
+    events::{repo as events_repo, DomainEvent},
+    jobs::{repo as jobs_repo, Job},
+    meals::{dto::MealStatus, repo as meals_repo, services as meals_services},
+    routes::auth::is_valid_email,
+    tasks::maintenance,
+};
+
+/// Meals rescored per batch.
+const RESCORE_BATCH: i64 = 500;
+
+fn check_password(password: &str) -> anyhow::Result<()> {
+    if password.len() < password::MIN_PASSWORD_LEN {
+        bail!(
+            "password must be at least {} characters",
+            password::MIN_PASSWORD_LEN
+        );
+    }
+    Ok(())
+}
+
+/// Registers a user like `/auth/register` does, optionally as an admin.
+pub async fn create_user(
+    state: &AppState,
+    email: &str,
+    password: &str,
+    admin: bool,
+) -> anyhow::Result<User> {
+    let email = email.trim().to_lowercase();
+    if !is_valid_email(&email) {
+        bail!("invalid email: {}", email);
+    }
+    check_password(password)?;
+    let hash = password::hash_password(password)?;
+    let mut tx = state.db.begin().await?;
+    let user = match users_repo::create(&mut *tx, &email, &hash).await {
+        Ok(user) => user,
+        Err(RepoError::Conflict(_)) => bail!("{} is already registered", email),
+        Err(e) => return Err(e.into()),
+    };
+    events_repo::record(&mut *tx, user.id, &DomainEvent::UserRegistered).await?;
+    tx.commit().await?;
+    if admin {
+        repo::set_admin(&state.db, user.id, true).await?;
+    }
+    info!(user_id = %user.id, admin, "user created");
+    Ok(user)
+}
+
+/// Sets a new password for the user with `email`; returns their id.
+pub async fn reset_password(state: &AppState, email: &str, password: &str) -> anyhow::Result<Uuid> {
+    let email = email.trim().to_lowercase();
+    check_password(password)?;
+    let hash = password::hash_password(password)?;
+    let user_id = repo::set_password_hash(&state.db, &email, &hash)
+        .await?
+        .with_context(|| format!("no user with email {}", email))?;
+    info!(user_id = %user_id, "password reset");
+    Ok(user_id)
+}
+
+/// Puts the meal back to `pending` and queues its analysis for the workers
+/// of the running API, without screening the photos again; returns the job
+/// id.
+pub async fn reanalyze_meal(state: &AppState, meal_id: Uuid) -> anyhow::Result<Uuid> {
+    meals_repo::set_status(&state.db, meal_id, MealStatus::Pending)
+        .await?
+        .with_context(|| format!("no meal with id {}", meal_id))?;
+    let job = Job::AnalyzeMeal {
+        meal_id,
+        skip_screening: true,
+    };
+    let job_id = jobs_repo::enqueue(&state.db, &job, state.config.jobs.max_attempts).await?;
+    info!(meal_id = %meal_id, job_id = %job_id, "meal analysis queued");
+    Ok(job_id)
+}
+
+/// Recomputes nutrition totals and global scores of every analyzed meal,
+/// e.g. after changing the score weights; returns how many were rescored.
+pub async fn rescore_meals(state: &AppState) -> anyhow::Result<usize> {
+    let mut after = None;
+    let mut rescored = 0;
+    loop {
+        let meals = repo::meals_with_nutrition(&state.db, after, RESCORE_BATCH).await?;
+        let Some(&(last, _)) = meals.last() else {
+            break;
+        };
+        for (meal_id, user_id) in meals {
+            meals_services::refresh_nutrition(state, user_id, meal_id).await?;
+            rescored += 1;
+        }
+        after = Some(last);
+    }
+    info!(meals = rescored, "meals rescored");
+    Ok(rescored)
+}
+
+/// Runs the orphaned photo cleanup now instead of on its cron schedule.
+pub async fn storage_gc(state: &AppState) -> anyhow::Result<()> {
+    maintenance::cleanup_orphan_photos(state).await
+}
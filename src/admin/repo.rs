@@ -0,0 +1,221 @@
+//! Queries across all users, for the admin API and `mealmind-admin`.
+//! Aggregate windows are `days` UTC days ending today. Aggregates take an
+//! optional organization to report on its members only.
+
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::admin::dto::{AnalysisStats, AppliedMigration, DailyActivity, StorageUsage, UserCounts};
+
+pub async fn user_counts(db: &PgPool, days: i32, org: Option<Uuid>) -> anyhow::Result<UserCounts> {
+    let counts = sqlx::query_as::<_, UserCounts>(
+        r#"
+        WITH bounds AS (
+            SELECT ((NOW() AT TIME ZONE 'UTC')::date - ($1 - 1))::timestamp
+                AT TIME ZONE 'UTC' AS since
+        )
+        SELECT
+            (SELECT COUNT(*) FROM users WHERE $2::uuid IS NULL OR org_id = $2) AS total,
+            (SELECT COUNT(*) FROM users, bounds
+             WHERE created_at >= since AND ($2::uuid IS NULL OR org_id = $2)) AS new,
+            (SELECT COUNT(DISTINCT user_id) FROM meals, bounds
+             WHERE created_at >= since AND ($2::uuid IS NULL OR org_id = $2)) AS active
+        "#,
+    )
+    .bind(days)
+    .bind(org)
+    .fetch_one(db)
+    .await?;
+    Ok(counts)
+}
+
+/// One row per day, oldest first, including days without activity.
+pub async fn daily_activity(
+    db: &PgPool,
+    days: i32,
+    org: Option<Uuid>,
+) -> anyhow::Result<Vec<DailyActivity>> {
+    let rows = sqlx::query_as::<_, DailyActivity>(
+        r#"
+        WITH days AS (
+            SELECT generate_series(
+                (NOW() AT TIME ZONE 'UTC')::date - ($1 - 1),
+                (NOW() AT TIME ZONE 'UTC')::date,
+                INTERVAL '1 day'
+            )::date AS date
+        ),
+        meals_per_day AS (
+            SELECT (created_at AT TIME ZONE 'UTC')::date AS date,
+                   COUNT(*) AS meals,
+                   COUNT(DISTINCT user_id) AS active_users
+            FROM meals
+            WHERE created_at >= (SELECT MIN(date) FROM days)::timestamp AT TIME ZONE 'UTC'
+              AND ($2::uuid IS NULL OR org_id = $2)
+            GROUP BY 1
+        ),
+        users_per_day AS (
+            SELECT (created_at AT TIME ZONE 'UTC')::date AS date, COUNT(*) AS new_users
+            FROM users
+            WHERE created_at >= (SELECT MIN(date) FROM days)::timestamp AT TIME ZONE 'UTC'
+              AND ($2::uuid IS NULL OR org_id = $2)
+            GROUP BY 1
+        )
+        SELECT d.date,
+               COALESCE(m.active_users, 0) AS active_users,
+               COALESCE(m.meals, 0) AS meals,
+               COALESCE(u.new_users, 0) AS new_users
+        FROM days d
+        LEFT JOIN meals_per_day m ON m.date = d.date
+        LEFT JOIN users_per_day u ON u.date = d.date
+        ORDER BY d.date
+        "#,
+    )
+    .bind(days)
+    .bind(org)
+    .fetch_all(db)
+    .await?;
+    Ok(rows)
+}
+
+pub async fn storage_usage(db: &PgPool, org: Option<Uuid>) -> anyhow::Result<StorageUsage> {
+    let usage = sqlx::query_as::<_, StorageUsage>(
+        r#"
+        WITH members AS (
+            SELECT id FROM users WHERE $1::uuid IS NULL OR org_id = $1
+        ),
+        objects AS (
+            SELECT s3_key, MAX(size_bytes) AS size_bytes
+            FROM photos
+            WHERE user_id IN (SELECT id FROM members)
+            GROUP BY s3_key
+        ),
+        photo_totals AS (
+            SELECT COUNT(*) AS photo_objects,
+                   COALESCE(SUM(size_bytes), 0)::bigint AS photo_bytes
+            FROM objects
+        ),
+        export_totals AS (
+            SELECT COALESCE(SUM(size_bytes), 0)::bigint AS export_bytes
+            FROM data_exports
+            WHERE status = 'done' AND user_id IN (SELECT id FROM members)
+        )
+        SELECT photo_objects, photo_bytes, export_bytes,
+               photo_bytes + export_bytes AS total_bytes
+        FROM photo_totals, export_totals
+        "#,
+    )
+    .bind(org)
+    .fetch_one(db)
+    .await?;
+    Ok(usage)
+}
+
+/// With `org`, jobs count when their meal still exists and belongs to it.
+pub async fn analysis_stats(
+    db: &PgPool,
+    days: i32,
+    org: Option<Uuid>,
+) -> anyhow::Result<AnalysisStats> {
+    let stats = sqlx::query_as::<_, AnalysisStats>(
+        r#"
+        WITH bounds AS (
+            SELECT ((NOW() AT TIME ZONE 'UTC')::date - ($1 - 1))::timestamp
+                AT TIME ZONE 'UTC' AS since
+        ),
+        jobs_in_window AS (
+            SELECT
+                COUNT(*) FILTER (WHERE status = 'done') AS succeeded,
+                COUNT(*) FILTER (WHERE status = 'failed') AS failed,
+                COUNT(*) FILTER (WHERE status IN ('queued', 'running')) AS pending
+            FROM jobs, bounds
+            WHERE kind = 'analyze_meal' AND created_at >= since
+              AND ($2::uuid IS NULL OR EXISTS (
+                  SELECT 1 FROM meals m
+                  WHERE m.id = (jobs.payload->>'meal_id')::uuid AND m.org_id = $2
+              ))
+        )
+        SELECT succeeded, failed, pending,
+               succeeded::float8 / NULLIF(succeeded + failed, 0) AS success_rate,
+               (SELECT COUNT(*) FROM meal_nutrition n, bounds
+                WHERE n.source = 'ai' AND n.updated_at >= since
+                  AND ($2::uuid IS NULL OR EXISTS (
+                      SELECT 1 FROM meals m WHERE m.id = n.meal_id AND m.org_id = $2
+                  ))) AS estimates_stored
+        FROM jobs_in_window
+        "#,
+    )
+    .bind(days)
+    .bind(org)
+    .fetch_one(db)
+    .await?;
+    Ok(stats)
+}
+
+/// Migrations recorded by sqlx; none before the first run created the table.
+pub async fn applied_migrations(db: &PgPool) -> anyhow::Result<Vec<AppliedMigration>> {
+    let exists =
+        sqlx::query_scalar::<_, bool>(r#"SELECT to_regclass('_sqlx_migrations') IS NOT NULL"#)
+            .fetch_one(db)
+            .await?;
+    if !exists {
+        return Ok(Vec::new());
+    }
+    let migrations = sqlx::query_as::<_, AppliedMigration>(
+        r#"
+        SELECT version, description, installed_on, success, checksum, execution_time
+        FROM _sqlx_migrations
+        ORDER BY version
+        "#,
+    )
+    .fetch_all(db)
+    .await?;
+    Ok(migrations)
+}
+
+pub async fn set_password_hash(
+    db: &PgPool,
+    email: &str,
+    password_hash: &str,
+) -> anyhow::Result<Option<Uuid>> {
+    let user_id = sqlx::query_scalar::<_, Uuid>(
+        r#"UPDATE users SET password_hash = $2 WHERE email = $1 RETURNING id"#,
+    )
+    .bind(email)
+    .bind(password_hash)
+    .fetch_optional(db)
+    .await?;
+    Ok(user_id)
+}
+
+pub async fn set_admin(db: &PgPool, user_id: Uuid, is_admin: bool) -> anyhow::Result<()> {
+    sqlx::query(r#"UPDATE users SET is_admin = $2 WHERE id = $1"#)
+        .bind(user_id)
+        .bind(is_admin)
+        .execute(db)
+        .await?;
+    Ok(())
+}
+
+/// Up to `limit` meals with nutrition and their owners, in id order after
+/// `after`.
+pub async fn meals_with_nutrition(
+    db: &PgPool,
+    after: Option<Uuid>,
+    limit: i64,
+) -> anyhow::Result<Vec<(Uuid, Uuid)>> {
+    let meals = sqlx::query_as::<_, (Uuid, Uuid)>(
+        r#"
+        SELECT m.id, m.user_id
+        FROM meals m
+        JOIN meal_nutrition n ON n.meal_id = m.id
+        WHERE $1::uuid IS NULL OR m.id > $1
+        ORDER BY m.id
+        LIMIT $2
+        "#,
+    )
+    .bind(after)
+    .bind(limit)
+    .fetch_all(db)
+    .await?;
+    Ok(meals)
+}
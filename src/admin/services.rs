@@ -0,0 +1,165 @@
+use std::collections::BTreeMap;
+
+use sqlx::migrate::Migration;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::{
+    admin::{
+        dto::{
+            AdminStats, AdminStatsQuery, AppliedMigration, MigrationInfo, MigrationState,
+            MigrationsReport, DEFAULT_STATS_DAYS, MAX_STATS_DAYS,
+        },
+        repo,
+    },
+    db::{AppState, MIGRATOR},
+};
+
+/// The requested window, or an error message for one out of range.
+pub fn window_days(query: &AdminStatsQuery) -> Result<i32, String> {
+    match query.days {
+        None => Ok(DEFAULT_STATS_DAYS),
+        Some(days) if (1..=MAX_STATS_DAYS).contains(&days) => Ok(days),
+        Some(_) => Err(format!("days must be between 1 and {}", MAX_STATS_DAYS)),
+    }
+}
+
+/// Platform-wide statistics, or those of one organization; the aggregates
+/// run concurrently.
+pub async fn admin_stats(
+    state: &AppState,
+    days: i32,
+    org: Option<Uuid>,
+) -> anyhow::Result<AdminStats> {
+    let (users, daily, storage, analysis) = tokio::try_join!(
+        repo::user_counts(&state.db, days, org),
+        repo::daily_activity(&state.db, days, org),
+        repo::storage_usage(&state.db, org),
+        repo::analysis_stats(&state.db, days, org),
+    )?;
+    Ok(AdminStats {
+        generated_at: OffsetDateTime::now_utc(),
+        days,
+        users,
+        daily,
+        storage,
+        analysis,
+    })
+}
+
+pub async fn migrations(state: &AppState) -> anyhow::Result<MigrationsReport> {
+    let applied = repo::applied_migrations(&state.db).await?;
+    Ok(migrations_report(MIGRATOR.iter(), applied))
+}
+
+/// Matches the migrations of this build against the recorded ones by
+/// version. Down migrations aren't used, so only up migrations count.
+fn migrations_report<'a>(
+    known: impl Iterator<Item = &'a Migration>,
+    applied: Vec<AppliedMigration>,
+) -> MigrationsReport {
+    let mut applied: BTreeMap<i64, AppliedMigration> =
+        applied.into_iter().map(|m| (m.version, m)).collect();
+    let mut migrations: Vec<MigrationInfo> = known
+        .filter(|m| m.migration_type.is_up_migration())
+        .map(|m| match applied.remove(&m.version) {
+            None => MigrationInfo {
+                version: m.version,
+                description: m.description.to_string(),
+                state: MigrationState::Pending,
+                installed_on: None,
+                execution_time_ms: None,
+            },
+            Some(row) => {
+                let state = if !row.success {
+                    MigrationState::Failed
+                } else if row.checksum != *m.checksum {
+                    MigrationState::Modified
+                } else {
+                    MigrationState::Applied
+                };
+                applied_info(row, state)
+            }
+        })
+        .collect();
+    migrations.extend(
+        applied
+            .into_values()
+            .map(|row| applied_info(row, MigrationState::Unknown)),
+    );
+    migrations.sort_by_key(|m| m.version);
+    let count = |state| migrations.iter().filter(|m| m.state == state).count();
+    let (applied, pending) = (
+        count(MigrationState::Applied),
+        count(MigrationState::Pending),
+    );
+    MigrationsReport {
+        up_to_date: applied == migrations.len(),
+        applied,
+        pending,
+        migrations,
+    }
+}
+
+fn applied_info(row: AppliedMigration, state: MigrationState) -> MigrationInfo {
+    MigrationInfo {
+        version: row.version,
+        description: row.description,
+        state,
+        installed_on: Some(row.installed_on),
+        // sqlx records nanoseconds.
+        execution_time_ms: Some(row.execution_time / 1_000_000),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn window_days_defaults_and_bounds() {
+        let query = |days| AdminStatsQuery { days };
+        assert_eq!(window_days(&query(None)), Ok(DEFAULT_STATS_DAYS));
+        assert_eq!(window_days(&query(Some(1))), Ok(1));
+        assert_eq!(window_days(&query(Some(365))), Ok(365));
+        assert!(window_days(&query(Some(0))).is_err());
+        assert!(window_days(&query(Some(366))).is_err());
+    }
+
+    #[test]
+    fn migrations_are_matched_by_version_and_checksum() {
+        let known: Vec<&Migration> = MIGRATOR.iter().take(3).collect();
+        let row = |m: &Migration, success, checksum: &[u8]| AppliedMigration {
+            version: m.version,
+            description: m.description.to_string(),
+            installed_on: OffsetDateTime::UNIX_EPOCH,
+            success,
+            checksum: checksum.to_vec(),
+            execution_time: 4_000_000,
+        };
+        let mut newer = row(known[0], true, b"");
+        newer.version = 9999;
+        let applied = vec![
+            row(known[0], true, &known[0].checksum),
+            row(known[1], true, b"edited"),
+            newer,
+        ];
+        let report = migrations_report(known.iter().copied(), applied);
+        let states: Vec<_> = report.migrations.iter().map(|m| m.state).collect();
+        assert_eq!(
+            states,
+            [
+                MigrationState::Applied,
+                MigrationState::Modified,
+                MigrationState::Pending,
+                MigrationState::Unknown,
+            ]
+        );
+        assert_eq!((report.applied, report.pending), (1, 1));
+        assert!(!report.up_to_date);
+        assert_eq!(report.migrations[0].execution_time_ms, Some(4));
+
+        let all = known.iter().map(|m| row(m, true, &m.checksum)).collect();
+        assert!(migrations_report(known.iter().copied(), all).up_to_date);
+    }
+}
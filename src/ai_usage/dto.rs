@@ -0,0 +1,141 @@
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::{analysis::TokenUsage, config::ModelPrice};
+
+/// Users listed in the usage report, by cost.
+pub const TOP_USERS: i64 = 50;
+
+/// Price of a call in USD; calls of unpriced models cost nothing.
+pub fn cost_usd(usage: &TokenUsage, price: Option<ModelPrice>) -> f64 {
+    let Some(price) = price else {
+        return 0.0;
+    };
+    (usage.input_tokens as f64 * price.input + usage.output_tokens as f64 * price.output)
+        / 1_000_000.0
+}
+
+/// A user's spend this month against their budget.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BudgetStatus {
+    /// `None` when unlimited.
+    pub monthly_budget_usd: Option<f64>,
+    pub month_cost_usd: f64,
+}
+
+impl BudgetStatus {
+    pub fn exceeded(&self) -> bool {
+        self.monthly_budget_usd
+            .is_some_and(|budget| self.month_cost_usd >= budget)
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, sqlx::FromRow)]
+pub struct UsageTotals {
+    pub calls: i64,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub cost_usd: f64,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct ModelUsage {
+    pub provider: String,
+    pub model: String,
+    pub calls: i64,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub cost_usd: f64,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct UserUsage {
+    pub user_id: Uuid,
+    pub email: String,
+    pub calls: i64,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub cost_usd: f64,
+    /// Spend in the current calendar month, which the budget applies to.
+    pub month_cost_usd: f64,
+    /// The user's own budget, if one was set.
+    #[serde(skip)]
+    pub custom_budget_usd: Option<f64>,
+    /// The budget in effect; `null` when unlimited.
+    pub monthly_budget_usd: Option<f64>,
+    /// Automatic analysis is off until the month ends.
+    pub paused: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AiUsageReport {
+    #[serde(with = "time::serde::rfc3339")]
+    pub generated_at: OffsetDateTime,
+    pub days: i32,
+    pub totals: UsageTotals,
+    pub models: Vec<ModelUsage>,
+    /// The users with the highest cost in the window.
+    pub users: Vec<UserUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetBudgetRequest {
+    /// `null` goes back to the default budget.
+    pub monthly_budget_usd: Option<f64>,
+}
+
+impl SetBudgetRequest {
+    pub fn validated(&self) -> Result<Option<f64>, String> {
+        match self.monthly_budget_usd {
+            Some(budget) if !budget.is_finite() || budget < 0.0 => {
+                Err("monthly_budget_usd must not be negative".into())
+            }
+            budget => Ok(budget),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BudgetResponse {
+    pub user_id: Uuid,
+    /// The budget in effect; `null` when unlimited.
+    pub monthly_budget_usd: Option<f64>,
+    /// Whether it is the user's own rather than the default.
+    pub custom: bool,
+    pub month_cost_usd: f64,
+    pub paused: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cost_is_priced_per_million_tokens() {
+        let usage = TokenUsage {
+            provider: "openai",
+            model: "gpt-4o-mini".into(),
+            input_tokens: 2_000,
+            output_tokens: 500,
+        };
+        let price = ModelPrice {
+            input: 0.15,
+            output: 0.60,
+        };
+        assert!((cost_usd(&usage, Some(price)) - 0.0006).abs() < 1e-12);
+        assert_eq!(cost_usd(&usage, None), 0.0);
+    }
+
+    #[test]
+    fn budget_is_exceeded_once_spent() {
+        let status = |budget, spent| BudgetStatus {
+            monthly_budget_usd: budget,
+            month_cost_usd: spent,
+        };
+        assert!(!status(None, 100.0).exceeded());
+        assert!(!status(Some(1.0), 0.99).exceeded());
+        assert!(status(Some(1.0), 1.0).exceeded());
+        assert!(status(Some(0.0), 0.0).exceeded());
+    }
+}
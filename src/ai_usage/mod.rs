@@ -0,0 +1,8 @@
+//! What AI analysis costs: every call's tokens and price are recorded in
+//! `ai_usage`, and a user whose spend this month reached their budget gets
+//! no automatic analysis until the next month. Manual nutrition and meal
+//! items are unaffected.
+
+pub mod dto;
+pub mod repo;
+pub mod services;
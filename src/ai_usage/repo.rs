@@ -0,0 +1,172 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+    ai_usage::dto::{ModelUsage, UsageTotals, UserUsage},
+    analysis::TokenUsage,
+};
+
+/// Start of the current calendar month in UTC, as SQL.
+const MONTH_START: &str = "date_trunc('month', NOW() AT TIME ZONE 'UTC') AT TIME ZONE 'UTC'";
+
+/// Start of a window of `$1` UTC days ending today, as SQL.
+const WINDOW_START: &str =
+    "((NOW() AT TIME ZONE 'UTC')::date - ($1 - 1))::timestamp AT TIME ZONE 'UTC'";
+
+pub async fn record(
+    db: &PgPool,
+    user_id: Uuid,
+    meal_id: Uuid,
+    usage: &TokenUsage,
+    cost_usd: f64,
+) -> anyhow::Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO ai_usage (user_id, meal_id, provider, model, input_tokens, output_tokens,
+                              cost_usd)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        "#,
+    )
+    .bind(user_id)
+    .bind(meal_id)
+    .bind(usage.provider)
+    .bind(&usage.model)
+    .bind(usage.input_tokens)
+    .bind(usage.output_tokens)
+    .bind(cost_usd)
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+/// The user's AI spend in the current calendar month (UTC).
+pub async fn month_cost(db: &PgPool, user_id: Uuid) -> anyhow::Result<f64> {
+    let cost = sqlx::query_scalar::<_, f64>(&format!(
+        r#"
+        SELECT COALESCE(SUM(cost_usd), 0)::float8
+        FROM ai_usage
+        WHERE user_id = $1 AND created_at >= {MONTH_START}
+        "#
+    ))
+    .bind(user_id)
+    .fetch_one(db)
+    .await?;
+    Ok(cost)
+}
+
+/// The user's own budget, overriding the default.
+pub async fn find_budget(db: &PgPool, user_id: Uuid) -> anyhow::Result<Option<f64>> {
+    let budget = sqlx::query_scalar::<_, f64>(
+        "SELECT monthly_budget_usd::float8 FROM ai_budgets WHERE user_id = $1",
+    )
+    .bind(user_id)
+    .fetch_optional(db)
+    .await?;
+    Ok(budget)
+}
+
+/// Sets or, with `None`, removes the user's own budget.
+pub async fn set_budget(db: &PgPool, user_id: Uuid, budget: Option<f64>) -> anyhow::Result<()> {
+    match budget {
+        Some(budget) => {
+            sqlx::query(
+                r#"
+                INSERT INTO ai_budgets (user_id, monthly_budget_usd) VALUES ($1, $2)
+                ON CONFLICT (user_id) DO UPDATE
+                SET monthly_budget_usd = EXCLUDED.monthly_budget_usd, updated_at = NOW()
+                "#,
+            )
+            .bind(user_id)
+            .bind(budget)
+            .execute(db)
+            .await?;
+        }
+        None => {
+            sqlx::query("DELETE FROM ai_budgets WHERE user_id = $1")
+                .bind(user_id)
+                .execute(db)
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+pub async fn user_exists(db: &PgPool, user_id: Uuid) -> anyhow::Result<bool> {
+    let exists = sqlx::query_scalar::<_, bool>("SELECT EXISTS (SELECT 1 FROM users WHERE id = $1)")
+        .bind(user_id)
+        .fetch_one(db)
+        .await?;
+    Ok(exists)
+}
+
+pub async fn totals(db: &PgPool, days: i32) -> anyhow::Result<UsageTotals> {
+    let totals = sqlx::query_as::<_, UsageTotals>(&format!(
+        r#"
+        SELECT COUNT(*) AS calls,
+               COALESCE(SUM(input_tokens), 0)::int8 AS input_tokens,
+               COALESCE(SUM(output_tokens), 0)::int8 AS output_tokens,
+               COALESCE(SUM(cost_usd), 0)::float8 AS cost_usd
+        FROM ai_usage
+        WHERE created_at >= {WINDOW_START}
+        "#
+    ))
+    .bind(days)
+    .fetch_one(db)
+    .await?;
+    Ok(totals)
+}
+
+pub async fn by_model(db: &PgPool, days: i32) -> anyhow::Result<Vec<ModelUsage>> {
+    let models = sqlx::query_as::<_, ModelUsage>(&format!(
+        r#"
+        SELECT provider, model, COUNT(*) AS calls,
+               SUM(input_tokens)::int8 AS input_tokens,
+               SUM(output_tokens)::int8 AS output_tokens,
+               SUM(cost_usd)::float8 AS cost_usd
+        FROM ai_usage
+        WHERE created_at >= {WINDOW_START}
+        GROUP BY provider, model
+        ORDER BY cost_usd DESC, calls DESC
+        "#
+    ))
+    .bind(days)
+    .fetch_all(db)
+    .await?;
+    Ok(models)
+}
+
+/// The `limit` users with the highest cost in the window, with this month's
+/// spend and their own budget; the caller fills in the effective budget.
+pub async fn top_users(db: &PgPool, days: i32, limit: i64) -> anyhow::Result<Vec<UserUsage>> {
+    let users = sqlx::query_as::<_, UserUsage>(&format!(
+        r#"
+        WITH window_usage AS (
+            SELECT user_id, COUNT(*) AS calls,
+                   SUM(input_tokens)::int8 AS input_tokens,
+                   SUM(output_tokens)::int8 AS output_tokens,
+                   SUM(cost_usd)::float8 AS cost_usd
+            FROM ai_usage
+            WHERE created_at >= {WINDOW_START}
+            GROUP BY user_id
+            ORDER BY cost_usd DESC, calls DESC
+            LIMIT $2
+        )
+        SELECT w.user_id, u.email, w.calls, w.input_tokens, w.output_tokens, w.cost_usd,
+               COALESCE((SELECT SUM(a.cost_usd) FROM ai_usage a
+                         WHERE a.user_id = w.user_id AND a.created_at >= {MONTH_START}),
+                        0)::float8 AS month_cost_usd,
+               b.monthly_budget_usd::float8 AS custom_budget_usd,
+               NULL::float8 AS monthly_budget_usd,
+               FALSE AS paused
+        FROM window_usage w
+        JOIN users u ON u.id = w.user_id
+        LEFT JOIN ai_budgets b ON b.user_id = w.user_id
+        ORDER BY w.cost_usd DESC, w.calls DESC
+        "#
+    ))
+    .bind(days)
+    .bind(limit)
+    .fetch_all(db)
+    .await?;
+    Ok(users)
+}
@@ -0,0 +1,104 @@
+use axum::http::StatusCode;
+use time::OffsetDateTime;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    ai_usage::{
+        dto::{cost_usd, AiUsageReport, BudgetResponse, BudgetStatus, TOP_USERS},
+        repo,
+    },
+    analysis::TokenUsage,
+    db::AppState,
+    error::ErrorCode,
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum BudgetError {
+    #[error("User not found")]
+    UserNotFound,
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+impl BudgetError {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            BudgetError::UserNotFound => StatusCode::NOT_FOUND,
+            BudgetError::Other(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            BudgetError::UserNotFound => ErrorCode::AuthUserNotFound,
+            BudgetError::Other(_) => ErrorCode::Internal,
+        }
+    }
+}
+
+/// Stores the tokens and price of one analysis call.
+pub async fn record_usage(
+    state: &AppState,
+    user_id: Uuid,
+    meal_id: Uuid,
+    usage: &TokenUsage,
+) -> anyhow::Result<()> {
+    let cost = cost_usd(usage, state.config.analyzer.price(&usage.model));
+    repo::record(&state.db, user_id, meal_id, usage, cost).await
+}
+
+pub async fn budget_status(state: &AppState, user_id: Uuid) -> anyhow::Result<BudgetStatus> {
+    let custom = repo::find_budget(&state.db, user_id).await?;
+    Ok(BudgetStatus {
+        monthly_budget_usd: custom.or(state.config.analyzer.monthly_budget_usd),
+        month_cost_usd: repo::month_cost(&state.db, user_id).await?,
+    })
+}
+
+/// Sets the user's own budget, or with `None` returns them to the default.
+pub async fn set_budget(
+    state: &AppState,
+    user_id: Uuid,
+    budget: Option<f64>,
+) -> Result<BudgetResponse, BudgetError> {
+    if !repo::user_exists(&state.db, user_id).await? {
+        return Err(BudgetError::UserNotFound);
+    }
+    repo::set_budget(&state.db, user_id, budget).await?;
+    info!(user_id = %user_id, budget = ?budget, "AI budget set");
+    let status = budget_status(state, user_id).await?;
+    Ok(BudgetResponse {
+        user_id,
+        monthly_budget_usd: status.monthly_budget_usd,
+        custom: budget.is_some(),
+        month_cost_usd: status.month_cost_usd,
+        paused: status.exceeded(),
+    })
+}
+
+/// AI usage over the last `days` UTC days; the aggregates run concurrently.
+pub async fn usage_report(state: &AppState, days: i32) -> anyhow::Result<AiUsageReport> {
+    let (totals, models, mut users) = tokio::try_join!(
+        repo::totals(&state.db, days),
+        repo::by_model(&state.db, days),
+        repo::top_users(&state.db, days, TOP_USERS),
+    )?;
+    for user in &mut users {
+        let status = BudgetStatus {
+            monthly_budget_usd: user
+                .custom_budget_usd
+                .or(state.config.analyzer.monthly_budget_usd),
+            month_cost_usd: user.month_cost_usd,
+        };
+        user.monthly_budget_usd = status.monthly_budget_usd;
+        user.paused = status.exceeded();
+    }
+    Ok(AiUsageReport {
+        generated_at: OffsetDateTime::now_utc(),
+        days,
+        totals,
+        models,
+        users,
+    })
+}
@@ -0,0 +1,126 @@
+use anyhow::Context;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde_json::{json, Value};
+
+use crate::{
+    analysis::{
+        Analysis, MealInput, NutritionAnalyzer, NutritionEstimate, Screening, TokenUsage, PROMPT,
+        SCREEN_PROMPT, SCREEN_QUESTION,
+    },
+    config::AnalyzerConfig,
+};
+
+const DEFAULT_BASE_URL: &str = "https://api.anthropic.com";
+const DEFAULT_MODEL: &str = "claude-3-5-sonnet-latest";
+const API_VERSION: &str = "2023-06-01";
+const MAX_TOKENS: u32 = 1024;
+
+/// Messages API with base64 image blocks.
+pub struct AnthropicAnalyzer {
+    http: reqwest::Client,
+    api_key: String,
+    base_url: String,
+    model: String,
+    screening_model: String,
+}
+
+impl AnthropicAnalyzer {
+    pub fn new(http: reqwest::Client, api_key: String, config: &AnalyzerConfig) -> Self {
+        Self {
+            http,
+            api_key,
+            base_url: config
+                .base_url
+                .clone()
+                .unwrap_or_else(|| DEFAULT_BASE_URL.into()),
+            model: config.model.clone().unwrap_or_else(|| DEFAULT_MODEL.into()),
+            screening_model: config
+                .screening_model
+                .clone()
+                .or_else(|| config.model.clone())
+                .unwrap_or_else(|| DEFAULT_MODEL.into()),
+        }
+    }
+}
+
+impl AnthropicAnalyzer {
+    /// One message with `meal`'s photos; returns the reply text and the raw
+    /// response.
+    async fn complete(
+        &self,
+        model: &str,
+        system: &str,
+        text: &str,
+        meal: &MealInput,
+    ) -> anyhow::Result<(String, Value, Option<TokenUsage>)> {
+        let mut content: Vec<Value> = meal
+            .images
+            .iter()
+            .map(|image| {
+                json!({
+                    "type": "image",
+                    "source": {
+                        "type": "base64",
+                        "media_type": image.content_type,
+                        "data": STANDARD.encode(&image.bytes),
+                    },
+                })
+            })
+            .collect();
+        content.push(json!({"type": "text", "text": text}));
+        let body = json!({
+            "model": model,
+            "max_tokens": MAX_TOKENS,
+            "system": system,
+            "messages": [{"role": "user", "content": content}],
+        });
+        let raw: Value = self
+            .http
+            .post(format!(
+                "{}/v1/messages",
+                self.base_url.trim_end_matches('/')
+            ))
+            .header("x-api-key", &self.api_key)
+            .header("anthropic-version", API_VERSION)
+            .json(&body)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        let reply = raw["content"]
+            .as_array()
+            .and_then(|blocks| blocks.iter().find_map(|b| b["text"].as_str()))
+            .context("anthropic response has no text block")?
+            .to_string();
+        let usage = TokenUsage::from_raw(
+            "anthropic",
+            &raw,
+            model,
+            "/usage/input_tokens",
+            "/usage/output_tokens",
+        );
+        Ok((reply, raw, usage))
+    }
+}
+
+#[axum::async_trait]
+impl NutritionAnalyzer for AnthropicAnalyzer {
+    async fn analyze(&self, meal: &MealInput) -> anyhow::Result<Analysis> {
+        let (reply, raw, usage) = self
+            .complete(&self.model, PROMPT, &meal.user_text(), meal)
+            .await?;
+        Ok(Analysis {
+            estimate: NutritionEstimate::from_reply(&reply)?,
+            raw,
+            usage,
+        })
+    }
+
+    async fn screen(&self, meal: &MealInput) -> anyhow::Result<Screening> {
+        let (reply, _, usage) = self
+            .complete(&self.screening_model, SCREEN_PROMPT, SCREEN_QUESTION, meal)
+            .await?;
+        Screening::from_reply(&reply, usage)
+    }
+}
@@ -0,0 +1,54 @@
+use std::sync::Arc;
+
+use reqwest::StatusCode;
+
+use crate::{
+    analysis::{Analysis, MealInput, NutritionAnalyzer, Screening},
+    breaker::CircuitBreaker,
+    config::CircuitBreakerConfig,
+};
+
+/// Stops calling the provider while it is down, so analysis jobs fail and
+/// back off right away instead of each waiting for the request timeout.
+pub struct CircuitBreakingAnalyzer {
+    inner: Arc<dyn NutritionAnalyzer>,
+    breaker: CircuitBreaker,
+}
+
+impl CircuitBreakingAnalyzer {
+    pub fn new(inner: Arc<dyn NutritionAnalyzer>, config: &CircuitBreakerConfig) -> Self {
+        Self {
+            inner,
+            breaker: CircuitBreaker::new("analyzer", config),
+        }
+    }
+}
+
+/// Timeouts, refused connections, throttling and 5xx answers. A reply the
+/// model got wrong says nothing about the provider being up.
+fn is_outage(err: &anyhow::Error) -> bool {
+    err.chain()
+        .filter_map(|cause| cause.downcast_ref::<reqwest::Error>())
+        .any(|e| {
+            e.is_timeout()
+                || e.is_connect()
+                || e.status().is_some_and(|status| {
+                    status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS
+                })
+        })
+}
+
+#[axum::async_trait]
+impl NutritionAnalyzer for CircuitBreakingAnalyzer {
+    async fn analyze(&self, meal: &MealInput) -> anyhow::Result<Analysis> {
+        self.breaker
+            .call(is_outage, || self.inner.analyze(meal))
+            .await?
+    }
+
+    async fn screen(&self, meal: &MealInput) -> anyhow::Result<Screening> {
+        self.breaker
+            .call(is_outage, || self.inner.screen(meal))
+            .await?
+    }
+}
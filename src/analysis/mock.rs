@@ -0,0 +1,57 @@
+use crate::{
+    analysis::{Analysis, MealInput, NutritionAnalyzer, NutritionEstimate, Screening},
+    meals::dto::DetectedItem,
+};
+
+/// Returns the same estimate and screening verdict for every meal.
+#[derive(Debug, Clone)]
+pub struct MockAnalyzer {
+    pub estimate: NutritionEstimate,
+    pub is_food: bool,
+}
+
+impl Default for MockAnalyzer {
+    fn default() -> Self {
+        Self {
+            estimate: NutritionEstimate {
+                total_calories_kcal: Some(550.0),
+                protein_g: Some(30.0),
+                fat_g: Some(20.0),
+                carbs_g: Some(60.0),
+                sodium_mg: Some(800.0),
+                sugar_g: Some(8.0),
+                fiber_g: Some(7.0),
+                caffeine_mg: Some(0.0),
+                alcohol_g: Some(0.0),
+                micros: None,
+                description: Some("mock meal".into()),
+                title: Some("Mock meal".into()),
+                items: vec![DetectedItem {
+                    name: "mock food".into(),
+                    grams: Some(350.0),
+                }],
+                contains: Vec::new(),
+            },
+            is_food: true,
+        }
+    }
+}
+
+#[axum::async_trait]
+impl NutritionAnalyzer for MockAnalyzer {
+    async fn analyze(&self, _meal: &MealInput) -> anyhow::Result<Analysis> {
+        Ok(Analysis {
+            raw: serde_json::to_value(&self.estimate)?,
+            estimate: self.estimate.clone(),
+            usage: None,
+        })
+    }
+
+    async fn screen(&self, _meal: &MealInput) -> anyhow::Result<Screening> {
+        Ok(Screening {
+            is_food: self.is_food,
+            reason: (!self.is_food).then(|| "mock non-food".into()),
+            usage: None,
+        })
+    }
+}
@@ -0,0 +1,588 @@
+//! AI analysis of meal photos, run from the job queue. Providers sit behind
+//! [`NutritionAnalyzer`] the same way object stores sit behind
+//! `StorageClient`.
+
+use std::{sync::Arc, time::Duration};
+
+use futures::TryStreamExt;
+use serde::{Deserialize, Deserializer, Serialize};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::{
+    ai_usage::services as ai_usage_services,
+    config::{AnalyzerConfig, AnalyzerProvider},
+    db::AppState,
+    events::{repo as events_repo, DomainEvent},
+    flags::Flag,
+    foods::dto::FoodNutrition,
+    images::sniff::ImageFormat,
+    meals::{
+        dto::{DetectedItem, MealStatus, MAX_DETECTED_ITEMS, MAX_TITLE_LEN},
+        repo as meals_repo, services as meals_services,
+        warnings::{self, Ingredient},
+    },
+    photos::repo as photos_repo,
+    realtime::MealEvent,
+};
+
+pub mod anthropic;
+pub mod breaker;
+pub mod mock;
+pub mod ollama;
+pub mod openai;
+
+pub use breaker::CircuitBreakingAnalyzer;
+pub use mock::MockAnalyzer;
+
+/// Instructions shared by all LLM providers; the reply must be one JSON
+/// object matching [`NutritionEstimate`].
+pub const PROMPT: &str = "You are a nutrition assistant. Estimate the nutrition of the meal shown \
+in the photos (all photos show the same meal). Reply with a single JSON object and nothing else, \
+using these keys: total_calories_kcal, protein_g, fat_g, carbs_g, sodium_mg, sugar_g, fiber_g, \
+caffeine_mg, alcohol_g (numbers, 0 when the meal has none, or null if unknown), micros (object of micronutrient name to amount with unit, or null), \
+description (short text naming the foods you see), title (a short name for the meal, such as \
+\"Grilled chicken with rice and broccoli\") and items (array of the dish components you see, \
+each an object with name and grams, the estimated portion or null) and contains (array of what \
+the meal likely contains out of peanuts, tree_nuts, gluten, dairy, eggs, soy, fish, shellfish, \
+sesame and meat).";
+
+/// Instructions for [`NutritionAnalyzer::screen`]; the reply must be one
+/// JSON object matching [`Screening`].
+pub const SCREEN_PROMPT: &str = "You check photos before a nutrition analysis. Decide whether \
+they show food or drink someone is eating or about to eat. Reply with a single JSON object and \
+nothing else, using these keys: food (true or false) and reason (short text naming what the \
+photos show instead, such as \"screenshot\" or \"selfie\", or null when they show food).";
+
+/// User text sent with [`SCREEN_PROMPT`].
+pub const SCREEN_QUESTION: &str = "Do these photos show food or drink?";
+
+/// Image formats every provider accepts.
+const SUPPORTED_FORMATS: [ImageFormat; 3] =
+    [ImageFormat::Jpeg, ImageFormat::Png, ImageFormat::Webp];
+
+#[derive(Debug, Clone)]
+pub struct MealImage {
+    pub content_type: String,
+    pub bytes: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct MealInput {
+    pub title: Option<String>,
+    pub notes: Option<String>,
+    pub images: Vec<MealImage>,
+}
+
+impl MealInput {
+    /// User text appended to [`PROMPT`], so titles like "no sauce" count.
+    pub fn user_text(&self) -> String {
+        let mut text = String::from("Estimate the nutrition of this meal.");
+        if let Some(title) = &self.title {
+            text.push_str(&format!("\nTitle: {}", title));
+        }
+        if let Some(notes) = &self.notes {
+            text.push_str(&format!("\nNotes: {}", notes));
+        }
+        text
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NutritionEstimate {
+    pub total_calories_kcal: Option<f64>,
+    pub protein_g: Option<f64>,
+    pub fat_g: Option<f64>,
+    pub carbs_g: Option<f64>,
+    pub sodium_mg: Option<f64>,
+    pub sugar_g: Option<f64>,
+    pub fiber_g: Option<f64>,
+    pub caffeine_mg: Option<f64>,
+    pub alcohol_g: Option<f64>,
+    pub micros: Option<serde_json::Value>,
+    pub description: Option<String>,
+    /// Suggested name for meals logged without a title.
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub items: Vec<DetectedItem>,
+    /// Allergens and animal products the analyzer saw.
+    #[serde(default, deserialize_with = "known_ingredients")]
+    pub contains: Vec<Ingredient>,
+}
+
+/// Drops names outside [`Ingredient`] instead of failing the whole reply.
+fn known_ingredients<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<Ingredient>, D::Error> {
+    let names = Option::<Vec<serde_json::Value>>::deserialize(d)?.unwrap_or_default();
+    Ok(names
+        .into_iter()
+        .filter_map(|name| serde_json::from_value(name).ok())
+        .collect())
+}
+
+impl From<&NutritionEstimate> for FoodNutrition {
+    fn from(e: &NutritionEstimate) -> Self {
+        Self {
+            total_calories_kcal: e.total_calories_kcal,
+            protein_g: e.protein_g,
+            fat_g: e.fat_g,
+            carbs_g: e.carbs_g,
+            sodium_mg: e.sodium_mg,
+            sugar_g: e.sugar_g,
+            fiber_g: e.fiber_g,
+        }
+    }
+}
+
+impl NutritionEstimate {
+    /// Parses a model reply, tolerating Markdown code fences around the JSON
+    /// and dropping negative or non-finite numbers, blank names and overlong
+    /// titles.
+    pub fn from_reply(reply: &str) -> anyhow::Result<Self> {
+        let mut estimate: Self = serde_json::from_str(json_object(reply)?)?;
+        for value in [
+            &mut estimate.total_calories_kcal,
+            &mut estimate.protein_g,
+            &mut estimate.fat_g,
+            &mut estimate.carbs_g,
+            &mut estimate.sodium_mg,
+            &mut estimate.sugar_g,
+            &mut estimate.fiber_g,
+            &mut estimate.caffeine_mg,
+            &mut estimate.alcohol_g,
+        ] {
+            *value = value.filter(|v| v.is_finite() && *v >= 0.0);
+        }
+        estimate.title = estimate
+            .title
+            .map(|t| t.trim().chars().take(MAX_TITLE_LEN).collect::<String>())
+            .filter(|t| !t.is_empty());
+        estimate.items.retain(|item| !item.name.trim().is_empty());
+        estimate.items.truncate(MAX_DETECTED_ITEMS);
+        for item in &mut estimate.items {
+            item.name = item.name.trim().to_string();
+            item.grams = item.grams.filter(|g| g.is_finite() && *g >= 0.0);
+        }
+        Ok(estimate)
+    }
+}
+
+/// The JSON object in a model reply, without Markdown code fences or other
+/// text around it.
+fn json_object(reply: &str) -> anyhow::Result<&str> {
+    let trimmed = reply.trim();
+    match (trimmed.find('{'), trimmed.rfind('}')) {
+        (Some(start), Some(end)) if start < end => Ok(&trimmed[start..=end]),
+        _ => anyhow::bail!("analyzer reply contains no JSON object"),
+    }
+}
+
+/// Verdict of the cheap check run before the full analysis, so screenshots
+/// and selfies don't get nutrition made up for them.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Screening {
+    #[serde(rename = "food")]
+    pub is_food: bool,
+    /// What the photos show instead, e.g. "screenshot".
+    #[serde(default)]
+    pub reason: Option<String>,
+    #[serde(skip)]
+    pub usage: Option<TokenUsage>,
+}
+
+impl Screening {
+    /// Lets everything through, for providers without a screening call.
+    pub fn food() -> Self {
+        Self {
+            is_food: true,
+            reason: None,
+            usage: None,
+        }
+    }
+
+    /// Parses a model reply to [`SCREEN_PROMPT`].
+    pub fn from_reply(reply: &str, usage: Option<TokenUsage>) -> anyhow::Result<Self> {
+        let mut screening: Self = serde_json::from_str(json_object(reply)?)?;
+        screening.reason = screening
+            .reason
+            .map(|r| r.trim().to_string())
+            .filter(|r| !r.is_empty());
+        screening.usage = usage;
+        Ok(screening)
+    }
+}
+
+/// Tokens one analysis call was billed for.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokenUsage {
+    pub provider: &'static str,
+    /// As reported by the provider, e.g. `gpt-4o-mini-2024-07-18`.
+    pub model: String,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+}
+
+impl TokenUsage {
+    /// Reads the token counts at `input` and `output` of a raw response;
+    /// `None` when the provider didn't report them.
+    fn from_raw(
+        provider: &'static str,
+        raw: &serde_json::Value,
+        default_model: &str,
+        input: &str,
+        output: &str,
+    ) -> Option<Self> {
+        let input_tokens = raw.pointer(input)?.as_i64()?;
+        let output_tokens = raw.pointer(output).and_then(|v| v.as_i64()).unwrap_or(0);
+        Some(Self {
+            provider,
+            model: raw["model"].as_str().unwrap_or(default_model).to_string(),
+            input_tokens,
+            output_tokens,
+        })
+    }
+}
+
+/// Result of one analysis: the parsed estimate plus the provider's raw
+/// response, kept for debugging in `meal_nutrition.ai_raw`.
+#[derive(Debug, Clone)]
+pub struct Analysis {
+    pub estimate: NutritionEstimate,
+    pub raw: serde_json::Value,
+    /// `None` for providers that don't bill by token.
+    pub usage: Option<TokenUsage>,
+}
+
+#[axum::async_trait]
+pub trait NutritionAnalyzer: Send + Sync {
+    async fn analyze(&self, meal: &MealInput) -> anyhow::Result<Analysis>;
+
+    /// Checks that the photos show food at all, with [`SCREEN_PROMPT`] and
+    /// the cheaper `ANALYZER_SCREENING_MODEL` where the provider has one.
+    async fn screen(&self, _meal: &MealInput) -> anyhow::Result<Screening> {
+        Ok(Screening::food())
+    }
+}
+
+pub fn from_config(config: &AnalyzerConfig) -> anyhow::Result<Option<Arc<dyn NutritionAnalyzer>>> {
+    let timeout = Duration::from_secs(config.timeout_secs);
+    let http = || reqwest::Client::builder().timeout(timeout).build();
+    let api_key = || {
+        config.api_key.clone().ok_or_else(|| {
+            anyhow::anyhow!("ANALYZER_API_KEY is required for {:?}", config.provider)
+        })
+    };
+    let analyzer: Arc<dyn NutritionAnalyzer> = match config.provider {
+        AnalyzerProvider::None => {
+            warn!("no nutrition analyzer configured; meals will not be analyzed");
+            return Ok(None);
+        }
+        AnalyzerProvider::Mock => Arc::new(MockAnalyzer::default()),
+        AnalyzerProvider::OpenAi => {
+            Arc::new(openai::OpenAiAnalyzer::new(http()?, api_key()?, config))
+        }
+        AnalyzerProvider::Anthropic => Arc::new(anthropic::AnthropicAnalyzer::new(
+            http()?,
+            api_key()?,
+            config,
+        )),
+        AnalyzerProvider::Ollama => Arc::new(ollama::OllamaAnalyzer::new(http()?, config)),
+    };
+    info!(provider = ?config.provider, "nutrition analyzer configured");
+    Ok(Some(Arc::new(CircuitBreakingAnalyzer::new(
+        analyzer,
+        &config.breaker,
+    ))))
+}
+
+/// Stores the new status and pushes it to the owner's live connections.
+/// Returns the owner, or `None` if the meal no longer exists.
+async fn set_status(
+    state: &AppState,
+    meal_id: Uuid,
+    status: MealStatus,
+) -> anyhow::Result<Option<Uuid>> {
+    let user_id = meals_repo::set_status(&state.db, meal_id, status).await?;
+    if let Some(user_id) = user_id {
+        state
+            .events
+            .publish(user_id, MealEvent::MealStatus { meal_id, status });
+    }
+    Ok(user_id)
+}
+
+/// Loads the photos of `meal_id` that every provider can read, skipping any
+/// over `UPLOAD_MAX_IMAGE_BYTES` (large chunked uploads). Also returns how
+/// many photos were skipped.
+async fn load_images(state: &AppState, meal_id: Uuid) -> anyhow::Result<(Vec<MealImage>, usize)> {
+    let photos = photos_repo::list_for_meals(&state.db, &[meal_id]).await?;
+    let max_bytes = state.config.uploads.max_image_bytes as i64;
+    let mut images = Vec::with_capacity(photos.len());
+    let mut skipped = 0;
+    for photo in photos {
+        let content_type = photo.content_type.unwrap_or_default();
+        if !SUPPORTED_FORMATS
+            .iter()
+            .any(|f| f.matches_content_type(&content_type))
+        {
+            skipped += 1;
+            continue;
+        }
+        if photo.size_bytes.is_some_and(|size| size > max_bytes) {
+            warn!(photo_id = %photo.id, size_bytes = ?photo.size_bytes, "photo too large to analyze");
+            skipped += 1;
+            continue;
+        }
+        let Some(object) = state.storage.get_object_stream(&photo.s3_key).await? else {
+            warn!(photo_id = %photo.id, "photo object missing; skipping in analysis");
+            skipped += 1;
+            continue;
+        };
+        let chunks: Vec<_> = object.body.try_collect().await?;
+        images.push(MealImage {
+            content_type,
+            bytes: chunks.concat(),
+        });
+    }
+    Ok((images, skipped))
+}
+
+/// Records what a call cost. The call is paid for either way; failing the
+/// job would pay twice.
+async fn record_usage(state: &AppState, user_id: Uuid, meal_id: Uuid, usage: &TokenUsage) {
+    if let Err(e) = ai_usage_services::record_usage(state, user_id, meal_id, usage).await {
+        warn!(meal_id = %meal_id, error = %e, "recording AI usage failed");
+    }
+}
+
+/// Runs the screening when `ANALYZER_SCREENING` is on; `false` when the
+/// photos don't show food. A failed screening lets the analysis go ahead.
+async fn passes_screening(
+    state: &AppState,
+    analyzer: &dyn NutritionAnalyzer,
+    user_id: Uuid,
+    meal_id: Uuid,
+    input: &MealInput,
+) -> bool {
+    if !state.config.analyzer.screening {
+        return true;
+    }
+    let screening = match analyzer.screen(input).await {
+        Ok(screening) => screening,
+        Err(e) => {
+            warn!(meal_id = %meal_id, error = %e, "screening failed; analyzing anyway");
+            return true;
+        }
+    };
+    if let Some(usage) = &screening.usage {
+        record_usage(state, user_id, meal_id, usage).await;
+    }
+    if !screening.is_food {
+        info!(meal_id = %meal_id, reason = ?screening.reason, "photos show no food");
+    }
+    screening.is_food
+}
+
+/// Analyzes the photos of `meal_id` and stores the nutrition estimate. Meals
+/// deleted before the job ran are skipped rather than treated as failures,
+/// and meals whose photos fail the screening are left `needs_review` unless
+/// `skip_screening` is set.
+pub async fn analyze_meal(
+    state: &AppState,
+    meal_id: Uuid,
+    skip_screening: bool,
+) -> anyhow::Result<()> {
+    let Some(user_id) = set_status(state, meal_id, MealStatus::Analyzing).await? else {
+        info!(meal_id = %meal_id, "meal deleted before analysis");
+        return Ok(());
+    };
+    let analyzer = state
+        .analyzer
+        .as_ref()
+        .filter(|_| state.flags.is_enabled(Flag::AiAnalysis, user_id));
+    let Some(analyzer) = analyzer else {
+        set_status(state, meal_id, MealStatus::Done).await?;
+        return Ok(());
+    };
+    if ai_usage_services::budget_status(state, user_id)
+        .await?
+        .exceeded()
+    {
+        info!(meal_id = %meal_id, user_id = %user_id, "monthly AI budget reached; analysis skipped");
+        set_status(state, meal_id, MealStatus::Done).await?;
+        return Ok(());
+    }
+    let (images, skipped) = load_images(state, meal_id).await?;
+    if images.is_empty() {
+        // Photos that can't be analyzed fail the meal rather than leave it
+        // done without nutrition.
+        let status = if skipped > 0 {
+            warn!(meal_id = %meal_id, skipped, "no photo can be analyzed");
+            MealStatus::Failed
+        } else {
+            info!(meal_id = %meal_id, "no photos to analyze");
+            MealStatus::Done
+        };
+        set_status(state, meal_id, status).await?;
+        return Ok(());
+    }
+    let meal = meals_repo::find_meal(&state.db, user_id, meal_id).await?;
+    let input = MealInput {
+        title: meal.as_ref().and_then(|m| m.title.clone()),
+        notes: meal.and_then(|m| m.notes),
+        images,
+    };
+    if !skip_screening
+        && !passes_screening(state, analyzer.as_ref(), user_id, meal_id, &input).await
+    {
+        set_status(state, meal_id, MealStatus::NeedsReview).await?;
+        return Ok(());
+    }
+
+    let analysis = analyzer.analyze(&input).await?;
+    if let Some(usage) = &analysis.usage {
+        record_usage(state, user_id, meal_id, usage).await;
+    }
+    let mut tx = state.db.begin().await?;
+    let stored =
+        meals_repo::upsert_nutrition(&mut *tx, meal_id, &analysis.estimate, &analysis.raw).await?;
+    let estimate = &analysis.estimate;
+    let contains = warnings::detect(
+        &estimate.contains,
+        &estimate.items,
+        estimate.description.as_deref(),
+    );
+    meals_repo::set_detected(
+        &mut *tx,
+        meal_id,
+        estimate.title.as_deref(),
+        &estimate.items,
+        &contains,
+    )
+    .await?;
+    if stored {
+        let ready = DomainEvent::NutritionReady { meal_id };
+        events_repo::record(&mut *tx, user_id, &ready).await?;
+    }
+    tx.commit().await?;
+    if stored {
+        meals_services::refresh_nutrition(state, user_id, meal_id).await?;
+        state
+            .events
+            .publish(user_id, MealEvent::NutritionUpdated { meal_id });
+        info!(
+            meal_id = %meal_id,
+            calories = ?analysis.estimate.total_calories_kcal,
+            "meal analyzed"
+        );
+    } else {
+        info!(meal_id = %meal_id, "manual nutrition kept; analysis discarded");
+    }
+    set_status(state, meal_id, MealStatus::Done).await?;
+    Ok(())
+}
+
+/// Reflects a failed analysis attempt on the meal: back to `pending` while
+/// the job will be retried, `failed` once it gave up.
+pub async fn record_failure(state: &AppState, meal_id: Uuid, retrying: bool) -> anyhow::Result<()> {
+    let status = if retrying {
+        MealStatus::Pending
+    } else {
+        MealStatus::Failed
+    };
+    set_status(state, meal_id, status).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_fenced_reply_and_drops_bad_numbers() {
+        let reply =
+            "```json\n{\"total_calories_kcal\": 640.5, \"protein_g\": -3, \"fat_g\": null, \
+                     \"carbs_g\": 80, \"description\": \"pasta\"}\n```";
+        let estimate = NutritionEstimate::from_reply(reply).unwrap();
+        assert_eq!(estimate.total_calories_kcal, Some(640.5));
+        assert_eq!(estimate.protein_g, None);
+        assert_eq!(estimate.carbs_g, Some(80.0));
+        assert_eq!(estimate.description.as_deref(), Some("pasta"));
+        assert_eq!(estimate.title, None);
+        assert!(estimate.items.is_empty());
+    }
+
+    #[test]
+    fn cleans_title_and_items() {
+        let reply = format!(
+            "{{\"title\": \"  {}  \", \"items\": [{{\"name\": \" rice \", \"grams\": 150}}, \
+             {{\"name\": \" \"}}, {{\"name\": \"broccoli\", \"grams\": -5}}]}}",
+            "x".repeat(MAX_TITLE_LEN + 10)
+        );
+        let estimate = NutritionEstimate::from_reply(&reply).unwrap();
+        assert_eq!(
+            estimate.title.map(|t| t.chars().count()),
+            Some(MAX_TITLE_LEN)
+        );
+        let names: Vec<_> = estimate.items.iter().map(|i| i.name.as_str()).collect();
+        assert_eq!(names, ["rice", "broccoli"]);
+        assert_eq!(estimate.items[0].grams, Some(150.0));
+        assert_eq!(estimate.items[1].grams, None);
+    }
+
+    #[test]
+    fn ignores_unknown_ingredients() {
+        let reply = r#"{"contains": ["gluten", "msg", "meat"]}"#;
+        let estimate = NutritionEstimate::from_reply(reply).unwrap();
+        assert_eq!(estimate.contains, [Ingredient::Gluten, Ingredient::Meat]);
+        let estimate = NutritionEstimate::from_reply(r#"{"contains": null}"#).unwrap();
+        assert!(estimate.contains.is_empty());
+    }
+
+    #[test]
+    fn reads_token_usage_from_raw_response() {
+        let raw = serde_json::json!({
+            "model": "gpt-4o-mini-2024-07-18",
+            "usage": {"prompt_tokens": 1200, "completion_tokens": 90}
+        });
+        let usage = TokenUsage::from_raw(
+            "openai",
+            &raw,
+            "gpt-4o-mini",
+            "/usage/prompt_tokens",
+            "/usage/completion_tokens",
+        )
+        .unwrap();
+        assert_eq!(usage.model, "gpt-4o-mini-2024-07-18");
+        assert_eq!((usage.input_tokens, usage.output_tokens), (1200, 90));
+        assert!(TokenUsage::from_raw("ollama", &raw, "llava", "/eval", "/x").is_none());
+    }
+
+    #[test]
+    fn parses_screening_reply() {
+        let screening = Screening::from_reply(
+            "```json\n{\"food\": false, \"reason\": \" screenshot \"}\n```",
+            None,
+        )
+        .unwrap();
+        assert!(!screening.is_food);
+        assert_eq!(screening.reason.as_deref(), Some("screenshot"));
+        let screening = Screening::from_reply(r#"{"food": true, "reason": ""}"#, None).unwrap();
+        assert_eq!(screening, Screening::food());
+        assert!(Screening::from_reply(r#"{"reason": "cat"}"#, None).is_err());
+    }
+
+    #[test]
+    fn rejects_reply_without_json() {
+        assert!(NutritionEstimate::from_reply("I can't see any food.").is_err());
+    }
+
+    #[tokio::test]
+    async fn mock_analyzer_returns_its_estimate() {
+        let analysis = MockAnalyzer::default()
+            .analyze(&MealInput::default())
+            .await
+            .unwrap();
+        assert!(analysis.estimate.total_calories_kcal.is_some());
+    }
+}
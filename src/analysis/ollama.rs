@@ -0,0 +1,104 @@
+use anyhow::Context;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde_json::{json, Value};
+
+use crate::{
+    analysis::{
+        Analysis, MealInput, NutritionAnalyzer, NutritionEstimate, Screening, TokenUsage, PROMPT,
+        SCREEN_PROMPT, SCREEN_QUESTION,
+    },
+    config::AnalyzerConfig,
+};
+
+const DEFAULT_BASE_URL: &str = "http://localhost:11434";
+const DEFAULT_MODEL: &str = "llava";
+
+/// Ollama `/api/chat` with a vision model, forced to JSON output.
+pub struct OllamaAnalyzer {
+    http: reqwest::Client,
+    base_url: String,
+    model: String,
+    screening_model: String,
+}
+
+impl OllamaAnalyzer {
+    pub fn new(http: reqwest::Client, config: &AnalyzerConfig) -> Self {
+        Self {
+            http,
+            base_url: config
+                .base_url
+                .clone()
+                .unwrap_or_else(|| DEFAULT_BASE_URL.into()),
+            model: config.model.clone().unwrap_or_else(|| DEFAULT_MODEL.into()),
+            screening_model: config
+                .screening_model
+                .clone()
+                .or_else(|| config.model.clone())
+                .unwrap_or_else(|| DEFAULT_MODEL.into()),
+        }
+    }
+}
+
+impl OllamaAnalyzer {
+    /// One chat turn with `meal`'s photos; returns the reply text and the
+    /// raw response.
+    async fn complete(
+        &self,
+        model: &str,
+        system: &str,
+        text: &str,
+        meal: &MealInput,
+    ) -> anyhow::Result<(String, Value, Option<TokenUsage>)> {
+        let images: Vec<String> = meal
+            .images
+            .iter()
+            .map(|image| STANDARD.encode(&image.bytes))
+            .collect();
+        let body = json!({
+            "model": model,
+            "stream": false,
+            "format": "json",
+            "messages": [
+                {"role": "system", "content": system},
+                {"role": "user", "content": text, "images": images},
+            ],
+        });
+        let raw: Value = self
+            .http
+            .post(format!("{}/api/chat", self.base_url.trim_end_matches('/')))
+            .json(&body)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        let reply = raw["message"]["content"]
+            .as_str()
+            .context("ollama response has no message content")?
+            .to_string();
+        let usage =
+            TokenUsage::from_raw("ollama", &raw, model, "/prompt_eval_count", "/eval_count");
+        Ok((reply, raw, usage))
+    }
+}
+
+#[axum::async_trait]
+impl NutritionAnalyzer for OllamaAnalyzer {
+    async fn analyze(&self, meal: &MealInput) -> anyhow::Result<Analysis> {
+        let (reply, raw, usage) = self
+            .complete(&self.model, PROMPT, &meal.user_text(), meal)
+            .await?;
+        Ok(Analysis {
+            estimate: NutritionEstimate::from_reply(&reply)?,
+            raw,
+            usage,
+        })
+    }
+
+    async fn screen(&self, meal: &MealInput) -> anyhow::Result<Screening> {
+        let (reply, _, usage) = self
+            .complete(&self.screening_model, SCREEN_PROMPT, SCREEN_QUESTION, meal)
+            .await?;
+        Screening::from_reply(&reply, usage)
+    }
+}
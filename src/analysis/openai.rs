@@ -0,0 +1,127 @@
+use anyhow::Context;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde_json::{json, Value};
+
+use crate::{
+    analysis::{
+        Analysis, MealInput, NutritionAnalyzer, NutritionEstimate, Screening, TokenUsage, PROMPT,
+        SCREEN_PROMPT, SCREEN_QUESTION,
+    },
+    config::AnalyzerConfig,
+};
+
+const DEFAULT_BASE_URL: &str = "https://api.openai.com";
+const DEFAULT_MODEL: &str = "gpt-4o-mini";
+
+/// Chat Completions API with images inlined as data URLs.
+pub struct OpenAiAnalyzer {
+    http: reqwest::Client,
+    api_key: String,
+    base_url: String,
+    model: String,
+    screening_model: String,
+}
+
+impl OpenAiAnalyzer {
+    pub fn new(http: reqwest::Client, api_key: String, config: &AnalyzerConfig) -> Self {
+        Self {
+            http,
+            api_key,
+            base_url: config
+                .base_url
+                .clone()
+                .unwrap_or_else(|| DEFAULT_BASE_URL.into()),
+            model: config.model.clone().unwrap_or_else(|| DEFAULT_MODEL.into()),
+            screening_model: config
+                .screening_model
+                .clone()
+                .or_else(|| config.model.clone())
+                .unwrap_or_else(|| DEFAULT_MODEL.into()),
+        }
+    }
+}
+
+impl OpenAiAnalyzer {
+    /// One chat completion with `meal`'s photos; returns the reply text and
+    /// the raw response. `detail` is the image resolution to bill for.
+    async fn complete(
+        &self,
+        model: &str,
+        system: &str,
+        text: &str,
+        meal: &MealInput,
+        detail: &str,
+    ) -> anyhow::Result<(String, Value, Option<TokenUsage>)> {
+        let mut content = vec![json!({"type": "text", "text": text})];
+        content.extend(meal.images.iter().map(|image| {
+            json!({
+                "type": "image_url",
+                "image_url": {
+                    "url": format!("data:{};base64,{}", image.content_type, STANDARD.encode(&image.bytes)),
+                    "detail": detail,
+                },
+            })
+        }));
+        let body = json!({
+            "model": model,
+            "response_format": {"type": "json_object"},
+            "messages": [
+                {"role": "system", "content": system},
+                {"role": "user", "content": content},
+            ],
+        });
+        let raw: Value = self
+            .http
+            .post(format!(
+                "{}/v1/chat/completions",
+                self.base_url.trim_end_matches('/')
+            ))
+            .bearer_auth(&self.api_key)
+            .json(&body)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        let reply = raw["choices"][0]["message"]["content"]
+            .as_str()
+            .context("openai response has no message content")?
+            .to_string();
+        let usage = TokenUsage::from_raw(
+            "openai",
+            &raw,
+            model,
+            "/usage/prompt_tokens",
+            "/usage/completion_tokens",
+        );
+        Ok((reply, raw, usage))
+    }
+}
+
+#[axum::async_trait]
+impl NutritionAnalyzer for OpenAiAnalyzer {
+    async fn analyze(&self, meal: &MealInput) -> anyhow::Result<Analysis> {
+        let (reply, raw, usage) = self
+            .complete(&self.model, PROMPT, &meal.user_text(), meal, "auto")
+            .await?;
+        Ok(Analysis {
+            estimate: NutritionEstimate::from_reply(&reply)?,
+            raw,
+            usage,
+        })
+    }
+
+    async fn screen(&self, meal: &MealInput) -> anyhow::Result<Screening> {
+        // Low detail bills a fixed, small number of tokens per image.
+        let (reply, _, usage) = self
+            .complete(
+                &self.screening_model,
+                SCREEN_PROMPT,
+                SCREEN_QUESTION,
+                meal,
+                "low",
+            )
+            .await?;
+        Screening::from_reply(&reply, usage)
+    }
+}
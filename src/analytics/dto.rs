@@ -0,0 +1,221 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use time::{Duration, OffsetDateTime};
+use uuid::Uuid;
+
+pub const MAX_BATCH_EVENTS: usize = 100;
+pub const MAX_NAME_LEN: usize = 100;
+pub const MAX_SESSION_ID_LEN: usize = 64;
+pub const MAX_APP_VERSION_LEN: usize = 32;
+/// Serialized size limit of one event's `properties`.
+pub const MAX_PROPERTIES_BYTES: usize = 2048;
+/// Apps queue events while offline; older ones are dropped.
+pub const MAX_EVENT_AGE_DAYS: i64 = 30;
+
+/// What a user shares of their product analytics.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+pub enum AnalyticsSharing {
+    #[default]
+    Full,
+    /// Stored without the account or session, so events can't be tied to
+    /// the user or to each other.
+    Anonymous,
+    /// Events are accepted and discarded.
+    Off,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    ScreenView,
+    FeatureUse,
+}
+
+impl EventKind {
+    /// Matches the serialized form.
+    pub fn name(self) -> &'static str {
+        match self {
+            EventKind::ScreenView => "screen_view",
+            EventKind::FeatureUse => "feature_use",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AnalyticsEventInput {
+    pub kind: EventKind,
+    /// The screen or feature, e.g. `meal_detail` or `barcode_scan`.
+    pub name: String,
+    #[serde(default)]
+    pub properties: Map<String, Value>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub occurred_at: OffsetDateTime,
+    pub session_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AnalyticsBatchRequest {
+    pub app_version: Option<String>,
+    pub events: Vec<AnalyticsEventInput>,
+}
+
+/// An event as stored or forwarded to the sink.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AnalyticsEvent {
+    pub user_id: Option<Uuid>,
+    pub kind: EventKind,
+    pub name: String,
+    pub properties: Value,
+    pub session_id: Option<String>,
+    pub app_version: Option<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub occurred_at: OffsetDateTime,
+}
+
+impl AnalyticsEvent {
+    pub fn anonymize(self) -> Self {
+        Self {
+            user_id: None,
+            session_id: None,
+            ..self
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct AnalyticsBatchResponse {
+    pub received: usize,
+    /// Less than `received` when events were too old or analytics is off.
+    pub recorded: usize,
+}
+
+fn valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= MAX_NAME_LEN
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | ':' | '/' | '-'))
+}
+
+fn trimmed(value: Option<String>, field: &str, max_len: usize) -> Result<Option<String>, String> {
+    let value = value
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty());
+    if value.as_ref().is_some_and(|v| v.chars().count() > max_len) {
+        return Err(format!("{} must be at most {} characters", field, max_len));
+    }
+    Ok(value)
+}
+
+impl AnalyticsBatchRequest {
+    /// Checks every event, failing with the index of the first invalid one.
+    /// Events older than [`MAX_EVENT_AGE_DAYS`] or more than five minutes in
+    /// the future are left out rather than failing the batch, so an app
+    /// with a wrong clock doesn't retry it forever.
+    pub fn normalized(
+        self,
+        user_id: Uuid,
+        now: OffsetDateTime,
+    ) -> Result<Vec<AnalyticsEvent>, String> {
+        if self.events.is_empty() {
+            return Err("events must not be empty".into());
+        }
+        if self.events.len() > MAX_BATCH_EVENTS {
+            return Err(format!(
+                "at most {} events can be sent at once",
+                MAX_BATCH_EVENTS
+            ));
+        }
+        let app_version = trimmed(self.app_version, "app_version", MAX_APP_VERSION_LEN)?;
+        let oldest = now - Duration::days(MAX_EVENT_AGE_DAYS);
+        let latest = now + Duration::minutes(5);
+        let mut events = Vec::with_capacity(self.events.len());
+        for (i, event) in self.events.into_iter().enumerate() {
+            let name = event.name.trim();
+            if !valid_name(name) {
+                return Err(format!(
+                    "events[{}].name must be 1-{} letters, digits or _.:/-",
+                    i, MAX_NAME_LEN
+                ));
+            }
+            let properties = Value::Object(event.properties);
+            if properties.to_string().len() > MAX_PROPERTIES_BYTES {
+                return Err(format!(
+                    "events[{}].properties must be at most {} bytes",
+                    i, MAX_PROPERTIES_BYTES
+                ));
+            }
+            let session_id = trimmed(
+                event.session_id,
+                &format!("events[{}].session_id", i),
+                MAX_SESSION_ID_LEN,
+            )?;
+            if event.occurred_at < oldest || event.occurred_at > latest {
+                continue;
+            }
+            events.push(AnalyticsEvent {
+                user_id: Some(user_id),
+                kind: event.kind,
+                name: name.to_string(),
+                properties,
+                session_id,
+                app_version: app_version.clone(),
+                occurred_at: event.occurred_at,
+            });
+        }
+        Ok(events)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::datetime;
+
+    const NOW: OffsetDateTime = datetime!(2024-06-01 12:00 UTC);
+
+    fn batch(events: Value) -> AnalyticsBatchRequest {
+        serde_json::from_value(serde_json::json!({"app_version": "2.3.0", "events": events}))
+            .unwrap()
+    }
+
+    #[test]
+    fn stale_events_are_dropped() {
+        let events = batch(serde_json::json!([
+            {"kind": "screen_view", "name": "meal_detail", "occurred_at": "2024-06-01T11:59:00Z", "session_id": "s1"},
+            {"kind": "feature_use", "name": "barcode_scan", "properties": {"found": true}, "occurred_at": "2024-04-01T12:00:00Z"},
+            {"kind": "feature_use", "name": "barcode_scan", "occurred_at": "2024-06-02T12:00:00Z"},
+        ]))
+        .normalized(Uuid::nil(), NOW)
+        .unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].app_version.as_deref(), Some("2.3.0"));
+        assert_eq!(events[0].properties, serde_json::json!({}));
+
+        let anonymous = events[0].clone().anonymize();
+        assert_eq!((anonymous.user_id, anonymous.session_id), (None, None));
+    }
+
+    #[test]
+    fn invalid_events_fail_the_batch() {
+        let err = batch(serde_json::json!([
+            {"kind": "screen_view", "name": "home", "occurred_at": "2024-06-01T11:59:00Z"},
+            {"kind": "screen_view", "name": "meal detail", "occurred_at": "2024-06-01T11:59:00Z"},
+        ]))
+        .normalized(Uuid::nil(), NOW)
+        .unwrap_err();
+        assert!(err.starts_with("events[1].name"), "{}", err);
+
+        let big = "x".repeat(MAX_PROPERTIES_BYTES);
+        assert!(batch(serde_json::json!([
+            {"kind": "screen_view", "name": "home", "properties": {"note": big}, "occurred_at": "2024-06-01T11:59:00Z"},
+        ]))
+        .normalized(Uuid::nil(), NOW)
+        .is_err());
+        assert!(batch(serde_json::json!([]))
+            .normalized(Uuid::nil(), NOW)
+            .is_err());
+    }
+}
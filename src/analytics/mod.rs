@@ -0,0 +1,42 @@
+//! Product analytics from the apps: `POST /analytics/events` takes batches
+//! of screen views and feature usage. Events go to the monthly partitions of
+//! `analytics_events`, or to `ANALYTICS_SINK_URL` instead when set, honoring
+//! each user's `analytics` preference.
+
+use std::{sync::Arc, time::Duration};
+
+use crate::{analytics::dto::AnalyticsEvent, config::AnalyticsConfig};
+
+pub mod dto;
+pub mod repo;
+pub mod services;
+
+/// External endpoint receiving each batch as `{"events": [..]}`. Any non-2xx
+/// answer fails the request so the app retries the batch.
+pub struct AnalyticsSink {
+    http: reqwest::Client,
+    url: String,
+}
+
+impl AnalyticsSink {
+    /// The configured sink, or `None` to store events in the database.
+    pub fn from_config(config: &AnalyticsConfig) -> anyhow::Result<Option<Arc<Self>>> {
+        let Some(url) = config.sink_url.clone() else {
+            return Ok(None);
+        };
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(config.sink_timeout_secs))
+            .build()?;
+        Ok(Some(Arc::new(Self { http, url })))
+    }
+
+    pub async fn send(&self, events: &[AnalyticsEvent]) -> anyhow::Result<()> {
+        self.http
+            .post(&self.url)
+            .json(&serde_json::json!({ "events": events }))
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,127 @@
+use serde_json::Value;
+use sqlx::PgPool;
+use time::{Date, Month, OffsetDateTime};
+use uuid::Uuid;
+
+use crate::analytics::dto::AnalyticsEvent;
+
+pub async fn insert_events(db: &PgPool, events: &[AnalyticsEvent]) -> anyhow::Result<u64> {
+    let user_ids: Vec<Option<Uuid>> = events.iter().map(|e| e.user_id).collect();
+    let kinds: Vec<&str> = events.iter().map(|e| e.kind.name()).collect();
+    let names: Vec<&str> = events.iter().map(|e| e.name.as_str()).collect();
+    let properties: Vec<Value> = events.iter().map(|e| e.properties.clone()).collect();
+    let session_ids: Vec<Option<&str>> = events.iter().map(|e| e.session_id.as_deref()).collect();
+    let app_versions: Vec<Option<&str>> = events.iter().map(|e| e.app_version.as_deref()).collect();
+    let occurred: Vec<OffsetDateTime> = events.iter().map(|e| e.occurred_at).collect();
+    let result = sqlx::query(
+        r#"
+        INSERT INTO analytics_events
+            (user_id, kind, name, properties, session_id, app_version, occurred_at)
+        SELECT * FROM UNNEST($1::uuid[], $2::text[], $3::text[], $4::jsonb[], $5::text[],
+                             $6::text[], $7::timestamptz[])
+        "#,
+    )
+    .bind(&user_ids)
+    .bind(&kinds)
+    .bind(&names)
+    .bind(&properties)
+    .bind(&session_ids)
+    .bind(&app_versions)
+    .bind(&occurred)
+    .execute(db)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+/// Partition of the month starting on `month`.
+fn partition_name(month: Date) -> String {
+    format!(
+        "analytics_events_p{:04}{:02}",
+        month.year(),
+        u8::from(month.month())
+    )
+}
+
+/// First day of the month `name` covers, for partitions made by
+/// [`ensure_partition`].
+fn partition_month(name: &str) -> Option<Date> {
+    let digits = name.strip_prefix("analytics_events_p")?;
+    if digits.len() != 6 || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let year = digits[..4].parse().ok()?;
+    let month = Month::try_from(digits[4..].parse::<u8>().ok()?).ok()?;
+    Date::from_calendar_date(year, month, 1).ok()
+}
+
+pub fn month_start(day: Date) -> Date {
+    day.replace_day(1).expect("every month has a first day")
+}
+
+pub fn next_month(month: Date) -> Date {
+    let next = month.month().next();
+    let year = if next == Month::January {
+        month.year() + 1
+    } else {
+        month.year()
+    };
+    Date::from_calendar_date(year, next, 1).expect("every month has a first day")
+}
+
+/// Creates the partition of the month starting on `month` unless it exists.
+/// The name and bounds come from the date, never from input.
+pub async fn ensure_partition(db: &PgPool, month: Date) -> anyhow::Result<()> {
+    let sql = format!(
+        "CREATE TABLE IF NOT EXISTS {} PARTITION OF analytics_events \
+         FOR VALUES FROM ('{} 00:00:00+00') TO ('{} 00:00:00+00')",
+        partition_name(month),
+        month,
+        next_month(month)
+    );
+    sqlx::query(&sql).execute(db).await?;
+    Ok(())
+}
+
+/// Drops the monthly partitions that end on or before `before`; returns
+/// their names.
+pub async fn drop_partitions_before(db: &PgPool, before: Date) -> anyhow::Result<Vec<String>> {
+    let names = sqlx::query_scalar::<_, String>(
+        r#"
+        SELECT c.relname::text
+        FROM pg_inherits i
+        JOIN pg_class c ON c.oid = i.inhrelid
+        WHERE i.inhparent = 'analytics_events'::regclass
+        "#,
+    )
+    .fetch_all(db)
+    .await?;
+    let mut dropped = Vec::new();
+    for name in names {
+        let Some(month) = partition_month(&name) else {
+            continue;
+        };
+        if next_month(month) <= before {
+            sqlx::query(&format!("DROP TABLE IF EXISTS {}", partition_name(month)))
+                .execute(db)
+                .await?;
+            dropped.push(name);
+        }
+    }
+    Ok(dropped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::date;
+
+    #[test]
+    fn partition_names_round_trip() {
+        let month = date!(2024 - 12 - 01);
+        assert_eq!(partition_name(month), "analytics_events_p202412");
+        assert_eq!(partition_month("analytics_events_p202412"), Some(month));
+        assert_eq!(partition_month("analytics_events_default"), None);
+        assert_eq!(next_month(month), date!(2025 - 01 - 01));
+        assert_eq!(month_start(date!(2024 - 02 - 29)), date!(2024 - 02 - 01));
+    }
+}
@@ -0,0 +1,86 @@
+use axum::http::StatusCode;
+use time::{Duration, OffsetDateTime};
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    analytics::{
+        dto::{AnalyticsBatchResponse, AnalyticsEvent, AnalyticsSharing},
+        repo,
+    },
+    db::AppState,
+    error::ErrorCode,
+    preferences::repo as preferences_repo,
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum AnalyticsError {
+    #[error("Analytics sink unavailable")]
+    Sink(#[source] anyhow::Error),
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+impl AnalyticsError {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            AnalyticsError::Sink(_) => StatusCode::BAD_GATEWAY,
+            AnalyticsError::Other(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            AnalyticsError::Sink(_) => ErrorCode::BadGateway,
+            AnalyticsError::Other(_) => ErrorCode::Internal,
+        }
+    }
+}
+
+/// Records a checked batch as the user's `analytics` preference allows:
+/// in full, without account and session, or not at all.
+pub async fn record_events(
+    state: &AppState,
+    user_id: Uuid,
+    received: usize,
+    events: Vec<AnalyticsEvent>,
+) -> Result<AnalyticsBatchResponse, AnalyticsError> {
+    let sharing = preferences_repo::find_preferences(&state.db, user_id)
+        .await?
+        .unwrap_or_default()
+        .analytics;
+    let events: Vec<AnalyticsEvent> = match sharing {
+        AnalyticsSharing::Full => events,
+        AnalyticsSharing::Anonymous => events.into_iter().map(AnalyticsEvent::anonymize).collect(),
+        AnalyticsSharing::Off => Vec::new(),
+    };
+    if !events.is_empty() {
+        match &state.analytics_sink {
+            Some(sink) => sink.send(&events).await.map_err(AnalyticsError::Sink)?,
+            None => {
+                repo::insert_events(&state.db, &events).await?;
+            }
+        }
+    }
+    Ok(AnalyticsBatchResponse {
+        received,
+        recorded: events.len(),
+    })
+}
+
+/// Cron job: creates this and next month's partitions, and drops months
+/// entirely older than `ANALYTICS_RETENTION_DAYS` unless that is 0.
+pub async fn maintain_partitions(state: &AppState) -> anyhow::Result<()> {
+    let today = OffsetDateTime::now_utc().date();
+    let month = repo::month_start(today);
+    repo::ensure_partition(&state.db, month).await?;
+    repo::ensure_partition(&state.db, repo::next_month(month)).await?;
+    let retention_days = state.config.analytics.retention_days;
+    if retention_days > 0 {
+        let before = today - Duration::days(i64::from(retention_days));
+        for name in repo::drop_partitions_before(&state.db, before).await? {
+            info!(partition = %name, "dropped expired analytics partition");
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,450 @@
+//! Assembles the HTTP application: routes, limits and cross-cutting layers.
+
+use std::{future::Future, sync::Arc, time::Duration};
+
+use anyhow::Context;
+use axum::{
+    extract::DefaultBodyLimit,
+    middleware::{from_fn, from_fn_with_state},
+    routing::get,
+    Router,
+};
+use hyper_util::{
+    rt::{TokioExecutor, TokioIo},
+    server::conn::auto,
+    service::TowerToHyperService,
+};
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    net::TcpListener,
+    sync::watch,
+};
+use tokio_rustls::{
+    rustls::{
+        self,
+        pki_types::{pem::PemObject, CertificateDer, PrivateKeyDer},
+    },
+    TlsAcceptor,
+};
+use tower_http::{
+    catch_panic::CatchPanicLayer, cors::CorsLayer, limit::RequestBodyLimitLayer,
+    timeout::TimeoutLayer, trace::TraceLayer,
+};
+
+use crate::{
+    config::HttpConfig,
+    db::AppState,
+    error::{self, ApiError},
+    i18n,
+    load_shed::{self, InFlightLimit},
+    request_id,
+    routes::{
+        achievements::achievement_routes,
+        admin::admin_routes,
+        analytics::analytics_routes,
+        auth::auth_routes,
+        billing::billing_routes,
+        coaching::coaching_routes,
+        docs::docs_routes,
+        events::event_routes,
+        export::export_routes,
+        flags::flag_routes,
+        foods::food_routes,
+        goals::goal_routes,
+        health::health_routes,
+        households::household_routes,
+        imports::import_routes,
+        integrations::integration_routes,
+        me::me_route,
+        meal_items::meal_item_routes,
+        meals::{meal_routes, meal_upload_routes},
+        notifications::notification_routes,
+        orgs::org_routes,
+        photos::photo_routes,
+        plans::plan_routes,
+        preferences::preference_routes,
+        progress::{progress_routes, progress_upload_routes},
+        push::push_routes,
+        reactions::reaction_routes,
+        recipes::recipe_routes,
+        recommendations::recommendation_routes,
+        shares::share_routes,
+        stats::stats_routes,
+        summary::summary_routes,
+        sync::sync_routes,
+        templates::template_routes,
+        uploads::{upload_photo_routes, upload_routes},
+        v2::{v2_routes, v2_upload_routes},
+        webhooks::webhook_routes,
+        weights::weight_routes,
+        ws::ws_routes,
+    },
+    security_headers::{self, SecurityHeaders},
+};
+
+/// Per-route body limit replacing the default one, for routes that accept
+/// uploads.
+pub fn body_limit(max: usize) -> (DefaultBodyLimit, RequestBodyLimitLayer) {
+    (DefaultBodyLimit::disable(), RequestBodyLimitLayer::new(max))
+}
+
+/// Applies the default body limit and request timeout to `routes`, and the
+/// longer upload timeout and the upload budget to `uploads`, which set their
+/// own body limits.
+fn with_limits(
+    routes: Router<AppState>,
+    uploads: Router<AppState>,
+    http: &HttpConfig,
+    upload_budget: &Arc<InFlightLimit>,
+) -> Router<AppState> {
+    let routes = routes
+        .layer(DefaultBodyLimit::disable())
+        .layer(RequestBodyLimitLayer::new(http.max_body_bytes))
+        .layer(TimeoutLayer::new(Duration::from_secs(
+            http.request_timeout_secs,
+        )));
+    let uploads = uploads
+        .layer(TimeoutLayer::new(Duration::from_secs(
+            http.upload_timeout_secs,
+        )))
+        .layer(from_fn_with_state(upload_budget.clone(), load_shed::shed));
+    routes.merge(uploads)
+}
+
+pub fn build_app(state: AppState) -> Router {
+    let http = &state.config.http;
+    let security = SecurityHeaders::new(&state.config.security_headers);
+    let budget = InFlightLimit::new("all", http.max_in_flight, http.shed_retry_after_secs);
+    let upload_budget = InFlightLimit::new(
+        "uploads",
+        http.max_uploads_in_flight,
+        http.shed_retry_after_secs,
+    );
+
+    let v1_api = Router::new()
+        .merge(achievement_routes())
+        .merge(admin_routes())
+        .merge(analytics_routes())
+        .merge(auth_routes())
+        .merge(billing_routes())
+        .merge(coaching_routes())
+        .merge(meal_routes())
+        .merge(meal_item_routes())
+        .merge(notification_routes())
+        .merge(org_routes())
+        .merge(photo_routes())
+        .merge(plan_routes())
+        .merge(preference_routes())
+        .merge(progress_routes())
+        .merge(push_routes())
+        .merge(reaction_routes())
+        .merge(recipe_routes())
+        .merge(recommendation_routes())
+        .merge(share_routes())
+        .merge(event_routes())
+        .merge(export_routes())
+        .merge(flag_routes())
+        .merge(food_routes())
+        .merge(goal_routes())
+        .merge(household_routes())
+        .merge(integration_routes())
+        .merge(stats_routes())
+        .merge(summary_routes())
+        .merge(sync_routes())
+        .merge(template_routes())
+        .merge(upload_routes())
+        .merge(webhook_routes())
+        .merge(weight_routes())
+        .merge(ws_routes())
+        .route("/me", get(me_route));
+    let v1_uploads = Router::new()
+        .merge(meal_upload_routes(&state))
+        .merge(progress_upload_routes(&state))
+        .merge(upload_photo_routes(&state))
+        .merge(import_routes());
+    let v1 = with_limits(v1_api, v1_uploads, http, &upload_budget);
+
+    let v2 = with_limits(v2_routes(), v2_upload_routes(&state), http, &upload_budget)
+        .fallback(|| async { ApiError::NotFound("Not found".to_string()) });
+
+    let unversioned = with_limits(
+        Router::new().merge(docs_routes()).merge(health_routes()),
+        Router::new(),
+        http,
+        &upload_budget,
+    );
+
+    // v1 is also served without a prefix, where existing clients call it.
+    // Health checks stay outside the budget so a busy instance isn't
+    // mistaken for a dead one.
+    let api = Router::new()
+        .nest("/api/v1", v1.clone())
+        .nest("/api/v2", v2)
+        .merge(v1)
+        .layer(from_fn_with_state(budget, load_shed::shed));
+    api.merge(unversioned)
+        // Extractor rejections and unknown routes get the error body too.
+        .layer(from_fn(error::envelope))
+        .layer(from_fn(i18n::negotiate))
+        .with_state(state)
+        // Inside the trace and request id layers so a panic is logged and
+        // answered like any other internal error.
+        .layer(CatchPanicLayer::custom(error::recover_panic))
+        // Outside the panic layer so its responses get the headers too.
+        .layer(from_fn_with_state(security, security_headers::set))
+        .layer(CorsLayer::permissive())
+        .layer(
+            TraceLayer::new_for_http()
+                .make_span_with(|req: &axum::http::Request<_>| {
+                    let method = req.method().clone();
+                    let uri = loggable_uri(req.uri());
+                    let request_id = req
+                        .headers()
+                        .get(&request_id::X_REQUEST_ID)
+                        .and_then(|v| v.to_str().ok())
+                        .unwrap_or_default();
+                    tracing::info_span!("http_request", %method, uri = %uri, %request_id)
+                })
+                .on_response(
+                    |res: &axum::http::Response<_>,
+                     _latency: std::time::Duration,
+                     span: &tracing::Span| {
+                        let status = res.status();
+                        span.record("status", tracing::field::display(status));
+                        if status.is_server_error() {
+                            tracing::error!(%status, "response");
+                        } else {
+                            tracing::info!(%status, "response");
+                        }
+                    },
+                ),
+        )
+        // Outermost so the trace span and every handler see the id.
+        .layer(axum::middleware::from_fn(request_id::propagate))
+}
+
+/// Query parameters whose values are credentials, e.g. the WebSocket
+/// `?token=`; their values never reach the logs.
+const SECRET_PARAMS: [&str; 1] = ["token"];
+
+/// The request URI for logs, with the values of [`SECRET_PARAMS`] redacted.
+fn loggable_uri(uri: &axum::http::Uri) -> String {
+    let Some(query) = uri.query() else {
+        return uri.path().to_string();
+    };
+    let query: Vec<String> = query
+        .split('&')
+        .map(|pair| match pair.split_once('=') {
+            Some((name, _)) if SECRET_PARAMS.contains(&name) => format!("{}=REDACTED", name),
+            _ => pair.to_string(),
+        })
+        .collect();
+    format!("{}?{}", uri.path(), query.join("&"))
+}
+
+/// Time a client gets to complete the TLS handshake.
+const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Serves `app` on the configured TCP address or Unix socket, with TLS when
+/// a certificate is configured, until `shutdown` resolves. In-flight
+/// requests are finished before returning.
+pub async fn serve(
+    app: Router,
+    http: &HttpConfig,
+    shutdown: impl Future<Output = ()> + Send + 'static,
+) -> anyhow::Result<()> {
+    let tls = match (&http.tls_cert_path, &http.tls_key_path) {
+        (Some(cert), Some(key)) => Some(tls_acceptor(cert, key).context("load TLS certificate")?),
+        _ => None,
+    };
+    let scheme = if tls.is_some() { "https" } else { "http" };
+
+    #[cfg(unix)]
+    if let Some(path) = &http.unix_socket {
+        // A socket file left behind by an unclean exit blocks binding.
+        remove_socket(path)?;
+        let listener = tokio::net::UnixListener::bind(path)
+            .with_context(|| format!("bind unix socket {}", path))?;
+        tracing::info!("listening on {}+unix:{}", scheme, path);
+        let accept = || async { listener.accept().await.map(|(io, _)| boxed(io)) };
+        let result = serve_connections(app, accept, tls, shutdown).await;
+        let _ = remove_socket(path);
+        return result;
+    }
+    #[cfg(not(unix))]
+    if http.unix_socket.is_some() {
+        anyhow::bail!("APP_UNIX_SOCKET is only supported on Unix");
+    }
+
+    let addr = format!("{}:{}", http.host, http.port);
+    let listener = TcpListener::bind(&addr)
+        .await
+        .with_context(|| format!("bind {}", addr))?;
+    tracing::info!("listening on {}://{}", scheme, listener.local_addr()?);
+    match tls {
+        None => axum::serve(listener, app)
+            .with_graceful_shutdown(shutdown)
+            .await
+            .map_err(Into::into),
+        Some(tls) => {
+            let accept = || async { listener.accept().await.map(|(io, _)| boxed(io)) };
+            serve_connections(app, accept, Some(tls), shutdown).await
+        }
+    }
+}
+
+/// Removes the Unix socket at `path`, if there is one. Anything else there,
+/// such as a file `APP_UNIX_SOCKET` points at by mistake, is an error.
+#[cfg(unix)]
+fn remove_socket(path: &str) -> anyhow::Result<()> {
+    use std::os::unix::fs::FileTypeExt;
+
+    match std::fs::symlink_metadata(path) {
+        Ok(meta) if meta.file_type().is_socket() => {
+            std::fs::remove_file(path).with_context(|| format!("remove stale socket {}", path))
+        }
+        Ok(_) => anyhow::bail!("{} exists and is not a socket", path),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e).with_context(|| format!("inspect {}", path)),
+    }
+}
+
+fn tls_acceptor(cert_path: &str, key_path: &str) -> anyhow::Result<TlsAcceptor> {
+    let certs = CertificateDer::pem_file_iter(cert_path)
+        .with_context(|| format!("read {}", cert_path))?
+        .collect::<Result<Vec<_>, _>>()
+        .with_context(|| format!("parse {}", cert_path))?;
+    let key =
+        PrivateKeyDer::from_pem_file(key_path).with_context(|| format!("read {}", key_path))?;
+    let mut config = rustls::ServerConfig::builder_with_provider(Arc::new(
+        rustls::crypto::ring::default_provider(),
+    ))
+    .with_safe_default_protocol_versions()?
+    .with_no_client_auth()
+    .with_single_cert(certs, key)?;
+    config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+    Ok(TlsAcceptor::from(Arc::new(config)))
+}
+
+trait Io: AsyncRead + AsyncWrite + Unpin + Send + 'static {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin + Send + 'static> Io for T {}
+
+fn boxed(io: impl Io) -> Box<dyn Io> {
+    Box::new(io)
+}
+
+/// Accept loop for the listeners `axum::serve` doesn't cover: TLS and Unix
+/// sockets. Each connection speaks HTTP/1 (with upgrades, for WebSockets)
+/// or HTTP/2 and is asked to finish gracefully on shutdown.
+async fn serve_connections<A, F>(
+    app: Router,
+    accept: A,
+    tls: Option<TlsAcceptor>,
+    shutdown: impl Future<Output = ()>,
+) -> anyhow::Result<()>
+where
+    A: Fn() -> F,
+    F: Future<Output = std::io::Result<Box<dyn Io>>>,
+{
+    let (shutdown_tx, shutdown_rx) = watch::channel(());
+    // Every connection holds a receiver; `closed` resolves once all are gone.
+    let (close_tx, close_rx) = watch::channel(());
+    tokio::pin!(shutdown);
+    loop {
+        let io = tokio::select! {
+            accepted = accept() => match accepted {
+                Ok(io) => io,
+                Err(e) => {
+                    // Usually out of file descriptors; back off instead of spinning.
+                    tracing::error!(error = %e, "accepting connection failed");
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                    continue;
+                }
+            },
+            _ = &mut shutdown => break,
+        };
+        let app = app.clone();
+        let tls = tls.clone();
+        let mut shutdown_rx = shutdown_rx.clone();
+        let close_rx = close_rx.clone();
+        tokio::spawn(async move {
+            let io = match tls {
+                Some(tls) => {
+                    match tokio::time::timeout(TLS_HANDSHAKE_TIMEOUT, tls.accept(io)).await {
+                        Ok(Ok(io)) => boxed(io),
+                        Ok(Err(e)) => {
+                            tracing::debug!(error = %e, "TLS handshake failed");
+                            return;
+                        }
+                        Err(_) => {
+                            tracing::debug!("TLS handshake timed out");
+                            return;
+                        }
+                    }
+                }
+                None => io,
+            };
+            let builder = auto::Builder::new(TokioExecutor::new());
+            let conn = builder
+                .serve_connection_with_upgrades(TokioIo::new(io), TowerToHyperService::new(app));
+            tokio::pin!(conn);
+            let result = tokio::select! {
+                result = conn.as_mut() => result,
+                _ = shutdown_rx.changed() => {
+                    conn.as_mut().graceful_shutdown();
+                    conn.await
+                }
+            };
+            if let Err(e) = result {
+                tracing::debug!(error = %e, "connection closed with an error");
+            }
+            drop(close_rx);
+        });
+    }
+    drop(close_rx);
+    let _ = shutdown_tx.send(());
+    close_tx.closed().await;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn logged_uris_hide_tokens() {
+        let uri = "/ws?token=eyJhbGciOi.payload.sig".parse().unwrap();
+        assert_eq!(loggable_uri(&uri), "/ws?token=REDACTED");
+        let uri = "/meals?limit=5&token=abc&offset=10".parse().unwrap();
+        assert_eq!(
+            loggable_uri(&uri),
+            "/meals?limit=5&token=REDACTED&offset=10"
+        );
+        let uri = "/meals?tokens=1".parse().unwrap();
+        assert_eq!(loggable_uri(&uri), "/meals?tokens=1");
+        assert_eq!(loggable_uri(&"/health".parse().unwrap()), "/health");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn only_sockets_are_removed() {
+        let dir = std::env::temp_dir();
+        let path = |name: &str| {
+            let path = dir.join(format!("mealmind-{}-{}", uuid::Uuid::new_v4(), name));
+            path.to_str().unwrap().to_string()
+        };
+
+        let socket = path("app.sock");
+        let _listener = std::os::unix::net::UnixListener::bind(&socket).unwrap();
+        remove_socket(&socket).unwrap();
+        assert!(std::fs::symlink_metadata(&socket).is_err());
+        remove_socket(&socket).unwrap();
+
+        let file = path("data.db");
+        std::fs::write(&file, b"keep me").unwrap();
+        assert!(remove_socket(&file).is_err());
+        assert_eq!(std::fs::read(&file).unwrap(), b"keep me");
+        std::fs::remove_file(&file).unwrap();
+    }
+}
@@ -1,2 +1,4 @@
+pub mod dto;
 pub mod jwt;
 pub mod password;
+pub mod repo;
@@ -5,6 +5,8 @@ use argon2::{
 use rand_core::OsRng;
 use tracing::error;
 
+pub const MIN_PASSWORD_LEN: usize = 8;
+
 pub fn hash_password(plain: &str) -> anyhow::Result<String> {
     let salt = SaltString::generate(&mut OsRng);
     let argon2 = Argon2::default();
@@ -0,0 +1,70 @@
+use sqlx::{PgExecutor, PgPool};
+use uuid::Uuid;
+
+use crate::db::{RepoError, RepoResult, User};
+
+/// Unique index on `lower(email)`, reported by [`create`] for taken emails.
+pub const EMAIL_CONSTRAINT: &str = "idx_users_email_lower";
+
+pub async fn find_by_email(db: &PgPool, email: &str) -> RepoResult<Option<User>> {
+    let user = sqlx::query_as!(
+        User,
+        r#"
+        SELECT id, email, password_hash, created_at
+        FROM users
+        WHERE email = $1
+        "#,
+        email
+    )
+    .fetch_optional(db)
+    .await?;
+    Ok(user)
+}
+
+pub async fn find_by_id(db: &PgPool, user_id: Uuid) -> RepoResult<Option<User>> {
+    let user = sqlx::query_as!(
+        User,
+        r#"
+        SELECT id, email, password_hash, created_at
+        FROM users
+        WHERE id = $1
+        "#,
+        user_id
+    )
+    .fetch_optional(db)
+    .await?;
+    Ok(user)
+}
+
+/// Inserts a user, or fails with [`RepoError::Conflict`] on
+/// [`EMAIL_CONSTRAINT`] when the email is taken. The unique index decides,
+/// so of concurrent registrations of one email exactly one succeeds; no
+/// lookup beforehand is needed. `ON CONFLICT` names the case-insensitive
+/// index from migration 38 and keeps a surrounding transaction usable.
+pub async fn create(db: impl PgExecutor<'_>, email: &str, password_hash: &str) -> RepoResult<User> {
+    sqlx::query_as!(
+        User,
+        r#"
+        INSERT INTO users (email, password_hash)
+        VALUES ($1, $2)
+        ON CONFLICT ((lower(email))) DO NOTHING
+        RETURNING id, email, password_hash, created_at
+        "#,
+        email,
+        password_hash
+    )
+    .fetch_optional(db)
+    .await?
+    .ok_or_else(|| RepoError::Conflict(EMAIL_CONSTRAINT.to_string()))
+}
+
+/// False for unknown users as well.
+pub async fn is_admin(db: &PgPool, user_id: Uuid) -> RepoResult<bool> {
+    let is_admin = sqlx::query_scalar!(
+        r#"SELECT EXISTS (SELECT 1 FROM users WHERE id = $1 AND is_admin) AS "is_admin!""#,
+        user_id
+    )
+    .fetch_one(db)
+    .await?;
+    Ok(is_admin)
+}
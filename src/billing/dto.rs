@@ -0,0 +1,164 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+pub enum PlanTier {
+    Free,
+    Pro,
+}
+
+/// The plan columns of a user.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct PlanState {
+    pub plan: PlanTier,
+    pub plan_status: Option<String>,
+    pub plan_renews_at: Option<OffsetDateTime>,
+}
+
+/// `GET /me/plan`.
+#[derive(Debug, Serialize)]
+pub struct PlanResponse {
+    pub plan: PlanTier,
+    /// Status of the Stripe subscription behind the plan, e.g. `active` or
+    /// `past_due`; `null` without one.
+    pub status: Option<String>,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub renews_at: Option<OffsetDateTime>,
+    /// Photo analyses included per month; `null` when unlimited.
+    pub monthly_analyses: Option<u32>,
+    pub analyses_used: i32,
+    /// Start of the next UTC month, when `analyses_used` goes back to 0.
+    #[serde(with = "time::serde::rfc3339")]
+    pub resets_at: OffsetDateTime,
+}
+
+/// A Stripe webhook event; `data.object` depends on `type`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct StripeEvent {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// Unix seconds.
+    pub created: i64,
+    pub data: StripeEventData,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct StripeEventData {
+    pub object: serde_json::Value,
+}
+
+/// The fields of a Stripe subscription object the plan depends on.
+#[derive(Debug, Clone, Deserialize)]
+pub struct StripeSubscription {
+    pub id: String,
+    pub customer: String,
+    pub status: String,
+    /// Unix seconds.
+    pub current_period_end: Option<i64>,
+    /// Checkout puts the MealMind user id under `user_id`.
+    #[serde(default)]
+    pub metadata: HashMap<String, String>,
+}
+
+impl StripeSubscription {
+    /// Pro while the subscription is paid for or in its trial. `past_due`
+    /// keeps Pro while Stripe retries the payment; every other status,
+    /// such as `canceled`, `unpaid` or `incomplete`, is free.
+    pub fn tier(&self) -> PlanTier {
+        match self.status.as_str() {
+            "active" | "trialing" | "past_due" => PlanTier::Pro,
+            _ => PlanTier::Free,
+        }
+    }
+
+    pub fn user_id(&self) -> Option<Uuid> {
+        self.metadata.get("user_id")?.parse().ok()
+    }
+}
+
+/// The fields of a completed Stripe Checkout session the plan depends on.
+#[derive(Debug, Clone, Deserialize)]
+pub struct StripeCheckoutSession {
+    pub id: String,
+    pub mode: Option<String>,
+    /// The MealMind user id, set when the session was created.
+    pub client_reference_id: Option<String>,
+    pub customer: Option<String>,
+    pub subscription: Option<String>,
+    /// `paid`, `unpaid` or `no_payment_required`.
+    pub payment_status: Option<String>,
+    #[serde(default)]
+    pub metadata: HashMap<String, String>,
+}
+
+impl StripeCheckoutSession {
+    pub fn user_id(&self) -> Option<Uuid> {
+        self.client_reference_id
+            .as_deref()
+            .or(self.metadata.get("user_id").map(String::as_str))?
+            .parse()
+            .ok()
+    }
+
+    /// Whether the session paid for (or started the trial of) a
+    /// subscription.
+    pub fn is_paid(&self) -> bool {
+        self.mode.as_deref() == Some("subscription")
+            && matches!(
+                self.payment_status.as_deref(),
+                Some("paid" | "no_payment_required")
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn subscription(status: &str) -> StripeSubscription {
+        serde_json::from_value(serde_json::json!({
+            "id": "sub_1",
+            "object": "subscription",
+            "customer": "cus_1",
+            "status": status,
+            "current_period_end": 1_700_000_000,
+            "metadata": {"user_id": "00000000-0000-0000-0000-000000000007"}
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn subscription_status_picks_the_tier() {
+        assert_eq!(subscription("active").tier(), PlanTier::Pro);
+        assert_eq!(subscription("past_due").tier(), PlanTier::Pro);
+        assert_eq!(subscription("canceled").tier(), PlanTier::Free);
+        assert_eq!(subscription("incomplete").tier(), PlanTier::Free);
+        assert_eq!(subscription("active").user_id(), Some(Uuid::from_u128(7)));
+    }
+
+    #[test]
+    fn checkout_session_names_the_user() {
+        let session: StripeCheckoutSession = serde_json::from_value(serde_json::json!({
+            "id": "cs_1",
+            "mode": "subscription",
+            "client_reference_id": "00000000-0000-0000-0000-000000000007",
+            "customer": "cus_1",
+            "subscription": "sub_1",
+            "payment_status": "paid"
+        }))
+        .unwrap();
+        assert_eq!(session.user_id(), Some(Uuid::from_u128(7)));
+        assert!(session.is_paid());
+        let unpaid = StripeCheckoutSession {
+            payment_status: Some("unpaid".to_string()),
+            ..session
+        };
+        assert!(!unpaid.is_paid());
+    }
+}
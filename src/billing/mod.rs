@@ -0,0 +1,85 @@
+//! Plan tiers. Free users get `FREE_PLAN_MONTHLY_ANALYSES` photo analyses
+//! per UTC month, Pro users are unlimited. The tier is kept on the user and
+//! follows their Stripe subscription; [`enforce_plan`] guards the routes
+//! that upload meal photos for analysis; `POST /webhooks/stripe` keeps the
+//! tier in step with the subscription.
+
+pub mod dto;
+pub mod repo;
+pub mod services;
+pub mod stripe;
+
+use axum::{
+    body::{to_bytes, Body},
+    extract::{Request, State},
+    http::header::CONTENT_TYPE,
+    middleware::Next,
+    response::{IntoResponse, Response},
+    RequestExt,
+};
+use serde::de::IgnoredAny;
+use tracing::error;
+
+use crate::{auth::jwt::AuthUser, billing::services::QuotaError, db::AppState, error::ApiError};
+
+/// Just the images of a JSON meal body, skipped over rather than decoded.
+#[derive(serde::Deserialize)]
+struct ImageCount {
+    #[serde(default)]
+    images: Vec<IgnoredAny>,
+    upload_session_id: Option<IgnoredAny>,
+}
+
+/// Whether the request uploads photos: any multipart body, or a JSON body
+/// with a non-empty `images` or an `upload_session_id`. Meals logged by hand pass on any plan. The
+/// body is read under the route's `DefaultBodyLimit` and put back.
+async fn uploads_images(req: Request) -> Result<(Request, bool), Response> {
+    let content_type = req
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default();
+    if content_type.starts_with("multipart/form-data") {
+        return Ok((req, true));
+    }
+    let (parts, body) = req.with_limited_body().into_parts();
+    let Ok(bytes) = to_bytes(body, usize::MAX).await else {
+        return Err(
+            ApiError::PayloadTooLarge("Request body too large".to_string()).into_response(),
+        );
+    };
+    // Malformed bodies are left for the handler to reject.
+    let uploads = serde_json::from_slice::<ImageCount>(&bytes)
+        .map(|body| !body.images.is_empty() || body.upload_session_id.is_some())
+        .unwrap_or(false);
+    Ok((Request::from_parts(parts, Body::from(bytes)), uploads))
+}
+
+/// Route middleware rejecting photo uploads of free users who used up this
+/// month's analyses. Requests without valid credentials go straight to the
+/// handler, which rejects them.
+pub async fn enforce_plan(
+    State(state): State<AppState>,
+    auth: Option<AuthUser>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let Some(AuthUser(user_id)) = auth else {
+        return next.run(req).await;
+    };
+    let req = match uploads_images(req).await {
+        Ok((req, true)) => req,
+        Ok((req, false)) => return next.run(req).await,
+        Err(response) => return response,
+    };
+    match services::check_quota(&state, user_id).await {
+        Ok(()) => next.run(req).await,
+        Err(QuotaError::Other(e)) => {
+            error!(error = %e, user_id = %user_id, "check analysis quota failed");
+            ApiError::internal(&e, "Failed to check plan").into_response()
+        }
+        Err(e) => ApiError::from_status(e.status(), e.to_string())
+            .with_code(e.code())
+            .into_response(),
+    }
+}
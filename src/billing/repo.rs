@@ -0,0 +1,153 @@
+use sqlx::PgPool;
+use time::{Date, OffsetDateTime};
+use uuid::Uuid;
+
+use crate::billing::dto::{PlanState, PlanTier, StripeEvent};
+
+pub async fn find_plan(db: &PgPool, user_id: Uuid) -> anyhow::Result<Option<PlanState>> {
+    let plan = sqlx::query_as::<_, PlanState>(
+        "SELECT plan, plan_status, plan_renews_at FROM users WHERE id = $1",
+    )
+    .bind(user_id)
+    .fetch_optional(db)
+    .await?;
+    Ok(plan)
+}
+
+/// Analyses counted for the user in the month starting on `month`.
+pub async fn analyses_used(db: &PgPool, user_id: Uuid, month: Date) -> anyhow::Result<i32> {
+    let used = sqlx::query_scalar::<_, i32>(
+        "SELECT analyses FROM analysis_quota WHERE user_id = $1 AND month = $2",
+    )
+    .bind(user_id)
+    .bind(month)
+    .fetch_optional(db)
+    .await?;
+    Ok(used.unwrap_or(0))
+}
+
+pub async fn count_analysis(db: &PgPool, user_id: Uuid, month: Date) -> anyhow::Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO analysis_quota (user_id, month, analyses) VALUES ($1, $2, 1)
+        ON CONFLICT (user_id, month) DO UPDATE SET analyses = analysis_quota.analyses + 1
+        "#,
+    )
+    .bind(user_id)
+    .bind(month)
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+/// A Stripe subscription's state, applied to the user it belongs to.
+pub struct SubscriptionUpdate<'a> {
+    /// From the subscription's metadata; otherwise the user is found by
+    /// `customer_id`.
+    pub user_id: Option<Uuid>,
+    pub customer_id: &'a str,
+    pub subscription_id: &'a str,
+    pub plan: PlanTier,
+    pub status: &'a str,
+    pub renews_at: Option<OffsetDateTime>,
+    /// Creation time of the event carrying the state.
+    pub event_at: OffsetDateTime,
+}
+
+/// Applies `update` unless the user already has the state of a newer
+/// event, or it would downgrade them for a subscription other than their
+/// current one. Returns whether a user was updated.
+pub async fn apply_subscription(
+    db: &PgPool,
+    update: &SubscriptionUpdate<'_>,
+) -> anyhow::Result<bool> {
+    let result = sqlx::query(
+        r#"
+        UPDATE users
+        SET plan = $3, plan_status = $4, plan_renews_at = $5,
+            stripe_customer_id = $2, stripe_subscription_id = $6, plan_event_at = $7
+        WHERE (id = $1 OR ($1::uuid IS NULL AND stripe_customer_id = $2))
+          AND (plan_event_at IS NULL OR plan_event_at <= $7)
+          AND ($3 = 'pro' OR stripe_subscription_id IS NULL OR stripe_subscription_id = $6)
+        "#,
+    )
+    .bind(update.user_id)
+    .bind(update.customer_id)
+    .bind(update.plan)
+    .bind(update.status)
+    .bind(update.renews_at)
+    .bind(update.subscription_id)
+    .bind(update.event_at)
+    .execute(db)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Links the customer and subscription of a paid checkout to the user and
+/// grants Pro, unless a newer event already set their plan.
+pub async fn apply_checkout(
+    db: &PgPool,
+    user_id: Uuid,
+    customer_id: &str,
+    subscription_id: Option<&str>,
+    event_at: OffsetDateTime,
+) -> anyhow::Result<bool> {
+    let result = sqlx::query(
+        r#"
+        UPDATE users
+        SET plan = 'pro', plan_status = COALESCE(plan_status, 'active'),
+            stripe_customer_id = $2,
+            stripe_subscription_id = COALESCE($3, stripe_subscription_id),
+            plan_event_at = $4
+        WHERE id = $1 AND (plan_event_at IS NULL OR plan_event_at <= $4)
+        "#,
+    )
+    .bind(user_id)
+    .bind(customer_id)
+    .bind(subscription_id)
+    .bind(event_at)
+    .execute(db)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Logs a delivery of `event`; returns whether the event was already
+/// processed by an earlier delivery.
+pub async fn log_event(
+    db: &PgPool,
+    event: &StripeEvent,
+    created_at: OffsetDateTime,
+    payload: &serde_json::Value,
+) -> anyhow::Result<bool> {
+    let processed = sqlx::query_scalar::<_, bool>(
+        r#"
+        INSERT INTO stripe_events (id, type, created_at, payload) VALUES ($1, $2, $3, $4)
+        ON CONFLICT (id) DO UPDATE SET deliveries = stripe_events.deliveries + 1
+        RETURNING processed_at IS NOT NULL
+        "#,
+    )
+    .bind(&event.id)
+    .bind(&event.kind)
+    .bind(created_at)
+    .bind(payload)
+    .fetch_one(db)
+    .await?;
+    Ok(processed)
+}
+
+/// Records the outcome of processing an event; `error` is `None` on
+/// success.
+pub async fn finish_event(db: &PgPool, id: &str, error: Option<&str>) -> anyhow::Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE stripe_events
+        SET processed_at = CASE WHEN $2::text IS NULL THEN NOW() END, last_error = $2
+        WHERE id = $1
+        "#,
+    )
+    .bind(id)
+    .bind(error)
+    .execute(db)
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,238 @@
+use axum::http::StatusCode;
+use serde::Deserialize;
+use time::{OffsetDateTime, Time};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::{
+    analytics::repo::{month_start, next_month},
+    billing::{
+        dto::{PlanResponse, PlanTier, StripeCheckoutSession, StripeEvent, StripeSubscription},
+        repo::{self, SubscriptionUpdate},
+        stripe::{self, SignatureError},
+    },
+    db::AppState,
+    error::ErrorCode,
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum QuotaError {
+    #[error("The free plan includes {limit} photo analyses a month; upgrade to Pro for more")]
+    Exceeded { limit: u32 },
+    #[error("User not found")]
+    UserNotFound,
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+impl QuotaError {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            QuotaError::Exceeded { .. } => StatusCode::FORBIDDEN,
+            QuotaError::UserNotFound => StatusCode::UNAUTHORIZED,
+            QuotaError::Other(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            QuotaError::Exceeded { .. } => ErrorCode::AnalysisLimitReached,
+            QuotaError::UserNotFound => ErrorCode::AuthUserNotFound,
+            QuotaError::Other(_) => ErrorCode::Internal,
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum StripeWebhookError {
+    #[error("Stripe webhooks need STRIPE_WEBHOOK_SECRET")]
+    NotConfigured,
+    #[error(transparent)]
+    Signature(#[from] SignatureError),
+    #[error("Invalid Stripe event: {0}")]
+    Invalid(String),
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+impl StripeWebhookError {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            StripeWebhookError::NotConfigured => StatusCode::SERVICE_UNAVAILABLE,
+            StripeWebhookError::Signature(_) | StripeWebhookError::Invalid(_) => {
+                StatusCode::BAD_REQUEST
+            }
+            StripeWebhookError::Other(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            StripeWebhookError::NotConfigured => ErrorCode::ServiceUnavailable,
+            StripeWebhookError::Signature(_) => ErrorCode::StripeSignatureInvalid,
+            StripeWebhookError::Invalid(_) => ErrorCode::BadRequest,
+            StripeWebhookError::Other(_) => ErrorCode::Internal,
+        }
+    }
+}
+
+/// Photo analyses a month on `plan`; `None` when unlimited.
+pub fn monthly_analyses(state: &AppState, plan: PlanTier) -> Option<u32> {
+    match plan {
+        PlanTier::Free => Some(state.config.billing.free_monthly_analyses),
+        PlanTier::Pro => None,
+    }
+}
+
+pub async fn get_plan(state: &AppState, user_id: Uuid) -> Result<PlanResponse, QuotaError> {
+    let plan = repo::find_plan(&state.db, user_id)
+        .await?
+        .ok_or(QuotaError::UserNotFound)?;
+    let month = month_start(OffsetDateTime::now_utc().date());
+    Ok(PlanResponse {
+        monthly_analyses: monthly_analyses(state, plan.plan),
+        analyses_used: repo::analyses_used(&state.db, user_id, month).await?,
+        resets_at: next_month(month).with_time(Time::MIDNIGHT).assume_utc(),
+        plan: plan.plan,
+        status: plan.plan_status,
+        renews_at: plan.plan_renews_at,
+    })
+}
+
+/// Fails once a free user has used this month's analyses.
+pub async fn check_quota(state: &AppState, user_id: Uuid) -> Result<(), QuotaError> {
+    let plan = repo::find_plan(&state.db, user_id)
+        .await?
+        .ok_or(QuotaError::UserNotFound)?;
+    let Some(limit) = monthly_analyses(state, plan.plan) else {
+        return Ok(());
+    };
+    let month = month_start(OffsetDateTime::now_utc().date());
+    if repo::analyses_used(&state.db, user_id, month).await? >= limit as i32 {
+        return Err(QuotaError::Exceeded { limit });
+    }
+    Ok(())
+}
+
+/// Counts a meal queued for analysis against this month's allowance.
+pub async fn count_analysis(state: &AppState, user_id: Uuid) -> anyhow::Result<()> {
+    let month = month_start(OffsetDateTime::now_utc().date());
+    repo::count_analysis(&state.db, user_id, month).await
+}
+
+/// Verifies and applies a Stripe webhook delivery. Every event is logged;
+/// one that was already processed is acknowledged without applying it
+/// again, and one that fails is left unprocessed so Stripe's retry can
+/// apply it.
+pub async fn handle_stripe_webhook(
+    state: &AppState,
+    signature: Option<&str>,
+    body: &[u8],
+) -> Result<(), StripeWebhookError> {
+    let billing = &state.config.billing;
+    let secret = billing
+        .stripe_webhook_secret
+        .as_deref()
+        .ok_or(StripeWebhookError::NotConfigured)?;
+    stripe::verify(
+        signature.ok_or(SignatureError::Malformed)?,
+        body,
+        secret,
+        OffsetDateTime::now_utc().unix_timestamp(),
+        billing.stripe_webhook_tolerance_secs as i64,
+    )?;
+    let payload: serde_json::Value =
+        serde_json::from_slice(body).map_err(|e| StripeWebhookError::Invalid(e.to_string()))?;
+    let event = StripeEvent::deserialize(&payload)
+        .map_err(|e| StripeWebhookError::Invalid(e.to_string()))?;
+    let created_at = OffsetDateTime::from_unix_timestamp(event.created)
+        .map_err(|e| StripeWebhookError::Invalid(e.to_string()))?;
+
+    if repo::log_event(&state.db, &event, created_at, &payload).await? {
+        info!(event_id = %event.id, kind = %event.kind, "Stripe event already processed");
+        return Ok(());
+    }
+    match apply_stripe_event(state, &event).await {
+        Ok(()) => {
+            repo::finish_event(&state.db, &event.id, None).await?;
+            Ok(())
+        }
+        Err(e) => {
+            let message = format!("{e:#}");
+            if let Err(log) = repo::finish_event(&state.db, &event.id, Some(&message)).await {
+                warn!(error = %log, event_id = %event.id, "failed to record Stripe event error");
+            }
+            Err(e.into())
+        }
+    }
+}
+
+/// Applies a paid checkout or a subscription lifecycle event to the
+/// subscriber's plan. Other event types are ignored.
+pub async fn apply_stripe_event(state: &AppState, event: &StripeEvent) -> anyhow::Result<()> {
+    let deleted = match event.kind.as_str() {
+        "checkout.session.completed" => return apply_checkout(state, event).await,
+        "customer.subscription.deleted" => true,
+        "customer.subscription.created"
+        | "customer.subscription.updated"
+        | "customer.subscription.paused"
+        | "customer.subscription.resumed" => false,
+        _ => return Ok(()),
+    };
+    let subscription: StripeSubscription = serde_json::from_value(event.data.object.clone())?;
+    let plan = if deleted {
+        PlanTier::Free
+    } else {
+        subscription.tier()
+    };
+    let update = SubscriptionUpdate {
+        user_id: subscription.user_id(),
+        customer_id: &subscription.customer,
+        subscription_id: &subscription.id,
+        plan,
+        status: &subscription.status,
+        renews_at: subscription
+            .current_period_end
+            .and_then(|t| OffsetDateTime::from_unix_timestamp(t).ok()),
+        event_at: OffsetDateTime::from_unix_timestamp(event.created)?,
+    };
+    if repo::apply_subscription(&state.db, &update).await? {
+        info!(
+            event_id = %event.id,
+            subscription = %subscription.id,
+            plan = ?plan,
+            status = %subscription.status,
+            "plan updated from Stripe"
+        );
+    } else {
+        warn!(
+            event_id = %event.id,
+            customer = %subscription.customer,
+            "Stripe event matched no user or is older than their plan"
+        );
+    }
+    Ok(())
+}
+
+async fn apply_checkout(state: &AppState, event: &StripeEvent) -> anyhow::Result<()> {
+    let session: StripeCheckoutSession = serde_json::from_value(event.data.object.clone())?;
+    if !session.is_paid() {
+        return Ok(());
+    }
+    let (Some(user_id), Some(customer)) = (session.user_id(), session.customer.as_deref()) else {
+        warn!(event_id = %event.id, session = %session.id, "checkout without user or customer");
+        return Ok(());
+    };
+    let event_at = OffsetDateTime::from_unix_timestamp(event.created)?;
+    let subscription = session.subscription.as_deref();
+    if repo::apply_checkout(&state.db, user_id, customer, subscription, event_at).await? {
+        info!(event_id = %event.id, user_id = %user_id, "plan upgraded from Stripe checkout");
+    } else {
+        warn!(
+            event_id = %event.id,
+            user_id = %user_id,
+            "Stripe checkout matched no user or is older than their plan"
+        );
+    }
+    Ok(())
+}
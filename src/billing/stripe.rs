@@ -0,0 +1,115 @@
+//! Verification of the `Stripe-Signature` header: `t=<unix seconds>` and one
+//! or more `v1=<hex HMAC-SHA256 of "t.body">` under the endpoint's signing
+//! secret. Several `v1` entries appear while Stripe rolls the secret.
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+type HmacSha256 = Hmac<Sha256>;
+
+pub const SIGNATURE_HEADER: &str = "Stripe-Signature";
+
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+pub enum SignatureError {
+    #[error("Missing or malformed Stripe-Signature header")]
+    Malformed,
+    #[error("Stripe-Signature does not match the payload")]
+    Mismatch,
+    /// Signed too long ago (or ahead): a replayed or badly delayed request.
+    #[error("Stripe-Signature timestamp is outside the tolerance")]
+    Expired,
+}
+
+/// Checks `header` against `body`, accepting signatures made within
+/// `tolerance_secs` of `now`.
+pub fn verify(
+    header: &str,
+    body: &[u8],
+    secret: &str,
+    now: i64,
+    tolerance_secs: i64,
+) -> Result<(), SignatureError> {
+    let mut timestamp = None;
+    let mut signatures = Vec::new();
+    for (key, value) in header
+        .split(',')
+        .filter_map(|part| part.trim().split_once('='))
+    {
+        match key {
+            "t" => timestamp = value.parse::<i64>().ok(),
+            "v1" => signatures.extend(hex::decode(value).ok()),
+            _ => {}
+        }
+    }
+    let Some(timestamp) = timestamp else {
+        return Err(SignatureError::Malformed);
+    };
+    if signatures.is_empty() {
+        return Err(SignatureError::Malformed);
+    }
+    // HMAC accepts keys of any length.
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).expect("any key length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    if !signatures
+        .iter()
+        .any(|signature| mac.clone().verify_slice(signature).is_ok())
+    {
+        return Err(SignatureError::Mismatch);
+    }
+    if (now - timestamp).abs() > tolerance_secs {
+        return Err(SignatureError::Expired);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::webhooks::delivery::signature;
+
+    const BODY: &[u8] = br#"{"id":"evt_1","type":"customer.subscription.updated"}"#;
+
+    #[test]
+    fn accepts_any_matching_signature_within_tolerance() {
+        // Our outgoing webhooks are signed the same way.
+        let header = signature("whsec_test", 1_700_000_000, BODY);
+        assert_eq!(
+            verify(&header, BODY, "whsec_test", 1_700_000_100, 300),
+            Ok(())
+        );
+
+        let (_, current) = header.split_once(',').unwrap();
+        let rolled = format!("t=1700000000,v1={},{current}", "ab".repeat(32));
+        assert_eq!(
+            verify(&rolled, BODY, "whsec_test", 1_700_000_000, 300),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn rejects_tampered_stale_and_malformed_headers() {
+        let header = signature("whsec_test", 1_700_000_000, BODY);
+        assert_eq!(
+            verify(&header, b"{}", "whsec_test", 1_700_000_000, 300),
+            Err(SignatureError::Mismatch)
+        );
+        assert_eq!(
+            verify(&header, BODY, "whsec_other", 1_700_000_000, 300),
+            Err(SignatureError::Mismatch)
+        );
+        assert_eq!(
+            verify(&header, BODY, "whsec_test", 1_700_000_301, 300),
+            Err(SignatureError::Expired)
+        );
+        assert_eq!(
+            verify("v1=abcd", BODY, "whsec_test", 0, 300),
+            Err(SignatureError::Malformed)
+        );
+        assert_eq!(
+            verify("t=1700000000", BODY, "whsec_test", 0, 300),
+            Err(SignatureError::Malformed)
+        );
+    }
+}
@@ -0,0 +1,154 @@
+//! Operations CLI sharing the configuration (`.env`, `CONFIG_FILE`) and
+//! database of the API server. Passwords are read from stdin so they stay
+//! out of shell history and process lists.
+
+use std::{
+    io::{self, BufRead, IsTerminal, Write},
+    process::ExitCode,
+};
+
+use anyhow::Context;
+use mealmind::{admin::ops, db::AppState};
+use uuid::Uuid;
+
+const USAGE: &str = "\
+Usage: mealmind-admin <command>
+
+Commands:
+  create-user <email> [--admin]   Register a user; reads the password from stdin
+  reset-password <email>          Set a new password; reads it from stdin
+  reanalyze <meal-id>             Queue the AI analysis of a meal again
+  rescore                         Recompute nutrition totals and scores of all meals
+  storage-gc                      Delete orphaned photos and their objects now";
+
+#[derive(Debug, PartialEq, Eq)]
+enum Command {
+    CreateUser { email: String, admin: bool },
+    ResetPassword { email: String },
+    Reanalyze { meal_id: Uuid },
+    Rescore,
+    StorageGc,
+}
+
+impl Command {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        match args.as_slice() {
+            ["create-user", email] => Ok(Command::CreateUser {
+                email: email.to_string(),
+                admin: false,
+            }),
+            ["create-user", email, "--admin"] | ["create-user", "--admin", email] => {
+                Ok(Command::CreateUser {
+                    email: email.to_string(),
+                    admin: true,
+                })
+            }
+            ["reset-password", email] => Ok(Command::ResetPassword {
+                email: email.to_string(),
+            }),
+            ["reanalyze", id] => id
+                .parse()
+                .map(|meal_id| Command::Reanalyze { meal_id })
+                .map_err(|_| format!("invalid meal id: {}", id)),
+            ["rescore"] => Ok(Command::Rescore),
+            ["storage-gc"] => Ok(Command::StorageGc),
+            [] => Err("missing command".to_string()),
+            [command, ..] => Err(format!("unknown command or arguments: {}", command)),
+        }
+    }
+}
+
+fn read_password() -> anyhow::Result<String> {
+    let stdin = io::stdin();
+    if stdin.is_terminal() {
+        eprint!("Password: ");
+        io::stderr().flush()?;
+    }
+    let mut line = String::new();
+    stdin.lock().read_line(&mut line).context("read password")?;
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+async fn run(command: Command) -> anyhow::Result<()> {
+    let state = AppState::init().await?;
+    match command {
+        Command::CreateUser { email, admin } => {
+            let password = read_password()?;
+            let user = ops::create_user(&state, &email, &password, admin).await?;
+            println!("created user {} ({})", user.id, user.email);
+        }
+        Command::ResetPassword { email } => {
+            let password = read_password()?;
+            let user_id = ops::reset_password(&state, &email, &password).await?;
+            println!("password of user {} reset", user_id);
+        }
+        Command::Reanalyze { meal_id } => {
+            let job_id = ops::reanalyze_meal(&state, meal_id).await?;
+            println!("analysis of meal {} queued as job {}", meal_id, job_id);
+        }
+        Command::Rescore => {
+            let meals = ops::rescore_meals(&state).await?;
+            println!("rescored {} meals", meals);
+        }
+        Command::StorageGc => {
+            ops::storage_gc(&state).await?;
+            println!("storage cleanup done");
+        }
+    }
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    dotenvy::dotenv().ok();
+    let env_filter = std::env::var("RUST_LOG").unwrap_or_else(|_| "mealmind=info".to_string());
+    tracing_subscriber::fmt()
+        .with_env_filter(env_filter)
+        .with_writer(io::stderr)
+        .init();
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if matches!(args.first().map(String::as_str), Some("-h" | "--help")) {
+        println!("{}", USAGE);
+        return ExitCode::SUCCESS;
+    }
+    let command = match Command::parse(&args) {
+        Ok(command) => command,
+        Err(e) => {
+            eprintln!("error: {}\n\n{}", e, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+    match run(command).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {:#}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Command, String> {
+        Command::parse(&args.iter().map(|a| a.to_string()).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn parses_commands_and_their_arguments() {
+        assert_eq!(
+            parse(&["create-user", "ops@example.com", "--admin"]),
+            Ok(Command::CreateUser {
+                email: "ops@example.com".into(),
+                admin: true
+            })
+        );
+        assert_eq!(parse(&["rescore"]), Ok(Command::Rescore));
+        assert!(parse(&["reanalyze", "not-a-uuid"]).is_err());
+        assert!(parse(&["rescore", "now"]).is_err());
+        assert!(parse(&[]).is_err());
+    }
+}
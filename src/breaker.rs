@@ -0,0 +1,193 @@
+//! Circuit breaker for calls to dependencies that can go down as a whole,
+//! like object storage and the AI provider. After enough consecutive
+//! failures the circuit opens and calls fail at once instead of each
+//! waiting for its own timeout; once the open period is over a single probe
+//! call is let through, and its outcome closes or reopens the circuit.
+
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use tracing::{info, warn};
+
+use crate::config::CircuitBreakerConfig;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Closed {
+        failures: u32,
+    },
+    Open {
+        until: Instant,
+    },
+    /// The probe is in flight; other calls are still refused. A probe that
+    /// never reports back (its request was dropped) is replaced after the
+    /// open period.
+    HalfOpen {
+        since: Instant,
+    },
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("{0} circuit open")]
+pub struct CircuitOpen(pub &'static str);
+
+pub struct CircuitBreaker {
+    name: &'static str,
+    threshold: u32,
+    open_for: Duration,
+    state: Mutex<State>,
+}
+
+impl CircuitBreaker {
+    pub fn new(name: &'static str, config: &CircuitBreakerConfig) -> Self {
+        Self {
+            name,
+            threshold: config.failure_threshold,
+            open_for: Duration::from_secs(config.open_secs),
+            state: Mutex::new(State::Closed { failures: 0 }),
+        }
+    }
+
+    /// Runs `call` unless the circuit is open. `is_failure` decides which
+    /// errors count against the dependency; others, like a missing object,
+    /// say nothing about its health.
+    pub async fn call<T, E, Fut>(
+        &self,
+        is_failure: impl FnOnce(&E) -> bool,
+        call: impl FnOnce() -> Fut,
+    ) -> Result<Result<T, E>, CircuitOpen>
+    where
+        Fut: std::future::Future<Output = Result<T, E>>,
+    {
+        if self.threshold == 0 {
+            return Ok(call().await);
+        }
+        self.acquire(Instant::now())?;
+        let result = call().await;
+        let failed = result.as_ref().err().is_some_and(is_failure);
+        self.record(failed, Instant::now());
+        Ok(result)
+    }
+
+    fn acquire(&self, now: Instant) -> Result<(), CircuitOpen> {
+        let mut state = self.state.lock().expect("breaker lock");
+        let probe_at = match *state {
+            State::Closed { .. } => return Ok(()),
+            State::Open { until } => until,
+            State::HalfOpen { since } => since + self.open_for,
+        };
+        if now < probe_at {
+            return Err(CircuitOpen(self.name));
+        }
+        *state = State::HalfOpen { since: now };
+        info!(dependency = self.name, "circuit half-open; probing");
+        Ok(())
+    }
+
+    fn record(&self, failed: bool, now: Instant) {
+        let mut state = self.state.lock().expect("breaker lock");
+        let next = match (*state, failed) {
+            (State::HalfOpen { .. }, false) => {
+                info!(dependency = self.name, "circuit closed");
+                State::Closed { failures: 0 }
+            }
+            (State::Closed { .. }, false) => State::Closed { failures: 0 },
+            (State::HalfOpen { .. }, true) => State::Open {
+                until: now + self.open_for,
+            },
+            (State::Closed { failures }, true) if failures + 1 >= self.threshold => {
+                warn!(
+                    dependency = self.name,
+                    failures = failures + 1,
+                    open_secs = self.open_for.as_secs(),
+                    "circuit opened"
+                );
+                State::Open {
+                    until: now + self.open_for,
+                }
+            }
+            (State::Closed { failures }, true) => State::Closed {
+                failures: failures + 1,
+            },
+            // Calls that started before the circuit opened.
+            (open @ State::Open { .. }, _) => open,
+        };
+        *state = next;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn breaker() -> CircuitBreaker {
+        CircuitBreaker::new(
+            "test",
+            &CircuitBreakerConfig {
+                failure_threshold: 3,
+                open_secs: 30,
+            },
+        )
+    }
+
+    #[test]
+    fn opens_after_consecutive_failures() {
+        let breaker = breaker();
+        let now = Instant::now();
+        breaker.record(true, now);
+        breaker.record(true, now);
+        breaker.record(false, now);
+        breaker.record(true, now);
+        breaker.record(true, now);
+        assert!(breaker.acquire(now).is_ok());
+        breaker.record(true, now);
+        assert!(breaker.acquire(now).is_err());
+        assert!(breaker.acquire(now + Duration::from_secs(29)).is_err());
+    }
+
+    #[test]
+    fn lets_one_probe_through_and_follows_its_outcome() {
+        let breaker = breaker();
+        let now = Instant::now();
+        for _ in 0..3 {
+            breaker.record(true, now);
+        }
+        let later = now + Duration::from_secs(30);
+        assert!(breaker.acquire(later).is_ok());
+        assert!(breaker.acquire(later).is_err(), "one probe at a time");
+        breaker.record(true, later);
+        assert!(breaker.acquire(later + Duration::from_secs(1)).is_err());
+
+        let retry = later + Duration::from_secs(30);
+        assert!(breaker.acquire(retry).is_ok());
+        breaker.record(false, retry);
+        assert!(breaker.acquire(retry).is_ok());
+        assert!(breaker.acquire(retry).is_ok());
+    }
+
+    #[tokio::test]
+    async fn ignored_errors_and_disabled_breakers_never_open() {
+        let breaker = breaker();
+        for _ in 0..5 {
+            let result = breaker
+                .call(|_: &&str| false, || async { Err::<(), _>("not found") })
+                .await;
+            assert!(result.is_ok());
+        }
+        let disabled = CircuitBreaker::new(
+            "off",
+            &CircuitBreakerConfig {
+                failure_threshold: 0,
+                open_secs: 30,
+            },
+        );
+        for _ in 0..5 {
+            let result = disabled
+                .call(|_: &&str| true, || async { Err::<(), _>("down") })
+                .await;
+            assert!(result.is_ok());
+        }
+    }
+}
@@ -0,0 +1,86 @@
+//! In-process [`CacheStore`], for tests and entries an instance may keep to
+//! itself.
+
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use super::CacheStore;
+
+#[derive(Default)]
+pub struct MemoryStore {
+    entries: Mutex<HashMap<String, (Instant, String)>>,
+    /// Unbounded when `None`.
+    max_entries: Option<usize>,
+}
+
+impl MemoryStore {
+    /// A store that drops expired entries once it would grow past
+    /// `max_entries`, and everything if that isn't enough.
+    pub fn bounded(max_entries: usize) -> Self {
+        Self {
+            entries: Mutex::default(),
+            max_entries: Some(max_entries),
+        }
+    }
+}
+
+#[axum::async_trait]
+impl CacheStore for MemoryStore {
+    async fn get(&self, keys: &[String]) -> anyhow::Result<Vec<Option<String>>> {
+        let mut entries = self.entries.lock().unwrap();
+        let now = Instant::now();
+        entries.retain(|_, (expires_at, _)| *expires_at > now);
+        Ok(keys
+            .iter()
+            .map(|key| entries.get(key).map(|(_, value)| value.clone()))
+            .collect())
+    }
+
+    async fn set(&self, new: &[(String, String)], ttl: Duration) -> anyhow::Result<()> {
+        let now = Instant::now();
+        let expires_at = now + ttl;
+        let mut entries = self.entries.lock().unwrap();
+        if let Some(max) = self.max_entries {
+            if entries.len() + new.len() > max {
+                entries.retain(|_, (expires_at, _)| *expires_at > now);
+            }
+            if entries.len() + new.len() > max {
+                entries.clear();
+            }
+        }
+        for (key, value) in new {
+            entries.insert(key.clone(), (expires_at, value.clone()));
+        }
+        Ok(())
+    }
+
+    async fn delete(&self, keys: &[String]) -> anyhow::Result<()> {
+        let mut entries = self.entries.lock().unwrap();
+        for key in keys {
+            entries.remove(key);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn bounded_store_makes_room() {
+        let store = MemoryStore::bounded(2);
+        let entry = |key: &str| (key.to_string(), "1".to_string());
+        let ttl = Duration::from_secs(60);
+        store.set(&[entry("a"), entry("b")], ttl).await.unwrap();
+        store.set(&[entry("c")], ttl).await.unwrap();
+        let keys = ["a", "b", "c"].map(String::from);
+        assert_eq!(
+            store.get(&keys).await.unwrap(),
+            vec![None, None, Some("1".to_string())]
+        );
+    }
+}
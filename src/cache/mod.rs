@@ -0,0 +1,244 @@
+//! Short-lived cache for hot reads: daily summaries, presigned photo URLs and
+//! `GET /me`. Backed by Redis when `REDIS_URL` is set and disabled otherwise,
+//! so every instance sees the same entries and invalidations. Entries that
+//! are never invalidated, like presigned URLs, can fall back to this
+//! instance's memory through [`Cache::or_local`].
+//!
+//! Cache failures never fail a request: reads fall back to the database and
+//! write errors are only logged.
+
+use std::{sync::Arc, time::Duration};
+
+use serde::{de::DeserializeOwned, Serialize};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+pub mod memory;
+mod redis;
+
+pub use memory::MemoryStore;
+
+/// `GET /me` profile.
+pub const ME_TTL: Duration = Duration::from_secs(5 * 60);
+/// Daily summaries; invalidated on meal, goal and plan writes.
+pub const SUMMARY_TTL: Duration = Duration::from_secs(60);
+/// Outlives any summary entry, so an expired generation can't bring back
+/// entries stored under the initial one.
+const SUMMARY_GENERATION_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+/// Entries this instance keeps in memory without Redis.
+const LOCAL_MAX_ENTRIES: usize = 10_000;
+
+/// Key/value store holding serialized entries.
+#[axum::async_trait]
+pub trait CacheStore: Send + Sync {
+    /// Values of `keys`, in order; missing or expired keys are `None`.
+    async fn get(&self, keys: &[String]) -> anyhow::Result<Vec<Option<String>>>;
+
+    async fn set(&self, entries: &[(String, String)], ttl: Duration) -> anyhow::Result<()>;
+
+    /// Deleting missing keys is not an error.
+    async fn delete(&self, keys: &[String]) -> anyhow::Result<()>;
+}
+
+/// Cache handle kept in `AppState`; a no-op without a store.
+#[derive(Clone, Default)]
+pub struct Cache {
+    store: Option<Arc<dyn CacheStore>>,
+    /// Used by [`Cache::or_local`] when there is no shared store.
+    local: Option<Arc<dyn CacheStore>>,
+}
+
+impl Cache {
+    pub fn new(store: Arc<dyn CacheStore>) -> Self {
+        Self {
+            store: Some(store),
+            local: None,
+        }
+    }
+
+    pub async fn connect(redis_url: Option<&str>) -> anyhow::Result<Self> {
+        match redis_url {
+            Some(url) => {
+                let store = redis::RedisStore::connect(url).await?;
+                info!("read cache backed by redis");
+                Ok(Self::new(Arc::new(store)))
+            }
+            None => Ok(Self {
+                store: None,
+                local: Some(Arc::new(MemoryStore::bounded(LOCAL_MAX_ENTRIES))),
+            }),
+        }
+    }
+
+    /// This cache, or without Redis one in this instance's memory. Only for
+    /// entries that never need invalidating on other instances.
+    pub fn or_local(&self) -> Cache {
+        Cache {
+            store: self.store.clone().or_else(|| self.local.clone()),
+            local: None,
+        }
+    }
+
+    pub async fn get<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        self.get_many(&[key.to_string()]).await.pop().flatten()
+    }
+
+    /// Entries for `keys`, in order. Unreadable entries count as misses.
+    pub async fn get_many<T: DeserializeOwned>(&self, keys: &[String]) -> Vec<Option<T>> {
+        let Some(store) = &self.store else {
+            return keys.iter().map(|_| None).collect();
+        };
+        match store.get(keys).await {
+            Ok(values) => values
+                .into_iter()
+                .map(|value| value.and_then(|v| serde_json::from_str(&v).ok()))
+                .collect(),
+            Err(e) => {
+                warn!(error = %e, "cache read failed");
+                keys.iter().map(|_| None).collect()
+            }
+        }
+    }
+
+    pub async fn put<T: Serialize>(&self, key: &str, value: &T, ttl: Duration) {
+        self.put_many(&[(key.to_string(), value)], ttl).await
+    }
+
+    pub async fn put_many<T: Serialize>(&self, entries: &[(String, T)], ttl: Duration) {
+        let Some(store) = &self.store else {
+            return;
+        };
+        let serialized: Result<Vec<_>, _> = entries
+            .iter()
+            .map(|(key, value)| serde_json::to_string(value).map(|v| (key.clone(), v)))
+            .collect();
+        let result = match serialized {
+            Ok(entries) if entries.is_empty() => Ok(()),
+            Ok(entries) => store.set(&entries, ttl).await,
+            Err(e) => Err(e.into()),
+        };
+        if let Err(e) = result {
+            warn!(error = %e, "cache write failed");
+        }
+    }
+
+    pub async fn invalidate(&self, keys: &[String]) {
+        let Some(store) = &self.store else {
+            return;
+        };
+        if let Err(e) = store.delete(keys).await {
+            // Entries expire on their own; stale reads last at most one TTL.
+            warn!(error = %e, "cache invalidation failed");
+        }
+    }
+
+    /// Generation the user's daily summaries are stored under; read it
+    /// before computing a summary and store the result under it.
+    pub async fn summary_generation(&self, user_id: Uuid) -> String {
+        self.get(&keys::summary_generation(user_id))
+            .await
+            .unwrap_or_default()
+    }
+
+    /// Drops the user's cached daily summaries after a write that changes
+    /// intake, goals or plans by starting a new generation, so a summary
+    /// computed before the write and stored afterwards is never read.
+    pub async fn invalidate_summaries(&self, user_id: Uuid) {
+        let generation = Uuid::new_v4().to_string();
+        let key = keys::summary_generation(user_id);
+        self.put(&key, &generation, SUMMARY_GENERATION_TTL).await
+    }
+}
+
+pub mod keys {
+    use uuid::Uuid;
+
+    pub fn me(user_id: Uuid) -> String {
+        format!("mealmind:me:{user_id}")
+    }
+
+    /// One entry per user and generation mapping dates to summaries, so
+    /// starting a new generation invalidates every cached day.
+    pub fn daily_summaries(user_id: Uuid, generation: &str) -> String {
+        format!("mealmind:summary:{user_id}:{generation}")
+    }
+
+    pub fn summary_generation(user_id: Uuid) -> String {
+        format!("mealmind:summary-generation:{user_id}")
+    }
+
+    pub fn photo_url(photo_id: Uuid) -> String {
+        format!("mealmind:photo-url:{photo_id}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn round_trips_and_invalidates() {
+        let cache = Cache::new(Arc::new(MemoryStore::default()));
+        let user = Uuid::new_v4();
+        let key = keys::me(user);
+        cache.put(&key, &vec![1, 2, 3], ME_TTL).await;
+        assert_eq!(cache.get::<Vec<i32>>(&key).await, Some(vec![1, 2, 3]));
+
+        cache.invalidate(std::slice::from_ref(&key)).await;
+        assert_eq!(cache.get::<Vec<i32>>(&key).await, None);
+    }
+
+    #[tokio::test]
+    async fn get_many_keeps_order_and_skips_unreadable_entries() {
+        let store = Arc::new(MemoryStore::default());
+        let cache = Cache::new(store.clone());
+        store
+            .set(&[("b".into(), "not json".into())], ME_TTL)
+            .await
+            .unwrap();
+        cache.put("a", &1, ME_TTL).await;
+        let values = cache
+            .get_many::<i32>(&["a".into(), "b".into(), "c".into()])
+            .await;
+        assert_eq!(values, vec![Some(1), None, None]);
+    }
+
+    #[tokio::test]
+    async fn local_fallback_only_without_a_shared_store() {
+        let local = Cache {
+            store: None,
+            local: Some(Arc::new(MemoryStore::default())),
+        };
+        local.put("a", &1, ME_TTL).await;
+        assert_eq!(local.get::<i32>("a").await, None);
+        local.or_local().put("a", &1, ME_TTL).await;
+        assert_eq!(local.or_local().get::<i32>("a").await, Some(1));
+
+        let shared = Cache::new(Arc::new(MemoryStore::default()));
+        shared.or_local().put("b", &2, ME_TTL).await;
+        assert_eq!(shared.get::<i32>("b").await, Some(2));
+    }
+
+    #[tokio::test]
+    async fn summaries_stored_after_an_invalidation_are_not_read() {
+        let cache = Cache::new(Arc::new(MemoryStore::default()));
+        let user = Uuid::new_v4();
+        let before = cache.summary_generation(user).await;
+        cache.invalidate_summaries(user).await;
+        // A summary computed before the write lands after the invalidation.
+        let stale = keys::daily_summaries(user, &before);
+        cache.put(&stale, &1, SUMMARY_TTL).await;
+
+        let current = cache.summary_generation(user).await;
+        assert_ne!(current, before);
+        let key = keys::daily_summaries(user, &current);
+        assert_eq!(cache.get::<i32>(&key).await, None);
+    }
+
+    #[tokio::test]
+    async fn disabled_cache_always_misses() {
+        let cache = Cache::default();
+        cache.put("a", &1, ME_TTL).await;
+        assert_eq!(cache.get::<i32>("a").await, None);
+    }
+}
@@ -0,0 +1,55 @@
+//! Redis-backed [`CacheStore`]. The connection manager reconnects on its own
+//! after Redis restarts.
+
+use std::time::Duration;
+
+use redis::aio::ConnectionManager;
+
+use super::CacheStore;
+
+pub struct RedisStore {
+    conn: ConnectionManager,
+}
+
+impl RedisStore {
+    pub async fn connect(url: &str) -> anyhow::Result<Self> {
+        let client = redis::Client::open(url)?;
+        let conn = ConnectionManager::new(client).await?;
+        Ok(Self { conn })
+    }
+}
+
+#[axum::async_trait]
+impl CacheStore for RedisStore {
+    async fn get(&self, keys: &[String]) -> anyhow::Result<Vec<Option<String>>> {
+        if keys.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut conn = self.conn.clone();
+        // MGET always replies with an array, even for a single key.
+        let values = redis::cmd("MGET").arg(keys).query_async(&mut conn).await?;
+        Ok(values)
+    }
+
+    async fn set(&self, entries: &[(String, String)], ttl: Duration) -> anyhow::Result<()> {
+        let mut conn = self.conn.clone();
+        let mut pipe = redis::pipe();
+        for (key, value) in entries {
+            pipe.set_ex(key, value, ttl.as_secs().max(1)).ignore();
+        }
+        pipe.query_async::<()>(&mut conn).await?;
+        Ok(())
+    }
+
+    async fn delete(&self, keys: &[String]) -> anyhow::Result<()> {
+        if keys.is_empty() {
+            return Ok(());
+        }
+        let mut conn = self.conn.clone();
+        redis::cmd("DEL")
+            .arg(keys)
+            .query_async::<()>(&mut conn)
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,43 @@
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+pub const MAX_CLIENTS_PER_COACH: i64 = 200;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+pub enum CoachingStatus {
+    /// Invited by the coach, waiting for the client's consent.
+    Pending,
+    Active,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct InviteClientRequest {
+    pub email: String,
+}
+
+/// A client as listed for their coach.
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct CoachClient {
+    pub client_id: Uuid,
+    pub email: String,
+    pub status: CoachingStatus,
+    #[serde(with = "time::serde::rfc3339")]
+    pub invited_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub accepted_at: Option<OffsetDateTime>,
+}
+
+/// A coach as listed for their client.
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct Coach {
+    pub coach_id: Uuid,
+    pub email: String,
+    pub status: CoachingStatus,
+    #[serde(with = "time::serde::rfc3339")]
+    pub invited_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub accepted_at: Option<OffsetDateTime>,
+}
@@ -0,0 +1,5 @@
+//! Coaches with read-only access to the meals of clients who consented.
+
+pub mod dto;
+pub mod repo;
+pub mod services;
@@ -0,0 +1,128 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::coaching::dto::{Coach, CoachClient, CoachingStatus};
+
+pub async fn client_count(db: &PgPool, coach_id: Uuid) -> anyhow::Result<i64> {
+    let count =
+        sqlx::query_scalar::<_, i64>(r#"SELECT COUNT(*) FROM coach_clients WHERE coach_id = $1"#)
+            .bind(coach_id)
+            .fetch_one(db)
+            .await?;
+    Ok(count)
+}
+
+pub async fn find_status(
+    db: &PgPool,
+    coach_id: Uuid,
+    client_id: Uuid,
+) -> anyhow::Result<Option<CoachingStatus>> {
+    let status = sqlx::query_scalar::<_, CoachingStatus>(
+        r#"SELECT status FROM coach_clients WHERE coach_id = $1 AND client_id = $2"#,
+    )
+    .bind(coach_id)
+    .bind(client_id)
+    .fetch_optional(db)
+    .await?;
+    Ok(status)
+}
+
+/// Records a pending invite; `None` if the coach already invited or coaches
+/// this client.
+pub async fn insert_invite(
+    db: &PgPool,
+    coach_id: Uuid,
+    client_id: Uuid,
+) -> anyhow::Result<Option<CoachClient>> {
+    let client = sqlx::query_as::<_, CoachClient>(
+        r#"
+        WITH inserted AS (
+            INSERT INTO coach_clients (coach_id, client_id)
+            VALUES ($1, $2)
+            ON CONFLICT DO NOTHING
+            RETURNING client_id, status, invited_at, accepted_at
+        )
+        SELECT i.client_id, u.email, i.status, i.invited_at, i.accepted_at
+        FROM inserted i
+        JOIN users u ON u.id = i.client_id
+        "#,
+    )
+    .bind(coach_id)
+    .bind(client_id)
+    .fetch_optional(db)
+    .await?;
+    Ok(client)
+}
+
+pub async fn list_clients(db: &PgPool, coach_id: Uuid) -> anyhow::Result<Vec<CoachClient>> {
+    let clients = sqlx::query_as::<_, CoachClient>(
+        r#"
+        SELECT c.client_id, u.email, c.status, c.invited_at, c.accepted_at
+        FROM coach_clients c
+        JOIN users u ON u.id = c.client_id
+        WHERE c.coach_id = $1
+        ORDER BY u.email, c.client_id
+        "#,
+    )
+    .bind(coach_id)
+    .fetch_all(db)
+    .await?;
+    Ok(clients)
+}
+
+pub async fn list_coaches(db: &PgPool, client_id: Uuid) -> anyhow::Result<Vec<Coach>> {
+    let coaches = sqlx::query_as::<_, Coach>(
+        r#"
+        SELECT c.coach_id, u.email, c.status, c.invited_at, c.accepted_at
+        FROM coach_clients c
+        JOIN users u ON u.id = c.coach_id
+        WHERE c.client_id = $1
+        ORDER BY c.invited_at, c.coach_id
+        "#,
+    )
+    .bind(client_id)
+    .fetch_all(db)
+    .await?;
+    Ok(coaches)
+}
+
+/// Records the client's consent to a pending invite. Returns `None` when
+/// there is no pending invite from `coach_id`.
+pub async fn accept_invite(
+    db: &PgPool,
+    client_id: Uuid,
+    coach_id: Uuid,
+) -> anyhow::Result<Option<Coach>> {
+    let coach = sqlx::query_as::<_, Coach>(
+        r#"
+        WITH accepted AS (
+            UPDATE coach_clients
+            SET status = 'active', accepted_at = NOW()
+            WHERE coach_id = $1 AND client_id = $2 AND status = 'pending'
+            RETURNING coach_id, status, invited_at, accepted_at
+        )
+        SELECT a.coach_id, u.email, a.status, a.invited_at, a.accepted_at
+        FROM accepted a
+        JOIN users u ON u.id = a.coach_id
+        "#,
+    )
+    .bind(coach_id)
+    .bind(client_id)
+    .fetch_optional(db)
+    .await?;
+    Ok(coach)
+}
+
+/// Ends the relationship whatever its status; either side may do this.
+pub async fn delete_relationship(
+    db: &PgPool,
+    coach_id: Uuid,
+    client_id: Uuid,
+) -> anyhow::Result<bool> {
+    let result = sqlx::query(r#"DELETE FROM coach_clients WHERE coach_id = $1 AND client_id = $2"#)
+        .bind(coach_id)
+        .bind(client_id)
+        .execute(db)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
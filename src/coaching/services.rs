@@ -0,0 +1,186 @@
+use axum::http::StatusCode;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    auth::repo as users_repo,
+    coaching::{
+        dto::{Coach, CoachClient, CoachingStatus, InviteClientRequest, MAX_CLIENTS_PER_COACH},
+        repo,
+    },
+    db::{AppState, RepoError},
+    error::ErrorCode,
+    meals::{
+        dto::{ListMealsQuery, MealDetails, MealResponse},
+        services as meals_services,
+    },
+    orgs::repo as orgs_repo,
+    routes::auth::is_valid_email,
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum CoachingError {
+    #[error("Client not found")]
+    ClientNotFound,
+    #[error("Coach not found")]
+    CoachNotFound,
+    #[error("Meal not found")]
+    MealNotFound,
+    #[error("{0}")]
+    Conflict(String),
+    #[error("{0}")]
+    Invalid(String),
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+impl From<RepoError> for CoachingError {
+    fn from(e: RepoError) -> Self {
+        CoachingError::Other(e.into())
+    }
+}
+
+impl CoachingError {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            CoachingError::ClientNotFound
+            | CoachingError::CoachNotFound
+            | CoachingError::MealNotFound => StatusCode::NOT_FOUND,
+            CoachingError::Conflict(_) => StatusCode::CONFLICT,
+            CoachingError::Invalid(_) => StatusCode::BAD_REQUEST,
+            CoachingError::Other(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            CoachingError::ClientNotFound => ErrorCode::CoachingClientNotFound,
+            CoachingError::CoachNotFound => ErrorCode::CoachNotFound,
+            CoachingError::MealNotFound => ErrorCode::MealNotFound,
+            CoachingError::Conflict(_) => ErrorCode::CoachingConflict,
+            CoachingError::Invalid(_) => ErrorCode::ValidationFailed,
+            CoachingError::Other(_) => ErrorCode::Internal,
+        }
+    }
+}
+
+/// Invites the user registered under `input.email` to become a client. Their
+/// meals stay hidden until they accept.
+pub async fn invite_client(
+    state: &AppState,
+    coach_id: Uuid,
+    input: InviteClientRequest,
+) -> Result<CoachClient, CoachingError> {
+    let email = input.email.trim().to_lowercase();
+    if !is_valid_email(&email) {
+        return Err(CoachingError::Invalid("Invalid email".to_string()));
+    }
+    let client = users_repo::find_by_email(&state.db, &email)
+        .await?
+        .ok_or(CoachingError::ClientNotFound)?;
+    // Users of other organizations don't exist as far as the coach can tell.
+    if !orgs_repo::same_org(&state.db, coach_id, client.id).await? {
+        return Err(CoachingError::ClientNotFound);
+    }
+    if client.id == coach_id {
+        return Err(CoachingError::Invalid(
+            "You can't coach yourself".to_string(),
+        ));
+    }
+    if repo::client_count(&state.db, coach_id).await? >= MAX_CLIENTS_PER_COACH {
+        return Err(CoachingError::Conflict(format!(
+            "A coach can have at most {} clients",
+            MAX_CLIENTS_PER_COACH
+        )));
+    }
+    let invite = repo::insert_invite(&state.db, coach_id, client.id)
+        .await?
+        .ok_or_else(|| CoachingError::Conflict("Already invited".to_string()))?;
+    info!(coach_id = %coach_id, client_id = %client.id, "coaching invite sent");
+    Ok(invite)
+}
+
+pub async fn list_clients(
+    state: &AppState,
+    coach_id: Uuid,
+) -> Result<Vec<CoachClient>, CoachingError> {
+    Ok(repo::list_clients(&state.db, coach_id).await?)
+}
+
+/// Cancels an invite or stops coaching a client.
+pub async fn remove_client(
+    state: &AppState,
+    coach_id: Uuid,
+    client_id: Uuid,
+) -> Result<(), CoachingError> {
+    if !repo::delete_relationship(&state.db, coach_id, client_id).await? {
+        return Err(CoachingError::ClientNotFound);
+    }
+    info!(coach_id = %coach_id, client_id = %client_id, "coaching ended by coach");
+    Ok(())
+}
+
+pub async fn list_client_meals(
+    state: &AppState,
+    coach_id: Uuid,
+    client_id: Uuid,
+    query: &ListMealsQuery,
+) -> Result<Vec<MealResponse>, CoachingError> {
+    ensure_active(state, coach_id, client_id).await?;
+    Ok(meals_services::list_client_meals(state, coach_id, client_id, query).await?)
+}
+
+pub async fn get_client_meal(
+    state: &AppState,
+    coach_id: Uuid,
+    client_id: Uuid,
+    meal_id: Uuid,
+) -> Result<MealDetails, CoachingError> {
+    ensure_active(state, coach_id, client_id).await?;
+    meals_services::get_client_meal_details(state, coach_id, client_id, meal_id)
+        .await?
+        .ok_or(CoachingError::MealNotFound)
+}
+
+/// Pending invites look the same as unknown clients, so a coach learns
+/// nothing about a client before they consent.
+async fn ensure_active(
+    state: &AppState,
+    coach_id: Uuid,
+    client_id: Uuid,
+) -> Result<(), CoachingError> {
+    match repo::find_status(&state.db, coach_id, client_id).await? {
+        Some(CoachingStatus::Active) => Ok(()),
+        _ => Err(CoachingError::ClientNotFound),
+    }
+}
+
+pub async fn list_coaches(state: &AppState, client_id: Uuid) -> Result<Vec<Coach>, CoachingError> {
+    Ok(repo::list_coaches(&state.db, client_id).await?)
+}
+
+/// The client's consent: from now on the coach can read their meals.
+pub async fn accept_coach(
+    state: &AppState,
+    client_id: Uuid,
+    coach_id: Uuid,
+) -> Result<Coach, CoachingError> {
+    let coach = repo::accept_invite(&state.db, client_id, coach_id)
+        .await?
+        .ok_or(CoachingError::CoachNotFound)?;
+    info!(coach_id = %coach_id, client_id = %client_id, "coaching invite accepted");
+    Ok(coach)
+}
+
+/// Declines an invite or withdraws consent; access ends immediately.
+pub async fn remove_coach(
+    state: &AppState,
+    client_id: Uuid,
+    coach_id: Uuid,
+) -> Result<(), CoachingError> {
+    if !repo::delete_relationship(&state.db, coach_id, client_id).await? {
+        return Err(CoachingError::CoachNotFound);
+    }
+    info!(coach_id = %coach_id, client_id = %client_id, "coaching ended by client");
+    Ok(())
+}
@@ -0,0 +1,1354 @@
+pub mod source;
+
+use std::{collections::HashMap, time::Duration};
+
+use axum::http::HeaderValue;
+use serde::Deserialize;
+
+pub use source::{ConfigError, ConfigSource};
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct JwtConfig {
+    pub secret: String,
+    pub issuer: String,
+    pub audience: String,
+    pub ttl_minutes: i64,
+    pub refresh_ttl_minutes: i64,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageBackend {
+    #[default]
+    S3,
+    /// In-process object store for local development and tests.
+    Memory,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct S3Config {
+    pub endpoint: Option<String>,
+    pub bucket: String,
+    pub region: String,
+    pub access_key: String,
+    pub secret_key: String,
+    pub use_path_style: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct UploadConfig {
+    /// Largest accepted single image, in decoded bytes.
+    pub max_image_bytes: usize,
+    /// Largest accepted sum of all images in one request, in decoded bytes.
+    pub max_request_bytes: usize,
+    /// How long an upload session accepts photos and can become a meal.
+    pub session_ttl_hours: i32,
+    /// Largest photo accepted through a chunked upload, in bytes.
+    pub max_chunked_image_bytes: usize,
+}
+
+impl Default for UploadConfig {
+    fn default() -> Self {
+        Self {
+            max_image_bytes: 10 * 1024 * 1024,
+            max_request_bytes: 40 * 1024 * 1024,
+            session_ttl_hours: 24,
+            max_chunked_image_bytes: 100 * 1024 * 1024,
+        }
+    }
+}
+
+/// Lifetime of presigned photo URLs.
+#[derive(Debug, Clone, Deserialize)]
+pub struct PhotoUrlConfig {
+    /// How long a presigned URL stays valid.
+    pub ttl_secs: u64,
+    /// URLs are reused from the cache until this much of their lifetime is
+    /// left, so a client never gets one that is about to expire.
+    pub refresh_margin_secs: u64,
+}
+
+impl PhotoUrlConfig {
+    pub fn ttl(&self) -> Duration {
+        Duration::from_secs(self.ttl_secs)
+    }
+
+    /// How long a freshly presigned URL may be served from the cache.
+    pub fn cache_ttl(&self) -> Duration {
+        Duration::from_secs(self.ttl_secs.saturating_sub(self.refresh_margin_secs))
+    }
+}
+
+impl Default for PhotoUrlConfig {
+    fn default() -> Self {
+        Self {
+            ttl_secs: 30 * 60,
+            refresh_margin_secs: 10 * 60,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct HttpConfig {
+    pub host: String,
+    pub port: u16,
+    /// Listens on this Unix domain socket instead of `host:port`.
+    pub unix_socket: Option<String>,
+    /// PEM certificate chain and private key; TLS is off unless both are set.
+    pub tls_cert_path: Option<String>,
+    pub tls_key_path: Option<String>,
+    /// Largest request body outside the upload routes, which derive their
+    /// limits from [`UploadConfig`] and the import size cap.
+    pub max_body_bytes: usize,
+    /// Requests still unanswered after this get `408`.
+    pub request_timeout_secs: u64,
+    /// Same for the upload routes, which may stream large bodies over slow
+    /// mobile networks.
+    pub upload_timeout_secs: u64,
+    /// Requests handled at once before new ones get `503`; 0 is unlimited.
+    /// Health checks and docs are not counted.
+    pub max_in_flight: usize,
+    /// Budget of the upload routes, on top of the general one.
+    pub max_uploads_in_flight: usize,
+    /// `Retry-After` of shed requests.
+    pub shed_retry_after_secs: u64,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            host: "0.0.0.0".into(),
+            port: 8080,
+            unix_socket: None,
+            tls_cert_path: None,
+            tls_key_path: None,
+            max_body_bytes: 1024 * 1024,
+            request_timeout_secs: 30,
+            upload_timeout_secs: 300,
+            max_in_flight: 512,
+            max_uploads_in_flight: 32,
+            shed_retry_after_secs: 1,
+        }
+    }
+}
+
+/// Headers set on every response; see [`crate::security_headers`].
+#[derive(Debug, Clone, Deserialize)]
+pub struct SecurityHeadersConfig {
+    /// `max-age` of `Strict-Transport-Security`; 0 leaves the header out.
+    pub hsts_max_age_secs: u64,
+    /// `Referrer-Policy`; `None` leaves the header out.
+    pub referrer_policy: Option<String>,
+    /// `Content-Security-Policy` of HTML responses; `None` leaves it out.
+    pub content_security_policy: Option<String>,
+}
+
+/// Enough for the Swagger UI page: its script and stylesheet come from
+/// unpkg, the spec and "try it out" calls go to this origin.
+pub const DEFAULT_CONTENT_SECURITY_POLICY: &str = "default-src 'none'; \
+    script-src 'self' https://unpkg.com; style-src 'self' 'unsafe-inline' https://unpkg.com; \
+    img-src 'self' data:; connect-src 'self'; base-uri 'none'; form-action 'none'; \
+    frame-ancestors 'none'";
+
+impl Default for SecurityHeadersConfig {
+    fn default() -> Self {
+        Self {
+            hsts_max_age_secs: 60 * 60 * 24 * 365,
+            referrer_policy: Some("no-referrer".into()),
+            content_security_policy: Some(DEFAULT_CONTENT_SECURITY_POLICY.into()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TranscodeConfig {
+    /// Command converting HEIC to JPEG, with `{input}`/`{output}` placeholders.
+    /// Transcoding is disabled when unset.
+    pub heic_command: Option<String>,
+    /// Also store the original HEIC next to the converted JPEG.
+    pub keep_original: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DuplicatesConfig {
+    /// Command reducing an image to a raw 9×8 grayscale bitmap, with
+    /// `{input}`/`{output}` placeholders. Duplicate detection is disabled
+    /// when unset.
+    pub fingerprint_command: Option<String>,
+    /// How far back earlier photos are compared.
+    pub window_hours: i32,
+    /// Fingerprints at most this many bits apart count as the same photo.
+    pub max_distance: u32,
+    /// Reject the meal instead of only flagging it.
+    pub block: bool,
+    /// Fingerprint commands running at once across all requests.
+    pub fingerprint_concurrency: usize,
+}
+
+impl Default for DuplicatesConfig {
+    fn default() -> Self {
+        Self {
+            fingerprint_command: None,
+            window_hours: 24,
+            max_distance: 6,
+            block: false,
+            fingerprint_concurrency: 4,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct StorageRetryConfig {
+    /// Total tries per storage call, including the first one.
+    pub max_attempts: u32,
+    pub base_delay_ms: u64,
+    pub max_delay_ms: u64,
+    /// Fraction of each delay that is randomized, between 0 and 1.
+    pub jitter: f64,
+}
+
+impl Default for StorageRetryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay_ms: 100,
+            max_delay_ms: 2000,
+            jitter: 0.2,
+        }
+    }
+}
+
+/// See [`crate::breaker`].
+#[derive(Debug, Clone, Deserialize)]
+pub struct CircuitBreakerConfig {
+    /// Consecutive failures that open the circuit; 0 disables the breaker.
+    pub failure_threshold: u32,
+    /// How long an open circuit refuses calls before a probe is let through.
+    pub open_secs: u64,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 5,
+            open_secs: 30,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AnalyzerProvider {
+    /// Analysis jobs complete without producing nutrition.
+    #[default]
+    None,
+    /// Fixed estimate, for local development and tests.
+    Mock,
+    OpenAi,
+    Anthropic,
+    /// Local Ollama (or compatible) server.
+    Ollama,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AnalyzerConfig {
+    pub provider: AnalyzerProvider,
+    pub api_key: Option<String>,
+    /// Provider default when unset.
+    pub model: Option<String>,
+    /// Provider default when unset.
+    pub base_url: Option<String>,
+    pub timeout_secs: u64,
+    pub breaker: CircuitBreakerConfig,
+    /// Prices by model name prefix; models without one cost nothing.
+    pub prices: HashMap<String, ModelPrice>,
+    /// AI spend per user and calendar month (UTC) after which meals are no
+    /// longer analyzed automatically; unlimited when unset.
+    pub monthly_budget_usd: Option<f64>,
+    /// Check that photos show food before analyzing them.
+    pub screening: bool,
+    /// Model for the screening; the analysis model when unset.
+    pub screening_model: Option<String>,
+}
+
+impl Default for AnalyzerConfig {
+    fn default() -> Self {
+        Self {
+            provider: AnalyzerProvider::None,
+            api_key: None,
+            model: None,
+            base_url: None,
+            timeout_secs: 60,
+            breaker: CircuitBreakerConfig {
+                failure_threshold: 5,
+                open_secs: 60,
+            },
+            prices: ModelPrice::defaults(),
+            monthly_budget_usd: None,
+            screening: false,
+            screening_model: None,
+        }
+    }
+}
+
+impl AnalyzerConfig {
+    /// Price of the longest configured name `model` starts with, so
+    /// `gpt-4o-mini` also prices `gpt-4o-mini-2024-07-18`.
+    pub fn price(&self, model: &str) -> Option<ModelPrice> {
+        self.prices
+            .iter()
+            .filter(|(name, _)| model.starts_with(name.as_str()))
+            .max_by_key(|(name, _)| name.len())
+            .map(|(_, price)| *price)
+    }
+}
+
+/// USD per million tokens.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct ModelPrice {
+    pub input: f64,
+    pub output: f64,
+}
+
+impl ModelPrice {
+    /// List prices of the providers' default models.
+    fn defaults() -> HashMap<String, ModelPrice> {
+        [
+            ("gpt-4o-mini", 0.15, 0.60),
+            ("gpt-4o", 2.50, 10.00),
+            ("claude-3-5-sonnet", 3.00, 15.00),
+            ("claude-3-5-haiku", 0.80, 4.00),
+        ]
+        .into_iter()
+        .map(|(name, input, output)| (name.to_string(), ModelPrice { input, output }))
+        .collect()
+    }
+
+    /// Parses `model=input/output` pairs separated by `;`, e.g.
+    /// `gpt-4o-mini=0.15/0.60`. Returns the pairs that don't parse as the
+    /// error.
+    pub fn parse_overrides(spec: &str) -> Result<HashMap<String, ModelPrice>, String> {
+        let mut prices = HashMap::new();
+        let mut invalid = Vec::new();
+        for pair in spec.split(';').map(str::trim).filter(|p| !p.is_empty()) {
+            let parsed = pair.split_once('=').and_then(|(name, price)| {
+                let (input, output) = price.split_once('/')?;
+                let price = ModelPrice {
+                    input: input.trim().parse().ok()?,
+                    output: output.trim().parse().ok()?,
+                };
+                let valid = [price.input, price.output]
+                    .iter()
+                    .all(|p| p.is_finite() && *p >= 0.0);
+                (valid && !name.trim().is_empty()).then(|| (name.trim().to_string(), price))
+            });
+            match parsed {
+                Some((name, price)) => {
+                    prices.insert(name, price);
+                }
+                None => invalid.push(pair),
+            }
+        }
+        if invalid.is_empty() {
+            Ok(prices)
+        } else {
+            Err(format!(
+                "expected model=input/output pairs, got {}",
+                invalid.join(", ")
+            ))
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct JobsConfig {
+    /// Number of worker tasks; 0 disables job processing in this process.
+    pub workers: usize,
+    pub poll_interval_ms: u64,
+    pub max_attempts: i32,
+    /// First retry delay; doubles on every further attempt.
+    pub retry_base_secs: u64,
+    /// A running job locked for longer than this is assumed abandoned.
+    pub lock_timeout_secs: u64,
+}
+
+impl Default for JobsConfig {
+    fn default() -> Self {
+        Self {
+            workers: 2,
+            poll_interval_ms: 1000,
+            max_attempts: 5,
+            retry_base_secs: 10,
+            lock_timeout_secs: 300,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TasksConfig {
+    /// How long background tasks get to finish their current work on shutdown.
+    pub shutdown_grace_secs: u64,
+    /// Photos detached from any meal are deleted once older than this.
+    pub orphan_photo_grace_hours: i32,
+    /// Expired or revoked share links are kept this long, then pruned.
+    pub share_retention_days: i32,
+    /// Meals older than this move to the archive; 0 keeps them all.
+    pub meal_archive_after_days: i32,
+    /// Tombstones of deleted meals are kept this long for offline clients.
+    pub sync_tombstone_retention_days: i32,
+    /// How often feature flags are reloaded from the database.
+    pub flags_refresh_secs: u64,
+}
+
+impl Default for TasksConfig {
+    fn default() -> Self {
+        Self {
+            shutdown_grace_secs: 30,
+            orphan_photo_grace_hours: 24,
+            share_retention_days: 30,
+            meal_archive_after_days: 0,
+            sync_tombstone_retention_days: 90,
+            flags_refresh_secs: 30,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CronConfig {
+    /// How often each instance checks whether a scheduled job is due.
+    pub tick_secs: u64,
+    /// A run still marked running after this is assumed dead and may start
+    /// again on another instance.
+    pub lock_timeout_secs: u64,
+    /// Schedule overrides by job name: a cron expression or `off`.
+    pub schedules: HashMap<String, String>,
+}
+
+impl Default for CronConfig {
+    fn default() -> Self {
+        Self {
+            tick_secs: 30,
+            lock_timeout_secs: 3600,
+            schedules: HashMap::new(),
+        }
+    }
+}
+
+impl CronConfig {
+    /// Parses `name=expression` pairs separated by `;` (cron expressions
+    /// contain commas). Malformed pairs are skipped.
+    pub fn parse_schedules(spec: &str) -> HashMap<String, String> {
+        spec.split(';')
+            .filter_map(|pair| {
+                let (name, expr) = pair.split_once('=')?;
+                let (name, expr) = (name.trim(), expr.trim());
+                (!name.is_empty() && !expr.is_empty()).then(|| (name.to_string(), expr.to_string()))
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct EventsConfig {
+    /// Idle poll interval of the outbox relay.
+    pub poll_interval_ms: u64,
+    /// Events claimed per relay round.
+    pub batch_size: i64,
+    /// Delivered events are kept this long, then pruned.
+    pub retention_days: i32,
+    /// External endpoint receiving every event as JSON; none when unset.
+    pub sink_url: Option<String>,
+    pub sink_timeout_secs: u64,
+}
+
+impl Default for EventsConfig {
+    fn default() -> Self {
+        Self {
+            poll_interval_ms: 1000,
+            batch_size: 100,
+            retention_days: 7,
+            sink_url: None,
+            sink_timeout_secs: 10,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct WebhooksConfig {
+    pub timeout_secs: u64,
+    /// Deliveries still failing after this many attempts are given up on.
+    pub max_attempts: i32,
+    /// Accepts plain `http` and local or private hosts; for development only.
+    pub allow_insecure: bool,
+}
+
+impl Default for WebhooksConfig {
+    fn default() -> Self {
+        Self {
+            timeout_secs: 10,
+            max_attempts: 8,
+            allow_insecure: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PushConfig {
+    /// Logs pushes instead of sending them, for development.
+    pub dry_run: bool,
+    /// Firebase service account key file; Android pushes are off without it.
+    pub fcm_credentials: Option<String>,
+    /// APNs `.p8` signing key; iOS pushes are off without it.
+    pub apns_key_path: Option<String>,
+    pub apns_key_id: Option<String>,
+    pub apns_team_id: Option<String>,
+    /// Bundle id of the iOS app.
+    pub apns_topic: Option<String>,
+    /// Sends to the APNs development environment.
+    pub apns_sandbox: bool,
+    pub timeout_secs: u64,
+}
+
+impl Default for PushConfig {
+    fn default() -> Self {
+        Self {
+            dry_run: false,
+            fcm_credentials: None,
+            apns_key_path: None,
+            apns_key_id: None,
+            apns_team_id: None,
+            apns_topic: None,
+            apns_sandbox: false,
+            timeout_secs: 10,
+        }
+    }
+}
+
+/// Relative weight of each component of the global health score; 0 drops a
+/// component from the formula.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ScoreWeights {
+    pub calories: f64,
+    pub protein: f64,
+    pub fat: f64,
+    pub carbs: f64,
+    pub fiber: f64,
+    pub sugar: f64,
+    pub sodium: f64,
+}
+
+impl Default for ScoreWeights {
+    fn default() -> Self {
+        Self {
+            calories: 2.0,
+            protein: 1.5,
+            fat: 1.0,
+            carbs: 0.5,
+            fiber: 1.0,
+            sugar: 1.5,
+            sodium: 1.5,
+        }
+    }
+}
+
+impl ScoreWeights {
+    /// Overrides weights from a `name=value,...` list, e.g. `sugar=2,carbs=0`.
+    /// Unknown names and unparsable values are ignored.
+    pub fn apply_overrides(&mut self, spec: &str) {
+        for pair in spec.split(',') {
+            let Some((name, value)) = pair.split_once('=') else {
+                continue;
+            };
+            let Ok(value) = value.trim().parse::<f64>() else {
+                continue;
+            };
+            let value = value.max(0.0);
+            match name.trim() {
+                "calories" => self.calories = value,
+                "protein" => self.protein = value,
+                "fat" => self.fat = value,
+                "carbs" => self.carbs = value,
+                "fiber" => self.fiber = value,
+                "sugar" => self.sugar = value,
+                "sodium" => self.sodium = value,
+                _ => {}
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ScoreConfig {
+    pub weights: ScoreWeights,
+    /// Daily targets are split evenly over this many meals.
+    pub meals_per_day: f64,
+}
+
+impl Default for ScoreConfig {
+    fn default() -> Self {
+        Self {
+            weights: ScoreWeights::default(),
+            meals_per_day: 3.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct StatsConfig {
+    /// How long computed stats are served from cache; 0 disables caching.
+    pub cache_ttl_secs: u64,
+}
+
+impl Default for StatsConfig {
+    fn default() -> Self {
+        Self {
+            cache_ttl_secs: 300,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct FoodsConfig {
+    pub off_base_url: String,
+    /// Enables `/foods/search` when set.
+    pub usda_api_key: Option<String>,
+    pub usda_base_url: String,
+    pub timeout_secs: u64,
+    /// How long a found food is served from `foods_cache`.
+    pub cache_ttl_hours: i64,
+    /// How long "not found" answers are cached.
+    pub miss_ttl_hours: i64,
+    /// How long search result lists are cached per query.
+    pub search_ttl_hours: i64,
+}
+
+impl Default for FoodsConfig {
+    fn default() -> Self {
+        Self {
+            off_base_url: "https://world.openfoodfacts.org".into(),
+            usda_api_key: None,
+            usda_base_url: "https://api.nal.usda.gov/fdc".into(),
+            timeout_secs: 10,
+            cache_ttl_hours: 24 * 7,
+            miss_ttl_hours: 24,
+            search_ttl_hours: 24,
+        }
+    }
+}
+
+/// An OAuth app registered with a health data provider.
+#[derive(Debug, Clone, Deserialize)]
+pub struct OAuthAppConfig {
+    pub client_id: String,
+    pub client_secret: String,
+    /// Our `/integrations/:provider/callback`, as registered with the app.
+    pub redirect_url: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct IntegrationsConfig {
+    pub timeout_secs: u64,
+    /// Days pulled by the first sync of a connection.
+    pub backfill_days: i64,
+    /// Enables Google Fit when set.
+    pub google_fit: Option<OAuthAppConfig>,
+    /// Enables Fitbit when set.
+    pub fitbit: Option<OAuthAppConfig>,
+}
+
+impl Default for IntegrationsConfig {
+    fn default() -> Self {
+        Self {
+            timeout_secs: 10,
+            backfill_days: 7,
+            google_fit: None,
+            fitbit: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct GeocodingConfig {
+    /// Nominatim (or compatible) server; reverse geocoding is off when unset.
+    pub nominatim_url: Option<String>,
+    pub timeout_secs: u64,
+}
+
+impl Default for GeocodingConfig {
+    fn default() -> Self {
+        Self {
+            nominatim_url: None,
+            timeout_secs: 10,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AnalyticsConfig {
+    /// Receives app analytics instead of the database when set.
+    pub sink_url: Option<String>,
+    pub sink_timeout_secs: u64,
+    /// Monthly partitions entirely older than this are dropped; 0 keeps
+    /// them.
+    pub retention_days: u32,
+}
+
+impl Default for AnalyticsConfig {
+    fn default() -> Self {
+        Self {
+            sink_url: None,
+            sink_timeout_secs: 10,
+            retention_days: 365,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ResearchConfig {
+    /// Keys the pseudonyms of research exports; they are off when unset.
+    /// Keeping the key keeps pseudonyms stable across exports.
+    pub pseudonym_key: Option<String>,
+    /// Least k a research export can be requested with, and the default.
+    pub min_k: u32,
+}
+
+impl Default for ResearchConfig {
+    fn default() -> Self {
+        Self {
+            pseudonym_key: None,
+            min_k: 5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BillingConfig {
+    /// Photo analyses a free user gets per UTC month.
+    pub free_monthly_analyses: u32,
+    /// Signing secret of the Stripe webhook endpoint; it answers `503`
+    /// when unset.
+    pub stripe_webhook_secret: Option<String>,
+    /// How far a `Stripe-Signature` timestamp may be from now.
+    pub stripe_webhook_tolerance_secs: u64,
+}
+
+impl Default for BillingConfig {
+    fn default() -> Self {
+        Self {
+            free_monthly_analyses: 30,
+            stripe_webhook_secret: None,
+            stripe_webhook_tolerance_secs: 300,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AppConfig {
+    pub database_url: String,
+    /// Fail startup when migrations can't be applied, instead of logging a
+    /// warning and serving with the schema as it is.
+    pub strict_migrations: bool,
+    /// Scope user requests to `app.current_user_id` so the row-level
+    /// security policies from migration 36 apply; see [`AppState::begin_as`].
+    ///
+    /// [`AppState::begin_as`]: crate::db::AppState::begin_as
+    pub row_level_security: bool,
+    /// Enables cross-instance fan-out of realtime events when set.
+    pub redis_url: Option<String>,
+    pub jwt: JwtConfig,
+    pub storage_backend: StorageBackend,
+    pub s3: S3Config,
+    pub storage_retry: StorageRetryConfig,
+    pub storage_breaker: CircuitBreakerConfig,
+    pub uploads: UploadConfig,
+    pub photo_urls: PhotoUrlConfig,
+    pub http: HttpConfig,
+    pub security_headers: SecurityHeadersConfig,
+    pub transcode: TranscodeConfig,
+    pub duplicates: DuplicatesConfig,
+    pub jobs: JobsConfig,
+    pub tasks: TasksConfig,
+    pub cron: CronConfig,
+    pub events: EventsConfig,
+    pub webhooks: WebhooksConfig,
+    pub push: PushConfig,
+    pub analyzer: AnalyzerConfig,
+    pub score: ScoreConfig,
+    pub stats: StatsConfig,
+    pub foods: FoodsConfig,
+    pub integrations: IntegrationsConfig,
+    pub geocoding: GeocodingConfig,
+    pub analytics: AnalyticsConfig,
+    pub research: ResearchConfig,
+    pub billing: BillingConfig,
+}
+
+impl AppConfig {
+    /// Reads the environment over the optional `CONFIG_FILE`; see
+    /// [`source`] for how the two are merged.
+    pub fn load() -> Result<Self, ConfigError> {
+        Self::from_source(ConfigSource::load()?)
+    }
+
+    /// Builds the configuration, failing with every missing or invalid
+    /// setting at once rather than the first one.
+    pub fn from_source(src: ConfigSource) -> Result<Self, ConfigError> {
+        let database_url = src.required("DATABASE_URL");
+        let strict_migrations = src.flag("STRICT_MIGRATIONS", false);
+        let row_level_security = src.flag("DB_ROW_LEVEL_SECURITY", false);
+        let redis_url = src.get("REDIS_URL");
+        let jwt = JwtConfig {
+            secret: src.required("JWT_SECRET"),
+            issuer: src.string("JWT_ISSUER", "mealmind"),
+            audience: src.string("JWT_AUDIENCE", "mealmind-users"),
+            ttl_minutes: src.parse("JWT_TTL_MINUTES", 60),
+            refresh_ttl_minutes: src.parse("JWT_REFRESH_TTL_MINUTES", 60 * 24 * 14),
+        };
+        let storage_backend = src.choice(
+            "STORAGE_BACKEND",
+            &[
+                ("s3", StorageBackend::S3),
+                ("memory", StorageBackend::Memory),
+            ],
+            StorageBackend::S3,
+        );
+        let (access_key, minio_user) = (src.get("S3_ACCESS_KEY"), src.get("MINIO_ROOT_USER"));
+        let (secret_key, minio_password) =
+            (src.get("S3_SECRET_KEY"), src.get("MINIO_ROOT_PASSWORD"));
+        let s3 = S3Config {
+            endpoint: src.get("S3_ENDPOINT"),
+            bucket: src.string("S3_BUCKET", "mealmind"),
+            region: src.string("S3_REGION", "us-east-1"),
+            access_key: access_key.or(minio_user).unwrap_or_default(),
+            secret_key: secret_key.or(minio_password).unwrap_or_default(),
+            use_path_style: src.flag("S3_USE_PATH_STYLE", false),
+        };
+        let retry_defaults = StorageRetryConfig::default();
+        let storage_retry = StorageRetryConfig {
+            max_attempts: src
+                .parse("STORAGE_RETRY_ATTEMPTS", retry_defaults.max_attempts)
+                .max(1),
+            base_delay_ms: src.parse("STORAGE_RETRY_BASE_MS", retry_defaults.base_delay_ms),
+            max_delay_ms: src.parse("STORAGE_RETRY_MAX_MS", retry_defaults.max_delay_ms),
+            jitter: src
+                .parse("STORAGE_RETRY_JITTER", retry_defaults.jitter)
+                .clamp(0.0, 1.0),
+        };
+        let storage_breaker = breaker_config(&src, "STORAGE", CircuitBreakerConfig::default());
+        let upload_defaults = UploadConfig::default();
+        let uploads = UploadConfig {
+            max_image_bytes: src.parse("UPLOAD_MAX_IMAGE_BYTES", upload_defaults.max_image_bytes),
+            max_request_bytes: src.parse(
+                "UPLOAD_MAX_REQUEST_BYTES",
+                upload_defaults.max_request_bytes,
+            ),
+            session_ttl_hours: src.parse(
+                "UPLOAD_SESSION_TTL_HOURS",
+                upload_defaults.session_ttl_hours,
+            ),
+            max_chunked_image_bytes: src.parse(
+                "UPLOAD_MAX_CHUNKED_IMAGE_BYTES",
+                upload_defaults.max_chunked_image_bytes,
+            ),
+        };
+        if uploads.session_ttl_hours < 1 {
+            src.problem("UPLOAD_SESSION_TTL_HOURS", "must be at least 1".to_string());
+        }
+        let photo_url_defaults = PhotoUrlConfig::default();
+        let photo_urls = PhotoUrlConfig {
+            ttl_secs: src.parse("PHOTO_URL_TTL_SECS", photo_url_defaults.ttl_secs),
+            refresh_margin_secs: src.parse(
+                "PHOTO_URL_REFRESH_MARGIN_SECS",
+                photo_url_defaults.refresh_margin_secs,
+            ),
+        };
+        // SigV4 presigned URLs are valid for at most 7 days.
+        if !(60..=7 * 24 * 3600).contains(&photo_urls.ttl_secs) {
+            src.problem(
+                "PHOTO_URL_TTL_SECS",
+                "must be between 60 and 604800".to_string(),
+            );
+        } else if photo_urls.refresh_margin_secs >= photo_urls.ttl_secs {
+            src.problem(
+                "PHOTO_URL_REFRESH_MARGIN_SECS",
+                "must be less than PHOTO_URL_TTL_SECS".to_string(),
+            );
+        }
+        let http_defaults = HttpConfig::default();
+        let http = HttpConfig {
+            host: src.string("APP_HOST", &http_defaults.host),
+            port: src.parse("APP_PORT", http_defaults.port),
+            unix_socket: src.get("APP_UNIX_SOCKET"),
+            tls_cert_path: src.get("TLS_CERT_PATH"),
+            tls_key_path: src.get("TLS_KEY_PATH"),
+            max_body_bytes: src.parse("HTTP_MAX_BODY_BYTES", http_defaults.max_body_bytes),
+            request_timeout_secs: src
+                .parse(
+                    "HTTP_REQUEST_TIMEOUT_SECS",
+                    http_defaults.request_timeout_secs,
+                )
+                .max(1),
+            upload_timeout_secs: src
+                .parse(
+                    "HTTP_UPLOAD_TIMEOUT_SECS",
+                    http_defaults.upload_timeout_secs,
+                )
+                .max(1),
+            max_in_flight: src.parse("HTTP_MAX_IN_FLIGHT", http_defaults.max_in_flight),
+            max_uploads_in_flight: src.parse(
+                "HTTP_MAX_UPLOADS_IN_FLIGHT",
+                http_defaults.max_uploads_in_flight,
+            ),
+            shed_retry_after_secs: src.parse(
+                "HTTP_SHED_RETRY_AFTER_SECS",
+                http_defaults.shed_retry_after_secs,
+            ),
+        };
+        match (&http.tls_cert_path, &http.tls_key_path) {
+            (Some(_), None) => src.problem("TLS_KEY_PATH", "is required with TLS_CERT_PATH".into()),
+            (None, Some(_)) => src.problem("TLS_CERT_PATH", "is required with TLS_KEY_PATH".into()),
+            _ => {}
+        }
+        let security_defaults = SecurityHeadersConfig::default();
+        // `off` drops a header; anything else must be a valid header value.
+        let header = |name: &str, default: Option<String>| match src.get(name) {
+            Some(value) if value.trim().eq_ignore_ascii_case("off") => None,
+            Some(value) if HeaderValue::from_str(&value).is_err() => {
+                src.problem(name, "is not a valid header value".into());
+                default
+            }
+            Some(value) => Some(value),
+            None => default,
+        };
+        let security_headers = SecurityHeadersConfig {
+            hsts_max_age_secs: src.parse(
+                "SECURITY_HSTS_MAX_AGE_SECS",
+                security_defaults.hsts_max_age_secs,
+            ),
+            referrer_policy: header(
+                "SECURITY_REFERRER_POLICY",
+                security_defaults.referrer_policy,
+            ),
+            content_security_policy: header(
+                "SECURITY_CSP",
+                security_defaults.content_security_policy,
+            ),
+        };
+        let transcode = TranscodeConfig {
+            heic_command: src.get("HEIC_TRANSCODE_CMD"),
+            keep_original: src.flag("HEIC_KEEP_ORIGINAL", false),
+        };
+        let duplicate_defaults = DuplicatesConfig::default();
+        let duplicates = DuplicatesConfig {
+            fingerprint_command: src.get("DUPLICATE_FINGERPRINT_CMD"),
+            window_hours: src.parse("DUPLICATE_WINDOW_HOURS", duplicate_defaults.window_hours),
+            max_distance: src.parse("DUPLICATE_MAX_DISTANCE", duplicate_defaults.max_distance),
+            block: src.flag("DUPLICATE_BLOCK", duplicate_defaults.block),
+            fingerprint_concurrency: src.parse(
+                "DUPLICATE_FINGERPRINT_CONCURRENCY",
+                duplicate_defaults.fingerprint_concurrency,
+            ),
+        };
+        if duplicates.max_distance > 64 {
+            src.problem("DUPLICATE_MAX_DISTANCE", "must be at most 64".to_string());
+        }
+        if duplicates.fingerprint_concurrency == 0 {
+            src.problem(
+                "DUPLICATE_FINGERPRINT_CONCURRENCY",
+                "must be at least 1".to_string(),
+            );
+        }
+        let job_defaults = JobsConfig::default();
+        let jobs = JobsConfig {
+            workers: src.parse("JOB_WORKERS", job_defaults.workers),
+            poll_interval_ms: src.parse("JOB_POLL_INTERVAL_MS", job_defaults.poll_interval_ms),
+            max_attempts: src
+                .parse("JOB_MAX_ATTEMPTS", job_defaults.max_attempts)
+                .max(1),
+            retry_base_secs: src.parse("JOB_RETRY_BASE_SECS", job_defaults.retry_base_secs),
+            lock_timeout_secs: src.parse("JOB_LOCK_TIMEOUT_SECS", job_defaults.lock_timeout_secs),
+        };
+        let task_defaults = TasksConfig::default();
+        let tasks = TasksConfig {
+            shutdown_grace_secs: src
+                .parse("SHUTDOWN_GRACE_SECS", task_defaults.shutdown_grace_secs),
+            orphan_photo_grace_hours: src
+                .parse(
+                    "ORPHAN_PHOTO_GRACE_HOURS",
+                    task_defaults.orphan_photo_grace_hours,
+                )
+                .max(0),
+            share_retention_days: src
+                .parse("SHARE_RETENTION_DAYS", task_defaults.share_retention_days)
+                .max(0),
+            meal_archive_after_days: src
+                .parse(
+                    "MEAL_ARCHIVE_AFTER_DAYS",
+                    task_defaults.meal_archive_after_days,
+                )
+                .max(0),
+            sync_tombstone_retention_days: src
+                .parse(
+                    "SYNC_TOMBSTONE_RETENTION_DAYS",
+                    task_defaults.sync_tombstone_retention_days,
+                )
+                .max(0),
+            flags_refresh_secs: src.parse("FLAGS_REFRESH_SECS", task_defaults.flags_refresh_secs),
+        };
+        let cron_defaults = CronConfig::default();
+        let cron = CronConfig {
+            tick_secs: src.parse("CRON_TICK_SECS", cron_defaults.tick_secs).max(1),
+            lock_timeout_secs: src.parse("CRON_LOCK_TIMEOUT_SECS", cron_defaults.lock_timeout_secs),
+            schedules: src
+                .get("CRON_SCHEDULES")
+                .map(|spec| CronConfig::parse_schedules(&spec))
+                .unwrap_or_default(),
+        };
+        let event_defaults = EventsConfig::default();
+        let events = EventsConfig {
+            poll_interval_ms: src.parse("EVENTS_POLL_INTERVAL_MS", event_defaults.poll_interval_ms),
+            batch_size: src
+                .parse("EVENTS_BATCH_SIZE", event_defaults.batch_size)
+                .max(1),
+            retention_days: src
+                .parse("EVENTS_RETENTION_DAYS", event_defaults.retention_days)
+                .max(0),
+            sink_url: src.get("EVENTS_SINK_URL"),
+            sink_timeout_secs: src
+                .parse("EVENTS_SINK_TIMEOUT_SECS", event_defaults.sink_timeout_secs),
+        };
+        let webhook_defaults = WebhooksConfig::default();
+        let webhooks = WebhooksConfig {
+            timeout_secs: src.parse("WEBHOOK_TIMEOUT_SECS", webhook_defaults.timeout_secs),
+            max_attempts: src
+                .parse("WEBHOOK_MAX_ATTEMPTS", webhook_defaults.max_attempts)
+                .max(1),
+            allow_insecure: src.flag("WEBHOOK_ALLOW_INSECURE", webhook_defaults.allow_insecure),
+        };
+        let push = PushConfig {
+            dry_run: src.flag("PUSH_DRY_RUN", false),
+            fcm_credentials: src.get("PUSH_FCM_CREDENTIALS"),
+            apns_key_path: src.get("PUSH_APNS_KEY_PATH"),
+            apns_key_id: src.get("PUSH_APNS_KEY_ID"),
+            apns_team_id: src.get("PUSH_APNS_TEAM_ID"),
+            apns_topic: src.get("PUSH_APNS_TOPIC"),
+            apns_sandbox: src.flag("PUSH_APNS_SANDBOX", false),
+            timeout_secs: src.parse("PUSH_TIMEOUT_SECS", PushConfig::default().timeout_secs),
+        };
+        if push.apns_key_path.is_some() {
+            for (name, value) in [
+                ("PUSH_APNS_KEY_ID", &push.apns_key_id),
+                ("PUSH_APNS_TEAM_ID", &push.apns_team_id),
+                ("PUSH_APNS_TOPIC", &push.apns_topic),
+            ] {
+                if value.is_none() {
+                    src.problem(name, "is required with PUSH_APNS_KEY_PATH".to_string());
+                }
+            }
+        }
+        let provider = src.choice(
+            "ANALYZER_PROVIDER",
+            &[
+                ("none", AnalyzerProvider::None),
+                ("mock", AnalyzerProvider::Mock),
+                ("openai", AnalyzerProvider::OpenAi),
+                ("anthropic", AnalyzerProvider::Anthropic),
+                ("ollama", AnalyzerProvider::Ollama),
+            ],
+            AnalyzerProvider::None,
+        );
+        let (openai_key, anthropic_key) = (src.get("OPENAI_API_KEY"), src.get("ANTHROPIC_API_KEY"));
+        let provider_key = match provider {
+            AnalyzerProvider::OpenAi => openai_key,
+            AnalyzerProvider::Anthropic => anthropic_key,
+            _ => None,
+        };
+        let analyzer = AnalyzerConfig {
+            provider,
+            api_key: src.get("ANALYZER_API_KEY").or(provider_key),
+            model: src.get("ANALYZER_MODEL"),
+            base_url: src.get("ANALYZER_BASE_URL"),
+            timeout_secs: src.parse(
+                "ANALYZER_TIMEOUT_SECS",
+                AnalyzerConfig::default().timeout_secs,
+            ),
+            breaker: breaker_config(&src, "ANALYZER", AnalyzerConfig::default().breaker),
+            prices: {
+                let mut prices = ModelPrice::defaults();
+                if let Some(spec) = src.get("ANALYZER_PRICES") {
+                    match ModelPrice::parse_overrides(&spec) {
+                        Ok(overrides) => prices.extend(overrides),
+                        Err(e) => src.problem("ANALYZER_PRICES", e),
+                    }
+                }
+                prices
+            },
+            monthly_budget_usd: src
+                .get("ANALYZER_MONTHLY_BUDGET_USD")
+                .map(|_| src.parse("ANALYZER_MONTHLY_BUDGET_USD", 0.0_f64).max(0.0)),
+            screening: src.flag("ANALYZER_SCREENING", AnalyzerConfig::default().screening),
+            screening_model: src.get("ANALYZER_SCREENING_MODEL"),
+        };
+        if matches!(
+            analyzer.provider,
+            AnalyzerProvider::OpenAi | AnalyzerProvider::Anthropic
+        ) && analyzer.api_key.is_none()
+        {
+            src.problem(
+                "ANALYZER_API_KEY",
+                "is required for the openai and anthropic providers".to_string(),
+            );
+        }
+        let mut weights = ScoreWeights::default();
+        if let Some(spec) = src.get("SCORE_WEIGHTS") {
+            weights.apply_overrides(&spec);
+        }
+        let score_defaults = ScoreConfig::default();
+        let meals_per_day = src.parse("SCORE_MEALS_PER_DAY", score_defaults.meals_per_day);
+        if meals_per_day < 1.0 {
+            src.problem("SCORE_MEALS_PER_DAY", "must be at least 1".to_string());
+        }
+        let score = ScoreConfig {
+            weights,
+            meals_per_day: meals_per_day.max(1.0),
+        };
+        let stats = StatsConfig {
+            cache_ttl_secs: src.parse(
+                "STATS_CACHE_TTL_SECS",
+                StatsConfig::default().cache_ttl_secs,
+            ),
+        };
+        let food_defaults = FoodsConfig::default();
+        let foods = FoodsConfig {
+            off_base_url: src.string("OFF_BASE_URL", &food_defaults.off_base_url),
+            usda_api_key: src.get("USDA_API_KEY"),
+            usda_base_url: src.string("USDA_BASE_URL", &food_defaults.usda_base_url),
+            timeout_secs: src.parse("FOODS_TIMEOUT_SECS", food_defaults.timeout_secs),
+            cache_ttl_hours: src.parse("FOODS_CACHE_TTL_HOURS", food_defaults.cache_ttl_hours),
+            miss_ttl_hours: src.parse("FOODS_MISS_TTL_HOURS", food_defaults.miss_ttl_hours),
+            search_ttl_hours: src.parse("FOODS_SEARCH_TTL_HOURS", food_defaults.search_ttl_hours),
+        };
+        let integration_defaults = IntegrationsConfig::default();
+        let integrations = IntegrationsConfig {
+            timeout_secs: src.parse(
+                "INTEGRATIONS_TIMEOUT_SECS",
+                integration_defaults.timeout_secs,
+            ),
+            backfill_days: src
+                .parse(
+                    "INTEGRATIONS_BACKFILL_DAYS",
+                    integration_defaults.backfill_days,
+                )
+                .max(1),
+            google_fit: oauth_app_config(&src, "GOOGLE_FIT"),
+            fitbit: oauth_app_config(&src, "FITBIT"),
+        };
+        let geocoding = GeocodingConfig {
+            nominatim_url: src.get("NOMINATIM_URL"),
+            timeout_secs: src.parse(
+                "GEOCODING_TIMEOUT_SECS",
+                GeocodingConfig::default().timeout_secs,
+            ),
+        };
+        let analytics_defaults = AnalyticsConfig::default();
+        let analytics = AnalyticsConfig {
+            sink_url: src.get("ANALYTICS_SINK_URL"),
+            sink_timeout_secs: src.parse(
+                "ANALYTICS_SINK_TIMEOUT_SECS",
+                analytics_defaults.sink_timeout_secs,
+            ),
+            retention_days: src.parse(
+                "ANALYTICS_RETENTION_DAYS",
+                analytics_defaults.retention_days,
+            ),
+        };
+        let research = ResearchConfig {
+            pseudonym_key: src.get("RESEARCH_PSEUDONYM_KEY"),
+            min_k: src
+                .parse("RESEARCH_EXPORT_MIN_K", ResearchConfig::default().min_k)
+                .max(2),
+        };
+        let billing_defaults = BillingConfig::default();
+        let billing = BillingConfig {
+            free_monthly_analyses: src.parse(
+                "FREE_PLAN_MONTHLY_ANALYSES",
+                billing_defaults.free_monthly_analyses,
+            ),
+            stripe_webhook_secret: src.get("STRIPE_WEBHOOK_SECRET"),
+            stripe_webhook_tolerance_secs: src.parse(
+                "STRIPE_WEBHOOK_TOLERANCE_SECS",
+                billing_defaults.stripe_webhook_tolerance_secs,
+            ),
+        };
+        src.finish()?;
+        Ok(Self {
+            database_url,
+            strict_migrations,
+            row_level_security,
+            redis_url,
+            jwt,
+            storage_backend,
+            s3,
+            storage_retry,
+            storage_breaker,
+            uploads,
+            photo_urls,
+            http,
+            security_headers,
+            transcode,
+            duplicates,
+            jobs,
+            tasks,
+            cron,
+            events,
+            webhooks,
+            push,
+            analyzer,
+            score,
+            stats,
+            foods,
+            integrations,
+            geocoding,
+            analytics,
+            research,
+            billing,
+        })
+    }
+}
+
+/// `{prefix}_CLIENT_ID`, `{prefix}_CLIENT_SECRET` and `{prefix}_REDIRECT_URL`;
+/// `None` without a client id.
+fn oauth_app_config(src: &ConfigSource, prefix: &str) -> Option<OAuthAppConfig> {
+    let client_id = src.get(&format!("{}_CLIENT_ID", prefix))?;
+    let required = |suffix: &str| {
+        let name = format!("{}_{}", prefix, suffix);
+        src.get(&name).unwrap_or_else(|| {
+            src.problem(&name, format!("is required with {}_CLIENT_ID", prefix));
+            String::new()
+        })
+    };
+    Some(OAuthAppConfig {
+        client_id,
+        client_secret: required("CLIENT_SECRET"),
+        redirect_url: required("REDIRECT_URL"),
+    })
+}
+
+/// `{prefix}_BREAKER_THRESHOLD` and `{prefix}_BREAKER_OPEN_SECS`.
+fn breaker_config(
+    src: &ConfigSource,
+    prefix: &str,
+    defaults: CircuitBreakerConfig,
+) -> CircuitBreakerConfig {
+    CircuitBreakerConfig {
+        failure_threshold: src.parse(
+            &format!("{}_BREAKER_THRESHOLD", prefix),
+            defaults.failure_threshold,
+        ),
+        open_secs: src
+            .parse(&format!("{}_BREAKER_OPEN_SECS", prefix), defaults.open_secs)
+            .max(1),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::*;
+
+    fn source(env: &[(&str, &str)], file: Option<&str>) -> ConfigSource {
+        let env = env
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        ConfigSource::new(env, file.map(|contents| ("mealmind.toml", contents))).unwrap()
+    }
+
+    const REQUIRED: [(&str, &str); 2] = [
+        ("DATABASE_URL", "postgres://localhost/mealmind"),
+        ("JWT_SECRET", "secret"),
+    ];
+
+    #[test]
+    fn file_values_are_overridden_by_env() {
+        let file = r#"
+            database_url = "postgres://db/mealmind"
+            storage_backend = "memory"
+
+            [jwt]
+            secret = "from-file"
+            ttl_minutes = 15
+
+            [job]
+            workers = 4
+        "#;
+        let config = AppConfig::from_source(source(&[("JOB_WORKERS", "8")], Some(file))).unwrap();
+        assert_eq!(config.database_url, "postgres://db/mealmind");
+        assert_eq!(config.jwt.secret, "from-file");
+        assert_eq!(config.jwt.ttl_minutes, 15);
+        assert_eq!(config.jobs.workers, 8);
+        assert_eq!(config.storage_backend, StorageBackend::Memory);
+    }
+
+    #[test]
+    fn secrets_are_read_from_file_paths() {
+        let path = std::env::temp_dir().join(format!("mealmind-secret-{}", uuid::Uuid::new_v4()));
+        std::fs::File::create(&path)
+            .unwrap()
+            .write_all(b"s3cr3t\n")
+            .unwrap();
+        let path = path.to_str().unwrap();
+        let config = AppConfig::from_source(source(
+            &[
+                ("DATABASE_URL", "postgres://localhost/mealmind"),
+                ("JWT_SECRET_FILE", path),
+            ],
+            None,
+        ))
+        .unwrap();
+        assert_eq!(config.jwt.secret, "s3cr3t");
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn every_problem_is_reported() {
+        let err = AppConfig::from_source(source(
+            &[
+                ("JOB_WORKERS", "two"),
+                ("STORAGE_BACKEND", "disk"),
+                ("PUSH_APNS_KEY_PATH", "/keys/apns.p8"),
+                ("SECURITY_CSP", "default-src\n'none'"),
+                ("PHOTO_URL_REFRESH_MARGIN_SECS", "3600"),
+            ],
+            Some("[s3]\nbucktet = \"meals\""),
+        ))
+        .unwrap_err();
+        let problems = err.problems.join("\n");
+        for key in [
+            "DATABASE_URL: is required",
+            "JWT_SECRET: is required",
+            "JOB_WORKERS: invalid value \"two\"",
+            "STORAGE_BACKEND: invalid value \"disk\"",
+            "PUSH_APNS_TOPIC: is required",
+            "SECURITY_CSP: is not a valid header value",
+            "PHOTO_URL_REFRESH_MARGIN_SECS: must be less than PHOTO_URL_TTL_SECS",
+            "S3_BUCKTET: is not a known setting",
+        ] {
+            assert!(problems.contains(key), "{key} not in {problems}");
+        }
+        assert!(AppConfig::from_source(source(&REQUIRED, None)).is_ok());
+    }
+
+    #[test]
+    fn analyzer_prices_match_by_longest_prefix() {
+        let mut env = REQUIRED.to_vec();
+        env.push(("ANALYZER_PRICES", "gpt-4o-mini=0.2/0.8; llava=0/0"));
+        let config = AppConfig::from_source(source(&env, None)).unwrap().analyzer;
+        let price = |model| config.price(model).map(|p| (p.input, p.output));
+        assert_eq!(price("gpt-4o-mini-2024-07-18"), Some((0.2, 0.8)));
+        assert_eq!(price("gpt-4o-2024-08-06"), Some((2.5, 10.0)));
+        assert_eq!(price("mistral"), None);
+        assert!(ModelPrice::parse_overrides("gpt-4o=1;x=1/-2").is_err());
+    }
+}
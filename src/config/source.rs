@@ -0,0 +1,248 @@
+//! Where settings come from. Each setting has one name, the environment
+//! variable (`JOB_WORKERS`); in the optional TOML file named by
+//! `CONFIG_FILE` the same setting is `job_workers`, or `workers` in a
+//! `[job]` table. The environment wins over the file, and any setting can
+//! instead be read from a file given as `NAME_FILE`, for Docker secrets.
+//!
+//! Lookups never fail on the spot: problems are collected and reported
+//! together by [`ConfigSource::finish`].
+
+use std::{
+    cell::RefCell,
+    collections::{BTreeSet, HashMap},
+    fmt,
+    str::FromStr,
+};
+
+/// Every problem found while loading the configuration.
+#[derive(Debug)]
+pub struct ConfigError {
+    pub problems: Vec<String>,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid configuration:")?;
+        for problem in &self.problems {
+            write!(f, "\n  - {}", problem)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// Variables read outside [`AppConfig`](super::AppConfig), and the file
+/// pointer itself; they're only valid in the environment.
+const ENV_ONLY: [&str; 3] = ["CONFIG_FILE", "RUST_LOG", "LOG_FORMAT"];
+
+pub struct ConfigSource {
+    env: HashMap<String, String>,
+    file: HashMap<String, String>,
+    file_name: Option<String>,
+    /// Names looked up so far, to report unknown keys in the file.
+    read: RefCell<BTreeSet<String>>,
+    problems: RefCell<Vec<String>>,
+}
+
+impl ConfigSource {
+    /// The process environment, layered over `CONFIG_FILE` if set.
+    pub fn load() -> Result<Self, ConfigError> {
+        let env: HashMap<String, String> = std::env::vars().collect();
+        let file = match env.get("CONFIG_FILE").filter(|v| !v.trim().is_empty()) {
+            Some(path) => {
+                let contents = std::fs::read_to_string(path).map_err(|e| ConfigError {
+                    problems: vec![format!("CONFIG_FILE: cannot read {}: {}", path, e)],
+                })?;
+                Some((path.clone(), contents))
+            }
+            None => None,
+        };
+        Self::new(
+            env,
+            file.as_ref()
+                .map(|(path, contents)| (path.as_str(), contents.as_str())),
+        )
+    }
+
+    /// `file` is the name and TOML contents of the config file.
+    pub fn new(
+        env: HashMap<String, String>,
+        file: Option<(&str, &str)>,
+    ) -> Result<Self, ConfigError> {
+        let mut problems = Vec::new();
+        let mut values = HashMap::new();
+        if let Some((name, contents)) = file {
+            match contents.parse::<toml::Table>() {
+                Ok(table) => flatten("", &table, &mut values, &mut problems),
+                Err(e) => problems.push(format!("{}: {}", name, e.message())),
+            }
+        }
+        if !problems.is_empty() {
+            return Err(ConfigError { problems });
+        }
+        Ok(Self {
+            env,
+            file: values,
+            file_name: file.map(|(name, _)| name.to_string()),
+            read: RefCell::default(),
+            problems: RefCell::default(),
+        })
+    }
+
+    /// The value of a setting; blank values count as unset.
+    pub fn get(&self, name: &str) -> Option<String> {
+        let secret = format!("{}_FILE", name);
+        self.read.borrow_mut().insert(name.to_string());
+        self.read.borrow_mut().insert(secret.clone());
+        for layer in [&self.env, &self.file] {
+            if let Some(value) = layer.get(name).filter(|v| !v.trim().is_empty()) {
+                return Some(value.clone());
+            }
+            if let Some(path) = layer.get(&secret).filter(|v| !v.trim().is_empty()) {
+                return match std::fs::read_to_string(path) {
+                    Ok(value) => Some(value.trim().to_string()).filter(|v| !v.is_empty()),
+                    Err(e) => {
+                        self.problem(&secret, format!("cannot read {}: {}", path, e));
+                        None
+                    }
+                };
+            }
+        }
+        None
+    }
+
+    pub fn string(&self, name: &str, default: &str) -> String {
+        self.get(name).unwrap_or_else(|| default.to_string())
+    }
+
+    /// A setting without a default; reported as missing when unset.
+    pub fn required(&self, name: &str) -> String {
+        self.get(name).unwrap_or_else(|| {
+            self.problem(name, "is required".to_string());
+            String::new()
+        })
+    }
+
+    pub fn parse<T>(&self, name: &str, default: T) -> T
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        let Some(value) = self.get(name) else {
+            return default;
+        };
+        match value.trim().parse() {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                self.problem(name, format!("invalid value {:?}: {}", value, e));
+                default
+            }
+        }
+    }
+
+    pub fn flag(&self, name: &str, default: bool) -> bool {
+        let Some(value) = self.get(name) else {
+            return default;
+        };
+        match value.trim().to_ascii_lowercase().as_str() {
+            "true" | "1" | "yes" | "on" => true,
+            "false" | "0" | "no" | "off" => false,
+            _ => {
+                self.problem(
+                    name,
+                    format!("invalid value {:?}: expected true or false", value),
+                );
+                default
+            }
+        }
+    }
+
+    /// One of a fixed set of named values.
+    pub fn choice<T: Copy>(&self, name: &str, choices: &[(&str, T)], default: T) -> T {
+        let Some(value) = self.get(name) else {
+            return default;
+        };
+        match choices.iter().find(|(key, _)| *key == value.trim()) {
+            Some((_, choice)) => *choice,
+            None => {
+                let names: Vec<&str> = choices.iter().map(|(key, _)| *key).collect();
+                self.problem(
+                    name,
+                    format!(
+                        "invalid value {:?}: expected one of {}",
+                        value,
+                        names.join(", ")
+                    ),
+                );
+                default
+            }
+        }
+    }
+
+    pub fn problem(&self, name: &str, message: String) {
+        self.problems
+            .borrow_mut()
+            .push(format!("{}: {}", name, message));
+    }
+
+    /// Reports everything collected so far, and keys in the file no lookup
+    /// asked for, which are most likely typos.
+    pub fn finish(self) -> Result<(), ConfigError> {
+        let mut problems = self.problems.into_inner();
+        let read = self.read.into_inner();
+        let mut unknown: Vec<&String> = self
+            .file
+            .keys()
+            .filter(|key| !read.contains(*key))
+            .collect();
+        unknown.sort();
+        for key in unknown {
+            let hint = if ENV_ONLY.contains(&key.as_str()) {
+                "can only be set in the environment"
+            } else {
+                "is not a known setting"
+            };
+            problems.push(format!(
+                "{}: {} in {}",
+                key,
+                hint,
+                self.file_name.as_deref().unwrap_or("config file")
+            ));
+        }
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigError { problems })
+        }
+    }
+}
+
+/// Flattens tables into setting names: `[s3] bucket` becomes `S3_BUCKET`.
+fn flatten(
+    prefix: &str,
+    table: &toml::Table,
+    values: &mut HashMap<String, String>,
+    problems: &mut Vec<String>,
+) {
+    for (key, value) in table {
+        let name = format!("{}{}", prefix, key.to_ascii_uppercase().replace('-', "_"));
+        let value = match value {
+            toml::Value::Table(table) => {
+                flatten(&format!("{}_", name), table, values, problems);
+                continue;
+            }
+            toml::Value::String(s) => s.clone(),
+            toml::Value::Integer(n) => n.to_string(),
+            toml::Value::Float(n) => n.to_string(),
+            toml::Value::Boolean(b) => b.to_string(),
+            toml::Value::Datetime(_) | toml::Value::Array(_) => {
+                problems.push(format!("{}: expected a string, number or boolean", name));
+                continue;
+            }
+        };
+        if values.insert(name.clone(), value).is_some() {
+            problems.push(format!("{}: set more than once in the config file", name));
+        }
+    }
+}
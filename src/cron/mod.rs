@@ -0,0 +1,232 @@
+//! Scheduled jobs: recurring housekeeping and reminders on cron schedules.
+//! Every instance runs a scheduler task per job, and the `job_runs` table
+//! makes sure each due run happens on only one of them.
+
+pub mod repo;
+
+use std::{str::FromStr, time::Duration};
+
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use cron::Schedule;
+use time::OffsetDateTime;
+use tracing::{info, warn};
+
+use crate::{
+    analytics::services as analytics_services,
+    db::AppState,
+    integrations::services as integrations_services,
+    push::services as push_services,
+    retention::services as retention_services,
+    tasks::{maintenance, BackgroundTasks, TaskContext},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CronJob {
+    OrphanPhotoCleanup,
+    TokenPruning,
+    OutboxPruning,
+    MealReminders,
+    MealArchival,
+    SyncTombstonePruning,
+    ActivitySync,
+    AnalyticsPartitions,
+}
+
+impl CronJob {
+    pub const ALL: [CronJob; 8] = [
+        CronJob::OrphanPhotoCleanup,
+        CronJob::TokenPruning,
+        CronJob::OutboxPruning,
+        CronJob::MealReminders,
+        CronJob::MealArchival,
+        CronJob::SyncTombstonePruning,
+        CronJob::ActivitySync,
+        CronJob::AnalyticsPartitions,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            CronJob::OrphanPhotoCleanup => "orphan_photo_cleanup",
+            CronJob::TokenPruning => "token_pruning",
+            CronJob::OutboxPruning => "outbox_pruning",
+            CronJob::MealReminders => "meal_reminders",
+            CronJob::MealArchival => "meal_archival",
+            CronJob::SyncTombstonePruning => "sync_tombstone_pruning",
+            CronJob::ActivitySync => "activity_sync",
+            CronJob::AnalyticsPartitions => "analytics_partitions",
+        }
+    }
+
+    fn default_schedule(self) -> &'static str {
+        match self {
+            CronJob::OrphanPhotoCleanup => "15 * * * *",
+            CronJob::TokenPruning => "45 * * * *",
+            CronJob::OutboxPruning => "30 3 * * *",
+            // Hourly, as each user picks the local hour of their reminder.
+            CronJob::MealReminders => "0 * * * *",
+            CronJob::MealArchival => "0 4 * * *",
+            CronJob::SyncTombstonePruning => "50 3 * * *",
+            CronJob::ActivitySync => "20 * * * *",
+            CronJob::AnalyticsPartitions => "10 0 * * *",
+        }
+    }
+
+    async fn run(self, state: &AppState) -> anyhow::Result<()> {
+        match self {
+            CronJob::OrphanPhotoCleanup => maintenance::cleanup_orphan_photos(state).await,
+            CronJob::TokenPruning => maintenance::prune_tokens(state).await,
+            CronJob::OutboxPruning => maintenance::prune_outbox(state).await,
+            CronJob::MealReminders => push_services::send_meal_reminders(state).await,
+            CronJob::MealArchival => retention_services::archive_meals(state).await,
+            CronJob::SyncTombstonePruning => maintenance::prune_sync_tombstones(state).await,
+            CronJob::ActivitySync => integrations_services::sync_all(state).await,
+            CronJob::AnalyticsPartitions => analytics_services::maintain_partitions(state).await,
+        }
+    }
+}
+
+/// Parses a cron expression, `None` for `off`. Standard five-field
+/// expressions run at second 0; six or seven fields start with seconds.
+pub fn parse_schedule(expr: &str) -> anyhow::Result<Option<Schedule>> {
+    let expr = expr.trim();
+    if expr.eq_ignore_ascii_case("off") {
+        return Ok(None);
+    }
+    let expr = match expr.split_whitespace().count() {
+        5 => format!("0 {}", expr),
+        _ => expr.to_string(),
+    };
+    let schedule = Schedule::from_str(&expr).map_err(|e| anyhow::anyhow!("{}", e))?;
+    Ok(Some(schedule))
+}
+
+/// First occurrence of `schedule` strictly after `at`.
+fn next_after(schedule: &Schedule, at: OffsetDateTime) -> Option<OffsetDateTime> {
+    let at = DateTime::<Utc>::from_timestamp(at.unix_timestamp(), at.nanosecond())?;
+    let next = schedule.after(&at).next()?;
+    OffsetDateTime::from_unix_timestamp(next.timestamp()).ok()
+}
+
+/// Starts a scheduler task for every enabled job. Fails on an invalid
+/// schedule so a typo doesn't silently disable a job.
+pub fn register(tasks: &mut BackgroundTasks, state: &AppState) -> anyhow::Result<()> {
+    let config = &state.config.cron;
+    for name in config.schedules.keys() {
+        if !CronJob::ALL.iter().any(|job| job.name() == name) {
+            warn!(job = %name, "CRON_SCHEDULES names an unknown job; ignoring");
+        }
+    }
+    for job in CronJob::ALL {
+        let expr = config
+            .schedules
+            .get(job.name())
+            .map(String::as_str)
+            .unwrap_or(job.default_schedule());
+        let Some(schedule) =
+            parse_schedule(expr).with_context(|| format!("schedule of {}", job.name()))?
+        else {
+            info!(job = job.name(), "cron job disabled");
+            continue;
+        };
+        let state = state.clone();
+        tasks.spawn(format!("cron:{}", job.name()), move |ctx| {
+            run_scheduler(state, job, schedule, ctx)
+        });
+    }
+    Ok(())
+}
+
+async fn run_scheduler(state: AppState, job: CronJob, schedule: Schedule, mut ctx: TaskContext) {
+    let tick = Duration::from_secs(state.config.cron.tick_secs);
+    let mut ensured = false;
+    loop {
+        if let Err(e) = run_if_due(&state, job, &schedule, &mut ensured, &ctx).await {
+            // Bookkeeping failed (database down); the readiness check already
+            // reports that, so the job's own health is left alone.
+            warn!(job = job.name(), error = %e, "cron bookkeeping failed");
+        }
+        if !ctx.sleep(tick).await {
+            break;
+        }
+    }
+}
+
+async fn run_if_due(
+    state: &AppState,
+    job: CronJob,
+    schedule: &Schedule,
+    ensured: &mut bool,
+    ctx: &TaskContext,
+) -> anyhow::Result<()> {
+    let Some(next) = next_after(schedule, OffsetDateTime::now_utc()) else {
+        return Ok(());
+    };
+    if !*ensured {
+        repo::ensure(&state.db, job.name(), next).await?;
+        *ensured = true;
+    }
+    let lock_timeout = state.config.cron.lock_timeout_secs as i64;
+    if !repo::claim(&state.db, job.name(), next, lock_timeout).await? {
+        return Ok(());
+    }
+
+    info!(job = job.name(), "cron job started");
+    let result = job.run(state).await;
+    let error = result.as_ref().err().map(|e| format!("{:#}", e));
+    repo::finish(&state.db, job.name(), error.as_deref()).await?;
+    match result {
+        Ok(()) => {
+            info!(job = job.name(), "cron job done");
+            ctx.succeeded();
+        }
+        Err(e) => {
+            warn!(job = job.name(), error = %e, "cron job failed");
+            ctx.failed(&e);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use time::macros::datetime;
+
+    use super::*;
+    use crate::config::CronConfig;
+
+    #[test]
+    fn parses_five_and_six_field_schedules() {
+        let hourly = parse_schedule("15 * * * *").unwrap().unwrap();
+        let at = datetime!(2024-01-01 12:20:30 UTC);
+        assert_eq!(
+            next_after(&hourly, at),
+            Some(datetime!(2024-01-01 13:15:00 UTC))
+        );
+
+        let seconds = parse_schedule("*/10 * * * * *").unwrap().unwrap();
+        assert_eq!(
+            next_after(&seconds, at),
+            Some(datetime!(2024-01-01 12:20:40 UTC))
+        );
+
+        assert!(parse_schedule("OFF").unwrap().is_none());
+        assert!(parse_schedule("every hour").is_err());
+    }
+
+    #[test]
+    fn default_schedules_are_valid() {
+        for job in CronJob::ALL {
+            assert!(parse_schedule(job.default_schedule()).unwrap().is_some());
+        }
+    }
+
+    #[test]
+    fn schedule_overrides_split_on_semicolons() {
+        let schedules =
+            CronConfig::parse_schedules("token_pruning=0 3 * * 1,4; orphan_photo_cleanup=off;bad");
+        assert_eq!(schedules["token_pruning"], "0 3 * * 1,4");
+        assert_eq!(schedules["orphan_photo_cleanup"], "off");
+        assert_eq!(schedules.len(), 2);
+    }
+}
@@ -0,0 +1,64 @@
+use sqlx::PgPool;
+use time::OffsetDateTime;
+
+/// Creates the bookkeeping row of a job, or pulls its next run forward when
+/// the schedule changed to an earlier time.
+pub async fn ensure(db: &PgPool, name: &str, next_run_at: OffsetDateTime) -> anyhow::Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO job_runs (name, next_run_at)
+        VALUES ($1, $2)
+        ON CONFLICT (name) DO UPDATE
+        SET next_run_at = LEAST(job_runs.next_run_at, EXCLUDED.next_run_at)
+        "#,
+    )
+    .bind(name)
+    .bind(next_run_at)
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+/// Marks the job running and schedules the following run, if it's due and
+/// not already running elsewhere (a run older than `lock_timeout_secs` is
+/// assumed dead). Only one instance gets `true` per due run.
+pub async fn claim(
+    db: &PgPool,
+    name: &str,
+    following_run_at: OffsetDateTime,
+    lock_timeout_secs: i64,
+) -> anyhow::Result<bool> {
+    let claimed = sqlx::query(
+        r#"
+        UPDATE job_runs
+        SET next_run_at = $2, last_started_at = NOW(), last_status = 'running'
+        WHERE name = $1
+          AND next_run_at <= NOW()
+          AND (last_status IS DISTINCT FROM 'running'
+               OR last_started_at < NOW() - make_interval(secs => $3))
+        "#,
+    )
+    .bind(name)
+    .bind(following_run_at)
+    .bind(lock_timeout_secs as f64)
+    .execute(db)
+    .await?;
+    Ok(claimed.rows_affected() > 0)
+}
+
+pub async fn finish(db: &PgPool, name: &str, error: Option<&str>) -> anyhow::Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE job_runs
+        SET last_finished_at = NOW(),
+            last_status = CASE WHEN $2::text IS NULL THEN 'succeeded' ELSE 'failed' END,
+            last_error = $2
+        WHERE name = $1
+        "#,
+    )
+    .bind(name)
+    .bind(error)
+    .execute(db)
+    .await?;
+    Ok(())
+}
@@ -1,29 +1,134 @@
 use std::sync::Arc;
 
 use anyhow::Context;
+use axum::http::StatusCode;
 use serde::{Deserialize, Serialize};
-use sqlx::{postgres::PgPoolOptions, FromRow, PgPool};
+use sqlx::{migrate::Migrator, postgres::PgPoolOptions, FromRow, PgPool, Postgres, Transaction};
 use time::OffsetDateTime;
 use uuid::Uuid;
 
-use crate::config::AppConfig;
+use crate::{
+    analysis::{self, NutritionAnalyzer},
+    analytics::AnalyticsSink,
+    cache::Cache,
+    config::AppConfig,
+    flags::Flags,
+    foods::FoodSources,
+    geocoding::{self, Geocoder},
+    images::fingerprint::Fingerprinter,
+    integrations::Integrations,
+    push::PushProviders,
+    realtime::EventHub,
+    recommendations::{MacroFitStrategy, RecommendationStrategy},
+    stats::cache::StatsCache,
+    storage::{self, StorageClient},
+    tasks::TaskHealth,
+};
+
+/// The migrations in `migrations/`, embedded at build time.
+pub static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
 
 #[derive(Clone)]
 pub struct AppState {
     pub db: PgPool,
     pub config: Arc<AppConfig>,
+    pub storage: Arc<dyn StorageClient>,
+    pub events: EventHub,
+    /// `None` when no provider is configured.
+    pub analyzer: Option<Arc<dyn NutritionAnalyzer>>,
+    pub stats_cache: StatsCache,
+    /// Shared read cache; disabled without Redis.
+    pub cache: Cache,
+    pub foods: FoodSources,
+    pub integrations: Integrations,
+    /// Reverse geocodes meal locations; `None` when not configured.
+    pub geocoder: Option<Arc<dyn Geocoder>>,
+    /// Receives app analytics instead of the database; `None` when not
+    /// configured.
+    pub analytics_sink: Option<Arc<AnalyticsSink>>,
+    /// Health of the background tasks, reported by `/health/ready`.
+    pub tasks: TaskHealth,
+    pub push: PushProviders,
+    /// Loaded by the `flags-refresh` task; defaults until then.
+    pub flags: Flags,
+    /// Ranks `GET /recommendations`.
+    pub recommender: Arc<dyn RecommendationStrategy>,
+    /// Shared by all requests so photo fingerprinting stays within
+    /// `DUPLICATE_FINGERPRINT_CONCURRENCY`.
+    pub fingerprinter: Fingerprinter,
 }
 
 impl AppState {
     pub async fn init() -> anyhow::Result<Self> {
-        let config = Arc::new(AppConfig::from_env()?);
+        let config = Arc::new(AppConfig::load()?);
         let db = PgPoolOptions::new()
             .max_connections(10)
             .connect(&config.database_url)
             .await
             .context("connect to database")?;
-        Ok(Self { db, config })
+        let storage = storage::from_config(&config)
+            .await
+            .context("init storage")?;
+        let events = EventHub::connect(config.redis_url.as_deref())
+            .await
+            .context("connect realtime events")?;
+        let cache = Cache::connect(config.redis_url.as_deref())
+            .await
+            .context("connect cache")?;
+        let analyzer = analysis::from_config(&config.analyzer).context("init analyzer")?;
+        let foods = FoodSources::from_config(&config.foods).context("init food sources")?;
+        let push = PushProviders::from_config(&config.push).context("init push providers")?;
+        let integrations =
+            Integrations::from_config(&config.integrations).context("init integrations")?;
+        let geocoder = geocoding::from_config(&config.geocoding).context("init geocoder")?;
+        let analytics_sink =
+            AnalyticsSink::from_config(&config.analytics).context("init analytics sink")?;
+        Ok(Self {
+            db,
+            storage,
+            events,
+            analyzer,
+            stats_cache: StatsCache::default(),
+            cache,
+            foods,
+            integrations,
+            geocoder,
+            analytics_sink,
+            tasks: TaskHealth::default(),
+            push,
+            flags: Flags::default(),
+            recommender: Arc::new(MacroFitStrategy),
+            fingerprinter: Fingerprinter::from_config(&config.duplicates),
+            config,
+        })
     }
+
+    /// Starts a transaction for requests of `user_id`. With
+    /// `DB_ROW_LEVEL_SECURITY` it first sets `app.current_user_id` for the
+    /// transaction only (`set_config(.., true)` is `SET LOCAL` with a bind
+    /// parameter), so the row-level security policies hide and refuse rows
+    /// of other users even if a query misses its filter.
+    pub async fn begin_as(&self, user_id: Uuid) -> sqlx::Result<Transaction<'static, Postgres>> {
+        let mut tx = self.db.begin().await?;
+        if self.config.row_level_security {
+            sqlx::query("SELECT set_config('app.current_user_id', $1, true)")
+                .bind(user_id.to_string())
+                .execute(&mut *tx)
+                .await?;
+        }
+        Ok(tx)
+    }
+}
+
+/// Whether the connected role skips row-level security, as superusers and
+/// roles with `BYPASSRLS` do; the policies can't protect anything then.
+pub async fn bypasses_row_level_security(db: &PgPool) -> anyhow::Result<bool> {
+    let bypasses = sqlx::query_scalar::<_, bool>(
+        "SELECT rolsuper OR rolbypassrls FROM pg_roles WHERE rolname = current_user",
+    )
+    .fetch_one(db)
+    .await?;
+    Ok(bypasses)
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
@@ -35,33 +140,59 @@ pub struct User {
     pub created_at: OffsetDateTime,
 }
 
-impl User {
-    pub async fn find_by_email(db: &PgPool, email: &str) -> anyhow::Result<Option<User>> {
-        let user = sqlx::query_as::<_, User>(
-            r#"
-            SELECT id, email, password_hash, created_at
-            FROM users
-            WHERE email = $1
-            "#,
-        )
-        .bind(email)
-        .fetch_optional(db)
-        .await?;
-        Ok(user)
+/// Error of the typed repositories (`auth::repo`, `meals::repo` and
+/// `photos::repo`), so callers can tell a missing row or a constraint
+/// conflict from a database failure without inspecting `sqlx::Error`.
+#[derive(Debug, thiserror::Error)]
+pub enum RepoError {
+    #[error("row not found")]
+    NotFound,
+    /// A unique constraint rejected the write; holds the constraint's name.
+    #[error("conflict on {0}")]
+    Conflict(String),
+    #[error(transparent)]
+    Db(sqlx::Error),
+}
+
+pub type RepoResult<T> = Result<T, RepoError>;
+
+impl RepoError {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            RepoError::NotFound => StatusCode::NOT_FOUND,
+            RepoError::Conflict(_) => StatusCode::CONFLICT,
+            RepoError::Db(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+impl From<sqlx::Error> for RepoError {
+    fn from(e: sqlx::Error) -> Self {
+        match e {
+            sqlx::Error::RowNotFound => RepoError::NotFound,
+            sqlx::Error::Database(db) if db.is_unique_violation() => {
+                RepoError::Conflict(db.constraint().unwrap_or_default().to_string())
+            }
+            e => RepoError::Db(e),
+        }
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    pub async fn create(db: &PgPool, email: &str, password_hash: &str) -> anyhow::Result<User> {
-        let user = sqlx::query_as::<_, User>(
-            r#"
-            INSERT INTO users (email, password_hash)
-            VALUES ($1, $2)
-            RETURNING id, email, password_hash, created_at
-            "#,
-        )
-        .bind(email)
-        .bind(password_hash)
-        .fetch_one(db)
-        .await?;
-        Ok(user)
+    #[test]
+    fn repo_errors_map_to_statuses() {
+        let missing = RepoError::from(sqlx::Error::RowNotFound);
+        assert!(matches!(missing, RepoError::NotFound));
+        assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+        let closed = RepoError::from(sqlx::Error::PoolClosed);
+        assert!(matches!(closed, RepoError::Db(_)));
+        assert_eq!(closed.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(
+            RepoError::Conflict("idx_users_email_lower".into()).status(),
+            StatusCode::CONFLICT
+        );
     }
 }
@@ -0,0 +1,77 @@
+//! Headers announcing that an endpoint is going away: `Deprecation`
+//! (RFC 9745), `Sunset` (RFC 8594) and a `Link` to its replacement.
+
+use axum::{
+    extract::{Request, State},
+    http::{header::LINK, HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use time::{format_description::well_known::Rfc2822, Date};
+
+static DEPRECATION: HeaderName = HeaderName::from_static("deprecation");
+static SUNSET: HeaderName = HeaderName::from_static("sunset");
+
+#[derive(Debug, Clone, Copy)]
+pub struct Deprecation {
+    /// Day the endpoint was deprecated.
+    pub since: Date,
+    /// Day after which it may stop answering.
+    pub sunset: Date,
+    /// Path of the endpoint replacing it.
+    pub successor: &'static str,
+}
+
+impl Deprecation {
+    fn headers(&self) -> [(HeaderName, String); 3] {
+        let since = self.since.midnight().assume_utc().unix_timestamp();
+        // HTTP dates are RFC 2822 dates in GMT.
+        let sunset = self
+            .sunset
+            .midnight()
+            .assume_utc()
+            .format(&Rfc2822)
+            .map(|date| date.replace("+0000", "GMT"))
+            .unwrap_or_default();
+        [
+            (DEPRECATION.clone(), format!("@{}", since)),
+            (SUNSET.clone(), sunset),
+            (
+                LINK,
+                format!("<{}>; rel=\"successor-version\"", self.successor),
+            ),
+        ]
+    }
+}
+
+/// Route middleware adding the deprecation headers to every response, e.g.
+/// `from_fn_with_state(DEPRECATION, deprecation::mark)`.
+pub async fn mark(State(deprecation): State<Deprecation>, req: Request, next: Next) -> Response {
+    let mut response = next.run(req).await;
+    for (name, value) in deprecation.headers() {
+        if let Ok(value) = HeaderValue::from_str(&value) {
+            response.headers_mut().append(name, value);
+        }
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use time::macros::date;
+
+    use super::*;
+
+    #[test]
+    fn headers_use_structured_and_http_dates() {
+        let deprecation = Deprecation {
+            since: date!(2026 - 10 - 16),
+            sunset: date!(2027 - 04 - 01),
+            successor: "/api/v2/meals",
+        };
+        let [(_, since), (_, sunset), (_, link)] = deprecation.headers();
+        assert_eq!(since, "@1792108800");
+        assert_eq!(sunset, "Thu, 01 Apr 2027 00:00:00 GMT");
+        assert_eq!(link, "</api/v2/meals>; rel=\"successor-version\"");
+    }
+}
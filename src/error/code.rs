@@ -0,0 +1,103 @@
+//! The catalogue of error codes. Codes are part of the API: once released a
+//! code keeps its name and meaning, so clients can branch on it instead of
+//! the message. New failure modes get new codes.
+
+use serde::Serialize;
+use utoipa::ToSchema;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    // Without a more specific code, one per error class.
+    BadRequest,
+    Unauthorized,
+    Forbidden,
+    NotFound,
+    Conflict,
+    PayloadTooLarge,
+    UnsupportedMediaType,
+    Unprocessable,
+    TooManyRequests,
+    BadGateway,
+    ServiceUnavailable,
+    Internal,
+    /// A field failed validation; the message says which.
+    ValidationFailed,
+
+    AuthInvalidEmail,
+    AuthPasswordTooShort,
+    AuthEmailTaken,
+    AuthInvalidCredentials,
+    AuthInvalidRefreshToken,
+    AuthTokenMissing,
+    AuthTokenInvalid,
+    AuthUserNotFound,
+    AdminRequired,
+    OrgAdminRequired,
+
+    MealNotFound,
+    /// A meal needs images, nutrition or at least a title.
+    MealEmpty,
+    NutritionNotFound,
+    MealItemNotFound,
+    PhotoNotFound,
+    /// A photo looks like one logged shortly before; see `details`.
+    PhotoDuplicate,
+
+    UploadTooLarge,
+    UploadTooManyImages,
+    UploadNoImages,
+    UploadInvalidImage,
+    UploadSessionNotFound,
+    /// The upload session was finalized into a meal or expired.
+    UploadSessionClosed,
+    ChunkedUploadNotFound,
+    /// The free plan's photo analyses for this month are used up.
+    AnalysisLimitReached,
+    StripeSignatureInvalid,
+
+    IdempotencyKeyInvalid,
+    IdempotencyKeyReused,
+    IdempotencyInProgress,
+
+    FoodNotFound,
+    FoodUnitUnsupported,
+    FoodSourceDisabled,
+    FoodSourceUnavailable,
+
+    RecipeNotFound,
+    TemplateNotFound,
+    PlanNotFound,
+    PlanSlotNotFound,
+    PlanExists,
+
+    HouseholdNotFound,
+    HouseholdInviteNotFound,
+    HouseholdMemberNotFound,
+    HouseholdOwnerRequired,
+    HouseholdConflict,
+
+    CoachingClientNotFound,
+    CoachNotFound,
+    CoachingConflict,
+
+    OrgNotFound,
+
+    ShareNotFound,
+    ExportNotFound,
+    WeightNotFound,
+    MeasurementNotFound,
+    ProgressPhotoNotFound,
+    /// Changes before the cursor were pruned; pull again from cursor 0.
+    SyncCursorExpired,
+    ImportRejected,
+    DeviceNotFound,
+    NotificationNotFound,
+    WebhookNotFound,
+    WebhookLimitReached,
+    IntegrationDisabled,
+    IntegrationNotConnected,
+    /// Authorizing with the provider failed or expired; connect again.
+    IntegrationAuthFailed,
+    IntegrationUnavailable,
+}
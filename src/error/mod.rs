@@ -0,0 +1,477 @@
+//! JSON error responses shared by the HTTP handlers.
+
+mod code;
+
+use std::any::Any;
+
+use axum::{
+    body::to_bytes,
+    extract::{multipart::MultipartError, Request},
+    http::{header, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+use serde_json::{json, Value};
+use utoipa::ToSchema;
+
+use crate::{
+    i18n,
+    images::services::{DuplicatePhoto, ImageError},
+    request_id, storage,
+};
+
+pub use code::ErrorCode;
+
+/// Error returned by handlers, rendered as `{"code": "...", "error_code":
+/// "...", "message": "...", "details": ..., "request_id": "..."}`.
+///
+/// Messages are shown to clients, translated when the catalogue in
+/// [`i18n`] has them; `Internal` carries a generic description and the
+/// underlying error is only logged.
+#[derive(Debug)]
+pub enum ApiError {
+    BadRequest(String),
+    Unauthorized(String),
+    Forbidden(String),
+    NotFound(String),
+    Conflict(String),
+    PayloadTooLarge(String),
+    UnsupportedMediaType(String),
+    Unprocessable(String),
+    TooManyRequests(String),
+    /// An upstream service, such as a food database, failed.
+    BadGateway(String),
+    /// Object storage or another dependency is temporarily unreachable.
+    Unavailable(String),
+    Internal(String),
+    /// A status without a variant of its own, e.g. `410` or `504`; the
+    /// `code` is that of its class (`BadRequest` or `Internal`).
+    Other(StatusCode, String),
+    /// Any of the above with structured context, e.g. which image was rejected.
+    WithDetails(Box<ApiError>, Value),
+    /// Any of the above with a more specific code than its class.
+    WithCode(Box<ApiError>, ErrorCode),
+}
+
+/// Body of every [`ApiError`] response.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ErrorResponse {
+    /// Class of the error, matching the HTTP status.
+    #[schema(example = "not_found")]
+    pub code: &'static str,
+    /// Specific error from the [`ErrorCode`] catalogue; branch on this.
+    #[schema(example = "MEAL_NOT_FOUND")]
+    pub error_code: ErrorCode,
+    #[schema(example = "Meal not found")]
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
+    pub details: Option<Value>,
+    /// Same as the `X-Request-Id` response header; quote it when reporting a
+    /// problem.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+}
+
+impl ApiError {
+    /// 503 when object storage is temporarily unreachable, otherwise a
+    /// generic 500 with `msg`.
+    pub fn internal(e: &anyhow::Error, msg: &str) -> Self {
+        if storage::is_unavailable(e) {
+            ApiError::Unavailable("Storage temporarily unavailable".to_string())
+        } else {
+            ApiError::Internal(msg.to_string())
+        }
+    }
+
+    /// Maps a status picked elsewhere (domain errors, axum rejections) onto a
+    /// variant; other statuses are kept as they are with the code of their
+    /// class.
+    pub fn from_status(status: StatusCode, message: impl Into<String>) -> Self {
+        let message = message.into();
+        match status {
+            StatusCode::UNAUTHORIZED => ApiError::Unauthorized(message),
+            StatusCode::FORBIDDEN => ApiError::Forbidden(message),
+            StatusCode::NOT_FOUND => ApiError::NotFound(message),
+            StatusCode::CONFLICT => ApiError::Conflict(message),
+            StatusCode::PAYLOAD_TOO_LARGE => ApiError::PayloadTooLarge(message),
+            StatusCode::UNSUPPORTED_MEDIA_TYPE => ApiError::UnsupportedMediaType(message),
+            StatusCode::UNPROCESSABLE_ENTITY => ApiError::Unprocessable(message),
+            StatusCode::TOO_MANY_REQUESTS => ApiError::TooManyRequests(message),
+            StatusCode::BAD_GATEWAY => ApiError::BadGateway(message),
+            StatusCode::SERVICE_UNAVAILABLE => ApiError::Unavailable(message),
+            StatusCode::BAD_REQUEST => ApiError::BadRequest(message),
+            StatusCode::INTERNAL_SERVER_ERROR => ApiError::Internal(message),
+            s => ApiError::Other(s, message),
+        }
+    }
+
+    /// `400` for input failing validation, with `message` saying why.
+    pub fn validation(message: impl Into<String>) -> Self {
+        ApiError::BadRequest(message.into()).with_code(ErrorCode::ValidationFailed)
+    }
+
+    pub fn with_details(self, details: Value) -> Self {
+        ApiError::WithDetails(Box::new(self), details)
+    }
+
+    pub fn with_code(self, code: ErrorCode) -> Self {
+        ApiError::WithCode(Box::new(self), code)
+    }
+
+    /// The code set with [`with_code`](Self::with_code), or the generic one
+    /// of the error's class.
+    pub fn error_code(&self) -> ErrorCode {
+        match self {
+            ApiError::BadRequest(_) => ErrorCode::BadRequest,
+            ApiError::Unauthorized(_) => ErrorCode::Unauthorized,
+            ApiError::Forbidden(_) => ErrorCode::Forbidden,
+            ApiError::NotFound(_) => ErrorCode::NotFound,
+            ApiError::Conflict(_) => ErrorCode::Conflict,
+            ApiError::PayloadTooLarge(_) => ErrorCode::PayloadTooLarge,
+            ApiError::UnsupportedMediaType(_) => ErrorCode::UnsupportedMediaType,
+            ApiError::Unprocessable(_) => ErrorCode::Unprocessable,
+            ApiError::TooManyRequests(_) => ErrorCode::TooManyRequests,
+            ApiError::BadGateway(_) => ErrorCode::BadGateway,
+            ApiError::Unavailable(_) => ErrorCode::ServiceUnavailable,
+            ApiError::Internal(_) => ErrorCode::Internal,
+            ApiError::Other(s, _) if s.is_server_error() => ErrorCode::Internal,
+            ApiError::Other(..) => ErrorCode::BadRequest,
+            ApiError::WithDetails(inner, _) => inner.error_code(),
+            ApiError::WithCode(_, code) => *code,
+        }
+    }
+
+    pub fn status(&self) -> StatusCode {
+        match self {
+            ApiError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            ApiError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            ApiError::Forbidden(_) => StatusCode::FORBIDDEN,
+            ApiError::NotFound(_) => StatusCode::NOT_FOUND,
+            ApiError::Conflict(_) => StatusCode::CONFLICT,
+            ApiError::PayloadTooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
+            ApiError::UnsupportedMediaType(_) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            ApiError::Unprocessable(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            ApiError::TooManyRequests(_) => StatusCode::TOO_MANY_REQUESTS,
+            ApiError::BadGateway(_) => StatusCode::BAD_GATEWAY,
+            ApiError::Unavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+            ApiError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            ApiError::Other(s, _) => *s,
+            ApiError::WithDetails(inner, _) | ApiError::WithCode(inner, _) => inner.status(),
+        }
+    }
+
+    /// Stable machine-readable code clients can branch on.
+    pub fn code(&self) -> &'static str {
+        match self {
+            ApiError::BadRequest(_) => "bad_request",
+            ApiError::Unauthorized(_) => "unauthorized",
+            ApiError::Forbidden(_) => "forbidden",
+            ApiError::NotFound(_) => "not_found",
+            ApiError::Conflict(_) => "conflict",
+            ApiError::PayloadTooLarge(_) => "payload_too_large",
+            ApiError::UnsupportedMediaType(_) => "unsupported_media_type",
+            ApiError::Unprocessable(_) => "unprocessable",
+            ApiError::TooManyRequests(_) => "too_many_requests",
+            ApiError::BadGateway(_) => "bad_gateway",
+            ApiError::Unavailable(_) => "service_unavailable",
+            ApiError::Internal(_) => "internal",
+            ApiError::Other(s, _) if s.is_server_error() => "internal",
+            ApiError::Other(..) => "bad_request",
+            ApiError::WithDetails(inner, _) | ApiError::WithCode(inner, _) => inner.code(),
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            ApiError::BadRequest(m)
+            | ApiError::Unauthorized(m)
+            | ApiError::Forbidden(m)
+            | ApiError::NotFound(m)
+            | ApiError::Conflict(m)
+            | ApiError::PayloadTooLarge(m)
+            | ApiError::UnsupportedMediaType(m)
+            | ApiError::Unprocessable(m)
+            | ApiError::TooManyRequests(m)
+            | ApiError::BadGateway(m)
+            | ApiError::Unavailable(m)
+            | ApiError::Internal(m)
+            | ApiError::Other(_, m) => m,
+            ApiError::WithDetails(inner, _) | ApiError::WithCode(inner, _) => inner.message(),
+        }
+    }
+
+    fn details(&self) -> Option<&Value> {
+        match self {
+            ApiError::WithDetails(_, details) => Some(details),
+            ApiError::WithCode(inner, _) => inner.details(),
+            _ => None,
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let translated = i18n::translate(self.message());
+        let body = ErrorResponse {
+            code: self.code(),
+            error_code: self.error_code(),
+            message: translated
+                .clone()
+                .unwrap_or_else(|| self.message().to_string()),
+            details: self.details().cloned(),
+            request_id: request_id::current(),
+        };
+        let mut response = (self.status(), Json(body)).into_response();
+        if translated.is_some() {
+            response.headers_mut().insert(
+                header::CONTENT_LANGUAGE,
+                HeaderValue::from_static(i18n::current().tag()),
+            );
+        }
+        response
+    }
+}
+
+/// Lets handlers keep using helpers that still return `(StatusCode, String)`.
+impl From<(StatusCode, String)> for ApiError {
+    fn from((status, message): (StatusCode, String)) -> Self {
+        ApiError::from_status(status, message)
+    }
+}
+
+impl From<MultipartError> for ApiError {
+    fn from(e: MultipartError) -> Self {
+        ApiError::from_status(e.status(), e.body_text())
+    }
+}
+
+impl From<ImageError> for ApiError {
+    fn from(e: ImageError) -> Self {
+        let index = match &e {
+            ImageError::InvalidBase64 { index }
+            | ImageError::Empty { index }
+            | ImageError::TooLarge { index, .. }
+            | ImageError::NotAnImage { index }
+            | ImageError::TypeMismatch { index, .. } => Some(*index),
+            ImageError::TooMany { .. }
+            | ImageError::RequestTooLarge { .. }
+            | ImageError::NotStored { .. } => None,
+        };
+        let error = ApiError::from_status(e.status(), e.to_string()).with_code(e.code());
+        match index {
+            Some(index) => error.with_details(json!({ "index": index })),
+            None => error,
+        }
+    }
+}
+
+impl From<DuplicatePhoto> for ApiError {
+    fn from(e: DuplicatePhoto) -> Self {
+        ApiError::Conflict(e.to_string())
+            .with_code(ErrorCode::PhotoDuplicate)
+            .with_details(json!({ "possible_duplicate_of": e.meal_id }))
+    }
+}
+
+/// Largest plain-text error body [`envelope`] reads into a message.
+const MAX_PLAIN_ERROR_BYTES: usize = 16 * 1024;
+
+/// Middleware rewriting error responses that aren't JSON yet, such as
+/// extractor rejections and handlers returning `(StatusCode, String)`, into
+/// the [`ErrorResponse`] envelope. The status and other headers are kept.
+pub async fn envelope(req: Request, next: Next) -> Response {
+    let response = next.run(req).await;
+    let status = response.status();
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("application/json"));
+    if !(status.is_client_error() || status.is_server_error()) || is_json {
+        return response;
+    }
+    let (mut parts, body) = response.into_parts();
+    let text = to_bytes(body, MAX_PLAIN_ERROR_BYTES)
+        .await
+        .map(|bytes| String::from_utf8_lossy(&bytes).trim().to_string())
+        .unwrap_or_default();
+    let message = if text.is_empty() {
+        status.canonical_reason().unwrap_or("Error").to_string()
+    } else {
+        text
+    };
+    let enveloped = ApiError::from_status(status, message).into_response();
+    parts.headers.remove(header::CONTENT_LENGTH);
+    parts.headers.extend(enveloped.headers().clone());
+    Response::from_parts(parts, enveloped.into_body())
+}
+
+/// Response for a handler that panicked, for `CatchPanicLayer`: the usual
+/// `internal` error instead of a dropped connection. The panic message only
+/// goes to the logs.
+pub fn recover_panic(panic: Box<dyn Any + Send + 'static>) -> Response {
+    let message = panic
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| panic.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("non-string panic payload");
+    tracing::error!(panic = %message, "handler panicked");
+    ApiError::Internal("Internal server error".to_string()).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn body(error: ApiError) -> (StatusCode, Value) {
+        let response = error.into_response();
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap())
+    }
+
+    #[tokio::test]
+    async fn renders_code_and_message() {
+        let (status, body) = body(ApiError::NotFound("Meal not found".into())).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(
+            body,
+            json!({"code": "not_found", "error_code": "NOT_FOUND", "message": "Meal not found"})
+        );
+    }
+
+    #[tokio::test]
+    async fn includes_the_current_request_id() {
+        let (_, body) = request_id::scoped(
+            "req-1".to_string(),
+            body(ApiError::Internal("Failed to load meal".into())),
+        )
+        .await;
+        assert_eq!(body["request_id"], "req-1");
+    }
+
+    #[tokio::test]
+    async fn image_errors_carry_the_image_index() {
+        let (status, body) = body(ImageError::NotAnImage { index: 2 }.into()).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(body["code"], "unprocessable");
+        assert_eq!(body["error_code"], "UPLOAD_INVALID_IMAGE");
+        assert_eq!(body["details"], json!({"index": 2}));
+    }
+
+    #[tokio::test]
+    async fn specific_codes_keep_the_status_and_class() {
+        let error = ApiError::NotFound("Meal not found".into())
+            .with_code(ErrorCode::MealNotFound)
+            .with_details(json!({"id": 1}));
+        assert_eq!(error.error_code(), ErrorCode::MealNotFound);
+        let (status, body) = body(error).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["code"], "not_found");
+        assert_eq!(body["error_code"], "MEAL_NOT_FOUND");
+        assert_eq!(body["details"], json!({"id": 1}));
+    }
+
+    #[tokio::test]
+    async fn messages_follow_the_request_locale() {
+        let error = ApiError::NotFound("Meal not found".into()).with_code(ErrorCode::MealNotFound);
+        let response = i18n::scoped(i18n::Locale::Ru, async { error.into_response() }).await;
+        assert_eq!(response.headers()[header::CONTENT_LANGUAGE], "ru");
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(body["message"], "Приём пищи не найден");
+        assert_eq!(body["error_code"], "MEAL_NOT_FOUND");
+    }
+
+    #[test]
+    fn unknown_statuses_keep_their_status_and_take_the_class_code() {
+        let error = ApiError::from_status(StatusCode::GATEWAY_TIMEOUT, "upstream");
+        assert_eq!(error.status(), StatusCode::GATEWAY_TIMEOUT);
+        assert_eq!(error.code(), "internal");
+        assert_eq!(error.error_code(), ErrorCode::Internal);
+        let error = ApiError::from_status(StatusCode::GONE, "gone");
+        assert_eq!(error.status(), StatusCode::GONE);
+        assert_eq!(error.code(), "bad_request");
+        assert_eq!(error.error_code(), ErrorCode::BadRequest);
+    }
+
+    #[tokio::test]
+    async fn envelope_wraps_plain_errors_and_keeps_the_status() {
+        use axum::{body::Body, http::Request, routing::get, Router};
+        use tower::ServiceExt;
+
+        let app = Router::new()
+            .route(
+                "/plain",
+                get(|| async { (StatusCode::METHOD_NOT_ALLOWED, "Nope") }),
+            )
+            .layer(axum::middleware::from_fn(envelope));
+        let response = app
+            .oneshot(Request::get("/plain").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(
+            body,
+            json!({"code": "bad_request", "error_code": "BAD_REQUEST", "message": "Nope"})
+        );
+    }
+
+    #[tokio::test]
+    async fn panics_answer_500_without_failing_other_requests() {
+        use std::time::Duration;
+
+        use axum::{routing::get, Router};
+        use tower_http::catch_panic::CatchPanicLayer;
+
+        let app = Router::new()
+            .route(
+                "/slow",
+                get(|| async {
+                    tokio::time::sleep(Duration::from_millis(200)).await;
+                    "done"
+                }),
+            )
+            .route(
+                "/panic",
+                get(|| async {
+                    if true {
+                        panic!("boom");
+                    }
+                    "unreachable"
+                }),
+            )
+            .layer(CatchPanicLayer::custom(recover_panic))
+            .layer(axum::middleware::from_fn(request_id::propagate));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let client = reqwest::Client::new();
+        let slow = tokio::spawn(client.get(format!("http://{addr}/slow")).send());
+        let panicked = client
+            .get(format!("http://{addr}/panic"))
+            .header("x-request-id", "req-panic")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(panicked.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        let body: Value = panicked.json().await.unwrap();
+        assert_eq!(body["error_code"], "INTERNAL");
+        assert_eq!(body["request_id"], "req-panic");
+
+        let slow = slow.await.unwrap().unwrap();
+        assert_eq!(slow.status(), StatusCode::OK);
+        assert_eq!(slow.text().await.unwrap(), "done");
+        // The server keeps serving after the panic.
+        let again = client.get(format!("http://{addr}/slow")).send().await;
+        assert_eq!(again.unwrap().status(), StatusCode::OK);
+    }
+}
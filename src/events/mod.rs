@@ -0,0 +1,121 @@
+//! Domain events with a transactional outbox. Services record an event in
+//! the same transaction as the change it describes, and the relay task
+//! delivers recorded events to every subscriber at least once, so
+//! subscribers must tolerate duplicates.
+
+pub mod relay;
+pub mod repo;
+mod sink;
+
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::{
+    achievements::{dto::Badge, services::AchievementSubscriber},
+    db::AppState,
+    notifications::services::NotificationSubscriber,
+    push::services::PushSubscriber,
+    reactions::dto::Reaction,
+    webhooks::delivery::WebhookSubscriber,
+};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DomainEvent {
+    MealCreated {
+        meal_id: Uuid,
+    },
+    /// Analysis stored a nutrition estimate for the meal.
+    NutritionReady {
+        meal_id: Uuid,
+    },
+    UserRegistered,
+    /// Someone else reacted to the user's meal.
+    MealReaction {
+        meal_id: Uuid,
+        reaction: Reaction,
+        reacted_by: Uuid,
+    },
+    AchievementUnlocked {
+        badge: Badge,
+    },
+}
+
+impl DomainEvent {
+    /// Matches the serialized `type`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            DomainEvent::MealCreated { .. } => "meal_created",
+            DomainEvent::NutritionReady { .. } => "nutrition_ready",
+            DomainEvent::UserRegistered => "user_registered",
+            DomainEvent::MealReaction { .. } => "meal_reaction",
+            DomainEvent::AchievementUnlocked { .. } => "achievement_unlocked",
+        }
+    }
+}
+
+/// A recorded event as subscribers receive it. Serializes flat, e.g.
+/// `{"id": 7, "user_id": "…", "type": "meal_created", "meal_id": "…", …}`.
+#[derive(Debug, Clone, Serialize)]
+pub struct OutboxEvent {
+    pub id: i64,
+    pub user_id: Uuid,
+    #[serde(flatten)]
+    pub event: DomainEvent,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+#[axum::async_trait]
+pub trait EventSubscriber: Send + Sync {
+    /// Identifies the subscriber in logs and delivery errors.
+    fn name(&self) -> &'static str;
+
+    async fn handle(&self, event: &OutboxEvent) -> anyhow::Result<()>;
+}
+
+/// Subscribers of this process, built from the configuration.
+pub fn subscribers(state: &AppState) -> anyhow::Result<Vec<Arc<dyn EventSubscriber>>> {
+    let config = &state.config.events;
+    let mut subscribers: Vec<Arc<dyn EventSubscriber>> = vec![
+        Arc::new(WebhookSubscriber::new(state.db.clone())),
+        Arc::new(AchievementSubscriber::new(state.clone())),
+        Arc::new(NotificationSubscriber::new(state.clone())),
+    ];
+    if !state.push.is_empty() {
+        subscribers.push(Arc::new(PushSubscriber::new(state.clone())));
+    }
+    if let Some(url) = &config.sink_url {
+        subscribers.push(Arc::new(sink::HttpSink::new(url.clone(), config)?));
+    }
+    Ok(subscribers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn events_serialize_flat() {
+        let meal_id = Uuid::new_v4();
+        let event = OutboxEvent {
+            id: 7,
+            user_id: Uuid::nil(),
+            event: DomainEvent::MealCreated { meal_id },
+            created_at: OffsetDateTime::UNIX_EPOCH,
+        };
+        let value = serde_json::to_value(&event).unwrap();
+        assert_eq!(value["type"], event.event.name());
+        assert_eq!(value["meal_id"], meal_id.to_string());
+        assert_eq!(value["created_at"], "1970-01-01T00:00:00Z");
+
+        let payload = serde_json::to_value(&DomainEvent::UserRegistered).unwrap();
+        assert_eq!(
+            serde_json::from_value::<DomainEvent>(payload).unwrap(),
+            DomainEvent::UserRegistered
+        );
+    }
+}
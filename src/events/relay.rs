@@ -0,0 +1,187 @@
+//! Delivers recorded events from the outbox to the subscribers.
+
+use std::{sync::Arc, time::Duration};
+
+use time::OffsetDateTime;
+use tracing::{error, info, warn};
+
+use crate::{
+    config::EventsConfig,
+    db::AppState,
+    events::{
+        repo::{self, OutboxRow},
+        DomainEvent, EventSubscriber, OutboxEvent,
+    },
+    tasks::{BackgroundTasks, TaskContext},
+};
+
+/// A relay holding events longer than this is assumed dead.
+const LOCK_TIMEOUT_SECS: i64 = 300;
+
+pub fn register(
+    tasks: &mut BackgroundTasks,
+    state: &AppState,
+    subscribers: Vec<Arc<dyn EventSubscriber>>,
+) {
+    let state = state.clone();
+    info!(subscribers = subscribers.len(), "event relay started");
+    tasks.spawn("outbox-relay", move |ctx| {
+        run_relay(state, subscribers, ctx)
+    });
+}
+
+async fn run_relay(
+    state: AppState,
+    subscribers: Vec<Arc<dyn EventSubscriber>>,
+    mut ctx: TaskContext,
+) {
+    let config = &state.config.events;
+    let idle = Duration::from_millis(config.poll_interval_ms);
+    loop {
+        match relay_batch(&state, &subscribers, config).await {
+            // A full batch means more are probably waiting.
+            Ok(relayed) if relayed as i64 == config.batch_size => {
+                ctx.succeeded();
+                if ctx.is_shutting_down() {
+                    break;
+                }
+                continue;
+            }
+            Ok(_) => ctx.succeeded(),
+            Err(e) => {
+                error!(error = %e, "relaying events failed");
+                ctx.failed(&e);
+            }
+        }
+        if !ctx.sleep(idle).await {
+            break;
+        }
+    }
+}
+
+/// Claims one batch and delivers it, returning how many events it claimed.
+async fn relay_batch(
+    state: &AppState,
+    subscribers: &[Arc<dyn EventSubscriber>],
+    config: &EventsConfig,
+) -> anyhow::Result<usize> {
+    let rows = repo::claim_batch(&state.db, config.batch_size, LOCK_TIMEOUT_SECS).await?;
+    let claimed = rows.len();
+    for row in rows {
+        let (id, attempts) = (row.id, row.attempts);
+        let result = match decode(row) {
+            Ok(event) => dispatch(subscribers, &event).await,
+            Err(e) => Err(format!("undecodable payload: {}", e)),
+        };
+        match result {
+            Ok(()) => repo::mark_dispatched(&state.db, id).await?,
+            Err(e) => {
+                let retry_at = OffsetDateTime::now_utc() + retry_delay(attempts);
+                warn!(event_id = id, attempts, error = %e, "event delivery failed");
+                repo::mark_failed(&state.db, id, &e, retry_at).await?;
+            }
+        }
+    }
+    Ok(claimed)
+}
+
+fn decode(row: OutboxRow) -> serde_json::Result<OutboxEvent> {
+    Ok(OutboxEvent {
+        id: row.id,
+        user_id: row.user_id,
+        event: serde_json::from_value::<DomainEvent>(row.payload)?,
+        created_at: row.created_at,
+    })
+}
+
+/// Hands the event to every subscriber. One failing doesn't keep the others
+/// from receiving it; the whole event is retried, so those get it again.
+async fn dispatch(
+    subscribers: &[Arc<dyn EventSubscriber>],
+    event: &OutboxEvent,
+) -> Result<(), String> {
+    let mut failures = Vec::new();
+    for subscriber in subscribers {
+        if let Err(e) = subscriber.handle(event).await {
+            failures.push(format!("{}: {:#}", subscriber.name(), e));
+        }
+    }
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(failures.join("; "))
+    }
+}
+
+/// `5s * 2^(attempts - 1)`, capped at one hour. Events are never given up
+/// on; they wait until the subscriber recovers.
+fn retry_delay(attempts: i32) -> Duration {
+    let exp = attempts.saturating_sub(1).clamp(0, 16) as u32;
+    Duration::from_secs((5u64 << exp).min(3600))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use uuid::Uuid;
+
+    use super::*;
+
+    /// Records the ids it sees; fails when `fail` is set.
+    struct Recording {
+        seen: Mutex<Vec<i64>>,
+        fail: bool,
+    }
+
+    #[axum::async_trait]
+    impl EventSubscriber for Recording {
+        fn name(&self) -> &'static str {
+            if self.fail {
+                "failing"
+            } else {
+                "recording"
+            }
+        }
+
+        async fn handle(&self, event: &OutboxEvent) -> anyhow::Result<()> {
+            self.seen.lock().unwrap().push(event.id);
+            if self.fail {
+                anyhow::bail!("unreachable");
+            }
+            Ok(())
+        }
+    }
+
+    fn subscriber(fail: bool) -> Arc<Recording> {
+        Arc::new(Recording {
+            seen: Mutex::new(Vec::new()),
+            fail,
+        })
+    }
+
+    #[tokio::test]
+    async fn dispatch_reaches_all_subscribers_and_reports_failures() {
+        let (ok, failing) = (subscriber(false), subscriber(true));
+        let subscribers: Vec<Arc<dyn EventSubscriber>> = vec![failing.clone(), ok.clone()];
+        let event = OutboxEvent {
+            id: 3,
+            user_id: Uuid::nil(),
+            event: DomainEvent::UserRegistered,
+            created_at: OffsetDateTime::now_utc(),
+        };
+
+        let err = dispatch(&subscribers, &event).await.unwrap_err();
+        assert_eq!(err, "failing: unreachable");
+        assert_eq!(*ok.seen.lock().unwrap(), vec![3]);
+        assert_eq!(*failing.seen.lock().unwrap(), vec![3]);
+        assert!(dispatch(&subscribers[1..], &event).await.is_ok());
+    }
+
+    #[test]
+    fn retry_delay_doubles_and_caps() {
+        assert_eq!(retry_delay(1), Duration::from_secs(5));
+        assert_eq!(retry_delay(3), Duration::from_secs(20));
+        assert_eq!(retry_delay(30), Duration::from_secs(3600));
+    }
+}
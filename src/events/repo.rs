@@ -0,0 +1,116 @@
+use sqlx::{FromRow, PgExecutor, PgPool};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::events::DomainEvent;
+
+#[derive(Debug, Clone, FromRow)]
+pub struct OutboxRow {
+    pub id: i64,
+    pub user_id: Uuid,
+    pub payload: serde_json::Value,
+    pub created_at: OffsetDateTime,
+    pub attempts: i32,
+}
+
+/// Records an event; pass the transaction of the change it describes so both
+/// commit or roll back together.
+pub async fn record(
+    db: impl PgExecutor<'_>,
+    user_id: Uuid,
+    event: &DomainEvent,
+) -> anyhow::Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO outbox (user_id, event_type, payload)
+        VALUES ($1, $2, $3)
+        "#,
+    )
+    .bind(user_id)
+    .bind(event.name())
+    .bind(serde_json::to_value(event)?)
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+/// Locks up to `limit` due events, oldest first. Events locked by a relay
+/// that died more than `lock_timeout_secs` ago are picked up again.
+pub async fn claim_batch(
+    db: &PgPool,
+    limit: i64,
+    lock_timeout_secs: i64,
+) -> anyhow::Result<Vec<OutboxRow>> {
+    let mut rows = sqlx::query_as::<_, OutboxRow>(
+        r#"
+        UPDATE outbox
+        SET locked_at = NOW(), attempts = attempts + 1
+        WHERE id IN (
+            SELECT id FROM outbox
+            WHERE dispatched_at IS NULL
+              AND next_attempt_at <= NOW()
+              AND (locked_at IS NULL OR locked_at < NOW() - make_interval(secs => $2))
+            ORDER BY id
+            FOR UPDATE SKIP LOCKED
+            LIMIT $1
+        )
+        RETURNING id, user_id, payload, created_at, attempts
+        "#,
+    )
+    .bind(limit)
+    .bind(lock_timeout_secs as f64)
+    .fetch_all(db)
+    .await?;
+    // UPDATE ... RETURNING doesn't keep the subquery's order.
+    rows.sort_by_key(|row| row.id);
+    Ok(rows)
+}
+
+pub async fn mark_dispatched(db: &PgPool, id: i64) -> anyhow::Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE outbox
+        SET dispatched_at = NOW(), locked_at = NULL, last_error = NULL
+        WHERE id = $1
+        "#,
+    )
+    .bind(id)
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+pub async fn mark_failed(
+    db: &PgPool,
+    id: i64,
+    error: &str,
+    retry_at: OffsetDateTime,
+) -> anyhow::Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE outbox
+        SET locked_at = NULL, last_error = $2, next_attempt_at = $3
+        WHERE id = $1
+        "#,
+    )
+    .bind(id)
+    .bind(error)
+    .bind(retry_at)
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+/// Deletes events delivered more than `retention_days` ago.
+pub async fn prune_dispatched(db: &PgPool, retention_days: i32) -> anyhow::Result<u64> {
+    let result = sqlx::query(
+        r#"
+        DELETE FROM outbox
+        WHERE dispatched_at < NOW() - make_interval(days => $1)
+        "#,
+    )
+    .bind(retention_days)
+    .execute(db)
+    .await?;
+    Ok(result.rows_affected())
+}
@@ -0,0 +1,39 @@
+use std::time::Duration;
+
+use crate::{
+    config::EventsConfig,
+    events::{EventSubscriber, OutboxEvent},
+};
+
+/// Posts every event as JSON to an external endpoint (`EVENTS_SINK_URL`).
+/// Any non-2xx answer counts as a failed delivery and is retried.
+pub struct HttpSink {
+    http: reqwest::Client,
+    url: String,
+}
+
+impl HttpSink {
+    pub fn new(url: String, config: &EventsConfig) -> anyhow::Result<Self> {
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(config.sink_timeout_secs))
+            .build()?;
+        Ok(Self { http, url })
+    }
+}
+
+#[axum::async_trait]
+impl EventSubscriber for HttpSink {
+    fn name(&self) -> &'static str {
+        "http_sink"
+    }
+
+    async fn handle(&self, event: &OutboxEvent) -> anyhow::Result<()> {
+        self.http
+            .post(&self.url)
+            .json(event)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,280 @@
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::{
+    goals::dto::GoalsResponse,
+    meal_items::dto::MealItem,
+    meals::dto::{MealStatus, MealType, NutritionSource},
+    preferences::dto::PreferencesResponse,
+};
+
+/// Rows fetched per database round trip while streaming an export.
+pub const EXPORT_PAGE_SIZE: i64 = 500;
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    #[default]
+    Csv,
+    Json,
+}
+
+impl ExportFormat {
+    pub fn content_type(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "text/csv; charset=utf-8",
+            ExportFormat::Json => "application/json",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ExportQuery {
+    #[serde(default)]
+    pub format: ExportFormat,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub from: Option<OffsetDateTime>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub to: Option<OffsetDateTime>,
+}
+
+/// One meal with its nutrition totals, flattened for spreadsheets.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct ExportedMeal {
+    pub id: Uuid,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    pub title: Option<String>,
+    pub notes: Option<String>,
+    pub meal_type: Option<MealType>,
+    pub tags: Vec<String>,
+    pub status: MealStatus,
+    pub total_calories_kcal: Option<f64>,
+    pub protein_g: Option<f64>,
+    pub fat_g: Option<f64>,
+    pub carbs_g: Option<f64>,
+    pub sodium_mg: Option<f64>,
+    pub sugar_g: Option<f64>,
+    pub fiber_g: Option<f64>,
+    pub global_score: Option<f64>,
+    pub nutrition_source: Option<NutritionSource>,
+}
+
+pub const CSV_HEADER: &str = "id,created_at,title,notes,meal_type,tags,status,\
+total_calories_kcal,protein_g,fat_g,carbs_g,sodium_mg,sugar_g,fiber_g,global_score,\
+nutrition_source\r\n";
+
+/// Quotes a CSV field when needed and defuses values a spreadsheet would
+/// run as a formula.
+fn csv_field(value: &str) -> String {
+    let value = if value.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        format!("'{value}")
+    } else {
+        value.to_string()
+    };
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
+
+/// Serde name of a unit enum, e.g. `breakfast`.
+fn enum_name<T: Serialize>(value: &T) -> String {
+    match serde_json::to_value(value) {
+        Ok(serde_json::Value::String(s)) => s,
+        _ => String::new(),
+    }
+}
+
+impl ExportedMeal {
+    /// One CSV record, CRLF-terminated. Tags are joined with `;`.
+    pub fn csv_record(&self) -> String {
+        let number = |v: Option<f64>| v.map(|v| v.to_string()).unwrap_or_default();
+        let created_at = self
+            .created_at
+            .format(&time::format_description::well_known::Rfc3339)
+            .unwrap_or_default();
+        let fields = [
+            self.id.to_string(),
+            created_at,
+            csv_field(self.title.as_deref().unwrap_or_default()),
+            csv_field(self.notes.as_deref().unwrap_or_default()),
+            self.meal_type.as_ref().map(enum_name).unwrap_or_default(),
+            csv_field(&self.tags.join(";")),
+            enum_name(&self.status),
+            number(self.total_calories_kcal),
+            number(self.protein_g),
+            number(self.fat_g),
+            number(self.carbs_g),
+            number(self.sodium_mg),
+            number(self.sugar_g),
+            number(self.fiber_g),
+            number(self.global_score),
+            self.nutrition_source
+                .as_ref()
+                .map(enum_name)
+                .unwrap_or_default(),
+        ];
+        format!("{}\r\n", fields.join(","))
+    }
+}
+
+/// Progress of a full-account export.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+pub enum DataExportStatus {
+    Pending,
+    Running,
+    Done,
+    Failed,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct DataExport {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub status: DataExportStatus,
+    pub s3_key: Option<String>,
+    pub size_bytes: Option<i64>,
+    pub created_at: OffsetDateTime,
+    pub completed_at: Option<OffsetDateTime>,
+}
+
+/// Export status; `download_url` is set once the archive is ready and is
+/// valid until `expires_at`.
+#[derive(Debug, Serialize)]
+pub struct DataExportResponse {
+    pub id: Uuid,
+    pub status: DataExportStatus,
+    pub size_bytes: Option<i64>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub completed_at: Option<OffsetDateTime>,
+    pub download_url: Option<String>,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub expires_at: Option<OffsetDateTime>,
+}
+
+/// `profile.json` in the archive.
+#[derive(Debug, Serialize)]
+pub struct ArchivedProfile {
+    pub id: Uuid,
+    pub email: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    pub goals: GoalsResponse,
+    pub preferences: PreferencesResponse,
+    #[serde(with = "time::serde::rfc3339")]
+    pub exported_at: OffsetDateTime,
+}
+
+/// A meal item tagged with its meal, for `meal_items.json`.
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct ArchivedItem {
+    pub meal_id: Uuid,
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub item: MealItem,
+}
+
+/// Entry of `photos.json`; `file` is the photo's path inside the archive,
+/// `null` if the object was missing from storage.
+#[derive(Debug, Serialize)]
+pub struct ArchivedPhoto {
+    pub id: Uuid,
+    pub meal_id: Option<Uuid>,
+    pub file: Option<String>,
+    pub content_type: Option<String>,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub taken_at: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+impl ArchivedPhoto {
+    /// Archive path for a photo, e.g. `photos/<meal_id>/<id>.jpg`.
+    pub fn path(meal_id: Option<Uuid>, id: Uuid, content_type: Option<&str>) -> String {
+        let ext = match content_type {
+            Some("image/png") => "png",
+            Some("image/webp") => "webp",
+            Some("image/heic") => "heic",
+            _ => "jpg",
+        };
+        match meal_id {
+            Some(meal_id) => format!("photos/{meal_id}/{id}.{ext}"),
+            None => format!("photos/unlinked/{id}.{ext}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::datetime;
+
+    fn meal() -> ExportedMeal {
+        ExportedMeal {
+            id: Uuid::nil(),
+            created_at: datetime!(2024-05-01 12:30 UTC),
+            title: Some("Eggs, \"sunny\" side".into()),
+            notes: Some("=HYPERLINK(\"x\")".into()),
+            meal_type: Some(MealType::Breakfast),
+            tags: vec!["home".into(), "quick".into()],
+            status: MealStatus::Done,
+            total_calories_kcal: Some(320.5),
+            protein_g: None,
+            fat_g: None,
+            carbs_g: None,
+            sodium_mg: None,
+            sugar_g: None,
+            fiber_g: None,
+            global_score: Some(71.0),
+            nutrition_source: Some(NutritionSource::Manual),
+        }
+    }
+
+    #[test]
+    fn csv_record_matches_header_columns() {
+        let record = meal().csv_record();
+        assert!(record.ends_with("\r\n"));
+        assert_eq!(
+            record,
+            "00000000-0000-0000-0000-000000000000,2024-05-01T12:30:00Z,\
+             \"Eggs, \"\"sunny\"\" side\",\"'=HYPERLINK(\"\"x\"\")\",breakfast,home;quick,done,\
+             320.5,,,,,,,71,manual\r\n"
+        );
+        assert_eq!(CSV_HEADER.split(',').count(), 16);
+    }
+
+    #[test]
+    fn photo_paths_group_by_meal() {
+        let id = Uuid::nil();
+        assert_eq!(
+            ArchivedPhoto::path(Some(id), id, Some("image/png")),
+            format!("photos/{id}/{id}.png")
+        );
+        assert_eq!(
+            ArchivedPhoto::path(None, id, None),
+            format!("photos/unlinked/{id}.jpg")
+        );
+    }
+
+    #[test]
+    fn plain_fields_are_not_quoted() {
+        assert_eq!(csv_field("Oatmeal"), "Oatmeal");
+        assert_eq!(csv_field("-5"), "'-5");
+    }
+}
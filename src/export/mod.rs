@@ -0,0 +1,5 @@
+//! Downloads of a user's data.
+
+pub mod dto;
+pub mod repo;
+pub mod services;
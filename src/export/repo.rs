@@ -0,0 +1,205 @@
+use sqlx::{PgExecutor, PgPool};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::{
+    db::User,
+    export::dto::{ArchivedItem, DataExport, DataExportStatus, ExportedMeal},
+    photos::repo::Photo,
+};
+
+const EXPORT_COLUMNS: &str = "id, user_id, status, s3_key, size_bytes, created_at, completed_at";
+
+/// Meals in creation order after the `(created_at, id)` cursor, so pages
+/// stay stable while new meals are logged.
+pub async fn meals_page(
+    db: &PgPool,
+    user_id: Uuid,
+    from: Option<OffsetDateTime>,
+    to: Option<OffsetDateTime>,
+    after: Option<(OffsetDateTime, Uuid)>,
+    limit: i64,
+) -> anyhow::Result<Vec<ExportedMeal>> {
+    let meals = sqlx::query_as::<_, ExportedMeal>(
+        r#"
+        SELECT m.id, m.created_at, m.title, m.notes, m.meal_type, m.tags, m.status,
+               n.total_calories_kcal::float8 AS total_calories_kcal,
+               n.protein_g::float8 AS protein_g,
+               n.fat_g::float8 AS fat_g,
+               n.carbs_g::float8 AS carbs_g,
+               n.sodium_mg::float8 AS sodium_mg,
+               n.sugar_g::float8 AS sugar_g,
+               n.fiber_g::float8 AS fiber_g,
+               n.global_score::float8 AS global_score,
+               n.source AS nutrition_source
+        FROM meals m
+        LEFT JOIN meal_nutrition n ON n.meal_id = m.id
+        WHERE m.user_id = $1
+          AND ($2::timestamptz IS NULL OR m.created_at >= $2)
+          AND ($3::timestamptz IS NULL OR m.created_at < $3)
+          AND ($4::timestamptz IS NULL OR (m.created_at, m.id) > ($4, $5))
+        ORDER BY m.created_at, m.id
+        LIMIT $6
+        "#,
+    )
+    .bind(user_id)
+    .bind(from)
+    .bind(to)
+    .bind(after.map(|(created_at, _)| created_at))
+    .bind(after.map(|(_, id)| id))
+    .bind(limit)
+    .fetch_all(db)
+    .await?;
+    Ok(meals)
+}
+
+pub async fn find_user(db: &PgPool, user_id: Uuid) -> anyhow::Result<Option<User>> {
+    let user = sqlx::query_as::<_, User>(
+        r#"SELECT id, email, password_hash, created_at FROM users WHERE id = $1"#,
+    )
+    .bind(user_id)
+    .fetch_optional(db)
+    .await?;
+    Ok(user)
+}
+
+/// Every meal item of the user, grouped by meal.
+pub async fn items_for_user(db: &PgPool, user_id: Uuid) -> anyhow::Result<Vec<ArchivedItem>> {
+    let items = sqlx::query_as::<_, ArchivedItem>(
+        r#"
+        SELECT i.meal_id, i.id, i.food_source, i.food_id, i.name,
+               i.quantity::float8 AS quantity, i.unit,
+               i.total_calories_kcal::float8 AS total_calories_kcal,
+               i.protein_g::float8 AS protein_g,
+               i.fat_g::float8 AS fat_g,
+               i.carbs_g::float8 AS carbs_g,
+               i.sodium_mg::float8 AS sodium_mg,
+               i.sugar_g::float8 AS sugar_g,
+               i.fiber_g::float8 AS fiber_g,
+               i.created_at, i.updated_at
+        FROM meal_items i
+        JOIN meals m ON m.id = i.meal_id
+        WHERE m.user_id = $1
+        ORDER BY m.created_at, m.id, i.created_at, i.id
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(db)
+    .await?;
+    Ok(items)
+}
+
+/// Every photo the user uploaded, including ones no longer linked to a meal.
+pub async fn photos_for_user(db: &PgPool, user_id: Uuid) -> anyhow::Result<Vec<Photo>> {
+    let photos = sqlx::query_as::<_, Photo>(
+        r#"
+        SELECT id, user_id, meal_id, s3_key, original_s3_key, content_type, size_bytes, width,
+               height, content_hash, taken_at, status, failure_reason, created_at
+        FROM photos
+        WHERE user_id = $1
+        ORDER BY created_at, id
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(db)
+    .await?;
+    Ok(photos)
+}
+
+pub async fn create_data_export(
+    db: impl PgExecutor<'_>,
+    user_id: Uuid,
+) -> anyhow::Result<DataExport> {
+    let export = sqlx::query_as::<_, DataExport>(&format!(
+        "INSERT INTO data_exports (user_id) VALUES ($1) RETURNING {EXPORT_COLUMNS}"
+    ))
+    .bind(user_id)
+    .fetch_one(db)
+    .await?;
+    Ok(export)
+}
+
+/// The user's pending or running export, if one is already underway.
+pub async fn find_active_export(
+    db: impl PgExecutor<'_>,
+    user_id: Uuid,
+) -> anyhow::Result<Option<DataExport>> {
+    let export = sqlx::query_as::<_, DataExport>(&format!(
+        r#"
+        SELECT {EXPORT_COLUMNS} FROM data_exports
+        WHERE user_id = $1 AND status IN ('pending', 'running')
+        ORDER BY created_at DESC
+        LIMIT 1
+        "#
+    ))
+    .bind(user_id)
+    .fetch_optional(db)
+    .await?;
+    Ok(export)
+}
+
+pub async fn find_data_export(db: &PgPool, export_id: Uuid) -> anyhow::Result<Option<DataExport>> {
+    let export = sqlx::query_as::<_, DataExport>(&format!(
+        "SELECT {EXPORT_COLUMNS} FROM data_exports WHERE id = $1"
+    ))
+    .bind(export_id)
+    .fetch_optional(db)
+    .await?;
+    Ok(export)
+}
+
+pub async fn find_data_export_for_owner(
+    db: impl PgExecutor<'_>,
+    user_id: Uuid,
+    export_id: Uuid,
+) -> anyhow::Result<Option<DataExport>> {
+    let export = sqlx::query_as::<_, DataExport>(&format!(
+        "SELECT {EXPORT_COLUMNS} FROM data_exports WHERE id = $1 AND user_id = $2"
+    ))
+    .bind(export_id)
+    .bind(user_id)
+    .fetch_optional(db)
+    .await?;
+    Ok(export)
+}
+
+pub async fn set_export_status(
+    db: &PgPool,
+    export_id: Uuid,
+    status: DataExportStatus,
+) -> anyhow::Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE data_exports
+        SET status = $2,
+            completed_at = CASE WHEN $2 = 'failed' THEN NOW() ELSE completed_at END
+        WHERE id = $1
+        "#,
+    )
+    .bind(export_id)
+    .bind(status)
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+pub async fn complete_export(
+    db: &PgPool,
+    export_id: Uuid,
+    s3_key: &str,
+    size_bytes: i64,
+) -> anyhow::Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE data_exports
+        SET status = 'done', s3_key = $2, size_bytes = $3, completed_at = NOW()
+        WHERE id = $1
+        "#,
+    )
+    .bind(export_id)
+    .bind(s3_key)
+    .bind(size_bytes)
+    .execute(db)
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,465 @@
+use std::{
+    fs::File,
+    io::{Seek, SeekFrom, Write},
+    time::Duration,
+};
+
+use axum::body::Bytes;
+use futures::{stream, Stream, TryStreamExt};
+use serde::Serialize;
+use sqlx::PgPool;
+use time::OffsetDateTime;
+use tokio::io::AsyncReadExt;
+use tracing::{info, warn};
+use uuid::Uuid;
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipWriter};
+
+use crate::{
+    db::AppState,
+    export::{
+        dto::{
+            ArchivedPhoto, ArchivedProfile, DataExport, DataExportResponse, DataExportStatus,
+            ExportFormat, ExportQuery, ExportedMeal, CSV_HEADER, EXPORT_PAGE_SIZE,
+        },
+        repo,
+    },
+    foods::repo as foods_repo,
+    goals::{dto::GoalsResponse, repo as goals_repo},
+    jobs::{repo as jobs_repo, Job},
+    preferences::{dto::PreferencesResponse, services as preferences_services},
+    push::repo as push_repo,
+    retention::repo as retention_repo,
+    storage::{CompletedPart, ObjectStream, StorageClient},
+    weights::repo as weights_repo,
+};
+
+/// Validity of the download link returned for a finished export.
+pub const DOWNLOAD_TTL: Duration = Duration::from_secs(60 * 60);
+
+enum Cursor {
+    Start,
+    After(OffsetDateTime, Uuid),
+    Done,
+}
+
+/// Renders one page; `first` and `last` add the CSV header or JSON brackets.
+fn render(format: ExportFormat, meals: &[ExportedMeal], first: bool, last: bool) -> String {
+    let mut out = String::new();
+    match format {
+        ExportFormat::Csv => {
+            if first {
+                out.push_str(CSV_HEADER);
+            }
+            for meal in meals {
+                out.push_str(&meal.csv_record());
+            }
+        }
+        ExportFormat::Json => {
+            if first {
+                out.push('[');
+            }
+            for (i, meal) in meals.iter().enumerate() {
+                if !(first && i == 0) {
+                    out.push(',');
+                }
+                out.push('\n');
+                // Plain data; serializing it cannot fail.
+                out.push_str(&serde_json::to_string(meal).unwrap_or_default());
+            }
+            if last {
+                out.push_str("\n]\n");
+            }
+        }
+    }
+    out
+}
+
+/// Streams a user's meals page by page, so large histories are never held
+/// in memory. A database error ends the stream, cutting the download short.
+pub fn meals_export(
+    db: PgPool,
+    user_id: Uuid,
+    query: ExportQuery,
+) -> impl Stream<Item = anyhow::Result<Bytes>> + Send + 'static {
+    stream::try_unfold(Cursor::Start, move |cursor| {
+        let db = db.clone();
+        let query = query.clone();
+        async move {
+            let after = match cursor {
+                Cursor::Done => return Ok(None),
+                Cursor::Start => None,
+                Cursor::After(created_at, id) => Some((created_at, id)),
+            };
+            let meals =
+                repo::meals_page(&db, user_id, query.from, query.to, after, EXPORT_PAGE_SIZE)
+                    .await?;
+            let last = (meals.len() as i64) < EXPORT_PAGE_SIZE;
+            let chunk = render(query.format, &meals, after.is_none(), last);
+            let next = match meals.last() {
+                Some(meal) if !last => Cursor::After(meal.created_at, meal.id),
+                _ => Cursor::Done,
+            };
+            Ok(Some((Bytes::from(chunk), next)))
+        }
+    })
+}
+
+/// Queues a full-account export, or returns the one already underway.
+pub async fn request_data_export(
+    state: &AppState,
+    user_id: Uuid,
+) -> anyhow::Result<DataExportResponse> {
+    let mut tx = state.begin_as(user_id).await?;
+    if let Some(export) = repo::find_active_export(&mut *tx, user_id).await? {
+        tx.commit().await?;
+        return data_export_response(state, export).await;
+    }
+    let export = repo::create_data_export(&mut *tx, user_id).await?;
+    tx.commit().await?;
+    let job = Job::ExportUserData {
+        export_id: export.id,
+    };
+    jobs_repo::enqueue(&state.db, &job, state.config.jobs.max_attempts).await?;
+    info!(user_id = %user_id, export_id = %export.id, "data export queued");
+    data_export_response(state, export).await
+}
+
+/// Status of one of the user's exports, with a fresh download link once done.
+pub async fn get_data_export(
+    state: &AppState,
+    user_id: Uuid,
+    export_id: Uuid,
+) -> anyhow::Result<Option<DataExportResponse>> {
+    let mut tx = state.begin_as(user_id).await?;
+    let export = repo::find_data_export_for_owner(&mut *tx, user_id, export_id).await?;
+    tx.commit().await?;
+    match export {
+        Some(export) => Ok(Some(data_export_response(state, export).await?)),
+        None => Ok(None),
+    }
+}
+
+async fn data_export_response(
+    state: &AppState,
+    export: DataExport,
+) -> anyhow::Result<DataExportResponse> {
+    let (download_url, expires_at) = match (&export.status, &export.s3_key) {
+        (DataExportStatus::Done, Some(key)) => (
+            Some(state.storage.presign_get(key, DOWNLOAD_TTL).await?),
+            Some(OffsetDateTime::now_utc() + DOWNLOAD_TTL),
+        ),
+        _ => (None, None),
+    };
+    Ok(DataExportResponse {
+        id: export.id,
+        status: export.status,
+        size_bytes: export.size_bytes,
+        created_at: export.created_at,
+        completed_at: export.completed_at,
+        download_url,
+        expires_at,
+    })
+}
+
+/// Storage key of an export archive.
+fn archive_key(user_id: Uuid, export_id: Uuid) -> String {
+    format!("exports/{user_id}/{export_id}.zip")
+}
+
+/// Size of the parts an archive is uploaded in; storage needs at least
+/// 5 MiB for all but the last.
+const ARCHIVE_PART_BYTES: usize = 8 * 1024 * 1024;
+
+/// ZIP being assembled in an anonymous temp file, so large accounts aren't
+/// held in memory. JSON is deflated; photos are stored as-is since they are
+/// already compressed.
+pub(crate) struct Archive {
+    zip: ZipWriter<File>,
+}
+
+impl Archive {
+    pub(crate) fn new() -> anyhow::Result<Self> {
+        Ok(Self {
+            zip: ZipWriter::new(tempfile::tempfile()?),
+        })
+    }
+
+    pub(crate) fn add_json<T: Serialize + ?Sized>(
+        &mut self,
+        name: &str,
+        value: &T,
+    ) -> anyhow::Result<()> {
+        let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+        self.zip.start_file(name, options)?;
+        serde_json::to_writer_pretty(&mut self.zip, value)?;
+        Ok(())
+    }
+
+    /// Adds text that compresses well, such as CSV.
+    pub(crate) fn add_text(&mut self, name: &str, bytes: &[u8]) -> anyhow::Result<()> {
+        let options = SimpleFileOptions::default()
+            .compression_method(CompressionMethod::Deflated)
+            .large_file(bytes.len() as u64 >= u32::MAX as u64);
+        self.zip.start_file(name, options)?;
+        self.zip.write_all(bytes)?;
+        Ok(())
+    }
+
+    /// Copies a stored object into the archive chunk by chunk.
+    async fn add_object(&mut self, name: &str, object: ObjectStream) -> anyhow::Result<()> {
+        let size = object.content_length.unwrap_or_default();
+        let options = SimpleFileOptions::default()
+            .compression_method(CompressionMethod::Stored)
+            .large_file(size >= u32::MAX as u64);
+        self.zip.start_file(name, options)?;
+        let mut body = object.body;
+        while let Some(chunk) = body.try_next().await? {
+            self.zip.write_all(&chunk)?;
+        }
+        Ok(())
+    }
+
+    /// Completes the ZIP, returning the file rewound to its start.
+    pub(crate) fn finish(self) -> anyhow::Result<File> {
+        let mut file = self.zip.finish()?;
+        file.seek(SeekFrom::Start(0))?;
+        Ok(file)
+    }
+
+    /// Completes the ZIP and writes it to `key` as a multipart upload,
+    /// [`ARCHIVE_PART_BYTES`] at a time. Returns its size in bytes.
+    pub(crate) async fn upload(
+        self,
+        storage: &dyn StorageClient,
+        key: &str,
+    ) -> anyhow::Result<i64> {
+        let mut file = tokio::fs::File::from_std(self.finish()?);
+        let upload_id = storage.create_multipart(key, "application/zip").await?;
+        let uploaded = async {
+            let mut parts = Vec::new();
+            let mut size = 0;
+            loop {
+                let mut part = Vec::with_capacity(ARCHIVE_PART_BYTES);
+                (&mut file)
+                    .take(ARCHIVE_PART_BYTES as u64)
+                    .read_to_end(&mut part)
+                    .await?;
+                // An empty ZIP still has its end record, so the first part
+                // is never empty.
+                if part.is_empty() {
+                    break;
+                }
+                size += part.len() as i64;
+                let number = parts.len() as i32 + 1;
+                let etag = storage.upload_part(key, &upload_id, number, part).await?;
+                parts.push(CompletedPart { number, etag });
+            }
+            storage.complete_multipart(key, &upload_id, &parts).await?;
+            Ok::<_, anyhow::Error>(size)
+        }
+        .await;
+        if uploaded.is_err() {
+            if let Err(e) = storage.abort_multipart(key, &upload_id).await {
+                warn!(error = %e, key = %key, "failed to abort archive upload");
+            }
+        }
+        uploaded
+    }
+}
+
+/// Job body: writes the user's profile, meals, items, weights, custom foods
+/// and photos into a ZIP and uploads it. Exports deleted before the job ran
+/// are skipped, and those of deleted users fail.
+pub async fn build_data_export(state: &AppState, export_id: Uuid) -> anyhow::Result<()> {
+    let Some(export) = repo::find_data_export(&state.db, export_id).await? else {
+        info!(export_id = %export_id, "data export deleted before it ran");
+        return Ok(());
+    };
+    let user_id = export.user_id;
+    repo::set_export_status(&state.db, export_id, DataExportStatus::Running).await?;
+    let Some(user) = repo::find_user(&state.db, user_id).await? else {
+        info!(export_id = %export_id, user_id = %user_id, "user deleted before export ran");
+        repo::set_export_status(&state.db, export_id, DataExportStatus::Failed).await?;
+        return Ok(());
+    };
+
+    let mut archive = Archive::new()?;
+    let goals = goals_repo::find_goals(&state.db, user_id).await?;
+    archive.add_json(
+        "profile.json",
+        &ArchivedProfile {
+            id: user.id,
+            email: user.email,
+            created_at: user.created_at,
+            goals: GoalsResponse::from(&goals),
+            preferences: PreferencesResponse {
+                preferences: preferences_services::preferences(state, user_id).await?,
+                notifications: push_repo::find_preferences(&state.db, user_id)
+                    .await?
+                    .unwrap_or_default(),
+            },
+            exported_at: OffsetDateTime::now_utc(),
+        },
+    )?;
+
+    let mut meals = Vec::new();
+    let mut after = None;
+    loop {
+        let page =
+            repo::meals_page(&state.db, user_id, None, None, after, EXPORT_PAGE_SIZE).await?;
+        let last = (page.len() as i64) < EXPORT_PAGE_SIZE;
+        after = page.last().map(|meal| (meal.created_at, meal.id));
+        meals.extend(page);
+        if last {
+            break;
+        }
+    }
+    archive.add_json("meals.json", &meals)?;
+    archive.add_json(
+        "archived_meals.json",
+        &retention_repo::list_for_user(&state.db, user_id).await?,
+    )?;
+    archive.add_json(
+        "meal_items.json",
+        &repo::items_for_user(&state.db, user_id).await?,
+    )?;
+    archive.add_json(
+        "weights.json",
+        &weights_repo::list_weights(&state.db, user_id, OffsetDateTime::UNIX_EPOCH, None).await?,
+    )?;
+    archive.add_json(
+        "custom_foods.json",
+        &foods_repo::list_custom_foods(&state.db, user_id).await?,
+    )?;
+
+    let photos = repo::photos_for_user(&state.db, user_id).await?;
+    let mut entries = Vec::with_capacity(photos.len());
+    for photo in photos {
+        let object = state.storage.get_object_stream(&photo.s3_key).await?;
+        let file = match object {
+            Some(object) => {
+                let path =
+                    ArchivedPhoto::path(photo.meal_id, photo.id, photo.content_type.as_deref());
+                archive.add_object(&path, object).await?;
+                Some(path)
+            }
+            None => {
+                warn!(photo_id = %photo.id, "photo object missing; left out of export");
+                None
+            }
+        };
+        entries.push(ArchivedPhoto {
+            id: photo.id,
+            meal_id: photo.meal_id,
+            file,
+            content_type: photo.content_type,
+            width: photo.width,
+            height: photo.height,
+            taken_at: photo.taken_at,
+            created_at: photo.created_at,
+        });
+    }
+    archive.add_json("photos.json", &entries)?;
+
+    let key = archive_key(user_id, export_id);
+    let size = archive.upload(state.storage.as_ref(), &key).await?;
+    repo::complete_export(&state.db, export_id, &key, size).await?;
+    info!(user_id = %user_id, export_id = %export_id, size, "data export ready");
+    Ok(())
+}
+
+/// Job failure hook: back to pending while retries remain, failed otherwise.
+pub async fn record_export_failure(
+    state: &AppState,
+    export_id: Uuid,
+    retrying: bool,
+) -> anyhow::Result<()> {
+    let status = if retrying {
+        DataExportStatus::Pending
+    } else {
+        DataExportStatus::Failed
+    };
+    repo::set_export_status(&state.db, export_id, status).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::meals::dto::MealStatus;
+
+    fn meal(title: &str) -> ExportedMeal {
+        ExportedMeal {
+            id: Uuid::nil(),
+            created_at: OffsetDateTime::UNIX_EPOCH,
+            title: Some(title.into()),
+            notes: None,
+            meal_type: None,
+            tags: vec![],
+            status: MealStatus::Pending,
+            total_calories_kcal: None,
+            protein_g: None,
+            fat_g: None,
+            carbs_g: None,
+            sodium_mg: None,
+            sugar_g: None,
+            fiber_g: None,
+            global_score: None,
+            nutrition_source: None,
+        }
+    }
+
+    #[test]
+    fn json_pages_join_into_one_array() {
+        let body = [
+            render(ExportFormat::Json, &[meal("a"), meal("b")], true, false),
+            render(ExportFormat::Json, &[meal("c")], false, false),
+            render(ExportFormat::Json, &[], false, true),
+        ]
+        .concat();
+        let parsed: Vec<serde_json::Value> = serde_json::from_str(&body).unwrap();
+        let titles: Vec<&str> = parsed
+            .iter()
+            .map(|m| m["title"].as_str().unwrap())
+            .collect();
+        assert_eq!(titles, ["a", "b", "c"]);
+    }
+
+    #[tokio::test]
+    async fn archive_round_trips_through_storage() {
+        let storage = crate::storage::FakeStorage::default();
+        storage
+            .put_object("photo", b"\xff\xd8jpeg".to_vec(), "image/jpeg")
+            .await
+            .unwrap();
+        let mut archive = Archive::new().unwrap();
+        archive.add_json("meals.json", &[meal("a")]).unwrap();
+        let photo = storage.get_object_stream("photo").await.unwrap().unwrap();
+        archive.add_object("photos/x.jpg", photo).await.unwrap();
+        let size = archive.upload(&storage, "export.zip").await.unwrap();
+
+        let object = storage
+            .get_object_stream("export.zip")
+            .await
+            .unwrap()
+            .unwrap();
+        let chunks: Vec<_> = object.body.try_collect().await.unwrap();
+        let bytes = chunks.concat();
+        assert_eq!(bytes.len() as i64, size);
+        let mut zip = zip::ZipArchive::new(std::io::Cursor::new(bytes)).unwrap();
+        let names: Vec<&str> = zip.file_names().collect();
+        assert_eq!(names.len(), 2);
+        let meals: Vec<serde_json::Value> =
+            serde_json::from_reader(zip.by_name("meals.json").unwrap()).unwrap();
+        assert_eq!(meals[0]["title"], "a");
+        assert_eq!(zip.by_name("photos/x.jpg").unwrap().size(), 6);
+    }
+
+    #[test]
+    fn empty_exports_are_still_valid() {
+        let json = render(ExportFormat::Json, &[], true, true);
+        assert!(serde_json::from_str::<Vec<serde_json::Value>>(&json)
+            .unwrap()
+            .is_empty());
+        assert_eq!(render(ExportFormat::Csv, &[], true, true), CSV_HEADER);
+    }
+}
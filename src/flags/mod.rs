@@ -0,0 +1,190 @@
+//! Feature flags from the `feature_flags` table, held in memory and reloaded
+//! in the background, so checking a flag never touches the database.
+
+pub mod repo;
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use sha2::{Digest, Sha256};
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::{
+    db::AppState,
+    tasks::{BackgroundTasks, TaskContext},
+};
+
+/// Flags the server itself checks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flag {
+    /// Analyze meal photos with the configured analyzer.
+    AiAnalysis,
+    /// Convert HEIC uploads to JPEG when a converter is configured.
+    HeicTranscode,
+}
+
+impl Flag {
+    pub const ALL: [Flag; 2] = [Flag::AiAnalysis, Flag::HeicTranscode];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Flag::AiAnalysis => "ai_analysis",
+            Flag::HeicTranscode => "heic_transcode",
+        }
+    }
+
+    /// Value without a row in `feature_flags`.
+    fn default_enabled(self) -> bool {
+        match self {
+            Flag::AiAnalysis | Flag::HeicTranscode => true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, sqlx::FromRow)]
+pub struct FlagRule {
+    pub name: String,
+    pub enabled: bool,
+    pub rollout_percent: i16,
+    pub user_ids: Vec<Uuid>,
+}
+
+impl FlagRule {
+    fn applies_to(&self, user_id: Uuid) -> bool {
+        self.enabled
+            && (self.user_ids.contains(&user_id)
+                || bucket(&self.name, user_id) < self.rollout_percent.clamp(0, 100) as u8)
+    }
+}
+
+/// Stable 0-99 bucket of a user for one flag. Hashing the flag name too
+/// means the first 10% of one flag aren't the first 10% of every flag.
+fn bucket(flag: &str, user_id: Uuid) -> u8 {
+    let digest = Sha256::new()
+        .chain_update(flag.as_bytes())
+        .chain_update(user_id.as_bytes())
+        .finalize();
+    let n = u32::from_be_bytes([digest[0], digest[1], digest[2], digest[3]]);
+    (n % 100) as u8
+}
+
+/// Shared, cheaply cloned view of the flag rules. Until the first load, and
+/// for flags without a rule, every flag has its default.
+#[derive(Clone, Default)]
+pub struct Flags {
+    rules: Arc<RwLock<HashMap<String, FlagRule>>>,
+}
+
+impl Flags {
+    pub fn is_enabled(&self, flag: Flag, user_id: Uuid) -> bool {
+        match self.rules.read().unwrap().get(flag.name()) {
+            Some(rule) => rule.applies_to(user_id),
+            None => flag.default_enabled(),
+        }
+    }
+
+    /// Every known flag and every flag in the table, as seen by the user.
+    pub fn for_user(&self, user_id: Uuid) -> BTreeMap<String, bool> {
+        let rules = self.rules.read().unwrap();
+        let mut flags: BTreeMap<String, bool> = Flag::ALL
+            .iter()
+            .map(|flag| (flag.name().to_string(), flag.default_enabled()))
+            .collect();
+        for (name, rule) in rules.iter() {
+            flags.insert(name.clone(), rule.applies_to(user_id));
+        }
+        flags
+    }
+
+    pub fn replace(&self, rules: Vec<FlagRule>) {
+        let rules = rules
+            .into_iter()
+            .map(|rule| (rule.name.clone(), rule))
+            .collect();
+        *self.rules.write().unwrap() = rules;
+    }
+
+    /// Reloads the rules from the database.
+    pub async fn refresh(&self, db: &sqlx::PgPool) -> anyhow::Result<()> {
+        self.replace(repo::list(db).await?);
+        Ok(())
+    }
+}
+
+/// Reloads the flags right away, then every `FLAGS_REFRESH_SECS`.
+pub fn register(tasks: &mut BackgroundTasks, state: &AppState) {
+    let state = state.clone();
+    tasks.spawn("flags-refresh", move |ctx| run_refresh(state, ctx));
+}
+
+async fn run_refresh(state: AppState, mut ctx: TaskContext) {
+    let interval = Duration::from_secs(state.config.tasks.flags_refresh_secs.max(1));
+    let mut loaded = false;
+    loop {
+        match state.flags.refresh(&state.db).await {
+            Ok(()) => {
+                if !loaded {
+                    info!("feature flags loaded");
+                    loaded = true;
+                }
+                ctx.succeeded();
+            }
+            Err(e) => {
+                error!(error = %e, "refreshing feature flags failed");
+                ctx.failed(&e);
+            }
+        }
+        if !ctx.sleep(interval).await {
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(name: &str, enabled: bool, rollout_percent: i16, user_ids: Vec<Uuid>) -> FlagRule {
+        FlagRule {
+            name: name.into(),
+            enabled,
+            rollout_percent,
+            user_ids,
+        }
+    }
+
+    #[test]
+    fn flags_without_rules_use_defaults() {
+        let flags = Flags::default();
+        let user = Uuid::new_v4();
+        assert!(flags.is_enabled(Flag::AiAnalysis, user));
+        assert_eq!(flags.for_user(user).len(), Flag::ALL.len());
+    }
+
+    #[test]
+    fn rules_apply_switch_allow_list_and_rollout() {
+        let flags = Flags::default();
+        let listed = Uuid::new_v4();
+        flags.replace(vec![
+            rule("ai_analysis", true, 0, vec![listed]),
+            rule("heic_transcode", false, 100, vec![listed]),
+            rule("new_dashboard", true, 100, vec![]),
+        ]);
+        assert!(flags.is_enabled(Flag::AiAnalysis, listed));
+        assert!(!flags.is_enabled(Flag::AiAnalysis, Uuid::new_v4()));
+        assert!(!flags.is_enabled(Flag::HeicTranscode, listed));
+        assert_eq!(flags.for_user(listed).get("new_dashboard"), Some(&true));
+    }
+
+    #[test]
+    fn rollout_reaches_roughly_its_share() {
+        let rule = rule("ai_analysis", true, 30, vec![]);
+        let users: Vec<Uuid> = (0..2000).map(|_| Uuid::new_v4()).collect();
+        let reached = users.iter().filter(|u| rule.applies_to(**u)).count();
+        assert!((450..750).contains(&reached), "{reached}");
+    }
+}
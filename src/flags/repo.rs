@@ -0,0 +1,15 @@
+use sqlx::PgPool;
+
+use crate::flags::FlagRule;
+
+pub async fn list(db: &PgPool) -> anyhow::Result<Vec<FlagRule>> {
+    let rules = sqlx::query_as::<_, FlagRule>(
+        r#"
+        SELECT name, enabled, rollout_percent, user_ids
+        FROM feature_flags
+        "#,
+    )
+    .fetch_all(db)
+    .await?;
+    Ok(rules)
+}
@@ -0,0 +1,446 @@
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::units::{grams_to_oz, ItemUnit, Units};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+pub enum FoodSource {
+    OpenFoodFacts,
+    /// USDA FoodData Central.
+    Usda,
+    /// The user's own foods; never cached.
+    Custom,
+}
+
+impl FoodSource {
+    /// Key in `foods_cache.source`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            FoodSource::OpenFoodFacts => "open_food_facts",
+            FoodSource::Usda => "usda",
+            FoodSource::Custom => "custom",
+        }
+    }
+}
+
+/// `foods_cache.source` of cached USDA search result lists, keyed by query.
+pub const USDA_SEARCH_CACHE: &str = "usda_search";
+pub const MIN_QUERY_LEN: usize = 2;
+pub const MAX_QUERY_LEN: usize = 100;
+
+#[derive(Debug, Deserialize)]
+pub struct FoodSearchQuery {
+    pub q: String,
+    pub units: Option<Units>,
+}
+
+impl FoodSearchQuery {
+    /// Lowercased with collapsed whitespace, so equivalent queries share a
+    /// cache entry.
+    pub fn normalized(&self) -> Result<String, String> {
+        let q = self
+            .q
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .to_lowercase();
+        let len = q.chars().count();
+        if !(MIN_QUERY_LEN..=MAX_QUERY_LEN).contains(&len) {
+            return Err(format!(
+                "q must be between {} and {} characters",
+                MIN_QUERY_LEN, MAX_QUERY_LEN
+            ));
+        }
+        Ok(q)
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct FoodSearchResponse {
+    pub query: String,
+    pub foods: Vec<FoodResponse<Food>>,
+    /// USDA could not be searched, so only custom foods are listed.
+    pub partial: bool,
+}
+
+/// Nutrition of a fixed amount of food, named like `MealNutrition`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, sqlx::FromRow)]
+pub struct FoodNutrition {
+    pub total_calories_kcal: Option<f64>,
+    pub protein_g: Option<f64>,
+    pub fat_g: Option<f64>,
+    pub carbs_g: Option<f64>,
+    pub sodium_mg: Option<f64>,
+    pub sugar_g: Option<f64>,
+    pub fiber_g: Option<f64>,
+}
+
+impl FoodNutrition {
+    pub fn is_empty(&self) -> bool {
+        *self == FoodNutrition::default()
+    }
+
+    /// Field-wise sum; a field is only `None` when missing on both sides.
+    pub fn plus(&self, other: &Self) -> Self {
+        let add = |a: Option<f64>, b: Option<f64>| match (a, b) {
+            (None, None) => None,
+            (a, b) => Some(a.unwrap_or(0.0) + b.unwrap_or(0.0)),
+        };
+        Self {
+            total_calories_kcal: add(self.total_calories_kcal, other.total_calories_kcal),
+            protein_g: add(self.protein_g, other.protein_g),
+            fat_g: add(self.fat_g, other.fat_g),
+            carbs_g: add(self.carbs_g, other.carbs_g),
+            sodium_mg: add(self.sodium_mg, other.sodium_mg),
+            sugar_g: add(self.sugar_g, other.sugar_g),
+            fiber_g: add(self.fiber_g, other.fiber_g),
+        }
+    }
+
+    /// Every value multiplied by `factor`, rounded to two decimals.
+    pub fn scaled(&self, factor: f64) -> Self {
+        let scale = |v: Option<f64>| v.map(|v| (v * factor * 100.0).round() / 100.0);
+        Self {
+            total_calories_kcal: scale(self.total_calories_kcal),
+            protein_g: scale(self.protein_g),
+            fat_g: scale(self.fat_g),
+            carbs_g: scale(self.carbs_g),
+            sodium_mg: scale(self.sodium_mg),
+            sugar_g: scale(self.sugar_g),
+            fiber_g: scale(self.fiber_g),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Food {
+    pub source: FoodSource,
+    /// Id within the source: the barcode for Open Food Facts, the FDC id for
+    /// USDA.
+    pub id: String,
+    pub name: String,
+    pub brand: Option<String>,
+    pub barcode: Option<String>,
+    pub serving_size_g: Option<f64>,
+    /// Serving as printed on the package, e.g. "1 bar (40 g)".
+    pub serving_description: Option<String>,
+    pub per_100g: FoodNutrition,
+    /// `null` when the serving size is unknown.
+    pub per_serving: Option<FoodNutrition>,
+}
+
+/// Foods with a serving size in grams.
+pub trait ServingSize {
+    fn serving_size_g(&self) -> Option<f64>;
+}
+
+impl ServingSize for Food {
+    fn serving_size_g(&self) -> Option<f64> {
+        self.serving_size_g
+    }
+}
+
+/// A food as returned to clients; in imperial units the serving size is
+/// given in ounces too.
+#[derive(Debug, Serialize)]
+pub struct FoodResponse<F> {
+    #[serde(flatten)]
+    pub food: F,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub serving_size_oz: Option<f64>,
+}
+
+impl<F: ServingSize> FoodResponse<F> {
+    pub fn new(food: F, units: Units) -> Self {
+        let serving_size_oz = match units {
+            Units::Imperial => food.serving_size_g().map(grams_to_oz),
+            Units::Metric => None,
+        };
+        Self {
+            food,
+            serving_size_oz,
+        }
+    }
+}
+
+pub const MAX_FOOD_NAME_LEN: usize = 200;
+pub const MAX_SERVING_SIZE_G: f64 = 5_000.0;
+/// Sanity cap for hand-entered nutrition values of one serving.
+pub const MAX_CUSTOM_VALUE: f64 = 100_000.0;
+
+/// Body of create and replace for a custom food.
+#[derive(Debug, Deserialize)]
+pub struct CustomFoodRequest {
+    pub name: String,
+    pub brand: Option<String>,
+    /// Needed to log the food in grams.
+    pub serving_size_g: Option<f64>,
+    /// Alternative to `serving_size_g`; stored in grams.
+    pub serving_size_oz: Option<f64>,
+    pub serving_description: Option<String>,
+    /// Per serving.
+    pub nutrition: FoodNutrition,
+}
+
+impl CustomFoodRequest {
+    /// Trims the texts and checks ranges.
+    pub fn normalized(self) -> Result<Self, String> {
+        let text = |field: &str, value: String| -> Result<Option<String>, String> {
+            let value = value.trim().to_string();
+            if value.chars().count() > MAX_FOOD_NAME_LEN {
+                return Err(format!(
+                    "{} must be at most {} characters",
+                    field, MAX_FOOD_NAME_LEN
+                ));
+            }
+            Ok(Some(value).filter(|v| !v.is_empty()))
+        };
+        let name = text("name", self.name)?.ok_or("name must not be empty")?;
+        let brand = self.brand.map(|b| text("brand", b)).transpose()?.flatten();
+        let serving_description = self
+            .serving_description
+            .map(|d| text("serving_description", d))
+            .transpose()?
+            .flatten();
+        let serving_size_g = match (self.serving_size_g, self.serving_size_oz) {
+            (Some(_), Some(_)) => {
+                return Err("send serving_size_g or serving_size_oz, not both".into())
+            }
+            (grams, None) => grams,
+            (None, Some(oz)) => ItemUnit::Oz.metric_amount(oz),
+        };
+        if serving_size_g.is_some_and(|g| !g.is_finite() || g <= 0.0 || g > MAX_SERVING_SIZE_G) {
+            return Err(format!(
+                "serving size must be greater than 0 and at most {} g",
+                MAX_SERVING_SIZE_G
+            ));
+        }
+        let n = &self.nutrition;
+        let values = [
+            n.total_calories_kcal,
+            n.protein_g,
+            n.fat_g,
+            n.carbs_g,
+            n.sodium_mg,
+            n.sugar_g,
+            n.fiber_g,
+        ];
+        if n.is_empty() {
+            return Err("nutrition needs at least one value".into());
+        }
+        if values
+            .into_iter()
+            .flatten()
+            .any(|v| !v.is_finite() || !(0.0..=MAX_CUSTOM_VALUE).contains(&v))
+        {
+            return Err(format!(
+                "nutrition values must be between 0 and {}",
+                MAX_CUSTOM_VALUE
+            ));
+        }
+        Ok(Self {
+            name,
+            brand,
+            serving_size_g,
+            serving_size_oz: None,
+            serving_description,
+            ..self
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct CustomFood {
+    pub id: Uuid,
+    pub name: String,
+    pub brand: Option<String>,
+    pub serving_size_g: Option<f64>,
+    pub serving_description: Option<String>,
+    /// Per serving.
+    #[sqlx(flatten)]
+    pub nutrition: FoodNutrition,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub updated_at: OffsetDateTime,
+}
+
+impl ServingSize for CustomFood {
+    fn serving_size_g(&self) -> Option<f64> {
+        self.serving_size_g
+    }
+}
+
+impl From<CustomFood> for Food {
+    /// Per-100 g values are derived from the serving size and stay empty
+    /// without one.
+    fn from(food: CustomFood) -> Self {
+        Self {
+            source: FoodSource::Custom,
+            id: food.id.to_string(),
+            name: food.name,
+            brand: food.brand,
+            barcode: None,
+            per_100g: food
+                .serving_size_g
+                .map(|g| food.nutrition.scaled(100.0 / g))
+                .unwrap_or_default(),
+            serving_size_g: food.serving_size_g,
+            serving_description: food.serving_description,
+            per_serving: Some(food.nutrition),
+        }
+    }
+}
+
+/// EAN-8, UPC-A, EAN-13 or GTIN-14 with a valid check digit.
+pub fn is_valid_gtin(code: &str) -> bool {
+    if !matches!(code.len(), 8 | 12 | 13 | 14) || !code.bytes().all(|b| b.is_ascii_digit()) {
+        return false;
+    }
+    let digits: Vec<u32> = code.bytes().map(|b| u32::from(b - b'0')).collect();
+    let (check, body) = digits.split_last().unwrap();
+    // Weights alternate 3, 1, 3, ... starting from the digit next to the check digit.
+    let sum: u32 = body
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, d)| if i % 2 == 0 { d * 3 } else { *d })
+        .sum();
+    (10 - sum % 10) % 10 == *check
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gtin_check_digits() {
+        assert!(is_valid_gtin("3017620422003"));
+        assert!(is_valid_gtin("96385074"));
+        assert!(is_valid_gtin("036000291452"));
+        assert!(!is_valid_gtin("3017620422004"));
+        assert!(!is_valid_gtin("30176204220a3"));
+        assert!(!is_valid_gtin("12345"));
+    }
+
+    #[test]
+    fn search_query_is_normalized_and_bounded() {
+        let query = FoodSearchQuery {
+            q: "  Greek   YOGURT ".into(),
+            units: None,
+        };
+        assert_eq!(query.normalized().unwrap(), "greek yogurt");
+        let short = FoodSearchQuery {
+            q: " a ".into(),
+            units: None,
+        };
+        assert!(short.normalized().is_err());
+    }
+
+    fn custom_request() -> CustomFoodRequest {
+        CustomFoodRequest {
+            name: "  Overnight oats ".into(),
+            brand: Some(" ".into()),
+            serving_size_g: Some(250.0),
+            serving_size_oz: None,
+            serving_description: None,
+            nutrition: FoodNutrition {
+                total_calories_kcal: Some(350.0),
+                ..FoodNutrition::default()
+            },
+        }
+    }
+
+    #[test]
+    fn custom_food_request_is_trimmed() {
+        let food = custom_request().normalized().unwrap();
+        assert_eq!(food.name, "Overnight oats");
+        assert_eq!(food.brand, None);
+    }
+
+    #[test]
+    fn custom_food_request_needs_nutrition_and_a_sane_serving() {
+        let mut food = custom_request();
+        food.nutrition = FoodNutrition::default();
+        assert!(food.normalized().is_err());
+        let mut food = custom_request();
+        food.serving_size_g = Some(0.0);
+        assert!(food.normalized().is_err());
+        let mut food = custom_request();
+        food.name = "   ".into();
+        assert!(food.normalized().is_err());
+    }
+
+    #[test]
+    fn custom_food_serving_size_can_be_given_in_ounces() {
+        let mut food = custom_request();
+        food.serving_size_g = None;
+        food.serving_size_oz = Some(2.0);
+        let food = food.normalized().unwrap();
+        assert_eq!(food.serving_size_g, Some(56.69904625));
+        assert_eq!(food.serving_size_oz, None);
+        let mut food = custom_request();
+        food.serving_size_oz = Some(2.0);
+        assert!(food.normalized().is_err());
+    }
+
+    #[test]
+    fn custom_food_per_100g_comes_from_serving_size() {
+        let custom = CustomFood {
+            id: Uuid::nil(),
+            name: "Oats".into(),
+            brand: None,
+            serving_size_g: Some(250.0),
+            serving_description: None,
+            nutrition: FoodNutrition {
+                total_calories_kcal: Some(350.0),
+                ..FoodNutrition::default()
+            },
+            created_at: OffsetDateTime::UNIX_EPOCH,
+            updated_at: OffsetDateTime::UNIX_EPOCH,
+        };
+        let food = Food::from(custom.clone());
+        assert_eq!(food.per_100g.total_calories_kcal, Some(140.0));
+        let food = Food::from(CustomFood {
+            serving_size_g: None,
+            ..custom
+        });
+        assert!(food.per_100g.is_empty());
+        assert!(food.per_serving.is_some());
+    }
+
+    #[test]
+    fn plus_keeps_fields_missing_on_both_sides_missing() {
+        let a = FoodNutrition {
+            total_calories_kcal: Some(300.0),
+            protein_g: Some(10.0),
+            ..FoodNutrition::default()
+        };
+        let b = FoodNutrition {
+            total_calories_kcal: Some(120.0),
+            fat_g: Some(4.0),
+            ..FoodNutrition::default()
+        };
+        let sum = a.plus(&b);
+        assert_eq!(sum.total_calories_kcal, Some(420.0));
+        assert_eq!(sum.protein_g, Some(10.0));
+        assert_eq!(sum.fat_g, Some(4.0));
+        assert_eq!(sum.sugar_g, None);
+    }
+
+    #[test]
+    fn scaled_keeps_missing_values_missing() {
+        let per_100g = FoodNutrition {
+            total_calories_kcal: Some(539.0),
+            sodium_mg: Some(43.0),
+            ..FoodNutrition::default()
+        };
+        let serving = per_100g.scaled(0.15);
+        assert_eq!(serving.total_calories_kcal, Some(80.85));
+        assert_eq!(serving.sodium_mg, Some(6.45));
+        assert_eq!(serving.protein_g, None);
+    }
+}
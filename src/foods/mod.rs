@@ -0,0 +1,38 @@
+//! Packaged and generic foods from external databases, normalized into one
+//! shape and cached in `foods_cache`.
+
+pub mod dto;
+pub mod off;
+pub mod repo;
+pub mod services;
+pub mod usda;
+
+use std::time::Duration;
+
+use crate::config::FoodsConfig;
+
+pub const USER_AGENT: &str = concat!("MealMind/", env!("CARGO_PKG_VERSION"));
+
+/// Clients for the configured food databases.
+#[derive(Clone)]
+pub struct FoodSources {
+    pub off: off::OpenFoodFacts,
+    /// `None` without a USDA API key; search is disabled then.
+    pub usda: Option<usda::Usda>,
+}
+
+impl FoodSources {
+    pub fn from_config(config: &FoodsConfig) -> anyhow::Result<Self> {
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(config.timeout_secs))
+            .user_agent(USER_AGENT)
+            .build()?;
+        Ok(Self {
+            off: off::OpenFoodFacts::new(http.clone(), config.off_base_url.clone()),
+            usda: config
+                .usda_api_key
+                .clone()
+                .map(|key| usda::Usda::new(http, key, config.usda_base_url.clone())),
+        })
+    }
+}
@@ -0,0 +1,159 @@
+use serde_json::Value;
+
+use crate::foods::dto::{Food, FoodNutrition, FoodSource};
+
+const FIELDS: &str =
+    "code,product_name,generic_name,brands,nutriments,serving_quantity,serving_size";
+/// Salt is 40% sodium by weight.
+const SODIUM_PER_SALT: f64 = 0.4;
+const KJ_PER_KCAL: f64 = 4.184;
+
+/// Open Food Facts product API (v2).
+#[derive(Clone)]
+pub struct OpenFoodFacts {
+    http: reqwest::Client,
+    base_url: String,
+}
+
+impl OpenFoodFacts {
+    pub fn new(http: reqwest::Client, base_url: String) -> Self {
+        Self { http, base_url }
+    }
+
+    /// `None` when Open Food Facts has no usable product for the barcode.
+    pub async fn product(&self, barcode: &str) -> anyhow::Result<Option<Food>> {
+        let response = self
+            .http
+            .get(format!(
+                "{}/api/v2/product/{}.json",
+                self.base_url.trim_end_matches('/'),
+                barcode
+            ))
+            .query(&[("fields", FIELDS)])
+            .send()
+            .await?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let body: Value = response.error_for_status()?.json().await?;
+        if body["status"].as_i64() != Some(1) {
+            return Ok(None);
+        }
+        Ok(normalize(barcode, &body["product"]))
+    }
+}
+
+/// Numbers in `nutriments` are sometimes sent as strings.
+fn number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+    .filter(|v: &f64| v.is_finite() && *v >= 0.0)
+}
+
+fn text(value: &Value) -> Option<String> {
+    value
+        .as_str()
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+}
+
+/// Reads one basis (`100g` or `serving`) from `nutriments`, converting
+/// kJ-only energy to kcal and grams of sodium (or salt) to milligrams.
+fn nutrition(nutriments: &Value, basis: &str) -> FoodNutrition {
+    let get = |name: &str| number(&nutriments[format!("{}_{}", name, basis)]);
+    FoodNutrition {
+        total_calories_kcal: get("energy-kcal")
+            .or_else(|| get("energy").map(|kj| kj / KJ_PER_KCAL)),
+        protein_g: get("proteins"),
+        fat_g: get("fat"),
+        carbs_g: get("carbohydrates"),
+        sodium_mg: get("sodium")
+            .or_else(|| get("salt").map(|salt| salt * SODIUM_PER_SALT))
+            .map(|g| g * 1000.0),
+        sugar_g: get("sugars"),
+        fiber_g: get("fiber"),
+    }
+    .scaled(1.0)
+}
+
+/// Maps an Open Food Facts product to [`Food`]; `None` without a name or any
+/// nutrition per 100 g.
+pub fn normalize(barcode: &str, product: &Value) -> Option<Food> {
+    let name = text(&product["product_name"]).or_else(|| text(&product["generic_name"]))?;
+    let nutriments = &product["nutriments"];
+    let per_100g = nutrition(nutriments, "100g");
+    if per_100g.is_empty() {
+        return None;
+    }
+    let serving_size_g = number(&product["serving_quantity"]).filter(|g| *g > 0.0);
+    let per_serving = serving_size_g
+        .map(|g| per_100g.scaled(g / 100.0))
+        .or_else(|| {
+            let printed = nutrition(nutriments, "serving");
+            (!printed.is_empty()).then_some(printed)
+        });
+    Some(Food {
+        source: FoodSource::OpenFoodFacts,
+        id: barcode.to_string(),
+        name,
+        brand: text(&product["brands"])
+            .map(|b| b.split(',').next().unwrap_or_default().trim().to_string()),
+        barcode: Some(barcode.to_string()),
+        serving_size_g,
+        serving_description: text(&product["serving_size"]),
+        per_100g,
+        per_serving,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn normalize_converts_units_and_scales_servings() {
+        let product = json!({
+            "product_name": "Nutella",
+            "brands": "Ferrero, Nutella",
+            "serving_quantity": "15",
+            "serving_size": "15 g",
+            "nutriments": {
+                "energy-kcal_100g": 539,
+                "proteins_100g": 6.3,
+                "fat_100g": "30.9",
+                "carbohydrates_100g": 57.5,
+                "sugars_100g": 56.3,
+                "salt_100g": 0.107,
+            },
+        });
+        let food = normalize("3017620422003", &product).unwrap();
+        assert_eq!(food.brand.as_deref(), Some("Ferrero"));
+        assert_eq!(food.per_100g.fat_g, Some(30.9));
+        assert_eq!(food.per_100g.sodium_mg, Some(42.8));
+        assert_eq!(food.per_100g.fiber_g, None);
+        let serving = food.per_serving.unwrap();
+        assert_eq!(serving.total_calories_kcal, Some(80.85));
+    }
+
+    #[test]
+    fn normalize_falls_back_to_kilojoules() {
+        let product = json!({
+            "product_name": "Water crackers",
+            "nutriments": {"energy_100g": 1674},
+        });
+        let food = normalize("96385074", &product).unwrap();
+        assert_eq!(food.per_100g.total_calories_kcal, Some(400.1));
+        assert!(food.per_serving.is_none());
+    }
+
+    #[test]
+    fn normalize_skips_products_without_nutrition() {
+        let product = json!({"product_name": "Mystery", "nutriments": {}});
+        assert!(normalize("96385074", &product).is_none());
+    }
+}
@@ -0,0 +1,207 @@
+use sqlx::{FromRow, PgExecutor, PgPool};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::foods::dto::{CustomFood, CustomFoodRequest};
+
+/// Upper bound on custom foods returned by a search.
+pub const CUSTOM_SEARCH_LIMIT: i64 = 25;
+
+const CUSTOM_FOOD_COLUMNS: &str = r#"
+    id, name, brand, serving_size_g::float8 AS serving_size_g, serving_description,
+    total_calories_kcal::float8 AS total_calories_kcal,
+    protein_g::float8 AS protein_g,
+    fat_g::float8 AS fat_g,
+    carbs_g::float8 AS carbs_g,
+    sodium_mg::float8 AS sodium_mg,
+    sugar_g::float8 AS sugar_g,
+    fiber_g::float8 AS fiber_g,
+    created_at, updated_at
+"#;
+
+#[derive(Debug, Clone, FromRow)]
+pub struct CachedFood {
+    /// `None` for a cached miss.
+    pub data: Option<serde_json::Value>,
+    pub fetched_at: OffsetDateTime,
+}
+
+pub async fn find_cached(
+    db: &PgPool,
+    source: &str,
+    external_id: &str,
+) -> anyhow::Result<Option<CachedFood>> {
+    let cached = sqlx::query_as::<_, CachedFood>(
+        r#"
+        SELECT data, fetched_at
+        FROM foods_cache
+        WHERE source = $1 AND external_id = $2
+        "#,
+    )
+    .bind(source)
+    .bind(external_id)
+    .fetch_optional(db)
+    .await?;
+    Ok(cached)
+}
+
+pub async fn store_cached(
+    db: &PgPool,
+    source: &str,
+    external_id: &str,
+    data: Option<&serde_json::Value>,
+) -> anyhow::Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO foods_cache (source, external_id, data)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (source, external_id) DO UPDATE
+        SET data = EXCLUDED.data,
+            fetched_at = NOW()
+        "#,
+    )
+    .bind(source)
+    .bind(external_id)
+    .bind(data)
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+pub async fn insert_custom_food(
+    db: impl PgExecutor<'_>,
+    user_id: Uuid,
+    food: &CustomFoodRequest,
+) -> anyhow::Result<CustomFood> {
+    let food = sqlx::query_as::<_, CustomFood>(&format!(
+        r#"
+        INSERT INTO custom_foods (user_id, name, brand, serving_size_g, serving_description,
+                                  total_calories_kcal, protein_g, fat_g, carbs_g,
+                                  sodium_mg, sugar_g, fiber_g)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+        RETURNING {CUSTOM_FOOD_COLUMNS}
+        "#
+    ))
+    .bind(user_id)
+    .bind(&food.name)
+    .bind(&food.brand)
+    .bind(food.serving_size_g)
+    .bind(&food.serving_description)
+    .bind(food.nutrition.total_calories_kcal)
+    .bind(food.nutrition.protein_g)
+    .bind(food.nutrition.fat_g)
+    .bind(food.nutrition.carbs_g)
+    .bind(food.nutrition.sodium_mg)
+    .bind(food.nutrition.sugar_g)
+    .bind(food.nutrition.fiber_g)
+    .fetch_one(db)
+    .await?;
+    Ok(food)
+}
+
+pub async fn list_custom_foods(
+    db: impl PgExecutor<'_>,
+    user_id: Uuid,
+) -> anyhow::Result<Vec<CustomFood>> {
+    let foods = sqlx::query_as::<_, CustomFood>(&format!(
+        "SELECT {CUSTOM_FOOD_COLUMNS} FROM custom_foods WHERE user_id = $1 ORDER BY lower(name), id"
+    ))
+    .bind(user_id)
+    .fetch_all(db)
+    .await?;
+    Ok(foods)
+}
+
+/// Custom foods whose name or brand contains `query`, case-insensitively.
+pub async fn search_custom_foods(
+    db: impl PgExecutor<'_>,
+    user_id: Uuid,
+    query: &str,
+) -> anyhow::Result<Vec<CustomFood>> {
+    let pattern = format!(
+        "%{}%",
+        query
+            .replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_")
+    );
+    let foods = sqlx::query_as::<_, CustomFood>(&format!(
+        r#"
+        SELECT {CUSTOM_FOOD_COLUMNS}
+        FROM custom_foods
+        WHERE user_id = $1 AND (name ILIKE $2 OR brand ILIKE $2)
+        ORDER BY lower(name), id
+        LIMIT $3
+        "#
+    ))
+    .bind(user_id)
+    .bind(pattern)
+    .bind(CUSTOM_SEARCH_LIMIT)
+    .fetch_all(db)
+    .await?;
+    Ok(foods)
+}
+
+pub async fn find_custom_food(
+    db: impl PgExecutor<'_>,
+    user_id: Uuid,
+    food_id: Uuid,
+) -> anyhow::Result<Option<CustomFood>> {
+    let food = sqlx::query_as::<_, CustomFood>(&format!(
+        "SELECT {CUSTOM_FOOD_COLUMNS} FROM custom_foods WHERE id = $1 AND user_id = $2"
+    ))
+    .bind(food_id)
+    .bind(user_id)
+    .fetch_optional(db)
+    .await?;
+    Ok(food)
+}
+
+/// Replaces a custom food; `None` if it doesn't belong to `user_id`. Meal
+/// items already logged keep their nutrition.
+pub async fn update_custom_food(
+    db: impl PgExecutor<'_>,
+    user_id: Uuid,
+    food_id: Uuid,
+    food: &CustomFoodRequest,
+) -> anyhow::Result<Option<CustomFood>> {
+    let food = sqlx::query_as::<_, CustomFood>(&format!(
+        r#"
+        UPDATE custom_foods
+        SET name = $3, brand = $4, serving_size_g = $5, serving_description = $6,
+            total_calories_kcal = $7, protein_g = $8, fat_g = $9, carbs_g = $10,
+            sodium_mg = $11, sugar_g = $12, fiber_g = $13, updated_at = NOW()
+        WHERE id = $1 AND user_id = $2
+        RETURNING {CUSTOM_FOOD_COLUMNS}
+        "#
+    ))
+    .bind(food_id)
+    .bind(user_id)
+    .bind(&food.name)
+    .bind(&food.brand)
+    .bind(food.serving_size_g)
+    .bind(&food.serving_description)
+    .bind(food.nutrition.total_calories_kcal)
+    .bind(food.nutrition.protein_g)
+    .bind(food.nutrition.fat_g)
+    .bind(food.nutrition.carbs_g)
+    .bind(food.nutrition.sodium_mg)
+    .bind(food.nutrition.sugar_g)
+    .bind(food.nutrition.fiber_g)
+    .fetch_optional(db)
+    .await?;
+    Ok(food)
+}
+
+pub async fn delete_custom_food(
+    db: impl PgExecutor<'_>,
+    user_id: Uuid,
+    food_id: Uuid,
+) -> anyhow::Result<bool> {
+    let result = sqlx::query(r#"DELETE FROM custom_foods WHERE id = $1 AND user_id = $2"#)
+        .bind(food_id)
+        .bind(user_id)
+        .execute(db)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
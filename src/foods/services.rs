@@ -0,0 +1,230 @@
+use std::future::Future;
+
+use serde::{de::DeserializeOwned, Serialize};
+use time::{Duration, OffsetDateTime};
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::{
+    db::AppState,
+    foods::{
+        dto::{CustomFood, CustomFoodRequest, Food, FoodSource, USDA_SEARCH_CACHE},
+        repo,
+    },
+};
+
+#[derive(Debug, thiserror::Error)]
+#[error("USDA food data is not configured")]
+pub struct UsdaDisabled;
+
+/// A food source's API failed and nothing was cached, as opposed to our own
+/// database failing. Handlers answer `502` for it and `500` otherwise.
+#[derive(Debug, thiserror::Error)]
+#[error("food source unavailable: {0}")]
+pub struct SourceUnavailable(pub anyhow::Error);
+
+/// Serves `source`/`key` from `foods_cache` while fresh, otherwise calls
+/// `fetch` and caches its answer. Hits are kept longer than misses; a stale
+/// hit is served when the upstream call fails, which is otherwise reported
+/// as [`SourceUnavailable`].
+async fn cached<T, F>(
+    state: &AppState,
+    source: &str,
+    key: &str,
+    hit_ttl: Duration,
+    fetch: F,
+) -> anyhow::Result<Option<T>>
+where
+    T: Serialize + DeserializeOwned,
+    F: Future<Output = anyhow::Result<Option<T>>>,
+{
+    let cached = repo::find_cached(&state.db, source, key).await?;
+    if let Some(entry) = &cached {
+        let ttl = if entry.data.is_some() {
+            hit_ttl
+        } else {
+            Duration::hours(state.config.foods.miss_ttl_hours)
+        };
+        if entry.fetched_at + ttl > OffsetDateTime::now_utc() {
+            return decode(entry.data.clone());
+        }
+    }
+
+    match fetch.await {
+        Ok(value) => {
+            let data = value.as_ref().map(serde_json::to_value).transpose()?;
+            repo::store_cached(&state.db, source, key, data.as_ref()).await?;
+            Ok(value)
+        }
+        Err(e) => match cached {
+            Some(stale) if stale.data.is_some() => {
+                warn!(error = %e, source, key, "food source unavailable; serving stale entry");
+                decode(stale.data)
+            }
+            _ => Err(SourceUnavailable(e).into()),
+        },
+    }
+}
+
+fn decode<T: DeserializeOwned>(data: Option<serde_json::Value>) -> anyhow::Result<Option<T>> {
+    Ok(data.map(serde_json::from_value).transpose()?)
+}
+
+/// Looks a barcode up in Open Food Facts, going through `foods_cache`.
+pub async fn lookup_barcode(state: &AppState, barcode: &str) -> anyhow::Result<Option<Food>> {
+    let ttl = Duration::hours(state.config.foods.cache_ttl_hours);
+    cached(
+        state,
+        FoodSource::OpenFoodFacts.as_str(),
+        barcode,
+        ttl,
+        state.foods.off.product(barcode),
+    )
+    .await
+}
+
+/// Details of one USDA food by FDC id, going through `foods_cache`.
+pub async fn usda_food(state: &AppState, fdc_id: &str) -> anyhow::Result<Option<Food>> {
+    let usda = state.foods.usda.as_ref().ok_or(UsdaDisabled)?;
+    let ttl = Duration::hours(state.config.foods.cache_ttl_hours);
+    cached(
+        state,
+        FoodSource::Usda.as_str(),
+        fdc_id,
+        ttl,
+        usda.food(fdc_id),
+    )
+    .await
+}
+
+/// Resolves a food reference from any source; custom foods only resolve
+/// for their owner.
+pub async fn find_food(
+    state: &AppState,
+    user_id: Uuid,
+    source: FoodSource,
+    id: &str,
+) -> anyhow::Result<Option<Food>> {
+    match source {
+        FoodSource::OpenFoodFacts => lookup_barcode(state, id).await,
+        FoodSource::Usda => usda_food(state, id).await,
+        FoodSource::Custom => {
+            let Ok(food_id) = Uuid::parse_str(id) else {
+                return Ok(None);
+            };
+            let food = get_custom_food(state, user_id, food_id).await?;
+            Ok(food.map(Food::from))
+        }
+    }
+}
+
+/// Searches the user's custom foods, then USDA FoodData Central when it is
+/// configured; `query` must already be normalized. USDA result lists are
+/// cached per query to stay within the API rate limit. When USDA fails the
+/// custom foods are still returned, flagged as partial by the `bool`.
+pub async fn search(
+    state: &AppState,
+    user_id: Uuid,
+    query: &str,
+) -> anyhow::Result<(Vec<Food>, bool)> {
+    let mut tx = state.begin_as(user_id).await?;
+    let custom = repo::search_custom_foods(&mut *tx, user_id, query).await?;
+    tx.commit().await?;
+    let mut foods: Vec<Food> = custom.into_iter().map(Food::from).collect();
+    let Some(usda) = state.foods.usda.as_ref() else {
+        return Ok((foods, false));
+    };
+    let ttl = Duration::hours(state.config.foods.search_ttl_hours);
+    let fetch = async { usda.search(query).await.map(Some) };
+    match cached(state, USDA_SEARCH_CACHE, query, ttl, fetch).await {
+        Ok(usda_foods) => {
+            foods.extend(usda_foods.unwrap_or_default());
+            Ok((foods, false))
+        }
+        Err(e) => {
+            let e = without_url(e);
+            warn!(error = %e, user_id = %user_id, "USDA search failed; returning custom foods only");
+            Ok((foods, true))
+        }
+    }
+}
+
+/// `e` without the URL of a failed request, so logs never carry API
+/// credentials that may be part of it.
+fn without_url(e: anyhow::Error) -> anyhow::Error {
+    match e.downcast::<SourceUnavailable>() {
+        Ok(SourceUnavailable(e)) => without_url(e),
+        Err(e) => match e.downcast::<reqwest::Error>() {
+            Ok(e) => e.without_url().into(),
+            Err(e) => e,
+        },
+    }
+}
+
+pub async fn create_custom_food(
+    state: &AppState,
+    user_id: Uuid,
+    food: &CustomFoodRequest,
+) -> anyhow::Result<CustomFood> {
+    let mut tx = state.begin_as(user_id).await?;
+    let food = repo::insert_custom_food(&mut *tx, user_id, food).await?;
+    tx.commit().await?;
+    Ok(food)
+}
+
+pub async fn list_custom_foods(state: &AppState, user_id: Uuid) -> anyhow::Result<Vec<CustomFood>> {
+    let mut tx = state.begin_as(user_id).await?;
+    let foods = repo::list_custom_foods(&mut *tx, user_id).await?;
+    tx.commit().await?;
+    Ok(foods)
+}
+
+pub async fn get_custom_food(
+    state: &AppState,
+    user_id: Uuid,
+    food_id: Uuid,
+) -> anyhow::Result<Option<CustomFood>> {
+    let mut tx = state.begin_as(user_id).await?;
+    let food = repo::find_custom_food(&mut *tx, user_id, food_id).await?;
+    tx.commit().await?;
+    Ok(food)
+}
+
+pub async fn update_custom_food(
+    state: &AppState,
+    user_id: Uuid,
+    food_id: Uuid,
+    food: &CustomFoodRequest,
+) -> anyhow::Result<Option<CustomFood>> {
+    let mut tx = state.begin_as(user_id).await?;
+    let food = repo::update_custom_food(&mut *tx, user_id, food_id, food).await?;
+    tx.commit().await?;
+    Ok(food)
+}
+
+/// `false` if the user has no such food.
+pub async fn delete_custom_food(
+    state: &AppState,
+    user_id: Uuid,
+    food_id: Uuid,
+) -> anyhow::Result<bool> {
+    let mut tx = state.begin_as(user_id).await?;
+    let deleted = repo::delete_custom_food(&mut *tx, user_id, food_id).await?;
+    tx.commit().await?;
+    Ok(deleted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn logged_errors_leave_out_the_url() {
+        let e = reqwest::get("http://127.0.0.1:1/v1/foods/search?api_key=secret")
+            .await
+            .unwrap_err();
+        assert!(e.to_string().contains("secret"));
+        let e = without_url(SourceUnavailable(e.into()).into());
+        assert!(!format!("{e:#}").contains("secret"), "{e:#}");
+    }
+}
@@ -0,0 +1,255 @@
+use serde_json::Value;
+
+use crate::foods::dto::{Food, FoodNutrition, FoodSource};
+
+/// Results per search request.
+const PAGE_SIZE: u32 = 25;
+const KJ_PER_KCAL: f64 = 4.184;
+
+/// FDC nutrient ids, as used in search results, and nutrient numbers, as
+/// used by the abridged food details.
+const ENERGY: (i64, &str) = (1008, "208");
+const ENERGY_ATWATER_GENERAL: (i64, &str) = (2047, "957");
+const ENERGY_ATWATER_SPECIFIC: (i64, &str) = (2048, "958");
+const PROTEIN: (i64, &str) = (1003, "203");
+const FAT: (i64, &str) = (1004, "204");
+const CARBS: (i64, &str) = (1005, "205");
+const SUGARS: (i64, &str) = (2000, "269");
+const FIBER: (i64, &str) = (1079, "291");
+const SODIUM: (i64, &str) = (1093, "307");
+
+/// USDA FoodData Central search API. The key goes in the `X-Api-Key` header
+/// rather than the query string, since `reqwest::Error` displays the URL.
+#[derive(Clone)]
+pub struct Usda {
+    http: reqwest::Client,
+    api_key: String,
+    base_url: String,
+}
+
+impl Usda {
+    pub fn new(http: reqwest::Client, api_key: String, base_url: String) -> Self {
+        Self {
+            http,
+            api_key,
+            base_url,
+        }
+    }
+
+    pub async fn search(&self, query: &str) -> anyhow::Result<Vec<Food>> {
+        let body: Value = self
+            .http
+            .get(format!(
+                "{}/v1/foods/search",
+                self.base_url.trim_end_matches('/')
+            ))
+            .header("X-Api-Key", &self.api_key)
+            .query(&[("query", query), ("pageSize", &PAGE_SIZE.to_string())])
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(body["foods"]
+            .as_array()
+            .map(|foods| foods.iter().filter_map(normalize).collect())
+            .unwrap_or_default())
+    }
+
+    /// One food by FDC id; `None` if USDA doesn't know it or it has no
+    /// usable nutrition.
+    pub async fn food(&self, fdc_id: &str) -> anyhow::Result<Option<Food>> {
+        let response = self
+            .http
+            .get(format!(
+                "{}/v1/food/{}",
+                self.base_url.trim_end_matches('/'),
+                fdc_id
+            ))
+            .header("X-Api-Key", &self.api_key)
+            .query(&[("format", "abridged")])
+            .send()
+            .await?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let body: Value = response.error_for_status()?.json().await?;
+        Ok(normalize(&body))
+    }
+}
+
+fn text(value: &Value) -> Option<String> {
+    value
+        .as_str()
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+}
+
+/// Value of the first of `ids` present in `foodNutrients`, converting kJ and
+/// grams to the units `FoodNutrition` uses for energy and sodium.
+fn nutrient(nutrients: &[Value], ids: &[(i64, &str)]) -> Option<f64> {
+    ids.iter().find_map(|&(id, number)| {
+        let n = nutrients.iter().find(|n| {
+            n["nutrientId"].as_i64() == Some(id) || n["number"].as_str() == Some(number)
+        })?;
+        let value = n["value"]
+            .as_f64()
+            .or_else(|| n["amount"].as_f64())
+            .filter(|v| v.is_finite() && *v >= 0.0)?;
+        let unit = n["unitName"].as_str().map(str::to_ascii_lowercase);
+        Some(match unit.as_deref() {
+            Some("kj") => value / KJ_PER_KCAL,
+            Some("g") if id == SODIUM.0 => value * 1000.0,
+            _ => value,
+        })
+    })
+}
+
+/// Maps a search hit or abridged food details to [`Food`]. Both report
+/// nutrients per 100 g for every data type. `None` without a description or
+/// nutrition.
+pub fn normalize(food: &Value) -> Option<Food> {
+    let id = food["fdcId"].as_i64()?;
+    let name = text(&food["description"])?;
+    let nutrients = food["foodNutrients"].as_array()?;
+    let per_100g = FoodNutrition {
+        total_calories_kcal: nutrient(
+            nutrients,
+            &[ENERGY, ENERGY_ATWATER_SPECIFIC, ENERGY_ATWATER_GENERAL],
+        ),
+        protein_g: nutrient(nutrients, &[PROTEIN]),
+        fat_g: nutrient(nutrients, &[FAT]),
+        carbs_g: nutrient(nutrients, &[CARBS]),
+        sodium_mg: nutrient(nutrients, &[SODIUM]),
+        sugar_g: nutrient(nutrients, &[SUGARS]),
+        fiber_g: nutrient(nutrients, &[FIBER]),
+    }
+    .scaled(1.0);
+    if per_100g.is_empty() {
+        return None;
+    }
+    let serving_size_g = food["servingSize"]
+        .as_f64()
+        .filter(|g| *g > 0.0)
+        .filter(|_| {
+            matches!(
+                food["servingSizeUnit"]
+                    .as_str()
+                    .map(str::to_ascii_lowercase)
+                    .as_deref(),
+                Some("g" | "grm")
+            )
+        });
+    Some(Food {
+        source: FoodSource::Usda,
+        id: id.to_string(),
+        name,
+        brand: text(&food["brandName"]).or_else(|| text(&food["brandOwner"])),
+        barcode: text(&food["gtinUpc"]),
+        serving_size_g,
+        serving_description: text(&food["householdServingFullText"]),
+        per_serving: serving_size_g.map(|g| per_100g.scaled(g / 100.0)),
+        per_100g,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn normalize_branded_food() {
+        let food = json!({
+            "fdcId": 2345678,
+            "description": "GREEK YOGURT, PLAIN",
+            "dataType": "Branded",
+            "brandOwner": "Dairy Co",
+            "gtinUpc": "036000291452",
+            "servingSize": 170,
+            "servingSizeUnit": "GRM",
+            "householdServingFullText": "1 container",
+            "foodNutrients": [
+                {"nutrientId": 1008, "unitName": "KCAL", "value": 59},
+                {"nutrientId": 1003, "unitName": "G", "value": 10.3},
+                {"nutrientId": 1093, "unitName": "MG", "value": 36},
+            ],
+        });
+        let food = normalize(&food).unwrap();
+        assert_eq!(food.id, "2345678");
+        assert_eq!(food.brand.as_deref(), Some("Dairy Co"));
+        assert_eq!(food.per_100g.protein_g, Some(10.3));
+        let serving = food.per_serving.unwrap();
+        assert_eq!(serving.total_calories_kcal, Some(100.3));
+        assert_eq!(serving.sodium_mg, Some(61.2));
+    }
+
+    #[test]
+    fn normalize_uses_atwater_energy_and_skips_liquid_servings() {
+        let food = json!({
+            "fdcId": 1,
+            "description": "Milk, whole",
+            "servingSize": 240,
+            "servingSizeUnit": "ml",
+            "foodNutrients": [
+                {"nutrientId": 2047, "unitName": "KCAL", "value": 61},
+                {"nutrientId": 1093, "unitName": "G", "value": 0.043},
+            ],
+        });
+        let food = normalize(&food).unwrap();
+        assert_eq!(food.per_100g.total_calories_kcal, Some(61.0));
+        assert_eq!(food.per_100g.sodium_mg, Some(43.0));
+        assert!(food.serving_size_g.is_none());
+        assert!(food.per_serving.is_none());
+    }
+
+    #[test]
+    fn normalize_reads_abridged_details() {
+        let food = json!({
+            "fdcId": 171705,
+            "description": "Avocados, raw, all commercial varieties",
+            "foodNutrients": [
+                {"number": "208", "name": "Energy", "amount": 160, "unitName": "kcal"},
+                {"number": "291", "name": "Fiber", "amount": 6.7, "unitName": "g"},
+            ],
+        });
+        let food = normalize(&food).unwrap();
+        assert_eq!(food.per_100g.total_calories_kcal, Some(160.0));
+        assert_eq!(food.per_100g.fiber_g, Some(6.7));
+    }
+
+    #[tokio::test]
+    async fn failed_lookups_do_not_reveal_the_key() {
+        use axum::{http::StatusCode, routing::get, Router};
+
+        let app = Router::new()
+            .route(
+                "/v1/foods/search",
+                get(|| async { StatusCode::TOO_MANY_REQUESTS }),
+            )
+            .route("/v1/food/:id", get(|| async { StatusCode::BAD_GATEWAY }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let usda = Usda::new(
+            reqwest::Client::new(),
+            "secret-fdc-key".into(),
+            format!("http://{addr}"),
+        );
+        let search = usda.search("apple").await.unwrap_err();
+        let food = usda.food("171705").await.unwrap_err();
+        for err in [search, food] {
+            let text = format!("{err:#} {err:?}");
+            assert!(text.contains("http://"), "{text}");
+            assert!(!text.contains("secret-fdc-key"), "{text}");
+        }
+    }
+
+    #[test]
+    fn normalize_skips_foods_without_nutrients() {
+        let food = json!({"fdcId": 1, "description": "Water", "foodNutrients": []});
+        assert!(normalize(&food).is_none());
+    }
+}
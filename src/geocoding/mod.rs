@@ -0,0 +1,56 @@
+//! Reverse geocoding of meal locations, so a meal logged with coordinates
+//! only still gets a place name. Providers sit behind [`Geocoder`]; lookups
+//! run from the job queue, never in the request.
+
+use std::{sync::Arc, time::Duration};
+
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    config::GeocodingConfig,
+    db::AppState,
+    foods::USER_AGENT,
+    meals::{dto::MAX_PLACE_NAME_LEN, repo as meals_repo},
+};
+
+pub mod nominatim;
+
+#[axum::async_trait]
+pub trait Geocoder: Send + Sync {
+    /// Name of the place at the coordinates; `None` when nothing is known
+    /// there.
+    async fn reverse(&self, latitude: f64, longitude: f64) -> anyhow::Result<Option<String>>;
+}
+
+/// The configured geocoder, or `None` when reverse geocoding is off.
+pub fn from_config(config: &GeocodingConfig) -> anyhow::Result<Option<Arc<dyn Geocoder>>> {
+    let Some(base_url) = config.nominatim_url.clone() else {
+        return Ok(None);
+    };
+    let http = reqwest::Client::builder()
+        .timeout(Duration::from_secs(config.timeout_secs))
+        .user_agent(USER_AGENT)
+        .build()?;
+    Ok(Some(Arc::new(nominatim::Nominatim::new(http, base_url))))
+}
+
+/// Job body: looks up and stores the place name of a meal that has
+/// coordinates but no name. A name set meanwhile is kept.
+pub async fn geocode_meal(state: &AppState, meal_id: Uuid) -> anyhow::Result<()> {
+    let Some(geocoder) = &state.geocoder else {
+        return Ok(());
+    };
+    let Some((latitude, longitude)) = meals_repo::find_unnamed_location(&state.db, meal_id).await?
+    else {
+        return Ok(());
+    };
+    let Some(name) = geocoder.reverse(latitude, longitude).await? else {
+        info!(meal_id = %meal_id, "no place found for meal location");
+        return Ok(());
+    };
+    let name: String = name.trim().chars().take(MAX_PLACE_NAME_LEN).collect();
+    meals_repo::set_place_name(&state.db, meal_id, &name).await?;
+    info!(meal_id = %meal_id, "meal place name geocoded");
+    Ok(())
+}
@@ -0,0 +1,87 @@
+use serde_json::Value;
+
+use crate::geocoding::Geocoder;
+
+/// Nominatim `/reverse`, as run by OpenStreetMap or self-hosted.
+pub struct Nominatim {
+    http: reqwest::Client,
+    base_url: String,
+}
+
+impl Nominatim {
+    pub fn new(http: reqwest::Client, base_url: String) -> Self {
+        Self { http, base_url }
+    }
+}
+
+#[axum::async_trait]
+impl Geocoder for Nominatim {
+    async fn reverse(&self, latitude: f64, longitude: f64) -> anyhow::Result<Option<String>> {
+        let body: Value = self
+            .http
+            .get(format!("{}/reverse", self.base_url.trim_end_matches('/')))
+            .query(&[
+                ("format", "jsonv2"),
+                ("lat", &latitude.to_string()),
+                ("lon", &longitude.to_string()),
+                // Building level, where restaurants are.
+                ("zoom", "18"),
+            ])
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(place_name(&body))
+    }
+}
+
+fn text(value: &Value) -> Option<&str> {
+    value.as_str().map(str::trim).filter(|s| !s.is_empty())
+}
+
+/// The named place (restaurant, café, shop) when there is one, else the
+/// start of the address. Nominatim answers `{"error": ..}` for places it
+/// can't resolve.
+fn place_name(body: &Value) -> Option<String> {
+    if body.get("error").is_some() {
+        return None;
+    }
+    let address = &body["address"];
+    text(&body["name"])
+        .or_else(|| text(&address["amenity"]))
+        .or_else(|| text(&address["shop"]))
+        .map(str::to_string)
+        .or_else(|| {
+            let display = text(&body["display_name"])?;
+            let parts: Vec<&str> = display.split(',').map(str::trim).take(2).collect();
+            Some(parts.join(", "))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn prefers_the_named_place() {
+        let body = json!({
+            "name": "Chez Marie",
+            "display_name": "Chez Marie, 12, Rue de Rivoli, Paris, France",
+            "address": {"amenity": "Chez Marie", "road": "Rue de Rivoli"},
+        });
+        assert_eq!(place_name(&body).as_deref(), Some("Chez Marie"));
+    }
+
+    #[test]
+    fn falls_back_to_the_address() {
+        let body = json!({
+            "name": "",
+            "display_name": "12, Rue de Rivoli, Paris, France",
+            "address": {"road": "Rue de Rivoli"},
+        });
+        assert_eq!(place_name(&body).as_deref(), Some("12, Rue de Rivoli"));
+        assert_eq!(place_name(&json!({"error": "Unable to geocode"})), None);
+    }
+}
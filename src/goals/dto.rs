@@ -0,0 +1,315 @@
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use utoipa::ToSchema;
+
+use crate::{
+    foods::dto::FoodNutrition,
+    meals::{dto::MealNutrition, score::DailyTargets},
+};
+
+/// Sanity caps for goal values.
+pub const MAX_GOAL_KCAL: f64 = 20_000.0;
+pub const MAX_GOAL_GRAMS: f64 = 5_000.0;
+pub const MAX_GOAL_MG: f64 = 100_000.0;
+
+/// Stored goals; unset values fall back to [`DailyTargets::default`].
+#[derive(Debug, Clone, Default, Deserialize, sqlx::FromRow)]
+pub struct Goals {
+    pub daily_calories_kcal: Option<f64>,
+    pub protein_g: Option<f64>,
+    pub fat_g: Option<f64>,
+    pub carbs_g: Option<f64>,
+    pub fiber_g: Option<f64>,
+    pub sugar_max_g: Option<f64>,
+    pub sodium_max_mg: Option<f64>,
+    pub caffeine_max_mg: Option<f64>,
+    pub alcohol_max_g: Option<f64>,
+    #[serde(skip)]
+    pub updated_at: Option<OffsetDateTime>,
+}
+
+impl Goals {
+    pub fn validate(&self) -> Result<(), String> {
+        let fields = [
+            (
+                "daily_calories_kcal",
+                self.daily_calories_kcal,
+                MAX_GOAL_KCAL,
+            ),
+            ("protein_g", self.protein_g, MAX_GOAL_GRAMS),
+            ("fat_g", self.fat_g, MAX_GOAL_GRAMS),
+            ("carbs_g", self.carbs_g, MAX_GOAL_GRAMS),
+            ("fiber_g", self.fiber_g, MAX_GOAL_GRAMS),
+            ("sugar_max_g", self.sugar_max_g, MAX_GOAL_GRAMS),
+            ("sodium_max_mg", self.sodium_max_mg, MAX_GOAL_MG),
+            ("caffeine_max_mg", self.caffeine_max_mg, MAX_GOAL_MG),
+            ("alcohol_max_g", self.alcohol_max_g, MAX_GOAL_GRAMS),
+        ];
+        for (name, value, max) in fields {
+            if let Some(v) = value {
+                if !v.is_finite() || v <= 0.0 || v > max {
+                    return Err(format!(
+                        "{} must be greater than 0 and at most {}",
+                        name, max
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn targets(&self) -> DailyTargets {
+        let defaults = DailyTargets::default();
+        DailyTargets {
+            calories_kcal: self.daily_calories_kcal.unwrap_or(defaults.calories_kcal),
+            protein_g: self.protein_g.unwrap_or(defaults.protein_g),
+            fat_g: self.fat_g.unwrap_or(defaults.fat_g),
+            carbs_g: self.carbs_g.unwrap_or(defaults.carbs_g),
+            fiber_g: self.fiber_g.unwrap_or(defaults.fiber_g),
+            sugar_max_g: self.sugar_max_g.unwrap_or(defaults.sugar_max_g),
+            sodium_max_mg: self.sodium_max_mg.unwrap_or(defaults.sodium_max_mg),
+            caffeine_max_mg: self.caffeine_max_mg.unwrap_or(defaults.caffeine_max_mg),
+            alcohol_max_g: self.alcohol_max_g.unwrap_or(defaults.alcohol_max_g),
+        }
+    }
+}
+
+/// Effective goals: the user's values with defaults filled in.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GoalsResponse {
+    pub daily_calories_kcal: f64,
+    pub protein_g: f64,
+    pub fat_g: f64,
+    pub carbs_g: f64,
+    pub fiber_g: f64,
+    pub sugar_max_g: f64,
+    pub sodium_max_mg: f64,
+    pub caffeine_max_mg: f64,
+    pub alcohol_max_g: f64,
+    /// `null` while the user relies on the defaults only.
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub updated_at: Option<OffsetDateTime>,
+}
+
+impl From<&Goals> for GoalsResponse {
+    fn from(goals: &Goals) -> Self {
+        let targets = goals.targets();
+        Self {
+            daily_calories_kcal: targets.calories_kcal,
+            protein_g: targets.protein_g,
+            fat_g: targets.fat_g,
+            carbs_g: targets.carbs_g,
+            fiber_g: targets.fiber_g,
+            sugar_max_g: targets.sugar_max_g,
+            sodium_max_mg: targets.sodium_max_mg,
+            caffeine_max_mg: targets.caffeine_max_mg,
+            alcohol_max_g: targets.alcohol_max_g,
+            updated_at: goals.updated_at,
+        }
+    }
+}
+
+/// Amounts consumed, per nutrient; `None` when unknown.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, sqlx::FromRow)]
+pub struct Intake {
+    pub calories_kcal: Option<f64>,
+    pub protein_g: Option<f64>,
+    pub fat_g: Option<f64>,
+    pub carbs_g: Option<f64>,
+    pub fiber_g: Option<f64>,
+    pub sugar_g: Option<f64>,
+    pub sodium_mg: Option<f64>,
+    pub caffeine_mg: Option<f64>,
+    pub alcohol_g: Option<f64>,
+}
+
+impl From<&FoodNutrition> for Intake {
+    fn from(n: &FoodNutrition) -> Self {
+        Self {
+            calories_kcal: n.total_calories_kcal,
+            protein_g: n.protein_g,
+            fat_g: n.fat_g,
+            carbs_g: n.carbs_g,
+            fiber_g: n.fiber_g,
+            sugar_g: n.sugar_g,
+            sodium_mg: n.sodium_mg,
+            caffeine_mg: None,
+            alcohol_g: None,
+        }
+    }
+}
+
+impl From<&MealNutrition> for Intake {
+    fn from(n: &MealNutrition) -> Self {
+        Self {
+            calories_kcal: n.total_calories_kcal,
+            protein_g: n.protein_g,
+            fat_g: n.fat_g,
+            carbs_g: n.carbs_g,
+            fiber_g: n.fiber_g,
+            sugar_g: n.sugar_g,
+            sodium_mg: n.sodium_mg,
+            caffeine_mg: n.caffeine_mg,
+            alcohol_g: n.alcohol_g,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct NutrientProgress {
+    pub consumed: f64,
+    pub target: f64,
+    /// Negative once the target is exceeded.
+    pub remaining: f64,
+    pub percent: f64,
+}
+
+impl NutrientProgress {
+    fn new(consumed: f64, target: f64) -> Self {
+        let round1 = |v: f64| (v * 10.0).round() / 10.0;
+        Self {
+            consumed: round1(consumed),
+            target: round1(target),
+            remaining: round1(target - consumed),
+            percent: round1(consumed / target * 100.0),
+        }
+    }
+}
+
+/// Intake measured against daily goals. Sugar, sodium, caffeine and alcohol
+/// targets are limits.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct GoalProgress {
+    pub calories_kcal: Option<NutrientProgress>,
+    pub protein_g: Option<NutrientProgress>,
+    pub fat_g: Option<NutrientProgress>,
+    pub carbs_g: Option<NutrientProgress>,
+    pub fiber_g: Option<NutrientProgress>,
+    pub sugar_g: Option<NutrientProgress>,
+    pub sodium_mg: Option<NutrientProgress>,
+    pub caffeine_mg: Option<NutrientProgress>,
+    pub alcohol_g: Option<NutrientProgress>,
+}
+
+/// A daily limit the intake went over.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct LimitWarning {
+    /// Intake field the limit applies to, e.g. `caffeine_mg`.
+    pub nutrient: String,
+    pub consumed: f64,
+    pub limit: f64,
+}
+
+impl GoalProgress {
+    pub fn new(intake: &Intake, targets: &DailyTargets) -> Self {
+        let progress =
+            |consumed: Option<f64>, target: f64| consumed.map(|c| NutrientProgress::new(c, target));
+        Self {
+            calories_kcal: progress(intake.calories_kcal, targets.calories_kcal),
+            protein_g: progress(intake.protein_g, targets.protein_g),
+            fat_g: progress(intake.fat_g, targets.fat_g),
+            carbs_g: progress(intake.carbs_g, targets.carbs_g),
+            fiber_g: progress(intake.fiber_g, targets.fiber_g),
+            sugar_g: progress(intake.sugar_g, targets.sugar_max_g),
+            sodium_mg: progress(intake.sodium_mg, targets.sodium_max_mg),
+            caffeine_mg: progress(intake.caffeine_mg, targets.caffeine_max_mg),
+            alcohol_g: progress(intake.alcohol_g, targets.alcohol_max_g),
+        }
+    }
+
+    /// Limits the intake went over, in field order.
+    pub fn exceeded_limits(&self) -> Vec<LimitWarning> {
+        [
+            ("sugar_g", &self.sugar_g),
+            ("sodium_mg", &self.sodium_mg),
+            ("caffeine_mg", &self.caffeine_mg),
+            ("alcohol_g", &self.alcohol_g),
+        ]
+        .into_iter()
+        .filter_map(|(nutrient, progress)| {
+            let progress = progress.as_ref().filter(|p| p.remaining < 0.0)?;
+            Some(LimitWarning {
+                nutrient: nutrient.to_string(),
+                consumed: progress.consumed,
+                limit: progress.target,
+            })
+        })
+        .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unset_goals_fall_back_to_defaults() {
+        let goals = Goals {
+            daily_calories_kcal: Some(1800.0),
+            ..Goals::default()
+        };
+        let targets = goals.targets();
+        assert_eq!(targets.calories_kcal, 1800.0);
+        assert_eq!(targets.protein_g, DailyTargets::default().protein_g);
+    }
+
+    #[test]
+    fn validate_rejects_non_positive_and_oversized_values() {
+        let zero = Goals {
+            protein_g: Some(0.0),
+            ..Goals::default()
+        };
+        assert!(zero.validate().is_err());
+        let huge = Goals {
+            sodium_max_mg: Some(MAX_GOAL_MG + 1.0),
+            ..Goals::default()
+        };
+        assert!(huge.validate().is_err());
+        assert!(Goals::default().validate().is_ok());
+    }
+
+    #[test]
+    fn progress_reports_remaining_and_percent() {
+        let intake = Intake {
+            calories_kcal: Some(1500.0),
+            sugar_g: Some(60.0),
+            ..Intake::default()
+        };
+        let progress = GoalProgress::new(&intake, &DailyTargets::default());
+        let calories = progress.calories_kcal.unwrap();
+        assert_eq!(calories.remaining, 500.0);
+        assert_eq!(calories.percent, 75.0);
+        assert_eq!(progress.sugar_g.unwrap().remaining, -10.0);
+        assert!(progress.protein_g.is_none());
+    }
+
+    #[test]
+    fn exceeded_limits_warn_about_caffeine_and_alcohol() {
+        let goals = Goals {
+            alcohol_max_g: Some(10.0),
+            ..Goals::default()
+        };
+        let intake = Intake {
+            sugar_g: Some(40.0),
+            caffeine_mg: Some(480.0),
+            alcohol_g: Some(10.0),
+            ..Intake::default()
+        };
+        let warnings = GoalProgress::new(&intake, &goals.targets()).exceeded_limits();
+        assert_eq!(
+            warnings,
+            vec![LimitWarning {
+                nutrient: "caffeine_mg".into(),
+                consumed: 480.0,
+                limit: 400.0,
+            }]
+        );
+        let over = Intake {
+            alcohol_g: Some(24.0),
+            ..intake
+        };
+        let warnings = GoalProgress::new(&over, &goals.targets()).exceeded_limits();
+        assert_eq!(warnings.len(), 2);
+        assert_eq!(warnings[1].nutrient, "alcohol_g");
+    }
+}
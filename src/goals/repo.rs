@@ -0,0 +1,77 @@
+use sqlx::PgExecutor;
+use uuid::Uuid;
+
+use crate::goals::dto::Goals;
+
+/// The user's goals, or all-default goals if none were ever saved.
+pub async fn find_goals(db: impl PgExecutor<'_>, user_id: Uuid) -> anyhow::Result<Goals> {
+    let goals = sqlx::query_as::<_, Goals>(
+        r#"
+        SELECT daily_calories_kcal::float8 AS daily_calories_kcal,
+               protein_g::float8 AS protein_g,
+               fat_g::float8 AS fat_g,
+               carbs_g::float8 AS carbs_g,
+               fiber_g::float8 AS fiber_g,
+               sugar_max_g::float8 AS sugar_max_g,
+               sodium_max_mg::float8 AS sodium_max_mg,
+               caffeine_max_mg::float8 AS caffeine_max_mg,
+               alcohol_max_g::float8 AS alcohol_max_g,
+               updated_at
+        FROM goals
+        WHERE user_id = $1
+        "#,
+    )
+    .bind(user_id)
+    .fetch_optional(db)
+    .await?;
+    Ok(goals.unwrap_or_default())
+}
+
+/// Replaces all of the user's goals; omitted values revert to defaults.
+pub async fn upsert_goals(
+    db: impl PgExecutor<'_>,
+    user_id: Uuid,
+    goals: &Goals,
+) -> anyhow::Result<Goals> {
+    let goals = sqlx::query_as::<_, Goals>(
+        r#"
+        INSERT INTO goals (user_id, daily_calories_kcal, protein_g, fat_g, carbs_g,
+                           fiber_g, sugar_max_g, sodium_max_mg, caffeine_max_mg, alcohol_max_g)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+        ON CONFLICT (user_id) DO UPDATE
+        SET daily_calories_kcal = EXCLUDED.daily_calories_kcal,
+            protein_g = EXCLUDED.protein_g,
+            fat_g = EXCLUDED.fat_g,
+            carbs_g = EXCLUDED.carbs_g,
+            fiber_g = EXCLUDED.fiber_g,
+            sugar_max_g = EXCLUDED.sugar_max_g,
+            sodium_max_mg = EXCLUDED.sodium_max_mg,
+            caffeine_max_mg = EXCLUDED.caffeine_max_mg,
+            alcohol_max_g = EXCLUDED.alcohol_max_g,
+            updated_at = NOW()
+        RETURNING daily_calories_kcal::float8 AS daily_calories_kcal,
+                  protein_g::float8 AS protein_g,
+                  fat_g::float8 AS fat_g,
+                  carbs_g::float8 AS carbs_g,
+                  fiber_g::float8 AS fiber_g,
+                  sugar_max_g::float8 AS sugar_max_g,
+                  sodium_max_mg::float8 AS sodium_max_mg,
+                  caffeine_max_mg::float8 AS caffeine_max_mg,
+                  alcohol_max_g::float8 AS alcohol_max_g,
+                  updated_at
+        "#,
+    )
+    .bind(user_id)
+    .bind(goals.daily_calories_kcal)
+    .bind(goals.protein_g)
+    .bind(goals.fat_g)
+    .bind(goals.carbs_g)
+    .bind(goals.fiber_g)
+    .bind(goals.sugar_max_g)
+    .bind(goals.sodium_max_mg)
+    .bind(goals.caffeine_max_mg)
+    .bind(goals.alcohol_max_g)
+    .fetch_one(db)
+    .await?;
+    Ok(goals)
+}
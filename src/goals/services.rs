@@ -0,0 +1,21 @@
+use uuid::Uuid;
+
+use crate::{
+    db::AppState,
+    goals::{dto::Goals, repo},
+};
+
+/// The user's own goals, read in their row-level security scope.
+pub async fn get_goals(state: &AppState, user_id: Uuid) -> anyhow::Result<Goals> {
+    let mut tx = state.begin_as(user_id).await?;
+    let goals = repo::find_goals(&mut *tx, user_id).await?;
+    tx.commit().await?;
+    Ok(goals)
+}
+
+pub async fn save_goals(state: &AppState, user_id: Uuid, goals: &Goals) -> anyhow::Result<Goals> {
+    let mut tx = state.begin_as(user_id).await?;
+    let goals = repo::upsert_goals(&mut *tx, user_id, goals).await?;
+    tx.commit().await?;
+    Ok(goals)
+}
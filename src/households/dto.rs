@@ -0,0 +1,111 @@
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+pub const MAX_NAME_LEN: usize = 100;
+pub const MAX_HOUSEHOLD_MEMBERS: i64 = 20;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+pub enum HouseholdRole {
+    /// Created the household; invites, removes members and deletes it.
+    Owner,
+    Member,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HouseholdRequest {
+    pub name: String,
+}
+
+impl HouseholdRequest {
+    pub fn normalized(self) -> Result<Self, String> {
+        let name = self.name.trim().to_string();
+        if name.is_empty() || name.chars().count() > MAX_NAME_LEN {
+            return Err(format!(
+                "name must be between 1 and {} characters",
+                MAX_NAME_LEN
+            ));
+        }
+        Ok(Self { name })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct InviteRequest {
+    pub email: String,
+}
+
+/// A household as seen by one of its members.
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct HouseholdSummary {
+    pub id: Uuid,
+    pub name: String,
+    pub role: HouseholdRole,
+    pub member_count: i64,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct HouseholdMember {
+    pub user_id: Uuid,
+    pub email: String,
+    pub role: HouseholdRole,
+    #[serde(with = "time::serde::rfc3339")]
+    pub joined_at: OffsetDateTime,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct HouseholdInvite {
+    pub id: Uuid,
+    pub household_id: Uuid,
+    pub email: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+/// Pending invite addressed to the current user.
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct ReceivedInvite {
+    pub id: Uuid,
+    pub household_id: Uuid,
+    pub household_name: String,
+    pub invited_by: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HouseholdDetails {
+    #[serde(flatten)]
+    pub household: HouseholdSummary,
+    pub members: Vec<HouseholdMember>,
+    pub invites: Vec<HouseholdInvite>,
+}
+
+/// Moves a meal into a household, or out of it with `null`.
+#[derive(Debug, Deserialize)]
+pub struct MealHouseholdRequest {
+    pub household_id: Option<Uuid>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn household_names_are_trimmed_and_bounded() {
+        let request = HouseholdRequest {
+            name: "  Home ".into(),
+        };
+        assert_eq!(request.normalized().unwrap().name, "Home");
+        assert!(HouseholdRequest { name: " ".into() }.normalized().is_err());
+        assert!(HouseholdRequest {
+            name: "x".repeat(MAX_NAME_LEN + 1)
+        }
+        .normalized()
+        .is_err());
+    }
+}
@@ -0,0 +1,5 @@
+//! Shared spaces where members see each other's meals.
+
+pub mod dto;
+pub mod repo;
+pub mod services;
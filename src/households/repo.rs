@@ -0,0 +1,309 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::households::dto::{HouseholdInvite, HouseholdMember, HouseholdSummary, ReceivedInvite};
+
+const SUMMARY_COLUMNS: &str = r#"
+    h.id, h.name, m.role, h.created_at,
+    (SELECT COUNT(*) FROM household_members c WHERE c.household_id = h.id) AS member_count
+"#;
+
+/// Creates a household with `user_id` as its owner.
+pub async fn create_household(
+    db: &PgPool,
+    user_id: Uuid,
+    name: &str,
+) -> anyhow::Result<HouseholdSummary> {
+    let mut tx = db.begin().await?;
+    let household_id =
+        sqlx::query_scalar::<_, Uuid>(r#"INSERT INTO households (name) VALUES ($1) RETURNING id"#)
+            .bind(name)
+            .fetch_one(&mut *tx)
+            .await?;
+    sqlx::query(
+        r#"INSERT INTO household_members (household_id, user_id, role) VALUES ($1, $2, 'owner')"#,
+    )
+    .bind(household_id)
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+    find_household(db, user_id, household_id)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("household disappeared right after creation"))
+}
+
+pub async fn list_households(db: &PgPool, user_id: Uuid) -> anyhow::Result<Vec<HouseholdSummary>> {
+    let households = sqlx::query_as::<_, HouseholdSummary>(&format!(
+        r#"
+        SELECT {SUMMARY_COLUMNS}
+        FROM households h
+        JOIN household_members m ON m.household_id = h.id
+        WHERE m.user_id = $1
+        ORDER BY h.name, h.id
+        "#
+    ))
+    .bind(user_id)
+    .fetch_all(db)
+    .await?;
+    Ok(households)
+}
+
+/// The household with the caller's role; `None` unless `user_id` is a member.
+pub async fn find_household(
+    db: &PgPool,
+    user_id: Uuid,
+    household_id: Uuid,
+) -> anyhow::Result<Option<HouseholdSummary>> {
+    let household = sqlx::query_as::<_, HouseholdSummary>(&format!(
+        r#"
+        SELECT {SUMMARY_COLUMNS}
+        FROM households h
+        JOIN household_members m ON m.household_id = h.id
+        WHERE h.id = $1 AND m.user_id = $2
+        "#
+    ))
+    .bind(household_id)
+    .bind(user_id)
+    .fetch_optional(db)
+    .await?;
+    Ok(household)
+}
+
+pub async fn is_member(db: &PgPool, user_id: Uuid, household_id: Uuid) -> anyhow::Result<bool> {
+    let member = sqlx::query_scalar::<_, bool>(
+        r#"
+        SELECT EXISTS (
+            SELECT 1 FROM household_members WHERE household_id = $1 AND user_id = $2
+        )
+        "#,
+    )
+    .bind(household_id)
+    .bind(user_id)
+    .fetch_one(db)
+    .await?;
+    Ok(member)
+}
+
+pub async fn list_members(db: &PgPool, household_id: Uuid) -> anyhow::Result<Vec<HouseholdMember>> {
+    let members = sqlx::query_as::<_, HouseholdMember>(
+        r#"
+        SELECT m.user_id, u.email, m.role, m.joined_at
+        FROM household_members m
+        JOIN users u ON u.id = m.user_id
+        WHERE m.household_id = $1
+        ORDER BY m.joined_at, m.user_id
+        "#,
+    )
+    .bind(household_id)
+    .fetch_all(db)
+    .await?;
+    Ok(members)
+}
+
+pub async fn delete_household(db: &PgPool, household_id: Uuid) -> anyhow::Result<()> {
+    sqlx::query(r#"DELETE FROM households WHERE id = $1"#)
+        .bind(household_id)
+        .execute(db)
+        .await?;
+    Ok(())
+}
+
+/// Removes a non-owner member and takes their meals back out of the
+/// household.
+pub async fn remove_member(db: &PgPool, household_id: Uuid, user_id: Uuid) -> anyhow::Result<bool> {
+    let mut tx = db.begin().await?;
+    let removed = sqlx::query(
+        r#"
+        DELETE FROM household_members
+        WHERE household_id = $1 AND user_id = $2 AND role = 'member'
+        "#,
+    )
+    .bind(household_id)
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await?
+    .rows_affected()
+        > 0;
+    if removed {
+        sqlx::query(
+            r#"UPDATE meals SET household_id = NULL WHERE household_id = $1 AND user_id = $2"#,
+        )
+        .bind(household_id)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+    Ok(removed)
+}
+
+pub async fn member_count(db: &PgPool, household_id: Uuid) -> anyhow::Result<i64> {
+    let count = sqlx::query_scalar::<_, i64>(
+        r#"SELECT COUNT(*) FROM household_members WHERE household_id = $1"#,
+    )
+    .bind(household_id)
+    .fetch_one(db)
+    .await?;
+    Ok(count)
+}
+
+/// Pending invites of a household.
+pub async fn list_invites(db: &PgPool, household_id: Uuid) -> anyhow::Result<Vec<HouseholdInvite>> {
+    let invites = sqlx::query_as::<_, HouseholdInvite>(
+        r#"
+        SELECT id, household_id, email, created_at
+        FROM household_invites
+        WHERE household_id = $1
+        ORDER BY created_at, id
+        "#,
+    )
+    .bind(household_id)
+    .fetch_all(db)
+    .await?;
+    Ok(invites)
+}
+
+/// `true` if an account with `email` already belongs to the household.
+pub async fn has_member_email(
+    db: &PgPool,
+    household_id: Uuid,
+    email: &str,
+) -> anyhow::Result<bool> {
+    let member = sqlx::query_scalar::<_, bool>(
+        r#"
+        SELECT EXISTS (
+            SELECT 1 FROM household_members m
+            JOIN users u ON u.id = m.user_id
+            WHERE m.household_id = $1 AND lower(u.email) = lower($2)
+        )
+        "#,
+    )
+    .bind(household_id)
+    .bind(email)
+    .fetch_one(db)
+    .await?;
+    Ok(member)
+}
+
+/// `None` if the email already has a pending invite to the household.
+pub async fn insert_invite(
+    db: &PgPool,
+    household_id: Uuid,
+    email: &str,
+    invited_by: Uuid,
+) -> anyhow::Result<Option<HouseholdInvite>> {
+    let invite = sqlx::query_as::<_, HouseholdInvite>(
+        r#"
+        INSERT INTO household_invites (household_id, email, invited_by)
+        VALUES ($1, $2, $3)
+        ON CONFLICT DO NOTHING
+        RETURNING id, household_id, email, created_at
+        "#,
+    )
+    .bind(household_id)
+    .bind(email)
+    .bind(invited_by)
+    .fetch_optional(db)
+    .await?;
+    Ok(invite)
+}
+
+pub async fn delete_invite(
+    db: &PgPool,
+    household_id: Uuid,
+    invite_id: Uuid,
+) -> anyhow::Result<bool> {
+    let result =
+        sqlx::query(r#"DELETE FROM household_invites WHERE id = $1 AND household_id = $2"#)
+            .bind(invite_id)
+            .bind(household_id)
+            .execute(db)
+            .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Pending invites addressed to the email of `user_id`. Invites from another
+/// organization are not seen, and can't be accepted either.
+pub async fn list_received_invites(
+    db: &PgPool,
+    user_id: Uuid,
+) -> anyhow::Result<Vec<ReceivedInvite>> {
+    let invites = sqlx::query_as::<_, ReceivedInvite>(
+        r#"
+        SELECT i.id, i.household_id, h.name AS household_name, inviter.email AS invited_by,
+               i.created_at
+        FROM household_invites i
+        JOIN users u ON lower(u.email) = lower(i.email)
+        JOIN households h ON h.id = i.household_id
+        JOIN users inviter ON inviter.id = i.invited_by
+        WHERE u.id = $1 AND inviter.org_id IS NOT DISTINCT FROM u.org_id
+        ORDER BY i.created_at, i.id
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(db)
+    .await?;
+    Ok(invites)
+}
+
+/// Household of an invite addressed to the email of `user_id`.
+pub async fn find_received_invite(
+    db: &PgPool,
+    user_id: Uuid,
+    invite_id: Uuid,
+) -> anyhow::Result<Option<Uuid>> {
+    let household_id = sqlx::query_scalar::<_, Uuid>(
+        r#"
+        SELECT i.household_id
+        FROM household_invites i
+        JOIN users u ON lower(u.email) = lower(i.email)
+        JOIN users inviter ON inviter.id = i.invited_by
+        WHERE i.id = $1 AND u.id = $2 AND inviter.org_id IS NOT DISTINCT FROM u.org_id
+        "#,
+    )
+    .bind(invite_id)
+    .bind(user_id)
+    .fetch_optional(db)
+    .await?;
+    Ok(household_id)
+}
+
+/// Consumes an invite addressed to `user_id`, adding them as a member when
+/// `accept` is set. Returns the household, or `None` for unknown invites.
+pub async fn answer_invite(
+    db: &PgPool,
+    user_id: Uuid,
+    invite_id: Uuid,
+    accept: bool,
+) -> anyhow::Result<Option<Uuid>> {
+    let mut tx = db.begin().await?;
+    let household_id = sqlx::query_scalar::<_, Uuid>(
+        r#"
+        DELETE FROM household_invites i
+        USING users u, users inviter
+        WHERE i.id = $1 AND u.id = $2 AND lower(u.email) = lower(i.email)
+          AND inviter.id = i.invited_by AND inviter.org_id IS NOT DISTINCT FROM u.org_id
+        RETURNING i.household_id
+        "#,
+    )
+    .bind(invite_id)
+    .bind(user_id)
+    .fetch_optional(&mut *tx)
+    .await?;
+    if let (Some(household_id), true) = (household_id, accept) {
+        sqlx::query(
+            r#"
+            INSERT INTO household_members (household_id, user_id, role)
+            VALUES ($1, $2, 'member')
+            ON CONFLICT DO NOTHING
+            "#,
+        )
+        .bind(household_id)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+    Ok(household_id)
+}
@@ -0,0 +1,274 @@
+use axum::http::StatusCode;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    db::{AppState, RepoError},
+    error::ErrorCode,
+    households::{
+        dto::{
+            HouseholdDetails, HouseholdInvite, HouseholdRequest, HouseholdRole, HouseholdSummary,
+            InviteRequest, ReceivedInvite, MAX_HOUSEHOLD_MEMBERS,
+        },
+        repo,
+    },
+    meals::{
+        dto::{ListMealsQuery, MealResponse},
+        repo as meals_repo, services as meals_services,
+    },
+    routes::auth::is_valid_email,
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum HouseholdError {
+    #[error("Household not found")]
+    NotFound,
+    #[error("Invite not found")]
+    InviteNotFound,
+    #[error("Member not found")]
+    MemberNotFound,
+    #[error("Meal not found")]
+    MealNotFound,
+    #[error("Only the household owner can do this")]
+    NotOwner,
+    #[error("{0}")]
+    Conflict(String),
+    #[error("{0}")]
+    Invalid(String),
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+impl From<RepoError> for HouseholdError {
+    fn from(e: RepoError) -> Self {
+        HouseholdError::Other(e.into())
+    }
+}
+
+impl HouseholdError {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            HouseholdError::NotFound
+            | HouseholdError::InviteNotFound
+            | HouseholdError::MemberNotFound
+            | HouseholdError::MealNotFound => StatusCode::NOT_FOUND,
+            HouseholdError::NotOwner => StatusCode::FORBIDDEN,
+            HouseholdError::Conflict(_) => StatusCode::CONFLICT,
+            HouseholdError::Invalid(_) => StatusCode::BAD_REQUEST,
+            HouseholdError::Other(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            HouseholdError::NotFound => ErrorCode::HouseholdNotFound,
+            HouseholdError::InviteNotFound => ErrorCode::HouseholdInviteNotFound,
+            HouseholdError::MemberNotFound => ErrorCode::HouseholdMemberNotFound,
+            HouseholdError::MealNotFound => ErrorCode::MealNotFound,
+            HouseholdError::NotOwner => ErrorCode::HouseholdOwnerRequired,
+            HouseholdError::Conflict(_) => ErrorCode::HouseholdConflict,
+            HouseholdError::Invalid(_) => ErrorCode::ValidationFailed,
+            HouseholdError::Other(_) => ErrorCode::Internal,
+        }
+    }
+}
+
+/// The household if `user_id` is a member; non-members get `NotFound` so
+/// household ids can't be probed.
+async fn membership(
+    state: &AppState,
+    user_id: Uuid,
+    household_id: Uuid,
+) -> Result<HouseholdSummary, HouseholdError> {
+    repo::find_household(&state.db, user_id, household_id)
+        .await?
+        .ok_or(HouseholdError::NotFound)
+}
+
+async fn ownership(
+    state: &AppState,
+    user_id: Uuid,
+    household_id: Uuid,
+) -> Result<HouseholdSummary, HouseholdError> {
+    let household = membership(state, user_id, household_id).await?;
+    if household.role != HouseholdRole::Owner {
+        return Err(HouseholdError::NotOwner);
+    }
+    Ok(household)
+}
+
+pub async fn create_household(
+    state: &AppState,
+    user_id: Uuid,
+    input: HouseholdRequest,
+) -> Result<HouseholdSummary, HouseholdError> {
+    let input = input.normalized().map_err(HouseholdError::Invalid)?;
+    let household = repo::create_household(&state.db, user_id, &input.name).await?;
+    info!(user_id = %user_id, household_id = %household.id, "household created");
+    Ok(household)
+}
+
+pub async fn get_household(
+    state: &AppState,
+    user_id: Uuid,
+    household_id: Uuid,
+) -> Result<HouseholdDetails, HouseholdError> {
+    let household = membership(state, user_id, household_id).await?;
+    let members = repo::list_members(&state.db, household_id).await?;
+    let invites = repo::list_invites(&state.db, household_id).await?;
+    Ok(HouseholdDetails {
+        household,
+        members,
+        invites,
+    })
+}
+
+/// Deletes the household; meals logged into it go back to being private.
+pub async fn delete_household(
+    state: &AppState,
+    user_id: Uuid,
+    household_id: Uuid,
+) -> Result<(), HouseholdError> {
+    ownership(state, user_id, household_id).await?;
+    repo::delete_household(&state.db, household_id).await?;
+    info!(user_id = %user_id, household_id = %household_id, "household deleted");
+    Ok(())
+}
+
+pub async fn invite(
+    state: &AppState,
+    user_id: Uuid,
+    household_id: Uuid,
+    input: InviteRequest,
+) -> Result<HouseholdInvite, HouseholdError> {
+    ownership(state, user_id, household_id).await?;
+    let email = input.email.trim().to_lowercase();
+    if !is_valid_email(&email) {
+        return Err(HouseholdError::Invalid("Invalid email".to_string()));
+    }
+    if repo::has_member_email(&state.db, household_id, &email).await? {
+        return Err(HouseholdError::Conflict("Already a member".to_string()));
+    }
+    if repo::member_count(&state.db, household_id).await? >= MAX_HOUSEHOLD_MEMBERS {
+        return Err(HouseholdError::Conflict(format!(
+            "A household can have at most {} members",
+            MAX_HOUSEHOLD_MEMBERS
+        )));
+    }
+    let invite = repo::insert_invite(&state.db, household_id, &email, user_id)
+        .await?
+        .ok_or_else(|| HouseholdError::Conflict("Already invited".to_string()))?;
+    info!(user_id = %user_id, household_id = %household_id, invite_id = %invite.id, "household invite sent");
+    Ok(invite)
+}
+
+pub async fn cancel_invite(
+    state: &AppState,
+    user_id: Uuid,
+    household_id: Uuid,
+    invite_id: Uuid,
+) -> Result<(), HouseholdError> {
+    ownership(state, user_id, household_id).await?;
+    if !repo::delete_invite(&state.db, household_id, invite_id).await? {
+        return Err(HouseholdError::InviteNotFound);
+    }
+    Ok(())
+}
+
+pub async fn received_invites(
+    state: &AppState,
+    user_id: Uuid,
+) -> Result<Vec<ReceivedInvite>, HouseholdError> {
+    Ok(repo::list_received_invites(&state.db, user_id).await?)
+}
+
+pub async fn accept_invite(
+    state: &AppState,
+    user_id: Uuid,
+    invite_id: Uuid,
+) -> Result<HouseholdSummary, HouseholdError> {
+    let household_id = repo::find_received_invite(&state.db, user_id, invite_id)
+        .await?
+        .ok_or(HouseholdError::InviteNotFound)?;
+    if repo::member_count(&state.db, household_id).await? >= MAX_HOUSEHOLD_MEMBERS {
+        return Err(HouseholdError::Conflict("Household is full".to_string()));
+    }
+    repo::answer_invite(&state.db, user_id, invite_id, true)
+        .await?
+        .ok_or(HouseholdError::InviteNotFound)?;
+    info!(user_id = %user_id, household_id = %household_id, "household joined");
+    membership(state, user_id, household_id).await
+}
+
+pub async fn decline_invite(
+    state: &AppState,
+    user_id: Uuid,
+    invite_id: Uuid,
+) -> Result<(), HouseholdError> {
+    repo::answer_invite(&state.db, user_id, invite_id, false)
+        .await?
+        .ok_or(HouseholdError::InviteNotFound)?;
+    Ok(())
+}
+
+/// Owners remove members; members remove themselves to leave. The owner
+/// can't leave and deletes the household instead.
+pub async fn remove_member(
+    state: &AppState,
+    user_id: Uuid,
+    household_id: Uuid,
+    member_id: Uuid,
+) -> Result<(), HouseholdError> {
+    let household = membership(state, user_id, household_id).await?;
+    if member_id != user_id && household.role != HouseholdRole::Owner {
+        return Err(HouseholdError::NotOwner);
+    }
+    if !repo::remove_member(&state.db, household_id, member_id).await? {
+        return Err(if member_id == user_id {
+            HouseholdError::Conflict("The owner can't leave; delete the household instead".into())
+        } else {
+            HouseholdError::MemberNotFound
+        });
+    }
+    info!(user_id = %user_id, household_id = %household_id, member_id = %member_id, "household member removed");
+    Ok(())
+}
+
+pub async fn list_meals(
+    state: &AppState,
+    user_id: Uuid,
+    household_id: Uuid,
+    query: &ListMealsQuery,
+) -> Result<Vec<MealResponse>, HouseholdError> {
+    membership(state, user_id, household_id).await?;
+    Ok(meals_services::list_household_meals(state, user_id, household_id, query).await?)
+}
+
+/// Checks that `user_id` may log meals into `household_id`.
+pub async fn ensure_member(
+    state: &AppState,
+    user_id: Uuid,
+    household_id: Uuid,
+) -> Result<(), HouseholdError> {
+    if !repo::is_member(&state.db, user_id, household_id).await? {
+        return Err(HouseholdError::NotFound);
+    }
+    Ok(())
+}
+
+/// Moves one of the user's meals into a household they belong to, or back
+/// out with `None`.
+pub async fn set_meal_household(
+    state: &AppState,
+    user_id: Uuid,
+    meal_id: Uuid,
+    household_id: Option<Uuid>,
+) -> Result<Option<Uuid>, HouseholdError> {
+    if let Some(household_id) = household_id {
+        ensure_member(state, user_id, household_id).await?;
+    }
+    let meal = meals_repo::set_household(&state.db, user_id, meal_id, household_id)
+        .await?
+        .ok_or(HouseholdError::MealNotFound)?;
+    Ok(meal.household_id)
+}
@@ -0,0 +1,399 @@
+//! German and Russian translations of auth and meal messages and of push
+//! notifications, keyed by the English message as the code writes it. `{}`
+//! stands for a value that is carried over from the English message in
+//! order.
+
+use super::Locale;
+
+/// `(English, German, Russian)`.
+const MESSAGES: &[(&str, &str, &str)] = &[
+    // Auth
+    (
+        "Invalid credentials",
+        "Ungültige Anmeldedaten",
+        "Неверный email или пароль",
+    ),
+    (
+        "Invalid email",
+        "Ungültige E-Mail-Adresse",
+        "Некорректный адрес электронной почты",
+    ),
+    (
+        "Password too short",
+        "Passwort zu kurz",
+        "Слишком короткий пароль",
+    ),
+    (
+        "Email already registered",
+        "Diese E-Mail-Adresse ist bereits registriert",
+        "Этот адрес электронной почты уже зарегистрирован",
+    ),
+    (
+        "Failed to register",
+        "Registrierung fehlgeschlagen",
+        "Не удалось зарегистрироваться",
+    ),
+    (
+        "Failed to log in",
+        "Anmeldung fehlgeschlagen",
+        "Не удалось войти",
+    ),
+    (
+        "Failed to issue tokens",
+        "Tokens konnten nicht ausgestellt werden",
+        "Не удалось выдать токены",
+    ),
+    (
+        "Invalid or expired refresh token",
+        "Ungültiges oder abgelaufenes Refresh-Token",
+        "Недействительный или просроченный refresh-токен",
+    ),
+    (
+        "User not found",
+        "Benutzer nicht gefunden",
+        "Пользователь не найден",
+    ),
+    (
+        "Missing Authorization header",
+        "Authorization-Header fehlt",
+        "Отсутствует заголовок Authorization",
+    ),
+    (
+        "Invalid Authorization header",
+        "Ungültiger Authorization-Header",
+        "Некорректный заголовок Authorization",
+    ),
+    (
+        "Invalid or expired token",
+        "Ungültiges oder abgelaufenes Token",
+        "Недействительный или просроченный токен",
+    ),
+    (
+        "Access token required",
+        "Access-Token erforderlich",
+        "Требуется access-токен",
+    ),
+    (
+        "Admin access required",
+        "Administratorrechte erforderlich",
+        "Требуются права администратора",
+    ),
+    (
+        "Failed to check permissions",
+        "Berechtigungen konnten nicht geprüft werden",
+        "Не удалось проверить права доступа",
+    ),
+    // Meals
+    (
+        "Meal not found",
+        "Mahlzeit nicht gefunden",
+        "Приём пищи не найден",
+    ),
+    (
+        "Failed to list meals",
+        "Mahlzeiten konnten nicht geladen werden",
+        "Не удалось загрузить приёмы пищи",
+    ),
+    (
+        "Failed to load meal",
+        "Mahlzeit konnte nicht geladen werden",
+        "Не удалось загрузить приём пищи",
+    ),
+    (
+        "Failed to load meal status",
+        "Status der Mahlzeit konnte nicht geladen werden",
+        "Не удалось загрузить статус приёма пищи",
+    ),
+    (
+        "Failed to create meal",
+        "Mahlzeit konnte nicht erstellt werden",
+        "Не удалось создать приём пищи",
+    ),
+    (
+        "Provide images, nutrition, quick_add or a title",
+        "Bitte Bilder, nutrition, quick_add oder einen Titel angeben",
+        "Укажите изображения, nutrition, quick_add или название",
+    ),
+    (
+        "nutrition and quick_add are only accepted without images",
+        "nutrition und quick_add sind nur ohne Bilder erlaubt",
+        "nutrition и quick_add принимаются только без изображений",
+    ),
+    (
+        "No images provided",
+        "Keine Bilder angegeben",
+        "Изображения не переданы",
+    ),
+    (
+        "household_id must be a UUID",
+        "household_id muss eine UUID sein",
+        "household_id должен быть UUID",
+    ),
+    (
+        "Nutrition not found",
+        "Nährwerte nicht gefunden",
+        "Пищевая ценность не найдена",
+    ),
+    (
+        "Failed to save nutrition",
+        "Nährwerte konnten nicht gespeichert werden",
+        "Не удалось сохранить пищевую ценность",
+    ),
+    (
+        "Failed to delete nutrition",
+        "Nährwerte konnten nicht gelöscht werden",
+        "Не удалось удалить пищевую ценность",
+    ),
+    (
+        "Photo not found",
+        "Foto nicht gefunden",
+        "Фотография не найдена",
+    ),
+    (
+        "Failed to list photos",
+        "Fotos konnten nicht geladen werden",
+        "Не удалось загрузить фотографии",
+    ),
+    (
+        "Failed to delete photo",
+        "Foto konnte nicht gelöscht werden",
+        "Не удалось удалить фотографию",
+    ),
+    (
+        "Bulk operation failed",
+        "Sammelvorgang fehlgeschlagen",
+        "Не удалось выполнить групповую операцию",
+    ),
+    (
+        "Storage temporarily unavailable",
+        "Speicher vorübergehend nicht verfügbar",
+        "Хранилище временно недоступно",
+    ),
+    // Meal validation
+    (
+        "Unknown meal type: {}",
+        "Unbekannte Mahlzeitart: {}",
+        "Неизвестный тип приёма пищи: {}",
+    ),
+    (
+        "Use either nutrition or quick_add, not both",
+        "Entweder nutrition oder quick_add angeben, nicht beides",
+        "Укажите либо nutrition, либо quick_add, но не оба",
+    ),
+    (
+        "Title must be at most {} characters",
+        "Der Titel darf höchstens {} Zeichen lang sein",
+        "Название должно быть не длиннее {} символов",
+    ),
+    (
+        "Notes must be at most {} characters",
+        "Notizen dürfen höchstens {} Zeichen lang sein",
+        "Заметки должны быть не длиннее {} символов",
+    ),
+    (
+        "At least one nutrition value is required",
+        "Mindestens ein Nährwert ist erforderlich",
+        "Требуется хотя бы одно значение пищевой ценности",
+    ),
+    (
+        "{} must be between 0 and {}",
+        "{} muss zwischen 0 und {} liegen",
+        "{} должно быть от 0 до {}",
+    ),
+    (
+        "micros must be an object",
+        "micros muss ein Objekt sein",
+        "micros должно быть объектом",
+    ),
+    (
+        "No operations provided",
+        "Keine Operationen angegeben",
+        "Операции не переданы",
+    ),
+    (
+        "No meal ids provided",
+        "Keine Mahlzeit-IDs angegeben",
+        "Идентификаторы приёмов пищи не переданы",
+    ),
+    (
+        "Too many meal ids (max {} per request)",
+        "Zu viele Mahlzeit-IDs (höchstens {} pro Anfrage)",
+        "Слишком много идентификаторов приёмов пищи (не больше {} за запрос)",
+    ),
+    (
+        "Tag operation requires at least one tag",
+        "Die Tag-Operation braucht mindestens ein Tag",
+        "Для операции с тегами нужен хотя бы один тег",
+    ),
+    (
+        "Tags must be at most {} characters",
+        "Tags dürfen höchstens {} Zeichen lang sein",
+        "Теги должны быть не длиннее {} символов",
+    ),
+    // Meal images
+    (
+        "Too many images (max {} per meal)",
+        "Zu viele Bilder (höchstens {} pro Mahlzeit)",
+        "Слишком много изображений (не больше {} на приём пищи)",
+    ),
+    (
+        "Image {}: invalid base64",
+        "Bild {}: ungültiges Base64",
+        "Изображение {}: некорректный base64",
+    ),
+    (
+        "Image {}: empty image",
+        "Bild {}: leeres Bild",
+        "Изображение {}: пустое изображение",
+    ),
+    (
+        "Image {}: exceeds {} bytes",
+        "Bild {}: größer als {} Bytes",
+        "Изображение {}: больше {} байт",
+    ),
+    (
+        "Images exceed {} bytes in total",
+        "Bilder sind zusammen größer als {} Bytes",
+        "Общий размер изображений больше {} байт",
+    ),
+    (
+        "Image {}: not a supported image (jpeg, png, webp, heic)",
+        "Bild {}: kein unterstütztes Bildformat (jpeg, png, webp, heic)",
+        "Изображение {}: неподдерживаемый формат (jpeg, png, webp, heic)",
+    ),
+    (
+        "Image {}: declared {} but content is {}",
+        "Bild {}: als {} angegeben, Inhalt ist aber {}",
+        "Изображение {}: заявлен {}, но содержимое {}",
+    ),
+    // Push notifications
+    (
+        "Analysis complete",
+        "Analyse abgeschlossen",
+        "Анализ завершён",
+    ),
+    (
+        "Nutrition for \"{}\" is ready.",
+        "Nährwerte für \"{}\" sind fertig.",
+        "Пищевая ценность для «{}» готова.",
+    ),
+    (
+        "Nutrition for your meal is ready.",
+        "Die Nährwerte deiner Mahlzeit sind fertig.",
+        "Пищевая ценность вашего приёма пищи готова.",
+    ),
+    (
+        "Log your meals",
+        "Trag deine Mahlzeiten ein",
+        "Запишите приёмы пищи",
+    ),
+    (
+        "You haven't logged anything today.",
+        "Du hast heute noch nichts eingetragen.",
+        "Сегодня вы ещё ничего не записали.",
+    ),
+    (
+        "Achievement unlocked",
+        "Erfolg freigeschaltet",
+        "Достижение получено",
+    ),
+    ("New reaction", "Neue Reaktion", "Новая реакция"),
+    (
+        "Someone reacted to \"{}\".",
+        "Jemand hat auf \"{}\" reagiert.",
+        "Кто-то отреагировал на «{}».",
+    ),
+    (
+        "Someone reacted to your meal.",
+        "Jemand hat auf deine Mahlzeit reagiert.",
+        "Кто-то отреагировал на ваш приём пищи.",
+    ),
+    // Badges
+    ("7-day streak", "7-Tage-Serie", "7 дней подряд"),
+    (
+        "Logged meals 7 days in a row.",
+        "7 Tage in Folge Mahlzeiten eingetragen.",
+        "Записывали приёмы пищи 7 дней подряд.",
+    ),
+    ("100 meals", "100 Mahlzeiten", "100 приёмов пищи"),
+    (
+        "Logged 100 meals.",
+        "100 Mahlzeiten eingetragen.",
+        "Записано 100 приёмов пищи.",
+    ),
+    ("Protein streak", "Protein-Serie", "Белковая серия"),
+    (
+        "Hit the protein goal 5 days in a row.",
+        "5 Tage in Folge das Proteinziel erreicht.",
+        "Цель по белку выполнена 5 дней подряд.",
+    ),
+];
+
+pub fn translate(locale: Locale, message: &str) -> Option<String> {
+    if locale == Locale::En {
+        return None;
+    }
+    MESSAGES.iter().find_map(|(en, de, ru)| {
+        let args = placeholders(en, message)?;
+        let template = if locale == Locale::De { de } else { ru };
+        let mut args = args.into_iter();
+        let mut pieces = template.split("{}");
+        let mut out = pieces.next().unwrap_or_default().to_string();
+        for piece in pieces {
+            out.push_str(args.next().unwrap_or_default());
+            out.push_str(piece);
+        }
+        Some(out)
+    })
+}
+
+/// The values `message` fills into the `{}`s of `template`, if it matches.
+fn placeholders<'a>(template: &str, message: &'a str) -> Option<Vec<&'a str>> {
+    let mut pieces = template.split("{}");
+    let mut rest = message.strip_prefix(pieces.next().unwrap_or_default())?;
+    let mut args = Vec::new();
+    let mut pieces = pieces.peekable();
+    while let Some(piece) = pieces.next() {
+        let (arg, after) = if pieces.peek().is_none() {
+            (rest.strip_suffix(piece)?, "")
+        } else {
+            let at = rest.find(piece)?;
+            (&rest[..at], &rest[at + piece.len()..])
+        };
+        if arg.is_empty() {
+            return None;
+        }
+        args.push(arg);
+        rest = after;
+    }
+    rest.is_empty().then_some(args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fills_values_into_the_translation() {
+        assert_eq!(
+            translate(Locale::De, "protein_g must be between 0 and 1000").as_deref(),
+            Some("protein_g muss zwischen 0 und 1000 liegen")
+        );
+        assert_eq!(
+            translate(Locale::Ru, "Image 2: exceeds 10485760 bytes").as_deref(),
+            Some("Изображение 2: больше 10485760 байт")
+        );
+        assert_eq!(translate(Locale::De, "Image 2: declared"), None);
+        assert_eq!(translate(Locale::En, "Meal not found"), None);
+    }
+
+    #[test]
+    fn translations_keep_every_value() {
+        for (en, de, ru) in MESSAGES {
+            let count = en.matches("{}").count();
+            assert_eq!(de.matches("{}").count(), count, "{en}");
+            assert_eq!(ru.matches("{}").count(), count, "{en}");
+            assert!(!en.contains("{}{}"), "{en}");
+        }
+    }
+}
@@ -0,0 +1,146 @@
+//! Localized error messages. The locale is negotiated from `Accept-Language`
+//! per request and kept for the request like the request id, so
+//! [`ApiError`](crate::error::ApiError) can translate its message when it
+//! renders. Messages without a translation stay in English.
+
+mod catalog;
+
+use std::future::Future;
+
+use axum::{
+    extract::Request,
+    http::{
+        header::{ACCEPT_LANGUAGE, VARY},
+        HeaderValue,
+    },
+    middleware::Next,
+    response::Response,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "text", rename_all = "lowercase")]
+pub enum Locale {
+    #[default]
+    En,
+    De,
+    Ru,
+}
+
+impl Locale {
+    /// Language tag for `Content-Language`.
+    pub fn tag(self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::De => "de",
+            Locale::Ru => "ru",
+        }
+    }
+
+    /// Matches the primary subtag, so `de-AT` is German.
+    fn from_tag(tag: &str) -> Option<Self> {
+        let primary = tag.split('-').next().unwrap_or_default();
+        match primary.to_ascii_lowercase().as_str() {
+            "en" | "*" => Some(Locale::En),
+            "de" => Some(Locale::De),
+            "ru" => Some(Locale::Ru),
+            _ => None,
+        }
+    }
+
+    /// The supported locale the client prefers most, by quality and then
+    /// order; English when nothing matches.
+    pub fn negotiate(accept_language: Option<&HeaderValue>) -> Self {
+        let Some(header) = accept_language.and_then(|v| v.to_str().ok()) else {
+            return Locale::En;
+        };
+        let mut best: Option<(Locale, f32)> = None;
+        for range in header.split(',') {
+            let mut parts = range.split(';');
+            let Some(locale) = Locale::from_tag(parts.next().unwrap_or_default().trim()) else {
+                continue;
+            };
+            let quality = parts
+                .filter_map(|p| p.trim().strip_prefix("q="))
+                .find_map(|q| q.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+            if quality > 0.0 && best.is_none_or(|(_, q)| quality > q) {
+                best = Some((locale, quality));
+            }
+        }
+        best.map(|(locale, _)| locale).unwrap_or_default()
+    }
+}
+
+tokio::task_local! {
+    static LOCALE: Locale;
+}
+
+/// Locale of the request being handled; English outside [`negotiate`].
+pub fn current() -> Locale {
+    LOCALE.try_with(|locale| *locale).unwrap_or_default()
+}
+
+/// Runs `fut` with `locale` as the [`current`] locale.
+pub async fn scoped<F: Future>(locale: Locale, fut: F) -> F::Output {
+    LOCALE.scope(locale, fut).await
+}
+
+/// `message` in the current locale, if the catalogue has it.
+pub fn translate(message: &str) -> Option<String> {
+    catalog::translate(current(), message)
+}
+
+/// `message` in `locale`, for text sent outside a request such as pushes;
+/// falls back to `message` itself.
+pub fn translate_to(locale: Locale, message: &str) -> String {
+    catalog::translate(locale, message).unwrap_or_else(|| message.to_string())
+}
+
+/// Middleware picking the locale from `Accept-Language`. Error responses
+/// depend on it, so they get `Vary: Accept-Language`.
+pub async fn negotiate(req: Request, next: Next) -> Response {
+    let locale = Locale::negotiate(req.headers().get(ACCEPT_LANGUAGE));
+    let mut response = scoped(locale, next.run(req)).await;
+    let status = response.status();
+    if status.is_client_error() || status.is_server_error() {
+        response
+            .headers_mut()
+            .append(VARY, HeaderValue::from_name(ACCEPT_LANGUAGE));
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn negotiate(header: &'static str) -> Locale {
+        Locale::negotiate(Some(&HeaderValue::from_static(header)))
+    }
+
+    #[test]
+    fn picks_the_preferred_supported_language() {
+        assert_eq!(negotiate("de-DE,de;q=0.9,en;q=0.8"), Locale::De);
+        assert_eq!(negotiate("fr-FR, ru;q=0.5, en;q=0.4"), Locale::Ru);
+        assert_eq!(negotiate("en;q=0.3, ru-RU;q=0.7"), Locale::Ru);
+        assert_eq!(negotiate("ru;q=0, de"), Locale::De);
+    }
+
+    #[test]
+    fn falls_back_to_english() {
+        assert_eq!(Locale::negotiate(None), Locale::En);
+        assert_eq!(negotiate("fr, es;q=0.5"), Locale::En);
+        assert_eq!(negotiate("*"), Locale::En);
+    }
+
+    #[tokio::test]
+    async fn translates_in_the_current_locale_only() {
+        assert_eq!(translate("Meal not found"), None);
+        let german = scoped(Locale::De, async { translate("Meal not found") }).await;
+        assert_eq!(german.as_deref(), Some("Mahlzeit nicht gefunden"));
+        let unknown = scoped(Locale::Ru, async { translate("Something else") }).await;
+        assert_eq!(unknown, None);
+    }
+}
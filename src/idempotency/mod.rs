@@ -0,0 +1,280 @@
+//! `Idempotency-Key` support: the first response to a keyed request is stored
+//! for 24 hours and replayed when a client retries with the same key.
+
+pub mod repo;
+
+use axum::{
+    body::{to_bytes, Body, Bytes},
+    extract::{Request, State},
+    http::{header, HeaderName, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    RequestExt,
+};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::{
+    auth::jwt::AuthUser,
+    db::AppState,
+    error::{ApiError, ErrorCode},
+};
+
+pub static IDEMPOTENCY_KEY: HeaderName = HeaderName::from_static("idempotency-key");
+/// Set on responses served from a stored key instead of the handler.
+pub static IDEMPOTENT_REPLAYED: HeaderName = HeaderName::from_static("idempotent-replayed");
+
+const MAX_KEY_LEN: usize = 255;
+
+fn parse_key(header: &HeaderValue) -> Result<&str, ApiError> {
+    header
+        .to_str()
+        .ok()
+        .map(str::trim)
+        .filter(|key| !key.is_empty() && key.len() <= MAX_KEY_LEN)
+        .ok_or_else(|| {
+            ApiError::BadRequest(format!(
+                "Idempotency-Key must be 1 to {MAX_KEY_LEN} visible ASCII characters"
+            ))
+            .with_code(ErrorCode::IdempotencyKeyInvalid)
+        })
+}
+
+/// Body as fingerprinted. Multipart bodies are reduced to each part's name,
+/// filename, content type and SHA-256, in order, so a retry encoded with a new
+/// boundary still matches; anything that doesn't parse is taken as is.
+async fn fingerprint_body(content_type: Option<&HeaderValue>, body: &Bytes) -> Bytes {
+    let Some(boundary) = content_type
+        .and_then(|v| v.to_str().ok())
+        .and_then(|ct| multer::parse_boundary(ct).ok())
+    else {
+        return body.clone();
+    };
+    let chunk = Ok::<_, std::io::Error>(body.clone());
+    let mut multipart = multer::Multipart::new(futures::stream::iter([chunk]), boundary);
+    let mut parts = String::new();
+    loop {
+        let field = match multipart.next_field().await {
+            Ok(Some(field)) => field,
+            Ok(None) => return Bytes::from(parts),
+            Err(_) => return body.clone(),
+        };
+        let name = field.name().unwrap_or_default().to_string();
+        let file_name = field.file_name().unwrap_or_default().to_string();
+        let content_type = field
+            .content_type()
+            .map(ToString::to_string)
+            .unwrap_or_default();
+        let Ok(data) = field.bytes().await else {
+            return body.clone();
+        };
+        let digest = hex::encode(Sha256::digest(&data));
+        parts.push_str(&format!("{name}\t{file_name}\t{content_type}\t{digest}\n"));
+    }
+}
+
+/// Fingerprint used to reject a key reused for a different request.
+fn request_hash(method: &str, path: &str, body: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(method.as_bytes());
+    hasher.update(b" ");
+    hasher.update(path.as_bytes());
+    hasher.update(b"\n");
+    hasher.update(body);
+    hex::encode(hasher.finalize())
+}
+
+/// Route middleware replaying responses for requests carrying an
+/// `Idempotency-Key`. Keys are scoped per user; requests without the header,
+/// or without valid credentials, go straight to the handler.
+pub async fn replay(
+    State(state): State<AppState>,
+    auth: Option<AuthUser>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let (Some(header), Some(AuthUser(user_id))) = (req.headers().get(&IDEMPOTENCY_KEY), auth)
+    else {
+        return next.run(req).await;
+    };
+    let key = match parse_key(header) {
+        Ok(key) => key.to_string(),
+        Err(e) => return e.into_response(),
+    };
+
+    let (parts, body) = req.with_limited_body().into_parts();
+    // Reading fails once the route's `DefaultBodyLimit` is exceeded.
+    let Ok(bytes) = to_bytes(body, usize::MAX).await else {
+        return ApiError::PayloadTooLarge("Request body too large".to_string()).into_response();
+    };
+    let fingerprint = fingerprint_body(parts.headers.get(header::CONTENT_TYPE), &bytes).await;
+    let hash = request_hash(parts.method.as_str(), parts.uri.path(), &fingerprint);
+
+    match claim(&state, user_id, &key, &hash).await {
+        Ok(true) => {}
+        Ok(false) => return stored_response(&state, user_id, &key, &hash).await,
+        Err(e) => {
+            tracing::error!(error = %e, %user_id, "idempotency: failed to claim key");
+            return ApiError::internal(&e, "Failed to process request").into_response();
+        }
+    }
+
+    let response = next
+        .run(Request::from_parts(parts, Body::from(bytes)))
+        .await;
+    remember(&state, user_id, &key, response).await
+}
+
+async fn claim(state: &AppState, user_id: Uuid, key: &str, hash: &str) -> anyhow::Result<bool> {
+    repo::purge_expired(&state.db, user_id).await?;
+    // A request still marked in progress after the longest allowed upload
+    // has been abandoned; let the retry take over.
+    let abandon_after = state.config.http.upload_timeout_secs as i64;
+    repo::claim(&state.db, user_id, key, hash, abandon_after).await
+}
+
+/// Stores the handler's response, or frees the key after a server error so a
+/// retry gets another chance.
+async fn remember(state: &AppState, user_id: Uuid, key: &str, response: Response) -> Response {
+    let status = response.status();
+    if status.is_server_error() {
+        if let Err(e) = repo::release(&state.db, user_id, key).await {
+            tracing::warn!(error = %e, %user_id, "idempotency: failed to release key");
+        }
+        return response;
+    }
+
+    let (parts, body) = response.into_parts();
+    let bytes = match to_bytes(body, usize::MAX).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            tracing::error!(error = %e, %user_id, "idempotency: failed to read response");
+            let _ = repo::release(&state.db, user_id, key).await;
+            return ApiError::Internal("Failed to process request".to_string()).into_response();
+        }
+    };
+    let content_type = parts
+        .headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok());
+    if let Err(e) = repo::complete(
+        &state.db,
+        user_id,
+        key,
+        status.as_u16() as i16,
+        content_type,
+        &bytes,
+    )
+    .await
+    {
+        tracing::error!(error = %e, %user_id, "idempotency: failed to store response");
+    }
+    Response::from_parts(parts, Body::from(bytes))
+}
+
+fn in_progress() -> ApiError {
+    ApiError::Conflict("A request with this Idempotency-Key is still in progress".to_string())
+        .with_code(ErrorCode::IdempotencyInProgress)
+}
+
+async fn stored_response(state: &AppState, user_id: Uuid, key: &str, hash: &str) -> Response {
+    let stored = match repo::find(&state.db, user_id, key).await {
+        Ok(Some(stored)) => stored,
+        // Released between the claim and the lookup; the retry can go again.
+        Ok(None) => return in_progress().into_response(),
+        Err(e) => {
+            tracing::error!(error = %e, %user_id, "idempotency: failed to load key");
+            return ApiError::internal(&e, "Failed to process request").into_response();
+        }
+    };
+    if stored.request_hash != hash {
+        return ApiError::Unprocessable(
+            "Idempotency-Key was already used for a different request".to_string(),
+        )
+        .with_code(ErrorCode::IdempotencyKeyReused)
+        .into_response();
+    }
+    let Some(status) = stored
+        .status_code
+        .and_then(|code| StatusCode::from_u16(code as u16).ok())
+    else {
+        return in_progress().into_response();
+    };
+
+    let mut response = Response::new(Body::from(Bytes::from(
+        stored.response_body.unwrap_or_default(),
+    )));
+    *response.status_mut() = status;
+    let headers = response.headers_mut();
+    if let Some(value) = stored
+        .content_type
+        .and_then(|ct| HeaderValue::from_str(&ct).ok())
+    {
+        headers.insert(header::CONTENT_TYPE, value);
+    }
+    headers.insert(
+        IDEMPOTENT_REPLAYED.clone(),
+        HeaderValue::from_static("true"),
+    );
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_empty_and_oversized_keys() {
+        assert_eq!(
+            parse_key(&HeaderValue::from_static(" retry-1 ")).unwrap(),
+            "retry-1"
+        );
+        assert!(parse_key(&HeaderValue::from_static("  ")).is_err());
+        let long = HeaderValue::from_str(&"k".repeat(MAX_KEY_LEN + 1)).unwrap();
+        assert!(parse_key(&long).is_err());
+    }
+
+    #[test]
+    fn hash_covers_route_and_body() {
+        let base = request_hash("POST", "/meals", br#"{"title":"Soup"}"#);
+        assert_eq!(base, request_hash("POST", "/meals", br#"{"title":"Soup"}"#));
+        assert_ne!(
+            base,
+            request_hash("POST", "/meals", br#"{"title":"Salad"}"#)
+        );
+        assert_ne!(
+            base,
+            request_hash("POST", "/recipes", br#"{"title":"Soup"}"#)
+        );
+    }
+
+    fn multipart(boundary: &str, photo: &str) -> (HeaderValue, Bytes) {
+        let content_type =
+            HeaderValue::from_str(&format!("multipart/form-data; boundary={boundary}")).unwrap();
+        let body = format!(
+            "--{boundary}\r\n\
+             Content-Disposition: form-data; name=\"title\"\r\n\r\n\
+             Soup\r\n\
+             --{boundary}\r\n\
+             Content-Disposition: form-data; name=\"photos\"; filename=\"a.jpg\"\r\n\
+             Content-Type: image/jpeg\r\n\r\n\
+             {photo}\r\n\
+             --{boundary}--\r\n"
+        );
+        (content_type, Bytes::from(body))
+    }
+
+    #[tokio::test]
+    async fn multipart_fingerprints_ignore_the_boundary() {
+        let (ct, body) = multipart("first", "jpeg-bytes");
+        let base = fingerprint_body(Some(&ct), &body).await;
+        let (ct, body) = multipart("second-boundary", "jpeg-bytes");
+        assert_eq!(base, fingerprint_body(Some(&ct), &body).await);
+        let (ct, body) = multipart("first", "other-bytes");
+        assert_ne!(base, fingerprint_body(Some(&ct), &body).await);
+
+        let json = Bytes::from_static(br#"{"title":"Soup"}"#);
+        let ct = HeaderValue::from_static("application/json");
+        assert_eq!(fingerprint_body(Some(&ct), &json).await, json);
+    }
+}
@@ -0,0 +1,129 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// Hours a stored response is replayed for.
+pub const KEY_TTL_HOURS: i32 = 24;
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct StoredKey {
+    pub request_hash: String,
+    pub status_code: Option<i16>,
+    pub content_type: Option<String>,
+    pub response_body: Option<Vec<u8>>,
+}
+
+/// Claims `key` for a new request. Expired keys, and keys whose first request
+/// has been in progress for longer than `abandon_after_secs`, are taken over.
+/// Returns `false` when another request holds the key.
+pub async fn claim(
+    db: &PgPool,
+    user_id: Uuid,
+    key: &str,
+    request_hash: &str,
+    abandon_after_secs: i64,
+) -> anyhow::Result<bool> {
+    let claimed = sqlx::query_scalar::<_, String>(
+        r#"
+        INSERT INTO idempotency_keys (user_id, key, request_hash)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (user_id, key) DO UPDATE
+        SET request_hash = EXCLUDED.request_hash,
+            status_code = NULL,
+            content_type = NULL,
+            response_body = NULL,
+            created_at = NOW()
+        WHERE idempotency_keys.created_at < NOW() - make_interval(hours => $4)
+           OR (idempotency_keys.status_code IS NULL
+               AND idempotency_keys.created_at < NOW() - make_interval(secs => $5))
+        RETURNING key
+        "#,
+    )
+    .bind(user_id)
+    .bind(key)
+    .bind(request_hash)
+    .bind(KEY_TTL_HOURS)
+    .bind(abandon_after_secs as f64)
+    .fetch_optional(db)
+    .await?;
+    Ok(claimed.is_some())
+}
+
+pub async fn find(db: &PgPool, user_id: Uuid, key: &str) -> anyhow::Result<Option<StoredKey>> {
+    let stored = sqlx::query_as::<_, StoredKey>(
+        r#"
+        SELECT request_hash, status_code, content_type, response_body
+        FROM idempotency_keys
+        WHERE user_id = $1 AND key = $2
+        "#,
+    )
+    .bind(user_id)
+    .bind(key)
+    .fetch_optional(db)
+    .await?;
+    Ok(stored)
+}
+
+pub async fn complete(
+    db: &PgPool,
+    user_id: Uuid,
+    key: &str,
+    status_code: i16,
+    content_type: Option<&str>,
+    body: &[u8],
+) -> anyhow::Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE idempotency_keys
+        SET status_code = $3, content_type = $4, response_body = $5
+        WHERE user_id = $1 AND key = $2
+        "#,
+    )
+    .bind(user_id)
+    .bind(key)
+    .bind(status_code)
+    .bind(content_type)
+    .bind(body)
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+/// Frees a key whose request failed in a way worth retrying.
+pub async fn release(db: &PgPool, user_id: Uuid, key: &str) -> anyhow::Result<()> {
+    sqlx::query(r#"DELETE FROM idempotency_keys WHERE user_id = $1 AND key = $2"#)
+        .bind(user_id)
+        .bind(key)
+        .execute(db)
+        .await?;
+    Ok(())
+}
+
+/// Drops the user's expired keys; run on each claim so the table stays small
+/// between [`purge_all_expired`] runs.
+pub async fn purge_expired(db: &PgPool, user_id: Uuid) -> anyhow::Result<()> {
+    sqlx::query(
+        r#"
+        DELETE FROM idempotency_keys
+        WHERE user_id = $1 AND created_at < NOW() - make_interval(hours => $2)
+        "#,
+    )
+    .bind(user_id)
+    .bind(KEY_TTL_HOURS)
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+/// Drops every expired key, including those of users who never came back.
+pub async fn purge_all_expired(db: &PgPool) -> anyhow::Result<u64> {
+    let result = sqlx::query(
+        r#"
+        DELETE FROM idempotency_keys
+        WHERE created_at < NOW() - make_interval(hours => $1)
+        "#,
+    )
+    .bind(KEY_TTL_HOURS)
+    .execute(db)
+    .await?;
+    Ok(result.rows_affected())
+}
@@ -0,0 +1,45 @@
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// A photo reference the client can fetch directly until `expires_at`.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PresignedPhoto {
+    pub photo_id: Uuid,
+    pub url: String,
+    /// Untranscoded upload (e.g. the HEIC behind a JPEG), when kept.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub original_url: Option<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub expires_at: OffsetDateTime,
+}
+
+/// Image as sent by the client inside a JSON meal payload.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ImageInput {
+    pub content_type: String,
+    /// Standard base64 (no data-URL prefix).
+    pub data: String,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub taken_at: Option<OffsetDateTime>,
+}
+
+/// Decoded, validated image ready to be stored.
+#[derive(Debug, Clone)]
+pub struct NormalizedImage {
+    pub content_type: String,
+    pub bytes: Vec<u8>,
+    pub taken_at: Option<OffsetDateTime>,
+}
+
+impl NormalizedImage {
+    pub fn extension(&self) -> &'static str {
+        match self.content_type.as_str() {
+            "image/png" => "png",
+            "image/webp" => "webp",
+            "image/heic" | "image/heif" => "heic",
+            _ => "jpg",
+        }
+    }
+}
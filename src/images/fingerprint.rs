@@ -0,0 +1,140 @@
+//! Perceptual fingerprints for spotting near-identical photos. An external
+//! tool (e.g. ImageMagick) shrinks the image to a 9×8 grayscale bitmap; the
+//! fingerprint is its difference hash, one bit per horizontally adjacent
+//! pixel pair, so re-encoded, resized or slightly cropped copies of a photo
+//! stay within a few bits of each other.
+
+use std::sync::Arc;
+
+use anyhow::Context;
+use tokio::{process::Command, sync::Semaphore};
+use uuid::Uuid;
+
+use crate::{config::DuplicatesConfig, images::transcode::build_command};
+
+const WIDTH: usize = 9;
+const HEIGHT: usize = 8;
+
+/// Difference hash of a 9×8 8-bit grayscale bitmap, row by row; `None` if
+/// `gray` isn't exactly 72 bytes.
+pub fn dhash(gray: &[u8]) -> Option<i64> {
+    if gray.len() != WIDTH * HEIGHT {
+        return None;
+    }
+    let bits = gray
+        .chunks_exact(WIDTH)
+        .flat_map(|row| row.windows(2).map(|pair| pair[0] > pair[1]))
+        .fold(0u64, |hash, brighter| (hash << 1) | u64::from(brighter));
+    // Stored as BIGINT; only the bit pattern matters.
+    Some(bits as i64)
+}
+
+/// Number of differing bits between two fingerprints.
+pub fn distance(a: i64, b: i64) -> u32 {
+    (a ^ b).count_ones()
+}
+
+/// Runs fingerprint commands for every request, at most
+/// `DUPLICATE_FINGERPRINT_CONCURRENCY` at a time; the others wait their turn.
+#[derive(Clone)]
+pub struct Fingerprinter {
+    permits: Arc<Semaphore>,
+}
+
+impl Fingerprinter {
+    pub fn from_config(config: &DuplicatesConfig) -> Self {
+        Self {
+            permits: Arc::new(Semaphore::new(config.fingerprint_concurrency)),
+        }
+    }
+
+    /// [`fingerprint`] once a slot is free.
+    pub async fn fingerprint(
+        &self,
+        template: &str,
+        image: &[u8],
+        extension: &str,
+    ) -> anyhow::Result<i64> {
+        let _permit = self.permits.acquire().await?;
+        fingerprint(template, image, extension).await
+    }
+}
+
+impl Default for Fingerprinter {
+    fn default() -> Self {
+        Self::from_config(&DuplicatesConfig::default())
+    }
+}
+
+/// Runs `template` (with `{input}`/`{output}` placeholders) to reduce the
+/// image to raw 9×8 grayscale bytes and hashes them.
+async fn fingerprint(template: &str, image: &[u8], extension: &str) -> anyhow::Result<i64> {
+    let dir = std::env::temp_dir();
+    let id = Uuid::new_v4();
+    let input = dir.join(format!("mealmind-{}.{}", id, extension));
+    let output = dir.join(format!("mealmind-{}.gray", id));
+
+    let result = async {
+        tokio::fs::write(&input, image)
+            .await
+            .context("write fingerprint temp file")?;
+        let (program, args) = build_command(
+            template,
+            &input.to_string_lossy(),
+            &output.to_string_lossy(),
+        )
+        .context("empty fingerprint command")?;
+        let status = Command::new(&program)
+            .args(&args)
+            .kill_on_drop(true)
+            .status()
+            .await
+            .with_context(|| format!("spawn {}", program))?;
+        if !status.success() {
+            anyhow::bail!("{} exited with {}", program, status);
+        }
+        let gray = tokio::fs::read(&output)
+            .await
+            .context("read fingerprint bitmap")?;
+        dhash(&gray).with_context(|| {
+            format!(
+                "expected a {WIDTH}x{HEIGHT} grayscale bitmap, got {} bytes",
+                gray.len()
+            )
+        })
+    }
+    .await;
+
+    let _ = tokio::fs::remove_file(&input).await;
+    let _ = tokio::fs::remove_file(&output).await;
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A left-to-right gradient with `noise` added to every pixel.
+    fn gradient(noise: impl Fn(usize) -> u8) -> Vec<u8> {
+        (0..WIDTH * HEIGHT)
+            .map(|i| (255 - (i % WIDTH) as u8 * 20).saturating_sub(noise(i)))
+            .collect()
+    }
+
+    #[test]
+    fn hashes_brightness_steps() {
+        // Every pixel is brighter than its right neighbour.
+        assert_eq!(dhash(&gradient(|_| 0)), Some(-1));
+        assert_eq!(dhash(&[7; 72]), Some(0));
+        assert_eq!(dhash(&[0; 71]), None);
+    }
+
+    #[test]
+    fn near_copies_stay_close() {
+        let original = dhash(&gradient(|_| 0)).unwrap();
+        let noisy = dhash(&gradient(|i| (i % 3) as u8)).unwrap();
+        let flipped: Vec<u8> = gradient(|_| 0).into_iter().rev().collect();
+        assert!(distance(original, noisy) <= 4);
+        assert_eq!(distance(original, dhash(&flipped).unwrap()), 64);
+    }
+}
@@ -0,0 +1,5 @@
+pub mod dto;
+pub mod fingerprint;
+pub mod services;
+pub mod sniff;
+pub mod transcode;
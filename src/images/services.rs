@@ -0,0 +1,741 @@
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use axum::http::StatusCode;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use futures::future::{join_all, try_join_all};
+use sha2::{Digest, Sha256};
+use sqlx::PgConnection;
+use time::OffsetDateTime;
+use tokio::sync::Semaphore;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::{
+    cache::keys,
+    config::{TranscodeConfig, UploadConfig},
+    db::AppState,
+    error::ErrorCode,
+    flags::Flag,
+    images::{
+        dto::{ImageInput, NormalizedImage, PresignedPhoto},
+        sniff::{self, ImageFormat},
+        transcode,
+    },
+    photos::repo::{self as photos_repo, NewPhoto, Photo},
+    retention::repo as retention_repo,
+    storage::{ObjectStream, StorageClient},
+};
+
+pub const MAX_IMAGES_PER_MEAL: usize = 10;
+/// Upper bound on simultaneous storage calls issued for a single request.
+pub const STORAGE_CONCURRENCY: usize = 4;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ImageError {
+    #[error("Too many images (max {max} per meal)")]
+    TooMany { max: usize },
+    #[error("Image {index}: invalid base64")]
+    InvalidBase64 { index: usize },
+    #[error("Image {index}: empty image")]
+    Empty { index: usize },
+    #[error("Image {index}: exceeds {max} bytes")]
+    TooLarge { index: usize, max: usize },
+    #[error("Images exceed {max} bytes in total")]
+    RequestTooLarge { max: usize },
+    #[error("Image {index}: not a supported image (jpeg, png, webp, heic)")]
+    NotAnImage { index: usize },
+    #[error("Image {index}: declared {declared} but content is {detected}")]
+    TypeMismatch {
+        index: usize,
+        declared: String,
+        detected: &'static str,
+    },
+    /// A validated image ended up without a photo to store; a bug on our
+    /// side rather than a bad upload.
+    #[error("Image {index}: failed to store")]
+    NotStored { index: usize },
+}
+
+impl ImageError {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            ImageError::TooMany { .. }
+            | ImageError::InvalidBase64 { .. }
+            | ImageError::Empty { .. } => StatusCode::BAD_REQUEST,
+            ImageError::TooLarge { .. } | ImageError::RequestTooLarge { .. } => {
+                StatusCode::PAYLOAD_TOO_LARGE
+            }
+            ImageError::NotAnImage { .. } | ImageError::TypeMismatch { .. } => {
+                StatusCode::UNPROCESSABLE_ENTITY
+            }
+            ImageError::NotStored { .. } => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            ImageError::TooLarge { .. } | ImageError::RequestTooLarge { .. } => {
+                ErrorCode::UploadTooLarge
+            }
+            ImageError::TooMany { .. } => ErrorCode::UploadTooManyImages,
+            ImageError::InvalidBase64 { .. }
+            | ImageError::Empty { .. }
+            | ImageError::NotAnImage { .. }
+            | ImageError::TypeMismatch { .. } => ErrorCode::UploadInvalidImage,
+            ImageError::NotStored { .. } => ErrorCode::Internal,
+        }
+    }
+}
+
+/// Validates a single image against its sniffed format; `index` is only used
+/// in error messages.
+pub fn normalize_image(
+    index: usize,
+    content_type: &str,
+    bytes: Vec<u8>,
+    taken_at: Option<OffsetDateTime>,
+    limits: &UploadConfig,
+) -> Result<NormalizedImage, ImageError> {
+    if bytes.is_empty() {
+        return Err(ImageError::Empty { index });
+    }
+    if bytes.len() > limits.max_image_bytes {
+        return Err(ImageError::TooLarge {
+            index,
+            max: limits.max_image_bytes,
+        });
+    }
+    let format = sniff::detect(&bytes).ok_or(ImageError::NotAnImage { index })?;
+    let declared = content_type.trim().to_lowercase();
+    if !format.matches_content_type(&declared) {
+        return Err(ImageError::TypeMismatch {
+            index,
+            declared,
+            detected: format.content_type(),
+        });
+    }
+    Ok(NormalizedImage {
+        content_type: format.content_type().to_string(),
+        bytes,
+        taken_at,
+    })
+}
+
+/// Decodes and validates client-supplied base64 images.
+pub fn normalize_images(
+    inputs: Vec<ImageInput>,
+    limits: &UploadConfig,
+) -> Result<Vec<NormalizedImage>, ImageError> {
+    if inputs.len() > MAX_IMAGES_PER_MEAL {
+        return Err(ImageError::TooMany {
+            max: MAX_IMAGES_PER_MEAL,
+        });
+    }
+    let mut total = 0usize;
+    inputs
+        .into_iter()
+        .enumerate()
+        .map(|(index, input)| {
+            let bytes = STANDARD
+                .decode(input.data.trim())
+                .map_err(|_| ImageError::InvalidBase64 { index })?;
+            total += bytes.len();
+            if total > limits.max_request_bytes {
+                return Err(ImageError::RequestTooLarge {
+                    max: limits.max_request_bytes,
+                });
+            }
+            normalize_image(index, &input.content_type, bytes, input.taken_at, limits)
+        })
+        .collect()
+}
+
+/// Presigns a GET URL valid for `ttl` for each photo, preserving input
+/// order.
+pub async fn presign_many(
+    storage: &dyn StorageClient,
+    photos: &[Photo],
+    ttl: Duration,
+) -> anyhow::Result<Vec<PresignedPhoto>> {
+    let expires_at = OffsetDateTime::now_utc() + ttl;
+    let limit = Semaphore::new(STORAGE_CONCURRENCY);
+    try_join_all(photos.iter().map(|photo| {
+        let limit = &limit;
+        async move {
+            let _permit = limit.acquire().await?;
+            let url = storage.presign_get(&photo.s3_key, ttl).await?;
+            let original_url = match &photo.original_s3_key {
+                Some(key) => Some(storage.presign_get(key, ttl).await?),
+                None => None,
+            };
+            Ok::<_, anyhow::Error>(PresignedPhoto {
+                photo_id: photo.id,
+                url,
+                original_url,
+                expires_at,
+            })
+        }
+    }))
+    .await
+}
+
+/// [`presign_many`] through the cache (this instance's memory without
+/// Redis), so repeated reads hand out the same
+/// URLs instead of signing new ones until only
+/// [`PhotoUrlConfig::refresh_margin_secs`] of their validity is left.
+///
+/// [`PhotoUrlConfig::refresh_margin_secs`]: crate::config::PhotoUrlConfig::refresh_margin_secs
+pub async fn presign_cached(
+    state: &AppState,
+    photos: &[Photo],
+) -> anyhow::Result<Vec<PresignedPhoto>> {
+    let cache_keys: Vec<String> = photos.iter().map(|p| keys::photo_url(p.id)).collect();
+    let cache = state.cache.or_local();
+    let cached = cache.get_many::<PresignedPhoto>(&cache_keys).await;
+    let missing: Vec<Photo> = photos
+        .iter()
+        .zip(&cached)
+        .filter(|(_, hit)| hit.is_none())
+        .map(|(photo, _)| photo.clone())
+        .collect();
+    if missing.is_empty() {
+        return Ok(cached.into_iter().flatten().collect());
+    }
+
+    let config = &state.config.photo_urls;
+    let fresh = presign_many(state.storage.as_ref(), &missing, config.ttl()).await?;
+    let entries: Vec<(String, &PresignedPhoto)> = fresh
+        .iter()
+        .map(|p| (keys::photo_url(p.photo_id), p))
+        .collect();
+    cache.put_many(&entries, config.cache_ttl()).await;
+
+    let mut fresh = fresh.into_iter();
+    Ok(cached
+        .into_iter()
+        .map(|hit| hit.unwrap_or_else(|| fresh.next().expect("one URL per missing photo")))
+        .collect())
+}
+
+pub fn object_key(user_id: Uuid, meal_id: Uuid, photo_id: Uuid, extension: &str) -> String {
+    format!(
+        "users/{}/meals/{}/{}.{}",
+        user_id, meal_id, photo_id, extension
+    )
+}
+
+/// Returns the image to store as the photo plus, when configured, the
+/// original to keep next to it. Transcoding failures fall back to storing
+/// the original as-is.
+async fn prepare_for_storage(
+    config: &TranscodeConfig,
+    image: NormalizedImage,
+) -> (NormalizedImage, Option<NormalizedImage>) {
+    let Some(command) = config.heic_command.as_deref() else {
+        return (image, None);
+    };
+    if image.content_type != ImageFormat::Heic.content_type() {
+        return (image, None);
+    }
+    match transcode::heic_to_jpeg(command, &image.bytes).await {
+        Ok(jpeg) => {
+            let converted = NormalizedImage {
+                content_type: ImageFormat::Jpeg.content_type().to_string(),
+                bytes: jpeg,
+                taken_at: image.taken_at,
+            };
+            (converted, config.keep_original.then_some(image))
+        }
+        Err(e) => {
+            warn!(error = %e, "heic transcode failed; storing original");
+            (image, None)
+        }
+    }
+}
+
+/// The HEIC → JPEG converter to run on `user_id`'s photos, unless
+/// transcoding is off for them.
+pub fn heic_command(state: &AppState, user_id: Uuid) -> Option<&str> {
+    if !state.flags.is_enabled(Flag::HeicTranscode, user_id) {
+        return None;
+    }
+    state.config.transcode.heic_command.as_deref()
+}
+
+/// Hex SHA-256 of the bytes as uploaded (before any transcoding).
+pub fn content_hash(bytes: &[u8]) -> String {
+    hex::encode(Sha256::digest(bytes))
+}
+
+/// Copy of `source` recorded as a new photo that shares its stored object.
+fn reuse_object(source: &NewPhoto, taken_at: Option<OffsetDateTime>) -> NewPhoto {
+    NewPhoto {
+        id: Uuid::new_v4(),
+        taken_at,
+        ..source.clone()
+    }
+}
+
+/// Photos uploaded for a meal that doesn't exist yet. `new_keys` are the
+/// objects this upload wrote, to delete again if the meal is never created.
+pub struct StagedPhotos {
+    pub photos: Vec<NewPhoto>,
+    pub new_keys: Vec<String>,
+}
+
+/// Uploads all images of a new meal to storage in parallel, ready to be
+/// inserted together with the meal. Bytes the user already uploaded (same
+/// SHA-256) reuse the existing object instead of being stored again; the
+/// photos reused are locked on `conn`, so insert the staged rows in that same
+/// transaction or a concurrent delete may release their objects. If any
+/// upload fails, the objects already written are deleted.
+pub async fn upload_images(
+    state: &AppState,
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    meal_id: Uuid,
+    images: Vec<NormalizedImage>,
+) -> anyhow::Result<StagedPhotos> {
+    let hashes: Vec<String> = images.iter().map(|i| content_hash(&i.bytes)).collect();
+    let fingerprints = fingerprint_images(state, &images).await;
+    let existing: HashMap<String, NewPhoto> = photos_repo::find_by_hashes(conn, user_id, &hashes)
+        .await?
+        .into_iter()
+        .filter_map(|photo| Some((photo.content_hash.clone()?, NewPhoto::from(photo))))
+        .collect();
+
+    // Only the first occurrence of each unknown hash is uploaded.
+    let mut first_seen: HashMap<&str, usize> = HashMap::new();
+    let mut taken_at = Vec::with_capacity(images.len());
+    let mut to_upload = Vec::new();
+    for (index, (image, hash)) in images.into_iter().zip(&hashes).enumerate() {
+        taken_at.push(image.taken_at);
+        if !existing.contains_key(hash) && !first_seen.contains_key(hash.as_str()) {
+            first_seen.insert(hash, index);
+            to_upload.push((index, hash.clone(), image));
+        }
+    }
+
+    let transcode_off = TranscodeConfig::default();
+    let transcode = if state.flags.is_enabled(Flag::HeicTranscode, user_id) {
+        &state.config.transcode
+    } else {
+        &transcode_off
+    };
+    let mut uploaded = store_images(&state.storage, transcode, user_id, meal_id, to_upload).await?;
+    let new_keys: Vec<String> = uploaded
+        .values()
+        .flat_map(|photo| {
+            std::iter::once(photo.s3_key.clone()).chain(photo.original_s3_key.clone())
+        })
+        .collect();
+
+    let mut photos = Vec::with_capacity(hashes.len());
+    for (index, hash) in hashes.iter().enumerate() {
+        let photo = match (existing.get(hash), first_seen.get(hash.as_str())) {
+            (Some(source), _) => Some(reuse_object(source, taken_at[index])),
+            (None, Some(&first)) if first == index => uploaded.remove(&index),
+            (None, Some(&first)) => photos
+                .get(first)
+                .map(|photo| reuse_object(photo, taken_at[index])),
+            (None, None) => None,
+        };
+        let Some(photo) = photo else {
+            discard_objects(&state.storage, &new_keys).await;
+            return Err(ImageError::NotStored { index }.into());
+        };
+        photos.push(photo);
+    }
+    for (photo, fingerprint) in photos.iter_mut().zip(fingerprints) {
+        photo.perceptual_hash = fingerprint;
+    }
+    let reused = photos.len() - first_seen.len();
+    if reused > 0 {
+        info!(user_id = %user_id, meal_id = %meal_id, reused, "linked duplicate photos to existing objects");
+    }
+    Ok(StagedPhotos { photos, new_keys })
+}
+
+/// Perceptual fingerprints of `images` when duplicate detection is on. A
+/// photo that can't be fingerprinted is simply never flagged.
+async fn fingerprint_images(state: &AppState, images: &[NormalizedImage]) -> Vec<Option<i64>> {
+    let Some(command) = state.config.duplicates.fingerprint_command.as_deref() else {
+        return vec![None; images.len()];
+    };
+    join_all(images.iter().map(|image| async move {
+        state
+            .fingerprinter
+            .fingerprint(command, &image.bytes, image.extension())
+            .await
+            .inspect_err(|e| warn!(error = %e, "failed to fingerprint photo"))
+            .ok()
+    }))
+    .await
+}
+
+/// Photos near-identical to one logged recently, rejected because
+/// `DUPLICATE_BLOCK` is on.
+#[derive(Debug, Clone, Copy, thiserror::Error)]
+#[error("A near-identical photo was logged recently")]
+pub struct DuplicatePhoto {
+    /// The meal with the earlier photo.
+    pub meal_id: Uuid,
+}
+
+/// The meal of a photo the user logged within `DUPLICATE_WINDOW_HOURS` that
+/// looks like one of `fingerprints`. Best effort: a failed lookup flags
+/// nothing.
+pub async fn find_possible_duplicate(
+    state: &AppState,
+    user_id: Uuid,
+    fingerprints: impl IntoIterator<Item = Option<i64>>,
+) -> Option<Uuid> {
+    let fingerprints: Vec<i64> = fingerprints.into_iter().flatten().collect();
+    if fingerprints.is_empty() {
+        return None;
+    }
+    let config = &state.config.duplicates;
+    photos_repo::find_similar_recent(
+        &state.db,
+        user_id,
+        &fingerprints,
+        config.window_hours,
+        config.max_distance,
+    )
+    .await
+    .inspect_err(|e| warn!(error = %e, user_id = %user_id, "duplicate photo lookup failed"))
+    .ok()
+    .flatten()
+}
+
+/// Writes each image (and its kept original) under `meal_id`, keyed by its
+/// position in the request. All-or-nothing: on the first failure every
+/// object written by the other uploads is deleted before returning the error.
+async fn store_images(
+    storage: &Arc<dyn StorageClient>,
+    transcode: &TranscodeConfig,
+    user_id: Uuid,
+    meal_id: Uuid,
+    to_upload: Vec<(usize, String, NormalizedImage)>,
+) -> anyhow::Result<HashMap<usize, NewPhoto>> {
+    let limit = Arc::new(Semaphore::new(STORAGE_CONCURRENCY));
+    let uploads = to_upload.into_iter().map(|(index, hash, image)| {
+        let limit = limit.clone();
+        async move {
+            let mut written = Vec::new();
+            let result = async {
+                let photo_id = Uuid::new_v4();
+                let _permit = limit.acquire().await?;
+                let (image, original) = prepare_for_storage(transcode, image).await;
+
+                let original_s3_key = match original {
+                    Some(original) => {
+                        let key = object_key(user_id, meal_id, photo_id, original.extension());
+                        storage
+                            .put_object(&key, original.bytes, &original.content_type)
+                            .await?;
+                        written.push(key.clone());
+                        Some(key)
+                    }
+                    None => None,
+                };
+                let (width, height) = sniff::detect(&image.bytes)
+                    .and_then(|format| sniff::dimensions(format, &image.bytes))
+                    .map(|(w, h)| (i32::try_from(w).ok(), i32::try_from(h).ok()))
+                    .unwrap_or_default();
+                let size_bytes = image.bytes.len() as i64;
+                let key = object_key(user_id, meal_id, photo_id, image.extension());
+                storage
+                    .put_object(&key, image.bytes, &image.content_type)
+                    .await?;
+                written.push(key.clone());
+                Ok::<_, anyhow::Error>(NewPhoto {
+                    id: photo_id,
+                    s3_key: key,
+                    original_s3_key,
+                    content_type: image.content_type,
+                    size_bytes,
+                    width,
+                    height,
+                    content_hash: hash,
+                    taken_at: image.taken_at,
+                    perceptual_hash: None,
+                })
+            }
+            .await;
+            (index, written, result)
+        }
+    });
+
+    let mut uploaded = HashMap::new();
+    let mut written = Vec::new();
+    let mut failure = None;
+    for (index, keys, result) in join_all(uploads).await {
+        written.extend(keys);
+        match result {
+            Ok(photo) => {
+                uploaded.insert(index, photo);
+            }
+            Err(e) => {
+                failure.get_or_insert(e);
+            }
+        }
+    }
+    if let Some(e) = failure {
+        discard_objects(storage, &written).await;
+        return Err(e);
+    }
+    Ok(uploaded)
+}
+
+/// Deletes objects written for a meal that was never created. Unlike
+/// [`release_objects`] there's no reference check: no row points at them.
+pub async fn discard_objects(storage: &Arc<dyn StorageClient>, keys: &[String]) {
+    for key in keys {
+        if let Err(e) = storage.delete_object(key).await {
+            warn!(error = %e, key = %key, "failed to delete orphaned photo object");
+        }
+    }
+}
+
+/// Removes a meal photo row and its stored object. Returns `false` when the
+/// photo doesn't exist or isn't owned by `user_id`.
+pub async fn delete_meal_photo(
+    state: &AppState,
+    user_id: Uuid,
+    meal_id: Uuid,
+    photo_id: Uuid,
+) -> anyhow::Result<bool> {
+    let mut tx = state.begin_as(user_id).await?;
+    let deleted = photos_repo::delete_for_owner(&mut *tx, user_id, meal_id, photo_id).await?;
+    tx.commit().await?;
+    let Some((key, original_key)) = deleted else {
+        return Ok(false);
+    };
+    state
+        .cache
+        .or_local()
+        .invalidate(&[keys::photo_url(photo_id)])
+        .await;
+    release_objects(state, std::iter::once(key).chain(original_key)).await?;
+    Ok(true)
+}
+
+/// Deletes stored objects whose last photo row is gone. The rows are gone
+/// either way; a failed object delete only leaves an orphan.
+pub async fn release_objects(
+    state: &AppState,
+    keys: impl IntoIterator<Item = String>,
+) -> anyhow::Result<()> {
+    for key in keys {
+        if photos_repo::key_in_use(&state.db, &key).await?
+            || retention_repo::key_archived(&state.db, &key).await?
+        {
+            // Still referenced by a deduplicated copy, a template or an
+            // archived meal.
+            continue;
+        }
+        if let Err(e) = state.storage.delete_object(&key).await {
+            warn!(error = %e, key = %key, "failed to delete photo object");
+        }
+    }
+    Ok(())
+}
+
+/// Opens the stored object of a photo owned by `user_id`. `None` covers both
+/// an unknown photo and a row whose object is missing from storage.
+pub async fn open_photo_content(
+    state: &AppState,
+    user_id: Uuid,
+    photo_id: Uuid,
+) -> anyhow::Result<Option<ObjectStream>> {
+    let Some(photo) = photos_repo::find_for_owner(&state.db, user_id, photo_id).await? else {
+        return Ok(None);
+    };
+    let object = state.storage.get_object_stream(&photo.s3_key).await?;
+    if object.is_none() {
+        warn!(photo_id = %photo_id, key = %photo.s3_key, "photo object missing from storage");
+    }
+    Ok(object)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{CompletedPart, StorageError, StorageResult};
+
+    const JPEG: &[u8] = &[0xFF, 0xD8, 0xFF, 0xE0, b'h', b'i'];
+    const PNG: &[u8] = b"\x89PNG\r\n\x1a\n\0\0";
+
+    fn input(content_type: &str, data: &[u8]) -> ImageInput {
+        ImageInput {
+            content_type: content_type.into(),
+            data: STANDARD.encode(data),
+            taken_at: None,
+        }
+    }
+
+    #[test]
+    fn normalize_accepts_matching_types() {
+        let images = normalize_images(
+            vec![input("image/JPG", JPEG), input("image/png", PNG)],
+            &UploadConfig::default(),
+        )
+        .expect("valid images");
+        assert_eq!(images[0].content_type, "image/jpeg");
+        assert_eq!(images[0].extension(), "jpg");
+        assert_eq!(images[1].extension(), "png");
+    }
+
+    #[test]
+    fn normalize_rejects_bad_input() {
+        let limits = UploadConfig::default();
+        let err = normalize_images(vec![input("image/png", b"plain text")], &limits).unwrap_err();
+        assert_eq!(err.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let err = normalize_images(vec![input("image/png", JPEG)], &limits).unwrap_err();
+        assert!(matches!(err, ImageError::TypeMismatch { .. }));
+        assert!(normalize_images(vec![input("image/png", b"")], &limits).is_err());
+        let bad_b64 = ImageInput {
+            content_type: "image/png".into(),
+            data: "***".into(),
+            taken_at: None,
+        };
+        assert!(normalize_images(vec![bad_b64], &limits).is_err());
+    }
+
+    #[test]
+    fn normalize_enforces_size_limits() {
+        let limits = UploadConfig {
+            max_image_bytes: 8,
+            max_request_bytes: 10,
+            ..UploadConfig::default()
+        };
+        let err = normalize_images(vec![input("image/png", b"\x89PNG\r\n\x1a\n\0")], &limits)
+            .unwrap_err();
+        assert!(matches!(err, ImageError::TooLarge { .. }));
+        let err = normalize_images(
+            vec![input("image/jpeg", JPEG), input("image/jpeg", JPEG)],
+            &limits,
+        )
+        .unwrap_err();
+        assert_eq!(err.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[tokio::test]
+    async fn presign_many_preserves_order() {
+        let storage = crate::storage::FakeStorage::default();
+        let photos: Vec<Photo> = (0..6)
+            .map(|i| Photo {
+                id: Uuid::new_v4(),
+                user_id: Uuid::nil(),
+                meal_id: None,
+                s3_key: format!("k{}", i),
+                original_s3_key: None,
+                content_type: Some("image/jpeg".into()),
+                size_bytes: Some(6),
+                width: None,
+                height: None,
+                content_hash: None,
+                taken_at: None,
+                status: "uploaded".into(),
+                failure_reason: None,
+                created_at: OffsetDateTime::now_utc(),
+            })
+            .collect();
+        let urls = presign_many(&storage, &photos, Duration::from_secs(60))
+            .await
+            .expect("presign");
+        for (photo, url) in photos.iter().zip(&urls) {
+            assert_eq!(photo.id, url.photo_id);
+            assert!(url.url.contains(&photo.s3_key));
+        }
+    }
+
+    /// Keeps track of stored keys and refuses to store an image whose bytes
+    /// are `broken`.
+    #[derive(Default)]
+    struct Tracking {
+        keys: std::sync::Mutex<std::collections::HashSet<String>>,
+    }
+
+    #[axum::async_trait]
+    impl StorageClient for Tracking {
+        async fn put_object(&self, key: &str, bytes: Vec<u8>, _: &str) -> StorageResult<()> {
+            if bytes == b"broken" {
+                return Err(StorageError::Other("500".into()));
+            }
+            self.keys.lock().unwrap().insert(key.to_string());
+            Ok(())
+        }
+
+        async fn delete_object(&self, key: &str) -> StorageResult<()> {
+            self.keys.lock().unwrap().remove(key);
+            Ok(())
+        }
+
+        async fn get_object_stream(&self, _: &str) -> StorageResult<Option<ObjectStream>> {
+            Ok(None)
+        }
+
+        async fn presign_get(&self, key: &str, _: Duration) -> StorageResult<String> {
+            Ok(key.to_string())
+        }
+
+        async fn create_multipart(&self, _: &str, _: &str) -> StorageResult<String> {
+            Ok("upload".into())
+        }
+
+        async fn upload_part(&self, _: &str, _: &str, _: i32, _: Vec<u8>) -> StorageResult<String> {
+            Ok("etag".into())
+        }
+
+        async fn complete_multipart(
+            &self,
+            _: &str,
+            _: &str,
+            _: &[CompletedPart],
+        ) -> StorageResult<()> {
+            Ok(())
+        }
+
+        async fn abort_multipart(&self, _: &str, _: &str) -> StorageResult<()> {
+            Ok(())
+        }
+    }
+
+    fn upload(index: usize, bytes: &[u8]) -> (usize, String, NormalizedImage) {
+        let image = NormalizedImage {
+            content_type: "image/jpeg".into(),
+            bytes: bytes.to_vec(),
+            taken_at: None,
+        };
+        (index, content_hash(bytes), image)
+    }
+
+    #[tokio::test]
+    async fn store_images_removes_partial_uploads() {
+        let tracking = Arc::new(Tracking::default());
+        let storage: Arc<dyn StorageClient> = tracking.clone();
+        let config = TranscodeConfig::default();
+        let (user_id, meal_id) = (Uuid::new_v4(), Uuid::new_v4());
+
+        let stored = store_images(
+            &storage,
+            &config,
+            user_id,
+            meal_id,
+            vec![upload(0, JPEG), upload(1, PNG)],
+        )
+        .await
+        .expect("uploads succeed");
+        assert_eq!(stored.len(), 2);
+        assert!(tracking.keys.lock().unwrap().contains(&stored[&1].s3_key));
+
+        tracking.keys.lock().unwrap().clear();
+        let uploads = vec![upload(0, JPEG), upload(1, b"broken"), upload(2, PNG)];
+        assert!(store_images(&storage, &config, user_id, meal_id, uploads)
+            .await
+            .is_err());
+        assert!(tracking.keys.lock().unwrap().is_empty());
+    }
+}
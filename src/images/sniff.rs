@@ -0,0 +1,213 @@
+//! Image format detection and header parsing from raw bytes, independent of
+//! whatever content type the client claimed.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    Jpeg,
+    Png,
+    Webp,
+    Heic,
+}
+
+/// ISO-BMFF brands used by HEIC/HEIF stills.
+const HEIF_BRANDS: &[&[u8; 4]] = &[
+    b"heic", b"heix", b"hevc", b"hevx", b"heim", b"heis", b"mif1", b"msf1",
+];
+
+impl ImageFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ImageFormat::Jpeg => "image/jpeg",
+            ImageFormat::Png => "image/png",
+            ImageFormat::Webp => "image/webp",
+            ImageFormat::Heic => "image/heic",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ImageFormat::Jpeg => "jpg",
+            ImageFormat::Png => "png",
+            ImageFormat::Webp => "webp",
+            ImageFormat::Heic => "heic",
+        }
+    }
+
+    /// The format a declared content type names, for uploads whose bytes
+    /// arrive later.
+    pub fn from_content_type(content_type: &str) -> Option<Self> {
+        let declared = content_type.trim().to_lowercase();
+        [Self::Jpeg, Self::Png, Self::Webp, Self::Heic]
+            .into_iter()
+            .find(|format| format.matches_content_type(&declared))
+    }
+
+    /// Whether a declared (already lowercased) content type names this format.
+    pub fn matches_content_type(&self, content_type: &str) -> bool {
+        match self {
+            ImageFormat::Jpeg => matches!(content_type, "image/jpeg" | "image/jpg"),
+            ImageFormat::Heic => matches!(content_type, "image/heic" | "image/heif"),
+            other => content_type == other.content_type(),
+        }
+    }
+}
+
+pub fn detect(bytes: &[u8]) -> Option<ImageFormat> {
+    if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        return Some(ImageFormat::Jpeg);
+    }
+    if bytes.starts_with(&[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A]) {
+        return Some(ImageFormat::Png);
+    }
+    if bytes.len() >= 12 && &bytes[0..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        return Some(ImageFormat::Webp);
+    }
+    if bytes.len() >= 12 && &bytes[4..8] == b"ftyp" {
+        let brand = &bytes[8..12];
+        if HEIF_BRANDS.iter().any(|b| b.as_slice() == brand) {
+            return Some(ImageFormat::Heic);
+        }
+    }
+    None
+}
+
+fn be_u16(bytes: &[u8], at: usize) -> Option<u32> {
+    let b = bytes.get(at..at + 2)?;
+    Some(u32::from(u16::from_be_bytes([b[0], b[1]])))
+}
+
+fn le_u16(bytes: &[u8], at: usize) -> Option<u32> {
+    let b = bytes.get(at..at + 2)?;
+    Some(u32::from(u16::from_le_bytes([b[0], b[1]])))
+}
+
+fn be_u32(bytes: &[u8], at: usize) -> Option<u32> {
+    let b = bytes.get(at..at + 4)?;
+    Some(u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+fn le_u24(bytes: &[u8], at: usize) -> Option<u32> {
+    let b = bytes.get(at..at + 3)?;
+    Some(u32::from_le_bytes([b[0], b[1], b[2], 0]))
+}
+
+/// Reads `(width, height)` from the image header without decoding pixels.
+/// Returns `None` for truncated or unusual files.
+pub fn dimensions(format: ImageFormat, bytes: &[u8]) -> Option<(u32, u32)> {
+    match format {
+        ImageFormat::Png => Some((be_u32(bytes, 16)?, be_u32(bytes, 20)?)),
+        ImageFormat::Jpeg => jpeg_dimensions(bytes),
+        ImageFormat::Webp => match bytes.get(12..16)? {
+            b"VP8 " => Some((le_u16(bytes, 26)? & 0x3FFF, le_u16(bytes, 28)? & 0x3FFF)),
+            b"VP8L" => {
+                let b = bytes.get(21..25)?;
+                let bits = u32::from_le_bytes([b[0], b[1], b[2], b[3]]);
+                Some(((bits & 0x3FFF) + 1, ((bits >> 14) & 0x3FFF) + 1))
+            }
+            b"VP8X" => Some((le_u24(bytes, 24)? + 1, le_u24(bytes, 27)? + 1)),
+            _ => None,
+        },
+        ImageFormat::Heic => heic_dimensions(bytes),
+    }
+}
+
+/// Walks JPEG segments up to the first start-of-frame marker.
+fn jpeg_dimensions(bytes: &[u8]) -> Option<(u32, u32)> {
+    let mut i = 2;
+    loop {
+        while *bytes.get(i)? == 0xFF && *bytes.get(i + 1)? == 0xFF {
+            i += 1;
+        }
+        if *bytes.get(i)? != 0xFF {
+            return None;
+        }
+        let marker = *bytes.get(i + 1)?;
+        match marker {
+            0xD0..=0xD9 | 0x01 => i += 2,
+            0xC0..=0xCF if !matches!(marker, 0xC4 | 0xC8 | 0xCC) => {
+                return Some((be_u16(bytes, i + 7)?, be_u16(bytes, i + 5)?));
+            }
+            _ => i += 2 + be_u16(bytes, i + 2)? as usize,
+        }
+    }
+}
+
+/// HEIF stores sizes in `ispe` properties; the largest one belongs to the
+/// primary image rather than a thumbnail or grid tile.
+fn heic_dimensions(bytes: &[u8]) -> Option<(u32, u32)> {
+    bytes
+        .windows(4)
+        .enumerate()
+        .filter(|(_, w)| *w == b"ispe")
+        .filter_map(|(i, _)| Some((be_u32(bytes, i + 8)?, be_u32(bytes, i + 12)?)))
+        .max_by_key(|(w, h)| u64::from(*w) * u64::from(*h))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_known_signatures() {
+        assert_eq!(
+            detect(&[0xFF, 0xD8, 0xFF, 0xE0, 0, 0]),
+            Some(ImageFormat::Jpeg)
+        );
+        assert_eq!(
+            detect(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"),
+            Some(ImageFormat::Png)
+        );
+        assert_eq!(detect(b"RIFF\x10\0\0\0WEBPVP8 "), Some(ImageFormat::Webp));
+        assert_eq!(
+            detect(b"\0\0\0\x18ftypheic\0\0\0\0"),
+            Some(ImageFormat::Heic)
+        );
+    }
+
+    #[test]
+    fn rejects_non_images() {
+        assert_eq!(detect(b"%PDF-1.7"), None);
+        assert_eq!(detect(b"\0\0\0\x18ftypisom\0\0\0\0"), None);
+        assert_eq!(detect(b""), None);
+    }
+
+    #[test]
+    fn content_type_aliases() {
+        assert!(ImageFormat::Jpeg.matches_content_type("image/jpg"));
+        assert!(ImageFormat::Heic.matches_content_type("image/heif"));
+        assert!(!ImageFormat::Png.matches_content_type("image/jpeg"));
+        assert_eq!(
+            ImageFormat::from_content_type(" Image/HEIF"),
+            Some(ImageFormat::Heic)
+        );
+        assert_eq!(ImageFormat::from_content_type("application/pdf"), None);
+    }
+
+    #[test]
+    fn reads_dimensions() {
+        let png = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR\0\0\x01\x40\0\0\0\xf0";
+        assert_eq!(dimensions(ImageFormat::Png, png), Some((320, 240)));
+
+        let jpeg = [
+            0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x04, 0x00, 0x00, // APP0, 2 payload bytes
+            0xFF, 0xC0, 0x00, 0x0B, 0x08, 0x01, 0xE0, 0x02, 0x80, // SOF0 480x640
+        ];
+        assert_eq!(dimensions(ImageFormat::Jpeg, &jpeg), Some((640, 480)));
+
+        let mut webp = b"RIFF\0\0\0\0WEBPVP8X\0\0\0\0\0\0\0\0".to_vec();
+        webp.extend_from_slice(&[0x3F, 0x01, 0x00, 0xEF, 0x00, 0x00]);
+        assert_eq!(dimensions(ImageFormat::Webp, &webp), Some((320, 240)));
+
+        let heic = b"....ispe\0\0\0\0\0\0\0\x40\0\0\0\x30....ispe\0\0\0\0\0\0\x0f\xc0\0\0\x0b\xd0";
+        assert_eq!(dimensions(ImageFormat::Heic, heic), Some((4032, 3024)));
+    }
+
+    #[test]
+    fn truncated_headers_have_no_dimensions() {
+        assert_eq!(dimensions(ImageFormat::Png, b"\x89PNG\r\n\x1a\n"), None);
+        assert_eq!(
+            dimensions(ImageFormat::Jpeg, &[0xFF, 0xD8, 0xFF, 0xE0]),
+            None
+        );
+    }
+}
@@ -0,0 +1,89 @@
+//! Optional HEIC → JPEG conversion through an external tool (e.g.
+//! `heif-convert` from libheif or ImageMagick), since many web clients can't
+//! render HEIC.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use tokio::process::Command;
+use uuid::Uuid;
+
+use crate::images::sniff::{self, ImageFormat};
+
+/// Expands `{input}`/`{output}` in a whitespace-separated command template.
+pub fn build_command(template: &str, input: &str, output: &str) -> Option<(String, Vec<String>)> {
+    let mut parts = template
+        .split_whitespace()
+        .map(|p| p.replace("{input}", input).replace("{output}", output));
+    let program = parts.next()?;
+    Some((program, parts.collect()))
+}
+
+pub async fn heic_to_jpeg(template: &str, heic: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let input = temp_path("heic");
+    let result = async {
+        tokio::fs::write(&input, heic)
+            .await
+            .context("write heic temp file")?;
+        heic_file_to_jpeg(template, &input).await
+    }
+    .await;
+    let _ = tokio::fs::remove_file(&input).await;
+    result
+}
+
+/// Like [`heic_to_jpeg`] for a HEIC already written to `input`, so large
+/// photos needn't be held in memory.
+pub async fn heic_file_to_jpeg(template: &str, input: &Path) -> anyhow::Result<Vec<u8>> {
+    let output = temp_path("jpg");
+    let result = async {
+        let (program, args) = build_command(
+            template,
+            &input.to_string_lossy(),
+            &output.to_string_lossy(),
+        )
+        .context("empty transcode command")?;
+        let status = Command::new(&program)
+            .args(&args)
+            .kill_on_drop(true)
+            .status()
+            .await
+            .with_context(|| format!("spawn {}", program))?;
+        if !status.success() {
+            anyhow::bail!("{} exited with {}", program, status);
+        }
+        let jpeg = tokio::fs::read(&output)
+            .await
+            .context("read transcoded jpeg")?;
+        if sniff::detect(&jpeg) != Some(ImageFormat::Jpeg) {
+            anyhow::bail!("converter did not produce a jpeg");
+        }
+        Ok(jpeg)
+    }
+    .await;
+    let _ = tokio::fs::remove_file(&output).await;
+    result
+}
+
+/// A fresh path in the temp dir for a file with `extension`.
+pub fn temp_path(extension: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("mealmind-{}.{}", Uuid::new_v4(), extension))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expands_placeholders() {
+        let (program, args) = build_command(
+            "heif-convert -q 90 {input} {output}",
+            "/t/a.heic",
+            "/t/a.jpg",
+        )
+        .expect("command");
+        assert_eq!(program, "heif-convert");
+        assert_eq!(args, vec!["-q", "90", "/t/a.heic", "/t/a.jpg"]);
+        assert!(build_command("   ", "a", "b").is_none());
+    }
+}
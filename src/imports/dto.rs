@@ -0,0 +1,66 @@
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+use crate::{foods::dto::FoodNutrition, meals::dto::MealType};
+
+/// Largest CSV body accepted by `POST /import/meals`.
+pub const MAX_IMPORT_BYTES: usize = 10 * 1024 * 1024;
+/// Data rows accepted per import.
+pub const MAX_IMPORT_ROWS: usize = 20_000;
+
+/// App whose CSV export is being imported.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportSource {
+    /// "Nutrition" export: one row per day and meal with its totals.
+    MyFitnessPal,
+    /// `servings.csv` export: one row per food eaten.
+    Cronometer,
+}
+
+impl ImportSource {
+    /// Tag added to every meal imported from this source.
+    pub fn tag(self) -> &'static str {
+        match self {
+            ImportSource::MyFitnessPal => "myfitnesspal",
+            ImportSource::Cronometer => "cronometer",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ImportQuery {
+    /// Detected from the header row when omitted.
+    pub source: Option<ImportSource>,
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// One meal assembled from the rows sharing a date and meal name.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedMeal {
+    pub eaten_at: OffsetDateTime,
+    pub meal_type: Option<MealType>,
+    pub title: String,
+    pub notes: Option<String>,
+    pub nutrition: FoodNutrition,
+}
+
+/// Problem with one CSV line; `line` counts the header as line 1.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RowError {
+    pub line: u64,
+    pub error: String,
+}
+
+/// Outcome of an import. Nothing is stored when `errors` is non-empty or
+/// `dry_run` is set.
+#[derive(Debug, Serialize)]
+pub struct ImportReport {
+    pub source: ImportSource,
+    pub dry_run: bool,
+    pub rows: usize,
+    pub meals: usize,
+    pub imported: usize,
+    pub errors: Vec<RowError>,
+}
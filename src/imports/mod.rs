@@ -0,0 +1,6 @@
+//! Meal history imported from other tracking apps' CSV exports.
+
+pub mod dto;
+pub mod parse;
+pub mod repo;
+pub mod services;
@@ -0,0 +1,339 @@
+//! Column mapping for MyFitnessPal and Cronometer CSV exports.
+
+use std::collections::HashMap;
+
+use csv::StringRecord;
+use time::{macros::format_description, macros::time, Date, Time};
+
+use crate::{
+    foods::dto::FoodNutrition,
+    imports::dto::{ImportSource, ImportedMeal, RowError, MAX_IMPORT_ROWS},
+    meals::dto::{
+        MealType, MAX_MANUAL_GRAMS, MAX_MANUAL_KCAL, MAX_MANUAL_MG, MAX_NOTES_LEN, MAX_TITLE_LEN,
+    },
+};
+
+/// Rows grouped into meals, plus the rows that could not be read.
+#[derive(Debug)]
+pub struct Parsed {
+    pub source: ImportSource,
+    pub rows: usize,
+    pub meals: Vec<ImportedMeal>,
+    pub errors: Vec<RowError>,
+}
+
+/// Positions of the columns an import reads; nutrients are optional.
+struct Columns {
+    date: usize,
+    meal: Option<usize>,
+    food: Option<usize>,
+    note: Option<usize>,
+    nutrients: [(Nutrient, Option<usize>); 7],
+}
+
+#[derive(Clone, Copy)]
+enum Nutrient {
+    Calories,
+    Protein,
+    Fat,
+    Carbs,
+    Sodium,
+    Sugar,
+    Fiber,
+}
+
+impl Nutrient {
+    fn max(self) -> f64 {
+        match self {
+            Nutrient::Calories => MAX_MANUAL_KCAL,
+            Nutrient::Sodium => MAX_MANUAL_MG,
+            _ => MAX_MANUAL_GRAMS,
+        }
+    }
+
+    fn set(self, nutrition: &mut FoodNutrition, value: f64) {
+        let field = match self {
+            Nutrient::Calories => &mut nutrition.total_calories_kcal,
+            Nutrient::Protein => &mut nutrition.protein_g,
+            Nutrient::Fat => &mut nutrition.fat_g,
+            Nutrient::Carbs => &mut nutrition.carbs_g,
+            Nutrient::Sodium => &mut nutrition.sodium_mg,
+            Nutrient::Sugar => &mut nutrition.sugar_g,
+            Nutrient::Fiber => &mut nutrition.fiber_g,
+        };
+        *field = Some(value);
+    }
+}
+
+fn find(headers: &StringRecord, names: &[&str]) -> Option<usize> {
+    headers.iter().position(|h| {
+        let h = h.trim_start_matches('\u{feff}').trim();
+        names.iter().any(|name| h.eq_ignore_ascii_case(name))
+    })
+}
+
+/// Guesses the source from the header row.
+pub fn detect(headers: &StringRecord) -> Option<ImportSource> {
+    if find(headers, &["Food Name"]).is_some() && find(headers, &["Day"]).is_some() {
+        Some(ImportSource::Cronometer)
+    } else if find(headers, &["Meal"]).is_some() && find(headers, &["Calories"]).is_some() {
+        Some(ImportSource::MyFitnessPal)
+    } else {
+        None
+    }
+}
+
+impl Columns {
+    fn for_source(source: ImportSource, headers: &StringRecord) -> Result<Self, String> {
+        let required = |names: &[&str]| {
+            find(headers, names).ok_or_else(|| format!("missing column \"{}\"", names[0]))
+        };
+        let columns = match source {
+            ImportSource::MyFitnessPal => Columns {
+                date: required(&["Date"])?,
+                meal: Some(required(&["Meal"])?),
+                food: None,
+                note: find(headers, &["Note"]),
+                nutrients: [
+                    (Nutrient::Calories, Some(required(&["Calories"])?)),
+                    (Nutrient::Protein, find(headers, &["Protein (g)"])),
+                    (Nutrient::Fat, find(headers, &["Fat (g)"])),
+                    (Nutrient::Carbs, find(headers, &["Carbohydrates (g)"])),
+                    (Nutrient::Sodium, find(headers, &["Sodium (mg)"])),
+                    (Nutrient::Sugar, find(headers, &["Sugar", "Sugar (g)"])),
+                    (Nutrient::Fiber, find(headers, &["Fiber", "Fiber (g)"])),
+                ],
+            },
+            ImportSource::Cronometer => Columns {
+                date: required(&["Day"])?,
+                meal: find(headers, &["Group"]),
+                food: Some(required(&["Food Name"])?),
+                note: None,
+                nutrients: [
+                    (Nutrient::Calories, Some(required(&["Energy (kcal)"])?)),
+                    (Nutrient::Protein, find(headers, &["Protein (g)"])),
+                    (Nutrient::Fat, find(headers, &["Fat (g)"])),
+                    (Nutrient::Carbs, find(headers, &["Carbs (g)"])),
+                    (Nutrient::Sodium, find(headers, &["Sodium (mg)"])),
+                    (Nutrient::Sugar, find(headers, &["Sugars (g)"])),
+                    (Nutrient::Fiber, find(headers, &["Fiber (g)"])),
+                ],
+            },
+        };
+        Ok(columns)
+    }
+}
+
+/// Known meal names; anything else is imported without a meal type.
+fn meal_type(label: &str) -> Option<MealType> {
+    match label.to_ascii_lowercase().as_str() {
+        "breakfast" => Some(MealType::Breakfast),
+        "lunch" => Some(MealType::Lunch),
+        "dinner" => Some(MealType::Dinner),
+        "snack" | "snacks" => Some(MealType::Snack),
+        _ => None,
+    }
+}
+
+/// The exports carry no reliable time of day, so meals are placed at a
+/// typical UTC time for their type to keep a day's meals in order.
+fn typical_time(meal_type: Option<MealType>) -> Time {
+    match meal_type {
+        Some(MealType::Breakfast) => time!(08:00),
+        Some(MealType::Lunch) => time!(12:30),
+        Some(MealType::Snack) => time!(15:30),
+        Some(MealType::Dinner) => time!(19:00),
+        None => time!(12:00),
+    }
+}
+
+fn number(value: &str, nutrient: Nutrient, header: &str) -> Result<Option<f64>, String> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Ok(None);
+    }
+    match value.parse::<f64>() {
+        Ok(v) if v.is_finite() && (0.0..=nutrient.max()).contains(&v) => Ok(Some(v)),
+        Ok(_) => Err(format!("{header} must be between 0 and {}", nutrient.max())),
+        Err(_) => Err(format!("{header} is not a number: \"{value}\"")),
+    }
+}
+
+/// One data row: its date, meal label, food or note text and nutrition.
+fn read_row(
+    columns: &Columns,
+    headers: &StringRecord,
+    record: &StringRecord,
+) -> Result<(Date, String, Option<String>, FoodNutrition), String> {
+    let field = |i: usize| record.get(i).unwrap_or_default().trim();
+    let raw_date = field(columns.date);
+    let date = Date::parse(raw_date, format_description!("[year]-[month]-[day]"))
+        .map_err(|_| format!("date must be YYYY-MM-DD, got \"{raw_date}\""))?;
+    let label = columns.meal.map(field).unwrap_or_default().to_string();
+    let text = columns
+        .food
+        .or(columns.note)
+        .map(field)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string);
+    let mut nutrition = FoodNutrition::default();
+    for (nutrient, column) in columns.nutrients {
+        let Some(i) = column else { continue };
+        if let Some(v) = number(field(i), nutrient, headers.get(i).unwrap_or_default())? {
+            nutrient.set(&mut nutrition, v);
+        }
+    }
+    Ok((date, label, text, nutrition))
+}
+
+fn truncate(text: &str, max: usize) -> String {
+    text.chars().take(max).collect()
+}
+
+/// Reads a CSV export and merges its rows into one meal per date and meal
+/// name, in order of first appearance. Fails as a whole only when the
+/// header is unusable or the file is too long; bad rows become
+/// [`RowError`]s.
+pub fn parse(body: &str, source: Option<ImportSource>) -> Result<Parsed, String> {
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .from_reader(body.as_bytes());
+    let headers = reader
+        .headers()
+        .map_err(|e| format!("unreadable header row: {e}"))?
+        .clone();
+    let source = source
+        .or_else(|| detect(&headers))
+        .ok_or("unrecognized CSV; expected a MyFitnessPal or Cronometer export")?;
+    let columns = Columns::for_source(source, &headers)?;
+
+    let mut meals: Vec<ImportedMeal> = Vec::new();
+    let mut texts: Vec<Vec<String>> = Vec::new();
+    let mut index: HashMap<(Date, String), usize> = HashMap::new();
+    let mut errors = Vec::new();
+    let mut rows = 0;
+    for record in reader.records() {
+        rows += 1;
+        if rows > MAX_IMPORT_ROWS {
+            return Err(format!(
+                "at most {MAX_IMPORT_ROWS} rows can be imported at once"
+            ));
+        }
+        let record = match record {
+            Ok(record) => record,
+            Err(e) => {
+                let line = e.position().map(|p| p.line()).unwrap_or_default();
+                errors.push(RowError {
+                    line,
+                    error: e.to_string(),
+                });
+                continue;
+            }
+        };
+        let line = record.position().map(|p| p.line()).unwrap_or_default();
+        if record.iter().all(|f| f.trim().is_empty()) {
+            continue;
+        }
+        let (date, label, text, nutrition) = match read_row(&columns, &headers, &record) {
+            Ok(row) => row,
+            Err(error) => {
+                errors.push(RowError { line, error });
+                continue;
+            }
+        };
+        let key = (date, label.to_lowercase());
+        let i = *index.entry(key).or_insert_with(|| {
+            let meal_type = meal_type(&label);
+            let title = if label.is_empty() {
+                "Imported meal".to_string()
+            } else {
+                truncate(&label, MAX_TITLE_LEN)
+            };
+            meals.push(ImportedMeal {
+                eaten_at: date.with_time(typical_time(meal_type)).assume_utc(),
+                meal_type,
+                title,
+                notes: None,
+                nutrition: FoodNutrition::default(),
+            });
+            texts.push(Vec::new());
+            meals.len() - 1
+        });
+        meals[i].nutrition = meals[i].nutrition.plus(&nutrition);
+        texts[i].extend(text);
+    }
+
+    for (meal, texts) in meals.iter_mut().zip(texts) {
+        if !texts.is_empty() {
+            meal.notes = Some(truncate(&texts.join(", "), MAX_NOTES_LEN));
+        }
+    }
+    Ok(Parsed {
+        source,
+        rows,
+        meals,
+        errors,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::datetime;
+
+    const MFP: &str = "\
+Date,Meal,Calories,Fat (g),Saturated Fat,Carbohydrates (g),Fiber,Sugar,Protein (g),Sodium (mg),Note
+2024-05-01,Breakfast,420,12,3,55,6,10,18,300,oats
+2024-05-01,Snacks,150,,,20,,15,2,,
+2024-05-02,Dinner,abc,1,,1,,,1,,
+";
+
+    const CRONOMETER: &str = "\
+Day,Time,Group,Food Name,Amount,Energy (kcal),Carbs (g),Fiber (g),Sugars (g),Fat (g),Protein (g),Sodium (mg)
+2024-05-01,,Lunch,Rice,100 g,130,28,0.4,0.1,0.3,2.7,1
+2024-05-01,,Lunch,Chicken,150 g,240,0,0,0,5,45,110
+2024-05-01,,Uncategorized,Coffee,1 cup,2,0,0,0,0,0.3,5
+2024/05/02,,Lunch,Rice,100 g,130,28,0.4,0.1,0.3,2.7,1
+";
+
+    #[test]
+    fn detects_source_from_headers() {
+        assert_eq!(parse(MFP, None).unwrap().source, ImportSource::MyFitnessPal);
+        assert_eq!(
+            parse(CRONOMETER, None).unwrap().source,
+            ImportSource::Cronometer
+        );
+        assert!(parse("a,b\n1,2\n", None).is_err());
+        assert!(parse(MFP, Some(ImportSource::Cronometer)).is_err());
+    }
+
+    #[test]
+    fn maps_myfitnesspal_rows_to_meals() {
+        let parsed = parse(MFP, None).unwrap();
+        assert_eq!(parsed.rows, 3);
+        assert_eq!(parsed.meals.len(), 2);
+        let breakfast = &parsed.meals[0];
+        assert_eq!(breakfast.meal_type, Some(MealType::Breakfast));
+        assert_eq!(breakfast.eaten_at, datetime!(2024-05-01 08:00 UTC));
+        assert_eq!(breakfast.nutrition.carbs_g, Some(55.0));
+        assert_eq!(breakfast.notes.as_deref(), Some("oats"));
+        assert_eq!(parsed.meals[1].meal_type, Some(MealType::Snack));
+        assert_eq!(parsed.meals[1].nutrition.fat_g, None);
+        assert_eq!(parsed.errors.len(), 1);
+        assert_eq!(parsed.errors[0].line, 4);
+        assert!(parsed.errors[0].error.contains("Calories"));
+    }
+
+    #[test]
+    fn groups_cronometer_servings_by_day_and_group() {
+        let parsed = parse(CRONOMETER, None).unwrap();
+        assert_eq!(parsed.meals.len(), 2);
+        let lunch = &parsed.meals[0];
+        assert_eq!(lunch.nutrition.total_calories_kcal, Some(370.0));
+        assert_eq!(lunch.nutrition.protein_g, Some(47.7));
+        assert_eq!(lunch.notes.as_deref(), Some("Rice, Chicken"));
+        assert_eq!(parsed.meals[1].meal_type, None);
+        assert_eq!(parsed.meals[1].title, "Uncategorized");
+        assert_eq!(parsed.errors[0].line, 5);
+    }
+}
@@ -0,0 +1,59 @@
+use sqlx::{types::Json, PgPool};
+use uuid::Uuid;
+
+use crate::{
+    events::{repo as events_repo, DomainEvent},
+    imports::dto::ImportedMeal,
+};
+
+/// Stores the meals with their nutrition in one transaction, so a failed
+/// import leaves nothing behind. `scores[i]` is the score of `meals[i]`.
+pub async fn insert_imported(
+    db: &PgPool,
+    user_id: Uuid,
+    meals: &[ImportedMeal],
+    scores: &[Option<f64>],
+    tags: &[String],
+) -> anyhow::Result<usize> {
+    let mut tx = db.begin().await?;
+    for (meal, score) in meals.iter().zip(scores) {
+        let meal_id = sqlx::query_scalar::<_, Uuid>(
+            r#"
+            INSERT INTO meals (user_id, title, notes, meal_type, tags, status, created_at)
+            VALUES ($1, $2, $3, $4, $5, 'done', $6)
+            RETURNING id
+            "#,
+        )
+        .bind(user_id)
+        .bind(&meal.title)
+        .bind(&meal.notes)
+        .bind(meal.meal_type)
+        .bind(tags)
+        .bind(meal.eaten_at)
+        .fetch_one(&mut *tx)
+        .await?;
+        events_repo::record(&mut *tx, user_id, &DomainEvent::MealCreated { meal_id }).await?;
+        let n = &meal.nutrition;
+        sqlx::query(
+            r#"
+            INSERT INTO meal_nutrition (meal_id, total_calories_kcal, protein_g, fat_g, carbs_g,
+                                        sodium_mg, sugar_g, fiber_g, global_score, base, source)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, 'import')
+            "#,
+        )
+        .bind(meal_id)
+        .bind(n.total_calories_kcal)
+        .bind(n.protein_g)
+        .bind(n.fat_g)
+        .bind(n.carbs_g)
+        .bind(n.sodium_mg)
+        .bind(n.sugar_g)
+        .bind(n.fiber_g)
+        .bind(score)
+        .bind(Json(n))
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+    Ok(meals.len())
+}
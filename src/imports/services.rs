@@ -0,0 +1,105 @@
+use axum::http::StatusCode;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    db::AppState,
+    error::ErrorCode,
+    goals::repo as goals_repo,
+    imports::{
+        dto::{ImportQuery, ImportReport},
+        parse, repo,
+    },
+    meals::{
+        dto::{MealNutrition, NutritionSource},
+        score,
+    },
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum ImportError {
+    #[error("{0}")]
+    Invalid(String),
+    /// Some rows could not be read; nothing was imported.
+    #[error("Import has invalid rows")]
+    Rejected(ImportReport),
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+impl ImportError {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            ImportError::Invalid(_) => StatusCode::BAD_REQUEST,
+            ImportError::Rejected(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            ImportError::Other(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            ImportError::Invalid(_) => ErrorCode::ValidationFailed,
+            ImportError::Rejected(_) => ErrorCode::ImportRejected,
+            ImportError::Other(_) => ErrorCode::Internal,
+        }
+    }
+}
+
+/// Parses a CSV export and, unless it is a dry run, stores its meals. Any
+/// invalid row rejects the whole import so it can be fixed and re-sent.
+pub async fn import_meals(
+    state: &AppState,
+    user_id: Uuid,
+    query: ImportQuery,
+    body: &str,
+) -> Result<ImportReport, ImportError> {
+    let parsed = parse::parse(body, query.source).map_err(ImportError::Invalid)?;
+    let mut report = ImportReport {
+        source: parsed.source,
+        dry_run: query.dry_run,
+        rows: parsed.rows,
+        meals: parsed.meals.len(),
+        imported: 0,
+        errors: parsed.errors,
+    };
+    if query.dry_run {
+        return Ok(report);
+    }
+    if !report.errors.is_empty() {
+        return Err(ImportError::Rejected(report));
+    }
+    if parsed.meals.is_empty() {
+        return Err(ImportError::Invalid("no meals to import".to_string()));
+    }
+
+    let targets = goals_repo::find_goals(&state.db, user_id).await?.targets();
+    let scores: Vec<_> = parsed
+        .meals
+        .iter()
+        .map(|meal| {
+            let n = &meal.nutrition;
+            let nutrition = MealNutrition {
+                total_calories_kcal: n.total_calories_kcal,
+                protein_g: n.protein_g,
+                fat_g: n.fat_g,
+                carbs_g: n.carbs_g,
+                sodium_mg: n.sodium_mg,
+                sugar_g: n.sugar_g,
+                fiber_g: n.fiber_g,
+                caffeine_mg: None,
+                alcohol_g: None,
+                micros: None,
+                global_score: None,
+                source: NutritionSource::Import,
+            };
+            score::global_score(&nutrition, &targets, &state.config.score)
+        })
+        .collect();
+    let tags = vec!["imported".to_string(), parsed.source.tag().to_string()];
+    report.imported =
+        repo::insert_imported(&state.db, user_id, &parsed.meals, &scores, &tags).await?;
+    state.stats_cache.invalidate(user_id);
+    state.cache.invalidate_summaries(user_id).await;
+    info!(user_id = %user_id, source = parsed.source.tag(), meals = report.imported, "meals imported");
+    Ok(report)
+}
@@ -0,0 +1,51 @@
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+use crate::integrations::{repo::Connection, Provider};
+
+/// How long a user has to finish authorizing.
+pub const STATE_TTL_MINUTES: i64 = 10;
+
+#[derive(Debug, Serialize)]
+pub struct ConnectResponse {
+    /// Open in a browser to grant access.
+    pub authorize_url: String,
+}
+
+/// The provider's redirect after the user granted or denied access.
+#[derive(Debug, Deserialize)]
+pub struct CallbackQuery {
+    pub state: String,
+    pub code: Option<String>,
+    /// Set instead of `code` when the user denied access.
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct IntegrationStatus {
+    pub provider: Provider,
+    #[serde(with = "time::serde::rfc3339")]
+    pub connected_at: OffsetDateTime,
+    /// Data up to here has been pulled; `null` before the first sync.
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub synced_at: Option<OffsetDateTime>,
+}
+
+impl From<&Connection> for IntegrationStatus {
+    fn from(connection: &Connection) -> Self {
+        Self {
+            provider: connection.provider,
+            connected_at: connection.created_at,
+            synced_at: connection.synced_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct SyncResponse {
+    /// Activity entries.
+    pub imported: usize,
+    pub weights: usize,
+    #[serde(with = "time::serde::rfc3339")]
+    pub synced_at: OffsetDateTime,
+}
@@ -0,0 +1,207 @@
+use serde_json::Value;
+use time::{
+    format_description::well_known::Rfc3339, macros::format_description, Date, Duration,
+    OffsetDateTime, PrimitiveDateTime, Time,
+};
+
+use crate::{
+    activity::dto::NewActivity,
+    config::OAuthAppConfig,
+    integrations::{
+        oauth::{OAuthClient, OAuthEndpoints},
+        IntegrationProvider,
+    },
+    weights::dto::ImportedWeight,
+};
+
+const API_URL: &str = "https://api.fitbit.com/1/user/-";
+/// Activities per page, the API's maximum.
+const PAGE_SIZE: u32 = 100;
+/// Pages followed per pull; the rest waits for the next one.
+const MAX_PAGES: usize = 10;
+/// Longest range of one weight log request.
+const MAX_WEIGHT_RANGE_DAYS: i64 = 31;
+
+/// Logged activities and weigh-ins from the Fitbit Web API. Requests carry
+/// no `Accept-Language`, so weights come in kilograms.
+pub struct Fitbit {
+    http: reqwest::Client,
+    oauth: OAuthClient,
+}
+
+impl Fitbit {
+    pub fn new(http: reqwest::Client, app: OAuthAppConfig) -> Self {
+        let oauth = OAuthClient::new(
+            http.clone(),
+            app,
+            OAuthEndpoints {
+                authorize_url: "https://www.fitbit.com/oauth2/authorize",
+                token_url: "https://api.fitbit.com/oauth2/token",
+                scopes: "activity weight",
+                extra_params: &[],
+            },
+        );
+        Self { http, oauth }
+    }
+
+    async fn get(&self, access_token: &str, url: &str) -> anyhow::Result<Value> {
+        let body = self
+            .http
+            .get(url)
+            .bearer_auth(access_token)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(body)
+    }
+}
+
+#[axum::async_trait]
+impl IntegrationProvider for Fitbit {
+    fn oauth(&self) -> &OAuthClient {
+        &self.oauth
+    }
+
+    async fn activity(
+        &self,
+        access_token: &str,
+        from: OffsetDateTime,
+        to: OffsetDateTime,
+    ) -> anyhow::Result<Vec<NewActivity>> {
+        let after = from.format(format_description!(
+            "[year]-[month]-[day]T[hour]:[minute]:[second]"
+        ))?;
+        let mut url = format!(
+            "{}/activities/list.json?afterDate={}&sort=asc&offset=0&limit={}",
+            API_URL, after, PAGE_SIZE
+        );
+        let mut entries = Vec::new();
+        for _ in 0..MAX_PAGES {
+            let body = self.get(access_token, &url).await?;
+            entries.extend(
+                parse_activities(&body)
+                    .into_iter()
+                    .filter(|entry| entry.started_at < to),
+            );
+            match body["pagination"]["next"].as_str() {
+                Some(next) if !next.is_empty() => url = next.to_string(),
+                _ => break,
+            }
+        }
+        Ok(entries)
+    }
+
+    async fn weights(
+        &self,
+        access_token: &str,
+        from: OffsetDateTime,
+        to: OffsetDateTime,
+    ) -> anyhow::Result<Vec<ImportedWeight>> {
+        let (mut start, end) = (from.date(), to.date());
+        let mut weights = Vec::new();
+        while start <= end {
+            let stop = (start + Duration::days(MAX_WEIGHT_RANGE_DAYS - 1)).min(end);
+            let url = format!("{}/body/log/weight/date/{}/{}.json", API_URL, start, stop);
+            weights.extend(parse_weights(&self.get(access_token, &url).await?));
+            start = stop + Duration::days(1);
+        }
+        Ok(weights)
+    }
+}
+
+/// Entries of an activity log list.
+pub fn parse_activities(body: &Value) -> Vec<NewActivity> {
+    let Some(activities) = body["activities"].as_array() else {
+        return Vec::new();
+    };
+    activities
+        .iter()
+        .filter_map(|activity| {
+            let started_at =
+                OffsetDateTime::parse(activity["startTime"].as_str()?, &Rfc3339).ok()?;
+            Some(NewActivity {
+                external_id: Some(activity["logId"].as_i64()?.to_string()),
+                name: activity["activityName"].as_str().map(str::to_string),
+                started_at,
+                duration_minutes: activity["duration"].as_f64().map(|ms| ms / 60_000.0),
+                calories_kcal: activity["calories"].as_f64()?,
+            })
+        })
+        .collect()
+}
+
+/// Weigh-ins of a weight log. Their date and time are local to the Fitbit
+/// account and are read as UTC.
+pub fn parse_weights(body: &Value) -> Vec<ImportedWeight> {
+    let Some(logs) = body["weight"].as_array() else {
+        return Vec::new();
+    };
+    logs.iter()
+        .filter_map(|log| {
+            let date = Date::parse(
+                log["date"].as_str()?,
+                format_description!("[year]-[month]-[day]"),
+            )
+            .ok()?;
+            let time = log["time"]
+                .as_str()
+                .and_then(|t| Time::parse(t, format_description!("[hour]:[minute]:[second]")).ok())
+                .unwrap_or(Time::MIDNIGHT);
+            Some(ImportedWeight {
+                external_id: log["logId"].as_i64()?.to_string(),
+                weight_kg: log["weight"].as_f64()?,
+                measured_at: PrimitiveDateTime::new(date, time).assume_utc(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use time::macros::datetime;
+
+    use super::*;
+
+    #[test]
+    fn parses_activity_logs() {
+        let body = json!({
+            "activities": [
+                {
+                    "logId": 4711,
+                    "activityName": "Walk",
+                    "startTime": "2024-01-01T08:00:00.000+01:00",
+                    "duration": 1_800_000,
+                    "calories": 150
+                },
+                {"logId": 4712, "activityName": "Broken"}
+            ],
+            "pagination": {"next": ""}
+        });
+        let entries = parse_activities(&body);
+        assert_eq!(entries.len(), 1);
+        let walk = &entries[0];
+        assert_eq!(walk.external_id.as_deref(), Some("4711"));
+        assert_eq!(walk.started_at, datetime!(2024-01-01 07:00 UTC));
+        assert_eq!(walk.duration_minutes, Some(30.0));
+        assert_eq!(walk.calories_kcal, 150.0);
+    }
+
+    #[test]
+    fn parses_weight_logs() {
+        let body = json!({
+            "weight": [
+                {"logId": 1, "weight": 72.4, "date": "2024-01-02", "time": "07:15:00"},
+                {"logId": 2, "weight": 72.1, "date": "2024-01-03"},
+                {"weight": 71.9, "date": "2024-01-04"}
+            ]
+        });
+        let weights = parse_weights(&body);
+        assert_eq!(weights.len(), 2);
+        assert_eq!(weights[0].measured_at, datetime!(2024-01-02 07:15 UTC));
+        assert_eq!(weights[1].external_id, "2");
+        assert_eq!(weights[1].measured_at, datetime!(2024-01-03 00:00 UTC));
+    }
+}
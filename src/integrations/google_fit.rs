@@ -0,0 +1,150 @@
+use serde_json::{json, Value};
+use time::OffsetDateTime;
+
+use crate::{
+    activity::dto::NewActivity,
+    config::OAuthAppConfig,
+    integrations::{
+        oauth::{OAuthClient, OAuthEndpoints},
+        IntegrationProvider,
+    },
+};
+
+const API_URL: &str = "https://www.googleapis.com/fitness/v1/users/me";
+/// Sessions shorter than this aren't workouts.
+const MIN_SESSION_MILLIS: i64 = 60_000;
+
+/// Workouts from the Google Fit REST API: the recorded sessions with the
+/// calories burned during each.
+pub struct GoogleFit {
+    http: reqwest::Client,
+    oauth: OAuthClient,
+}
+
+impl GoogleFit {
+    pub fn new(http: reqwest::Client, app: OAuthAppConfig) -> Self {
+        let oauth = OAuthClient::new(
+            http.clone(),
+            app,
+            OAuthEndpoints {
+                authorize_url: "https://accounts.google.com/o/oauth2/v2/auth",
+                token_url: "https://oauth2.googleapis.com/token",
+                scopes: "https://www.googleapis.com/auth/fitness.activity.read",
+                // A refresh token is only handed out for offline access, and
+                // again on later grants only with the consent prompt.
+                extra_params: &[("access_type", "offline"), ("prompt", "consent")],
+            },
+        );
+        Self { http, oauth }
+    }
+}
+
+#[axum::async_trait]
+impl IntegrationProvider for GoogleFit {
+    fn oauth(&self) -> &OAuthClient {
+        &self.oauth
+    }
+
+    async fn activity(
+        &self,
+        access_token: &str,
+        from: OffsetDateTime,
+        to: OffsetDateTime,
+    ) -> anyhow::Result<Vec<NewActivity>> {
+        let millis = |at: OffsetDateTime| (at.unix_timestamp_nanos() / 1_000_000) as i64;
+        let body: Value = self
+            .http
+            .post(format!("{}/dataset:aggregate", API_URL))
+            .bearer_auth(access_token)
+            .json(&json!({
+                "aggregateBy": [{"dataTypeName": "com.google.calories.expended"}],
+                "bucketBySession": {"minDurationMillis": MIN_SESSION_MILLIS},
+                "startTimeMillis": millis(from),
+                "endTimeMillis": millis(to),
+            }))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(parse_sessions(&body))
+    }
+}
+
+/// Int64 fields come as strings.
+fn int64(value: &Value) -> Option<i64> {
+    value
+        .as_i64()
+        .or_else(|| value.as_str().and_then(|s| s.parse().ok()))
+}
+
+/// One entry per session bucket of an aggregate response, with the calories
+/// summed over its data points.
+pub fn parse_sessions(body: &Value) -> Vec<NewActivity> {
+    let Some(buckets) = body["bucket"].as_array() else {
+        return Vec::new();
+    };
+    buckets
+        .iter()
+        .filter_map(|bucket| {
+            let session = &bucket["session"];
+            let start = int64(&session["startTimeMillis"])?;
+            let end = int64(&session["endTimeMillis"])?;
+            let calories_kcal = bucket["dataset"]
+                .as_array()?
+                .iter()
+                .filter_map(|set| set["point"].as_array())
+                .flatten()
+                .filter_map(|point| point["value"][0]["fpVal"].as_f64())
+                .sum();
+            Some(NewActivity {
+                external_id: session["id"].as_str().map(str::to_string),
+                name: session["name"].as_str().map(str::to_string),
+                started_at: OffsetDateTime::from_unix_timestamp_nanos(
+                    i128::from(start) * 1_000_000,
+                )
+                .ok()?,
+                duration_minutes: Some((end - start).max(0) as f64 / 60_000.0),
+                calories_kcal,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use time::macros::datetime;
+
+    use super::*;
+
+    #[test]
+    fn parses_session_buckets() {
+        let body = json!({
+            "bucket": [
+                {
+                    "session": {
+                        "id": "run-1",
+                        "name": "Morning run",
+                        "startTimeMillis": "1704096000000",
+                        "endTimeMillis": "1704097800000",
+                        "activityType": 8
+                    },
+                    "dataset": [{"point": [
+                        {"value": [{"fpVal": 180.5}]},
+                        {"value": [{"fpVal": 120.0}]}
+                    ]}]
+                },
+                {"session": {"id": "broken"}, "dataset": []}
+            ]
+        });
+        let sessions = parse_sessions(&body);
+        assert_eq!(sessions.len(), 1);
+        let run = &sessions[0];
+        assert_eq!(run.external_id.as_deref(), Some("run-1"));
+        assert_eq!(run.name.as_deref(), Some("Morning run"));
+        assert_eq!(run.started_at, datetime!(2024-01-01 08:00 UTC));
+        assert_eq!(run.duration_minutes, Some(30.0));
+        assert_eq!(run.calories_kcal, 300.5);
+        assert!(parse_sessions(&json!({})).is_empty());
+    }
+}
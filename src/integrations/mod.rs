@@ -0,0 +1,103 @@
+//! Connections to health data providers. Users authorize us through OAuth,
+//! and their activity, and weigh-ins where the provider has them, are
+//! pulled on a schedule and on demand. Providers sit
+//! behind [`IntegrationProvider`]; apps that can't be reached from the
+//! server, like Apple Health, push to `POST /integrations/activity` instead.
+
+use std::{sync::Arc, time::Duration};
+
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+use crate::{
+    activity::dto::{ActivitySource, NewActivity},
+    config::IntegrationsConfig,
+    foods::USER_AGENT,
+    weights::dto::ImportedWeight,
+};
+
+pub mod dto;
+pub mod fitbit;
+pub mod google_fit;
+pub mod oauth;
+pub mod repo;
+pub mod services;
+
+use oauth::OAuthClient;
+
+/// As in the paths, e.g. `/integrations/google-fit/connect`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "kebab-case")]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+pub enum Provider {
+    GoogleFit,
+    Fitbit,
+}
+
+impl Provider {
+    pub const ALL: [Provider; 2] = [Provider::GoogleFit, Provider::Fitbit];
+
+    /// Source of the activity pulled from the provider.
+    pub fn source(self) -> ActivitySource {
+        match self {
+            Provider::GoogleFit => ActivitySource::GoogleFit,
+            Provider::Fitbit => ActivitySource::Fitbit,
+        }
+    }
+}
+
+#[axum::async_trait]
+pub trait IntegrationProvider: Send + Sync {
+    fn oauth(&self) -> &OAuthClient;
+
+    /// Activity that started between `from` and `to`.
+    async fn activity(
+        &self,
+        access_token: &str,
+        from: OffsetDateTime,
+        to: OffsetDateTime,
+    ) -> anyhow::Result<Vec<NewActivity>>;
+
+    /// Weigh-ins between `from` and `to`, for providers that track weight.
+    async fn weights(
+        &self,
+        _access_token: &str,
+        _from: OffsetDateTime,
+        _to: OffsetDateTime,
+    ) -> anyhow::Result<Vec<ImportedWeight>> {
+        Ok(Vec::new())
+    }
+}
+
+/// Clients for the configured providers.
+#[derive(Clone, Default)]
+pub struct Integrations {
+    google_fit: Option<Arc<dyn IntegrationProvider>>,
+    fitbit: Option<Arc<dyn IntegrationProvider>>,
+}
+
+impl Integrations {
+    pub fn from_config(config: &IntegrationsConfig) -> anyhow::Result<Self> {
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(config.timeout_secs))
+            .user_agent(USER_AGENT)
+            .build()?;
+        Ok(Self {
+            google_fit: config.google_fit.clone().map(|app| {
+                Arc::new(google_fit::GoogleFit::new(http.clone(), app))
+                    as Arc<dyn IntegrationProvider>
+            }),
+            fitbit: config.fitbit.clone().map(|app| {
+                Arc::new(fitbit::Fitbit::new(http.clone(), app)) as Arc<dyn IntegrationProvider>
+            }),
+        })
+    }
+
+    /// `None` when the provider isn't configured.
+    pub fn get(&self, provider: Provider) -> Option<Arc<dyn IntegrationProvider>> {
+        match provider {
+            Provider::GoogleFit => self.google_fit.clone(),
+            Provider::Fitbit => self.fitbit.clone(),
+        }
+    }
+}
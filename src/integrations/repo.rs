@@ -0,0 +1,169 @@
+use sqlx::{FromRow, PgExecutor, PgPool};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::integrations::{oauth::TokenSet, Provider};
+
+#[derive(Debug, Clone, FromRow)]
+pub struct Connection {
+    pub user_id: Uuid,
+    pub provider: Provider,
+    pub access_token: String,
+    pub refresh_token: Option<String>,
+    pub expires_at: OffsetDateTime,
+    pub synced_at: Option<OffsetDateTime>,
+    pub created_at: OffsetDateTime,
+}
+
+const CONNECTION_COLUMNS: &str =
+    "user_id, provider, access_token, refresh_token, expires_at, synced_at, created_at";
+
+pub async fn insert_state(
+    db: &PgPool,
+    state: &str,
+    user_id: Uuid,
+    provider: Provider,
+    expires_at: OffsetDateTime,
+) -> anyhow::Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO integration_states (state, user_id, provider, expires_at)
+        VALUES ($1, $2, $3, $4)
+        "#,
+    )
+    .bind(state)
+    .bind(user_id)
+    .bind(provider)
+    .bind(expires_at)
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+/// Consumes a pending authorization; `None` if unknown, expired or for
+/// another provider. Expired ones are dropped on the way.
+pub async fn take_state(
+    db: &PgPool,
+    state: &str,
+    provider: Provider,
+) -> anyhow::Result<Option<Uuid>> {
+    sqlx::query("DELETE FROM integration_states WHERE expires_at < NOW()")
+        .execute(db)
+        .await?;
+    let user_id = sqlx::query_scalar::<_, Uuid>(
+        r#"
+        DELETE FROM integration_states
+        WHERE state = $1 AND provider = $2
+        RETURNING user_id
+        "#,
+    )
+    .bind(state)
+    .bind(provider)
+    .fetch_optional(db)
+    .await?;
+    Ok(user_id)
+}
+
+/// Connects the user, or replaces the tokens of an existing connection.
+pub async fn upsert_connection(
+    db: &PgPool,
+    user_id: Uuid,
+    provider: Provider,
+    tokens: &TokenSet,
+) -> anyhow::Result<Connection> {
+    let connection = sqlx::query_as::<_, Connection>(&format!(
+        r#"
+        INSERT INTO integrations (user_id, provider, access_token, refresh_token, expires_at)
+        VALUES ($1, $2, $3, $4, $5)
+        ON CONFLICT (user_id, provider) DO UPDATE
+        SET access_token = EXCLUDED.access_token,
+            refresh_token = COALESCE(EXCLUDED.refresh_token, integrations.refresh_token),
+            expires_at = EXCLUDED.expires_at
+        RETURNING {}
+        "#,
+        CONNECTION_COLUMNS
+    ))
+    .bind(user_id)
+    .bind(provider)
+    .bind(&tokens.access_token)
+    .bind(&tokens.refresh_token)
+    .bind(tokens.expires_at())
+    .fetch_one(db)
+    .await?;
+    Ok(connection)
+}
+
+pub async fn find_connection(
+    db: &PgPool,
+    user_id: Uuid,
+    provider: Provider,
+) -> anyhow::Result<Option<Connection>> {
+    let connection = sqlx::query_as::<_, Connection>(&format!(
+        "SELECT {} FROM integrations WHERE user_id = $1 AND provider = $2",
+        CONNECTION_COLUMNS
+    ))
+    .bind(user_id)
+    .bind(provider)
+    .fetch_optional(db)
+    .await?;
+    Ok(connection)
+}
+
+pub async fn list_connections(db: &PgPool, user_id: Uuid) -> anyhow::Result<Vec<Connection>> {
+    let connections = sqlx::query_as::<_, Connection>(&format!(
+        "SELECT {} FROM integrations WHERE user_id = $1 ORDER BY provider",
+        CONNECTION_COLUMNS
+    ))
+    .bind(user_id)
+    .fetch_all(db)
+    .await?;
+    Ok(connections)
+}
+
+/// Every connection to `provider`, least recently synced first.
+pub async fn provider_connections(
+    db: &PgPool,
+    provider: Provider,
+) -> anyhow::Result<Vec<Connection>> {
+    let connections = sqlx::query_as::<_, Connection>(&format!(
+        r#"
+        SELECT {} FROM integrations
+        WHERE provider = $1
+        ORDER BY synced_at NULLS FIRST, user_id
+        "#,
+        CONNECTION_COLUMNS
+    ))
+    .bind(provider)
+    .fetch_all(db)
+    .await?;
+    Ok(connections)
+}
+
+pub async fn mark_synced(
+    db: impl PgExecutor<'_>,
+    user_id: Uuid,
+    provider: Provider,
+    synced_at: OffsetDateTime,
+) -> anyhow::Result<()> {
+    sqlx::query("UPDATE integrations SET synced_at = $3 WHERE user_id = $1 AND provider = $2")
+        .bind(user_id)
+        .bind(provider)
+        .bind(synced_at)
+        .execute(db)
+        .await?;
+    Ok(())
+}
+
+/// `false` if the user wasn't connected.
+pub async fn delete_connection(
+    db: &PgPool,
+    user_id: Uuid,
+    provider: Provider,
+) -> anyhow::Result<bool> {
+    let result = sqlx::query("DELETE FROM integrations WHERE user_id = $1 AND provider = $2")
+        .bind(user_id)
+        .bind(provider)
+        .execute(db)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
@@ -0,0 +1,297 @@
+use axum::http::StatusCode;
+use rand_core::{OsRng, RngCore};
+use time::{Duration, OffsetDateTime};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::{
+    activity::{
+        dto::{ActivitySource, ImportActivityRequest, NewActivity},
+        repo as activity_repo,
+    },
+    db::AppState,
+    error::ErrorCode,
+    integrations::{
+        dto::{CallbackQuery, ConnectResponse, IntegrationStatus, SyncResponse, STATE_TTL_MINUTES},
+        repo::{self, Connection},
+        IntegrationProvider, Provider,
+    },
+    weights::{dto::ImportedWeight, repo as weights_repo},
+};
+
+/// Tokens this close to expiring are refreshed before use.
+const REFRESH_MARGIN_SECS: i64 = 60;
+
+#[derive(Debug, thiserror::Error)]
+pub enum IntegrationError {
+    #[error("This integration is not configured")]
+    Disabled,
+    #[error("Not connected")]
+    NotConnected,
+    #[error("{0}")]
+    AuthFailed(String),
+    #[error("{0}")]
+    Invalid(String),
+    #[error("Provider unavailable")]
+    Upstream(#[source] anyhow::Error),
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+impl IntegrationError {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            IntegrationError::Disabled => StatusCode::SERVICE_UNAVAILABLE,
+            IntegrationError::NotConnected => StatusCode::NOT_FOUND,
+            IntegrationError::AuthFailed(_) | IntegrationError::Invalid(_) => {
+                StatusCode::BAD_REQUEST
+            }
+            IntegrationError::Upstream(_) => StatusCode::BAD_GATEWAY,
+            IntegrationError::Other(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            IntegrationError::Disabled => ErrorCode::IntegrationDisabled,
+            IntegrationError::NotConnected => ErrorCode::IntegrationNotConnected,
+            IntegrationError::AuthFailed(_) => ErrorCode::IntegrationAuthFailed,
+            IntegrationError::Invalid(_) => ErrorCode::ValidationFailed,
+            IntegrationError::Upstream(_) => ErrorCode::IntegrationUnavailable,
+            IntegrationError::Other(_) => ErrorCode::Internal,
+        }
+    }
+}
+
+fn client(
+    state: &AppState,
+    provider: Provider,
+) -> Result<std::sync::Arc<dyn IntegrationProvider>, IntegrationError> {
+    state
+        .integrations
+        .get(provider)
+        .ok_or(IntegrationError::Disabled)
+}
+
+/// Stores activity sent by an app, replacing entries imported before under
+/// the same `external_id`.
+pub async fn import_activity(
+    state: &AppState,
+    user_id: Uuid,
+    input: ImportActivityRequest,
+) -> Result<usize, IntegrationError> {
+    let (source, entries) = input.normalized().map_err(IntegrationError::Invalid)?;
+    save_activity(state, user_id, source, &entries).await?;
+    Ok(entries.len())
+}
+
+async fn save_activity(
+    state: &AppState,
+    user_id: Uuid,
+    source: ActivitySource,
+    entries: &[NewActivity],
+) -> anyhow::Result<()> {
+    let mut tx = state.begin_as(user_id).await?;
+    for entry in entries {
+        activity_repo::upsert_activity(&mut *tx, user_id, source, entry).await?;
+    }
+    tx.commit().await?;
+    if !entries.is_empty() {
+        // The daily summary's calorie budget includes burned calories.
+        state.cache.invalidate_summaries(user_id).await;
+    }
+    Ok(())
+}
+
+/// Saves what a sync pulled in one transaction and marks it as synced up to
+/// `synced_at`.
+async fn save_pulled(
+    state: &AppState,
+    connection: &Connection,
+    entries: &[NewActivity],
+    weights: &[ImportedWeight],
+    synced_at: OffsetDateTime,
+) -> anyhow::Result<()> {
+    let (user_id, provider) = (connection.user_id, connection.provider);
+    let mut tx = state.begin_as(user_id).await?;
+    for entry in entries {
+        activity_repo::upsert_activity(&mut *tx, user_id, provider.source(), entry).await?;
+    }
+    for weight in weights {
+        weights_repo::upsert_imported_weight(&mut *tx, user_id, provider, weight).await?;
+    }
+    repo::mark_synced(&mut *tx, user_id, provider, synced_at).await?;
+    tx.commit().await?;
+    if !entries.is_empty() {
+        // The daily summary's calorie budget includes burned calories.
+        state.cache.invalidate_summaries(user_id).await;
+    }
+    Ok(())
+}
+
+pub async fn list_integrations(
+    state: &AppState,
+    user_id: Uuid,
+) -> anyhow::Result<Vec<IntegrationStatus>> {
+    let connections = repo::list_connections(&state.db, user_id).await?;
+    Ok(connections.iter().map(IntegrationStatus::from).collect())
+}
+
+/// Starts authorizing: the returned URL leads the user to the provider,
+/// which redirects back to [`complete`].
+pub async fn connect(
+    state: &AppState,
+    user_id: Uuid,
+    provider: Provider,
+) -> Result<ConnectResponse, IntegrationError> {
+    let client = client(state, provider)?;
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    let oauth_state = hex::encode(bytes);
+    let expires_at = OffsetDateTime::now_utc() + Duration::minutes(STATE_TTL_MINUTES);
+    repo::insert_state(&state.db, &oauth_state, user_id, provider, expires_at).await?;
+    Ok(ConnectResponse {
+        authorize_url: client.oauth().authorize_url(&oauth_state)?,
+    })
+}
+
+/// Finishes authorizing with the code from the provider's redirect, then
+/// runs a first sync. A failing first sync leaves the connection in place
+/// for the scheduled one.
+pub async fn complete(
+    state: &AppState,
+    provider: Provider,
+    query: CallbackQuery,
+) -> Result<IntegrationStatus, IntegrationError> {
+    let client = client(state, provider)?;
+    let user_id = repo::take_state(&state.db, &query.state, provider)
+        .await?
+        .ok_or_else(|| IntegrationError::AuthFailed("Authorization expired; start again".into()))?;
+    if let Some(error) = query.error {
+        return Err(IntegrationError::AuthFailed(format!(
+            "Authorization failed: {}",
+            error
+        )));
+    }
+    let code = query
+        .code
+        .ok_or_else(|| IntegrationError::AuthFailed("Missing authorization code".into()))?;
+    let tokens = client
+        .oauth()
+        .exchange(&code)
+        .await
+        .map_err(IntegrationError::Upstream)?;
+    let mut connection = repo::upsert_connection(&state.db, user_id, provider, &tokens).await?;
+    info!(user_id = %user_id, provider = ?provider, "integration connected");
+    match sync_connection(state, client.as_ref(), connection.clone()).await {
+        Ok(sync) => connection.synced_at = Some(sync.synced_at),
+        Err(e) => warn!(error = ?e, user_id = %user_id, provider = ?provider, "first sync failed"),
+    }
+    Ok(IntegrationStatus::from(&connection))
+}
+
+pub async fn sync(
+    state: &AppState,
+    user_id: Uuid,
+    provider: Provider,
+) -> Result<SyncResponse, IntegrationError> {
+    let client = client(state, provider)?;
+    let connection = repo::find_connection(&state.db, user_id, provider)
+        .await?
+        .ok_or(IntegrationError::NotConnected)?;
+    sync_connection(state, client.as_ref(), connection).await
+}
+
+pub async fn disconnect(
+    state: &AppState,
+    user_id: Uuid,
+    provider: Provider,
+) -> Result<(), IntegrationError> {
+    if !repo::delete_connection(&state.db, user_id, provider).await? {
+        return Err(IntegrationError::NotConnected);
+    }
+    Ok(())
+}
+
+/// The connection's access token, refreshed first when it is about to
+/// expire.
+async fn access_token(
+    state: &AppState,
+    client: &dyn IntegrationProvider,
+    connection: &Connection,
+) -> Result<String, IntegrationError> {
+    let margin = Duration::seconds(REFRESH_MARGIN_SECS);
+    if connection.expires_at > OffsetDateTime::now_utc() + margin {
+        return Ok(connection.access_token.clone());
+    }
+    let refresh_token = connection
+        .refresh_token
+        .as_deref()
+        .ok_or_else(|| IntegrationError::AuthFailed("Access expired; connect again".into()))?;
+    let tokens = client
+        .oauth()
+        .refresh(refresh_token)
+        .await
+        .map_err(IntegrationError::Upstream)?;
+    repo::upsert_connection(&state.db, connection.user_id, connection.provider, &tokens).await?;
+    Ok(tokens.access_token)
+}
+
+/// Pulls activity and weigh-ins since the last sync, going back a day to pick up late
+/// uploads, and never further than `INTEGRATIONS_BACKFILL_DAYS`.
+async fn sync_connection(
+    state: &AppState,
+    client: &dyn IntegrationProvider,
+    connection: Connection,
+) -> Result<SyncResponse, IntegrationError> {
+    let token = access_token(state, client, &connection).await?;
+    let now = OffsetDateTime::now_utc();
+    let backfill = now - Duration::days(state.config.integrations.backfill_days);
+    let from = connection
+        .synced_at
+        .map_or(backfill, |at| (at - Duration::days(1)).max(backfill));
+    let entries: Vec<NewActivity> = client
+        .activity(&token, from, now)
+        .await
+        .map_err(IntegrationError::Upstream)?
+        .into_iter()
+        .filter_map(|entry| entry.normalized().ok())
+        .collect();
+    let weights: Vec<ImportedWeight> = client
+        .weights(&token, from, now)
+        .await
+        .map_err(IntegrationError::Upstream)?
+        .into_iter()
+        .filter(ImportedWeight::is_valid)
+        .collect();
+    save_pulled(state, &connection, &entries, &weights, now).await?;
+    Ok(SyncResponse {
+        imported: entries.len(),
+        weights: weights.len(),
+        synced_at: now,
+    })
+}
+
+/// Syncs every connection of every configured provider. One failing
+/// connection, e.g. with revoked access, doesn't stop the others.
+pub async fn sync_all(state: &AppState) -> anyhow::Result<()> {
+    for provider in Provider::ALL {
+        let Some(client) = state.integrations.get(provider) else {
+            continue;
+        };
+        let connections = repo::provider_connections(&state.db, provider).await?;
+        let (mut imported, mut failed) = (0, 0);
+        for connection in connections {
+            let user_id = connection.user_id;
+            match sync_connection(state, client.as_ref(), connection).await {
+                Ok(sync) => imported += sync.imported,
+                Err(e) => {
+                    failed += 1;
+                    warn!(error = ?e, user_id = %user_id, provider = ?provider, "activity sync failed");
+                }
+            }
+        }
+        info!(provider = ?provider, imported, failed, "activity synced");
+    }
+    Ok(())
+}
@@ -0,0 +1,59 @@
+//! Postgres-backed background jobs. Jobs are enqueued as JSON rows and picked
+//! up by worker tasks with `FOR UPDATE SKIP LOCKED`, so any number of API
+//! instances can share one queue.
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+pub mod repo;
+pub mod worker;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Job {
+    AnalyzeMeal {
+        meal_id: Uuid,
+        /// Analyze even photos that don't look like food, e.g. once someone
+        /// reviewed a meal left `needs_review`.
+        #[serde(default)]
+        skip_screening: bool,
+    },
+    ExportUserData {
+        export_id: Uuid,
+    },
+    /// Looks up the place name of a meal logged with coordinates only.
+    GeocodeMeal {
+        meal_id: Uuid,
+    },
+    /// Builds an anonymized dataset of all users' meals.
+    ResearchExport {
+        export_id: Uuid,
+    },
+}
+
+impl Job {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Job::AnalyzeMeal { .. } => "analyze_meal",
+            Job::ExportUserData { .. } => "export_user_data",
+            Job::GeocodeMeal { .. } => "geocode_meal",
+            Job::ResearchExport { .. } => "research_export",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn job_payload_round_trips() {
+        let job = Job::AnalyzeMeal {
+            meal_id: Uuid::new_v4(),
+            skip_screening: false,
+        };
+        let value = serde_json::to_value(&job).unwrap();
+        assert_eq!(value["kind"], job.kind());
+        assert_eq!(serde_json::from_value::<Job>(value).unwrap(), job);
+    }
+}
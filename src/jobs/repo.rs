@@ -0,0 +1,126 @@
+use sqlx::{FromRow, PgPool};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::jobs::Job;
+
+#[derive(Debug, Clone, FromRow)]
+pub struct JobRow {
+    pub id: Uuid,
+    pub kind: String,
+    pub payload: serde_json::Value,
+    pub attempts: i32,
+    pub max_attempts: i32,
+    /// When this claim took the job; results are only recorded while it
+    /// still holds.
+    pub locked_at: OffsetDateTime,
+}
+
+pub async fn enqueue(db: &PgPool, job: &Job, max_attempts: i32) -> anyhow::Result<Uuid> {
+    let id = sqlx::query_scalar::<_, Uuid>(
+        r#"
+        INSERT INTO jobs (kind, payload, max_attempts)
+        VALUES ($1, $2, $3)
+        RETURNING id
+        "#,
+    )
+    .bind(job.kind())
+    .bind(serde_json::to_value(job)?)
+    .bind(max_attempts)
+    .fetch_one(db)
+    .await?;
+    Ok(id)
+}
+
+/// Locks the next due job (or one whose worker died mid-run with attempts
+/// left) and marks it running. Concurrent workers skip rows another worker
+/// already holds.
+pub async fn claim_next(db: &PgPool, lock_timeout_secs: i64) -> anyhow::Result<Option<JobRow>> {
+    let job = sqlx::query_as::<_, JobRow>(
+        r#"
+        UPDATE jobs
+        SET status = 'running', attempts = attempts + 1, locked_at = NOW(), updated_at = NOW()
+        WHERE id = (
+            SELECT id FROM jobs
+            WHERE (status = 'queued' AND run_at <= NOW())
+               OR (status = 'running' AND locked_at < NOW() - make_interval(secs => $1)
+                   AND attempts < max_attempts)
+            ORDER BY run_at
+            FOR UPDATE SKIP LOCKED
+            LIMIT 1
+        )
+        RETURNING id, kind, payload, attempts, max_attempts, locked_at
+        "#,
+    )
+    .bind(lock_timeout_secs as f64)
+    .fetch_optional(db)
+    .await?;
+    Ok(job)
+}
+
+/// Parks as `failed` the jobs whose worker died during their last attempt,
+/// which `claim_next` no longer picks up, and returns them.
+pub async fn fail_abandoned(db: &PgPool, lock_timeout_secs: i64) -> anyhow::Result<Vec<JobRow>> {
+    let jobs = sqlx::query_as::<_, JobRow>(
+        r#"
+        UPDATE jobs
+        SET status = 'failed', last_error = 'worker stopped during the last attempt',
+            updated_at = NOW()
+        WHERE status = 'running'
+          AND locked_at < NOW() - make_interval(secs => $1)
+          AND attempts >= max_attempts
+        RETURNING id, kind, payload, attempts, max_attempts, locked_at
+        "#,
+    )
+    .bind(lock_timeout_secs as f64)
+    .fetch_all(db)
+    .await?;
+    Ok(jobs)
+}
+
+/// Marks the job claimed at `locked_at` done; `false` if the claim was lost
+/// to another worker in the meantime.
+pub async fn mark_done(db: &PgPool, id: Uuid, locked_at: OffsetDateTime) -> anyhow::Result<bool> {
+    let result = sqlx::query(
+        r#"
+        UPDATE jobs
+        SET status = 'done', locked_at = NULL, last_error = NULL, updated_at = NOW()
+        WHERE id = $1 AND status = 'running' AND locked_at = $2
+        "#,
+    )
+    .bind(id)
+    .bind(locked_at)
+    .execute(db)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Records a failed attempt of the claim taken at `locked_at`. With
+/// `retry_at` the job is queued again, otherwise it is parked as `failed`.
+/// `false` if the claim was lost to another worker in the meantime.
+pub async fn mark_failed(
+    db: &PgPool,
+    id: Uuid,
+    locked_at: OffsetDateTime,
+    error: &str,
+    retry_at: Option<OffsetDateTime>,
+) -> anyhow::Result<bool> {
+    let result = sqlx::query(
+        r#"
+        UPDATE jobs
+        SET status = CASE WHEN $4::timestamptz IS NULL THEN 'failed' ELSE 'queued' END,
+            run_at = COALESCE($4, run_at),
+            locked_at = NULL,
+            last_error = $3,
+            updated_at = NOW()
+        WHERE id = $1 AND status = 'running' AND locked_at = $2
+        "#,
+    )
+    .bind(id)
+    .bind(locked_at)
+    .bind(error)
+    .bind(retry_at)
+    .execute(db)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
@@ -0,0 +1,180 @@
+use std::time::Duration;
+
+use time::OffsetDateTime;
+use tracing::{error, info, warn};
+
+use crate::{
+    analysis,
+    config::JobsConfig,
+    db::AppState,
+    export, geocoding,
+    jobs::{
+        repo::{self, JobRow},
+        Job,
+    },
+    research,
+    tasks::{BackgroundTasks, TaskContext},
+};
+
+/// Starts `config.jobs.workers` polling tasks on the background runtime.
+pub fn register(tasks: &mut BackgroundTasks, state: &AppState) {
+    let workers = state.config.jobs.workers;
+    if workers == 0 {
+        info!("job workers disabled");
+        return;
+    }
+    for worker in 0..workers {
+        let state = state.clone();
+        tasks.spawn(format!("job-worker-{}", worker), move |ctx| {
+            run_worker(state, worker, ctx)
+        });
+    }
+    info!(workers, "job workers started");
+}
+
+/// Polls for jobs until shutdown. A job being processed when shutdown starts
+/// is finished first.
+async fn run_worker(state: AppState, worker: usize, mut ctx: TaskContext) {
+    let config = &state.config.jobs;
+    let idle = Duration::from_millis(config.poll_interval_ms);
+    while !ctx.is_shutting_down() {
+        match repo::claim_next(&state.db, config.lock_timeout_secs as i64).await {
+            Ok(Some(job)) => {
+                ctx.succeeded();
+                process(&state, worker, job).await;
+            }
+            Ok(None) => {
+                ctx.succeeded();
+                fail_abandoned(&state, config.lock_timeout_secs as i64).await;
+                ctx.sleep(idle).await;
+            }
+            Err(e) => {
+                error!(error = %e, worker, "claiming job failed");
+                ctx.failed(&e);
+                ctx.sleep(idle).await;
+            }
+        }
+    }
+}
+
+/// Gives up on jobs whose worker died during their last attempt, running the
+/// failure hooks a finished attempt would have.
+async fn fail_abandoned(state: &AppState, lock_timeout_secs: i64) {
+    let rows = match repo::fail_abandoned(&state.db, lock_timeout_secs).await {
+        Ok(rows) => rows,
+        Err(e) => {
+            error!(error = %e, "failing abandoned jobs failed");
+            return;
+        }
+    };
+    for row in rows {
+        warn!(job_id = %row.id, kind = %row.kind, attempt = row.attempts, "job abandoned");
+        if let Ok(job) = serde_json::from_value::<Job>(row.payload) {
+            if let Err(e) = on_failure(state, &job, false).await {
+                error!(error = %e, job_id = %row.id, "job failure hook failed");
+            }
+        }
+    }
+}
+
+async fn process(state: &AppState, worker: usize, row: JobRow) {
+    let job = serde_json::from_value::<Job>(row.payload.clone());
+    let result = match &job {
+        Ok(job) => run(state, job).await,
+        Err(e) => Err(anyhow::anyhow!("invalid {} payload: {}", row.kind, e)),
+    };
+    let recorded = match result {
+        Ok(()) => {
+            info!(job_id = %row.id, kind = %row.kind, worker, "job done");
+            repo::mark_done(&state.db, row.id, row.locked_at).await
+        }
+        Err(e) => {
+            let retry_at = (row.attempts < row.max_attempts)
+                .then(|| OffsetDateTime::now_utc() + retry_delay(&state.config.jobs, row.attempts));
+            warn!(
+                job_id = %row.id,
+                kind = %row.kind,
+                attempt = row.attempts,
+                retrying = retry_at.is_some(),
+                error = %e,
+                "job failed"
+            );
+            if let Ok(job) = &job {
+                if let Err(e) = on_failure(state, job, retry_at.is_some()).await {
+                    error!(error = %e, job_id = %row.id, "job failure hook failed");
+                }
+            }
+            repo::mark_failed(
+                &state.db,
+                row.id,
+                row.locked_at,
+                &format!("{:#}", e),
+                retry_at,
+            )
+            .await
+        }
+    };
+    match recorded {
+        Ok(true) => {}
+        // The lock timed out and another worker reclaimed the job.
+        Ok(false) => warn!(job_id = %row.id, kind = %row.kind, "job result dropped, claim lost"),
+        Err(e) => error!(error = %e, job_id = %row.id, "recording job result failed"),
+    }
+}
+
+async fn run(state: &AppState, job: &Job) -> anyhow::Result<()> {
+    match job {
+        Job::AnalyzeMeal {
+            meal_id,
+            skip_screening,
+        } => analysis::analyze_meal(state, *meal_id, *skip_screening).await,
+        Job::ExportUserData { export_id } => {
+            export::services::build_data_export(state, *export_id).await
+        }
+        Job::GeocodeMeal { meal_id } => geocoding::geocode_meal(state, *meal_id).await,
+        Job::ResearchExport { export_id } => {
+            research::services::build_research_export(state, *export_id).await
+        }
+    }
+}
+
+async fn on_failure(state: &AppState, job: &Job, retrying: bool) -> anyhow::Result<()> {
+    match job {
+        Job::AnalyzeMeal { meal_id, .. } => {
+            analysis::record_failure(state, *meal_id, retrying).await
+        }
+        Job::ExportUserData { export_id } => {
+            export::services::record_export_failure(state, *export_id, retrying).await
+        }
+        // The meal just keeps its coordinates without a place name.
+        Job::GeocodeMeal { .. } => Ok(()),
+        Job::ResearchExport { export_id } => {
+            research::services::record_export_failure(state, *export_id, retrying).await
+        }
+    }
+}
+
+/// Delay after the `attempt`-th failure: `retry_base * 2^(attempt - 1)`,
+/// capped at one hour.
+pub fn retry_delay(config: &JobsConfig, attempt: i32) -> Duration {
+    let exp = attempt.saturating_sub(1).clamp(0, 16) as u32;
+    Duration::from_secs(config.retry_base_secs.saturating_mul(1 << exp).min(3600))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retry_delay_doubles_and_caps() {
+        let config = JobsConfig {
+            retry_base_secs: 10,
+            ..JobsConfig::default()
+        };
+        let delays: Vec<u64> = [1, 2, 3, 20]
+            .iter()
+            .map(|a| retry_delay(&config, *a).as_secs())
+            .collect();
+        assert_eq!(delays, vec![10, 20, 40, 3600]);
+    }
+}
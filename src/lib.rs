@@ -0,0 +1,66 @@
+//! MealMind: the API server (`mealmind`) and the operations CLI
+//! (`mealmind-admin`) are both built on this crate.
+
+pub mod achievements;
+pub mod activity;
+pub mod admin;
+pub mod ai_usage;
+pub mod analysis;
+pub mod analytics;
+pub mod app;
+pub mod auth;
+pub mod billing;
+pub mod breaker;
+pub mod cache;
+pub mod coaching;
+pub mod config;
+pub mod cron;
+pub mod db;
+pub mod deprecation;
+pub mod error;
+pub mod events;
+pub mod export;
+pub mod flags;
+pub mod foods;
+pub mod geocoding;
+pub mod goals;
+pub mod households;
+pub mod i18n;
+pub mod idempotency;
+pub mod images;
+pub mod imports;
+pub mod integrations;
+pub mod jobs;
+pub mod load_shed;
+pub mod meal_items;
+pub mod meals;
+pub mod notifications;
+pub mod orgs;
+pub mod photos;
+pub mod plans;
+pub mod preferences;
+pub mod progress;
+pub mod push;
+pub mod reactions;
+pub mod realtime;
+pub mod recipes;
+pub mod recommendations;
+pub mod request_id;
+pub mod research;
+pub mod retention;
+pub mod routes;
+pub mod security_headers;
+pub mod seed;
+pub mod shares;
+pub mod stats;
+pub mod storage;
+pub mod summary;
+pub mod sync;
+pub mod tasks;
+pub mod templates;
+#[cfg(all(test, feature = "test-support"))]
+mod test_support;
+pub mod units;
+pub mod uploads;
+pub mod webhooks;
+pub mod weights;
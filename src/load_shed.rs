@@ -0,0 +1,92 @@
+//! Load shedding: requests beyond a budget of in-flight requests are turned
+//! away with `503` and `Retry-After` instead of queueing until they time
+//! out. Uploads hold their own, smaller budget on top of the general one,
+//! since each of them buffers megabytes of images and runs the transcoder.
+//!
+//! A permit is held until the handler returns its response; streamed
+//! bodies, like photo downloads, don't count while they are sent.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Request, State},
+    http::{header::RETRY_AFTER, HeaderValue},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use tokio::sync::Semaphore;
+use tracing::debug;
+
+use crate::error::ApiError;
+
+pub struct InFlightLimit {
+    budget: &'static str,
+    permits: Semaphore,
+    retry_after: HeaderValue,
+}
+
+impl InFlightLimit {
+    /// `max` of 0 means no limit.
+    pub fn new(budget: &'static str, max: usize, retry_after_secs: u64) -> Arc<Self> {
+        let max = if max == 0 {
+            Semaphore::MAX_PERMITS
+        } else {
+            max
+        };
+        Arc::new(Self {
+            budget,
+            permits: Semaphore::new(max),
+            retry_after: HeaderValue::from(retry_after_secs),
+        })
+    }
+}
+
+/// Middleware, e.g. `from_fn_with_state(limit, load_shed::shed)`.
+pub async fn shed(State(limit): State<Arc<InFlightLimit>>, req: Request, next: Next) -> Response {
+    let Ok(_permit) = limit.permits.try_acquire() else {
+        debug!(budget = limit.budget, path = %req.uri().path(), "shedding request");
+        let mut response =
+            ApiError::Unavailable("Server busy, try again shortly".to_string()).into_response();
+        response
+            .headers_mut()
+            .insert(RETRY_AFTER, limit.retry_after.clone());
+        return response;
+    };
+    next.run(req).await
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::Body, http::StatusCode, middleware::from_fn_with_state, routing::get, Router,
+    };
+    use tokio::sync::Notify;
+    use tower::ServiceExt;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn sheds_requests_over_the_budget() {
+        let release = Arc::new(Notify::new());
+        let held = release.clone();
+        let limit = InFlightLimit::new("test", 1, 2);
+        let app = Router::new()
+            .route("/slow", get(move || async move { held.notified().await }))
+            .route("/fast", get(|| async {}))
+            .layer(from_fn_with_state(limit.clone(), shed));
+        let get = |path| Request::get(path).body(Body::empty()).unwrap();
+
+        let slow = tokio::spawn(app.clone().oneshot(get("/slow")));
+        while limit.permits.available_permits() > 0 {
+            tokio::task::yield_now().await;
+        }
+        let busy = app.clone().oneshot(get("/fast")).await.unwrap();
+        assert_eq!(busy.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(busy.headers()[RETRY_AFTER], "2");
+
+        release.notify_one();
+        assert_eq!(slow.await.unwrap().unwrap().status(), StatusCode::OK);
+        let after = app.oneshot(get("/fast")).await.unwrap();
+        assert_eq!(after.status(), StatusCode::OK);
+    }
+}
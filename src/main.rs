@@ -1,14 +1,7 @@
-use std::net::SocketAddr;
+use std::time::Duration;
 
-use axum::{routing::get, Router};
-use tower_http::{cors::CorsLayer, trace::TraceLayer};
-
-mod auth;
-mod config;
-mod db;
-mod routes;
-
-use crate::routes::{auth::auth_routes, me::me_route};
+use anyhow::Context;
+use mealmind::{app, cron, db, events, flags, jobs, seed, tasks, webhooks};
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
@@ -32,48 +25,71 @@ async fn main() -> anyhow::Result<()> {
 
     let app_state = db::AppState::init().await?;
 
-    // Run migrations if present
-    if let Err(e) = sqlx::migrate!("./migrations").run(&app_state.db).await {
-        tracing::warn!(error = %e, "migrations folder not found or migration failed; continuing");
+    if let Err(e) = db::MIGRATOR.run(&app_state.db).await {
+        if app_state.config.strict_migrations {
+            return Err(e).context("run migrations");
+        }
+        tracing::warn!(error = %e, "migration failed; continuing since STRICT_MIGRATIONS is off");
     }
 
-    let app = Router::new()
-        .merge(auth_routes())
-        .route("/me", get(me_route))
-        .with_state(app_state)
-        .layer(CorsLayer::permissive())
-        .layer(
-            TraceLayer::new_for_http()
-                .make_span_with(|req: &axum::http::Request<_>| {
-                    let method = req.method().clone();
-                    let uri = req.uri().clone();
-                    tracing::info_span!("http_request", %method, uri = %uri)
-                })
-                .on_response(
-                    |res: &axum::http::Response<_>,
-                     _latency: std::time::Duration,
-                     span: &tracing::Span| {
-                        let status = res.status();
-                        span.record("status", tracing::field::display(status));
-                        if status.is_server_error() {
-                            tracing::error!(%status, "response");
-                        } else {
-                            tracing::info!(%status, "response");
-                        }
-                    },
-                ),
+    if app_state.config.row_level_security
+        && db::bypasses_row_level_security(&app_state.db)
+            .await
+            .context("check database role")?
+    {
+        anyhow::bail!(
+            "DB_ROW_LEVEL_SECURITY is set but the database role bypasses row-level security; \
+             connect as a role without SUPERUSER or BYPASSRLS"
         );
+    }
+
+    if std::env::args().skip(1).any(|arg| arg == "--seed") {
+        seed::run(&app_state).await.context("seed demo data")?;
+    }
 
-    let addr: SocketAddr = format!(
-        "{}:{}",
-        std::env::var("APP_HOST").unwrap_or_else(|_| "0.0.0.0".into()),
-        std::env::var("APP_PORT").unwrap_or_else(|_| "8080".into())
-    )
-    .parse()?;
+    let mut tasks = tasks::BackgroundTasks::new(app_state.tasks.clone());
+    jobs::worker::register(&mut tasks, &app_state);
+    cron::register(&mut tasks, &app_state)?;
+    flags::register(&mut tasks, &app_state);
+    let subscribers = events::subscribers(&app_state).context("init event subscribers")?;
+    events::relay::register(&mut tasks, &app_state, subscribers);
+    webhooks::delivery::register(&mut tasks, &app_state).context("init webhook delivery")?;
+    let shutdown_grace = Duration::from_secs(app_state.config.tasks.shutdown_grace_secs);
+    let http = app_state.config.http.clone();
 
-    tracing::info!("listening on {}", addr);
-    let listener = tokio::net::TcpListener::bind(addr).await?;
-    axum::serve(listener, app).await?;
+    let app = app::build_app(app_state);
+    app::serve(app, &http, shutdown_signal()).await?;
+    tasks.shutdown(shutdown_grace).await;
 
     Ok(())
 }
+
+/// Resolves on Ctrl-C or SIGTERM.
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            tracing::error!(error = %e, "failed to listen for ctrl-c");
+            std::future::pending::<()>().await;
+        }
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(e) => {
+                tracing::error!(error = %e, "failed to listen for SIGTERM");
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+    tracing::info!("shutting down");
+}
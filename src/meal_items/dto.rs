@@ -0,0 +1,211 @@
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::{
+    foods::dto::{is_valid_gtin, FoodNutrition, FoodSource},
+    units::Units,
+};
+
+pub use crate::units::ItemUnit;
+
+pub const MAX_ITEM_NAME_LEN: usize = 200;
+/// Upper bounds for `quantity`, in grams (or millilitres) and servings.
+pub const MAX_ITEM_GRAMS: f64 = 10_000.0;
+pub const MAX_ITEM_SERVINGS: f64 = 100.0;
+/// Sanity cap for hand-entered item nutrition values.
+pub const MAX_ITEM_VALUE: f64 = 100_000.0;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct FoodRef {
+    pub source: FoodSource,
+    pub id: String,
+}
+
+/// Body of create and replace. Items either reference a food, whose
+/// nutrition is scaled to the quantity, or are free text with optional
+/// nutrition for the whole quantity.
+#[derive(Debug, Deserialize)]
+pub struct MealItemRequest {
+    pub food: Option<FoodRef>,
+    /// Defaults to the food's name for food references.
+    pub name: Option<String>,
+    pub quantity: f64,
+    pub unit: ItemUnit,
+    pub nutrition: Option<FoodNutrition>,
+}
+
+impl MealItemRequest {
+    /// Trims the name and checks quantity and nutrition ranges.
+    pub fn normalized(self) -> Result<Self, String> {
+        let max_quantity = self.unit.max_quantity(MAX_ITEM_GRAMS, MAX_ITEM_SERVINGS);
+        if !self.quantity.is_finite() || self.quantity <= 0.0 || self.quantity > max_quantity {
+            return Err(format!(
+                "quantity must be greater than 0 and at most {}",
+                max_quantity
+            ));
+        }
+        let name = self
+            .name
+            .map(|n| n.trim().to_string())
+            .filter(|n| !n.is_empty());
+        if name
+            .as_ref()
+            .is_some_and(|n| n.chars().count() > MAX_ITEM_NAME_LEN)
+        {
+            return Err(format!(
+                "name must be at most {} characters",
+                MAX_ITEM_NAME_LEN
+            ));
+        }
+        match (&self.food, &self.nutrition) {
+            (Some(_), Some(_)) => {
+                return Err("nutrition is taken from the food; send one or the other".into())
+            }
+            (Some(food), None) if food.id.trim().is_empty() => {
+                return Err("food.id must not be empty".into())
+            }
+            (Some(food), None)
+                if food.source == FoodSource::OpenFoodFacts && !is_valid_gtin(food.id.trim()) =>
+            {
+                return Err("food.id must be a valid barcode".into())
+            }
+            (None, _) if name.is_none() => return Err("name is required without a food".into()),
+            _ => {}
+        }
+        if let Some(n) = &self.nutrition {
+            let values = [
+                n.total_calories_kcal,
+                n.protein_g,
+                n.fat_g,
+                n.carbs_g,
+                n.sodium_mg,
+                n.sugar_g,
+                n.fiber_g,
+            ];
+            if values
+                .into_iter()
+                .flatten()
+                .any(|v| !v.is_finite() || !(0.0..=MAX_ITEM_VALUE).contains(&v))
+            {
+                return Err(format!(
+                    "nutrition values must be between 0 and {}",
+                    MAX_ITEM_VALUE
+                ));
+            }
+        }
+        let food = self.food.map(|f| FoodRef {
+            id: f.id.trim().to_string(),
+            ..f
+        });
+        Ok(Self { food, name, ..self })
+    }
+}
+
+/// Resolved item, ready to store. Templates keep these as JSON snapshots.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewMealItem {
+    pub food_source: Option<FoodSource>,
+    pub food_id: Option<String>,
+    pub name: String,
+    pub quantity: f64,
+    pub unit: ItemUnit,
+    pub nutrition: FoodNutrition,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct MealItem {
+    pub id: Uuid,
+    pub food_source: Option<FoodSource>,
+    pub food_id: Option<String>,
+    pub name: String,
+    pub quantity: f64,
+    pub unit: ItemUnit,
+    /// For the item's quantity.
+    #[sqlx(flatten)]
+    pub nutrition: FoodNutrition,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub updated_at: OffsetDateTime,
+}
+
+impl MealItem {
+    pub fn in_units(self, units: Units) -> Self {
+        let (unit, quantity) = self.unit.in_units(self.quantity, units);
+        Self {
+            unit,
+            quantity,
+            ..self
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn free_text(quantity: f64, unit: ItemUnit) -> MealItemRequest {
+        MealItemRequest {
+            food: None,
+            name: Some("  Apple ".into()),
+            quantity,
+            unit,
+            nutrition: None,
+        }
+    }
+
+    #[test]
+    fn trims_name() {
+        let item = free_text(150.0, ItemUnit::G).normalized().unwrap();
+        assert_eq!(item.name.as_deref(), Some("Apple"));
+    }
+
+    #[test]
+    fn caps_quantity_by_unit() {
+        assert!(free_text(0.0, ItemUnit::G).normalized().is_err());
+        assert!(free_text(f64::NAN, ItemUnit::G).normalized().is_err());
+        assert!(free_text(500.0, ItemUnit::Ml).normalized().is_ok());
+        assert!(free_text(500.0, ItemUnit::Serving).normalized().is_err());
+        assert!(free_text(20.0, ItemUnit::Lb).normalized().is_ok());
+        assert!(free_text(23.0, ItemUnit::Lb).normalized().is_err());
+    }
+
+    #[test]
+    fn requires_name_without_food() {
+        let mut item = free_text(1.0, ItemUnit::Serving);
+        item.name = Some("   ".into());
+        assert!(item.normalized().is_err());
+    }
+
+    #[test]
+    fn food_and_nutrition_are_exclusive() {
+        let mut item = free_text(1.0, ItemUnit::Serving);
+        item.food = Some(FoodRef {
+            source: FoodSource::Usda,
+            id: "171688".into(),
+        });
+        item.nutrition = Some(FoodNutrition::default());
+        assert!(item.normalized().is_err());
+    }
+
+    #[test]
+    fn checks_barcodes() {
+        let mut item = free_text(30.0, ItemUnit::G);
+        item.food = Some(FoodRef {
+            source: FoodSource::OpenFoodFacts,
+            id: "3017620422004".into(),
+        });
+        assert!(item.normalized().is_err());
+    }
+
+    #[test]
+    fn rejects_negative_nutrition() {
+        let mut item = free_text(1.0, ItemUnit::Serving);
+        item.nutrition = Some(FoodNutrition {
+            protein_g: Some(-1.0),
+            ..Default::default()
+        });
+        assert!(item.normalized().is_err());
+    }
+}
@@ -0,0 +1,131 @@
+use sqlx::{PgExecutor, PgPool};
+use uuid::Uuid;
+
+use crate::{
+    foods::dto::FoodNutrition,
+    meal_items::dto::{MealItem, NewMealItem},
+};
+
+const ITEM_COLUMNS: &str = r#"
+    id, food_source, food_id, name, quantity::float8 AS quantity, unit,
+    total_calories_kcal::float8 AS total_calories_kcal,
+    protein_g::float8 AS protein_g,
+    fat_g::float8 AS fat_g,
+    carbs_g::float8 AS carbs_g,
+    sodium_mg::float8 AS sodium_mg,
+    sugar_g::float8 AS sugar_g,
+    fiber_g::float8 AS fiber_g,
+    created_at, updated_at
+"#;
+
+pub async fn list_for_meal(db: &PgPool, meal_id: Uuid) -> anyhow::Result<Vec<MealItem>> {
+    let items = sqlx::query_as::<_, MealItem>(&format!(
+        "SELECT {ITEM_COLUMNS} FROM meal_items WHERE meal_id = $1 ORDER BY created_at, id"
+    ))
+    .bind(meal_id)
+    .fetch_all(db)
+    .await?;
+    Ok(items)
+}
+
+pub async fn insert_item(
+    db: impl PgExecutor<'_>,
+    meal_id: Uuid,
+    item: &NewMealItem,
+) -> anyhow::Result<MealItem> {
+    let item = sqlx::query_as::<_, MealItem>(&format!(
+        r#"
+        INSERT INTO meal_items (meal_id, food_source, food_id, name, quantity, unit,
+                                total_calories_kcal, protein_g, fat_g, carbs_g,
+                                sodium_mg, sugar_g, fiber_g)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+        RETURNING {ITEM_COLUMNS}
+        "#
+    ))
+    .bind(meal_id)
+    .bind(item.food_source)
+    .bind(&item.food_id)
+    .bind(&item.name)
+    .bind(item.quantity)
+    .bind(item.unit)
+    .bind(item.nutrition.total_calories_kcal)
+    .bind(item.nutrition.protein_g)
+    .bind(item.nutrition.fat_g)
+    .bind(item.nutrition.carbs_g)
+    .bind(item.nutrition.sodium_mg)
+    .bind(item.nutrition.sugar_g)
+    .bind(item.nutrition.fiber_g)
+    .fetch_one(db)
+    .await?;
+    Ok(item)
+}
+
+/// Replaces an item; `None` if it doesn't belong to the meal.
+pub async fn update_item(
+    db: &PgPool,
+    meal_id: Uuid,
+    item_id: Uuid,
+    item: &NewMealItem,
+) -> anyhow::Result<Option<MealItem>> {
+    let item = sqlx::query_as::<_, MealItem>(&format!(
+        r#"
+        UPDATE meal_items
+        SET food_source = $3, food_id = $4, name = $5, quantity = $6, unit = $7,
+            total_calories_kcal = $8, protein_g = $9, fat_g = $10, carbs_g = $11,
+            sodium_mg = $12, sugar_g = $13, fiber_g = $14, updated_at = NOW()
+        WHERE id = $2 AND meal_id = $1
+        RETURNING {ITEM_COLUMNS}
+        "#
+    ))
+    .bind(meal_id)
+    .bind(item_id)
+    .bind(item.food_source)
+    .bind(&item.food_id)
+    .bind(&item.name)
+    .bind(item.quantity)
+    .bind(item.unit)
+    .bind(item.nutrition.total_calories_kcal)
+    .bind(item.nutrition.protein_g)
+    .bind(item.nutrition.fat_g)
+    .bind(item.nutrition.carbs_g)
+    .bind(item.nutrition.sodium_mg)
+    .bind(item.nutrition.sugar_g)
+    .bind(item.nutrition.fiber_g)
+    .fetch_optional(db)
+    .await?;
+    Ok(item)
+}
+
+pub async fn delete_item(db: &PgPool, meal_id: Uuid, item_id: Uuid) -> anyhow::Result<bool> {
+    let result = sqlx::query(r#"DELETE FROM meal_items WHERE id = $1 AND meal_id = $2"#)
+        .bind(item_id)
+        .bind(meal_id)
+        .execute(db)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Summed nutrition of all items, or `None` if the meal has none.
+pub async fn sum_for_meal(
+    db: impl PgExecutor<'_>,
+    meal_id: Uuid,
+) -> anyhow::Result<Option<FoodNutrition>> {
+    let sum = sqlx::query_as::<_, FoodNutrition>(
+        r#"
+        SELECT SUM(total_calories_kcal)::float8 AS total_calories_kcal,
+               SUM(protein_g)::float8 AS protein_g,
+               SUM(fat_g)::float8 AS fat_g,
+               SUM(carbs_g)::float8 AS carbs_g,
+               SUM(sodium_mg)::float8 AS sodium_mg,
+               SUM(sugar_g)::float8 AS sugar_g,
+               SUM(fiber_g)::float8 AS fiber_g
+        FROM meal_items
+        WHERE meal_id = $1
+        HAVING COUNT(*) > 0
+        "#,
+    )
+    .bind(meal_id)
+    .fetch_optional(db)
+    .await?;
+    Ok(sum)
+}
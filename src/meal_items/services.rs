@@ -0,0 +1,263 @@
+use axum::http::StatusCode;
+use uuid::Uuid;
+
+use crate::{
+    db::{AppState, RepoError},
+    error::ErrorCode,
+    foods::{
+        dto::{Food, FoodNutrition},
+        services::{self as food_services, SourceUnavailable, UsdaDisabled},
+    },
+    meal_items::{
+        dto::{ItemUnit, MealItem, MealItemRequest, NewMealItem},
+        repo,
+    },
+    meals::{repo as meals_repo, services as meals_services},
+    realtime::MealEvent,
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum ItemError {
+    #[error("Meal not found")]
+    MealNotFound,
+    #[error("Item not found")]
+    ItemNotFound,
+    #[error("Food not found")]
+    FoodNotFound,
+    #[error("Food has no serving size; use g or ml")]
+    NoServingSize,
+    #[error("Food has no per-gram values; use serving")]
+    NoGramWeight,
+    #[error("USDA food data is not configured")]
+    UsdaDisabled,
+    #[error("Food database unavailable")]
+    Upstream(#[source] anyhow::Error),
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+impl From<RepoError> for ItemError {
+    fn from(e: RepoError) -> Self {
+        ItemError::Other(e.into())
+    }
+}
+
+impl ItemError {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            ItemError::MealNotFound | ItemError::ItemNotFound => StatusCode::NOT_FOUND,
+            ItemError::FoodNotFound | ItemError::NoServingSize | ItemError::NoGramWeight => {
+                StatusCode::UNPROCESSABLE_ENTITY
+            }
+            ItemError::UsdaDisabled => StatusCode::SERVICE_UNAVAILABLE,
+            ItemError::Upstream(_) => StatusCode::BAD_GATEWAY,
+            ItemError::Other(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            ItemError::MealNotFound => ErrorCode::MealNotFound,
+            ItemError::ItemNotFound => ErrorCode::MealItemNotFound,
+            ItemError::FoodNotFound => ErrorCode::FoodNotFound,
+            ItemError::NoServingSize | ItemError::NoGramWeight => ErrorCode::FoodUnitUnsupported,
+            ItemError::UsdaDisabled => ErrorCode::FoodSourceDisabled,
+            ItemError::Upstream(_) => ErrorCode::FoodSourceUnavailable,
+            ItemError::Other(_) => ErrorCode::Internal,
+        }
+    }
+}
+
+/// Nutrition of `quantity` of `food`. Volumes are treated as the same
+/// number of grams as they have millilitres.
+pub fn item_nutrition(
+    food: &Food,
+    quantity: f64,
+    unit: ItemUnit,
+) -> Result<FoodNutrition, ItemError> {
+    match unit.metric_amount(quantity) {
+        Some(_) if food.per_100g.is_empty() && food.per_serving.is_some() => {
+            Err(ItemError::NoGramWeight)
+        }
+        Some(grams) => Ok(food.per_100g.scaled(grams / 100.0)),
+        None => match (food.serving_size_g, &food.per_serving) {
+            (Some(grams), _) => Ok(food.per_100g.scaled(grams * quantity / 100.0)),
+            (None, Some(per_serving)) => Ok(per_serving.scaled(quantity)),
+            (None, None) => Err(ItemError::NoServingSize),
+        },
+    }
+}
+
+/// Looks up the referenced food, if any, and computes the item's nutrition.
+/// Recipe ingredients are resolved the same way.
+pub async fn resolve_item(
+    state: &AppState,
+    user_id: Uuid,
+    input: MealItemRequest,
+) -> Result<NewMealItem, ItemError> {
+    let Some(food_ref) = input.food else {
+        return Ok(NewMealItem {
+            food_source: None,
+            food_id: None,
+            // `normalized` requires a name for free-text items.
+            name: input.name.unwrap_or_default(),
+            quantity: input.quantity,
+            unit: input.unit,
+            nutrition: input.nutrition.unwrap_or_default(),
+        });
+    };
+    let food = match food_services::find_food(state, user_id, food_ref.source, &food_ref.id).await {
+        Ok(Some(food)) => food,
+        Ok(None) => return Err(ItemError::FoodNotFound),
+        Err(e) if e.is::<UsdaDisabled>() => return Err(ItemError::UsdaDisabled),
+        Err(e) if e.is::<SourceUnavailable>() => return Err(ItemError::Upstream(e)),
+        Err(e) => return Err(ItemError::Other(e)),
+    };
+    Ok(NewMealItem {
+        nutrition: item_nutrition(&food, input.quantity, input.unit)?,
+        food_source: Some(food.source),
+        food_id: Some(food.id),
+        name: input.name.unwrap_or(food.name),
+        quantity: input.quantity,
+        unit: input.unit,
+    })
+}
+
+async fn ensure_meal(state: &AppState, user_id: Uuid, meal_id: Uuid) -> Result<(), ItemError> {
+    match meals_repo::find_meal(&state.db, user_id, meal_id).await? {
+        Some(_) => Ok(()),
+        None => Err(ItemError::MealNotFound),
+    }
+}
+
+/// Recomputes the meal totals after an item change and notifies listeners.
+async fn items_changed(state: &AppState, user_id: Uuid, meal_id: Uuid) -> anyhow::Result<()> {
+    meals_services::refresh_nutrition(state, user_id, meal_id).await?;
+    state
+        .events
+        .publish(user_id, MealEvent::NutritionUpdated { meal_id });
+    Ok(())
+}
+
+pub async fn list_items(
+    state: &AppState,
+    user_id: Uuid,
+    meal_id: Uuid,
+) -> Result<Vec<MealItem>, ItemError> {
+    ensure_meal(state, user_id, meal_id).await?;
+    Ok(repo::list_for_meal(&state.db, meal_id).await?)
+}
+
+pub async fn add_item(
+    state: &AppState,
+    user_id: Uuid,
+    meal_id: Uuid,
+    input: MealItemRequest,
+) -> Result<MealItem, ItemError> {
+    ensure_meal(state, user_id, meal_id).await?;
+    let item = resolve_item(state, user_id, input).await?;
+    let item = repo::insert_item(&state.db, meal_id, &item).await?;
+    items_changed(state, user_id, meal_id).await?;
+    Ok(item)
+}
+
+pub async fn replace_item(
+    state: &AppState,
+    user_id: Uuid,
+    meal_id: Uuid,
+    item_id: Uuid,
+    input: MealItemRequest,
+) -> Result<MealItem, ItemError> {
+    ensure_meal(state, user_id, meal_id).await?;
+    let item = resolve_item(state, user_id, input).await?;
+    let item = repo::update_item(&state.db, meal_id, item_id, &item)
+        .await?
+        .ok_or(ItemError::ItemNotFound)?;
+    items_changed(state, user_id, meal_id).await?;
+    Ok(item)
+}
+
+pub async fn delete_item(
+    state: &AppState,
+    user_id: Uuid,
+    meal_id: Uuid,
+    item_id: Uuid,
+) -> Result<(), ItemError> {
+    ensure_meal(state, user_id, meal_id).await?;
+    if !repo::delete_item(&state.db, meal_id, item_id).await? {
+        return Err(ItemError::ItemNotFound);
+    }
+    items_changed(state, user_id, meal_id).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::foods::dto::FoodSource;
+    use crate::units::GRAMS_PER_OZ;
+
+    fn food(serving_size_g: Option<f64>, per_serving: Option<FoodNutrition>) -> Food {
+        Food {
+            source: FoodSource::OpenFoodFacts,
+            id: "3017620422003".into(),
+            name: "Spread".into(),
+            brand: None,
+            barcode: None,
+            serving_size_g,
+            serving_description: None,
+            per_100g: FoodNutrition {
+                total_calories_kcal: Some(539.0),
+                protein_g: Some(6.3),
+                ..Default::default()
+            },
+            per_serving,
+        }
+    }
+
+    #[test]
+    fn scales_grams_from_per_100g() {
+        let n = item_nutrition(&food(None, None), 15.0, ItemUnit::G).unwrap();
+        assert_eq!(n.total_calories_kcal, Some(80.85));
+        assert_eq!(n.protein_g, Some(0.95));
+        assert_eq!(n.fat_g, None);
+    }
+
+    #[test]
+    fn imperial_units_scale_like_their_grams() {
+        let food = food(None, None);
+        let ounce = item_nutrition(&food, 1.0, ItemUnit::Oz).unwrap();
+        let grams = item_nutrition(&food, GRAMS_PER_OZ, ItemUnit::G).unwrap();
+        assert_eq!(ounce, grams);
+    }
+
+    #[test]
+    fn servings_prefer_serving_size() {
+        let n = item_nutrition(&food(Some(15.0), None), 2.0, ItemUnit::Serving).unwrap();
+        assert_eq!(n.total_calories_kcal, Some(161.7));
+    }
+
+    #[test]
+    fn servings_fall_back_to_per_serving() {
+        let per_serving = FoodNutrition {
+            total_calories_kcal: Some(100.0),
+            ..Default::default()
+        };
+        let n = item_nutrition(&food(None, Some(per_serving)), 1.5, ItemUnit::Serving).unwrap();
+        assert_eq!(n.total_calories_kcal, Some(150.0));
+    }
+
+    #[test]
+    fn grams_need_per_100g_values() {
+        let mut custom = food(None, Some(FoodNutrition::default()));
+        custom.per_100g = FoodNutrition::default();
+        let err = item_nutrition(&custom, 100.0, ItemUnit::G).unwrap_err();
+        assert!(matches!(err, ItemError::NoGramWeight));
+    }
+
+    #[test]
+    fn servings_need_a_size() {
+        let err = item_nutrition(&food(None, None), 1.0, ItemUnit::Serving).unwrap_err();
+        assert!(matches!(err, ItemError::NoServingSize));
+    }
+}
@@ -0,0 +1,180 @@
+//! Diet profile compliance: a 0–100 rating of how well a meal, or a whole
+//! day, fits the diet the user follows, from its macros and the ingredients
+//! the analysis found. Unlike the global score it depends on the user's
+//! preferences, so it is computed when read rather than stored.
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::{
+    goals::dto::Intake,
+    meals::{
+        score::{at_least, at_most},
+        warnings::{Diet, Ingredient},
+    },
+};
+
+/// Daily sodium limit of the low-sodium profile, in mg.
+pub const LOW_SODIUM_DAILY_MG: f64 = 1500.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type, ToSchema)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+pub enum DietProfile {
+    Keto,
+    Vegan,
+    Mediterranean,
+    LowSodium,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct DietCompliance {
+    pub profile: DietProfile,
+    /// 0–100, rounded to one decimal.
+    pub score: f64,
+}
+
+/// Shares of energy from carbs, fat and protein; `None` unless all three
+/// are known and add up to something.
+fn energy_shares(intake: &Intake) -> Option<(f64, f64, f64)> {
+    let carbs = intake.carbs_g? * 4.0;
+    let fat = intake.fat_g? * 9.0;
+    let protein = intake.protein_g? * 4.0;
+    let total = carbs + fat + protein;
+    (total > 0.0).then(|| (carbs / total, fat / total, protein / total))
+}
+
+/// 1 from `low` to `high`, falling linearly to 0 `slack` beyond either end.
+fn within(value: f64, low: f64, high: f64, slack: f64) -> f64 {
+    let off = (low - value).max(value - high).max(0.0);
+    (1.0 - off / slack).clamp(0.0, 1.0)
+}
+
+impl DietProfile {
+    /// Compliance of `intake`, which covers `share` of a day (1 for a whole
+    /// day, a meal's share of the daily meals for one meal). `None` when the
+    /// values the profile needs are missing.
+    pub fn score(self, intake: &Intake, contains: &[Ingredient], share: f64) -> Option<f64> {
+        let components: Vec<f64> = match self {
+            DietProfile::Keto => {
+                let (carbs, fat, _) = energy_shares(intake)?;
+                // At most 10% of energy from carbs, at least 60% from fat.
+                vec![within(carbs, 0.0, 0.10, 0.20), within(fat, 0.60, 1.0, 0.30)]
+            }
+            DietProfile::Vegan => {
+                let animal = Diet::Vegan.excludes().iter().any(|i| contains.contains(i));
+                vec![if animal { 0.0 } else { 1.0 }]
+            }
+            DietProfile::Mediterranean => {
+                let mut components = Vec::new();
+                if let Some((_, fat, _)) = energy_shares(intake) {
+                    components.push(within(fat, 0.25, 0.40, 0.15));
+                }
+                if let (Some(kcal), Some(fiber)) = (intake.calories_kcal, intake.fiber_g) {
+                    // 14 g of fiber per 1000 kcal; sugar at most 10% of energy.
+                    if kcal > 0.0 {
+                        components.push(at_least(fiber, kcal / 1000.0 * 14.0));
+                    }
+                    if let Some(sugar) = intake.sugar_g.filter(|_| kcal > 0.0) {
+                        components.push(at_most(sugar * 4.0, kcal * 0.10));
+                    }
+                }
+                if components.is_empty() {
+                    return None;
+                }
+                // Red and processed meat only now and then.
+                components.push(if contains.contains(&Ingredient::Meat) {
+                    0.5
+                } else {
+                    1.0
+                });
+                components
+            }
+            DietProfile::LowSodium => {
+                let limit = (LOW_SODIUM_DAILY_MG * share).max(f64::EPSILON);
+                vec![at_most(intake.sodium_mg?, limit)]
+            }
+        };
+        let mean = components.iter().sum::<f64>() / components.len() as f64;
+        Some((mean * 1000.0).round() / 10.0)
+    }
+
+    /// [`DietCompliance`] of `intake`, see [`DietProfile::score`].
+    pub fn compliance(
+        self,
+        intake: &Intake,
+        contains: &[Ingredient],
+        share: f64,
+    ) -> Option<DietCompliance> {
+        self.score(intake, contains, share)
+            .map(|score| DietCompliance {
+                profile: self,
+                score,
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn intake(carbs: f64, fat: f64, protein: f64) -> Intake {
+        Intake {
+            calories_kcal: Some(carbs * 4.0 + fat * 9.0 + protein * 4.0),
+            protein_g: Some(protein),
+            fat_g: Some(fat),
+            carbs_g: Some(carbs),
+            ..Intake::default()
+        }
+    }
+
+    #[test]
+    fn keto_rewards_fat_and_punishes_carbs() {
+        let keto = intake(10.0, 60.0, 30.0);
+        assert_eq!(DietProfile::Keto.score(&keto, &[], 1.0), Some(100.0));
+        let pasta = intake(100.0, 10.0, 15.0);
+        assert_eq!(DietProfile::Keto.score(&pasta, &[], 1.0), Some(0.0));
+        assert_eq!(DietProfile::Keto.score(&Intake::default(), &[], 1.0), None);
+    }
+
+    #[test]
+    fn vegan_depends_on_ingredients_only() {
+        let meal = intake(50.0, 10.0, 10.0);
+        assert_eq!(
+            DietProfile::Vegan.score(&meal, &[Ingredient::Soy], 1.0),
+            Some(100.0)
+        );
+        assert_eq!(
+            DietProfile::Vegan.score(&meal, &[Ingredient::Eggs], 1.0),
+            Some(0.0)
+        );
+    }
+
+    #[test]
+    fn low_sodium_scales_the_limit_with_the_share() {
+        let meal = Intake {
+            sodium_mg: Some(500.0),
+            ..Intake::default()
+        };
+        assert_eq!(
+            DietProfile::LowSodium.score(&meal, &[], 1.0 / 3.0),
+            Some(100.0)
+        );
+        assert_eq!(DietProfile::LowSodium.score(&meal, &[], 0.2), Some(33.3));
+    }
+
+    #[test]
+    fn mediterranean_goes_down_with_meat() {
+        let meal = Intake {
+            fiber_g: Some(10.0),
+            sugar_g: Some(5.0),
+            ..intake(60.0, 20.0, 25.0)
+        };
+        let plain = DietProfile::Mediterranean.score(&meal, &[], 1.0).unwrap();
+        let meat = DietProfile::Mediterranean
+            .score(&meal, &[Ingredient::Meat], 1.0)
+            .unwrap();
+        assert_eq!(plain, 100.0);
+        assert!(meat < plain);
+    }
+}
@@ -0,0 +1,762 @@
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use crate::{
+    foods::dto::FoodNutrition,
+    goals::dto::GoalProgress,
+    images::dto::{ImageInput, PresignedPhoto},
+    meals::{compliance::DietCompliance, warnings::MealWarning},
+    reactions::dto::ReactionCount,
+};
+
+/// Upper bound on the number of meal ids touched by a single bulk request.
+pub const MAX_BULK_MEAL_IDS: usize = 100;
+pub const MAX_TAG_LEN: usize = 32;
+
+pub const DEFAULT_LIST_LIMIT: i64 = 50;
+pub const MAX_LIST_LIMIT: i64 = 200;
+pub const MAX_TITLE_LEN: usize = 200;
+pub const MAX_NOTES_LEN: usize = 4000;
+/// Dish components kept from one analysis.
+pub const MAX_DETECTED_ITEMS: usize = 20;
+/// Sanity caps for hand-entered nutrition.
+pub const MAX_MANUAL_KCAL: f64 = 20_000.0;
+pub const MAX_MANUAL_GRAMS: f64 = 5_000.0;
+pub const MAX_MANUAL_MG: f64 = 100_000.0;
+pub const MAX_PLACE_NAME_LEN: usize = 200;
+/// Radius of `near` when the query gives none.
+pub const DEFAULT_NEAR_RADIUS_M: f64 = 200.0;
+pub const MAX_NEAR_RADIUS_M: f64 = 50_000.0;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, sqlx::Type, ToSchema)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+pub enum MealType {
+    Breakfast,
+    Lunch,
+    Dinner,
+    Snack,
+}
+
+impl std::str::FromStr for MealType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "breakfast" => Ok(MealType::Breakfast),
+            "lunch" => Ok(MealType::Lunch),
+            "dinner" => Ok(MealType::Dinner),
+            "snack" => Ok(MealType::Snack),
+            _ => Err(format!("Unknown meal type: {}", s)),
+        }
+    }
+}
+
+/// Progress of the AI analysis of a meal.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, sqlx::Type, ToSchema)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+pub enum MealStatus {
+    Pending,
+    Analyzing,
+    Done,
+    Failed,
+    /// The photos don't seem to show food, so they weren't analyzed.
+    NeedsReview,
+}
+
+/// A dish component recognized by the analysis, e.g. "grilled chicken".
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct DetectedItem {
+    pub name: String,
+    /// Estimated portion in grams, when the analyzer gives one.
+    #[serde(default)]
+    pub grams: Option<f64>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListMealsQuery {
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub from: Option<OffsetDateTime>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub to: Option<OffsetDateTime>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+    /// `lat,lon[,radius_m]`: only meals logged within the radius (200 m by
+    /// default) of the point. Applies to the user's own meals.
+    pub near: Option<String>,
+    /// Comma-separated extras for the user's own meals: `nutrition` adds
+    /// each meal's calories and protein, `totals` the `X-Total-*` headers.
+    pub include: Option<String>,
+}
+
+/// What `include` asks the meal list for.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ListIncludes {
+    pub nutrition: bool,
+    pub totals: bool,
+}
+
+impl ListMealsQuery {
+    pub fn near(&self) -> Result<Option<Near>, String> {
+        self.near.as_deref().map(str::parse).transpose()
+    }
+
+    pub fn includes(&self) -> Result<ListIncludes, String> {
+        let mut includes = ListIncludes::default();
+        for part in self
+            .include
+            .iter()
+            .flat_map(|i| i.split(','))
+            .map(str::trim)
+        {
+            match part {
+                "nutrition" => includes.nutrition = true,
+                "totals" => includes.totals = true,
+                "" => {}
+                other => {
+                    return Err(format!(
+                        "Unknown include {other:?}; expected nutrition or totals"
+                    ))
+                }
+            }
+        }
+        Ok(includes)
+    }
+
+    pub fn limit(&self) -> i64 {
+        self.limit
+            .unwrap_or(DEFAULT_LIST_LIMIT)
+            .clamp(1, MAX_LIST_LIMIT)
+    }
+
+    pub fn offset(&self) -> i64 {
+        self.offset.unwrap_or(0).max(0)
+    }
+}
+
+/// A point and a radius to search meals around.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Near {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub radius_m: f64,
+}
+
+impl std::str::FromStr for Near {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || "near must be lat,lon or lat,lon,radius_m".to_string();
+        let parts = s
+            .split(',')
+            .map(|p| p.trim().parse::<f64>().map_err(|_| invalid()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let (latitude, longitude, radius_m) = match parts[..] {
+            [lat, lon] => (lat, lon, DEFAULT_NEAR_RADIUS_M),
+            [lat, lon, radius] => (lat, lon, radius),
+            _ => return Err(invalid()),
+        };
+        check_coordinates(latitude, longitude)?;
+        if !radius_m.is_finite() || radius_m <= 0.0 || radius_m > MAX_NEAR_RADIUS_M {
+            return Err(format!(
+                "near radius must be greater than 0 and at most {} meters",
+                MAX_NEAR_RADIUS_M
+            ));
+        }
+        Ok(Self {
+            latitude,
+            longitude,
+            radius_m,
+        })
+    }
+}
+
+fn check_coordinates(latitude: f64, longitude: f64) -> Result<(), String> {
+    if !latitude.is_finite() || !(-90.0..=90.0).contains(&latitude) {
+        return Err("latitude must be between -90 and 90".into());
+    }
+    if !longitude.is_finite() || !(-180.0..=180.0).contains(&longitude) {
+        return Err("longitude must be between -180 and 180".into());
+    }
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreatedMealRequest {
+    pub title: Option<String>,
+    pub notes: Option<String>,
+    pub meal_type: Option<MealType>,
+    /// Household to log the meal into; its members can then see it.
+    pub household_id: Option<Uuid>,
+    /// Where the meal was eaten; `latitude` and `longitude` go together.
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    /// Restaurant or place; looked up from the coordinates when omitted.
+    pub place_name: Option<String>,
+    #[serde(default)]
+    pub images: Vec<ImageInput>,
+    /// Upload session whose photos become the meal's, instead of `images`.
+    pub upload_session_id: Option<Uuid>,
+    /// Hand-entered nutrition for meals logged without images.
+    pub nutrition: Option<ManualNutritionRequest>,
+    pub quick_add: Option<QuickAdd>,
+}
+
+/// Calories-only shortcut for logging without a photo.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct QuickAdd {
+    pub calories_kcal: f64,
+}
+
+impl CreatedMealRequest {
+    /// Takes the validated manual nutrition out of `nutrition` or
+    /// `quick_add`; at most one of them may be set.
+    pub fn take_nutrition(&mut self) -> Result<Option<ManualNutritionRequest>, String> {
+        let nutrition = match (self.nutrition.take(), self.quick_add.take()) {
+            (Some(_), Some(_)) => {
+                return Err("Use either nutrition or quick_add, not both".into());
+            }
+            (Some(nutrition), None) => nutrition,
+            (None, Some(quick)) => ManualNutritionRequest {
+                total_calories_kcal: Some(quick.calories_kcal),
+                ..ManualNutritionRequest::default()
+            },
+            (None, None) => return Ok(None),
+        };
+        nutrition.validate()?;
+        Ok(Some(nutrition))
+    }
+}
+
+/// Meal fields shared by every creation path.
+#[derive(Debug, Clone, Default)]
+pub struct NewMeal {
+    pub title: Option<String>,
+    pub notes: Option<String>,
+    pub meal_type: Option<MealType>,
+    pub household_id: Option<Uuid>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub place_name: Option<String>,
+}
+
+impl NewMeal {
+    /// Trims text fields, drops empty ones and enforces length limits.
+    /// Coordinates must come as a valid pair.
+    pub fn normalized(self) -> Result<Self, String> {
+        let clean = |v: Option<String>| v.map(|s| s.trim().to_string()).filter(|s| !s.is_empty());
+        let title = clean(self.title);
+        let notes = clean(self.notes);
+        let place_name = clean(self.place_name);
+        if title
+            .as_ref()
+            .is_some_and(|t| t.chars().count() > MAX_TITLE_LEN)
+        {
+            return Err(format!(
+                "Title must be at most {} characters",
+                MAX_TITLE_LEN
+            ));
+        }
+        if notes
+            .as_ref()
+            .is_some_and(|n| n.chars().count() > MAX_NOTES_LEN)
+        {
+            return Err(format!(
+                "Notes must be at most {} characters",
+                MAX_NOTES_LEN
+            ));
+        }
+        if place_name
+            .as_ref()
+            .is_some_and(|p| p.chars().count() > MAX_PLACE_NAME_LEN)
+        {
+            return Err(format!(
+                "place_name must be at most {} characters",
+                MAX_PLACE_NAME_LEN
+            ));
+        }
+        match (self.latitude, self.longitude) {
+            (Some(latitude), Some(longitude)) => check_coordinates(latitude, longitude)?,
+            (None, None) => {}
+            _ => return Err("latitude and longitude must be set together".into()),
+        }
+        Ok(Self {
+            title,
+            notes,
+            place_name,
+            ..self
+        })
+    }
+}
+
+/// Item in the meal list.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct MealResponse {
+    pub id: Uuid,
+    pub household_id: Option<Uuid>,
+    pub title: Option<String>,
+    /// `title` was suggested by the analysis; setting one replaces it.
+    pub title_generated: bool,
+    pub notes: Option<String>,
+    pub meal_type: Option<MealType>,
+    pub tags: Vec<String>,
+    pub status: MealStatus,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub place_name: Option<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    pub photos: Vec<PresignedPhoto>,
+    /// Reactions of household members, by emoji.
+    pub reactions: Vec<ReactionCount>,
+    /// Only with `include=nutrition`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nutrition: Option<NutritionSnippet>,
+}
+
+/// The nutrition the meal list shows per meal; `null` values until the
+/// meal has nutrition.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, ToSchema)]
+pub struct NutritionSnippet {
+    pub total_calories_kcal: Option<f64>,
+    pub protein_g: Option<f64>,
+    pub fat_g: Option<f64>,
+    pub carbs_g: Option<f64>,
+    pub global_score: Option<f64>,
+}
+
+/// Aggregates over every meal matching a list's filters, regardless of
+/// `limit` and `offset`.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct MealListTotals {
+    pub count: i64,
+    pub total_calories_kcal: f64,
+    pub protein_g: f64,
+}
+
+/// A page of the user's meals, with the totals when asked for.
+#[derive(Debug)]
+pub struct MealList {
+    pub meals: Vec<MealResponse>,
+    pub totals: Option<MealListTotals>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, sqlx::Type, ToSchema)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+pub enum NutritionSource {
+    Ai,
+    Manual,
+    /// Only meal items, no AI or manual estimate.
+    Items,
+    /// Totals from another app's CSV export.
+    Import,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+pub struct MealNutrition {
+    pub total_calories_kcal: Option<f64>,
+    pub protein_g: Option<f64>,
+    pub fat_g: Option<f64>,
+    pub carbs_g: Option<f64>,
+    pub sodium_mg: Option<f64>,
+    pub sugar_g: Option<f64>,
+    pub fiber_g: Option<f64>,
+    pub caffeine_mg: Option<f64>,
+    pub alcohol_g: Option<f64>,
+    #[schema(value_type = Option<Object>)]
+    pub micros: Option<serde_json::Value>,
+    pub global_score: Option<f64>,
+    pub source: NutritionSource,
+}
+
+/// Hand-entered nutrition; replaces any existing values for the meal.
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct ManualNutritionRequest {
+    pub total_calories_kcal: Option<f64>,
+    pub protein_g: Option<f64>,
+    pub fat_g: Option<f64>,
+    pub carbs_g: Option<f64>,
+    pub sodium_mg: Option<f64>,
+    pub sugar_g: Option<f64>,
+    pub fiber_g: Option<f64>,
+    pub caffeine_mg: Option<f64>,
+    pub alcohol_g: Option<f64>,
+    #[schema(value_type = Option<Object>)]
+    pub micros: Option<serde_json::Value>,
+}
+
+impl From<&ManualNutritionRequest> for FoodNutrition {
+    fn from(n: &ManualNutritionRequest) -> Self {
+        Self {
+            total_calories_kcal: n.total_calories_kcal,
+            protein_g: n.protein_g,
+            fat_g: n.fat_g,
+            carbs_g: n.carbs_g,
+            sodium_mg: n.sodium_mg,
+            sugar_g: n.sugar_g,
+            fiber_g: n.fiber_g,
+        }
+    }
+}
+
+impl ManualNutritionRequest {
+    pub fn validate(&self) -> Result<(), String> {
+        let fields = [
+            (
+                "total_calories_kcal",
+                self.total_calories_kcal,
+                MAX_MANUAL_KCAL,
+            ),
+            ("protein_g", self.protein_g, MAX_MANUAL_GRAMS),
+            ("fat_g", self.fat_g, MAX_MANUAL_GRAMS),
+            ("carbs_g", self.carbs_g, MAX_MANUAL_GRAMS),
+            ("sodium_mg", self.sodium_mg, MAX_MANUAL_MG),
+            ("sugar_g", self.sugar_g, MAX_MANUAL_GRAMS),
+            ("fiber_g", self.fiber_g, MAX_MANUAL_GRAMS),
+            ("caffeine_mg", self.caffeine_mg, MAX_MANUAL_MG),
+            ("alcohol_g", self.alcohol_g, MAX_MANUAL_GRAMS),
+        ];
+        if fields.iter().all(|(_, value, _)| value.is_none()) {
+            return Err("At least one nutrition value is required".into());
+        }
+        for (name, value, max) in fields {
+            if let Some(v) = value {
+                if !v.is_finite() || v < 0.0 || v > max {
+                    return Err(format!("{} must be between 0 and {}", name, max));
+                }
+            }
+        }
+        if self.micros.as_ref().is_some_and(|m| !m.is_object()) {
+            return Err("micros must be an object".into());
+        }
+        Ok(())
+    }
+}
+
+/// Single meal with its images and nutrition.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct MealDetails {
+    pub id: Uuid,
+    pub household_id: Option<Uuid>,
+    pub title: Option<String>,
+    /// `title` was suggested by the analysis; setting one replaces it.
+    pub title_generated: bool,
+    pub notes: Option<String>,
+    pub meal_type: Option<MealType>,
+    pub tags: Vec<String>,
+    pub status: MealStatus,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    /// Restaurant or place; filled in by reverse geocoding shortly after
+    /// creation when only coordinates were sent.
+    pub place_name: Option<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    pub images: Vec<PresignedPhoto>,
+    /// Dish components the analysis recognized; empty before it ran.
+    pub detected_items: Vec<DetectedItem>,
+    pub nutrition: Option<MealNutrition>,
+    /// This meal's share of the user's daily goals; `null` without nutrition.
+    pub goal_progress: Option<GoalProgress>,
+    /// Likely conflicts with the owner's allergens and diets.
+    pub warnings: Vec<MealWarning>,
+    /// How well the meal fits the owner's diet profile; `null` without a
+    /// profile or nutrition.
+    pub diet_compliance: Option<DietCompliance>,
+    pub reactions: Vec<ReactionCount>,
+    /// Earlier meal with a near-identical photo, logged within
+    /// `DUPLICATE_WINDOW_HOURS`. Only set in the create response.
+    pub possible_duplicate_of: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct MealStatusResponse {
+    pub id: Uuid,
+    pub status: MealStatus,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum BulkOperation {
+    Delete {
+        meal_ids: Vec<Uuid>,
+    },
+    Tag {
+        meal_ids: Vec<Uuid>,
+        tags: Vec<String>,
+    },
+    SetMealType {
+        meal_ids: Vec<Uuid>,
+        meal_type: Option<MealType>,
+    },
+}
+
+impl BulkOperation {
+    pub fn name(&self) -> &'static str {
+        match self {
+            BulkOperation::Delete { .. } => "delete",
+            BulkOperation::Tag { .. } => "tag",
+            BulkOperation::SetMealType { .. } => "set_meal_type",
+        }
+    }
+
+    pub fn meal_ids(&self) -> &[Uuid] {
+        match self {
+            BulkOperation::Delete { meal_ids }
+            | BulkOperation::Tag { meal_ids, .. }
+            | BulkOperation::SetMealType { meal_ids, .. } => meal_ids,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct BulkMealRequest {
+    pub operations: Vec<BulkOperation>,
+}
+
+impl BulkMealRequest {
+    /// Trims/lowercases tags in place and checks request limits.
+    pub fn normalize(&mut self) -> Result<(), String> {
+        if self.operations.is_empty() {
+            return Err("No operations provided".into());
+        }
+        let total: usize = self.operations.iter().map(|op| op.meal_ids().len()).sum();
+        if total == 0 {
+            return Err("No meal ids provided".into());
+        }
+        if total > MAX_BULK_MEAL_IDS {
+            return Err(format!(
+                "Too many meal ids (max {} per request)",
+                MAX_BULK_MEAL_IDS
+            ));
+        }
+        for op in self.operations.iter_mut() {
+            if let BulkOperation::Tag { tags, .. } = op {
+                let mut cleaned: Vec<String> = tags
+                    .iter()
+                    .map(|t| t.trim().to_lowercase())
+                    .filter(|t| !t.is_empty())
+                    .collect();
+                cleaned.sort();
+                cleaned.dedup();
+                if cleaned.is_empty() {
+                    return Err("Tag operation requires at least one tag".into());
+                }
+                if cleaned.iter().any(|t| t.chars().count() > MAX_TAG_LEN) {
+                    return Err(format!("Tags must be at most {} characters", MAX_TAG_LEN));
+                }
+                *tags = cleaned;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BulkItemResult {
+    pub meal_id: Uuid,
+    pub op: &'static str,
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BulkMealResponse {
+    pub results: Vec<BulkItemResult>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn created(
+        nutrition: Option<ManualNutritionRequest>,
+        quick_add: Option<QuickAdd>,
+    ) -> CreatedMealRequest {
+        CreatedMealRequest {
+            title: None,
+            notes: None,
+            meal_type: None,
+            household_id: None,
+            latitude: None,
+            longitude: None,
+            place_name: None,
+            images: Vec::new(),
+            upload_session_id: None,
+            nutrition,
+            quick_add,
+        }
+    }
+
+    #[test]
+    fn quick_add_becomes_calories_only_nutrition() {
+        let mut request = created(
+            None,
+            Some(QuickAdd {
+                calories_kcal: 350.0,
+            }),
+        );
+        let nutrition = request.take_nutrition().unwrap().unwrap();
+        assert_eq!(nutrition.total_calories_kcal, Some(350.0));
+        assert_eq!(nutrition.protein_g, None);
+
+        let mut negative = created(
+            None,
+            Some(QuickAdd {
+                calories_kcal: -1.0,
+            }),
+        );
+        assert!(negative.take_nutrition().is_err());
+        assert!(matches!(created(None, None).take_nutrition(), Ok(None)));
+    }
+
+    #[test]
+    fn nutrition_and_quick_add_are_exclusive() {
+        let nutrition = ManualNutritionRequest {
+            protein_g: Some(20.0),
+            ..ManualNutritionRequest::default()
+        };
+        let mut request = created(Some(nutrition), Some(QuickAdd { calories_kcal: 1.0 }));
+        assert!(request.take_nutrition().is_err());
+    }
+
+    #[test]
+    fn parses_tagged_operations() {
+        let id = Uuid::new_v4();
+        let body = format!(
+            r#"{{"operations":[
+                {{"op":"delete","meal_ids":["{id}"]}},
+                {{"op":"tag","meal_ids":["{id}"],"tags":["x"]}},
+                {{"op":"set_meal_type","meal_ids":["{id}"],"meal_type":"lunch"}}
+            ]}}"#
+        );
+        let req: BulkMealRequest = serde_json::from_str(&body).expect("valid bulk request");
+        let names: Vec<_> = req.operations.iter().map(|o| o.name()).collect();
+        assert_eq!(names, vec!["delete", "tag", "set_meal_type"]);
+        match &req.operations[2] {
+            BulkOperation::SetMealType { meal_type, .. } => {
+                assert_eq!(*meal_type, Some(MealType::Lunch))
+            }
+            _ => panic!("expected set_meal_type"),
+        }
+    }
+
+    #[test]
+    fn normalize_cleans_tags() {
+        let mut req = BulkMealRequest {
+            operations: vec![BulkOperation::Tag {
+                meal_ids: vec![Uuid::new_v4()],
+                tags: vec![" Vegan ".into(), "vegan".into(), "".into(), "Cheat".into()],
+            }],
+        };
+        req.normalize().expect("valid");
+        match &req.operations[0] {
+            BulkOperation::Tag { tags, .. } => assert_eq!(tags, &vec!["cheat", "vegan"]),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn normalize_rejects_too_many_ids() {
+        let mut req = BulkMealRequest {
+            operations: vec![BulkOperation::Delete {
+                meal_ids: (0..=MAX_BULK_MEAL_IDS).map(|_| Uuid::new_v4()).collect(),
+            }],
+        };
+        assert!(req.normalize().is_err());
+    }
+
+    #[test]
+    fn meal_type_from_str() {
+        assert_eq!(" Dinner ".parse::<MealType>(), Ok(MealType::Dinner));
+        assert!("brunch".parse::<MealType>().is_err());
+    }
+
+    #[test]
+    fn manual_nutrition_validation() {
+        let ok = ManualNutritionRequest {
+            total_calories_kcal: Some(420.0),
+            protein_g: Some(0.0),
+            ..Default::default()
+        };
+        assert!(ok.validate().is_ok());
+        assert!(ManualNutritionRequest::default().validate().is_err());
+        let negative = ManualNutritionRequest {
+            fat_g: Some(-1.0),
+            ..Default::default()
+        };
+        assert!(negative.validate().is_err());
+        let bad_micros = ManualNutritionRequest {
+            fiber_g: Some(3.0),
+            micros: Some(serde_json::json!([1, 2])),
+            ..Default::default()
+        };
+        assert!(bad_micros.validate().is_err());
+    }
+
+    #[test]
+    fn near_parses_with_default_radius() {
+        assert_eq!(
+            "48.8566, 2.3522".parse::<Near>(),
+            Ok(Near {
+                latitude: 48.8566,
+                longitude: 2.3522,
+                radius_m: DEFAULT_NEAR_RADIUS_M,
+            })
+        );
+        assert_eq!("48.8,2.3,500".parse::<Near>().unwrap().radius_m, 500.0);
+        assert!("48.8".parse::<Near>().is_err());
+        assert!("91,2.3".parse::<Near>().is_err());
+        assert!("48.8,2.3,0".parse::<Near>().is_err());
+        assert!("48.8,2.3,1e9".parse::<Near>().is_err());
+        assert!("a,b".parse::<Near>().is_err());
+    }
+
+    #[test]
+    fn includes_parse_from_a_list() {
+        let query = |include: Option<&str>| ListMealsQuery {
+            from: None,
+            to: None,
+            limit: None,
+            offset: None,
+            near: None,
+            include: include.map(str::to_string),
+        };
+        assert_eq!(query(None).includes(), Ok(ListIncludes::default()));
+        assert_eq!(
+            query(Some("totals, nutrition")).includes(),
+            Ok(ListIncludes {
+                nutrition: true,
+                totals: true,
+            })
+        );
+        assert!(query(Some("nutrition,photos")).includes().is_err());
+    }
+
+    #[test]
+    fn location_needs_both_coordinates() {
+        let meal = |latitude, longitude| NewMeal {
+            latitude,
+            longitude,
+            place_name: Some("  Chez Marie ".into()),
+            ..NewMeal::default()
+        };
+        let ok = meal(Some(48.85), Some(2.35)).normalized().unwrap();
+        assert_eq!(ok.place_name.as_deref(), Some("Chez Marie"));
+        assert!(meal(None, None).normalized().is_ok());
+        assert!(meal(Some(48.85), None).normalized().is_err());
+        assert!(meal(Some(48.85), Some(200.0)).normalized().is_err());
+    }
+
+    #[test]
+    fn normalize_rejects_empty_request() {
+        let mut req = BulkMealRequest { operations: vec![] };
+        assert!(req.normalize().is_err());
+    }
+}
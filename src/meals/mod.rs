@@ -0,0 +1,6 @@
+pub mod compliance;
+pub mod dto;
+pub mod repo;
+pub mod score;
+pub mod services;
+pub mod warnings;
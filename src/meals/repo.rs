@@ -0,0 +1,824 @@
+use std::collections::HashSet;
+
+use sqlx::{types::Json, PgConnection, PgExecutor, PgPool};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::{
+    analysis::NutritionEstimate,
+    db::{RepoError, RepoResult},
+    foods::dto::FoodNutrition,
+    meals::{
+        dto::{
+            BulkItemResult, BulkOperation, DetectedItem, ListMealsQuery, ManualNutritionRequest,
+            MealListTotals, MealNutrition, MealStatus, MealType, Near, NewMeal, NutritionSnippet,
+            NutritionSource,
+        },
+        warnings::Ingredient,
+    },
+};
+
+#[derive(Debug, Clone)]
+pub struct Meal {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub household_id: Option<Uuid>,
+    pub title: Option<String>,
+    pub notes: Option<String>,
+    pub meal_type: Option<MealType>,
+    pub tags: Vec<String>,
+    pub status: MealStatus,
+    pub created_at: OffsetDateTime,
+    pub title_generated: bool,
+    pub detected_items: Option<Json<Vec<DetectedItem>>>,
+    pub contains: Vec<Ingredient>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub place_name: Option<String>,
+}
+
+/// Inserts a meal with a caller-chosen id, so photos can be uploaded under
+/// it before the row exists. Runs on a pool or inside a transaction; fails
+/// with [`RepoError::NotFound`] if `meal.household_id` is set to a household
+/// the user isn't a member of.
+pub async fn create_meal(
+    db: impl PgExecutor<'_>,
+    meal_id: Uuid,
+    user_id: Uuid,
+    meal: &NewMeal,
+) -> RepoResult<Meal> {
+    let meal = sqlx::query_as!(
+        Meal,
+        r#"
+        INSERT INTO meals (id, user_id, title, notes, meal_type, household_id,
+                           latitude, longitude, place_name)
+        SELECT $6, $1, $2, $3, $4, $5, $7, $8, $9
+        WHERE $5::uuid IS NULL
+           OR EXISTS (SELECT 1 FROM household_members hm
+                      WHERE hm.household_id = $5 AND hm.user_id = $1)
+        RETURNING id, user_id, household_id, title, notes,
+                  meal_type AS "meal_type: MealType", tags,
+                  status AS "status: MealStatus", created_at, title_generated,
+                  detected_items AS "detected_items: Json<Vec<DetectedItem>>",
+                  contains AS "contains: Vec<Ingredient>",
+                  latitude, longitude, place_name
+        "#,
+        user_id,
+        meal.title,
+        meal.notes,
+        meal.meal_type as Option<MealType>,
+        meal.household_id,
+        meal_id,
+        meal.latitude,
+        meal.longitude,
+        meal.place_name
+    )
+    .fetch_optional(db)
+    .await?;
+    meal.ok_or(RepoError::NotFound)
+}
+
+/// The user's meals, newest first; with `near`, only those logged within its
+/// radius, by great-circle distance. With `with_nutrition` each meal carries
+/// its nutrition summary (default for meals without nutrition), joined in the
+/// same query; otherwise `None`.
+pub async fn list_meals(
+    db: impl PgExecutor<'_>,
+    user_id: Uuid,
+    query: &ListMealsQuery,
+    near: Option<Near>,
+    with_nutrition: bool,
+) -> RepoResult<Vec<(Meal, Option<NutritionSnippet>)>> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT m.id, m.user_id, m.household_id, m.title, m.notes,
+               m.meal_type AS "meal_type: MealType", m.tags,
+               m.status AS "status: MealStatus", m.created_at, m.title_generated,
+               m.detected_items AS "detected_items: Json<Vec<DetectedItem>>",
+               m.contains AS "contains: Vec<Ingredient>",
+               m.latitude, m.longitude, m.place_name,
+               n.total_calories_kcal::float8 AS total_calories_kcal,
+               n.protein_g::float8 AS protein_g,
+               n.fat_g::float8 AS fat_g,
+               n.carbs_g::float8 AS carbs_g,
+               n.global_score::float8 AS global_score
+        FROM meals m
+        LEFT JOIN meal_nutrition n ON $9 AND n.meal_id = m.id
+        WHERE m.user_id = $1
+          AND ($2::timestamptz IS NULL OR m.created_at >= $2)
+          AND ($3::timestamptz IS NULL OR m.created_at < $3)
+          AND ($6::float8 IS NULL
+               OR (m.latitude BETWEEN $6 - $8::float8 / 111320 AND $6 + $8 / 111320
+                   AND 2 * 6371000 * asin(sqrt(
+                           power(sin(radians(m.latitude - $6) / 2), 2)
+                           + cos(radians($6)) * cos(radians(m.latitude))
+                             * power(sin(radians(m.longitude - $7) / 2), 2))) <= $8))
+        ORDER BY m.created_at DESC, m.id
+        LIMIT $4 OFFSET $5
+        "#,
+        user_id,
+        query.from,
+        query.to,
+        query.limit(),
+        query.offset(),
+        near.map(|n| n.latitude),
+        near.map(|n| n.longitude),
+        near.map(|n| n.radius_m),
+        with_nutrition
+    )
+    .fetch_all(db)
+    .await?;
+    Ok(rows
+        .into_iter()
+        .map(|r| {
+            let nutrition = with_nutrition.then_some(NutritionSnippet {
+                total_calories_kcal: r.total_calories_kcal,
+                protein_g: r.protein_g,
+                fat_g: r.fat_g,
+                carbs_g: r.carbs_g,
+                global_score: r.global_score,
+            });
+            let meal = Meal {
+                id: r.id,
+                user_id: r.user_id,
+                household_id: r.household_id,
+                title: r.title,
+                notes: r.notes,
+                meal_type: r.meal_type,
+                tags: r.tags,
+                status: r.status,
+                created_at: r.created_at,
+                title_generated: r.title_generated,
+                detected_items: r.detected_items,
+                contains: r.contains,
+                latitude: r.latitude,
+                longitude: r.longitude,
+                place_name: r.place_name,
+            };
+            (meal, nutrition)
+        })
+        .collect())
+}
+
+/// Count, calories and protein of every meal [`list_meals`] would return
+/// without `limit` and `offset`.
+pub async fn list_totals(
+    db: impl PgExecutor<'_>,
+    user_id: Uuid,
+    query: &ListMealsQuery,
+    near: Option<Near>,
+) -> RepoResult<MealListTotals> {
+    let row = sqlx::query!(
+        r#"
+        SELECT COUNT(*) AS "count!",
+               COALESCE(SUM(n.total_calories_kcal), 0)::float8 AS "total_calories_kcal!",
+               COALESCE(SUM(n.protein_g), 0)::float8 AS "protein_g!"
+        FROM meals m
+        LEFT JOIN meal_nutrition n ON n.meal_id = m.id
+        WHERE m.user_id = $1
+          AND ($2::timestamptz IS NULL OR m.created_at >= $2)
+          AND ($3::timestamptz IS NULL OR m.created_at < $3)
+          AND ($4::float8 IS NULL
+               OR (m.latitude BETWEEN $4 - $6::float8 / 111320 AND $4 + $6 / 111320
+                   AND 2 * 6371000 * asin(sqrt(
+                           power(sin(radians(m.latitude - $4) / 2), 2)
+                           + cos(radians($4)) * cos(radians(m.latitude))
+                             * power(sin(radians(m.longitude - $5) / 2), 2))) <= $6))
+        "#,
+        user_id,
+        query.from,
+        query.to,
+        near.map(|n| n.latitude),
+        near.map(|n| n.longitude),
+        near.map(|n| n.radius_m)
+    )
+    .fetch_one(db)
+    .await?;
+    Ok(MealListTotals {
+        count: row.count,
+        total_calories_kcal: row.total_calories_kcal,
+        protein_g: row.protein_g,
+    })
+}
+
+/// Meals all members logged into a household, newest first; empty unless
+/// `user_id` is a member.
+pub async fn list_household_meals(
+    db: &PgPool,
+    user_id: Uuid,
+    household_id: Uuid,
+    query: &ListMealsQuery,
+) -> RepoResult<Vec<Meal>> {
+    let meals = sqlx::query_as!(
+        Meal,
+        r#"
+        SELECT id, user_id, household_id, title, notes,
+               meal_type AS "meal_type: MealType", tags,
+               status AS "status: MealStatus", created_at, title_generated,
+               detected_items AS "detected_items: Json<Vec<DetectedItem>>",
+               contains AS "contains: Vec<Ingredient>",
+               latitude, longitude, place_name
+        FROM meals
+        WHERE household_id = $1
+          AND EXISTS (SELECT 1 FROM household_members hm
+                      WHERE hm.household_id = $1 AND hm.user_id = $2)
+          AND ($3::timestamptz IS NULL OR created_at >= $3)
+          AND ($4::timestamptz IS NULL OR created_at < $4)
+        ORDER BY created_at DESC, id
+        LIMIT $5 OFFSET $6
+        "#,
+        household_id,
+        user_id,
+        query.from,
+        query.to,
+        query.limit(),
+        query.offset()
+    )
+    .fetch_all(db)
+    .await?;
+    Ok(meals)
+}
+
+/// A client's meals, newest first; empty unless `coach_id` actively coaches
+/// `client_id`. Coaches only ever read, so the relationship check is never
+/// combined with the ownership checks that guard writes.
+pub async fn list_client_meals(
+    db: &PgPool,
+    coach_id: Uuid,
+    client_id: Uuid,
+    query: &ListMealsQuery,
+) -> RepoResult<Vec<Meal>> {
+    let meals = sqlx::query_as!(
+        Meal,
+        r#"
+        SELECT id, user_id, household_id, title, notes,
+               meal_type AS "meal_type: MealType", tags,
+               status AS "status: MealStatus", created_at, title_generated,
+               detected_items AS "detected_items: Json<Vec<DetectedItem>>",
+               contains AS "contains: Vec<Ingredient>",
+               latitude, longitude, place_name
+        FROM meals
+        WHERE user_id = $1
+          AND EXISTS (SELECT 1 FROM coach_clients cc
+                      WHERE cc.coach_id = $2 AND cc.client_id = $1 AND cc.status = 'active')
+          AND ($3::timestamptz IS NULL OR created_at >= $3)
+          AND ($4::timestamptz IS NULL OR created_at < $4)
+        ORDER BY created_at DESC, id
+        LIMIT $5 OFFSET $6
+        "#,
+        client_id,
+        coach_id,
+        query.from,
+        query.to,
+        query.limit(),
+        query.offset()
+    )
+    .fetch_all(db)
+    .await?;
+    Ok(meals)
+}
+
+/// One of a client's meals, if `coach_id` actively coaches `client_id`.
+pub async fn find_client_meal(
+    db: &PgPool,
+    coach_id: Uuid,
+    client_id: Uuid,
+    meal_id: Uuid,
+) -> RepoResult<Option<Meal>> {
+    let meal = sqlx::query_as!(
+        Meal,
+        r#"
+        SELECT id, user_id, household_id, title, notes,
+               meal_type AS "meal_type: MealType", tags,
+               status AS "status: MealStatus", created_at, title_generated,
+               detected_items AS "detected_items: Json<Vec<DetectedItem>>",
+               contains AS "contains: Vec<Ingredient>",
+               latitude, longitude, place_name
+        FROM meals
+        WHERE id = $1 AND user_id = $2
+          AND EXISTS (SELECT 1 FROM coach_clients cc
+                      WHERE cc.coach_id = $3 AND cc.client_id = $2 AND cc.status = 'active')
+        "#,
+        meal_id,
+        client_id,
+        coach_id
+    )
+    .fetch_optional(db)
+    .await?;
+    Ok(meal)
+}
+
+/// The meal if `user_id` owns it. Every write goes through this check.
+pub async fn find_meal(
+    db: impl PgExecutor<'_>,
+    user_id: Uuid,
+    meal_id: Uuid,
+) -> RepoResult<Option<Meal>> {
+    let meal = sqlx::query_as!(
+        Meal,
+        r#"
+        SELECT id, user_id, household_id, title, notes,
+               meal_type AS "meal_type: MealType", tags,
+               status AS "status: MealStatus", created_at, title_generated,
+               detected_items AS "detected_items: Json<Vec<DetectedItem>>",
+               contains AS "contains: Vec<Ingredient>",
+               latitude, longitude, place_name
+        FROM meals
+        WHERE id = $1 AND user_id = $2
+        "#,
+        meal_id,
+        user_id
+    )
+    .fetch_optional(db)
+    .await?;
+    Ok(meal)
+}
+
+/// The meal if `user_id` owns it or shares its household, for read-only
+/// views.
+pub async fn find_visible_meal(
+    db: &PgPool,
+    user_id: Uuid,
+    meal_id: Uuid,
+) -> RepoResult<Option<Meal>> {
+    let meal = sqlx::query_as!(
+        Meal,
+        r#"
+        SELECT id, user_id, household_id, title, notes,
+               meal_type AS "meal_type: MealType", tags,
+               status AS "status: MealStatus", created_at, title_generated,
+               detected_items AS "detected_items: Json<Vec<DetectedItem>>",
+               contains AS "contains: Vec<Ingredient>",
+               latitude, longitude, place_name
+        FROM meals
+        WHERE id = $1
+          AND (user_id = $2
+               OR (household_id IS NOT NULL
+                   AND EXISTS (SELECT 1 FROM household_members hm
+                               WHERE hm.household_id = meals.household_id
+                                 AND hm.user_id = $2)))
+        "#,
+        meal_id,
+        user_id
+    )
+    .fetch_optional(db)
+    .await?;
+    Ok(meal)
+}
+
+/// Moves a meal owned by `user_id` into a household the user belongs to, or
+/// back out with `None`. Returns `None` when either check fails.
+pub async fn set_household(
+    db: &PgPool,
+    user_id: Uuid,
+    meal_id: Uuid,
+    household_id: Option<Uuid>,
+) -> RepoResult<Option<Meal>> {
+    let meal = sqlx::query_as!(
+        Meal,
+        r#"
+        UPDATE meals
+        SET household_id = $3
+        WHERE id = $1 AND user_id = $2
+          AND ($3::uuid IS NULL
+               OR EXISTS (SELECT 1 FROM household_members hm
+                          WHERE hm.household_id = $3 AND hm.user_id = $2))
+        RETURNING id, user_id, household_id, title, notes,
+                  meal_type AS "meal_type: MealType", tags,
+                  status AS "status: MealStatus", created_at, title_generated,
+                  detected_items AS "detected_items: Json<Vec<DetectedItem>>",
+                  contains AS "contains: Vec<Ingredient>",
+                  latitude, longitude, place_name
+        "#,
+        meal_id,
+        user_id,
+        household_id
+    )
+    .fetch_optional(db)
+    .await?;
+    Ok(meal)
+}
+
+/// Coordinates of a meal that has them but no place name yet.
+pub async fn find_unnamed_location(db: &PgPool, meal_id: Uuid) -> RepoResult<Option<(f64, f64)>> {
+    let row = sqlx::query!(
+        r#"
+        SELECT latitude AS "latitude!", longitude AS "longitude!"
+        FROM meals
+        WHERE id = $1 AND place_name IS NULL AND latitude IS NOT NULL
+        "#,
+        meal_id
+    )
+    .fetch_optional(db)
+    .await?;
+    Ok(row.map(|r| (r.latitude, r.longitude)))
+}
+
+/// Stores a looked-up place name unless the meal got one in the meantime.
+pub async fn set_place_name(db: &PgPool, meal_id: Uuid, place_name: &str) -> RepoResult<()> {
+    sqlx::query!(
+        r#"UPDATE meals SET place_name = $2 WHERE id = $1 AND place_name IS NULL"#,
+        meal_id,
+        place_name
+    )
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+pub async fn find_status(
+    db: impl PgExecutor<'_>,
+    user_id: Uuid,
+    meal_id: Uuid,
+) -> RepoResult<Option<MealStatus>> {
+    let status = sqlx::query_scalar!(
+        r#"
+        SELECT status AS "status: MealStatus" FROM meals
+        WHERE id = $1 AND user_id = $2
+        "#,
+        meal_id,
+        user_id
+    )
+    .fetch_optional(db)
+    .await?;
+    Ok(status)
+}
+
+pub async fn set_tags(db: &PgPool, meal_id: Uuid, tags: &[String]) -> RepoResult<()> {
+    sqlx::query!(r#"UPDATE meals SET tags = $2 WHERE id = $1"#, meal_id, tags)
+        .execute(db)
+        .await?;
+    Ok(())
+}
+
+/// Updates the status and returns the owning user, or `None` if the meal is
+/// gone.
+pub async fn set_status(
+    db: impl PgExecutor<'_>,
+    meal_id: Uuid,
+    status: MealStatus,
+) -> RepoResult<Option<Uuid>> {
+    let user_id = sqlx::query_scalar!(
+        r#"UPDATE meals SET status = $2 WHERE id = $1 RETURNING user_id"#,
+        meal_id,
+        status as MealStatus
+    )
+    .fetch_optional(db)
+    .await?;
+    Ok(user_id)
+}
+
+/// Marks a meal left `needs_review` as `done`; `false` if it wasn't waiting
+/// for review.
+pub async fn finish_review(db: &PgPool, meal_id: Uuid) -> RepoResult<bool> {
+    let updated = sqlx::query!(
+        r#"UPDATE meals SET status = 'done' WHERE id = $1 AND status = 'needs_review'"#,
+        meal_id
+    )
+    .execute(db)
+    .await?;
+    Ok(updated.rows_affected() > 0)
+}
+
+pub async fn find_nutrition(
+    db: impl PgExecutor<'_>,
+    meal_id: Uuid,
+) -> RepoResult<Option<MealNutrition>> {
+    let nutrition = sqlx::query_as!(
+        MealNutrition,
+        r#"
+        SELECT total_calories_kcal::float8 AS total_calories_kcal,
+               protein_g::float8 AS protein_g,
+               fat_g::float8 AS fat_g,
+               carbs_g::float8 AS carbs_g,
+               sodium_mg::float8 AS sodium_mg,
+               sugar_g::float8 AS sugar_g,
+               fiber_g::float8 AS fiber_g,
+               caffeine_mg::float8 AS caffeine_mg,
+               alcohol_g::float8 AS alcohol_g,
+               micros,
+               global_score::float8 AS global_score,
+               source AS "source: NutritionSource"
+        FROM meal_nutrition
+        WHERE meal_id = $1
+        "#,
+        meal_id
+    )
+    .fetch_optional(db)
+    .await?;
+    Ok(nutrition)
+}
+
+pub async fn set_global_score(
+    db: impl PgExecutor<'_>,
+    meal_id: Uuid,
+    score: Option<f64>,
+) -> RepoResult<()> {
+    sqlx::query!(
+        r#"UPDATE meal_nutrition SET global_score = $2 WHERE meal_id = $1"#,
+        meal_id,
+        score as Option<f64>
+    )
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+/// Stores what the analysis recognized. `title` only fills in a missing or
+/// earlier generated title, never one the user set.
+pub async fn set_detected(
+    db: impl PgExecutor<'_>,
+    meal_id: Uuid,
+    title: Option<&str>,
+    items: &[DetectedItem],
+    contains: &[Ingredient],
+) -> RepoResult<()> {
+    sqlx::query!(
+        r#"
+        UPDATE meals
+        SET title = CASE WHEN title IS NULL OR title_generated THEN COALESCE($2, title)
+                         ELSE title END,
+            title_generated = title_generated OR (title IS NULL AND $2::text IS NOT NULL),
+            detected_items = $3,
+            contains = $4
+        WHERE id = $1
+        "#,
+        meal_id,
+        title,
+        Json(items) as _,
+        contains as &[Ingredient]
+    )
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+/// Stores an AI estimate as the meal's base nutrition. Returns `false`
+/// without touching anything when the meal already has manual or imported
+/// nutrition.
+pub async fn upsert_nutrition(
+    db: impl PgExecutor<'_>,
+    meal_id: Uuid,
+    estimate: &NutritionEstimate,
+    ai_raw: &serde_json::Value,
+) -> RepoResult<bool> {
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO meal_nutrition (meal_id, total_calories_kcal, protein_g, fat_g, carbs_g,
+                                    sodium_mg, sugar_g, fiber_g, caffeine_mg, alcohol_g,
+                                    micros, ai_raw, base)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+        ON CONFLICT (meal_id) DO UPDATE
+        SET total_calories_kcal = EXCLUDED.total_calories_kcal,
+            protein_g = EXCLUDED.protein_g,
+            fat_g = EXCLUDED.fat_g,
+            carbs_g = EXCLUDED.carbs_g,
+            sodium_mg = EXCLUDED.sodium_mg,
+            sugar_g = EXCLUDED.sugar_g,
+            fiber_g = EXCLUDED.fiber_g,
+            caffeine_mg = EXCLUDED.caffeine_mg,
+            alcohol_g = EXCLUDED.alcohol_g,
+            micros = EXCLUDED.micros,
+            ai_raw = EXCLUDED.ai_raw,
+            base = EXCLUDED.base,
+            source = 'ai',
+            updated_at = NOW()
+        WHERE meal_nutrition.source NOT IN ('manual', 'import')
+        "#,
+        meal_id,
+        estimate.total_calories_kcal as Option<f64>,
+        estimate.protein_g as Option<f64>,
+        estimate.fat_g as Option<f64>,
+        estimate.carbs_g as Option<f64>,
+        estimate.sodium_mg as Option<f64>,
+        estimate.sugar_g as Option<f64>,
+        estimate.fiber_g as Option<f64>,
+        estimate.caffeine_mg as Option<f64>,
+        estimate.alcohol_g as Option<f64>,
+        estimate.micros,
+        ai_raw,
+        Json(FoodNutrition::from(estimate)) as _
+    )
+    .execute(db)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Replaces the base nutrition of a meal owned by `user_id` with manual
+/// values. Returns `None` if the meal doesn't exist or belongs to someone
+/// else.
+pub async fn upsert_manual_nutrition(
+    db: impl PgExecutor<'_>,
+    user_id: Uuid,
+    meal_id: Uuid,
+    input: &ManualNutritionRequest,
+) -> RepoResult<Option<MealNutrition>> {
+    let nutrition = sqlx::query_as!(
+        MealNutrition,
+        r#"
+        INSERT INTO meal_nutrition (meal_id, total_calories_kcal, protein_g, fat_g, carbs_g,
+                                    sodium_mg, sugar_g, fiber_g, caffeine_mg, alcohol_g,
+                                    micros, base, source)
+        SELECT m.id, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, 'manual'
+        FROM meals m
+        WHERE m.id = $1 AND m.user_id = $2
+        ON CONFLICT (meal_id) DO UPDATE
+        SET total_calories_kcal = EXCLUDED.total_calories_kcal,
+            protein_g = EXCLUDED.protein_g,
+            fat_g = EXCLUDED.fat_g,
+            carbs_g = EXCLUDED.carbs_g,
+            sodium_mg = EXCLUDED.sodium_mg,
+            sugar_g = EXCLUDED.sugar_g,
+            fiber_g = EXCLUDED.fiber_g,
+            caffeine_mg = EXCLUDED.caffeine_mg,
+            alcohol_g = EXCLUDED.alcohol_g,
+            micros = EXCLUDED.micros,
+            base = EXCLUDED.base,
+            source = 'manual',
+            updated_at = NOW()
+        RETURNING total_calories_kcal::float8 AS total_calories_kcal,
+                  protein_g::float8 AS protein_g,
+                  fat_g::float8 AS fat_g,
+                  carbs_g::float8 AS carbs_g,
+                  sodium_mg::float8 AS sodium_mg,
+                  sugar_g::float8 AS sugar_g,
+                  fiber_g::float8 AS fiber_g,
+                  caffeine_mg::float8 AS caffeine_mg,
+                  alcohol_g::float8 AS alcohol_g,
+                  micros,
+                  global_score::float8 AS global_score,
+                  source AS "source: NutritionSource"
+        "#,
+        meal_id,
+        user_id,
+        input.total_calories_kcal as Option<f64>,
+        input.protein_g as Option<f64>,
+        input.fat_g as Option<f64>,
+        input.carbs_g as Option<f64>,
+        input.sodium_mg as Option<f64>,
+        input.sugar_g as Option<f64>,
+        input.fiber_g as Option<f64>,
+        input.caffeine_mg as Option<f64>,
+        input.alcohol_g as Option<f64>,
+        input.micros,
+        Json(FoodNutrition::from(input)) as _
+    )
+    .fetch_optional(db)
+    .await?;
+    Ok(nutrition)
+}
+
+/// The AI or manual nutrition that meal items are added to; `None` without
+/// one.
+pub async fn find_base_nutrition(
+    db: impl PgExecutor<'_>,
+    meal_id: Uuid,
+) -> RepoResult<Option<FoodNutrition>> {
+    let base = sqlx::query_scalar!(
+        r#"SELECT base AS "base: Json<FoodNutrition>" FROM meal_nutrition WHERE meal_id = $1"#,
+        meal_id
+    )
+    .fetch_optional(db)
+    .await?;
+    Ok(base.flatten().map(|b| b.0))
+}
+
+/// Writes the meal's totals, creating an items-only row when the meal has no
+/// base nutrition.
+pub async fn store_nutrition_totals(
+    db: impl PgExecutor<'_>,
+    meal_id: Uuid,
+    totals: &FoodNutrition,
+) -> RepoResult<()> {
+    sqlx::query!(
+        r#"
+        INSERT INTO meal_nutrition (meal_id, total_calories_kcal, protein_g, fat_g, carbs_g,
+                                    sodium_mg, sugar_g, fiber_g, source)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, 'items')
+        ON CONFLICT (meal_id) DO UPDATE
+        SET total_calories_kcal = EXCLUDED.total_calories_kcal,
+            protein_g = EXCLUDED.protein_g,
+            fat_g = EXCLUDED.fat_g,
+            carbs_g = EXCLUDED.carbs_g,
+            sodium_mg = EXCLUDED.sodium_mg,
+            sugar_g = EXCLUDED.sugar_g,
+            fiber_g = EXCLUDED.fiber_g,
+            updated_at = NOW()
+        "#,
+        meal_id,
+        totals.total_calories_kcal as Option<f64>,
+        totals.protein_g as Option<f64>,
+        totals.fat_g as Option<f64>,
+        totals.carbs_g as Option<f64>,
+        totals.sodium_mg as Option<f64>,
+        totals.sugar_g as Option<f64>,
+        totals.fiber_g as Option<f64>
+    )
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+/// Drops an items-only nutrition row once its last item is gone.
+pub async fn delete_items_nutrition(db: impl PgExecutor<'_>, meal_id: Uuid) -> RepoResult<()> {
+    sqlx::query!(
+        r#"DELETE FROM meal_nutrition WHERE meal_id = $1 AND base IS NULL"#,
+        meal_id
+    )
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+/// Removes the nutrition of a meal owned by `user_id`, whatever its source.
+pub async fn delete_nutrition(db: &PgPool, user_id: Uuid, meal_id: Uuid) -> RepoResult<bool> {
+    let result = sqlx::query!(
+        r#"
+        DELETE FROM meal_nutrition n
+        USING meals m
+        WHERE n.meal_id = $1
+          AND m.id = n.meal_id
+          AND m.user_id = $2
+        "#,
+        meal_id,
+        user_id
+    )
+    .execute(db)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Applies every operation on `conn`, which callers run as one transaction.
+/// Ids that don't exist or belong to another user are reported per item; any
+/// database error rolls the whole batch back.
+pub async fn apply_bulk(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    operations: &[BulkOperation],
+) -> RepoResult<Vec<BulkItemResult>> {
+    let mut results = Vec::new();
+
+    for op in operations {
+        let ids = op.meal_ids();
+        let touched: Vec<Uuid> = match op {
+            BulkOperation::Delete { .. } => {
+                sqlx::query_scalar!(
+                    r#"
+                    DELETE FROM meals
+                    WHERE id = ANY($1) AND user_id = $2
+                    RETURNING id
+                    "#,
+                    ids,
+                    user_id
+                )
+                .fetch_all(&mut *conn)
+                .await?
+            }
+            BulkOperation::Tag { tags, .. } => {
+                sqlx::query_scalar!(
+                    r#"
+                    UPDATE meals
+                    SET tags = ARRAY(
+                        SELECT DISTINCT t FROM unnest(tags || $3::text[]) AS t ORDER BY t
+                    )
+                    WHERE id = ANY($1) AND user_id = $2
+                    RETURNING id
+                    "#,
+                    ids,
+                    user_id,
+                    tags
+                )
+                .fetch_all(&mut *conn)
+                .await?
+            }
+            BulkOperation::SetMealType { meal_type, .. } => {
+                sqlx::query_scalar!(
+                    r#"
+                    UPDATE meals
+                    SET meal_type = $3
+                    WHERE id = ANY($1) AND user_id = $2
+                    RETURNING id
+                    "#,
+                    ids,
+                    user_id,
+                    *meal_type as Option<MealType>
+                )
+                .fetch_all(&mut *conn)
+                .await?
+            }
+        };
+
+        let touched: HashSet<Uuid> = touched.into_iter().collect();
+        results.extend(ids.iter().map(|id| {
+            let ok = touched.contains(id);
+            BulkItemResult {
+                meal_id: *id,
+                op: op.name(),
+                ok,
+                error: (!ok).then(|| "Meal not found".to_string()),
+            }
+        }));
+    }
+
+    Ok(results)
+}
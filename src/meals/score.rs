@@ -0,0 +1,198 @@
+//! Global health score: a 0–100 rating of one meal's nutrition against a
+//! per-meal share of daily targets.
+
+use crate::{config::ScoreConfig, meals::dto::MealNutrition};
+
+/// Daily intake targets and limits. Meals are scored against all but the
+/// caffeine and alcohol limits, which only raise warnings in summaries.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DailyTargets {
+    pub calories_kcal: f64,
+    pub protein_g: f64,
+    pub fat_g: f64,
+    pub carbs_g: f64,
+    pub fiber_g: f64,
+    pub sugar_max_g: f64,
+    pub sodium_max_mg: f64,
+    pub caffeine_max_mg: f64,
+    pub alcohol_max_g: f64,
+}
+
+impl Default for DailyTargets {
+    fn default() -> Self {
+        Self {
+            calories_kcal: 2000.0,
+            protein_g: 75.0,
+            fat_g: 70.0,
+            carbs_g: 260.0,
+            fiber_g: 30.0,
+            sugar_max_g: 50.0,
+            sodium_max_mg: 2300.0,
+            // EFSA's safe daily caffeine intake; about two standard drinks.
+            caffeine_max_mg: 400.0,
+            alcohol_max_g: 20.0,
+        }
+    }
+}
+
+/// 1 at the target, falling linearly to 0 at twice (or zero times) the target.
+fn closeness(value: f64, target: f64) -> f64 {
+    (1.0 - (value - target).abs() / target).clamp(0.0, 1.0)
+}
+
+/// 1 up to the target, proportionally less below it.
+pub(crate) fn at_least(value: f64, target: f64) -> f64 {
+    (value / target).clamp(0.0, 1.0)
+}
+
+/// 1 up to the limit, falling linearly to 0 at twice the limit.
+pub(crate) fn at_most(value: f64, limit: f64) -> f64 {
+    (1.0 - (value - limit).max(0.0) / limit).clamp(0.0, 1.0)
+}
+
+/// Weighted average of the components that have data, scaled to 0–100 and
+/// rounded to one decimal. `None` when nothing can be scored.
+pub fn global_score(
+    nutrition: &MealNutrition,
+    targets: &DailyTargets,
+    config: &ScoreConfig,
+) -> Option<f64> {
+    let share = config.meals_per_day.max(1.0);
+    let per_meal = |daily: f64| (daily / share).max(f64::EPSILON);
+    let w = &config.weights;
+    let components = [
+        (
+            w.calories,
+            nutrition
+                .total_calories_kcal
+                .map(|v| closeness(v, per_meal(targets.calories_kcal))),
+        ),
+        (
+            w.protein,
+            nutrition
+                .protein_g
+                .map(|v| at_least(v, per_meal(targets.protein_g))),
+        ),
+        (
+            w.fat,
+            nutrition.fat_g.map(|v| at_most(v, per_meal(targets.fat_g))),
+        ),
+        (
+            w.carbs,
+            nutrition
+                .carbs_g
+                .map(|v| at_most(v, per_meal(targets.carbs_g))),
+        ),
+        (
+            w.fiber,
+            nutrition
+                .fiber_g
+                .map(|v| at_least(v, per_meal(targets.fiber_g))),
+        ),
+        (
+            w.sugar,
+            nutrition
+                .sugar_g
+                .map(|v| at_most(v, per_meal(targets.sugar_max_g))),
+        ),
+        (
+            w.sodium,
+            nutrition
+                .sodium_mg
+                .map(|v| at_most(v, per_meal(targets.sodium_max_mg))),
+        ),
+    ];
+    let (weighted, total_weight) = components
+        .iter()
+        .filter_map(|(weight, score)| score.map(|s| (weight * s, *weight)))
+        .filter(|(_, weight)| *weight > 0.0)
+        .fold((0.0, 0.0), |(sum, total), (s, weight)| {
+            (sum + s, total + weight)
+        });
+    (total_weight > 0.0).then(|| (weighted / total_weight * 1000.0).round() / 10.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::meals::dto::NutritionSource;
+
+    fn nutrition() -> MealNutrition {
+        MealNutrition {
+            total_calories_kcal: None,
+            protein_g: None,
+            fat_g: None,
+            carbs_g: None,
+            sodium_mg: None,
+            sugar_g: None,
+            fiber_g: None,
+            caffeine_mg: None,
+            alcohol_g: None,
+            micros: None,
+            global_score: None,
+            source: NutritionSource::Ai,
+        }
+    }
+
+    fn config() -> ScoreConfig {
+        ScoreConfig {
+            meals_per_day: 2.0,
+            ..ScoreConfig::default()
+        }
+    }
+
+    #[test]
+    fn empty_nutrition_has_no_score() {
+        assert_eq!(
+            global_score(&nutrition(), &DailyTargets::default(), &config()),
+            None
+        );
+    }
+
+    #[test]
+    fn balanced_meal_scores_full() {
+        let meal = MealNutrition {
+            total_calories_kcal: Some(1000.0),
+            protein_g: Some(40.0),
+            fat_g: Some(30.0),
+            carbs_g: Some(100.0),
+            fiber_g: Some(15.0),
+            sugar_g: Some(10.0),
+            sodium_mg: Some(800.0),
+            ..nutrition()
+        };
+        assert_eq!(
+            global_score(&meal, &DailyTargets::default(), &config()),
+            Some(100.0)
+        );
+    }
+
+    #[test]
+    fn excess_sugar_and_sodium_lower_the_score() {
+        let meal = MealNutrition {
+            sugar_g: Some(50.0),
+            sodium_mg: Some(1150.0),
+            ..nutrition()
+        };
+        // Sugar at twice the per-meal limit scores 0, sodium at 1.0 scores 1.
+        assert_eq!(
+            global_score(&meal, &DailyTargets::default(), &config()),
+            Some(50.0)
+        );
+    }
+
+    #[test]
+    fn zero_weights_ignore_components() {
+        let mut config = config();
+        config.weights.sugar = 0.0;
+        let meal = MealNutrition {
+            sugar_g: Some(500.0),
+            fiber_g: Some(15.0),
+            ..nutrition()
+        };
+        assert_eq!(
+            global_score(&meal, &DailyTargets::default(), &config),
+            Some(100.0)
+        );
+    }
+}
@@ -0,0 +1,512 @@
+use std::collections::HashMap;
+
+use anyhow::Context;
+
+use sqlx::PgConnection;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::{
+    billing::services as billing_services,
+    config::ScoreConfig,
+    db::AppState,
+    events::{repo as events_repo, DomainEvent},
+    goals::{
+        dto::{GoalProgress, Intake},
+        repo as goals_repo,
+    },
+    images::{
+        dto::{NormalizedImage, PresignedPhoto},
+        services::{
+            discard_objects, find_possible_duplicate, presign_cached, upload_images, DuplicatePhoto,
+        },
+    },
+    jobs::{repo as jobs_repo, Job},
+    meal_items::repo as items_repo,
+    meals::{
+        dto::{
+            BulkItemResult, BulkOperation, ListIncludes, ListMealsQuery, ManualNutritionRequest,
+            MealDetails, MealList, MealNutrition, MealResponse, MealStatus, Near, NewMeal,
+        },
+        repo::{self, Meal},
+        score, warnings,
+    },
+    photos::{
+        dto::PhotoMetadata,
+        repo::{self as photos_repo, Photo},
+    },
+    preferences::services as preferences_services,
+    reactions::repo as reactions_repo,
+    realtime::MealEvent,
+};
+
+pub async fn list_meals(
+    state: &AppState,
+    user_id: Uuid,
+    query: &ListMealsQuery,
+    near: Option<Near>,
+    includes: ListIncludes,
+) -> anyhow::Result<MealList> {
+    let mut tx = state.begin_as(user_id).await?;
+    let rows = repo::list_meals(&mut *tx, user_id, query, near, includes.nutrition).await?;
+    let totals = if includes.totals {
+        Some(repo::list_totals(&mut *tx, user_id, query, near).await?)
+    } else {
+        None
+    };
+    tx.commit().await?;
+    let (meals, nutrition): (Vec<Meal>, Vec<_>) = rows.into_iter().unzip();
+    let mut meals = meal_responses(state, meals).await?;
+    for (meal, nutrition) in meals.iter_mut().zip(nutrition) {
+        meal.nutrition = nutrition;
+    }
+    Ok(MealList { meals, totals })
+}
+
+/// Meals logged into a household by any member, for one of its members.
+pub async fn list_household_meals(
+    state: &AppState,
+    user_id: Uuid,
+    household_id: Uuid,
+    query: &ListMealsQuery,
+) -> anyhow::Result<Vec<MealResponse>> {
+    let meals = repo::list_household_meals(&state.db, user_id, household_id, query).await?;
+    meal_responses(state, meals).await
+}
+
+/// A client's meals for their coach; empty without an active relationship.
+pub async fn list_client_meals(
+    state: &AppState,
+    coach_id: Uuid,
+    client_id: Uuid,
+    query: &ListMealsQuery,
+) -> anyhow::Result<Vec<MealResponse>> {
+    let meals = repo::list_client_meals(&state.db, coach_id, client_id, query).await?;
+    meal_responses(state, meals).await
+}
+
+/// List views of `meals`, with presigned photo URLs.
+pub async fn meal_responses(
+    state: &AppState,
+    meals: Vec<Meal>,
+) -> anyhow::Result<Vec<MealResponse>> {
+    let ids: Vec<Uuid> = meals.iter().map(|m| m.id).collect();
+    let photos = photos_repo::list_for_meals(&state.db, &ids).await?;
+    let presigned = presign_cached(state, &photos).await?;
+    let mut reactions = reactions_repo::counts_for_meals(&state.db, &ids).await?;
+
+    let mut by_meal: HashMap<Uuid, Vec<PresignedPhoto>> = HashMap::new();
+    for (photo, url) in photos.iter().zip(presigned) {
+        if let Some(meal_id) = photo.meal_id {
+            by_meal.entry(meal_id).or_default().push(url);
+        }
+    }
+
+    Ok(meals
+        .into_iter()
+        .map(|m| MealResponse {
+            photos: by_meal.remove(&m.id).unwrap_or_default(),
+            reactions: reactions.remove(&m.id).unwrap_or_default(),
+            id: m.id,
+            household_id: m.household_id,
+            title: m.title,
+            title_generated: m.title_generated,
+            notes: m.notes,
+            meal_type: m.meal_type,
+            tags: m.tags,
+            status: m.status,
+            latitude: m.latitude,
+            longitude: m.longitude,
+            place_name: m.place_name,
+            created_at: m.created_at,
+            nutrition: None,
+        })
+        .collect())
+}
+
+/// Full view of a meal for its owner or a member of its household.
+pub async fn get_meal_details(
+    state: &AppState,
+    user_id: Uuid,
+    meal_id: Uuid,
+) -> anyhow::Result<Option<MealDetails>> {
+    match repo::find_visible_meal(&state.db, user_id, meal_id).await? {
+        Some(meal) => meal_details(state, meal).await.map(Some),
+        None => Ok(None),
+    }
+}
+
+/// Full view of a client's meal for their coach.
+pub async fn get_client_meal_details(
+    state: &AppState,
+    coach_id: Uuid,
+    client_id: Uuid,
+    meal_id: Uuid,
+) -> anyhow::Result<Option<MealDetails>> {
+    match repo::find_client_meal(&state.db, coach_id, client_id, meal_id).await? {
+        Some(meal) => meal_details(state, meal).await.map(Some),
+        None => Ok(None),
+    }
+}
+
+async fn meal_details(state: &AppState, meal: Meal) -> anyhow::Result<MealDetails> {
+    let photos = photos_repo::list_for_meals(&state.db, &[meal.id]).await?;
+    let images = presign_cached(state, &photos).await?;
+    let nutrition = repo::find_nutrition(&state.db, meal.id).await?;
+    let reactions = reactions_repo::counts_for_meals(&state.db, &[meal.id])
+        .await?
+        .remove(&meal.id)
+        .unwrap_or_default();
+    let goal_progress = match &nutrition {
+        Some(n) => {
+            // Household members and coaches see progress against the owner's goals.
+            let goals = goals_repo::find_goals(&state.db, meal.user_id).await?;
+            Some(GoalProgress::new(&Intake::from(n), &goals.targets()))
+        }
+        None => None,
+    };
+    let prefs = preferences_services::preferences(state, meal.user_id).await?;
+    let warnings = warnings::warnings(&meal.contains, &prefs.allergens, &prefs.diets);
+    let share = 1.0 / state.config.score.meals_per_day.max(1.0);
+    let diet_compliance = prefs
+        .diet_profile
+        .zip(nutrition.as_ref())
+        .and_then(|(profile, n)| profile.compliance(&Intake::from(n), &meal.contains, share));
+
+    Ok(MealDetails {
+        id: meal.id,
+        household_id: meal.household_id,
+        title: meal.title,
+        title_generated: meal.title_generated,
+        notes: meal.notes,
+        meal_type: meal.meal_type,
+        tags: meal.tags,
+        status: meal.status,
+        latitude: meal.latitude,
+        longitude: meal.longitude,
+        place_name: meal.place_name,
+        created_at: meal.created_at,
+        images,
+        detected_items: meal.detected_items.map(|items| items.0).unwrap_or_default(),
+        nutrition,
+        goal_progress,
+        warnings,
+        diet_compliance,
+        reactions,
+        possible_duplicate_of: None,
+    })
+}
+
+pub async fn meal_status(
+    state: &AppState,
+    user_id: Uuid,
+    meal_id: Uuid,
+) -> anyhow::Result<Option<MealStatus>> {
+    let mut tx = state.begin_as(user_id).await?;
+    let status = repo::find_status(&mut *tx, user_id, meal_id).await?;
+    tx.commit().await?;
+    Ok(status)
+}
+
+/// Applies the bulk operations all or nothing and refreshes the user's caches.
+pub async fn apply_bulk(
+    state: &AppState,
+    user_id: Uuid,
+    operations: &[BulkOperation],
+) -> anyhow::Result<Vec<BulkItemResult>> {
+    let mut tx = state.begin_as(user_id).await?;
+    let results = repo::apply_bulk(&mut tx, user_id, operations).await?;
+    tx.commit().await?;
+    state.stats_cache.invalidate(user_id);
+    state.cache.invalidate_summaries(user_id).await;
+    Ok(results)
+}
+
+/// Photo metadata for one meal, or `None` if the meal isn't owned by `user_id`.
+pub async fn list_meal_photos(
+    state: &AppState,
+    user_id: Uuid,
+    meal_id: Uuid,
+) -> anyhow::Result<Option<Vec<PhotoMetadata>>> {
+    let mut tx = state.begin_as(user_id).await?;
+    if repo::find_meal(&mut *tx, user_id, meal_id).await?.is_none() {
+        return Ok(None);
+    }
+    let photos = photos_repo::list_for_meal(&mut *tx, user_id, meal_id).await?;
+    tx.commit().await?;
+    let presigned = presign_cached(state, &photos).await?;
+    Ok(Some(
+        photos
+            .into_iter()
+            .zip(presigned)
+            .map(|(photo, presigned)| PhotoMetadata {
+                id: photo.id,
+                content_type: photo.content_type,
+                size_bytes: photo.size_bytes,
+                width: photo.width,
+                height: photo.height,
+                status: photo.status,
+                taken_at: photo.taken_at,
+                created_at: photo.created_at,
+                url: presigned.url,
+                expires_at: presigned.expires_at,
+            })
+            .collect(),
+    ))
+}
+
+/// Stores hand-entered nutrition, which later AI analysis won't overwrite.
+pub async fn set_manual_nutrition(
+    state: &AppState,
+    user_id: Uuid,
+    meal_id: Uuid,
+    input: &ManualNutritionRequest,
+) -> anyhow::Result<Option<MealNutrition>> {
+    if repo::upsert_manual_nutrition(&state.db, user_id, meal_id, input)
+        .await?
+        .is_none()
+    {
+        return Ok(None);
+    }
+    let nutrition = refresh_nutrition(state, user_id, meal_id).await?;
+    state
+        .events
+        .publish(user_id, MealEvent::NutritionUpdated { meal_id });
+    // Entering the nutrition by hand settles a meal that failed screening.
+    if repo::finish_review(&state.db, meal_id).await? {
+        let status = MealStatus::Done;
+        state
+            .events
+            .publish(user_id, MealEvent::MealStatus { meal_id, status });
+    }
+    Ok(nutrition)
+}
+
+/// Sets a meal's totals to its AI or manual base plus its items, then
+/// rescores it against its owner's goals. Call after every nutrition or item
+/// write; returns the refreshed nutrition.
+pub async fn refresh_nutrition(
+    state: &AppState,
+    user_id: Uuid,
+    meal_id: Uuid,
+) -> anyhow::Result<Option<MealNutrition>> {
+    let mut conn = state.db.acquire().await?;
+    let nutrition = refresh_totals(&mut conn, &state.config.score, user_id, meal_id).await?;
+    state.cache.invalidate_summaries(user_id).await;
+    Ok(nutrition)
+}
+
+/// [`refresh_nutrition`] on `conn`, for writes that must commit together;
+/// the caller invalidates the cached summaries once they have.
+pub async fn refresh_totals(
+    conn: &mut PgConnection,
+    score_config: &ScoreConfig,
+    user_id: Uuid,
+    meal_id: Uuid,
+) -> anyhow::Result<Option<MealNutrition>> {
+    let base = repo::find_base_nutrition(&mut *conn, meal_id).await?;
+    let items = items_repo::sum_for_meal(&mut *conn, meal_id).await?;
+    match (base, items) {
+        (None, None) => {
+            repo::delete_items_nutrition(&mut *conn, meal_id).await?;
+            return Ok(None);
+        }
+        (base, items) => {
+            let totals = base.unwrap_or_default().plus(&items.unwrap_or_default());
+            repo::store_nutrition_totals(&mut *conn, meal_id, &totals).await?;
+        }
+    }
+    let Some(mut nutrition) = repo::find_nutrition(&mut *conn, meal_id).await? else {
+        return Ok(None);
+    };
+    let goals = goals_repo::find_goals(&mut *conn, user_id).await?;
+    let score = score::global_score(&nutrition, &goals.targets(), score_config);
+    repo::set_global_score(&mut *conn, meal_id, score).await?;
+    nutrition.global_score = score;
+    Ok(Some(nutrition))
+}
+
+pub async fn clear_nutrition(
+    state: &AppState,
+    user_id: Uuid,
+    meal_id: Uuid,
+) -> anyhow::Result<bool> {
+    let deleted = repo::delete_nutrition(&state.db, user_id, meal_id).await?;
+    if deleted {
+        // Items stay and keep counting on their own.
+        refresh_nutrition(state, user_id, meal_id).await?;
+        state
+            .events
+            .publish(user_id, MealEvent::NutritionUpdated { meal_id });
+    }
+    Ok(deleted)
+}
+
+/// Creates a meal without photos, optionally with hand-entered nutrition,
+/// all in one transaction. There is nothing to analyze, so the meal is
+/// `done` right away.
+pub async fn create_quick_meal(
+    state: &AppState,
+    user_id: Uuid,
+    meal: NewMeal,
+    nutrition: Option<ManualNutritionRequest>,
+) -> anyhow::Result<MealDetails> {
+    let mut tx = state.begin_as(user_id).await?;
+    let meal = insert_quick_meal(
+        &mut tx,
+        &state.config.score,
+        user_id,
+        &meal,
+        nutrition.as_ref(),
+    )
+    .await?;
+    tx.commit().await?;
+    if nutrition.is_some() {
+        state
+            .events
+            .publish(user_id, MealEvent::NutritionUpdated { meal_id: meal.id });
+    }
+    finish_quick_meal(state, user_id, &meal).await
+}
+
+/// Stores a meal without photos as `done` on `conn`, with hand-entered
+/// nutrition when given, for callers adding more writes to the same
+/// transaction. Follow up with [`finish_quick_meal`] once it commits.
+pub async fn insert_quick_meal(
+    conn: &mut PgConnection,
+    score_config: &ScoreConfig,
+    user_id: Uuid,
+    meal: &NewMeal,
+    nutrition: Option<&ManualNutritionRequest>,
+) -> anyhow::Result<Meal> {
+    let meal = repo::create_meal(&mut *conn, Uuid::new_v4(), user_id, meal).await?;
+    repo::set_status(&mut *conn, meal.id, MealStatus::Done).await?;
+    if let Some(nutrition) = nutrition {
+        repo::upsert_manual_nutrition(&mut *conn, user_id, meal.id, nutrition).await?;
+        refresh_totals(conn, score_config, user_id, meal.id).await?;
+    }
+    let created = DomainEvent::MealCreated { meal_id: meal.id };
+    events_repo::record(&mut *conn, user_id, &created).await?;
+    Ok(meal)
+}
+
+/// Follow-up for a quick meal just committed: refreshes caches, queues
+/// geocoding and loads the response.
+pub async fn finish_quick_meal(
+    state: &AppState,
+    user_id: Uuid,
+    meal: &Meal,
+) -> anyhow::Result<MealDetails> {
+    state.stats_cache.invalidate(user_id);
+    state.cache.invalidate_summaries(user_id).await;
+    enqueue_geocoding(state, meal).await;
+    info!(user_id = %user_id, meal_id = %meal.id, "quick meal created");
+    get_meal_details(state, user_id, meal.id)
+        .await?
+        .context("meal disappeared right after creation")
+}
+
+/// Queues a reverse geocoding lookup for meals sent with coordinates but no
+/// place name, when a geocoder is configured.
+async fn enqueue_geocoding(state: &AppState, meal: &Meal) {
+    if state.geocoder.is_none() || meal.place_name.is_some() || meal.latitude.is_none() {
+        return;
+    }
+    let job = Job::GeocodeMeal { meal_id: meal.id };
+    if let Err(e) = jobs_repo::enqueue(&state.db, &job, state.config.jobs.max_attempts).await {
+        // The place name is a convenience; the meal keeps its coordinates.
+        warn!(error = %e, meal_id = %meal.id, "failed to enqueue geocoding");
+    }
+}
+
+/// Uploads the photos, then inserts the meal and its photo rows in the
+/// transaction that looked up reusable objects, and queues analysis. A meal
+/// is never stored without its photos; objects uploaded for a meal that
+/// failed to insert are deleted.
+pub async fn create_meal_with_images(
+    state: &AppState,
+    user_id: Uuid,
+    meal: NewMeal,
+    images: Vec<NormalizedImage>,
+) -> anyhow::Result<MealDetails> {
+    if images.is_empty() {
+        anyhow::bail!("no images provided");
+    }
+    let meal_id = Uuid::new_v4();
+    let mut tx = state.begin_as(user_id).await?;
+    let staged = upload_images(state, &mut tx, user_id, meal_id, images).await?;
+    let fingerprints = staged.photos.iter().map(|photo| photo.perceptual_hash);
+    let duplicate_of = find_possible_duplicate(state, user_id, fingerprints).await;
+    if let (Some(meal_id), true) = (duplicate_of, state.config.duplicates.block) {
+        discard_objects(&state.storage, &staged.new_keys).await;
+        return Err(DuplicatePhoto { meal_id }.into());
+    }
+    let inserted = async {
+        let meal = repo::create_meal(&mut *tx, meal_id, user_id, &meal).await?;
+        let photos = photos_repo::insert_many(&mut *tx, user_id, meal_id, &staged.photos).await?;
+        events_repo::record(&mut *tx, user_id, &DomainEvent::MealCreated { meal_id }).await?;
+        tx.commit().await?;
+        Ok::<_, anyhow::Error>((meal, photos))
+    }
+    .await;
+    let (meal, photos) = match inserted {
+        Ok(inserted) => inserted,
+        Err(e) => {
+            discard_objects(&state.storage, &staged.new_keys).await;
+            return Err(e);
+        }
+    };
+    finish_photo_meal(state, user_id, meal, photos, duplicate_of).await
+}
+
+/// Follow-up for a meal just stored with its photos: refreshes caches,
+/// queues analysis and geocoding and builds the response.
+pub async fn finish_photo_meal(
+    state: &AppState,
+    user_id: Uuid,
+    meal: Meal,
+    photos: Vec<Photo>,
+    possible_duplicate_of: Option<Uuid>,
+) -> anyhow::Result<MealDetails> {
+    state.stats_cache.invalidate(user_id);
+    state.cache.invalidate_summaries(user_id).await;
+    info!(user_id = %user_id, meal_id = %meal.id, photos = photos.len(), "meal created");
+    let job = Job::AnalyzeMeal {
+        meal_id: meal.id,
+        skip_screening: false,
+    };
+    match jobs_repo::enqueue(&state.db, &job, state.config.jobs.max_attempts).await {
+        Ok(_) => {
+            if let Err(e) = billing_services::count_analysis(state, user_id).await {
+                warn!(error = %e, meal_id = %meal.id, "failed to count meal analysis");
+            }
+        }
+        // The meal is already stored; analysis can be re-queued later.
+        Err(e) => warn!(error = %e, meal_id = %meal.id, "failed to enqueue meal analysis"),
+    }
+    enqueue_geocoding(state, &meal).await;
+    let images = presign_cached(state, &photos).await?;
+
+    Ok(MealDetails {
+        id: meal.id,
+        household_id: meal.household_id,
+        title: meal.title,
+        title_generated: meal.title_generated,
+        notes: meal.notes,
+        meal_type: meal.meal_type,
+        tags: meal.tags,
+        status: meal.status,
+        latitude: meal.latitude,
+        longitude: meal.longitude,
+        place_name: meal.place_name,
+        created_at: meal.created_at,
+        images,
+        detected_items: Vec::new(),
+        nutrition: None,
+        goal_progress: None,
+        warnings: Vec::new(),
+        diet_compliance: None,
+        reactions: Vec::new(),
+        possible_duplicate_of,
+    })
+}
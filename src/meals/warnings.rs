@@ -0,0 +1,306 @@
+//! Allergen and diet warnings. The analysis records which common allergens
+//! and animal products a meal likely contains; meal details compare them
+//! with the owner's declared allergens and diets.
+
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::{PgHasArrayType, PgTypeInfo};
+use utoipa::ToSchema;
+
+use crate::meals::dto::DetectedItem;
+
+/// Something a meal may contain that people avoid. All but `meat` can be
+/// declared as allergens.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, sqlx::Type, ToSchema,
+)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+pub enum Ingredient {
+    Peanuts,
+    TreeNuts,
+    Gluten,
+    Dairy,
+    Eggs,
+    Soy,
+    Fish,
+    Shellfish,
+    Sesame,
+    Meat,
+}
+
+impl Ingredient {
+    pub fn name(self) -> &'static str {
+        match self {
+            Ingredient::Peanuts => "peanuts",
+            Ingredient::TreeNuts => "tree nuts",
+            Ingredient::Gluten => "gluten",
+            Ingredient::Dairy => "dairy",
+            Ingredient::Eggs => "eggs",
+            Ingredient::Soy => "soy",
+            Ingredient::Fish => "fish",
+            Ingredient::Shellfish => "shellfish",
+            Ingredient::Sesame => "sesame",
+            Ingredient::Meat => "meat",
+        }
+    }
+
+    /// Words in dish component names that suggest the ingredient, for
+    /// analyzers that don't report it themselves.
+    fn keywords(self) -> &'static [&'static str] {
+        match self {
+            Ingredient::Peanuts => &["peanut", "satay"],
+            Ingredient::TreeNuts => &[
+                "almond",
+                "walnut",
+                "cashew",
+                "pecan",
+                "hazelnut",
+                "pistachio",
+                "macadamia",
+            ],
+            Ingredient::Gluten => &[
+                "bread",
+                "pasta",
+                "wheat",
+                "noodle",
+                "flour",
+                "barley",
+                "pizza",
+                "toast",
+                "bun",
+                "couscous",
+                "croissant",
+                "spaghetti",
+            ],
+            Ingredient::Dairy => &["cheese", "milk", "yogurt", "yoghurt", "cream", "butter"],
+            Ingredient::Eggs => &["egg", "omelet", "omelette", "mayonnaise"],
+            Ingredient::Soy => &["soy", "soybean", "tofu", "edamame", "tempeh", "miso"],
+            Ingredient::Fish => &[
+                "fish",
+                "salmon",
+                "tuna",
+                "cod",
+                "sardine",
+                "trout",
+                "anchovy",
+                "anchovies",
+            ],
+            Ingredient::Shellfish => &[
+                "shrimp", "prawn", "crab", "lobster", "mussel", "oyster", "clam", "scallop",
+            ],
+            Ingredient::Sesame => &["sesame", "tahini"],
+            Ingredient::Meat => &[
+                "chicken", "beef", "pork", "bacon", "ham", "lamb", "turkey", "sausage", "steak",
+                "meat", "salami",
+            ],
+        }
+    }
+
+    /// Whether `word`, or its singular, is one of the keywords.
+    fn matches(self, word: &str) -> bool {
+        let singular = word.strip_suffix('s');
+        self.keywords().iter().any(|&k| {
+            word == k
+                || singular == Some(k)
+                || singular.and_then(|s| s.strip_suffix('e')) == Some(k)
+        })
+    }
+
+    const ALL: [Ingredient; 10] = [
+        Ingredient::Peanuts,
+        Ingredient::TreeNuts,
+        Ingredient::Gluten,
+        Ingredient::Dairy,
+        Ingredient::Eggs,
+        Ingredient::Soy,
+        Ingredient::Fish,
+        Ingredient::Shellfish,
+        Ingredient::Sesame,
+        Ingredient::Meat,
+    ];
+}
+
+// Both are stored as `TEXT[]`.
+impl PgHasArrayType for Ingredient {
+    fn array_type_info() -> PgTypeInfo {
+        <String as PgHasArrayType>::array_type_info()
+    }
+}
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, sqlx::Type, ToSchema,
+)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+pub enum Diet {
+    Vegan,
+    Vegetarian,
+    Pescatarian,
+}
+
+impl Diet {
+    pub fn name(self) -> &'static str {
+        match self {
+            Diet::Vegan => "vegan",
+            Diet::Vegetarian => "vegetarian",
+            Diet::Pescatarian => "pescatarian",
+        }
+    }
+
+    /// Ingredients the diet rules out.
+    pub(crate) fn excludes(self) -> &'static [Ingredient] {
+        match self {
+            Diet::Vegan => &[
+                Ingredient::Meat,
+                Ingredient::Fish,
+                Ingredient::Shellfish,
+                Ingredient::Dairy,
+                Ingredient::Eggs,
+            ],
+            Diet::Vegetarian => &[Ingredient::Meat, Ingredient::Fish, Ingredient::Shellfish],
+            Diet::Pescatarian => &[Ingredient::Meat],
+        }
+    }
+}
+
+impl PgHasArrayType for Diet {
+    fn array_type_info() -> PgTypeInfo {
+        <String as PgHasArrayType>::array_type_info()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum WarningKind {
+    Allergen,
+    Diet,
+}
+
+/// A likely conflict between a meal and the owner's restrictions.
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct MealWarning {
+    pub kind: WarningKind,
+    pub ingredient: Ingredient,
+    /// The diet the ingredient conflicts with, for `diet` warnings.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub diet: Option<Diet>,
+    pub message: String,
+}
+
+/// Words that make the following "milk", "butter" or "cream" plant-based.
+const PLANT_PREFIXES: [&str; 7] = [
+    "peanut", "almond", "cashew", "soy", "oat", "coconut", "rice",
+];
+
+/// What the meal likely contains: the analyzer's own report plus keyword
+/// matches in the detected components and description. Sorted, no
+/// duplicates.
+pub fn detect(
+    reported: &[Ingredient],
+    items: &[DetectedItem],
+    description: Option<&str>,
+) -> Vec<Ingredient> {
+    let text = items
+        .iter()
+        .map(|item| item.name.as_str())
+        .chain(description)
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase();
+    let words: Vec<&str> = text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect();
+    let mut found: Vec<Ingredient> = reported.to_vec();
+    for (i, word) in words.iter().enumerate() {
+        let plant_based = i > 0 && PLANT_PREFIXES.contains(&words[i - 1]);
+        found.extend(
+            Ingredient::ALL
+                .into_iter()
+                .filter(|ingredient| ingredient.matches(word))
+                .filter(|ingredient| !(plant_based && *ingredient == Ingredient::Dairy)),
+        );
+    }
+    found.sort();
+    found.dedup();
+    found
+}
+
+/// Warnings for a meal containing `contains`, one per declared allergen
+/// and one per diet and conflicting ingredient.
+pub fn warnings(
+    contains: &[Ingredient],
+    allergens: &[Ingredient],
+    diets: &[Diet],
+) -> Vec<MealWarning> {
+    let allergen_warnings = contains
+        .iter()
+        .filter(|i| allergens.contains(i))
+        .map(|&ingredient| MealWarning {
+            kind: WarningKind::Allergen,
+            ingredient,
+            diet: None,
+            message: format!("May contain {}", ingredient.name()),
+        });
+    let diet_warnings = diets.iter().flat_map(|&diet| {
+        diet.excludes()
+            .iter()
+            .filter(|i| contains.contains(i))
+            .map(move |&ingredient| MealWarning {
+                kind: WarningKind::Diet,
+                ingredient,
+                diet: Some(diet),
+                message: format!("May not be {}: contains {}", diet.name(), ingredient.name()),
+            })
+    });
+    allergen_warnings.chain(diet_warnings).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(name: &str) -> DetectedItem {
+        DetectedItem {
+            name: name.into(),
+            grams: None,
+        }
+    }
+
+    #[test]
+    fn detects_ingredients_from_components_and_report() {
+        let items = [item("Grilled chicken"), item("Peanut sauce"), item("rice")];
+        let found = detect(&[Ingredient::Soy], &items, Some("with scrambled eggs"));
+        assert_eq!(
+            found,
+            [
+                Ingredient::Peanuts,
+                Ingredient::Eggs,
+                Ingredient::Soy,
+                Ingredient::Meat
+            ]
+        );
+        // Whole words only, and plant milks aren't dairy.
+        assert_eq!(detect(&[], &[item("Eggplant"), item("oat milk")], None), []);
+        assert_eq!(
+            detect(&[], &[item("peanut butter"), item("buns")], None),
+            [Ingredient::Peanuts, Ingredient::Gluten]
+        );
+    }
+
+    #[test]
+    fn warns_about_allergens_and_diet_conflicts() {
+        let contains = [Ingredient::Dairy, Ingredient::Peanuts];
+        let found = warnings(
+            &contains,
+            &[Ingredient::Peanuts],
+            &[Diet::Vegan, Diet::Vegetarian],
+        );
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].kind, WarningKind::Allergen);
+        assert_eq!(found[0].message, "May contain peanuts");
+        assert_eq!(found[1].diet, Some(Diet::Vegan));
+        assert_eq!(found[1].ingredient, Ingredient::Dairy);
+        assert!(warnings(&contains, &[], &[Diet::Pescatarian]).is_empty());
+    }
+}
@@ -0,0 +1,73 @@
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::achievements::dto::Badge;
+
+pub const DEFAULT_LIST_LIMIT: i64 = 50;
+pub const MAX_LIST_LIMIT: i64 = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+pub enum NotificationKind {
+    AnalysisComplete,
+    AchievementUnlocked,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct StoredNotification {
+    pub id: Uuid,
+    pub kind: NotificationKind,
+    pub meal_id: Option<Uuid>,
+    /// Title of the meal at the time of listing.
+    pub meal_title: Option<String>,
+    pub badge: Option<Badge>,
+    pub created_at: OffsetDateTime,
+    pub read_at: Option<OffsetDateTime>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ListNotificationsQuery {
+    /// Only notifications that haven't been read.
+    #[serde(default)]
+    pub unread: bool,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+impl ListNotificationsQuery {
+    pub fn limit(&self) -> i64 {
+        self.limit
+            .unwrap_or(DEFAULT_LIST_LIMIT)
+            .clamp(1, MAX_LIST_LIMIT)
+    }
+
+    pub fn offset(&self) -> i64 {
+        self.offset.unwrap_or(0).max(0)
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct NotificationResponse {
+    pub id: Uuid,
+    pub kind: NotificationKind,
+    /// In the request's locale, as the push would have said it.
+    pub title: String,
+    pub body: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub meal_id: Option<Uuid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub badge: Option<Badge>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub read_at: Option<OffsetDateTime>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct NotificationsResponse {
+    /// Unread notifications in total, not only on this page.
+    pub unread_count: i64,
+    pub notifications: Vec<NotificationResponse>,
+}
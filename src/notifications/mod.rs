@@ -0,0 +1,8 @@
+//! The in-app notification center. The [`services::NotificationSubscriber`]
+//! stores a notification when a meal's analysis completes or a badge is
+//! unlocked; `GET /me/notifications` lists them with the unread count and
+//! `POST /me/notifications/:id/read` marks one as read.
+
+pub mod dto;
+pub mod repo;
+pub mod services;
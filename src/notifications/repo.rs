@@ -0,0 +1,94 @@
+use sqlx::{PgExecutor, PgPool};
+use uuid::Uuid;
+
+use crate::{
+    achievements::dto::Badge,
+    notifications::dto::{ListNotificationsQuery, NotificationKind, StoredNotification},
+};
+
+/// Stores the notification for outbox event `event_id`; a no-op when the
+/// event was already stored or its meal no longer belongs to the user.
+pub async fn insert(
+    db: &PgPool,
+    user_id: Uuid,
+    event_id: i64,
+    kind: NotificationKind,
+    meal_id: Option<Uuid>,
+    badge: Option<Badge>,
+) -> anyhow::Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO notifications (user_id, event_id, kind, meal_id, badge)
+        SELECT $1, $2, $3, $4, $5
+        WHERE $4::uuid IS NULL
+           OR EXISTS (SELECT 1 FROM meals WHERE id = $4 AND user_id = $1)
+        ON CONFLICT (event_id) DO NOTHING
+        "#,
+    )
+    .bind(user_id)
+    .bind(event_id)
+    .bind(kind)
+    .bind(meal_id)
+    .bind(badge)
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+/// The user's notifications, newest first.
+pub async fn list(
+    db: impl PgExecutor<'_>,
+    user_id: Uuid,
+    query: &ListNotificationsQuery,
+) -> anyhow::Result<Vec<StoredNotification>> {
+    let notifications = sqlx::query_as::<_, StoredNotification>(
+        r#"
+        SELECT n.id, n.kind, n.meal_id, m.title AS meal_title, n.badge,
+               n.created_at, n.read_at
+        FROM notifications n
+        LEFT JOIN meals m ON m.id = n.meal_id
+        WHERE n.user_id = $1
+          AND (NOT $2 OR n.read_at IS NULL)
+        ORDER BY n.created_at DESC, n.id
+        LIMIT $3 OFFSET $4
+        "#,
+    )
+    .bind(user_id)
+    .bind(query.unread)
+    .bind(query.limit())
+    .bind(query.offset())
+    .fetch_all(db)
+    .await?;
+    Ok(notifications)
+}
+
+pub async fn unread_count(db: impl PgExecutor<'_>, user_id: Uuid) -> anyhow::Result<i64> {
+    let count = sqlx::query_scalar::<_, i64>(
+        r#"SELECT COUNT(*) FROM notifications WHERE user_id = $1 AND read_at IS NULL"#,
+    )
+    .bind(user_id)
+    .fetch_one(db)
+    .await?;
+    Ok(count)
+}
+
+/// Marks the notification as read, keeping the first read time; `false`
+/// when the user has no such notification.
+pub async fn mark_read(
+    db: impl PgExecutor<'_>,
+    user_id: Uuid,
+    notification_id: Uuid,
+) -> anyhow::Result<bool> {
+    let result = sqlx::query(
+        r#"
+        UPDATE notifications
+        SET read_at = COALESCE(read_at, NOW())
+        WHERE id = $1 AND user_id = $2
+        "#,
+    )
+    .bind(notification_id)
+    .bind(user_id)
+    .execute(db)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
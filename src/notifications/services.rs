@@ -0,0 +1,157 @@
+use uuid::Uuid;
+
+use crate::{
+    db::AppState,
+    events::{DomainEvent, EventSubscriber, OutboxEvent},
+    i18n,
+    notifications::{
+        dto::{
+            ListNotificationsQuery, NotificationKind, NotificationResponse, NotificationsResponse,
+            StoredNotification,
+        },
+        repo,
+    },
+    push::services::Notification,
+};
+
+/// A page of the user's notifications, worded like the matching push in the
+/// request's locale, and the unread count.
+pub async fn list_notifications(
+    state: &AppState,
+    user_id: Uuid,
+    query: &ListNotificationsQuery,
+) -> anyhow::Result<NotificationsResponse> {
+    let mut tx = state.begin_as(user_id).await?;
+    let stored = repo::list(&mut *tx, user_id, query).await?;
+    let unread_count = repo::unread_count(&mut *tx, user_id).await?;
+    tx.commit().await?;
+    let locale = i18n::current();
+    let notifications = stored
+        .into_iter()
+        .map(|n| {
+            let message = push_notification(&n).message(locale);
+            NotificationResponse {
+                id: n.id,
+                kind: n.kind,
+                title: message.title,
+                body: message.body,
+                meal_id: n.meal_id,
+                badge: n.badge,
+                created_at: n.created_at,
+                read_at: n.read_at,
+            }
+        })
+        .collect();
+    Ok(NotificationsResponse {
+        unread_count,
+        notifications,
+    })
+}
+
+/// Marks one of the user's notifications as read; reading it again is a
+/// no-op. `false` if the user has no such notification.
+pub async fn mark_read(
+    state: &AppState,
+    user_id: Uuid,
+    notification_id: Uuid,
+) -> anyhow::Result<bool> {
+    let mut tx = state.begin_as(user_id).await?;
+    let found = repo::mark_read(&mut *tx, user_id, notification_id).await?;
+    tx.commit().await?;
+    Ok(found)
+}
+
+/// The push a stored notification corresponds to, for its wording.
+fn push_notification(stored: &StoredNotification) -> Notification {
+    match (stored.kind, stored.badge) {
+        (NotificationKind::AchievementUnlocked, Some(badge)) => {
+            Notification::AchievementUnlocked { badge }
+        }
+        _ => Notification::AnalysisComplete {
+            meal_id: stored.meal_id.unwrap_or_default(),
+            title: stored.meal_title.clone(),
+        },
+    }
+}
+
+/// Stores a notification for each completed analysis and unlocked badge.
+pub struct NotificationSubscriber {
+    state: AppState,
+}
+
+impl NotificationSubscriber {
+    pub fn new(state: AppState) -> Self {
+        Self { state }
+    }
+}
+
+#[axum::async_trait]
+impl EventSubscriber for NotificationSubscriber {
+    fn name(&self) -> &'static str {
+        "notifications"
+    }
+
+    async fn handle(&self, event: &OutboxEvent) -> anyhow::Result<()> {
+        let (kind, meal_id, badge) = match event.event {
+            DomainEvent::NutritionReady { meal_id } => {
+                (NotificationKind::AnalysisComplete, Some(meal_id), None)
+            }
+            DomainEvent::AchievementUnlocked { badge } => {
+                (NotificationKind::AchievementUnlocked, None, Some(badge))
+            }
+            DomainEvent::MealCreated { .. }
+            | DomainEvent::UserRegistered
+            | DomainEvent::MealReaction { .. } => return Ok(()),
+        };
+        repo::insert(
+            &self.state.db,
+            event.user_id,
+            event.id,
+            kind,
+            meal_id,
+            badge,
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::achievements::dto::Badge;
+    use time::OffsetDateTime;
+
+    fn stored(kind: NotificationKind, badge: Option<Badge>) -> StoredNotification {
+        StoredNotification {
+            id: Uuid::nil(),
+            kind,
+            meal_id: Some(Uuid::nil()),
+            meal_title: Some("Porridge".into()),
+            badge,
+            created_at: OffsetDateTime::UNIX_EPOCH,
+            read_at: None,
+        }
+    }
+
+    #[test]
+    fn stored_notifications_are_worded_like_their_push() {
+        let analysis = push_notification(&stored(NotificationKind::AnalysisComplete, None));
+        assert_eq!(
+            analysis,
+            Notification::AnalysisComplete {
+                meal_id: Uuid::nil(),
+                title: Some("Porridge".into()),
+            }
+        );
+        let badge = push_notification(&stored(
+            NotificationKind::AchievementUnlocked,
+            Some(Badge::WeekStreak),
+        ));
+        assert_eq!(
+            badge,
+            Notification::AchievementUnlocked {
+                badge: Badge::WeekStreak
+            }
+        );
+    }
+}
@@ -0,0 +1,76 @@
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+pub const MAX_NAME_LEN: usize = 100;
+
+#[derive(Debug, Deserialize)]
+pub struct OrgRequest {
+    pub name: String,
+}
+
+impl OrgRequest {
+    pub fn normalized(self) -> Result<Self, String> {
+        let name = self.name.trim().to_string();
+        if name.is_empty() || name.chars().count() > MAX_NAME_LEN {
+            return Err(format!(
+                "name must be between 1 and {} characters",
+                MAX_NAME_LEN
+            ));
+        }
+        Ok(Self { name })
+    }
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct Organization {
+    pub id: Uuid,
+    pub name: String,
+    pub member_count: i64,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+/// `PUT /admin/users/{id}/org`; `org_id: null` takes the user out of their
+/// organization.
+#[derive(Debug, Deserialize)]
+pub struct OrgMembershipRequest {
+    pub org_id: Option<Uuid>,
+    #[serde(default)]
+    pub org_admin: bool,
+}
+
+/// The organization a user belongs to, and whether they administer it.
+#[derive(Debug, Clone, Copy, sqlx::FromRow)]
+pub struct Membership {
+    pub org_id: Uuid,
+    pub org_admin: bool,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct OrgMember {
+    pub user_id: Uuid,
+    pub email: String,
+    pub org_admin: bool,
+    pub meal_count: i64,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub last_meal_at: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn membership_requests_default_to_plain_members() {
+        let request: OrgMembershipRequest =
+            serde_json::from_str(r#"{"org_id": "5f0c6f0e-9c1e-4d0a-8f2e-1b2c3d4e5f60"}"#).unwrap();
+        assert!(request.org_id.is_some());
+        assert!(!request.org_admin);
+        let request: OrgMembershipRequest = serde_json::from_str(r#"{"org_id": null}"#).unwrap();
+        assert!(request.org_id.is_none());
+        assert!(OrgRequest { name: "  ".into() }.normalized().is_err());
+    }
+}
@@ -0,0 +1,11 @@
+//! Organizations: tenants such as a clinic running MealMind for many
+//! patients in one deployment. Platform admins create them and assign
+//! users; organization admins see their members and statistics under
+//! `/org`. Data never crosses organizations: everything a user owns is
+//! already scoped to them, and the few queries that reach other users
+//! (household and coaching invites) only match users of the same
+//! organization, or users outside any like before.
+
+pub mod dto;
+pub mod repo;
+pub mod services;
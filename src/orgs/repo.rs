@@ -0,0 +1,150 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::orgs::dto::{Membership, OrgMember, Organization};
+
+const ORG_COLUMNS: &str = r#"
+    o.id, o.name, o.created_at,
+    (SELECT COUNT(*) FROM users u WHERE u.org_id = o.id) AS member_count
+"#;
+
+pub async fn create_org(db: &PgPool, name: &str) -> anyhow::Result<Organization> {
+    let org = sqlx::query_as::<_, Organization>(
+        r#"
+        INSERT INTO organizations (name) VALUES ($1)
+        RETURNING id, name, created_at, 0::bigint AS member_count
+        "#,
+    )
+    .bind(name)
+    .fetch_one(db)
+    .await?;
+    Ok(org)
+}
+
+pub async fn list_orgs(db: &PgPool) -> anyhow::Result<Vec<Organization>> {
+    let orgs = sqlx::query_as::<_, Organization>(&format!(
+        r#"SELECT {ORG_COLUMNS} FROM organizations o ORDER BY o.name, o.id"#
+    ))
+    .fetch_all(db)
+    .await?;
+    Ok(orgs)
+}
+
+pub async fn find_org(db: &PgPool, org_id: Uuid) -> anyhow::Result<Option<Organization>> {
+    let org = sqlx::query_as::<_, Organization>(&format!(
+        r#"SELECT {ORG_COLUMNS} FROM organizations o WHERE o.id = $1"#
+    ))
+    .bind(org_id)
+    .fetch_optional(db)
+    .await?;
+    Ok(org)
+}
+
+/// `None` for users outside any organization, and unknown users.
+pub async fn membership(db: &PgPool, user_id: Uuid) -> anyhow::Result<Option<Membership>> {
+    let membership = sqlx::query_as::<_, Membership>(
+        r#"SELECT org_id, org_admin FROM users WHERE id = $1 AND org_id IS NOT NULL"#,
+    )
+    .bind(user_id)
+    .fetch_optional(db)
+    .await?;
+    Ok(membership)
+}
+
+/// Moves `user_id` and their meals into `org_id` (or out of any), ending
+/// coaching relationships and household memberships that would now cross
+/// organizations. Returns `false` for unknown users.
+pub async fn set_membership(
+    db: &PgPool,
+    user_id: Uuid,
+    org_id: Option<Uuid>,
+    org_admin: bool,
+) -> anyhow::Result<bool> {
+    let mut tx = db.begin().await?;
+    let updated = sqlx::query(r#"UPDATE users SET org_id = $2, org_admin = $3 WHERE id = $1"#)
+        .bind(user_id)
+        .bind(org_id)
+        .bind(org_id.is_some() && org_admin)
+        .execute(&mut *tx)
+        .await?;
+    if updated.rows_affected() == 0 {
+        return Ok(false);
+    }
+    sqlx::query(r#"UPDATE meals SET org_id = $2 WHERE user_id = $1"#)
+        .bind(user_id)
+        .bind(org_id)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query(
+        r#"
+        DELETE FROM coach_clients c
+        USING users other
+        WHERE ((c.coach_id = $1 AND other.id = c.client_id)
+            OR (c.client_id = $1 AND other.id = c.coach_id))
+          AND other.org_id IS DISTINCT FROM $2
+        "#,
+    )
+    .bind(user_id)
+    .bind(org_id)
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query(
+        r#"
+        WITH left_households AS (
+            DELETE FROM household_members hm
+            WHERE hm.user_id = $1
+              AND EXISTS (
+                  SELECT 1
+                  FROM household_members other
+                  JOIN users u ON u.id = other.user_id
+                  WHERE other.household_id = hm.household_id
+                    AND other.user_id <> $1
+                    AND u.org_id IS DISTINCT FROM $2
+              )
+            RETURNING hm.household_id
+        )
+        UPDATE meals SET household_id = NULL
+        WHERE user_id = $1 AND household_id IN (SELECT household_id FROM left_households)
+        "#,
+    )
+    .bind(user_id)
+    .bind(org_id)
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+    Ok(true)
+}
+
+pub async fn list_members(db: &PgPool, org_id: Uuid) -> anyhow::Result<Vec<OrgMember>> {
+    let members = sqlx::query_as::<_, OrgMember>(
+        r#"
+        SELECT u.id AS user_id, u.email, u.org_admin, u.created_at,
+               COUNT(m.id) AS meal_count, MAX(m.created_at) AS last_meal_at
+        FROM users u
+        LEFT JOIN meals m ON m.user_id = u.id
+        WHERE u.org_id = $1
+        GROUP BY u.id
+        ORDER BY u.email, u.id
+        "#,
+    )
+    .bind(org_id)
+    .fetch_all(db)
+    .await?;
+    Ok(members)
+}
+
+/// Whether two users may see each other: both in the same organization,
+/// or both outside any.
+pub async fn same_org(db: &PgPool, user_id: Uuid, other_id: Uuid) -> anyhow::Result<bool> {
+    let same = sqlx::query_scalar::<_, bool>(
+        r#"
+        SELECT (SELECT org_id FROM users WHERE id = $1)
+            IS NOT DISTINCT FROM (SELECT org_id FROM users WHERE id = $2)
+        "#,
+    )
+    .bind(user_id)
+    .bind(other_id)
+    .fetch_one(db)
+    .await?;
+    Ok(same)
+}
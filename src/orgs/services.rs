@@ -0,0 +1,88 @@
+use axum::http::StatusCode;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    admin::{dto::AdminStats, services as admin_services},
+    db::AppState,
+    error::ErrorCode,
+    orgs::{
+        dto::{OrgMember, OrgMembershipRequest, OrgRequest, Organization},
+        repo,
+    },
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum OrgError {
+    #[error("Organization not found")]
+    NotFound,
+    #[error("User not found")]
+    UserNotFound,
+    #[error("{0}")]
+    Invalid(String),
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+impl OrgError {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            OrgError::NotFound | OrgError::UserNotFound => StatusCode::NOT_FOUND,
+            OrgError::Invalid(_) => StatusCode::BAD_REQUEST,
+            OrgError::Other(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            OrgError::NotFound => ErrorCode::OrgNotFound,
+            OrgError::UserNotFound => ErrorCode::AuthUserNotFound,
+            OrgError::Invalid(_) => ErrorCode::ValidationFailed,
+            OrgError::Other(_) => ErrorCode::Internal,
+        }
+    }
+}
+
+pub async fn create_org(state: &AppState, input: OrgRequest) -> Result<Organization, OrgError> {
+    let input = input.normalized().map_err(OrgError::Invalid)?;
+    let org = repo::create_org(&state.db, &input.name).await?;
+    info!(org_id = %org.id, "organization created");
+    Ok(org)
+}
+
+pub async fn get_org(state: &AppState, org_id: Uuid) -> Result<Organization, OrgError> {
+    repo::find_org(&state.db, org_id)
+        .await?
+        .ok_or(OrgError::NotFound)
+}
+
+pub async fn members(state: &AppState, org_id: Uuid) -> Result<Vec<OrgMember>, OrgError> {
+    get_org(state, org_id).await?;
+    Ok(repo::list_members(&state.db, org_id).await?)
+}
+
+/// Puts a user (with their meals) into an organization or takes them out.
+pub async fn set_membership(
+    state: &AppState,
+    user_id: Uuid,
+    input: OrgMembershipRequest,
+) -> Result<(), OrgError> {
+    if let Some(org_id) = input.org_id {
+        get_org(state, org_id).await?;
+    }
+    if !repo::set_membership(&state.db, user_id, input.org_id, input.org_admin).await? {
+        return Err(OrgError::UserNotFound);
+    }
+    info!(
+        user_id = %user_id,
+        org_id = ?input.org_id,
+        org_admin = input.org_admin,
+        "organization membership changed"
+    );
+    Ok(())
+}
+
+/// The admin dashboard numbers restricted to the organization's members.
+pub async fn org_stats(state: &AppState, org_id: Uuid, days: i32) -> Result<AdminStats, OrgError> {
+    Ok(admin_services::admin_stats(state, days, Some(org_id)).await?)
+}
@@ -0,0 +1,23 @@
+use serde::Serialize;
+use time::OffsetDateTime;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// Photo with its stored metadata and a presigned URL valid until
+/// `expires_at`. Metadata is `null` for photos uploaded before it was tracked.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PhotoMetadata {
+    pub id: Uuid,
+    pub content_type: Option<String>,
+    pub size_bytes: Option<i64>,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    pub status: String,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub taken_at: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    pub url: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub expires_at: OffsetDateTime,
+}
@@ -0,0 +1,312 @@
+use serde::Serialize;
+use sqlx::{FromRow, PgExecutor, PgPool};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::db::RepoResult;
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct Photo {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub meal_id: Option<Uuid>,
+    #[serde(skip_serializing)]
+    pub s3_key: String,
+    #[serde(skip_serializing)]
+    pub original_s3_key: Option<String>,
+    pub content_type: Option<String>,
+    pub size_bytes: Option<i64>,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    #[serde(skip_serializing)]
+    pub content_hash: Option<String>,
+    pub taken_at: Option<OffsetDateTime>,
+    pub status: String,
+    pub failure_reason: Option<String>,
+    pub created_at: OffsetDateTime,
+}
+
+pub async fn list_for_meals(db: impl PgExecutor<'_>, meal_ids: &[Uuid]) -> RepoResult<Vec<Photo>> {
+    let photos = sqlx::query_as!(
+        Photo,
+        r#"
+        SELECT id, user_id, meal_id, s3_key, original_s3_key, content_type, size_bytes, width,
+               height, content_hash, taken_at, status, failure_reason, created_at
+        FROM photos
+        WHERE meal_id = ANY($1)
+        ORDER BY created_at, id
+        "#,
+        meal_ids
+    )
+    .fetch_all(db)
+    .await?;
+    Ok(photos)
+}
+
+/// Photos of `meal_id`, if the meal belongs to `user_id`.
+pub async fn list_for_meal(
+    db: impl PgExecutor<'_>,
+    user_id: Uuid,
+    meal_id: Uuid,
+) -> RepoResult<Vec<Photo>> {
+    let photos = sqlx::query_as!(
+        Photo,
+        r#"
+        SELECT p.id, p.user_id, p.meal_id, p.s3_key, p.original_s3_key, p.content_type,
+               p.size_bytes, p.width, p.height, p.content_hash, p.taken_at, p.status,
+               p.failure_reason, p.created_at
+        FROM photos p
+        JOIN meals m ON m.id = p.meal_id
+        WHERE p.meal_id = $1 AND m.user_id = $2
+        ORDER BY p.created_at, p.id
+        "#,
+        meal_id,
+        user_id
+    )
+    .fetch_all(db)
+    .await?;
+    Ok(photos)
+}
+
+pub async fn find_for_owner(
+    db: impl PgExecutor<'_>,
+    user_id: Uuid,
+    photo_id: Uuid,
+) -> RepoResult<Option<Photo>> {
+    let photo = sqlx::query_as!(
+        Photo,
+        r#"
+        SELECT id, user_id, meal_id, s3_key, original_s3_key, content_type, size_bytes, width,
+               height, content_hash, taken_at, status, failure_reason, created_at
+        FROM photos
+        WHERE id = $1 AND user_id = $2
+        "#,
+        photo_id,
+        user_id
+    )
+    .fetch_optional(db)
+    .await?;
+    Ok(photo)
+}
+
+/// One existing photo of `user_id` per matching content hash, the oldest.
+/// Every match is locked `FOR SHARE` until the transaction ends, so none can
+/// be deleted, and its object released, before rows reusing the object are
+/// committed.
+pub async fn find_by_hashes(
+    db: impl PgExecutor<'_>,
+    user_id: Uuid,
+    hashes: &[String],
+) -> RepoResult<Vec<Photo>> {
+    let mut photos = sqlx::query_as!(
+        Photo,
+        r#"
+        SELECT id, user_id, meal_id, s3_key, original_s3_key, content_type, size_bytes, width,
+               height, content_hash, taken_at, status, failure_reason, created_at
+        FROM photos
+        WHERE user_id = $1 AND content_hash = ANY($2)
+        ORDER BY content_hash, created_at
+        FOR SHARE
+        "#,
+        user_id,
+        hashes
+    )
+    .fetch_all(db)
+    .await?;
+    photos.dedup_by(|later, first| later.content_hash == first.content_hash);
+    Ok(photos)
+}
+
+/// Whether any photo or template photo still points at `key`, as photo or
+/// kept original.
+pub async fn key_in_use(db: impl PgExecutor<'_>, key: &str) -> RepoResult<bool> {
+    let in_use = sqlx::query_scalar!(
+        r#"
+        SELECT EXISTS (
+            SELECT 1 FROM photos WHERE s3_key = $1 OR original_s3_key = $1
+        ) OR EXISTS (
+            SELECT 1 FROM template_photos WHERE s3_key = $1 OR original_s3_key = $1
+        ) AS "in_use!"
+        "#,
+        key
+    )
+    .fetch_one(db)
+    .await?;
+    Ok(in_use)
+}
+
+/// Photo already written to storage, waiting to be recorded.
+#[derive(Debug, Clone, FromRow)]
+pub struct NewPhoto {
+    pub id: Uuid,
+    pub s3_key: String,
+    pub original_s3_key: Option<String>,
+    pub content_type: String,
+    pub size_bytes: i64,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    pub content_hash: String,
+    pub taken_at: Option<OffsetDateTime>,
+    /// See [`crate::images::fingerprint`].
+    #[sqlx(default)]
+    pub perceptual_hash: Option<i64>,
+}
+
+impl From<Photo> for NewPhoto {
+    /// Carries over the stored object and its metadata; callers pick a new id.
+    fn from(photo: Photo) -> Self {
+        Self {
+            id: photo.id,
+            s3_key: photo.s3_key,
+            original_s3_key: photo.original_s3_key,
+            content_type: photo.content_type.unwrap_or_default(),
+            size_bytes: photo.size_bytes.unwrap_or_default(),
+            width: photo.width,
+            height: photo.height,
+            content_hash: photo.content_hash.unwrap_or_default(),
+            taken_at: photo.taken_at,
+            perceptual_hash: None,
+        }
+    }
+}
+
+pub async fn insert_many(
+    db: impl PgExecutor<'_>,
+    user_id: Uuid,
+    meal_id: Uuid,
+    photos: &[NewPhoto],
+) -> RepoResult<Vec<Photo>> {
+    let ids: Vec<Uuid> = photos.iter().map(|p| p.id).collect();
+    let keys: Vec<String> = photos.iter().map(|p| p.s3_key.clone()).collect();
+    let originals: Vec<Option<String>> = photos.iter().map(|p| p.original_s3_key.clone()).collect();
+    let content_types: Vec<String> = photos.iter().map(|p| p.content_type.clone()).collect();
+    let sizes: Vec<i64> = photos.iter().map(|p| p.size_bytes).collect();
+    let widths: Vec<Option<i32>> = photos.iter().map(|p| p.width).collect();
+    let heights: Vec<Option<i32>> = photos.iter().map(|p| p.height).collect();
+    let hashes: Vec<String> = photos.iter().map(|p| p.content_hash.clone()).collect();
+    let taken: Vec<Option<OffsetDateTime>> = photos.iter().map(|p| p.taken_at).collect();
+    let fingerprints: Vec<Option<i64>> = photos.iter().map(|p| p.perceptual_hash).collect();
+    let photos = sqlx::query_as!(
+        Photo,
+        r#"
+        INSERT INTO photos (id, user_id, meal_id, s3_key, original_s3_key, content_type,
+                            size_bytes, width, height, content_hash, taken_at, perceptual_hash)
+        SELECT id, $10, $11, s3_key, original_s3_key, content_type, size_bytes, width, height,
+               content_hash, taken_at, perceptual_hash
+        FROM UNNEST($1::uuid[], $2::text[], $3::text[], $4::text[], $5::int8[], $6::int4[],
+                    $7::int4[], $8::text[], $9::timestamptz[], $12::int8[])
+            AS t(id, s3_key, original_s3_key, content_type, size_bytes, width, height,
+                 content_hash, taken_at, perceptual_hash)
+        RETURNING id, user_id, meal_id, s3_key, original_s3_key, content_type, size_bytes, width,
+                  height, content_hash, taken_at, status, failure_reason, created_at
+        "#,
+        &ids,
+        &keys,
+        &originals as &[Option<String>],
+        &content_types,
+        &sizes,
+        &widths as &[Option<i32>],
+        &heights as &[Option<i32>],
+        &hashes,
+        &taken as &[Option<OffsetDateTime>],
+        user_id,
+        meal_id,
+        &fingerprints as &[Option<i64>]
+    )
+    .fetch_all(db)
+    .await?;
+    Ok(photos)
+}
+
+/// The meal of the latest photo `user_id` took within `window_hours` whose
+/// fingerprint is at most `max_distance` bits from any of `fingerprints`.
+pub async fn find_similar_recent(
+    db: impl PgExecutor<'_>,
+    user_id: Uuid,
+    fingerprints: &[i64],
+    window_hours: i32,
+    max_distance: u32,
+) -> RepoResult<Option<Uuid>> {
+    let meal_id = sqlx::query_scalar!(
+        r#"
+        SELECT p.meal_id AS "meal_id!"
+        FROM photos p
+        WHERE p.user_id = $1
+          AND p.meal_id IS NOT NULL
+          AND p.perceptual_hash IS NOT NULL
+          AND p.created_at > NOW() - make_interval(hours => $3)
+          AND EXISTS (
+              SELECT 1 FROM UNNEST($2::int8[]) AS f(hash)
+              WHERE bit_count((p.perceptual_hash # f.hash)::bit(64)) <= $4
+          )
+        ORDER BY p.created_at DESC
+        LIMIT 1
+        "#,
+        user_id,
+        fingerprints,
+        window_hours,
+        i64::from(max_distance)
+    )
+    .fetch_optional(db)
+    .await?;
+    Ok(meal_id)
+}
+
+/// Deletes a photo of `meal_id` if the meal belongs to `user_id`, returning
+/// the storage keys (photo, kept original) of the removed objects.
+pub async fn delete_for_owner(
+    db: impl PgExecutor<'_>,
+    user_id: Uuid,
+    meal_id: Uuid,
+    photo_id: Uuid,
+) -> RepoResult<Option<(String, Option<String>)>> {
+    let deleted = sqlx::query!(
+        r#"
+        DELETE FROM photos p
+        USING meals m
+        WHERE p.id = $1
+          AND p.meal_id = $2
+          AND m.id = p.meal_id
+          AND m.user_id = $3
+        RETURNING p.s3_key, p.original_s3_key
+        "#,
+        photo_id,
+        meal_id,
+        user_id
+    )
+    .fetch_optional(db)
+    .await?;
+    Ok(deleted.map(|row| (row.s3_key, row.original_s3_key)))
+}
+
+/// Deletes up to `limit` photos left without a meal (the meal was deleted)
+/// for longer than `grace_hours`, returning the storage keys they used.
+/// Photos of an open upload session are kept until it expires.
+pub async fn delete_orphans(db: &PgPool, grace_hours: i32, limit: i64) -> RepoResult<Vec<String>> {
+    let rows = sqlx::query!(
+        r#"
+        DELETE FROM photos
+        WHERE id IN (
+            SELECT id FROM photos
+            WHERE meal_id IS NULL
+              AND created_at < NOW() - make_interval(hours => $1)
+              AND NOT EXISTS (
+                  SELECT 1 FROM upload_sessions s
+                  WHERE s.id = photos.upload_session_id
+                    AND s.finalized_at IS NULL AND s.expires_at > NOW()
+              )
+            ORDER BY created_at
+            LIMIT $2
+        )
+        RETURNING s3_key, original_s3_key
+        "#,
+        grace_hours,
+        limit
+    )
+    .fetch_all(db)
+    .await?;
+    Ok(rows
+        .into_iter()
+        .flat_map(|row| std::iter::once(row.s3_key).chain(row.original_s3_key))
+        .collect())
+}
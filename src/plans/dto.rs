@@ -0,0 +1,258 @@
+use serde::{Deserialize, Serialize};
+use time::{Date, Duration, OffsetDateTime};
+use uuid::Uuid;
+
+use crate::{foods::dto::FoodNutrition, goals::dto::Intake, meals::dto::MealType};
+
+pub const MAX_SLOT_SERVINGS: f64 = 20.0;
+
+time::serde::format_description!(iso_date, Date, "[year]-[month]-[day]");
+
+/// Monday of the week containing `date`.
+pub fn week_start(date: Date) -> Date {
+    date - Duration::days(i64::from(date.weekday().number_days_from_monday()))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreatePlanRequest {
+    /// Any day of the week to plan; the current UTC week when omitted.
+    #[serde(default, with = "iso_date::option")]
+    pub week_of: Option<Date>,
+}
+
+impl CreatePlanRequest {
+    pub fn week_start(&self) -> Date {
+        week_start(
+            self.week_of
+                .unwrap_or_else(|| OffsetDateTime::now_utc().date()),
+        )
+    }
+}
+
+/// Body of `PUT /plans/:id/slots`; replaces whatever the slot held.
+#[derive(Debug, Deserialize)]
+pub struct SlotRequest {
+    #[serde(with = "iso_date")]
+    pub date: Date,
+    pub meal_type: MealType,
+    pub recipe_id: Option<Uuid>,
+    pub template_id: Option<Uuid>,
+    /// Servings of the recipe, or copies of the template; defaults to 1.
+    pub servings: Option<f64>,
+}
+
+impl SlotRequest {
+    /// Checks the slot against the plan's week and returns its servings.
+    pub fn validate(&self, week_start: Date) -> Result<f64, String> {
+        if self.date < week_start || self.date > week_start + Duration::days(6) {
+            return Err(format!(
+                "date must be within the plan's week ({} to {})",
+                week_start,
+                week_start + Duration::days(6)
+            ));
+        }
+        if self.recipe_id.is_some() == self.template_id.is_some() {
+            return Err("Set exactly one of recipe_id and template_id".into());
+        }
+        let servings = self.servings.unwrap_or(1.0);
+        if !servings.is_finite() || servings <= 0.0 || servings > MAX_SLOT_SERVINGS {
+            return Err(format!(
+                "servings must be greater than 0 and at most {}",
+                MAX_SLOT_SERVINGS
+            ));
+        }
+        Ok(servings)
+    }
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct PlanRow {
+    pub id: Uuid,
+    pub week_start: Date,
+    pub created_at: OffsetDateTime,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct SlotRow {
+    pub id: Uuid,
+    pub date: Date,
+    pub meal_type: MealType,
+    pub recipe_id: Option<Uuid>,
+    pub template_id: Option<Uuid>,
+    pub servings: f64,
+    /// Recipe or template name.
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PlanSlot {
+    pub id: Uuid,
+    #[serde(with = "iso_date")]
+    pub date: Date,
+    pub meal_type: MealType,
+    pub recipe_id: Option<Uuid>,
+    pub template_id: Option<Uuid>,
+    pub name: String,
+    pub servings: f64,
+    /// Planned nutrition for the slot's servings.
+    pub nutrition: FoodNutrition,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PlanDay {
+    #[serde(with = "iso_date")]
+    pub date: Date,
+    pub planned_meals: usize,
+    pub planned: FoodNutrition,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MealPlan {
+    pub id: Uuid,
+    #[serde(with = "iso_date")]
+    pub week_start: Date,
+    #[serde(with = "iso_date")]
+    pub week_end: Date,
+    pub slots: Vec<PlanSlot>,
+    /// All seven days, including ones with nothing planned.
+    pub days: Vec<PlanDay>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+impl MealPlan {
+    pub fn new(row: PlanRow, slots: Vec<PlanSlot>) -> Self {
+        let days = (0..7)
+            .map(|offset| {
+                let date = row.week_start + Duration::days(offset);
+                let day: Vec<&PlanSlot> = slots.iter().filter(|s| s.date == date).collect();
+                PlanDay {
+                    date,
+                    planned_meals: day.len(),
+                    planned: day
+                        .iter()
+                        .fold(FoodNutrition::default(), |sum, s| sum.plus(&s.nutrition)),
+                }
+            })
+            .collect();
+        Self {
+            id: row.id,
+            week_start: row.week_start,
+            week_end: row.week_start + Duration::days(6),
+            slots,
+            days,
+            created_at: row.created_at,
+        }
+    }
+}
+
+/// Planned intake of one day next to what was actually logged.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlanComparison {
+    pub planned_meals: usize,
+    pub planned: Intake,
+    /// Actual minus planned; positive when more was eaten than planned.
+    pub difference: Intake,
+}
+
+impl PlanComparison {
+    pub fn new(day: &PlanDay, actual: &Intake) -> Self {
+        let planned = Intake::from(&day.planned);
+        let diff = |actual: Option<f64>, planned: Option<f64>| {
+            planned.map(|p| ((actual.unwrap_or(0.0) - p) * 10.0).round() / 10.0)
+        };
+        Self {
+            planned_meals: day.planned_meals,
+            difference: Intake {
+                calories_kcal: diff(actual.calories_kcal, planned.calories_kcal),
+                protein_g: diff(actual.protein_g, planned.protein_g),
+                fat_g: diff(actual.fat_g, planned.fat_g),
+                carbs_g: diff(actual.carbs_g, planned.carbs_g),
+                fiber_g: diff(actual.fiber_g, planned.fiber_g),
+                sugar_g: diff(actual.sugar_g, planned.sugar_g),
+                sodium_mg: diff(actual.sodium_mg, planned.sodium_mg),
+                caffeine_mg: diff(actual.caffeine_mg, planned.caffeine_mg),
+                alcohol_g: diff(actual.alcohol_g, planned.alcohol_g),
+            },
+            planned,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::{date, datetime};
+
+    #[test]
+    fn weeks_start_on_monday() {
+        assert_eq!(week_start(date!(2024 - 05 - 01)), date!(2024 - 04 - 29));
+        assert_eq!(week_start(date!(2024 - 04 - 29)), date!(2024 - 04 - 29));
+        assert_eq!(week_start(date!(2024 - 05 - 05)), date!(2024 - 04 - 29));
+    }
+
+    fn slot(date: Date, kcal: f64) -> PlanSlot {
+        PlanSlot {
+            id: Uuid::nil(),
+            date,
+            meal_type: MealType::Lunch,
+            recipe_id: Some(Uuid::nil()),
+            template_id: None,
+            name: "Chili".into(),
+            servings: 1.0,
+            nutrition: FoodNutrition {
+                total_calories_kcal: Some(kcal),
+                ..FoodNutrition::default()
+            },
+        }
+    }
+
+    #[test]
+    fn plan_covers_every_day_of_the_week() {
+        let row = PlanRow {
+            id: Uuid::nil(),
+            week_start: date!(2024 - 04 - 29),
+            created_at: datetime!(2024-04-28 12:00 UTC),
+        };
+        let monday = date!(2024 - 04 - 29);
+        let plan = MealPlan::new(row, vec![slot(monday, 500.0), slot(monday, 700.0)]);
+        assert_eq!(plan.days.len(), 7);
+        assert_eq!(plan.week_end, date!(2024 - 05 - 05));
+        assert_eq!(plan.days[0].planned_meals, 2);
+        assert_eq!(plan.days[0].planned.total_calories_kcal, Some(1200.0));
+        assert_eq!(plan.days[1].planned.total_calories_kcal, None);
+    }
+
+    #[test]
+    fn difference_is_actual_minus_planned() {
+        let day = PlanDay {
+            date: date!(2024 - 04 - 29),
+            planned_meals: 1,
+            planned: FoodNutrition {
+                total_calories_kcal: Some(1800.0),
+                ..FoodNutrition::default()
+            },
+        };
+        let actual = Intake {
+            calories_kcal: Some(2000.0),
+            protein_g: Some(80.0),
+            ..Intake::default()
+        };
+        let comparison = PlanComparison::new(&day, &actual);
+        assert_eq!(comparison.difference.calories_kcal, Some(200.0));
+        assert_eq!(comparison.difference.protein_g, None);
+    }
+
+    #[test]
+    fn slots_must_fall_in_the_week() {
+        let request = SlotRequest {
+            date: date!(2024 - 05 - 06),
+            meal_type: MealType::Dinner,
+            recipe_id: Some(Uuid::nil()),
+            template_id: None,
+            servings: None,
+        };
+        assert!(request.validate(date!(2024 - 04 - 29)).is_err());
+        assert_eq!(request.validate(date!(2024 - 05 - 06)), Ok(1.0));
+    }
+}
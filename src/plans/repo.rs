@@ -0,0 +1,137 @@
+use sqlx::PgExecutor;
+use time::Date;
+use uuid::Uuid;
+
+use crate::plans::dto::{PlanRow, SlotRequest, SlotRow};
+
+/// Creates the plan for a week; `None` if the user already has one.
+pub async fn insert_plan(
+    db: impl PgExecutor<'_>,
+    user_id: Uuid,
+    week_start: Date,
+) -> anyhow::Result<Option<PlanRow>> {
+    let plan = sqlx::query_as::<_, PlanRow>(
+        r#"
+        INSERT INTO meal_plans (user_id, week_start)
+        VALUES ($1, $2)
+        ON CONFLICT (user_id, week_start) DO NOTHING
+        RETURNING id, week_start, created_at
+        "#,
+    )
+    .bind(user_id)
+    .bind(week_start)
+    .fetch_optional(db)
+    .await?;
+    Ok(plan)
+}
+
+pub async fn find_plan(
+    db: impl PgExecutor<'_>,
+    user_id: Uuid,
+    plan_id: Uuid,
+) -> anyhow::Result<Option<PlanRow>> {
+    let plan = sqlx::query_as::<_, PlanRow>(
+        r#"
+        SELECT id, week_start, created_at
+        FROM meal_plans
+        WHERE id = $1 AND user_id = $2
+        "#,
+    )
+    .bind(plan_id)
+    .bind(user_id)
+    .fetch_optional(db)
+    .await?;
+    Ok(plan)
+}
+
+pub async fn find_plan_for_week(
+    db: impl PgExecutor<'_>,
+    user_id: Uuid,
+    week_start: Date,
+) -> anyhow::Result<Option<PlanRow>> {
+    let plan = sqlx::query_as::<_, PlanRow>(
+        r#"
+        SELECT id, week_start, created_at
+        FROM meal_plans
+        WHERE user_id = $1 AND week_start = $2
+        "#,
+    )
+    .bind(user_id)
+    .bind(week_start)
+    .fetch_optional(db)
+    .await?;
+    Ok(plan)
+}
+
+pub async fn delete_plan(
+    db: impl PgExecutor<'_>,
+    user_id: Uuid,
+    plan_id: Uuid,
+) -> anyhow::Result<bool> {
+    let result = sqlx::query(r#"DELETE FROM meal_plans WHERE id = $1 AND user_id = $2"#)
+        .bind(plan_id)
+        .bind(user_id)
+        .execute(db)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Slots in day order, breakfast to snack.
+pub async fn list_slots(db: impl PgExecutor<'_>, plan_id: Uuid) -> anyhow::Result<Vec<SlotRow>> {
+    let slots = sqlx::query_as::<_, SlotRow>(
+        r#"
+        SELECT s.id, s.date, s.meal_type, s.recipe_id, s.template_id,
+               s.servings::float8 AS servings, COALESCE(r.name, t.name) AS name
+        FROM meal_plan_slots s
+        LEFT JOIN recipes r ON r.id = s.recipe_id
+        LEFT JOIN meal_templates t ON t.id = s.template_id
+        WHERE s.plan_id = $1
+        ORDER BY s.date,
+                 array_position(ARRAY['breakfast', 'lunch', 'dinner', 'snack'], s.meal_type)
+        "#,
+    )
+    .bind(plan_id)
+    .fetch_all(db)
+    .await?;
+    Ok(slots)
+}
+
+pub async fn upsert_slot(
+    db: impl PgExecutor<'_>,
+    plan_id: Uuid,
+    slot: &SlotRequest,
+    servings: f64,
+) -> anyhow::Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO meal_plan_slots (plan_id, date, meal_type, recipe_id, template_id, servings)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        ON CONFLICT (plan_id, date, meal_type) DO UPDATE
+        SET recipe_id = EXCLUDED.recipe_id,
+            template_id = EXCLUDED.template_id,
+            servings = EXCLUDED.servings
+        "#,
+    )
+    .bind(plan_id)
+    .bind(slot.date)
+    .bind(slot.meal_type)
+    .bind(slot.recipe_id)
+    .bind(slot.template_id)
+    .bind(servings)
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+pub async fn delete_slot(
+    db: impl PgExecutor<'_>,
+    plan_id: Uuid,
+    slot_id: Uuid,
+) -> anyhow::Result<bool> {
+    let result = sqlx::query(r#"DELETE FROM meal_plan_slots WHERE id = $1 AND plan_id = $2"#)
+        .bind(slot_id)
+        .bind(plan_id)
+        .execute(db)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
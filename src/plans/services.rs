@@ -0,0 +1,228 @@
+use std::collections::HashMap;
+
+use axum::http::StatusCode;
+use sqlx::PgConnection;
+use time::Date;
+use uuid::Uuid;
+
+use crate::{
+    db::AppState,
+    error::ErrorCode,
+    foods::dto::FoodNutrition,
+    plans::{
+        dto::{week_start, MealPlan, PlanDay, PlanRow, PlanSlot, SlotRequest},
+        repo,
+    },
+    recipes::repo as recipes_repo,
+    templates::repo as templates_repo,
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum PlanError {
+    #[error("Plan not found")]
+    NotFound,
+    #[error("Slot not found")]
+    SlotNotFound,
+    #[error("Recipe not found")]
+    RecipeNotFound,
+    #[error("Template not found")]
+    TemplateNotFound,
+    #[error("A plan for this week already exists")]
+    Exists,
+    #[error("{0}")]
+    Invalid(String),
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+impl PlanError {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            PlanError::NotFound | PlanError::SlotNotFound => StatusCode::NOT_FOUND,
+            PlanError::RecipeNotFound | PlanError::TemplateNotFound => {
+                StatusCode::UNPROCESSABLE_ENTITY
+            }
+            PlanError::Exists => StatusCode::CONFLICT,
+            PlanError::Invalid(_) => StatusCode::BAD_REQUEST,
+            PlanError::Other(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            PlanError::NotFound => ErrorCode::PlanNotFound,
+            PlanError::SlotNotFound => ErrorCode::PlanSlotNotFound,
+            PlanError::RecipeNotFound => ErrorCode::RecipeNotFound,
+            PlanError::TemplateNotFound => ErrorCode::TemplateNotFound,
+            PlanError::Exists => ErrorCode::PlanExists,
+            PlanError::Invalid(_) => ErrorCode::ValidationFailed,
+            PlanError::Other(_) => ErrorCode::Internal,
+        }
+    }
+}
+
+/// Loads a plan's slots with planned nutrition: recipe servings or
+/// template copies.
+async fn with_slots(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    row: PlanRow,
+) -> anyhow::Result<MealPlan> {
+    let slots = repo::list_slots(&mut *conn, row.id).await?;
+    let mut per_recipe = HashMap::new();
+    let mut per_template = HashMap::new();
+    if slots.iter().any(|s| s.recipe_id.is_some()) {
+        per_recipe = recipes_repo::list_recipes(&mut *conn, user_id)
+            .await?
+            .into_iter()
+            .map(|r| (r.id, r.nutrition_per_serving))
+            .collect();
+    }
+    if slots.iter().any(|s| s.template_id.is_some()) {
+        per_template = templates_repo::list_templates(&mut *conn, user_id)
+            .await?
+            .into_iter()
+            .map(|t| (t.id, t.total_nutrition()))
+            .collect();
+    }
+    let slots = slots
+        .into_iter()
+        .map(|slot| {
+            let per_unit: Option<&FoodNutrition> = match (slot.recipe_id, slot.template_id) {
+                (Some(id), _) => per_recipe.get(&id),
+                (None, Some(id)) => per_template.get(&id),
+                (None, None) => None,
+            };
+            PlanSlot {
+                nutrition: per_unit
+                    .map(|n| n.scaled(slot.servings))
+                    .unwrap_or_default(),
+                id: slot.id,
+                date: slot.date,
+                meal_type: slot.meal_type,
+                recipe_id: slot.recipe_id,
+                template_id: slot.template_id,
+                name: slot.name,
+                servings: slot.servings,
+            }
+        })
+        .collect();
+    Ok(MealPlan::new(row, slots))
+}
+
+pub async fn create_plan(
+    state: &AppState,
+    user_id: Uuid,
+    week_start: Date,
+) -> Result<MealPlan, PlanError> {
+    let mut tx = state.begin_as(user_id).await.map_err(anyhow::Error::from)?;
+    let row = repo::insert_plan(&mut *tx, user_id, week_start)
+        .await?
+        .ok_or(PlanError::Exists)?;
+    let plan = with_slots(&mut tx, user_id, row).await?;
+    tx.commit().await.map_err(anyhow::Error::from)?;
+    state.cache.invalidate_summaries(user_id).await;
+    Ok(plan)
+}
+
+pub async fn get_plan(
+    state: &AppState,
+    user_id: Uuid,
+    plan_id: Uuid,
+) -> Result<MealPlan, PlanError> {
+    let mut tx = state.begin_as(user_id).await.map_err(anyhow::Error::from)?;
+    let row = repo::find_plan(&mut *tx, user_id, plan_id)
+        .await?
+        .ok_or(PlanError::NotFound)?;
+    let plan = with_slots(&mut tx, user_id, row).await?;
+    tx.commit().await.map_err(anyhow::Error::from)?;
+    Ok(plan)
+}
+
+/// The plan of the week containing `date`, if there is one.
+pub async fn plan_for_week_of(
+    state: &AppState,
+    user_id: Uuid,
+    date: Date,
+) -> anyhow::Result<Option<MealPlan>> {
+    let mut tx = state.begin_as(user_id).await?;
+    let plan = match repo::find_plan_for_week(&mut *tx, user_id, week_start(date)).await? {
+        Some(row) => Some(with_slots(&mut tx, user_id, row).await?),
+        None => None,
+    };
+    tx.commit().await?;
+    Ok(plan)
+}
+
+/// What is planned for one day, or `None` without a plan covering it.
+pub async fn planned_day(
+    state: &AppState,
+    user_id: Uuid,
+    date: Date,
+) -> anyhow::Result<Option<PlanDay>> {
+    let plan = plan_for_week_of(state, user_id, date).await?;
+    Ok(plan.and_then(|p| p.days.into_iter().find(|d| d.date == date)))
+}
+
+pub async fn set_slot(
+    state: &AppState,
+    user_id: Uuid,
+    plan_id: Uuid,
+    slot: SlotRequest,
+) -> Result<MealPlan, PlanError> {
+    let mut tx = state.begin_as(user_id).await.map_err(anyhow::Error::from)?;
+    let row = repo::find_plan(&mut *tx, user_id, plan_id)
+        .await?
+        .ok_or(PlanError::NotFound)?;
+    let servings = slot.validate(row.week_start).map_err(PlanError::Invalid)?;
+    if let Some(recipe_id) = slot.recipe_id {
+        if recipes_repo::find_recipe(&mut *tx, user_id, recipe_id)
+            .await?
+            .is_none()
+        {
+            return Err(PlanError::RecipeNotFound);
+        }
+    }
+    if let Some(template_id) = slot.template_id {
+        if templates_repo::find_template(&mut *tx, user_id, template_id)
+            .await?
+            .is_none()
+        {
+            return Err(PlanError::TemplateNotFound);
+        }
+    }
+    repo::upsert_slot(&mut *tx, plan_id, &slot, servings).await?;
+    let plan = with_slots(&mut tx, user_id, row).await?;
+    tx.commit().await.map_err(anyhow::Error::from)?;
+    state.cache.invalidate_summaries(user_id).await;
+    Ok(plan)
+}
+
+/// `false` if the user has no such plan.
+pub async fn delete_plan(state: &AppState, user_id: Uuid, plan_id: Uuid) -> anyhow::Result<bool> {
+    let mut tx = state.begin_as(user_id).await?;
+    let deleted = repo::delete_plan(&mut *tx, user_id, plan_id).await?;
+    tx.commit().await?;
+    if deleted {
+        state.cache.invalidate_summaries(user_id).await;
+    }
+    Ok(deleted)
+}
+
+pub async fn delete_slot(
+    state: &AppState,
+    user_id: Uuid,
+    plan_id: Uuid,
+    slot_id: Uuid,
+) -> Result<(), PlanError> {
+    let mut tx = state.begin_as(user_id).await.map_err(anyhow::Error::from)?;
+    if repo::find_plan(&mut *tx, user_id, plan_id).await?.is_none() {
+        return Err(PlanError::NotFound);
+    }
+    if !repo::delete_slot(&mut *tx, plan_id, slot_id).await? {
+        return Err(PlanError::SlotNotFound);
+    }
+    tx.commit().await.map_err(anyhow::Error::from)?;
+    state.cache.invalidate_summaries(user_id).await;
+    Ok(())
+}
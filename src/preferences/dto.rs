@@ -0,0 +1,211 @@
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::{
+    activity::dto::DEFAULT_EAT_BACK_PERCENT,
+    analytics::dto::AnalyticsSharing,
+    i18n::Locale,
+    meals::{
+        compliance::DietProfile,
+        warnings::{Diet, Ingredient},
+    },
+    push::dto::{NotificationPreferences, UpdatePreferencesRequest as UpdateNotificationsRequest},
+    units::Units,
+};
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "text", rename_all = "lowercase")]
+pub enum WeekStart {
+    #[default]
+    Monday,
+    Saturday,
+    Sunday,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, sqlx::FromRow)]
+pub struct Preferences {
+    pub units: Units,
+    /// Language of pushes and other messages sent outside a request.
+    pub locale: Locale,
+    /// IANA name, e.g. `Europe/Berlin`.
+    pub timezone: String,
+    pub week_start: WeekStart,
+    /// Meal details warn about meals likely containing these.
+    pub allergens: Vec<Ingredient>,
+    pub diets: Vec<Diet>,
+    /// Meals and days are rated against it.
+    pub diet_profile: Option<DietProfile>,
+    /// Share of burned calories added to the day's calorie budget, 0–100.
+    pub activity_eat_back_percent: i16,
+    /// How much of the app's product analytics is recorded.
+    pub analytics: AnalyticsSharing,
+}
+
+impl Default for Preferences {
+    fn default() -> Self {
+        Self {
+            units: Units::default(),
+            locale: Locale::default(),
+            timezone: "UTC".into(),
+            week_start: WeekStart::default(),
+            allergens: Vec::new(),
+            diets: Vec::new(),
+            diet_profile: None,
+            activity_eat_back_percent: DEFAULT_EAT_BACK_PERCENT,
+            analytics: AnalyticsSharing::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PreferencesResponse {
+    #[serde(flatten)]
+    pub preferences: Preferences,
+    pub notifications: NotificationPreferences,
+}
+
+/// Omitted fields keep their current value.
+#[derive(Debug, Default, Deserialize)]
+pub struct UpdatePreferencesRequest {
+    pub units: Option<Units>,
+    pub locale: Option<Locale>,
+    pub timezone: Option<String>,
+    pub week_start: Option<WeekStart>,
+    /// Replaces the declared allergens; `[]` clears them.
+    pub allergens: Option<Vec<Ingredient>>,
+    pub diets: Option<Vec<Diet>>,
+    /// `null` stops rating meals against a profile.
+    #[serde(default, deserialize_with = "double_option")]
+    pub diet_profile: Option<Option<DietProfile>>,
+    pub activity_eat_back_percent: Option<i16>,
+    pub analytics: Option<AnalyticsSharing>,
+    pub notifications: Option<UpdateNotificationsRequest>,
+}
+
+/// Tells an explicit `null` (`Some(None)`) from a missing field (`None`).
+fn double_option<'de, T, D>(d: D) -> Result<Option<Option<T>>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    Option::<T>::deserialize(d).map(Some)
+}
+
+impl UpdatePreferencesRequest {
+    /// Applies the account settings; the time zone is checked against the
+    /// database by the caller.
+    pub fn apply(&self, prefs: &mut Preferences) -> Result<(), String> {
+        if let Some(timezone) = &self.timezone {
+            let timezone = timezone.trim();
+            if timezone.is_empty() {
+                return Err("timezone must not be empty".into());
+            }
+            prefs.timezone = timezone.to_string();
+        }
+        if let Some(units) = self.units {
+            prefs.units = units;
+        }
+        if let Some(locale) = self.locale {
+            prefs.locale = locale;
+        }
+        if let Some(week_start) = self.week_start {
+            prefs.week_start = week_start;
+        }
+        if let Some(allergens) = &self.allergens {
+            if allergens.contains(&Ingredient::Meat) {
+                return Err("meat is not an allergen; use diets instead".into());
+            }
+            prefs.allergens = allergens.clone();
+            prefs.allergens.sort();
+            prefs.allergens.dedup();
+        }
+        if let Some(diets) = &self.diets {
+            prefs.diets = diets.clone();
+            prefs.diets.sort();
+            prefs.diets.dedup();
+        }
+        if let Some(profile) = self.diet_profile {
+            prefs.diet_profile = profile;
+        }
+        if let Some(percent) = self.activity_eat_back_percent {
+            if !(0..=100).contains(&percent) {
+                return Err("activity_eat_back_percent must be between 0 and 100".into());
+            }
+            prefs.activity_eat_back_percent = percent;
+        }
+        if let Some(analytics) = self.analytics {
+            prefs.analytics = analytics;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn update_keeps_omitted_fields() {
+        let mut prefs = Preferences::default();
+        let update: UpdatePreferencesRequest =
+            serde_json::from_str(r#"{"units": "imperial", "locale": "de"}"#).unwrap();
+        update.apply(&mut prefs).unwrap();
+        assert_eq!(prefs.units, Units::Imperial);
+        assert_eq!(prefs.locale, Locale::De);
+        assert_eq!(prefs.timezone, "UTC");
+        assert_eq!(prefs.week_start, WeekStart::Monday);
+
+        let update = UpdatePreferencesRequest {
+            timezone: Some("  ".into()),
+            ..Default::default()
+        };
+        assert!(update.apply(&mut prefs).is_err());
+    }
+
+    #[test]
+    fn update_replaces_allergens_and_diets() {
+        let mut prefs = Preferences::default();
+        let update: UpdatePreferencesRequest = serde_json::from_str(
+            r#"{"allergens": ["sesame", "peanuts", "sesame"], "diets": ["vegan"]}"#,
+        )
+        .unwrap();
+        update.apply(&mut prefs).unwrap();
+        assert_eq!(prefs.allergens, [Ingredient::Peanuts, Ingredient::Sesame]);
+        assert_eq!(prefs.diets, [Diet::Vegan]);
+
+        let update: UpdatePreferencesRequest =
+            serde_json::from_str(r#"{"allergens": ["meat"]}"#).unwrap();
+        assert!(update.apply(&mut prefs).is_err());
+        assert!(
+            serde_json::from_str::<UpdatePreferencesRequest>(r#"{"diets": ["keto"]}"#).is_err()
+        );
+    }
+
+    #[test]
+    fn eat_back_percent_is_a_percentage() {
+        let mut prefs = Preferences::default();
+        let update = UpdatePreferencesRequest {
+            activity_eat_back_percent: Some(75),
+            ..Default::default()
+        };
+        update.apply(&mut prefs).unwrap();
+        assert_eq!(prefs.activity_eat_back_percent, 75);
+        let update = UpdatePreferencesRequest {
+            activity_eat_back_percent: Some(101),
+            ..Default::default()
+        };
+        assert!(update.apply(&mut prefs).is_err());
+    }
+
+    #[test]
+    fn response_nests_notifications() {
+        let response = PreferencesResponse {
+            preferences: Preferences::default(),
+            notifications: NotificationPreferences::default(),
+        };
+        let json = serde_json::to_value(&response).unwrap();
+        assert_eq!(json["units"], "metric");
+        assert_eq!(json["week_start"], "monday");
+        assert_eq!(json["notifications"]["reminder_hour"], 20);
+    }
+}
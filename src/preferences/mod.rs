@@ -0,0 +1,7 @@
+//! Account-wide settings: units, language, time zone and first day of the
+//! week. Notification switches live in [`push`](crate::push) and are served
+//! alongside.
+
+pub mod dto;
+pub mod repo;
+pub mod services;
@@ -0,0 +1,72 @@
+use sqlx::PgExecutor;
+use uuid::Uuid;
+
+use crate::preferences::dto::Preferences;
+
+pub async fn find_preferences(
+    db: impl PgExecutor<'_>,
+    user_id: Uuid,
+) -> anyhow::Result<Option<Preferences>> {
+    let prefs = sqlx::query_as::<_, Preferences>(
+        r#"
+        SELECT units, locale, timezone, week_start, allergens, diets, diet_profile,
+               activity_eat_back_percent, analytics
+        FROM user_preferences
+        WHERE user_id = $1
+        "#,
+    )
+    .bind(user_id)
+    .fetch_optional(db)
+    .await?;
+    Ok(prefs)
+}
+
+pub async fn upsert_preferences(
+    db: impl PgExecutor<'_>,
+    user_id: Uuid,
+    prefs: &Preferences,
+) -> anyhow::Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO user_preferences (user_id, units, locale, timezone, week_start, allergens,
+                                      diets, diet_profile, activity_eat_back_percent,
+                                      analytics)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+        ON CONFLICT (user_id) DO UPDATE
+        SET units = EXCLUDED.units,
+            locale = EXCLUDED.locale,
+            timezone = EXCLUDED.timezone,
+            week_start = EXCLUDED.week_start,
+            allergens = EXCLUDED.allergens,
+            diets = EXCLUDED.diets,
+            diet_profile = EXCLUDED.diet_profile,
+            activity_eat_back_percent = EXCLUDED.activity_eat_back_percent,
+            analytics = EXCLUDED.analytics,
+            updated_at = NOW()
+        "#,
+    )
+    .bind(user_id)
+    .bind(prefs.units)
+    .bind(prefs.locale)
+    .bind(&prefs.timezone)
+    .bind(prefs.week_start)
+    .bind(&prefs.allergens)
+    .bind(&prefs.diets)
+    .bind(prefs.diet_profile)
+    .bind(prefs.activity_eat_back_percent)
+    .bind(prefs.analytics)
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+/// The user's IANA time zone; `UTC` without preferences.
+pub async fn timezone(db: impl PgExecutor<'_>, user_id: Uuid) -> anyhow::Result<String> {
+    let tz = sqlx::query_scalar::<_, String>(
+        r#"SELECT timezone FROM user_preferences WHERE user_id = $1"#,
+    )
+    .bind(user_id)
+    .fetch_optional(db)
+    .await?;
+    Ok(tz.unwrap_or_else(|| "UTC".to_string()))
+}
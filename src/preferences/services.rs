@@ -0,0 +1,124 @@
+use axum::http::StatusCode;
+use uuid::Uuid;
+
+use crate::{
+    db::AppState,
+    error::ErrorCode,
+    preferences::{
+        dto::{Preferences, PreferencesResponse, UpdatePreferencesRequest},
+        repo,
+    },
+    push::repo as push_repo,
+    stats::repo as stats_repo,
+    units::Units,
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum PreferencesError {
+    #[error("{0}")]
+    Invalid(String),
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+impl PreferencesError {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            PreferencesError::Invalid(_) => StatusCode::BAD_REQUEST,
+            PreferencesError::Other(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            PreferencesError::Invalid(_) => ErrorCode::ValidationFailed,
+            PreferencesError::Other(_) => ErrorCode::Internal,
+        }
+    }
+}
+
+/// The user's settings, or the defaults for a user who never saved any.
+pub async fn preferences(state: &AppState, user_id: Uuid) -> anyhow::Result<Preferences> {
+    let mut tx = state.begin_as(user_id).await?;
+    let prefs = repo::find_preferences(&mut *tx, user_id).await?;
+    tx.commit().await?;
+    Ok(prefs.unwrap_or_default())
+}
+
+/// The user's IANA time zone; `UTC` without preferences.
+pub async fn timezone(state: &AppState, user_id: Uuid) -> anyhow::Result<String> {
+    let mut tx = state.begin_as(user_id).await?;
+    let tz = repo::timezone(&mut *tx, user_id).await?;
+    tx.commit().await?;
+    Ok(tz)
+}
+
+/// The unit system asked for, or else the one of the user's preferences.
+pub async fn units(
+    state: &AppState,
+    user_id: Uuid,
+    requested: Option<Units>,
+) -> anyhow::Result<Units> {
+    match requested {
+        Some(units) => Ok(units),
+        None => Ok(preferences(state, user_id).await?.units),
+    }
+}
+
+pub async fn get_preferences(
+    state: &AppState,
+    user_id: Uuid,
+) -> Result<PreferencesResponse, PreferencesError> {
+    let mut tx = state.begin_as(user_id).await.map_err(anyhow::Error::from)?;
+    let preferences = repo::find_preferences(&mut *tx, user_id).await?;
+    let notifications = push_repo::find_preferences(&mut *tx, user_id).await?;
+    tx.commit().await.map_err(anyhow::Error::from)?;
+    Ok(PreferencesResponse {
+        preferences: preferences.unwrap_or_default(),
+        notifications: notifications.unwrap_or_default(),
+    })
+}
+
+/// Applies a partial update of the settings and notification switches.
+pub async fn update_preferences(
+    state: &AppState,
+    user_id: Uuid,
+    input: UpdatePreferencesRequest,
+) -> Result<PreferencesResponse, PreferencesError> {
+    let PreferencesResponse {
+        mut preferences,
+        mut notifications,
+    } = get_preferences(state, user_id).await?;
+    input
+        .apply(&mut preferences)
+        .map_err(PreferencesError::Invalid)?;
+    if let Some(update) = &input.notifications {
+        update
+            .apply(&mut notifications)
+            .map_err(PreferencesError::Invalid)?;
+    }
+    let timezone_changed = input.timezone.is_some();
+    let budget_changed = input.diet_profile.is_some() || input.activity_eat_back_percent.is_some();
+    if timezone_changed && !stats_repo::timezone_exists(&state.db, &preferences.timezone).await? {
+        return Err(PreferencesError::Invalid(format!(
+            "Unknown time zone: {}",
+            preferences.timezone
+        )));
+    }
+
+    let mut tx = state.begin_as(user_id).await.map_err(anyhow::Error::from)?;
+    repo::upsert_preferences(&mut *tx, user_id, &preferences).await?;
+    if input.notifications.is_some() {
+        push_repo::upsert_preferences(&mut *tx, user_id, &notifications).await?;
+    }
+    tx.commit().await.map_err(anyhow::Error::from)?;
+    if timezone_changed || budget_changed {
+        // Summaries cover the user's local days, rate them against the diet
+        // profile and add burned calories to the budget.
+        state.cache.invalidate_summaries(user_id).await;
+    }
+    Ok(PreferencesResponse {
+        preferences,
+        notifications,
+    })
+}
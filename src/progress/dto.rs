@@ -0,0 +1,263 @@
+use serde::{Deserialize, Serialize};
+use time::{Duration, OffsetDateTime};
+use uuid::Uuid;
+
+pub const MIN_WAIST_CM: f64 = 30.0;
+pub const MAX_WAIST_CM: f64 = 300.0;
+pub const MIN_BODY_FAT_PERCENT: f64 = 2.0;
+pub const MAX_BODY_FAT_PERCENT: f64 = 75.0;
+pub const MAX_NOTE_LEN: usize = 500;
+/// Range returned when `from` is omitted.
+pub const DEFAULT_RANGE_DAYS: i64 = 365;
+
+/// Trims `note`, dropping it when blank, and checks its length.
+fn normalize_note(note: Option<String>) -> Result<Option<String>, String> {
+    let note = note.map(|n| n.trim().to_string()).filter(|n| !n.is_empty());
+    if note
+        .as_ref()
+        .is_some_and(|n| n.chars().count() > MAX_NOTE_LEN)
+    {
+        return Err(format!("note must be at most {} characters", MAX_NOTE_LEN));
+    }
+    Ok(note)
+}
+
+fn check_not_future(at: Option<OffsetDateTime>, field: &str) -> Result<(), String> {
+    if at.is_some_and(|at| at > OffsetDateTime::now_utc() + Duration::minutes(5)) {
+        return Err(format!("{} can't be in the future", field));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NewMeasurementRequest {
+    pub waist_cm: Option<f64>,
+    pub body_fat_percent: Option<f64>,
+    /// Defaults to now.
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub measured_at: Option<OffsetDateTime>,
+    pub note: Option<String>,
+}
+
+impl NewMeasurementRequest {
+    /// Needs at least one value; checks ranges, trims the note and rejects
+    /// measurements in the future.
+    pub fn normalized(self) -> Result<Self, String> {
+        if self.waist_cm.is_none() && self.body_fat_percent.is_none() {
+            return Err("waist_cm or body_fat_percent is required".into());
+        }
+        if self
+            .waist_cm
+            .is_some_and(|v| !v.is_finite() || !(MIN_WAIST_CM..=MAX_WAIST_CM).contains(&v))
+        {
+            return Err(format!(
+                "waist_cm must be between {} and {}",
+                MIN_WAIST_CM, MAX_WAIST_CM
+            ));
+        }
+        if self.body_fat_percent.is_some_and(|v| {
+            !v.is_finite() || !(MIN_BODY_FAT_PERCENT..=MAX_BODY_FAT_PERCENT).contains(&v)
+        }) {
+            return Err(format!(
+                "body_fat_percent must be between {} and {}",
+                MIN_BODY_FAT_PERCENT, MAX_BODY_FAT_PERCENT
+            ));
+        }
+        check_not_future(self.measured_at, "measured_at")?;
+        let note = normalize_note(self.note)?;
+        Ok(Self { note, ..self })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ProgressRangeQuery {
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub from: Option<OffsetDateTime>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub to: Option<OffsetDateTime>,
+}
+
+impl ProgressRangeQuery {
+    pub fn validate(&self) -> Result<(), String> {
+        match (self.from, self.to) {
+            (Some(from), Some(to)) if from >= to => Err("from must be before to".into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// `from`, or [`DEFAULT_RANGE_DAYS`] before `to` (or now).
+    pub fn start(&self) -> OffsetDateTime {
+        self.from.unwrap_or_else(|| {
+            self.to.unwrap_or_else(OffsetDateTime::now_utc) - Duration::days(DEFAULT_RANGE_DAYS)
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct MeasurementEntry {
+    pub id: Uuid,
+    pub waist_cm: Option<f64>,
+    pub body_fat_percent: Option<f64>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub measured_at: OffsetDateTime,
+    pub note: Option<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MeasurementsResponse {
+    /// Oldest first.
+    pub entries: Vec<MeasurementEntry>,
+    /// Last minus first waist measurement in the range.
+    pub waist_change_cm: Option<f64>,
+    /// Last minus first body fat measurement in the range.
+    pub body_fat_change_percent: Option<f64>,
+}
+
+impl MeasurementsResponse {
+    /// `entries` must be sorted by `measured_at`.
+    pub fn new(entries: Vec<MeasurementEntry>) -> Self {
+        let change = |value: fn(&MeasurementEntry) -> Option<f64>| {
+            let mut values = entries.iter().filter_map(value);
+            let first = values.next()?;
+            let last = values.next_back()?;
+            Some(((last - first) * 10.0).round() / 10.0)
+        };
+        Self {
+            waist_change_cm: change(|e| e.waist_cm),
+            body_fat_change_percent: change(|e| e.body_fat_percent),
+            entries,
+        }
+    }
+}
+
+/// Fields of a progress photo upload besides the image itself.
+#[derive(Debug, Default)]
+pub struct NewProgressPhoto {
+    /// Defaults to now.
+    pub taken_at: Option<OffsetDateTime>,
+    pub note: Option<String>,
+}
+
+impl NewProgressPhoto {
+    pub fn normalized(self) -> Result<Self, String> {
+        check_not_future(self.taken_at, "taken_at")?;
+        Ok(Self {
+            note: normalize_note(self.note)?,
+            ..self
+        })
+    }
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct ProgressPhoto {
+    pub id: Uuid,
+    pub s3_key: String,
+    pub content_type: String,
+    pub size_bytes: i64,
+    pub taken_at: OffsetDateTime,
+    pub note: Option<String>,
+    pub created_at: OffsetDateTime,
+}
+
+/// A progress photo with a URL the client can fetch until `expires_at`.
+#[derive(Debug, Serialize)]
+pub struct ProgressPhotoResponse {
+    pub id: Uuid,
+    pub content_type: String,
+    pub size_bytes: i64,
+    #[serde(with = "time::serde::rfc3339")]
+    pub taken_at: OffsetDateTime,
+    pub note: Option<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    pub url: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub expires_at: OffsetDateTime,
+}
+
+impl ProgressPhotoResponse {
+    pub fn new(photo: ProgressPhoto, url: String, expires_at: OffsetDateTime) -> Self {
+        Self {
+            id: photo.id,
+            content_type: photo.content_type,
+            size_bytes: photo.size_bytes,
+            taken_at: photo.taken_at,
+            note: photo.note,
+            created_at: photo.created_at,
+            url,
+            expires_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProgressPhotoUrl {
+    pub url: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub expires_at: OffsetDateTime,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::datetime;
+
+    fn request(waist_cm: Option<f64>, body_fat_percent: Option<f64>) -> NewMeasurementRequest {
+        NewMeasurementRequest {
+            waist_cm,
+            body_fat_percent,
+            measured_at: None,
+            note: Some("  morning ".into()),
+        }
+    }
+
+    fn entry(waist_cm: Option<f64>, body_fat_percent: Option<f64>) -> MeasurementEntry {
+        let at = datetime!(2024-03-01 07:00 UTC);
+        MeasurementEntry {
+            id: Uuid::new_v4(),
+            waist_cm,
+            body_fat_percent,
+            measured_at: at,
+            note: None,
+            created_at: at,
+        }
+    }
+
+    #[test]
+    fn measurements_need_a_value_in_range() {
+        assert_eq!(
+            request(Some(82.5), None)
+                .normalized()
+                .unwrap()
+                .note
+                .as_deref(),
+            Some("morning")
+        );
+        assert!(request(None, Some(18.0)).normalized().is_ok());
+        assert!(request(None, None).normalized().is_err());
+        assert!(request(Some(5.0), None).normalized().is_err());
+        assert!(request(None, Some(f64::NAN)).normalized().is_err());
+    }
+
+    #[test]
+    fn changes_use_the_first_and_last_value_of_each_kind() {
+        let response = MeasurementsResponse::new(vec![
+            entry(Some(90.0), None),
+            entry(None, Some(24.0)),
+            entry(Some(87.5), None),
+        ]);
+        assert_eq!(response.waist_change_cm, Some(-2.5));
+        assert_eq!(response.body_fat_change_percent, None);
+    }
+
+    #[test]
+    fn future_progress_photos_are_rejected() {
+        let photo = NewProgressPhoto {
+            taken_at: Some(OffsetDateTime::now_utc() + Duration::days(1)),
+            note: None,
+        };
+        assert!(photo.normalized().is_err());
+    }
+}
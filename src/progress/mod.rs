@@ -0,0 +1,8 @@
+//! Body progress beyond the scale: waist and body fat measurements, and
+//! private progress photos. Progress photos live under their own storage
+//! prefix, `users/{user_id}/progress/`, and have their own table, so nothing
+//! that handles meal photos (orphan cleanup, archive, sharing) touches them.
+
+pub mod dto;
+pub mod repo;
+pub mod services;
@@ -0,0 +1,160 @@
+use sqlx::PgExecutor;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::progress::dto::{MeasurementEntry, NewMeasurementRequest, ProgressPhoto};
+
+pub async fn insert_measurement(
+    db: impl PgExecutor<'_>,
+    user_id: Uuid,
+    input: &NewMeasurementRequest,
+) -> anyhow::Result<MeasurementEntry> {
+    let entry = sqlx::query_as::<_, MeasurementEntry>(
+        r#"
+        INSERT INTO body_measurements (user_id, waist_cm, body_fat_percent, measured_at, note)
+        VALUES ($1, $2, $3, COALESCE($4, NOW()), $5)
+        RETURNING id, waist_cm::float8 AS waist_cm, body_fat_percent::float8 AS body_fat_percent,
+                  measured_at, note, created_at
+        "#,
+    )
+    .bind(user_id)
+    .bind(input.waist_cm)
+    .bind(input.body_fat_percent)
+    .bind(input.measured_at)
+    .bind(&input.note)
+    .fetch_one(db)
+    .await?;
+    Ok(entry)
+}
+
+/// Measurements from `from` up to (excluding) `to`, oldest first.
+pub async fn list_measurements(
+    db: impl PgExecutor<'_>,
+    user_id: Uuid,
+    from: OffsetDateTime,
+    to: Option<OffsetDateTime>,
+) -> anyhow::Result<Vec<MeasurementEntry>> {
+    let entries = sqlx::query_as::<_, MeasurementEntry>(
+        r#"
+        SELECT id, waist_cm::float8 AS waist_cm, body_fat_percent::float8 AS body_fat_percent,
+               measured_at, note, created_at
+        FROM body_measurements
+        WHERE user_id = $1
+          AND measured_at >= $2
+          AND ($3::timestamptz IS NULL OR measured_at < $3)
+        ORDER BY measured_at, id
+        "#,
+    )
+    .bind(user_id)
+    .bind(from)
+    .bind(to)
+    .fetch_all(db)
+    .await?;
+    Ok(entries)
+}
+
+pub async fn delete_measurement(
+    db: impl PgExecutor<'_>,
+    user_id: Uuid,
+    measurement_id: Uuid,
+) -> anyhow::Result<bool> {
+    let result = sqlx::query(r#"DELETE FROM body_measurements WHERE id = $1 AND user_id = $2"#)
+        .bind(measurement_id)
+        .bind(user_id)
+        .execute(db)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+pub struct NewProgressPhotoRow<'a> {
+    pub id: Uuid,
+    pub s3_key: &'a str,
+    pub content_type: &'a str,
+    pub size_bytes: i64,
+    pub taken_at: Option<OffsetDateTime>,
+    pub note: Option<&'a str>,
+}
+
+pub async fn insert_photo(
+    db: impl PgExecutor<'_>,
+    user_id: Uuid,
+    photo: &NewProgressPhotoRow<'_>,
+) -> anyhow::Result<ProgressPhoto> {
+    let photo = sqlx::query_as::<_, ProgressPhoto>(
+        r#"
+        INSERT INTO progress_photos (id, user_id, s3_key, content_type, size_bytes, taken_at, note)
+        VALUES ($1, $2, $3, $4, $5, COALESCE($6, NOW()), $7)
+        RETURNING id, s3_key, content_type, size_bytes, taken_at, note, created_at
+        "#,
+    )
+    .bind(photo.id)
+    .bind(user_id)
+    .bind(photo.s3_key)
+    .bind(photo.content_type)
+    .bind(photo.size_bytes)
+    .bind(photo.taken_at)
+    .bind(photo.note)
+    .fetch_one(db)
+    .await?;
+    Ok(photo)
+}
+
+/// Photos taken from `from` up to (excluding) `to`, oldest first.
+pub async fn list_photos(
+    db: impl PgExecutor<'_>,
+    user_id: Uuid,
+    from: OffsetDateTime,
+    to: Option<OffsetDateTime>,
+) -> anyhow::Result<Vec<ProgressPhoto>> {
+    let photos = sqlx::query_as::<_, ProgressPhoto>(
+        r#"
+        SELECT id, s3_key, content_type, size_bytes, taken_at, note, created_at
+        FROM progress_photos
+        WHERE user_id = $1
+          AND taken_at >= $2
+          AND ($3::timestamptz IS NULL OR taken_at < $3)
+        ORDER BY taken_at, id
+        "#,
+    )
+    .bind(user_id)
+    .bind(from)
+    .bind(to)
+    .fetch_all(db)
+    .await?;
+    Ok(photos)
+}
+
+pub async fn find_photo(
+    db: impl PgExecutor<'_>,
+    user_id: Uuid,
+    photo_id: Uuid,
+) -> anyhow::Result<Option<ProgressPhoto>> {
+    let photo = sqlx::query_as::<_, ProgressPhoto>(
+        r#"
+        SELECT id, s3_key, content_type, size_bytes, taken_at, note, created_at
+        FROM progress_photos
+        WHERE id = $1 AND user_id = $2
+        "#,
+    )
+    .bind(photo_id)
+    .bind(user_id)
+    .fetch_optional(db)
+    .await?;
+    Ok(photo)
+}
+
+/// Deletes the row and returns the key of its stored object.
+pub async fn delete_photo(
+    db: impl PgExecutor<'_>,
+    user_id: Uuid,
+    photo_id: Uuid,
+) -> anyhow::Result<Option<String>> {
+    let key = sqlx::query_scalar::<_, String>(
+        r#"DELETE FROM progress_photos WHERE id = $1 AND user_id = $2 RETURNING s3_key"#,
+    )
+    .bind(photo_id)
+    .bind(user_id)
+    .fetch_optional(db)
+    .await?;
+    Ok(key)
+}
@@ -0,0 +1,161 @@
+use futures::future::try_join_all;
+use time::OffsetDateTime;
+use tokio::sync::Semaphore;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::{
+    db::AppState,
+    images::{dto::NormalizedImage, services::STORAGE_CONCURRENCY},
+    progress::{
+        dto::{
+            MeasurementEntry, MeasurementsResponse, NewMeasurementRequest, NewProgressPhoto,
+            ProgressPhoto, ProgressPhotoResponse, ProgressPhotoUrl, ProgressRangeQuery,
+        },
+        repo::{self, NewProgressPhotoRow},
+    },
+};
+
+pub fn object_key(user_id: Uuid, photo_id: Uuid, extension: &str) -> String {
+    format!("users/{}/progress/{}.{}", user_id, photo_id, extension)
+}
+
+pub async fn create_measurement(
+    state: &AppState,
+    user_id: Uuid,
+    input: &NewMeasurementRequest,
+) -> anyhow::Result<MeasurementEntry> {
+    let mut tx = state.begin_as(user_id).await?;
+    let entry = repo::insert_measurement(&mut *tx, user_id, input).await?;
+    tx.commit().await?;
+    Ok(entry)
+}
+
+/// Measurements in the requested range (the last year by default).
+pub async fn list_measurements(
+    state: &AppState,
+    user_id: Uuid,
+    query: &ProgressRangeQuery,
+) -> anyhow::Result<MeasurementsResponse> {
+    let mut tx = state.begin_as(user_id).await?;
+    let entries = repo::list_measurements(&mut *tx, user_id, query.start(), query.to).await?;
+    tx.commit().await?;
+    Ok(MeasurementsResponse::new(entries))
+}
+
+/// `false` if the user has no such measurement.
+pub async fn delete_measurement(
+    state: &AppState,
+    user_id: Uuid,
+    measurement_id: Uuid,
+) -> anyhow::Result<bool> {
+    let mut tx = state.begin_as(user_id).await?;
+    let deleted = repo::delete_measurement(&mut *tx, user_id, measurement_id).await?;
+    tx.commit().await?;
+    Ok(deleted)
+}
+
+/// Stores the image under the progress prefix and records it. The object is
+/// deleted again when the row can't be written.
+pub async fn upload_photo(
+    state: &AppState,
+    user_id: Uuid,
+    image: NormalizedImage,
+    input: NewProgressPhoto,
+) -> anyhow::Result<ProgressPhotoResponse> {
+    let id = Uuid::new_v4();
+    let key = object_key(user_id, id, image.extension());
+    let size_bytes = image.bytes.len() as i64;
+    state
+        .storage
+        .put_object(&key, image.bytes, &image.content_type)
+        .await?;
+
+    let row = NewProgressPhotoRow {
+        id,
+        s3_key: &key,
+        content_type: &image.content_type,
+        size_bytes,
+        taken_at: input.taken_at,
+        note: input.note.as_deref(),
+    };
+    let saved = async {
+        let mut tx = state.begin_as(user_id).await?;
+        let photo = repo::insert_photo(&mut *tx, user_id, &row).await?;
+        tx.commit().await?;
+        Ok::<_, anyhow::Error>(photo)
+    }
+    .await;
+    let photo = match saved {
+        Ok(photo) => photo,
+        Err(e) => {
+            if let Err(e) = state.storage.delete_object(&key).await {
+                warn!(error = %e, key = %key, "failed to delete unsaved progress photo");
+            }
+            return Err(e);
+        }
+    };
+    presign(state, photo).await
+}
+
+async fn presign(state: &AppState, photo: ProgressPhoto) -> anyhow::Result<ProgressPhotoResponse> {
+    let ttl = state.config.photo_urls.ttl();
+    let expires_at = OffsetDateTime::now_utc() + ttl;
+    let url = state.storage.presign_get(&photo.s3_key, ttl).await?;
+    Ok(ProgressPhotoResponse::new(photo, url, expires_at))
+}
+
+/// Photos taken in the requested range (the last year by default), oldest
+/// first, each with a fresh presigned URL.
+pub async fn list_photos(
+    state: &AppState,
+    user_id: Uuid,
+    query: &ProgressRangeQuery,
+) -> anyhow::Result<Vec<ProgressPhotoResponse>> {
+    let mut tx = state.begin_as(user_id).await?;
+    let photos = repo::list_photos(&mut *tx, user_id, query.start(), query.to).await?;
+    tx.commit().await?;
+
+    let limit = Semaphore::new(STORAGE_CONCURRENCY);
+    try_join_all(photos.into_iter().map(|photo| {
+        let limit = &limit;
+        async move {
+            let _permit = limit.acquire().await?;
+            presign(state, photo).await
+        }
+    }))
+    .await
+}
+
+/// A fresh presigned URL for one photo; `None` if the user has no such photo.
+pub async fn photo_url(
+    state: &AppState,
+    user_id: Uuid,
+    photo_id: Uuid,
+) -> anyhow::Result<Option<ProgressPhotoUrl>> {
+    let mut tx = state.begin_as(user_id).await?;
+    let photo = repo::find_photo(&mut *tx, user_id, photo_id).await?;
+    tx.commit().await?;
+    let Some(photo) = photo else {
+        return Ok(None);
+    };
+    let ttl = state.config.photo_urls.ttl();
+    let expires_at = OffsetDateTime::now_utc() + ttl;
+    let url = state.storage.presign_get(&photo.s3_key, ttl).await?;
+    Ok(Some(ProgressPhotoUrl { url, expires_at }))
+}
+
+/// Removes the photo and its stored object; `false` if the user has no such
+/// photo. A failed object delete is only logged: the row is already gone.
+pub async fn delete_photo(state: &AppState, user_id: Uuid, photo_id: Uuid) -> anyhow::Result<bool> {
+    let mut tx = state.begin_as(user_id).await?;
+    let key = repo::delete_photo(&mut *tx, user_id, photo_id).await?;
+    tx.commit().await?;
+    let Some(key) = key else {
+        return Ok(false);
+    };
+    if let Err(e) = state.storage.delete_object(&key).await {
+        warn!(error = %e, key = %key, "failed to delete progress photo object");
+    }
+    Ok(true)
+}
@@ -0,0 +1,171 @@
+use std::time::{Duration, Instant};
+
+use anyhow::Context;
+use jsonwebtoken::{Algorithm, EncodingKey, Header};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use time::OffsetDateTime;
+use tokio::sync::Mutex;
+
+use crate::{
+    config::PushConfig,
+    push::{PushError, PushMessage, PushProvider},
+};
+
+const PRODUCTION_URL: &str = "https://api.push.apple.com";
+const SANDBOX_URL: &str = "https://api.sandbox.push.apple.com";
+/// Apple rejects provider tokens older than an hour and throttles refreshes
+/// more often than every 20 minutes.
+const TOKEN_LIFETIME: Duration = Duration::from_secs(50 * 60);
+
+#[derive(Deserialize)]
+struct ErrorBody {
+    reason: String,
+}
+
+/// Apple Push Notification service over HTTP/2 with token-based (`.p8` key)
+/// authentication.
+pub struct ApnsProvider {
+    http: reqwest::Client,
+    base_url: &'static str,
+    topic: String,
+    key_id: String,
+    team_id: String,
+    key: EncodingKey,
+    provider_token: Mutex<Option<(String, Instant)>>,
+}
+
+impl ApnsProvider {
+    pub fn from_config(
+        http: reqwest::Client,
+        key_path: &str,
+        config: &PushConfig,
+    ) -> anyhow::Result<Self> {
+        let required = |value: &Option<String>, name: &str| {
+            value
+                .clone()
+                .with_context(|| format!("{} is required for APNs", name))
+        };
+        Ok(Self {
+            http,
+            base_url: if config.apns_sandbox {
+                SANDBOX_URL
+            } else {
+                PRODUCTION_URL
+            },
+            topic: required(&config.apns_topic, "PUSH_APNS_TOPIC")?,
+            key_id: required(&config.apns_key_id, "PUSH_APNS_KEY_ID")?,
+            team_id: required(&config.apns_team_id, "PUSH_APNS_TEAM_ID")?,
+            key: EncodingKey::from_ec_pem(&std::fs::read(key_path)?)?,
+            provider_token: Mutex::new(None),
+        })
+    }
+
+    async fn provider_token(&self) -> anyhow::Result<String> {
+        let mut cached = self.provider_token.lock().await;
+        if let Some((token, issued)) = cached.as_ref() {
+            if issued.elapsed() < TOKEN_LIFETIME {
+                return Ok(token.clone());
+            }
+        }
+        let mut header = Header::new(Algorithm::ES256);
+        header.kid = Some(self.key_id.clone());
+        let claims = json!({
+            "iss": self.team_id,
+            "iat": OffsetDateTime::now_utc().unix_timestamp(),
+        });
+        let token = jsonwebtoken::encode(&header, &claims, &self.key)?;
+        *cached = Some((token.clone(), Instant::now()));
+        Ok(token)
+    }
+}
+
+/// The alert under `aps`, with `data` as custom top-level keys.
+fn payload(message: &PushMessage) -> Value {
+    let mut payload = json!({
+        "aps": {
+            "alert": {"title": message.title, "body": message.body},
+            "sound": "default",
+        }
+    });
+    for (key, value) in &message.data {
+        if key != "aps" {
+            payload[key] = json!(value);
+        }
+    }
+    payload
+}
+
+/// Whether a rejection means the token is dead rather than the request bad.
+fn is_unregistered(status: reqwest::StatusCode, reason: &str) -> bool {
+    status == reqwest::StatusCode::GONE
+        || matches!(
+            reason,
+            "BadDeviceToken" | "Unregistered" | "DeviceTokenNotForTopic"
+        )
+}
+
+#[axum::async_trait]
+impl PushProvider for ApnsProvider {
+    async fn send(&self, token: &str, message: &PushMessage) -> Result<(), PushError> {
+        let provider_token = self.provider_token().await?;
+        let response = self
+            .http
+            .post(format!("{}/3/device/{}", self.base_url, token))
+            .bearer_auth(provider_token)
+            .header("apns-topic", &self.topic)
+            .header("apns-push-type", "alert")
+            .json(&payload(message))
+            .send()
+            .await
+            .map_err(anyhow::Error::from)?;
+        let status = response.status();
+        if status.is_success() {
+            return Ok(());
+        }
+        let reason = response
+            .json::<ErrorBody>()
+            .await
+            .map(|body| body.reason)
+            .unwrap_or_default();
+        if is_unregistered(status, &reason) {
+            return Err(PushError::Unregistered);
+        }
+        Err(anyhow::anyhow!("APNs answered {}: {}", status, reason).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::StatusCode;
+
+    use super::*;
+
+    #[test]
+    fn payload_puts_data_next_to_aps() {
+        let message = PushMessage {
+            title: "Analysis complete".into(),
+            body: "Pasta has been analyzed".into(),
+            data: [
+                ("meal_id".to_string(), "abc".to_string()),
+                ("aps".to_string(), "ignored".to_string()),
+            ]
+            .into(),
+        };
+        let payload = payload(&message);
+        assert_eq!(payload["aps"]["alert"]["title"], "Analysis complete");
+        assert_eq!(payload["meal_id"], "abc");
+        assert!(payload["aps"].is_object());
+    }
+
+    #[test]
+    fn dead_tokens_are_recognized() {
+        assert!(is_unregistered(StatusCode::GONE, "Unregistered"));
+        assert!(is_unregistered(StatusCode::BAD_REQUEST, "BadDeviceToken"));
+        assert!(!is_unregistered(StatusCode::BAD_REQUEST, "PayloadTooLarge"));
+        assert!(!is_unregistered(
+            StatusCode::FORBIDDEN,
+            "ExpiredProviderToken"
+        ));
+    }
+}
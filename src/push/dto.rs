@@ -0,0 +1,142 @@
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+/// Devices kept per user; registering another drops the least recently seen.
+pub const MAX_DEVICES_PER_USER: i64 = 20;
+const MAX_TOKEN_LEN: usize = 4096;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+pub enum Platform {
+    /// Delivered through FCM.
+    Android,
+    /// Delivered through APNs.
+    Ios,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RegisterDeviceRequest {
+    pub platform: Platform,
+    pub token: String,
+}
+
+impl RegisterDeviceRequest {
+    pub fn validate(&self) -> Result<(), String> {
+        let token = self.token.trim();
+        if token.is_empty() {
+            return Err("token must not be empty".into());
+        }
+        if token.len() > MAX_TOKEN_LEN || token.chars().any(char::is_whitespace) {
+            return Err("token is not a valid push token".into());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct Device {
+    pub id: Uuid,
+    pub platform: Platform,
+    pub token: String,
+    pub created_at: OffsetDateTime,
+    pub last_seen_at: OffsetDateTime,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DeviceResponse {
+    pub id: Uuid,
+    pub platform: Platform,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub last_seen_at: OffsetDateTime,
+}
+
+impl From<Device> for DeviceResponse {
+    fn from(device: Device) -> Self {
+        Self {
+            id: device.id,
+            platform: device.platform,
+            created_at: device.created_at,
+            last_seen_at: device.last_seen_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, sqlx::FromRow)]
+pub struct NotificationPreferences {
+    /// Push when analysis stored a meal's nutrition.
+    pub analysis_complete: bool,
+    /// Daily push at `reminder_hour` when no meal was logged that day.
+    pub meal_reminders: bool,
+    /// Push when a badge is unlocked.
+    pub achievements: bool,
+    /// Push when someone reacts to one of the user's meals.
+    pub reactions: bool,
+    /// Local hour (0-23) in the time zone of the user's preferences.
+    pub reminder_hour: i16,
+}
+
+impl Default for NotificationPreferences {
+    fn default() -> Self {
+        Self {
+            analysis_complete: true,
+            meal_reminders: false,
+            achievements: true,
+            reactions: true,
+            reminder_hour: 20,
+        }
+    }
+}
+
+/// Omitted fields keep their current value.
+#[derive(Debug, Default, Deserialize)]
+pub struct UpdatePreferencesRequest {
+    pub analysis_complete: Option<bool>,
+    pub meal_reminders: Option<bool>,
+    pub achievements: Option<bool>,
+    pub reactions: Option<bool>,
+    pub reminder_hour: Option<i16>,
+}
+
+impl UpdatePreferencesRequest {
+    pub fn apply(&self, prefs: &mut NotificationPreferences) -> Result<(), String> {
+        if let Some(hour) = self.reminder_hour {
+            if !(0..=23).contains(&hour) {
+                return Err("reminder_hour must be between 0 and 23".into());
+            }
+            prefs.reminder_hour = hour;
+        }
+        if let Some(enabled) = self.analysis_complete {
+            prefs.analysis_complete = enabled;
+        }
+        if let Some(enabled) = self.meal_reminders {
+            prefs.meal_reminders = enabled;
+        }
+        if let Some(enabled) = self.achievements {
+            prefs.achievements = enabled;
+        }
+        if let Some(enabled) = self.reactions {
+            prefs.reactions = enabled;
+        }
+        Ok(())
+    }
+}
+
+/// Body of `/me/notification-preferences`, which predates
+/// `/me/preferences` and still carries the account's time zone.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NotificationSettingsResponse {
+    #[serde(flatten)]
+    pub preferences: NotificationPreferences,
+    pub timezone: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct UpdateNotificationSettingsRequest {
+    #[serde(flatten)]
+    pub preferences: UpdatePreferencesRequest,
+    pub timezone: Option<String>,
+}
@@ -0,0 +1,128 @@
+use std::time::{Duration, Instant};
+
+use anyhow::Context;
+use jsonwebtoken::{Algorithm, EncodingKey, Header};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use time::OffsetDateTime;
+use tokio::sync::Mutex;
+
+use crate::push::{PushError, PushMessage, PushProvider};
+
+const SEND_BASE_URL: &str = "https://fcm.googleapis.com/v1/projects";
+const SCOPE: &str = "https://www.googleapis.com/auth/firebase.messaging";
+const GRANT_TYPE: &str = "urn:ietf:params:oauth:grant-type:jwt-bearer";
+
+/// The fields of a Firebase service account key file this provider uses.
+#[derive(Deserialize)]
+struct ServiceAccount {
+    project_id: String,
+    client_email: String,
+    private_key: String,
+    token_uri: String,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    expires_in: u64,
+}
+
+/// Firebase Cloud Messaging HTTP v1 API, authenticated with a service
+/// account. Access tokens are cached until shortly before they expire.
+pub struct FcmProvider {
+    http: reqwest::Client,
+    project_id: String,
+    client_email: String,
+    token_uri: String,
+    key: EncodingKey,
+    access_token: Mutex<Option<(String, Instant)>>,
+}
+
+impl FcmProvider {
+    pub fn from_file(http: reqwest::Client, path: &str) -> anyhow::Result<Self> {
+        let account: ServiceAccount =
+            serde_json::from_slice(&std::fs::read(path)?).context("parse service account JSON")?;
+        Ok(Self {
+            http,
+            key: EncodingKey::from_rsa_pem(account.private_key.as_bytes())?,
+            project_id: account.project_id,
+            client_email: account.client_email,
+            token_uri: account.token_uri,
+            access_token: Mutex::new(None),
+        })
+    }
+
+    async fn access_token(&self) -> anyhow::Result<String> {
+        let mut cached = self.access_token.lock().await;
+        if let Some((token, expires)) = cached.as_ref() {
+            if Instant::now() < *expires {
+                return Ok(token.clone());
+            }
+        }
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        let claims = json!({
+            "iss": self.client_email,
+            "scope": SCOPE,
+            "aud": self.token_uri,
+            "iat": now,
+            "exp": now + 3600,
+        });
+        let assertion = jsonwebtoken::encode(&Header::new(Algorithm::RS256), &claims, &self.key)?;
+        let response: TokenResponse = self
+            .http
+            .post(&self.token_uri)
+            .form(&[
+                ("grant_type", GRANT_TYPE),
+                ("assertion", assertion.as_str()),
+            ])
+            .send()
+            .await?
+            .error_for_status()
+            .context("fetch FCM access token")?
+            .json()
+            .await?;
+        // Refresh a minute early so a token never expires mid-request.
+        let expires = Instant::now() + Duration::from_secs(response.expires_in.saturating_sub(60));
+        *cached = Some((response.access_token.clone(), expires));
+        Ok(response.access_token)
+    }
+}
+
+fn payload(token: &str, message: &PushMessage) -> Value {
+    json!({
+        "message": {
+            "token": token,
+            "notification": {"title": message.title, "body": message.body},
+            "data": message.data,
+        }
+    })
+}
+
+#[axum::async_trait]
+impl PushProvider for FcmProvider {
+    async fn send(&self, token: &str, message: &PushMessage) -> Result<(), PushError> {
+        let access_token = self.access_token().await?;
+        let response = self
+            .http
+            .post(format!(
+                "{}/{}/messages:send",
+                SEND_BASE_URL, self.project_id
+            ))
+            .bearer_auth(access_token)
+            .json(&payload(token, message))
+            .send()
+            .await
+            .map_err(anyhow::Error::from)?;
+        let status = response.status();
+        if status.is_success() {
+            return Ok(());
+        }
+        // FCM answers 404 UNREGISTERED for uninstalled apps and stale tokens.
+        if status == reqwest::StatusCode::NOT_FOUND {
+            return Err(PushError::Unregistered);
+        }
+        let body = response.text().await.unwrap_or_default();
+        Err(anyhow::anyhow!("FCM answered {}: {}", status, body).into())
+    }
+}
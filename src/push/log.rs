@@ -0,0 +1,20 @@
+use tracing::info;
+
+use crate::push::{PushError, PushMessage, PushProvider};
+
+/// Logs every push instead of sending it (`PUSH_DRY_RUN`), for development.
+pub struct LogProvider;
+
+#[axum::async_trait]
+impl PushProvider for LogProvider {
+    async fn send(&self, token: &str, message: &PushMessage) -> Result<(), PushError> {
+        info!(
+            token = %token,
+            title = %message.title,
+            body = %message.body,
+            data = ?message.data,
+            "push notification (dry run)"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,97 @@
+//! Push notifications to the user's phones. Platforms' delivery services sit
+//! behind [`PushProvider`] the same way analyzers sit behind
+//! `NutritionAnalyzer`; a platform without a configured provider is skipped.
+
+use std::{collections::BTreeMap, sync::Arc, time::Duration};
+
+use anyhow::Context;
+use tracing::{info, warn};
+
+use crate::config::PushConfig;
+
+pub mod apns;
+pub mod dto;
+pub mod fcm;
+pub mod log;
+pub mod repo;
+pub mod services;
+
+use dto::Platform;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PushMessage {
+    pub title: String,
+    pub body: String,
+    /// Passed to the app alongside the alert, e.g. the meal to open.
+    pub data: BTreeMap<String, String>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum PushError {
+    /// The token no longer reaches the app; the device should be forgotten.
+    #[error("device token is no longer registered")]
+    Unregistered,
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+#[axum::async_trait]
+pub trait PushProvider: Send + Sync {
+    async fn send(&self, token: &str, message: &PushMessage) -> Result<(), PushError>;
+}
+
+/// The provider of each platform, `None` when it isn't configured.
+#[derive(Clone, Default)]
+pub struct PushProviders {
+    android: Option<Arc<dyn PushProvider>>,
+    ios: Option<Arc<dyn PushProvider>>,
+}
+
+impl PushProviders {
+    pub fn from_config(config: &PushConfig) -> anyhow::Result<Self> {
+        if config.dry_run {
+            info!("push notifications are logged, not sent");
+            let log: Arc<dyn PushProvider> = Arc::new(log::LogProvider);
+            return Ok(Self {
+                android: Some(log.clone()),
+                ios: Some(log),
+            });
+        }
+        let http = || {
+            reqwest::Client::builder()
+                .timeout(Duration::from_secs(config.timeout_secs))
+                .build()
+        };
+        let android = match &config.fcm_credentials {
+            Some(path) => {
+                let provider = fcm::FcmProvider::from_file(http()?, path)
+                    .with_context(|| format!("load FCM credentials from {}", path))?;
+                Some(Arc::new(provider) as Arc<dyn PushProvider>)
+            }
+            None => None,
+        };
+        let ios = match &config.apns_key_path {
+            Some(path) => {
+                let provider = apns::ApnsProvider::from_config(http()?, path, config)
+                    .with_context(|| format!("load APNs key from {}", path))?;
+                Some(Arc::new(provider) as Arc<dyn PushProvider>)
+            }
+            None => None,
+        };
+        if android.is_none() && ios.is_none() {
+            warn!("no push provider configured; push notifications are disabled");
+        }
+        Ok(Self { android, ios })
+    }
+
+    pub fn get(&self, platform: Platform) -> Option<&Arc<dyn PushProvider>> {
+        match platform {
+            Platform::Android => self.android.as_ref(),
+            Platform::Ios => self.ios.as_ref(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.android.is_none() && self.ios.is_none()
+    }
+}
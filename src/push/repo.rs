@@ -0,0 +1,165 @@
+use sqlx::{PgConnection, PgExecutor, PgPool};
+use uuid::Uuid;
+
+use crate::push::dto::{Device, NotificationPreferences, Platform};
+
+/// Registers the token for the user, taking it over from whoever had it, and
+/// drops the user's least recently seen devices beyond `max`.
+pub async fn upsert_device(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    platform: Platform,
+    token: &str,
+    max: i64,
+) -> anyhow::Result<Device> {
+    let device = sqlx::query_as::<_, Device>(
+        r#"
+        INSERT INTO devices (user_id, platform, token)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (token) DO UPDATE
+        SET user_id = EXCLUDED.user_id, platform = EXCLUDED.platform, last_seen_at = NOW()
+        RETURNING id, platform, token, created_at, last_seen_at
+        "#,
+    )
+    .bind(user_id)
+    .bind(platform)
+    .bind(token)
+    .fetch_one(&mut *conn)
+    .await?;
+    sqlx::query(
+        r#"
+        DELETE FROM devices
+        WHERE user_id = $1 AND id NOT IN (
+            SELECT id FROM devices
+            WHERE user_id = $1
+            ORDER BY last_seen_at DESC
+            LIMIT $2
+        )
+        "#,
+    )
+    .bind(user_id)
+    .bind(max)
+    .execute(conn)
+    .await?;
+    Ok(device)
+}
+
+pub async fn list_devices(db: impl PgExecutor<'_>, user_id: Uuid) -> anyhow::Result<Vec<Device>> {
+    let devices = sqlx::query_as::<_, Device>(
+        r#"
+        SELECT id, platform, token, created_at, last_seen_at
+        FROM devices
+        WHERE user_id = $1
+        ORDER BY last_seen_at DESC
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(db)
+    .await?;
+    Ok(devices)
+}
+
+pub async fn delete_device(
+    db: impl PgExecutor<'_>,
+    user_id: Uuid,
+    id: Uuid,
+) -> anyhow::Result<bool> {
+    let result = sqlx::query("DELETE FROM devices WHERE id = $1 AND user_id = $2")
+        .bind(id)
+        .bind(user_id)
+        .execute(db)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Forgets a token the provider reported as no longer registered.
+pub async fn delete_by_token(db: &PgPool, token: &str) -> anyhow::Result<()> {
+    sqlx::query("DELETE FROM devices WHERE token = $1")
+        .bind(token)
+        .execute(db)
+        .await?;
+    Ok(())
+}
+
+pub async fn find_preferences(
+    db: impl PgExecutor<'_>,
+    user_id: Uuid,
+) -> anyhow::Result<Option<NotificationPreferences>> {
+    let prefs = sqlx::query_as::<_, NotificationPreferences>(
+        r#"
+        SELECT analysis_complete, meal_reminders, achievements, reactions, reminder_hour
+        FROM notification_preferences
+        WHERE user_id = $1
+        "#,
+    )
+    .bind(user_id)
+    .fetch_optional(db)
+    .await?;
+    Ok(prefs)
+}
+
+pub async fn upsert_preferences(
+    db: impl PgExecutor<'_>,
+    user_id: Uuid,
+    prefs: &NotificationPreferences,
+) -> anyhow::Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO notification_preferences
+            (user_id, analysis_complete, meal_reminders, achievements, reactions,
+             reminder_hour)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        ON CONFLICT (user_id) DO UPDATE
+        SET analysis_complete = EXCLUDED.analysis_complete,
+            meal_reminders = EXCLUDED.meal_reminders,
+            achievements = EXCLUDED.achievements,
+            reactions = EXCLUDED.reactions,
+            reminder_hour = EXCLUDED.reminder_hour,
+            updated_at = NOW()
+        "#,
+    )
+    .bind(user_id)
+    .bind(prefs.analysis_complete)
+    .bind(prefs.meal_reminders)
+    .bind(prefs.achievements)
+    .bind(prefs.reactions)
+    .bind(prefs.reminder_hour)
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+/// Marks as reminded today, and returns, the users due a meal reminder: it's
+/// their reminder hour, they have a device, haven't been reminded and haven't
+/// logged a meal on their local date. Local means the time zone of the
+/// user's preferences.
+pub async fn claim_due_reminders(db: &PgPool) -> anyhow::Result<Vec<Uuid>> {
+    let users = sqlx::query_scalar::<_, Uuid>(
+        r#"
+        WITH zones AS (
+            SELECT n.user_id, COALESCE(u.timezone, 'UTC') AS timezone
+            FROM notification_preferences n
+            LEFT JOIN user_preferences u ON u.user_id = n.user_id
+            WHERE n.meal_reminders
+        )
+        UPDATE notification_preferences p
+        SET last_reminded_on = (NOW() AT TIME ZONE z.timezone)::date
+        FROM zones z
+        WHERE z.user_id = p.user_id
+          AND EXTRACT(HOUR FROM NOW() AT TIME ZONE z.timezone) = p.reminder_hour
+          AND (p.last_reminded_on IS NULL
+               OR p.last_reminded_on < (NOW() AT TIME ZONE z.timezone)::date)
+          AND EXISTS (SELECT 1 FROM devices d WHERE d.user_id = p.user_id)
+          AND NOT EXISTS (
+              SELECT 1 FROM meals m
+              WHERE m.user_id = p.user_id
+                AND (m.created_at AT TIME ZONE z.timezone)::date
+                    = (NOW() AT TIME ZONE z.timezone)::date
+          )
+        RETURNING p.user_id
+        "#,
+    )
+    .fetch_all(db)
+    .await?;
+    Ok(users)
+}
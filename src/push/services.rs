@@ -0,0 +1,379 @@
+use axum::http::StatusCode;
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+use crate::{
+    achievements::dto::Badge,
+    db::AppState,
+    error::ErrorCode,
+    events::{DomainEvent, EventSubscriber, OutboxEvent},
+    i18n::{self, Locale},
+    meals::repo as meals_repo,
+    preferences::{
+        dto::UpdatePreferencesRequest,
+        services::{self as preferences, PreferencesError},
+    },
+    push::{
+        dto::{
+            DeviceResponse, NotificationPreferences, NotificationSettingsResponse,
+            RegisterDeviceRequest, UpdateNotificationSettingsRequest, MAX_DEVICES_PER_USER,
+        },
+        repo, PushError, PushMessage,
+    },
+    reactions::dto::Reaction,
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum NotificationError {
+    #[error("Device not found")]
+    DeviceNotFound,
+    #[error("{0}")]
+    Invalid(String),
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+impl NotificationError {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            NotificationError::DeviceNotFound => StatusCode::NOT_FOUND,
+            NotificationError::Invalid(_) => StatusCode::BAD_REQUEST,
+            NotificationError::Other(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            NotificationError::DeviceNotFound => ErrorCode::DeviceNotFound,
+            NotificationError::Invalid(_) => ErrorCode::ValidationFailed,
+            NotificationError::Other(_) => ErrorCode::Internal,
+        }
+    }
+}
+
+/// What a push is about; each kind can be turned off in the preferences.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Notification {
+    AnalysisComplete {
+        meal_id: Uuid,
+        title: Option<String>,
+    },
+    MealReminder,
+    AchievementUnlocked {
+        badge: Badge,
+    },
+    MealReaction {
+        meal_id: Uuid,
+        title: Option<String>,
+        reaction: Reaction,
+    },
+}
+
+impl Notification {
+    fn enabled(&self, prefs: &NotificationPreferences) -> bool {
+        match self {
+            Notification::AnalysisComplete { .. } => prefs.analysis_complete,
+            Notification::MealReminder => prefs.meal_reminders,
+            Notification::AchievementUnlocked { .. } => prefs.achievements,
+            Notification::MealReaction { .. } => prefs.reactions,
+        }
+    }
+
+    /// The push in `locale`; the notification center words its entries the
+    /// same way.
+    pub fn message(&self, locale: Locale) -> PushMessage {
+        let text = |message: &str| i18n::translate_to(locale, message);
+        match self {
+            Notification::AnalysisComplete { meal_id, title } => PushMessage {
+                title: text("Analysis complete"),
+                body: match title {
+                    Some(title) => text(&format!("Nutrition for \"{}\" is ready.", title)),
+                    None => text("Nutrition for your meal is ready."),
+                },
+                data: [
+                    ("type".to_string(), "analysis_complete".to_string()),
+                    ("meal_id".to_string(), meal_id.to_string()),
+                ]
+                .into(),
+            },
+            Notification::MealReminder => PushMessage {
+                title: text("Log your meals"),
+                body: text("You haven't logged anything today."),
+                data: [("type".to_string(), "meal_reminder".to_string())].into(),
+            },
+            Notification::AchievementUnlocked { badge } => PushMessage {
+                title: text("Achievement unlocked"),
+                body: format!("{}: {}", text(badge.title()), text(badge.description())),
+                data: [
+                    ("type".to_string(), "achievement_unlocked".to_string()),
+                    ("badge".to_string(), badge.name().to_string()),
+                ]
+                .into(),
+            },
+            Notification::MealReaction {
+                meal_id,
+                title,
+                reaction,
+            } => PushMessage {
+                title: text("New reaction"),
+                body: format!(
+                    "{} {}",
+                    reaction.emoji(),
+                    match title {
+                        Some(title) => text(&format!("Someone reacted to \"{}\".", title)),
+                        None => text("Someone reacted to your meal."),
+                    }
+                ),
+                data: [
+                    ("type".to_string(), "meal_reaction".to_string()),
+                    ("meal_id".to_string(), meal_id.to_string()),
+                    ("reaction".to_string(), reaction.name().to_string()),
+                ]
+                .into(),
+            },
+        }
+    }
+}
+
+pub async fn register_device(
+    state: &AppState,
+    user_id: Uuid,
+    input: RegisterDeviceRequest,
+) -> Result<DeviceResponse, NotificationError> {
+    input.validate().map_err(NotificationError::Invalid)?;
+    let mut tx = state.begin_as(user_id).await.map_err(anyhow::Error::from)?;
+    let device = repo::upsert_device(
+        &mut tx,
+        user_id,
+        input.platform,
+        input.token.trim(),
+        MAX_DEVICES_PER_USER,
+    )
+    .await?;
+    tx.commit().await.map_err(anyhow::Error::from)?;
+    info!(user_id = %user_id, device_id = %device.id, platform = ?device.platform, "device registered");
+    Ok(device.into())
+}
+
+pub async fn list_devices(
+    state: &AppState,
+    user_id: Uuid,
+) -> Result<Vec<DeviceResponse>, NotificationError> {
+    let mut tx = state.begin_as(user_id).await.map_err(anyhow::Error::from)?;
+    let devices = repo::list_devices(&mut *tx, user_id).await?;
+    tx.commit().await.map_err(anyhow::Error::from)?;
+    Ok(devices.into_iter().map(Into::into).collect())
+}
+
+pub async fn delete_device(
+    state: &AppState,
+    user_id: Uuid,
+    device_id: Uuid,
+) -> Result<(), NotificationError> {
+    let mut tx = state.begin_as(user_id).await.map_err(anyhow::Error::from)?;
+    if !repo::delete_device(&mut *tx, user_id, device_id).await? {
+        return Err(NotificationError::DeviceNotFound);
+    }
+    tx.commit().await.map_err(anyhow::Error::from)?;
+    Ok(())
+}
+
+pub async fn get_preferences(
+    state: &AppState,
+    user_id: Uuid,
+) -> Result<NotificationSettingsResponse, NotificationError> {
+    let mut tx = state.begin_as(user_id).await.map_err(anyhow::Error::from)?;
+    let notifications = repo::find_preferences(&mut *tx, user_id).await?;
+    tx.commit().await.map_err(anyhow::Error::from)?;
+    Ok(NotificationSettingsResponse {
+        preferences: notifications.unwrap_or_default(),
+        timezone: preferences::preferences(state, user_id).await?.timezone,
+    })
+}
+
+/// Same as updating `notifications` and `timezone` of the user preferences.
+pub async fn update_preferences(
+    state: &AppState,
+    user_id: Uuid,
+    input: UpdateNotificationSettingsRequest,
+) -> Result<NotificationSettingsResponse, NotificationError> {
+    let update = UpdatePreferencesRequest {
+        timezone: input.timezone,
+        notifications: Some(input.preferences),
+        ..Default::default()
+    };
+    let updated = preferences::update_preferences(state, user_id, update)
+        .await
+        .map_err(|e| match e {
+            PreferencesError::Invalid(msg) => NotificationError::Invalid(msg),
+            PreferencesError::Other(e) => NotificationError::Other(e),
+        })?;
+    Ok(NotificationSettingsResponse {
+        preferences: updated.notifications,
+        timezone: updated.preferences.timezone,
+    })
+}
+
+/// Pushes the notification to every device of the user, unless the user
+/// turned that kind off. Devices whose token is dead are forgotten; other
+/// failures are logged, as pushes are best effort. Returns how many devices
+/// accepted it.
+pub async fn notify(
+    state: &AppState,
+    user_id: Uuid,
+    notification: &Notification,
+) -> anyhow::Result<usize> {
+    let prefs = repo::find_preferences(&state.db, user_id)
+        .await?
+        .unwrap_or_default();
+    if !notification.enabled(&prefs) {
+        return Ok(0);
+    }
+    let locale = preferences::preferences(state, user_id).await?.locale;
+    let message = notification.message(locale);
+    let mut sent = 0;
+    for device in repo::list_devices(&state.db, user_id).await? {
+        let Some(provider) = state.push.get(device.platform) else {
+            debug!(device_id = %device.id, platform = ?device.platform, "no push provider");
+            continue;
+        };
+        match provider.send(&device.token, &message).await {
+            Ok(()) => sent += 1,
+            Err(PushError::Unregistered) => {
+                info!(device_id = %device.id, "push token unregistered; forgetting device");
+                repo::delete_by_token(&state.db, &device.token).await?;
+            }
+            Err(PushError::Other(e)) => {
+                warn!(device_id = %device.id, error = %e, "push notification failed");
+            }
+        }
+    }
+    Ok(sent)
+}
+
+/// Reminds users who haven't logged a meal by their reminder hour.
+pub async fn send_meal_reminders(state: &AppState) -> anyhow::Result<()> {
+    let users = repo::claim_due_reminders(&state.db).await?;
+    let mut sent = 0;
+    for user_id in &users {
+        sent += notify(state, *user_id, &Notification::MealReminder).await?;
+    }
+    if !users.is_empty() {
+        info!(users = users.len(), devices = sent, "meal reminders sent");
+    }
+    Ok(())
+}
+
+/// Pushes "analysis complete" when a meal's nutrition is ready, and
+/// reactions of others to the user's meals.
+pub struct PushSubscriber {
+    state: AppState,
+}
+
+impl PushSubscriber {
+    pub fn new(state: AppState) -> Self {
+        Self { state }
+    }
+}
+
+#[axum::async_trait]
+impl EventSubscriber for PushSubscriber {
+    fn name(&self) -> &'static str {
+        "push"
+    }
+
+    async fn handle(&self, event: &OutboxEvent) -> anyhow::Result<()> {
+        let (DomainEvent::NutritionReady { meal_id } | DomainEvent::MealReaction { meal_id, .. }) =
+            event.event
+        else {
+            return Ok(());
+        };
+        let Some(meal) = meals_repo::find_meal(&self.state.db, event.user_id, meal_id).await?
+        else {
+            return Ok(());
+        };
+        let notification = match event.event {
+            DomainEvent::MealReaction { reaction, .. } => Notification::MealReaction {
+                meal_id,
+                title: meal.title,
+                reaction,
+            },
+            _ => Notification::AnalysisComplete {
+                meal_id,
+                title: meal.title,
+            },
+        };
+        notify(&self.state, event.user_id, &notification).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::push::dto::UpdatePreferencesRequest;
+
+    #[test]
+    fn notifications_follow_preferences() {
+        let prefs = NotificationPreferences::default();
+        let analysis = Notification::AnalysisComplete {
+            meal_id: Uuid::nil(),
+            title: Some("Pasta".into()),
+        };
+        assert!(analysis.enabled(&prefs));
+        assert!(!Notification::MealReminder.enabled(&prefs));
+
+        let message = analysis.message(Locale::En);
+        assert_eq!(message.body, "Nutrition for \"Pasta\" is ready.");
+        assert_eq!(message.data["meal_id"], Uuid::nil().to_string());
+        let message = analysis.message(Locale::De);
+        assert_eq!(message.body, "Nährwerte für \"Pasta\" sind fertig.");
+    }
+
+    #[test]
+    fn achievement_pushes_name_the_badge() {
+        let unlocked = Notification::AchievementUnlocked {
+            badge: Badge::WeekStreak,
+        };
+        assert!(unlocked.enabled(&NotificationPreferences::default()));
+        let message = unlocked.message(Locale::De);
+        assert_eq!(message.title, "Erfolg freigeschaltet");
+        assert_eq!(
+            message.body,
+            "7-Tage-Serie: 7 Tage in Folge Mahlzeiten eingetragen."
+        );
+        assert_eq!(message.data["badge"], "week_streak");
+    }
+
+    #[test]
+    fn reaction_pushes_show_the_emoji() {
+        let reaction = Notification::MealReaction {
+            meal_id: Uuid::nil(),
+            title: Some("Pasta".into()),
+            reaction: Reaction::Fire,
+        };
+        assert!(reaction.enabled(&NotificationPreferences::default()));
+        let message = reaction.message(Locale::En);
+        assert_eq!(message.body, "🔥 Someone reacted to \"Pasta\".");
+        assert_eq!(message.data["reaction"], "fire");
+    }
+
+    #[test]
+    fn update_checks_reminder_hour() {
+        let mut prefs = NotificationPreferences::default();
+        let update = UpdatePreferencesRequest {
+            reminder_hour: Some(24),
+            ..Default::default()
+        };
+        assert!(update.apply(&mut prefs).is_err());
+
+        let update = UpdatePreferencesRequest {
+            meal_reminders: Some(true),
+            reminder_hour: Some(7),
+            ..Default::default()
+        };
+        update.apply(&mut prefs).unwrap();
+        assert!(prefs.meal_reminders);
+        assert_eq!(prefs.reminder_hour, 7);
+    }
+}
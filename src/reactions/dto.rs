@@ -0,0 +1,140 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// The emoji set reactions are limited to.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    sqlx::Type,
+    ToSchema,
+)]
+#[serde(rename_all = "snake_case", try_from = "String")]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+pub enum Reaction {
+    ThumbsUp,
+    Heart,
+    Fire,
+    Yum,
+    Clap,
+}
+
+impl Reaction {
+    pub const ALL: [Reaction; 5] = [
+        Reaction::ThumbsUp,
+        Reaction::Heart,
+        Reaction::Fire,
+        Reaction::Yum,
+        Reaction::Clap,
+    ];
+
+    /// Matches the serialized form.
+    pub fn name(self) -> &'static str {
+        match self {
+            Reaction::ThumbsUp => "thumbs_up",
+            Reaction::Heart => "heart",
+            Reaction::Fire => "fire",
+            Reaction::Yum => "yum",
+            Reaction::Clap => "clap",
+        }
+    }
+
+    pub fn emoji(self) -> &'static str {
+        match self {
+            Reaction::ThumbsUp => "👍",
+            Reaction::Heart => "❤️",
+            Reaction::Fire => "🔥",
+            Reaction::Yum => "😋",
+            Reaction::Clap => "👏",
+        }
+    }
+}
+
+impl std::str::FromStr for Reaction {
+    type Err = String;
+
+    /// Takes the name or the emoji itself; a heart may come without the
+    /// variation selector.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        Reaction::ALL
+            .into_iter()
+            .find(|r| {
+                r.name() == s || r.emoji() == s || r.emoji().trim_end_matches('\u{fe0f}') == s
+            })
+            .ok_or_else(|| {
+                let names: Vec<&str> = Reaction::ALL.iter().map(|r| r.name()).collect();
+                format!("Unknown reaction; expected one of {}", names.join(", "))
+            })
+    }
+}
+
+impl TryFrom<String> for Reaction {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AddReactionRequest {
+    /// A reaction name such as `heart`, or its emoji.
+    pub emoji: Reaction,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct ReactionCount {
+    pub reaction: Reaction,
+    pub emoji: String,
+    pub count: i64,
+}
+
+impl ReactionCount {
+    pub fn new(reaction: Reaction, count: i64) -> Self {
+        Self {
+            reaction,
+            emoji: reaction.emoji().to_string(),
+            count,
+        }
+    }
+}
+
+/// Reactions on one meal after a change.
+#[derive(Debug, Serialize)]
+pub struct MealReactions {
+    pub meal_id: Uuid,
+    /// Reactions with at least one vote, in the order of the emoji set.
+    pub reactions: Vec<ReactionCount>,
+    /// The caller's own reactions.
+    pub mine: Vec<Reaction>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reactions_parse_from_names_and_emoji() {
+        assert_eq!("heart".parse::<Reaction>(), Ok(Reaction::Heart));
+        assert_eq!("❤️".parse::<Reaction>(), Ok(Reaction::Heart));
+        assert_eq!("\u{2764}".parse::<Reaction>(), Ok(Reaction::Heart));
+        assert_eq!(" 🔥 ".parse::<Reaction>(), Ok(Reaction::Fire));
+        assert!("🍕".parse::<Reaction>().is_err());
+
+        let request: AddReactionRequest = serde_json::from_str(r#"{"emoji":"👏"}"#).unwrap();
+        assert_eq!(request.emoji, Reaction::Clap);
+        assert_eq!(
+            serde_json::to_value(Reaction::ThumbsUp).unwrap(),
+            "thumbs_up"
+        );
+    }
+}
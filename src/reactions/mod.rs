@@ -0,0 +1,8 @@
+//! Emoji reactions on meals a user can see but usually doesn't own, i.e.
+//! meals of a shared household. A new reaction on someone else's meal is
+//! recorded as a `meal_reaction` event for the owner, which reaches their
+//! webhooks and devices.
+
+pub mod dto;
+pub mod repo;
+pub mod services;
@@ -0,0 +1,88 @@
+use std::collections::HashMap;
+
+use sqlx::{PgExecutor, PgPool};
+use uuid::Uuid;
+
+use crate::reactions::dto::{Reaction, ReactionCount};
+
+/// Adds the reaction; `false` when the user had already given it.
+pub async fn insert(
+    db: impl PgExecutor<'_>,
+    meal_id: Uuid,
+    user_id: Uuid,
+    reaction: Reaction,
+) -> anyhow::Result<bool> {
+    let result = sqlx::query(
+        r#"
+        INSERT INTO meal_reactions (meal_id, user_id, reaction)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (meal_id, user_id, reaction) DO NOTHING
+        "#,
+    )
+    .bind(meal_id)
+    .bind(user_id)
+    .bind(reaction)
+    .execute(db)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Removes the reaction; `false` when the user hadn't given it.
+pub async fn delete(
+    db: &PgPool,
+    meal_id: Uuid,
+    user_id: Uuid,
+    reaction: Reaction,
+) -> anyhow::Result<bool> {
+    let result = sqlx::query(
+        r#"DELETE FROM meal_reactions WHERE meal_id = $1 AND user_id = $2 AND reaction = $3"#,
+    )
+    .bind(meal_id)
+    .bind(user_id)
+    .bind(reaction)
+    .execute(db)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Reaction counts per meal, in the order of the emoji set; meals without
+/// reactions are left out.
+pub async fn counts_for_meals(
+    db: &PgPool,
+    meal_ids: &[Uuid],
+) -> anyhow::Result<HashMap<Uuid, Vec<ReactionCount>>> {
+    let rows = sqlx::query_as::<_, (Uuid, Reaction, i64)>(
+        r#"
+        SELECT meal_id, reaction, COUNT(*)
+        FROM meal_reactions
+        WHERE meal_id = ANY($1)
+        GROUP BY meal_id, reaction
+        "#,
+    )
+    .bind(meal_ids)
+    .fetch_all(db)
+    .await?;
+    let mut counts: HashMap<Uuid, Vec<ReactionCount>> = HashMap::new();
+    for (meal_id, reaction, count) in rows {
+        counts
+            .entry(meal_id)
+            .or_default()
+            .push(ReactionCount::new(reaction, count));
+    }
+    for list in counts.values_mut() {
+        list.sort_by_key(|c| c.reaction);
+    }
+    Ok(counts)
+}
+
+pub async fn list_mine(db: &PgPool, meal_id: Uuid, user_id: Uuid) -> anyhow::Result<Vec<Reaction>> {
+    let mut mine = sqlx::query_scalar::<_, Reaction>(
+        r#"SELECT reaction FROM meal_reactions WHERE meal_id = $1 AND user_id = $2"#,
+    )
+    .bind(meal_id)
+    .bind(user_id)
+    .fetch_all(db)
+    .await?;
+    mine.sort();
+    Ok(mine)
+}
@@ -0,0 +1,108 @@
+use axum::http::StatusCode;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    db::{AppState, RepoError},
+    error::ErrorCode,
+    events::{repo as events_repo, DomainEvent},
+    meals::repo as meals_repo,
+    reactions::{
+        dto::{MealReactions, Reaction},
+        repo,
+    },
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum ReactionError {
+    #[error("Meal not found")]
+    MealNotFound,
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+impl From<RepoError> for ReactionError {
+    fn from(e: RepoError) -> Self {
+        ReactionError::Other(e.into())
+    }
+}
+
+impl ReactionError {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            ReactionError::MealNotFound => StatusCode::NOT_FOUND,
+            ReactionError::Other(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            ReactionError::MealNotFound => ErrorCode::MealNotFound,
+            ReactionError::Other(_) => ErrorCode::Internal,
+        }
+    }
+}
+
+/// Reacts to a meal the user owns or shares a household with. A reaction
+/// that is new, on someone else's meal, is recorded as an event for the
+/// owner in the same transaction.
+pub async fn add_reaction(
+    state: &AppState,
+    user_id: Uuid,
+    meal_id: Uuid,
+    reaction: Reaction,
+) -> Result<MealReactions, ReactionError> {
+    let meal = meals_repo::find_visible_meal(&state.db, user_id, meal_id)
+        .await?
+        .ok_or(ReactionError::MealNotFound)?;
+    let mut tx = state.db.begin().await.map_err(anyhow::Error::from)?;
+    let added = repo::insert(&mut *tx, meal_id, user_id, reaction).await?;
+    if added && meal.user_id != user_id {
+        let event = DomainEvent::MealReaction {
+            meal_id,
+            reaction,
+            reacted_by: user_id,
+        };
+        events_repo::record(&mut *tx, meal.user_id, &event).await?;
+    }
+    tx.commit().await.map_err(anyhow::Error::from)?;
+    if added {
+        info!(user_id = %user_id, meal_id = %meal_id, reaction = reaction.name(), "meal reaction added");
+    }
+    meal_reactions(state, user_id, meal_id).await
+}
+
+/// Takes back one of the user's reactions; removing one that isn't there is
+/// not an error.
+pub async fn remove_reaction(
+    state: &AppState,
+    user_id: Uuid,
+    meal_id: Uuid,
+    reaction: Reaction,
+) -> Result<MealReactions, ReactionError> {
+    if meals_repo::find_visible_meal(&state.db, user_id, meal_id)
+        .await?
+        .is_none()
+    {
+        return Err(ReactionError::MealNotFound);
+    }
+    repo::delete(&state.db, meal_id, user_id, reaction).await?;
+    meal_reactions(state, user_id, meal_id).await
+}
+
+async fn meal_reactions(
+    state: &AppState,
+    user_id: Uuid,
+    meal_id: Uuid,
+) -> Result<MealReactions, ReactionError> {
+    let reactions = repo::counts_for_meals(&state.db, &[meal_id])
+        .await?
+        .remove(&meal_id)
+        .unwrap_or_default();
+    let mine = repo::list_mine(&state.db, meal_id, user_id).await?;
+    Ok(MealReactions {
+        meal_id,
+        reactions,
+        mine,
+    })
+}
@@ -0,0 +1,107 @@
+//! Per-user push events (meal analysis progress, nutrition changes) fanned
+//! out to connected clients, optionally across instances through Redis.
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::{broadcast, mpsc};
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::meals::dto::MealStatus;
+
+mod redis;
+
+/// Events buffered per subscriber before slow clients start missing some.
+const CHANNEL_CAPACITY: usize = 1024;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MealEvent {
+    MealStatus { meal_id: Uuid, status: MealStatus },
+    NutritionUpdated { meal_id: Uuid },
+}
+
+impl MealEvent {
+    /// SSE `event:` name, matching the serialized `type`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            MealEvent::MealStatus { .. } => "meal_status",
+            MealEvent::NutritionUpdated { .. } => "nutrition_updated",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserEvent {
+    pub user_id: Uuid,
+    pub event: MealEvent,
+}
+
+/// In-process broadcast of [`UserEvent`]s. Every subscriber sees every event
+/// and filters on its own user. With Redis, events take a round trip through
+/// pub/sub before being broadcast, so all instances deliver them alike.
+#[derive(Clone)]
+pub struct EventHub {
+    tx: broadcast::Sender<UserEvent>,
+    remote: Option<mpsc::UnboundedSender<UserEvent>>,
+}
+
+impl Default for EventHub {
+    fn default() -> Self {
+        let (tx, _) = broadcast::channel(CHANNEL_CAPACITY);
+        Self { tx, remote: None }
+    }
+}
+
+impl EventHub {
+    pub async fn connect(redis_url: Option<&str>) -> anyhow::Result<Self> {
+        let mut hub = Self::default();
+        if let Some(url) = redis_url {
+            hub.remote = Some(redis::connect(url, hub.tx.clone()).await?);
+        }
+        Ok(hub)
+    }
+
+    /// Publishing with no subscribers is fine; the event is simply dropped.
+    pub fn publish(&self, user_id: Uuid, event: MealEvent) {
+        let msg = UserEvent { user_id, event };
+        match &self.remote {
+            Some(remote) => {
+                if let Err(e) = remote.send(msg) {
+                    warn!("redis publisher stopped; delivering locally");
+                    let _ = self.tx.send(e.0);
+                }
+            }
+            None => {
+                let _ = self.tx.send(msg);
+            }
+        }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<UserEvent> {
+        self.tx.subscribe()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn subscribers_receive_published_events() {
+        let hub = EventHub::default();
+        let mut rx = hub.subscribe();
+        let user_id = Uuid::new_v4();
+        let event = MealEvent::MealStatus {
+            meal_id: Uuid::new_v4(),
+            status: MealStatus::Done,
+        };
+        hub.publish(user_id, event.clone());
+        let received = rx.recv().await.unwrap();
+        assert_eq!(received.user_id, user_id);
+        assert_eq!(received.event, event);
+        assert_eq!(
+            serde_json::to_value(&received.event).unwrap()["type"],
+            event.name()
+        );
+    }
+}
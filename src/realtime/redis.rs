@@ -0,0 +1,72 @@
+//! Redis pub/sub bridge so events published on one instance reach clients
+//! connected to any instance.
+
+use std::time::Duration;
+
+use futures::StreamExt;
+use redis::AsyncCommands;
+use tokio::sync::{broadcast, mpsc};
+use tracing::{info, warn};
+
+use crate::realtime::UserEvent;
+
+const CHANNEL: &str = "mealmind:events";
+const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
+/// Starts the publisher and subscriber tasks. Events sent on the returned
+/// channel go to Redis; everything received from Redis (including our own
+/// messages) is re-broadcast on `local`.
+pub async fn connect(
+    url: &str,
+    local: broadcast::Sender<UserEvent>,
+) -> anyhow::Result<mpsc::UnboundedSender<UserEvent>> {
+    let client = redis::Client::open(url)?;
+    let mut conn = client.get_multiplexed_async_connection().await?;
+    let (tx, mut rx) = mpsc::unbounded_channel::<UserEvent>();
+
+    let fallback = local.clone();
+    tokio::spawn(async move {
+        while let Some(msg) = rx.recv().await {
+            let published = match serde_json::to_string(&msg) {
+                Ok(payload) => conn
+                    .publish::<_, _, i64>(CHANNEL, payload)
+                    .await
+                    .map_err(anyhow::Error::from),
+                Err(e) => Err(e.into()),
+            };
+            if let Err(e) = published {
+                // Local clients still get the event even if Redis is down.
+                warn!(error = %e, "publishing event to redis failed");
+                let _ = fallback.send(msg);
+            }
+        }
+    });
+
+    tokio::spawn(async move {
+        loop {
+            if let Err(e) = relay(&client, &local).await {
+                warn!(error = %e, "redis event subscription lost; reconnecting");
+            }
+            tokio::time::sleep(RECONNECT_DELAY).await;
+        }
+    });
+
+    info!("realtime events fanned out via redis");
+    Ok(tx)
+}
+
+async fn relay(client: &redis::Client, local: &broadcast::Sender<UserEvent>) -> anyhow::Result<()> {
+    let mut pubsub = client.get_async_pubsub().await?;
+    pubsub.subscribe(CHANNEL).await?;
+    let mut messages = pubsub.on_message();
+    while let Some(msg) = messages.next().await {
+        let payload: String = msg.get_payload()?;
+        match serde_json::from_str::<UserEvent>(&payload) {
+            Ok(event) => {
+                let _ = local.send(event);
+            }
+            Err(e) => warn!(error = %e, "ignoring malformed realtime event"),
+        }
+    }
+    anyhow::bail!("subscription stream ended")
+}
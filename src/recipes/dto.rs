@@ -0,0 +1,275 @@
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::{
+    foods::dto::{FoodNutrition, FoodSource},
+    meal_items::dto::{ItemUnit, MealItemRequest},
+    meals::dto::{MealType, NewMeal},
+    units::Units,
+};
+
+pub const MAX_RECIPE_NAME_LEN: usize = 200;
+pub const MAX_INSTRUCTIONS_LEN: usize = 10_000;
+pub const MAX_INGREDIENTS: usize = 50;
+pub const MAX_SERVINGS: f64 = 100.0;
+
+/// Body of create and replace.
+#[derive(Debug, Deserialize)]
+pub struct RecipeRequest {
+    pub name: String,
+    /// How many servings the ingredients make.
+    pub servings: f64,
+    pub instructions: Option<String>,
+    pub ingredients: Vec<MealItemRequest>,
+}
+
+impl RecipeRequest {
+    /// Trims the texts and validates every ingredient like a meal item.
+    pub fn normalized(self) -> Result<Self, String> {
+        let name = self.name.trim().to_string();
+        if name.is_empty() || name.chars().count() > MAX_RECIPE_NAME_LEN {
+            return Err(format!(
+                "name must be between 1 and {} characters",
+                MAX_RECIPE_NAME_LEN
+            ));
+        }
+        validate_servings(self.servings)?;
+        let instructions = self
+            .instructions
+            .map(|i| i.trim().to_string())
+            .filter(|i| !i.is_empty());
+        if instructions
+            .as_ref()
+            .is_some_and(|i| i.chars().count() > MAX_INSTRUCTIONS_LEN)
+        {
+            return Err(format!(
+                "instructions must be at most {} characters",
+                MAX_INSTRUCTIONS_LEN
+            ));
+        }
+        if self.ingredients.is_empty() || self.ingredients.len() > MAX_INGREDIENTS {
+            return Err(format!(
+                "a recipe needs between 1 and {} ingredients",
+                MAX_INGREDIENTS
+            ));
+        }
+        let ingredients = self
+            .ingredients
+            .into_iter()
+            .enumerate()
+            .map(|(i, item)| {
+                item.normalized()
+                    .map_err(|e| format!("ingredients[{i}]: {e}"))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            name,
+            servings: self.servings,
+            instructions,
+            ingredients,
+        })
+    }
+}
+
+fn validate_servings(servings: f64) -> Result<(), String> {
+    if !servings.is_finite() || servings <= 0.0 || servings > MAX_SERVINGS {
+        return Err(format!(
+            "servings must be greater than 0 and at most {}",
+            MAX_SERVINGS
+        ));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct RecipeIngredient {
+    pub id: Uuid,
+    pub food_source: Option<FoodSource>,
+    pub food_id: Option<String>,
+    pub name: String,
+    pub quantity: f64,
+    pub unit: ItemUnit,
+    /// For the ingredient's quantity in the whole recipe.
+    #[sqlx(flatten)]
+    pub nutrition: FoodNutrition,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct RecipeRow {
+    pub id: Uuid,
+    pub name: String,
+    pub servings: f64,
+    pub instructions: Option<String>,
+    pub created_at: OffsetDateTime,
+    pub updated_at: OffsetDateTime,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Recipe {
+    pub id: Uuid,
+    pub name: String,
+    pub servings: f64,
+    pub instructions: Option<String>,
+    pub ingredients: Vec<RecipeIngredient>,
+    pub nutrition_total: FoodNutrition,
+    pub nutrition_per_serving: FoodNutrition,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub updated_at: OffsetDateTime,
+}
+
+impl Recipe {
+    /// Ingredient quantities in `units`.
+    pub fn in_units(self, units: Units) -> Self {
+        let ingredients = self
+            .ingredients
+            .into_iter()
+            .map(|ingredient| {
+                let (unit, quantity) = ingredient.unit.in_units(ingredient.quantity, units);
+                RecipeIngredient {
+                    unit,
+                    quantity,
+                    ..ingredient
+                }
+            })
+            .collect();
+        Self {
+            ingredients,
+            ..self
+        }
+    }
+
+    pub fn new(row: RecipeRow, ingredients: Vec<RecipeIngredient>) -> Self {
+        let nutrition_total = ingredients
+            .iter()
+            .fold(FoodNutrition::default(), |sum, i| sum.plus(&i.nutrition));
+        Self {
+            nutrition_per_serving: nutrition_total.scaled(1.0 / row.servings),
+            nutrition_total,
+            ingredients,
+            id: row.id,
+            name: row.name,
+            servings: row.servings,
+            instructions: row.instructions,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        }
+    }
+}
+
+/// List entry, without ingredients and instructions.
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct RecipeSummary {
+    pub id: Uuid,
+    pub name: String,
+    pub servings: f64,
+    pub ingredient_count: i64,
+    #[sqlx(flatten)]
+    pub nutrition_per_serving: FoodNutrition,
+    #[serde(with = "time::serde::rfc3339")]
+    pub updated_at: OffsetDateTime,
+}
+
+/// Body of `POST /recipes/:id/meals`.
+#[derive(Debug, Deserialize)]
+pub struct CookRecipeRequest {
+    pub servings: f64,
+    /// Defaults to the recipe name.
+    pub title: Option<String>,
+    pub notes: Option<String>,
+    pub meal_type: Option<MealType>,
+}
+
+impl CookRecipeRequest {
+    pub fn normalized(self) -> Result<(f64, NewMeal), String> {
+        validate_servings(self.servings)?;
+        let meal = NewMeal {
+            title: self.title,
+            notes: self.notes,
+            meal_type: self.meal_type,
+            ..NewMeal::default()
+        }
+        .normalized()?;
+        Ok((self.servings, meal))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ingredient(name: &str, kcal: f64) -> RecipeIngredient {
+        RecipeIngredient {
+            id: Uuid::nil(),
+            food_source: None,
+            food_id: None,
+            name: name.into(),
+            quantity: 1.0,
+            unit: ItemUnit::Serving,
+            nutrition: FoodNutrition {
+                total_calories_kcal: Some(kcal),
+                ..FoodNutrition::default()
+            },
+        }
+    }
+
+    #[test]
+    fn per_serving_divides_the_total() {
+        let row = RecipeRow {
+            id: Uuid::nil(),
+            name: "Chili".into(),
+            servings: 4.0,
+            instructions: None,
+            created_at: OffsetDateTime::UNIX_EPOCH,
+            updated_at: OffsetDateTime::UNIX_EPOCH,
+        };
+        let recipe = Recipe::new(
+            row,
+            vec![ingredient("Beans", 700.0), ingredient("Beef", 1000.0)],
+        );
+        assert_eq!(recipe.nutrition_total.total_calories_kcal, Some(1700.0));
+        assert_eq!(
+            recipe.nutrition_per_serving.total_calories_kcal,
+            Some(425.0)
+        );
+        assert_eq!(recipe.nutrition_per_serving.protein_g, None);
+    }
+
+    #[test]
+    fn ingredient_errors_name_the_index() {
+        let request = RecipeRequest {
+            name: "Chili".into(),
+            servings: 4.0,
+            instructions: None,
+            ingredients: vec![MealItemRequest {
+                food: None,
+                name: None,
+                quantity: 1.0,
+                unit: ItemUnit::Serving,
+                nutrition: None,
+            }],
+        };
+        let err = request.normalized().unwrap_err();
+        assert!(err.starts_with("ingredients[0]: "), "{err}");
+    }
+
+    #[test]
+    fn needs_ingredients_and_servings() {
+        let request = RecipeRequest {
+            name: "Water".into(),
+            servings: 1.0,
+            instructions: None,
+            ingredients: vec![],
+        };
+        assert!(request.normalized().is_err());
+        let cook = CookRecipeRequest {
+            servings: 0.0,
+            title: None,
+            notes: None,
+            meal_type: None,
+        };
+        assert!(cook.normalized().is_err());
+    }
+}
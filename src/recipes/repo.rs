@@ -0,0 +1,189 @@
+use sqlx::{PgExecutor, Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::{
+    meal_items::dto::NewMealItem,
+    recipes::dto::{RecipeIngredient, RecipeRow, RecipeSummary},
+};
+
+const RECIPE_COLUMNS: &str = r#"
+    id, name, servings::float8 AS servings, instructions, created_at, updated_at
+"#;
+
+pub async fn list_recipes(
+    db: impl PgExecutor<'_>,
+    user_id: Uuid,
+) -> anyhow::Result<Vec<RecipeSummary>> {
+    let recipes = sqlx::query_as::<_, RecipeSummary>(
+        r#"
+        SELECT r.id, r.name, r.servings::float8 AS servings, r.updated_at,
+               COUNT(i.id) AS ingredient_count,
+               ROUND(SUM(i.total_calories_kcal) / r.servings, 2)::float8 AS total_calories_kcal,
+               ROUND(SUM(i.protein_g) / r.servings, 2)::float8 AS protein_g,
+               ROUND(SUM(i.fat_g) / r.servings, 2)::float8 AS fat_g,
+               ROUND(SUM(i.carbs_g) / r.servings, 2)::float8 AS carbs_g,
+               ROUND(SUM(i.sodium_mg) / r.servings, 2)::float8 AS sodium_mg,
+               ROUND(SUM(i.sugar_g) / r.servings, 2)::float8 AS sugar_g,
+               ROUND(SUM(i.fiber_g) / r.servings, 2)::float8 AS fiber_g
+        FROM recipes r
+        LEFT JOIN recipe_ingredients i ON i.recipe_id = r.id
+        WHERE r.user_id = $1
+        GROUP BY r.id
+        ORDER BY lower(r.name), r.id
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(db)
+    .await?;
+    Ok(recipes)
+}
+
+pub async fn find_recipe(
+    db: impl PgExecutor<'_>,
+    user_id: Uuid,
+    recipe_id: Uuid,
+) -> anyhow::Result<Option<RecipeRow>> {
+    let recipe = sqlx::query_as::<_, RecipeRow>(&format!(
+        "SELECT {RECIPE_COLUMNS} FROM recipes WHERE id = $1 AND user_id = $2"
+    ))
+    .bind(recipe_id)
+    .bind(user_id)
+    .fetch_optional(db)
+    .await?;
+    Ok(recipe)
+}
+
+pub async fn list_ingredients(
+    db: impl PgExecutor<'_>,
+    recipe_id: Uuid,
+) -> anyhow::Result<Vec<RecipeIngredient>> {
+    let ingredients = sqlx::query_as::<_, RecipeIngredient>(
+        r#"
+        SELECT id, food_source, food_id, name, quantity::float8 AS quantity, unit,
+               total_calories_kcal::float8 AS total_calories_kcal,
+               protein_g::float8 AS protein_g,
+               fat_g::float8 AS fat_g,
+               carbs_g::float8 AS carbs_g,
+               sodium_mg::float8 AS sodium_mg,
+               sugar_g::float8 AS sugar_g,
+               fiber_g::float8 AS fiber_g
+        FROM recipe_ingredients
+        WHERE recipe_id = $1
+        ORDER BY position
+        "#,
+    )
+    .bind(recipe_id)
+    .fetch_all(db)
+    .await?;
+    Ok(ingredients)
+}
+
+/// Stores a recipe with its resolved ingredients.
+pub async fn insert_recipe(
+    tx: &mut Transaction<'_, Postgres>,
+    user_id: Uuid,
+    name: &str,
+    servings: f64,
+    instructions: Option<&str>,
+    ingredients: &[NewMealItem],
+) -> anyhow::Result<RecipeRow> {
+    let recipe = sqlx::query_as::<_, RecipeRow>(&format!(
+        r#"
+        INSERT INTO recipes (user_id, name, servings, instructions)
+        VALUES ($1, $2, $3, $4)
+        RETURNING {RECIPE_COLUMNS}
+        "#
+    ))
+    .bind(user_id)
+    .bind(name)
+    .bind(servings)
+    .bind(instructions)
+    .fetch_one(&mut **tx)
+    .await?;
+    insert_ingredients(tx, recipe.id, ingredients).await?;
+    Ok(recipe)
+}
+
+/// Replaces a recipe and all of its ingredients; `None` if it doesn't belong
+/// to `user_id`.
+pub async fn update_recipe(
+    tx: &mut Transaction<'_, Postgres>,
+    user_id: Uuid,
+    recipe_id: Uuid,
+    name: &str,
+    servings: f64,
+    instructions: Option<&str>,
+    ingredients: &[NewMealItem],
+) -> anyhow::Result<Option<RecipeRow>> {
+    let Some(recipe) = sqlx::query_as::<_, RecipeRow>(&format!(
+        r#"
+        UPDATE recipes
+        SET name = $3, servings = $4, instructions = $5, updated_at = NOW()
+        WHERE id = $1 AND user_id = $2
+        RETURNING {RECIPE_COLUMNS}
+        "#
+    ))
+    .bind(recipe_id)
+    .bind(user_id)
+    .bind(name)
+    .bind(servings)
+    .bind(instructions)
+    .fetch_optional(&mut **tx)
+    .await?
+    else {
+        return Ok(None);
+    };
+    sqlx::query(r#"DELETE FROM recipe_ingredients WHERE recipe_id = $1"#)
+        .bind(recipe_id)
+        .execute(&mut **tx)
+        .await?;
+    insert_ingredients(tx, recipe_id, ingredients).await?;
+    Ok(Some(recipe))
+}
+
+async fn insert_ingredients(
+    tx: &mut Transaction<'_, Postgres>,
+    recipe_id: Uuid,
+    ingredients: &[NewMealItem],
+) -> anyhow::Result<()> {
+    for (position, item) in ingredients.iter().enumerate() {
+        sqlx::query(
+            r#"
+            INSERT INTO recipe_ingredients (recipe_id, position, food_source, food_id, name,
+                                            quantity, unit, total_calories_kcal, protein_g,
+                                            fat_g, carbs_g, sodium_mg, sugar_g, fiber_g)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
+            "#,
+        )
+        .bind(recipe_id)
+        .bind(position as i32)
+        .bind(item.food_source)
+        .bind(&item.food_id)
+        .bind(&item.name)
+        .bind(item.quantity)
+        .bind(item.unit)
+        .bind(item.nutrition.total_calories_kcal)
+        .bind(item.nutrition.protein_g)
+        .bind(item.nutrition.fat_g)
+        .bind(item.nutrition.carbs_g)
+        .bind(item.nutrition.sodium_mg)
+        .bind(item.nutrition.sugar_g)
+        .bind(item.nutrition.fiber_g)
+        .execute(&mut **tx)
+        .await?;
+    }
+    Ok(())
+}
+
+pub async fn delete_recipe(
+    db: impl PgExecutor<'_>,
+    user_id: Uuid,
+    recipe_id: Uuid,
+) -> anyhow::Result<bool> {
+    let result = sqlx::query(r#"DELETE FROM recipes WHERE id = $1 AND user_id = $2"#)
+        .bind(recipe_id)
+        .bind(user_id)
+        .execute(db)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
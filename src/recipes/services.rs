@@ -0,0 +1,205 @@
+use axum::http::StatusCode;
+use uuid::Uuid;
+
+use crate::{
+    db::AppState,
+    error::ErrorCode,
+    meal_items::{
+        dto::{MealItemRequest, NewMealItem},
+        repo as items_repo,
+        services::{resolve_item, ItemError},
+    },
+    meals::{
+        dto::{MealDetails, NewMeal},
+        services as meals_services,
+    },
+    recipes::{
+        dto::{Recipe, RecipeIngredient, RecipeRequest, RecipeSummary},
+        repo,
+    },
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum RecipeError {
+    #[error("Recipe not found")]
+    NotFound,
+    #[error(transparent)]
+    Ingredient(#[from] ItemError),
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+impl RecipeError {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            RecipeError::NotFound => StatusCode::NOT_FOUND,
+            RecipeError::Ingredient(e) => e.status(),
+            RecipeError::Other(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            RecipeError::NotFound => ErrorCode::RecipeNotFound,
+            RecipeError::Ingredient(e) => e.code(),
+            RecipeError::Other(_) => ErrorCode::Internal,
+        }
+    }
+}
+
+async fn resolve_ingredients(
+    state: &AppState,
+    user_id: Uuid,
+    requested: Vec<MealItemRequest>,
+) -> Result<Vec<NewMealItem>, RecipeError> {
+    let mut ingredients = Vec::with_capacity(requested.len());
+    for item in requested {
+        ingredients.push(resolve_item(state, user_id, item).await?);
+    }
+    Ok(ingredients)
+}
+
+pub async fn list_recipes(state: &AppState, user_id: Uuid) -> anyhow::Result<Vec<RecipeSummary>> {
+    let mut tx = state.begin_as(user_id).await?;
+    let recipes = repo::list_recipes(&mut *tx, user_id).await?;
+    tx.commit().await?;
+    Ok(recipes)
+}
+
+pub async fn create_recipe(
+    state: &AppState,
+    user_id: Uuid,
+    input: RecipeRequest,
+) -> Result<Recipe, RecipeError> {
+    let ingredients = resolve_ingredients(state, user_id, input.ingredients).await?;
+    let mut tx = state.begin_as(user_id).await.map_err(anyhow::Error::from)?;
+    let row = repo::insert_recipe(
+        &mut tx,
+        user_id,
+        &input.name,
+        input.servings,
+        input.instructions.as_deref(),
+        &ingredients,
+    )
+    .await?;
+    let ingredients = repo::list_ingredients(&mut *tx, row.id).await?;
+    tx.commit().await.map_err(anyhow::Error::from)?;
+    Ok(Recipe::new(row, ingredients))
+}
+
+pub async fn get_recipe(
+    state: &AppState,
+    user_id: Uuid,
+    recipe_id: Uuid,
+) -> Result<Recipe, RecipeError> {
+    let mut tx = state.begin_as(user_id).await.map_err(anyhow::Error::from)?;
+    let row = repo::find_recipe(&mut *tx, user_id, recipe_id)
+        .await?
+        .ok_or(RecipeError::NotFound)?;
+    let ingredients = repo::list_ingredients(&mut *tx, row.id).await?;
+    tx.commit().await.map_err(anyhow::Error::from)?;
+    Ok(Recipe::new(row, ingredients))
+}
+
+pub async fn replace_recipe(
+    state: &AppState,
+    user_id: Uuid,
+    recipe_id: Uuid,
+    input: RecipeRequest,
+) -> Result<Recipe, RecipeError> {
+    // Checked first so an unknown recipe isn't worth the food lookups.
+    get_recipe(state, user_id, recipe_id).await?;
+    let ingredients = resolve_ingredients(state, user_id, input.ingredients).await?;
+    let mut tx = state.begin_as(user_id).await.map_err(anyhow::Error::from)?;
+    let row = repo::update_recipe(
+        &mut tx,
+        user_id,
+        recipe_id,
+        &input.name,
+        input.servings,
+        input.instructions.as_deref(),
+        &ingredients,
+    )
+    .await?
+    .ok_or(RecipeError::NotFound)?;
+    let ingredients = repo::list_ingredients(&mut *tx, row.id).await?;
+    tx.commit().await.map_err(anyhow::Error::from)?;
+    Ok(Recipe::new(row, ingredients))
+}
+
+/// `false` if the user has no such recipe.
+pub async fn delete_recipe(
+    state: &AppState,
+    user_id: Uuid,
+    recipe_id: Uuid,
+) -> anyhow::Result<bool> {
+    let mut tx = state.begin_as(user_id).await?;
+    let deleted = repo::delete_recipe(&mut *tx, user_id, recipe_id).await?;
+    tx.commit().await?;
+    Ok(deleted)
+}
+
+/// An ingredient as a meal item for `factor` times the recipe.
+pub fn scaled_item(ingredient: &RecipeIngredient, factor: f64) -> NewMealItem {
+    NewMealItem {
+        food_source: ingredient.food_source,
+        food_id: ingredient.food_id.clone(),
+        name: ingredient.name.clone(),
+        // Stored with two decimals and must stay positive.
+        quantity: ((ingredient.quantity * factor * 100.0).round() / 100.0).max(0.01),
+        unit: ingredient.unit,
+        nutrition: ingredient.nutrition.scaled(factor),
+    }
+}
+
+/// Logs `servings` of a recipe as a new meal whose items are the scaled
+/// ingredients, writing the meal and its items in one transaction.
+pub async fn cook_recipe(
+    state: &AppState,
+    user_id: Uuid,
+    recipe_id: Uuid,
+    servings: f64,
+    mut meal: NewMeal,
+) -> Result<MealDetails, RecipeError> {
+    let recipe = get_recipe(state, user_id, recipe_id).await?;
+    if meal.title.is_none() {
+        meal.title = Some(recipe.name.clone());
+    }
+    let score_config = &state.config.score;
+    let mut tx = state.begin_as(user_id).await.map_err(anyhow::Error::from)?;
+    let created =
+        meals_services::insert_quick_meal(&mut tx, score_config, user_id, &meal, None).await?;
+    let factor = servings / recipe.servings;
+    for ingredient in &recipe.ingredients {
+        items_repo::insert_item(&mut *tx, created.id, &scaled_item(ingredient, factor)).await?;
+    }
+    meals_services::refresh_totals(&mut tx, score_config, user_id, created.id).await?;
+    tx.commit().await.map_err(anyhow::Error::from)?;
+    Ok(meals_services::finish_quick_meal(state, user_id, &created).await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{foods::dto::FoodNutrition, meal_items::dto::ItemUnit};
+
+    #[test]
+    fn scaled_item_scales_quantity_and_nutrition() {
+        let ingredient = RecipeIngredient {
+            id: Uuid::nil(),
+            food_source: None,
+            food_id: None,
+            name: "Rice".into(),
+            quantity: 300.0,
+            unit: ItemUnit::G,
+            nutrition: FoodNutrition {
+                total_calories_kcal: Some(390.0),
+                ..FoodNutrition::default()
+            },
+        };
+        let item = scaled_item(&ingredient, 1.0 / 3.0);
+        assert_eq!(item.quantity, 100.0);
+        assert_eq!(item.nutrition.total_calories_kcal, Some(130.0));
+        assert_eq!(scaled_item(&ingredient, 0.00001).quantity, 0.01);
+    }
+}
@@ -0,0 +1,148 @@
+use serde::{Deserialize, Serialize};
+use time::Date;
+use uuid::Uuid;
+
+use crate::{
+    foods::dto::FoodNutrition,
+    goals::dto::Intake,
+    meals::{dto::MealType, score::DailyTargets},
+};
+
+pub const DEFAULT_RECOMMENDATIONS: usize = 5;
+pub const MAX_RECOMMENDATIONS: usize = 20;
+/// How far back meal history is read.
+pub const HISTORY_DAYS: i64 = 14;
+
+#[derive(Debug, Deserialize)]
+pub struct RecommendationsQuery {
+    /// Defaults to the meal type of the user's current local time.
+    pub meal_type: Option<MealType>,
+    pub limit: Option<usize>,
+}
+
+impl RecommendationsQuery {
+    pub fn limit(&self) -> usize {
+        self.limit
+            .unwrap_or(DEFAULT_RECOMMENDATIONS)
+            .clamp(1, MAX_RECOMMENDATIONS)
+    }
+}
+
+/// The meal type usually eaten at `hour`, local time.
+pub fn meal_type_at(hour: i32) -> MealType {
+    match hour {
+        4..=10 => MealType::Breakfast,
+        11..=15 => MealType::Lunch,
+        17..=21 => MealType::Dinner,
+        _ => MealType::Snack,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CandidateSource {
+    Template,
+    Recipe,
+}
+
+/// A saved template, or one serving of a saved recipe.
+#[derive(Debug, Clone)]
+pub struct Candidate {
+    pub source: CandidateSource,
+    pub id: Uuid,
+    pub name: String,
+    pub meal_type: Option<MealType>,
+    pub nutrition: FoodNutrition,
+}
+
+/// What is left of today's goals for the next meal: the remaining budget
+/// shared among the meals still to come, never below zero.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct MealBudget {
+    pub calories_kcal: f64,
+    pub protein_g: f64,
+    pub fat_g: f64,
+    pub carbs_g: f64,
+}
+
+impl MealBudget {
+    /// `meals_left` is at least 1, even once the day's meals are all logged.
+    pub fn new(targets: &DailyTargets, eaten: &Intake, meals_left: f64) -> Self {
+        let share = |target: f64, eaten: Option<f64>| {
+            ((target - eaten.unwrap_or(0.0)) / meals_left.max(1.0)).max(0.0)
+        };
+        Self {
+            calories_kcal: share(targets.calories_kcal, eaten.calories_kcal),
+            protein_g: share(targets.protein_g, eaten.protein_g),
+            fat_g: share(targets.fat_g, eaten.fat_g),
+            carbs_g: share(targets.carbs_g, eaten.carbs_g),
+        }
+    }
+}
+
+/// A meal logged recently, by title.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct RecentMeal {
+    pub title: String,
+    pub days_ago: i32,
+}
+
+/// Everything a strategy ranks candidates by.
+#[derive(Debug, Clone)]
+pub struct RecommendationContext {
+    pub date: Date,
+    pub meal_type: MealType,
+    pub budget: MealBudget,
+    /// Meals of the last [`HISTORY_DAYS`] days, newest first.
+    pub recent: Vec<RecentMeal>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Recommendation {
+    pub source: CandidateSource,
+    pub id: Uuid,
+    pub name: String,
+    pub meal_type: Option<MealType>,
+    pub nutrition: FoodNutrition,
+    /// 0–100, higher is better; only comparable within one response.
+    pub score: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RecommendationsResponse {
+    pub strategy: &'static str,
+    pub meal_type: MealType,
+    pub budget: MealBudget,
+    pub recommendations: Vec<Recommendation>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn budget_shares_what_is_left_among_the_remaining_meals() {
+        let eaten = Intake {
+            calories_kcal: Some(800.0),
+            protein_g: Some(200.0),
+            ..Intake::default()
+        };
+        let budget = MealBudget::new(&DailyTargets::default(), &eaten, 2.0);
+        assert_eq!(
+            budget.calories_kcal,
+            (DailyTargets::default().calories_kcal - 800.0) / 2.0
+        );
+        assert_eq!(budget.protein_g, 0.0);
+        let last = MealBudget::new(&DailyTargets::default(), &eaten, 0.0);
+        assert_eq!(last.fat_g, DailyTargets::default().fat_g);
+    }
+
+    #[test]
+    fn meal_type_follows_the_clock() {
+        assert_eq!(meal_type_at(7), MealType::Breakfast);
+        assert_eq!(meal_type_at(12), MealType::Lunch);
+        assert_eq!(meal_type_at(16), MealType::Snack);
+        assert_eq!(meal_type_at(19), MealType::Dinner);
+        assert_eq!(meal_type_at(23), MealType::Snack);
+    }
+}
@@ -0,0 +1,208 @@
+//! The default strategy: how closely a candidate fills the next meal's share
+//! of the remaining macros, nudged by meal type and recent history.
+
+use std::cmp::Ordering;
+
+use crate::recommendations::{
+    dto::{Candidate, MealBudget, RecentMeal, Recommendation, RecommendationContext},
+    RecommendationStrategy,
+};
+
+/// Bonus for a candidate saved for the requested meal type, and penalty for
+/// one saved for another.
+const MEAL_TYPE_WEIGHT: f64 = 0.1;
+/// Bonus per time a candidate was eaten in the history window, up to
+/// [`MAX_FAVORITE_COUNT`] times.
+const FAVORITE_WEIGHT: f64 = 0.03;
+const MAX_FAVORITE_COUNT: usize = 3;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MacroFitStrategy;
+
+/// 1 at `target`, falling linearly to 0 one `scale` away; `scale` keeps
+/// small or exhausted budgets from making every candidate a miss.
+fn closeness(value: f64, target: f64, min_scale: f64) -> f64 {
+    let scale = target.max(min_scale);
+    (1.0 - (value - target).abs() / scale).clamp(0.0, 1.0)
+}
+
+/// Mean closeness over the macros the candidate has values for; `None`
+/// without calories.
+fn macro_fit(candidate: &Candidate, budget: &MealBudget) -> Option<f64> {
+    let n = &candidate.nutrition;
+    let mut components = vec![closeness(
+        n.total_calories_kcal?,
+        budget.calories_kcal,
+        200.0,
+    )];
+    let macros = [
+        (n.protein_g, budget.protein_g, 10.0),
+        (n.fat_g, budget.fat_g, 10.0),
+        (n.carbs_g, budget.carbs_g, 20.0),
+    ];
+    components.extend(
+        macros
+            .into_iter()
+            .filter_map(|(value, target, scale)| value.map(|v| closeness(v, target, scale))),
+    );
+    Some(components.iter().sum::<f64>() / components.len() as f64)
+}
+
+/// Favorites score a little higher, but not when eaten in the last days.
+fn history_factor(name: &str, recent: &[RecentMeal]) -> f64 {
+    let mut eaten = recent
+        .iter()
+        .filter(|meal| meal.title.trim().eq_ignore_ascii_case(name.trim()));
+    let Some(last) = eaten.next() else {
+        return 1.0;
+    };
+    let count = 1 + eaten.count();
+    let favorite = 1.0 + FAVORITE_WEIGHT * count.min(MAX_FAVORITE_COUNT) as f64;
+    let variety = match last.days_ago {
+        0 | 1 => 0.5,
+        2 | 3 => 0.8,
+        _ => 1.0,
+    };
+    favorite * variety
+}
+
+impl MacroFitStrategy {
+    pub fn score(&self, context: &RecommendationContext, candidate: &Candidate) -> Option<f64> {
+        let mut score = macro_fit(candidate, &context.budget)?;
+        match candidate.meal_type {
+            Some(t) if t == context.meal_type => score += MEAL_TYPE_WEIGHT,
+            Some(_) => score -= MEAL_TYPE_WEIGHT,
+            None => {}
+        }
+        score *= history_factor(&candidate.name, &context.recent);
+        Some((score.clamp(0.0, 1.0) * 1000.0).round() / 10.0)
+    }
+}
+
+#[axum::async_trait]
+impl RecommendationStrategy for MacroFitStrategy {
+    fn name(&self) -> &'static str {
+        "macro_fit"
+    }
+
+    async fn rank(
+        &self,
+        context: &RecommendationContext,
+        candidates: Vec<Candidate>,
+    ) -> anyhow::Result<Vec<Recommendation>> {
+        let mut ranked: Vec<Recommendation> = candidates
+            .into_iter()
+            .filter_map(|candidate| {
+                let score = self.score(context, &candidate)?;
+                Some(Recommendation {
+                    source: candidate.source,
+                    id: candidate.id,
+                    name: candidate.name,
+                    meal_type: candidate.meal_type,
+                    nutrition: candidate.nutrition,
+                    score,
+                })
+            })
+            .collect();
+        ranked.sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(Ordering::Equal)
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        Ok(ranked)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use time::macros::date;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::{
+        foods::dto::FoodNutrition, meals::dto::MealType, recommendations::dto::CandidateSource,
+    };
+
+    fn candidate(name: &str, kcal: f64, protein: f64, meal_type: Option<MealType>) -> Candidate {
+        Candidate {
+            source: CandidateSource::Template,
+            id: Uuid::new_v4(),
+            name: name.into(),
+            meal_type,
+            nutrition: FoodNutrition {
+                total_calories_kcal: Some(kcal),
+                protein_g: Some(protein),
+                ..FoodNutrition::default()
+            },
+        }
+    }
+
+    fn context(recent: Vec<RecentMeal>) -> RecommendationContext {
+        RecommendationContext {
+            date: date!(2026 - 10 - 16),
+            meal_type: MealType::Dinner,
+            budget: MealBudget {
+                calories_kcal: 700.0,
+                protein_g: 40.0,
+                fat_g: 25.0,
+                carbs_g: 80.0,
+            },
+            recent,
+        }
+    }
+
+    #[tokio::test]
+    async fn ranks_by_fit_and_drops_unknown_nutrition() {
+        let unknown = Candidate {
+            nutrition: FoodNutrition::default(),
+            ..candidate("Mystery", 0.0, 0.0, None)
+        };
+        let ranked = MacroFitStrategy
+            .rank(
+                &context(vec![]),
+                vec![
+                    candidate("Salad", 250.0, 10.0, None),
+                    candidate("Chicken and rice", 700.0, 40.0, None),
+                    unknown,
+                ],
+            )
+            .await
+            .unwrap();
+        let names: Vec<&str> = ranked.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, ["Chicken and rice", "Salad"]);
+        assert_eq!(ranked[0].score, 100.0);
+    }
+
+    #[test]
+    fn meal_type_and_history_adjust_the_score() {
+        let strategy = MacroFitStrategy;
+        let ctx = context(vec![]);
+        let dinner = strategy
+            .score(
+                &ctx,
+                &candidate("Stew", 600.0, 35.0, Some(MealType::Dinner)),
+            )
+            .unwrap();
+        let breakfast = strategy
+            .score(
+                &ctx,
+                &candidate("Stew", 600.0, 35.0, Some(MealType::Breakfast)),
+            )
+            .unwrap();
+        assert!(dinner > breakfast);
+
+        let recent = |days_ago| RecentMeal {
+            title: "stew".into(),
+            days_ago,
+        };
+        let stew = candidate("Stew", 600.0, 35.0, None);
+        let fresh = strategy.score(&ctx, &stew).unwrap();
+        let yesterday = strategy.score(&context(vec![recent(1)]), &stew).unwrap();
+        let favorite = strategy
+            .score(&context(vec![recent(5), recent(9)]), &stew)
+            .unwrap();
+        assert!(yesterday < fresh);
+        assert!(favorite > fresh);
+    }
+}
@@ -0,0 +1,26 @@
+//! Suggestions for the next meal out of the user's saved templates and
+//! recipes. Ranking sits behind [`RecommendationStrategy`] so a learned
+//! model can replace [`MacroFitStrategy`] without touching the endpoint.
+
+use crate::recommendations::dto::{Candidate, Recommendation, RecommendationContext};
+
+pub mod dto;
+pub mod heuristic;
+pub mod repo;
+pub mod services;
+
+pub use heuristic::MacroFitStrategy;
+
+#[axum::async_trait]
+pub trait RecommendationStrategy: Send + Sync {
+    /// Reported with the recommendations.
+    fn name(&self) -> &'static str;
+
+    /// Scores `candidates` for `context`, best first. Candidates may be left
+    /// out, for example when their nutrition is unknown.
+    async fn rank(
+        &self,
+        context: &RecommendationContext,
+        candidates: Vec<Candidate>,
+    ) -> anyhow::Result<Vec<Recommendation>>;
+}
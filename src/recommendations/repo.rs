@@ -0,0 +1,47 @@
+use sqlx::PgPool;
+use time::Date;
+use uuid::Uuid;
+
+use crate::recommendations::dto::RecentMeal;
+
+/// Today's date and the current hour in time zone `tz`.
+pub async fn local_now(db: &PgPool, tz: &str) -> anyhow::Result<(Date, i32)> {
+    let now = sqlx::query_as::<_, (Date, i32)>(
+        r#"
+        SELECT (NOW() AT TIME ZONE $1)::date,
+               EXTRACT(HOUR FROM NOW() AT TIME ZONE $1)::int4
+        "#,
+    )
+    .bind(tz)
+    .fetch_one(db)
+    .await?;
+    Ok(now)
+}
+
+/// Titled meals of the `days` days before `today` and of today, newest first.
+pub async fn recent_meals(
+    db: &PgPool,
+    user_id: Uuid,
+    today: Date,
+    tz: &str,
+    days: i64,
+) -> anyhow::Result<Vec<RecentMeal>> {
+    let meals = sqlx::query_as::<_, RecentMeal>(
+        r#"
+        SELECT title,
+               ($2::date - (created_at AT TIME ZONE $3)::date)::int4 AS days_ago
+        FROM meals
+        WHERE user_id = $1
+          AND title IS NOT NULL
+          AND created_at >= ($2::date - $4::int4)::timestamp AT TIME ZONE $3
+        ORDER BY created_at DESC
+        "#,
+    )
+    .bind(user_id)
+    .bind(today)
+    .bind(tz)
+    .bind(days as i32)
+    .fetch_all(db)
+    .await?;
+    Ok(meals)
+}
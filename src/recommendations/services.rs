@@ -0,0 +1,75 @@
+use uuid::Uuid;
+
+use crate::{
+    activity::{dto::CalorieBudget, repo as activity_repo},
+    db::AppState,
+    goals::repo as goals_repo,
+    preferences::services as preferences_services,
+    recipes::repo as recipes_repo,
+    recommendations::{
+        dto::{
+            meal_type_at, Candidate, CandidateSource, MealBudget, RecommendationContext,
+            RecommendationsQuery, RecommendationsResponse, HISTORY_DAYS,
+        },
+        repo,
+    },
+    summary::repo as summary_repo,
+    templates::repo as templates_repo,
+};
+
+/// The user's templates and recipes ranked by `state.recommender` for the
+/// next meal of today, in the time zone of the user's preferences. Burned
+/// calories raise the calorie budget as in the daily summary.
+pub async fn recommend(
+    state: &AppState,
+    user_id: Uuid,
+    query: &RecommendationsQuery,
+) -> anyhow::Result<RecommendationsResponse> {
+    let tz = preferences_services::timezone(state, user_id).await?;
+    let (today, hour) = repo::local_now(&state.db, &tz).await?;
+    let totals = summary_repo::day_totals(&state.db, user_id, today, &tz).await?;
+    let goals = goals_repo::find_goals(&state.db, user_id).await?;
+    let activity = activity_repo::day_totals(&state.db, user_id, today, &tz).await?;
+    let eat_back = preferences_services::preferences(state, user_id)
+        .await?
+        .activity_eat_back_percent;
+    let mut targets = goals.targets();
+    targets.calories_kcal =
+        CalorieBudget::new(targets.calories_kcal, activity.burned_kcal, eat_back, 0.0).budget_kcal;
+    let meals_left = state.config.score.meals_per_day - totals.meals as f64;
+    let context = RecommendationContext {
+        date: today,
+        meal_type: query.meal_type.unwrap_or_else(|| meal_type_at(hour)),
+        budget: MealBudget::new(&targets, &totals.intake, meals_left),
+        recent: repo::recent_meals(&state.db, user_id, today, &tz, HISTORY_DAYS).await?,
+    };
+
+    let templates = templates_repo::list_templates(&state.db, user_id).await?;
+    let recipes = recipes_repo::list_recipes(&state.db, user_id).await?;
+    let candidates = templates
+        .iter()
+        .map(|template| Candidate {
+            source: CandidateSource::Template,
+            id: template.id,
+            name: template.name.clone(),
+            meal_type: template.meal_type,
+            nutrition: template.total_nutrition(),
+        })
+        .chain(recipes.into_iter().map(|recipe| Candidate {
+            source: CandidateSource::Recipe,
+            id: recipe.id,
+            name: recipe.name,
+            meal_type: None,
+            nutrition: recipe.nutrition_per_serving,
+        }))
+        .collect();
+
+    let mut recommendations = state.recommender.rank(&context, candidates).await?;
+    recommendations.truncate(query.limit());
+    Ok(RecommendationsResponse {
+        strategy: state.recommender.name(),
+        meal_type: context.meal_type,
+        budget: context.budget,
+        recommendations,
+    })
+}
@@ -0,0 +1,81 @@
+//! `X-Request-Id` handling: every request gets an id that is logged with its
+//! span, echoed in the response header and included in error bodies.
+
+use std::future::Future;
+
+use axum::{
+    extract::Request,
+    http::{HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use uuid::Uuid;
+
+pub static X_REQUEST_ID: HeaderName = HeaderName::from_static("x-request-id");
+
+const MAX_ID_LEN: usize = 128;
+
+tokio::task_local! {
+    static REQUEST_ID: String;
+}
+
+/// Id of the request being handled, if called from inside [`propagate`].
+pub fn current() -> Option<String> {
+    REQUEST_ID.try_with(|id| id.clone()).ok()
+}
+
+/// Runs `fut` with `id` as the [`current`] request id.
+pub async fn scoped<F: Future>(id: String, fut: F) -> F::Output {
+    REQUEST_ID.scope(id, fut).await
+}
+
+/// Keeps a client-supplied id when it is short and log-safe, otherwise
+/// generates one.
+fn accept_or_generate(header: Option<&HeaderValue>) -> String {
+    header
+        .and_then(|v| v.to_str().ok())
+        .filter(|id| {
+            !id.is_empty()
+                && id.len() <= MAX_ID_LEN
+                && id
+                    .bytes()
+                    .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.'))
+        })
+        .map(str::to_string)
+        .unwrap_or_else(|| Uuid::new_v4().to_string())
+}
+
+/// Outermost middleware: normalizes the request header so the trace span can
+/// pick it up, scopes the id for [`current`] and copies it to the response.
+pub async fn propagate(mut req: Request, next: Next) -> Response {
+    let id = accept_or_generate(req.headers().get(&X_REQUEST_ID));
+    let value = HeaderValue::from_str(&id).expect("request ids are ASCII");
+    req.headers_mut()
+        .insert(X_REQUEST_ID.clone(), value.clone());
+
+    let mut response = scoped(id, next.run(req)).await;
+    response.headers_mut().insert(X_REQUEST_ID.clone(), value);
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_safe_client_ids() {
+        let header = HeaderValue::from_static("mobile-4f2a_1.2");
+        assert_eq!(accept_or_generate(Some(&header)), "mobile-4f2a_1.2");
+    }
+
+    #[test]
+    fn replaces_missing_or_unsafe_ids() {
+        let generated = accept_or_generate(None);
+        assert!(Uuid::parse_str(&generated).is_ok());
+
+        let header = HeaderValue::from_static("id with spaces");
+        assert_ne!(accept_or_generate(Some(&header)), "id with spaces");
+        let long = HeaderValue::from_str(&"a".repeat(MAX_ID_LEN + 1)).unwrap();
+        assert_eq!(accept_or_generate(Some(&long)).len(), 36);
+    }
+}
@@ -0,0 +1,259 @@
+use std::collections::{HashMap, HashSet};
+
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use time::{Date, OffsetDateTime};
+use uuid::Uuid;
+
+use crate::{
+    export::dto::DataExportStatus,
+    meals::dto::{MealType, NutritionSource},
+};
+
+type HmacSha256 = Hmac<Sha256>;
+
+time::serde::format_description!(iso_date, Date, "[year]-[month]-[day]");
+
+pub const MAX_K: u32 = 1000;
+/// Meals read per query while building a dataset.
+pub const MEALS_PAGE_SIZE: i64 = 1000;
+/// Width of the calorie buckets meals are generalized to.
+pub const CALORIE_BUCKET_KCAL: f64 = 50.0;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct CreateResearchExportRequest {
+    /// Defaults to `RESEARCH_EXPORT_MIN_K`, which is also the least allowed.
+    pub k: Option<u32>,
+}
+
+impl CreateResearchExportRequest {
+    pub fn k(&self, min_k: u32) -> Result<u32, String> {
+        let k = self.k.unwrap_or(min_k);
+        if !(min_k..=MAX_K).contains(&k) {
+            return Err(format!("k must be between {} and {}", min_k, MAX_K));
+        }
+        Ok(k)
+    }
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct ResearchExport {
+    pub id: Uuid,
+    pub requested_by: Option<Uuid>,
+    pub k: i32,
+    pub status: DataExportStatus,
+    pub s3_key: Option<String>,
+    pub size_bytes: Option<i64>,
+    pub meal_count: Option<i32>,
+    pub user_count: Option<i32>,
+    pub suppressed_count: Option<i32>,
+    pub created_at: OffsetDateTime,
+    pub completed_at: Option<OffsetDateTime>,
+}
+
+/// Export status; `download_url` is set once the dataset is ready and is
+/// valid until `expires_at`.
+#[derive(Debug, Serialize)]
+pub struct ResearchExportResponse {
+    pub id: Uuid,
+    pub status: DataExportStatus,
+    pub k: i32,
+    pub meal_count: Option<i32>,
+    pub user_count: Option<i32>,
+    /// Meals left out because fewer than `k` users shared their class.
+    pub suppressed_count: Option<i32>,
+    pub size_bytes: Option<i64>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub completed_at: Option<OffsetDateTime>,
+    pub download_url: Option<String>,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub expires_at: Option<OffsetDateTime>,
+}
+
+/// A meal with nutrition as read from the database, before anonymization.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct ResearchMeal {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub created_at: OffsetDateTime,
+    pub meal_type: Option<MealType>,
+    pub source: NutritionSource,
+    pub total_calories_kcal: f64,
+    pub protein_g: Option<f64>,
+    pub fat_g: Option<f64>,
+    pub carbs_g: Option<f64>,
+    pub fiber_g: Option<f64>,
+    pub sugar_g: Option<f64>,
+    pub sodium_mg: Option<f64>,
+}
+
+/// A record of `meals.csv`. `week`, `meal_type` and `calories_kcal` are the
+/// quasi-identifiers the k-anonymity check runs on.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DatasetRow {
+    pub user: String,
+    /// Monday of the meal's ISO week, in UTC.
+    #[serde(with = "iso_date")]
+    pub week: Date,
+    pub meal_type: Option<MealType>,
+    pub calories_kcal: f64,
+    pub protein_g: Option<f64>,
+    pub fat_g: Option<f64>,
+    pub carbs_g: Option<f64>,
+    pub fiber_g: Option<f64>,
+    pub sugar_g: Option<f64>,
+    pub sodium_mg: Option<f64>,
+    pub source: NutritionSource,
+}
+
+/// `manifest.json` in the dataset archive.
+#[derive(Debug, Serialize)]
+pub struct DatasetManifest {
+    pub k: u32,
+    pub meal_count: usize,
+    pub user_count: usize,
+    pub suppressed_count: usize,
+    pub quasi_identifiers: [&'static str; 3],
+    #[serde(with = "time::serde::rfc3339")]
+    pub generated_at: OffsetDateTime,
+}
+
+#[derive(Debug, Default)]
+pub struct Dataset {
+    pub rows: Vec<DatasetRow>,
+    pub suppressed: usize,
+}
+
+impl Dataset {
+    pub fn user_count(&self) -> usize {
+        self.rows
+            .iter()
+            .map(|r| &r.user)
+            .collect::<HashSet<_>>()
+            .len()
+    }
+}
+
+/// Stable pseudonym of a user under `key`: the same in every export made
+/// with the key, and not reversible without it.
+pub fn pseudonym(key: &str, user_id: Uuid) -> String {
+    // HMAC accepts keys of any length.
+    let mut mac = HmacSha256::new_from_slice(key.as_bytes()).expect("any key length");
+    mac.update(b"research-user:");
+    mac.update(user_id.as_bytes());
+    hex::encode(&mac.finalize().into_bytes()[..16])
+}
+
+fn round_to(value: Option<f64>, step: f64) -> Option<f64> {
+    value.map(|v| (v / step).round() * step)
+}
+
+impl DatasetRow {
+    /// The meal pseudonymized under `key` and generalized.
+    pub fn new(meal: &ResearchMeal, key: &str) -> Self {
+        let day = meal.created_at.date();
+        DatasetRow {
+            user: pseudonym(key, meal.user_id),
+            week: day - time::Duration::days(i64::from(day.weekday().number_days_from_monday())),
+            meal_type: meal.meal_type,
+            calories_kcal: (meal.total_calories_kcal / CALORIE_BUCKET_KCAL).round()
+                * CALORIE_BUCKET_KCAL,
+            protein_g: round_to(meal.protein_g, 1.0),
+            fat_g: round_to(meal.fat_g, 1.0),
+            carbs_g: round_to(meal.carbs_g, 1.0),
+            fiber_g: round_to(meal.fiber_g, 1.0),
+            sugar_g: round_to(meal.sugar_g, 1.0),
+            sodium_mg: round_to(meal.sodium_mg, 10.0),
+            source: meal.source,
+        }
+    }
+}
+
+/// Drops every row whose quasi-identifiers are shared by fewer than `k`
+/// distinct users.
+pub fn anonymize(rows: Vec<DatasetRow>, k: u32) -> Dataset {
+    let class = |row: &DatasetRow| (row.week, row.meal_type, row.calories_kcal as i64);
+    let mut users: HashMap<_, HashSet<&str>> = HashMap::new();
+    for row in &rows {
+        users.entry(class(row)).or_default().insert(&row.user);
+    }
+    let keep: HashSet<_> = users
+        .into_iter()
+        .filter(|(_, users)| users.len() >= k as usize)
+        .map(|(class, _)| class)
+        .collect();
+    let total = rows.len();
+    let rows: Vec<DatasetRow> = rows
+        .into_iter()
+        .filter(|row| keep.contains(&class(row)))
+        .collect();
+    Dataset {
+        suppressed: total - rows.len(),
+        rows,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::{date, datetime};
+
+    fn meal(user: u128, created_at: OffsetDateTime, kcal: f64) -> ResearchMeal {
+        ResearchMeal {
+            id: Uuid::new_v4(),
+            user_id: Uuid::from_u128(user),
+            created_at,
+            meal_type: Some(MealType::Lunch),
+            source: NutritionSource::Ai,
+            total_calories_kcal: kcal,
+            protein_g: Some(31.4),
+            fat_g: None,
+            carbs_g: None,
+            fiber_g: None,
+            sugar_g: None,
+            sodium_mg: Some(1234.0),
+        }
+    }
+
+    #[test]
+    fn pseudonyms_are_stable_per_key() {
+        let user = Uuid::from_u128(7);
+        assert_eq!(pseudonym("key", user), pseudonym("key", user));
+        assert_ne!(pseudonym("key", user), pseudonym("other", user));
+        assert_eq!(pseudonym("key", user).len(), 32);
+        assert!(!pseudonym("key", user).contains(&user.simple().to_string()));
+    }
+
+    #[test]
+    fn rare_classes_are_suppressed() {
+        let meals = [
+            // Same ISO week and 50 kcal bucket for three users.
+            meal(1, datetime!(2024-06-03 12:00 UTC), 610.0),
+            meal(2, datetime!(2024-06-05 13:00 UTC), 590.0),
+            meal(3, datetime!(2024-06-09 12:30 UTC), 612.0),
+            // Only this user ate 1200 kcal that week.
+            meal(1, datetime!(2024-06-04 12:00 UTC), 1200.0),
+        ];
+        let rows = || meals.iter().map(|m| DatasetRow::new(m, "key")).collect();
+        let dataset = anonymize(rows(), 3);
+        assert_eq!(dataset.rows.len(), 3);
+        assert_eq!(dataset.suppressed, 1);
+        assert_eq!(dataset.user_count(), 3);
+        let row = &dataset.rows[0];
+        assert_eq!(row.week, date!(2024 - 06 - 03));
+        assert_eq!(row.calories_kcal, 600.0);
+        assert_eq!(row.protein_g, Some(31.0));
+        assert_eq!(row.sodium_mg, Some(1230.0));
+
+        assert!(anonymize(rows(), 4).rows.is_empty());
+    }
+
+    #[test]
+    fn k_is_at_least_the_minimum() {
+        assert_eq!(CreateResearchExportRequest::default().k(5), Ok(5));
+        assert!(CreateResearchExportRequest { k: Some(2) }.k(5).is_err());
+    }
+}
@@ -0,0 +1,9 @@
+//! Anonymized meal datasets for internal model training. Admins request one
+//! with a k; the `research_export` job pseudonymizes users, generalizes
+//! dates and calories, suppresses meals shared by fewer than k users and
+//! uploads the result. Photos, titles, notes, tags and locations are never
+//! included.
+
+pub mod dto;
+pub mod repo;
+pub mod services;
@@ -0,0 +1,121 @@
+use sqlx::PgPool;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::{
+    export::dto::DataExportStatus,
+    research::dto::{ResearchExport, ResearchMeal},
+};
+
+const EXPORT_COLUMNS: &str = "id, requested_by, k, status, s3_key, size_bytes, meal_count, \
+     user_count, suppressed_count, created_at, completed_at";
+
+pub async fn create_export(
+    db: &PgPool,
+    requested_by: Uuid,
+    k: i32,
+) -> anyhow::Result<ResearchExport> {
+    let export = sqlx::query_as::<_, ResearchExport>(&format!(
+        "INSERT INTO research_exports (requested_by, k) VALUES ($1, $2) RETURNING {EXPORT_COLUMNS}"
+    ))
+    .bind(requested_by)
+    .bind(k)
+    .fetch_one(db)
+    .await?;
+    Ok(export)
+}
+
+pub async fn find_export(db: &PgPool, export_id: Uuid) -> anyhow::Result<Option<ResearchExport>> {
+    let export = sqlx::query_as::<_, ResearchExport>(&format!(
+        "SELECT {EXPORT_COLUMNS} FROM research_exports WHERE id = $1"
+    ))
+    .bind(export_id)
+    .fetch_optional(db)
+    .await?;
+    Ok(export)
+}
+
+pub async fn set_export_status(
+    db: &PgPool,
+    export_id: Uuid,
+    status: DataExportStatus,
+) -> anyhow::Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE research_exports
+        SET status = $2,
+            completed_at = CASE WHEN $2 = 'failed' THEN NOW() ELSE completed_at END
+        WHERE id = $1
+        "#,
+    )
+    .bind(export_id)
+    .bind(status)
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+pub struct CompletedExport<'a> {
+    pub s3_key: &'a str,
+    pub size_bytes: i64,
+    pub meal_count: i32,
+    pub user_count: i32,
+    pub suppressed_count: i32,
+}
+
+pub async fn complete_export(
+    db: &PgPool,
+    export_id: Uuid,
+    done: &CompletedExport<'_>,
+) -> anyhow::Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE research_exports
+        SET status = 'done', s3_key = $2, size_bytes = $3, meal_count = $4, user_count = $5,
+            suppressed_count = $6, completed_at = NOW()
+        WHERE id = $1
+        "#,
+    )
+    .bind(export_id)
+    .bind(done.s3_key)
+    .bind(done.size_bytes)
+    .bind(done.meal_count)
+    .bind(done.user_count)
+    .bind(done.suppressed_count)
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+/// Meals with a calorie estimate, across all users, in creation order after
+/// the `(created_at, id)` cursor. Archived meals are not included.
+pub async fn meals_with_nutrition(
+    db: &PgPool,
+    after: Option<(OffsetDateTime, Uuid)>,
+    limit: i64,
+) -> anyhow::Result<Vec<ResearchMeal>> {
+    let meals = sqlx::query_as::<_, ResearchMeal>(
+        r#"
+        SELECT m.id, m.user_id, m.created_at, m.meal_type, n.source,
+               n.total_calories_kcal::float8 AS total_calories_kcal,
+               n.protein_g::float8 AS protein_g,
+               n.fat_g::float8 AS fat_g,
+               n.carbs_g::float8 AS carbs_g,
+               n.fiber_g::float8 AS fiber_g,
+               n.sugar_g::float8 AS sugar_g,
+               n.sodium_mg::float8 AS sodium_mg
+        FROM meals m
+        JOIN meal_nutrition n ON n.meal_id = m.id
+        WHERE n.total_calories_kcal IS NOT NULL
+          AND ($1::timestamptz IS NULL OR (m.created_at, m.id) > ($1, $2))
+        ORDER BY m.created_at, m.id
+        LIMIT $3
+        "#,
+    )
+    .bind(after.map(|(created_at, _)| created_at))
+    .bind(after.map(|(_, id)| id))
+    .bind(limit)
+    .fetch_all(db)
+    .await?;
+    Ok(meals)
+}
@@ -0,0 +1,190 @@
+use axum::http::StatusCode;
+use time::OffsetDateTime;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    db::AppState,
+    error::ErrorCode,
+    export::{
+        dto::DataExportStatus,
+        services::{Archive, DOWNLOAD_TTL},
+    },
+    jobs::{repo as jobs_repo, Job},
+    research::{
+        dto::{
+            anonymize, DatasetManifest, DatasetRow, ResearchExport, ResearchExportResponse,
+            MEALS_PAGE_SIZE,
+        },
+        repo::{self, CompletedExport},
+    },
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum ResearchError {
+    #[error("Research exports need RESEARCH_PSEUDONYM_KEY")]
+    NotConfigured,
+    #[error("Research export not found")]
+    NotFound,
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+impl ResearchError {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            ResearchError::NotConfigured => StatusCode::SERVICE_UNAVAILABLE,
+            ResearchError::NotFound => StatusCode::NOT_FOUND,
+            ResearchError::Other(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            ResearchError::NotConfigured => ErrorCode::ServiceUnavailable,
+            ResearchError::NotFound => ErrorCode::ExportNotFound,
+            ResearchError::Other(_) => ErrorCode::Internal,
+        }
+    }
+}
+
+/// Queues a dataset build with the given k.
+pub async fn request_export(
+    state: &AppState,
+    admin_id: Uuid,
+    k: u32,
+) -> Result<ResearchExportResponse, ResearchError> {
+    if state.config.research.pseudonym_key.is_none() {
+        return Err(ResearchError::NotConfigured);
+    }
+    let export = repo::create_export(&state.db, admin_id, k as i32).await?;
+    let job = Job::ResearchExport {
+        export_id: export.id,
+    };
+    jobs_repo::enqueue(&state.db, &job, state.config.jobs.max_attempts).await?;
+    info!(user_id = %admin_id, export_id = %export.id, k, "research export queued");
+    Ok(export_response(state, export).await?)
+}
+
+pub async fn get_export(
+    state: &AppState,
+    export_id: Uuid,
+) -> Result<ResearchExportResponse, ResearchError> {
+    let export = repo::find_export(&state.db, export_id)
+        .await?
+        .ok_or(ResearchError::NotFound)?;
+    Ok(export_response(state, export).await?)
+}
+
+async fn export_response(
+    state: &AppState,
+    export: ResearchExport,
+) -> anyhow::Result<ResearchExportResponse> {
+    let (download_url, expires_at) = match (&export.status, &export.s3_key) {
+        (DataExportStatus::Done, Some(key)) => (
+            Some(state.storage.presign_get(key, DOWNLOAD_TTL).await?),
+            Some(OffsetDateTime::now_utc() + DOWNLOAD_TTL),
+        ),
+        _ => (None, None),
+    };
+    Ok(ResearchExportResponse {
+        id: export.id,
+        status: export.status,
+        k: export.k,
+        meal_count: export.meal_count,
+        user_count: export.user_count,
+        suppressed_count: export.suppressed_count,
+        size_bytes: export.size_bytes,
+        created_at: export.created_at,
+        completed_at: export.completed_at,
+        download_url,
+        expires_at,
+    })
+}
+
+/// Job body: builds the anonymized dataset as a ZIP of `meals.csv` and
+/// `manifest.json` and uploads it.
+pub async fn build_research_export(state: &AppState, export_id: Uuid) -> anyhow::Result<()> {
+    let Some(export) = repo::find_export(&state.db, export_id).await? else {
+        info!(export_id = %export_id, "research export deleted before it ran");
+        return Ok(());
+    };
+    let key = state
+        .config
+        .research
+        .pseudonym_key
+        .as_deref()
+        .ok_or_else(|| anyhow::anyhow!("RESEARCH_PSEUDONYM_KEY is not set"))?;
+    repo::set_export_status(&state.db, export_id, DataExportStatus::Running).await?;
+
+    // Read page by page rather than as one result set; the k-anonymity
+    // check still needs every generalized row.
+    let mut rows = Vec::new();
+    let mut after = None;
+    loop {
+        let page = repo::meals_with_nutrition(&state.db, after, MEALS_PAGE_SIZE).await?;
+        let last = (page.len() as i64) < MEALS_PAGE_SIZE;
+        after = page.last().map(|meal| (meal.created_at, meal.id));
+        rows.extend(page.iter().map(|meal| DatasetRow::new(meal, key)));
+        if last {
+            break;
+        }
+    }
+    let k = export.k as u32;
+    let dataset = anonymize(rows, k);
+
+    let mut csv = csv::Writer::from_writer(Vec::new());
+    for row in &dataset.rows {
+        csv.serialize(row)?;
+    }
+    let csv = csv
+        .into_inner()
+        .map_err(|e| anyhow::anyhow!("{}", e.error()))?;
+    let manifest = DatasetManifest {
+        k,
+        meal_count: dataset.rows.len(),
+        user_count: dataset.user_count(),
+        suppressed_count: dataset.suppressed,
+        quasi_identifiers: ["week", "meal_type", "calories_kcal"],
+        generated_at: OffsetDateTime::now_utc(),
+    };
+    let mut archive = Archive::new()?;
+    archive.add_text("meals.csv", &csv)?;
+    archive.add_json("manifest.json", &manifest)?;
+    let s3_key = format!("research/{export_id}.zip");
+    let size = archive.upload(state.storage.as_ref(), &s3_key).await?;
+    repo::complete_export(
+        &state.db,
+        export_id,
+        &CompletedExport {
+            s3_key: &s3_key,
+            size_bytes: size,
+            meal_count: manifest.meal_count as i32,
+            user_count: manifest.user_count as i32,
+            suppressed_count: manifest.suppressed_count as i32,
+        },
+    )
+    .await?;
+    info!(
+        export_id = %export_id,
+        meals = manifest.meal_count,
+        suppressed = manifest.suppressed_count,
+        size,
+        "research export ready"
+    );
+    Ok(())
+}
+
+/// Job failure hook: back to pending while retries remain, failed otherwise.
+pub async fn record_export_failure(
+    state: &AppState,
+    export_id: Uuid,
+    retrying: bool,
+) -> anyhow::Result<()> {
+    let status = if retrying {
+        DataExportStatus::Pending
+    } else {
+        DataExportStatus::Failed
+    };
+    repo::set_export_status(&state.db, export_id, status).await
+}
@@ -0,0 +1,7 @@
+//! Meal retention: meals older than `MEAL_ARCHIVE_AFTER_DAYS` move from
+//! `meals` to `meal_archive`, keeping the hot table, and every query that
+//! lists, summarizes or scores meals, down to the recent ones active users
+//! look at. Archived meals leave the API but stay in the user's data export.
+
+pub mod repo;
+pub mod services;
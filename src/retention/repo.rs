@@ -0,0 +1,102 @@
+use serde::Serialize;
+use serde_json::Value;
+use sqlx::{FromRow, PgPool};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+/// A row of `meal_archive`, for `archived_meals.json` in the data export.
+#[derive(Debug, Serialize, FromRow)]
+pub struct ArchivedMeal {
+    pub id: Uuid,
+    pub household_id: Option<Uuid>,
+    pub title: Option<String>,
+    pub notes: Option<String>,
+    pub meal_type: Option<String>,
+    pub tags: Vec<String>,
+    pub status: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    pub nutrition: Option<Value>,
+    pub items: Value,
+    /// Photo metadata; the archive doesn't include the images themselves.
+    pub photos: Value,
+    #[serde(with = "time::serde::rfc3339")]
+    pub archived_at: OffsetDateTime,
+}
+
+/// Moves up to `limit` meals created more than `after_days` ago, oldest
+/// first, with their nutrition, items and photo rows, into `meal_archive`.
+/// Nothing is copied twice: the meals are deleted by the same statement.
+/// Returns how many were moved.
+pub async fn archive_batch(db: &PgPool, after_days: i32, limit: i64) -> anyhow::Result<u64> {
+    let moved = sqlx::query_scalar::<_, i64>(
+        r#"
+        WITH batch AS (
+            SELECT id FROM meals
+            WHERE created_at < NOW() - make_interval(days => $1)
+            ORDER BY created_at
+            LIMIT $2
+            FOR UPDATE SKIP LOCKED
+        ), archived AS (
+            INSERT INTO meal_archive (id, user_id, org_id, household_id, title, notes,
+                                      meal_type, tags, status, created_at,
+                                      nutrition, items, photos, photo_keys)
+            SELECT m.id, m.user_id, m.org_id, m.household_id, m.title, m.notes,
+                   m.meal_type, m.tags, m.status, m.created_at,
+                   (SELECT to_jsonb(n) - 'meal_id' FROM meal_nutrition n WHERE n.meal_id = m.id),
+                   COALESCE((SELECT jsonb_agg(to_jsonb(i) - 'meal_id' ORDER BY i.created_at, i.id)
+                             FROM meal_items i WHERE i.meal_id = m.id), '[]'),
+                   COALESCE((SELECT jsonb_agg(to_jsonb(p) - 'meal_id' - 'user_id'
+                                              ORDER BY p.created_at, p.id)
+                             FROM photos p WHERE p.meal_id = m.id), '[]'),
+                   ARRAY(SELECT k FROM photos p,
+                         unnest(ARRAY[p.s3_key, p.original_s3_key]) AS u(k)
+                         WHERE p.meal_id = m.id AND k IS NOT NULL)
+            FROM meals m
+            JOIN batch USING (id)
+            ON CONFLICT (id) DO NOTHING
+            RETURNING id
+        ), detached AS (
+            -- Otherwise the orphan cleanup would delete their objects.
+            DELETE FROM photos WHERE meal_id IN (SELECT id FROM archived)
+        ), deleted AS (
+            DELETE FROM meals WHERE id IN (SELECT id FROM archived)
+            RETURNING id
+        )
+        SELECT COUNT(*) FROM deleted
+        "#,
+    )
+    .bind(after_days)
+    .bind(limit)
+    .fetch_one(db)
+    .await?;
+    Ok(moved as u64)
+}
+
+/// Whether an archived meal still refers to the stored object `key`.
+pub async fn key_archived(db: &PgPool, key: &str) -> anyhow::Result<bool> {
+    let archived = sqlx::query_scalar::<_, bool>(
+        r#"SELECT EXISTS (SELECT 1 FROM meal_archive WHERE photo_keys @> ARRAY[$1])"#,
+    )
+    .bind(key)
+    .fetch_one(db)
+    .await?;
+    Ok(archived)
+}
+
+/// The user's archived meals, oldest first.
+pub async fn list_for_user(db: &PgPool, user_id: Uuid) -> anyhow::Result<Vec<ArchivedMeal>> {
+    let meals = sqlx::query_as::<_, ArchivedMeal>(
+        r#"
+        SELECT id, household_id, title, notes, meal_type, tags, status, created_at,
+               nutrition, items, photos, archived_at
+        FROM meal_archive
+        WHERE user_id = $1
+        ORDER BY created_at, id
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(db)
+    .await?;
+    Ok(meals)
+}
@@ -0,0 +1,27 @@
+use tracing::info;
+
+use crate::{db::AppState, retention::repo};
+
+/// Meals moved per statement, so one batch never holds many row locks.
+const ARCHIVE_BATCH: i64 = 500;
+
+/// The `meal_archival` job: archives every meal past
+/// `MEAL_ARCHIVE_AFTER_DAYS`, in batches. Does nothing while that is 0.
+pub async fn archive_meals(state: &AppState) -> anyhow::Result<()> {
+    let after_days = state.config.tasks.meal_archive_after_days;
+    if after_days == 0 {
+        return Ok(());
+    }
+    let mut archived = 0;
+    loop {
+        let moved = repo::archive_batch(&state.db, after_days, ARCHIVE_BATCH).await?;
+        archived += moved;
+        if moved < ARCHIVE_BATCH as u64 {
+            break;
+        }
+    }
+    if archived > 0 {
+        info!(meals = archived, after_days, "archived old meals");
+    }
+    Ok(())
+}
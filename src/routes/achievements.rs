@@ -0,0 +1,25 @@
+use axum::{extract::State, routing::get, Json, Router};
+use tracing::{error, instrument};
+
+use crate::{
+    achievements::{dto::AchievementsResponse, services},
+    auth::jwt::AuthUser,
+    db::AppState,
+    error::ApiError,
+};
+
+pub fn achievement_routes() -> Router<AppState> {
+    Router::new().route("/me/achievements", get(get_achievements))
+}
+
+#[instrument(skip(state))]
+pub async fn get_achievements(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+) -> Result<Json<AchievementsResponse>, ApiError> {
+    let achievements = services::achievements(&state, user_id).await.map_err(|e| {
+        error!(error = %e, user_id = %user_id, "load achievements failed");
+        ApiError::internal(&e, "Failed to load achievements")
+    })?;
+    Ok(Json(achievements))
+}
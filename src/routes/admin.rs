@@ -0,0 +1,142 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    routing::{get, post, put},
+    Json, Router,
+};
+use tracing::{error, instrument};
+use uuid::Uuid;
+
+use crate::{
+    admin::{
+        dto::{AdminStats, AdminStatsQuery, MigrationsReport},
+        services,
+    },
+    ai_usage::{
+        dto::{AiUsageReport, BudgetResponse, SetBudgetRequest},
+        services::{self as ai_usage_services, BudgetError},
+    },
+    auth::jwt::AdminUser,
+    db::AppState,
+    error::ApiError,
+    research::{
+        dto::{CreateResearchExportRequest, ResearchExportResponse},
+        services::{self as research_services, ResearchError},
+    },
+};
+
+pub fn admin_routes() -> Router<AppState> {
+    Router::new()
+        .route("/admin/stats", get(get_admin_stats))
+        .route("/admin/migrations", get(get_admin_migrations))
+        .route("/admin/ai-usage", get(get_ai_usage))
+        .route("/admin/users/:id/ai-budget", put(set_ai_budget))
+        .route("/admin/research-exports", post(create_research_export))
+        .route("/admin/research-exports/:id", get(get_research_export))
+}
+
+/// Platform-wide usage for the internal dashboard; admins only.
+#[instrument(skip(state))]
+pub async fn get_admin_stats(
+    State(state): State<AppState>,
+    AdminUser(user_id): AdminUser,
+    Query(query): Query<AdminStatsQuery>,
+) -> Result<Json<AdminStats>, ApiError> {
+    let days = services::window_days(&query).map_err(ApiError::validation)?;
+    services::admin_stats(&state, days, None)
+        .await
+        .map(Json)
+        .map_err(|e| {
+            error!(error = %e, user_id = %user_id, "load admin stats failed");
+            ApiError::Internal("Failed to load admin stats".to_string())
+        })
+}
+
+/// Applied and pending schema migrations of the database, compared with the
+/// ones built into this binary; admins only.
+#[instrument(skip(state))]
+pub async fn get_admin_migrations(
+    State(state): State<AppState>,
+    AdminUser(user_id): AdminUser,
+) -> Result<Json<MigrationsReport>, ApiError> {
+    services::migrations(&state).await.map(Json).map_err(|e| {
+        error!(error = %e, user_id = %user_id, "load migrations failed");
+        ApiError::Internal("Failed to load migrations".to_string())
+    })
+}
+
+/// AI analysis calls, tokens and cost over a window of days, by model and
+/// for the costliest users; admins only.
+#[instrument(skip(state))]
+pub async fn get_ai_usage(
+    State(state): State<AppState>,
+    AdminUser(user_id): AdminUser,
+    Query(query): Query<AdminStatsQuery>,
+) -> Result<Json<AiUsageReport>, ApiError> {
+    let days = services::window_days(&query).map_err(ApiError::validation)?;
+    ai_usage_services::usage_report(&state, days)
+        .await
+        .map(Json)
+        .map_err(|e| {
+            error!(error = %e, user_id = %user_id, "load AI usage failed");
+            ApiError::internal(&e, "Failed to load AI usage")
+        })
+}
+
+#[instrument(skip(state, payload))]
+pub async fn set_ai_budget(
+    State(state): State<AppState>,
+    AdminUser(user_id): AdminUser,
+    Path(target): Path<Uuid>,
+    Json(payload): Json<SetBudgetRequest>,
+) -> Result<Json<BudgetResponse>, ApiError> {
+    let budget = payload.validated().map_err(ApiError::validation)?;
+    ai_usage_services::set_budget(&state, target, budget)
+        .await
+        .map(Json)
+        .map_err(|e| match e {
+            BudgetError::Other(source) => {
+                error!(error = %source, user_id = %user_id, "set AI budget failed");
+                ApiError::internal(&source, "Failed to set AI budget")
+            }
+            e => ApiError::from_status(e.status(), e.to_string()).with_code(e.code()),
+        })
+}
+
+fn research_error(e: ResearchError, user_id: Uuid) -> ApiError {
+    if let ResearchError::Other(source) = &e {
+        error!(error = %source, user_id = %user_id, "research export request failed");
+        return ApiError::internal(source, "Failed to load research export");
+    }
+    ApiError::from_status(e.status(), e.to_string()).with_code(e.code())
+}
+
+/// Queues an anonymized dataset of all meals for model training; admins
+/// only.
+#[instrument(skip(state, payload))]
+pub async fn create_research_export(
+    State(state): State<AppState>,
+    AdminUser(user_id): AdminUser,
+    payload: Option<Json<CreateResearchExportRequest>>,
+) -> Result<(StatusCode, Json<ResearchExportResponse>), ApiError> {
+    let input = payload.map(|Json(p)| p).unwrap_or_default();
+    let k = input
+        .k(state.config.research.min_k)
+        .map_err(ApiError::validation)?;
+    research_services::request_export(&state, user_id, k)
+        .await
+        .map(|export| (StatusCode::ACCEPTED, Json(export)))
+        .map_err(|e| research_error(e, user_id))
+}
+
+#[instrument(skip(state))]
+pub async fn get_research_export(
+    State(state): State<AppState>,
+    AdminUser(user_id): AdminUser,
+    Path(export_id): Path<Uuid>,
+) -> Result<Json<ResearchExportResponse>, ApiError> {
+    research_services::get_export(&state, export_id)
+        .await
+        .map(Json)
+        .map_err(|e| research_error(e, user_id))
+}
@@ -0,0 +1,43 @@
+use axum::{extract::State, http::StatusCode, routing::post, Json, Router};
+use time::OffsetDateTime;
+use tracing::{error, instrument};
+
+use crate::{
+    analytics::{
+        dto::{AnalyticsBatchRequest, AnalyticsBatchResponse},
+        services::{self, AnalyticsError},
+    },
+    auth::jwt::AuthUser,
+    db::AppState,
+    error::ApiError,
+};
+
+pub fn analytics_routes() -> Router<AppState> {
+    Router::new().route("/analytics/events", post(record_events))
+}
+
+/// Takes a batch of app analytics events; answers `202` even when the
+/// user's preferences discard them.
+#[instrument(skip(state, payload))]
+pub async fn record_events(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Json(payload): Json<AnalyticsBatchRequest>,
+) -> Result<(StatusCode, Json<AnalyticsBatchResponse>), ApiError> {
+    let received = payload.events.len();
+    let events = payload
+        .normalized(user_id, OffsetDateTime::now_utc())
+        .map_err(ApiError::validation)?;
+    services::record_events(&state, user_id, received, events)
+        .await
+        .map(|response| (StatusCode::ACCEPTED, Json(response)))
+        .map_err(|e| {
+            error!(error = %e, user_id = %user_id, "record analytics events failed");
+            match e {
+                AnalyticsError::Other(source) => {
+                    ApiError::internal(&source, "Failed to record analytics events")
+                }
+                e => ApiError::from_status(e.status(), e.to_string()).with_code(e.code()),
+            }
+        })
+}
@@ -5,51 +5,32 @@ use axum::{
 };
 use lazy_static::lazy_static;
 use regex::Regex;
-use serde::{Deserialize, Serialize};
 use tracing::{error, info, instrument, warn};
 
 use crate::{
-    auth::{jwt::JwtKeys, password},
-    db::{AppState, User},
+    auth::{
+        dto::{AuthResponse, LoginRequest, PublicUser, RefreshRequest, RegisterRequest},
+        jwt::JwtKeys,
+        password, repo as users_repo,
+    },
+    db::{AppState, RepoError},
+    error::{ApiError, ErrorCode},
+    events::{repo as events_repo, DomainEvent},
 };
 
-#[derive(Debug, Deserialize)]
-pub struct RegisterRequest {
-    pub email: String,
-    pub password: String,
-}
-
-#[derive(Debug, Deserialize)]
-pub struct LoginRequest {
-    pub email: String,
-    pub password: String,
-}
-
-#[derive(Debug, Deserialize)]
-pub struct RefreshRequest {
-    pub refresh_token: String,
-}
-
-#[derive(Debug, Serialize)]
-pub struct AuthResponse {
-    pub access_token: String,
-    pub refresh_token: String,
-    pub user: PublicUser,
-}
-
-#[derive(Debug, Serialize)]
-pub struct PublicUser {
-    pub id: uuid::Uuid,
-    pub email: String,
-}
-
-fn is_valid_email(email: &str) -> bool {
+pub(crate) fn is_valid_email(email: &str) -> bool {
     lazy_static! {
         static ref EMAIL_RE: Regex = Regex::new(r"^[^@\s]+@[^@\s]+\.[^@\s]+$").unwrap();
     }
     EMAIL_RE.is_match(email)
 }
 
+/// Same answer for unknown emails and wrong passwords.
+fn invalid_credentials() -> ApiError {
+    ApiError::Unauthorized("Invalid credentials".into())
+        .with_code(ErrorCode::AuthInvalidCredentials)
+}
+
 pub fn auth_routes() -> Router<AppState> {
     Router::new()
         .route("/auth/register", post(register))
@@ -57,48 +38,67 @@ pub fn auth_routes() -> Router<AppState> {
         .route("/auth/refresh", post(refresh))
 }
 
+#[utoipa::path(
+    post,
+    path = "/auth/register",
+    tag = "auth",
+    request_body = RegisterRequest,
+    responses(
+        (status = 200, body = AuthResponse),
+        (status = 400, description = "Invalid email or password too short", body = ErrorResponse),
+        (status = 409, description = "Email already registered", body = ErrorResponse),
+    )
+)]
 #[instrument(skip(state, payload))]
 pub async fn register(
     State(state): State<AppState>,
     Json(mut payload): Json<RegisterRequest>,
-) -> Result<Json<AuthResponse>, (axum::http::StatusCode, String)> {
+) -> Result<Json<AuthResponse>, ApiError> {
     payload.email = payload.email.trim().to_lowercase();
 
     if !is_valid_email(&payload.email) {
         warn!(email = %payload.email, "invalid email");
-        return Err((axum::http::StatusCode::BAD_REQUEST, "Invalid email".into()));
+        return Err(
+            ApiError::BadRequest("Invalid email".into()).with_code(ErrorCode::AuthInvalidEmail)
+        );
     }
 
-    if payload.password.len() < 8 {
+    if payload.password.len() < password::MIN_PASSWORD_LEN {
         warn!("password too short");
-        return Err((
-            axum::http::StatusCode::BAD_REQUEST,
-            "Password too short".into(),
-        ));
-    }
-
-    // Ensure email is not taken
-    if let Ok(Some(_)) = User::find_by_email(&state.db, &payload.email).await {
-        warn!(email = %payload.email, "email already registered");
-        return Err((
-            axum::http::StatusCode::CONFLICT,
-            "Email already registered".into(),
-        ));
+        return Err(ApiError::BadRequest("Password too short".into())
+            .with_code(ErrorCode::AuthPasswordTooShort));
     }
 
     let hash = match password::hash_password(&payload.password) {
         Ok(h) => h,
         Err(e) => {
             error!(error = %e, "hash_password failed");
-            return Err((axum::http::StatusCode::INTERNAL_SERVER_ERROR, e.to_string()));
+            return Err(ApiError::Internal("Failed to register".into()));
         }
     };
 
-    let user = match User::create(&state.db, &payload.email, &hash).await {
-        Ok(u) => u,
+    let created = async {
+        let mut tx = state.db.begin().await?;
+        let user = match users_repo::create(&mut *tx, &payload.email, &hash).await {
+            Ok(user) => user,
+            // Taken, possibly by a registration running concurrently.
+            Err(RepoError::Conflict(_)) => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        events_repo::record(&mut *tx, user.id, &DomainEvent::UserRegistered).await?;
+        tx.commit().await?;
+        Ok::<_, anyhow::Error>(Some(user))
+    };
+    let user = match created.await {
+        Ok(Some(u)) => u,
+        Ok(None) => {
+            warn!(email = %payload.email, "email already registered");
+            return Err(ApiError::Conflict("Email already registered".into())
+                .with_code(ErrorCode::AuthEmailTaken));
+        }
         Err(e) => {
             error!(error = %e, "create user failed");
-            return Err((axum::http::StatusCode::INTERNAL_SERVER_ERROR, e.to_string()));
+            return Err(ApiError::Internal("Failed to register".into()));
         }
     };
 
@@ -107,14 +107,14 @@ pub async fn register(
         Ok(t) => t,
         Err(e) => {
             error!(error = %e, "jwt sign access failed");
-            return Err((axum::http::StatusCode::INTERNAL_SERVER_ERROR, e.to_string()));
+            return Err(ApiError::Internal("Failed to issue tokens".into()));
         }
     };
     let refresh_token = match keys.sign_refresh(user.id) {
         Ok(t) => t,
         Err(e) => {
             error!(error = %e, "jwt sign refresh failed");
-            return Err((axum::http::StatusCode::INTERNAL_SERVER_ERROR, e.to_string()));
+            return Err(ApiError::Internal("Failed to issue tokens".into()));
         }
     };
 
@@ -129,30 +129,40 @@ pub async fn register(
     }))
 }
 
+#[utoipa::path(
+    post,
+    path = "/auth/login",
+    tag = "auth",
+    request_body = LoginRequest,
+    responses(
+        (status = 200, body = AuthResponse),
+        (status = 400, description = "Invalid email", body = ErrorResponse),
+        (status = 401, description = "Invalid credentials", body = ErrorResponse),
+    )
+)]
 #[instrument(skip(state, payload))]
 pub async fn login(
     State(state): State<AppState>,
     Json(mut payload): Json<LoginRequest>,
-) -> Result<Json<AuthResponse>, (axum::http::StatusCode, String)> {
+) -> Result<Json<AuthResponse>, ApiError> {
     payload.email = payload.email.trim().to_lowercase();
 
     if !is_valid_email(&payload.email) {
         warn!(email = %payload.email, "invalid email");
-        return Err((axum::http::StatusCode::BAD_REQUEST, "Invalid email".into()));
+        return Err(
+            ApiError::BadRequest("Invalid email".into()).with_code(ErrorCode::AuthInvalidEmail)
+        );
     }
 
-    let user = match User::find_by_email(&state.db, &payload.email).await {
+    let user = match users_repo::find_by_email(&state.db, &payload.email).await {
         Ok(Some(u)) => u,
         Ok(None) => {
             warn!(email = %payload.email, "login unknown email");
-            return Err((
-                axum::http::StatusCode::UNAUTHORIZED,
-                "Invalid credentials".into(),
-            ));
+            return Err(invalid_credentials());
         }
         Err(e) => {
             error!(error = %e, "find_by_email failed");
-            return Err((axum::http::StatusCode::INTERNAL_SERVER_ERROR, e.to_string()));
+            return Err(ApiError::Internal("Failed to log in".into()));
         }
     };
 
@@ -160,16 +170,13 @@ pub async fn login(
         Ok(v) => v,
         Err(e) => {
             error!(error = %e, "verify_password failed");
-            return Err((axum::http::StatusCode::INTERNAL_SERVER_ERROR, e.to_string()));
+            return Err(ApiError::Internal("Failed to log in".into()));
         }
     };
 
     if !ok {
         warn!(email = %payload.email, user_id = %user.id, "login invalid password");
-        return Err((
-            axum::http::StatusCode::UNAUTHORIZED,
-            "Invalid credentials".into(),
-        ));
+        return Err(invalid_credentials());
     }
 
     let keys = JwtKeys::from_ref(&state);
@@ -177,14 +184,14 @@ pub async fn login(
         Ok(t) => t,
         Err(e) => {
             error!(error = %e, "jwt sign access failed");
-            return Err((axum::http::StatusCode::INTERNAL_SERVER_ERROR, e.to_string()));
+            return Err(ApiError::Internal("Failed to issue tokens".into()));
         }
     };
     let refresh_token = match keys.sign_refresh(user.id) {
         Ok(t) => t,
         Err(e) => {
             error!(error = %e, "jwt sign refresh failed");
-            return Err((axum::http::StatusCode::INTERNAL_SERVER_ERROR, e.to_string()));
+            return Err(ApiError::Internal("Failed to issue tokens".into()));
         }
     };
 
@@ -199,37 +206,47 @@ pub async fn login(
     }))
 }
 
+/// Exchanges a refresh token for a new token pair.
+#[utoipa::path(
+    post,
+    path = "/auth/refresh",
+    tag = "auth",
+    request_body = RefreshRequest,
+    responses(
+        (status = 200, body = AuthResponse),
+        (status = 401, description = "Invalid or expired refresh token", body = ErrorResponse),
+    )
+)]
 #[instrument(skip(state, payload))]
 pub async fn refresh(
     State(state): State<AppState>,
     Json(payload): Json<RefreshRequest>,
-) -> Result<Json<AuthResponse>, (axum::http::StatusCode, String)> {
+) -> Result<Json<AuthResponse>, ApiError> {
     let keys = JwtKeys::from_ref(&state);
-    let claims = keys
-        .verify_refresh(&payload.refresh_token)
-        .map_err(|e| (axum::http::StatusCode::UNAUTHORIZED, format!("{}", e)))?;
+    let claims = keys.verify_refresh(&payload.refresh_token).map_err(|e| {
+        warn!(error = %e, "invalid refresh token");
+        ApiError::Unauthorized("Invalid or expired refresh token".into())
+            .with_code(ErrorCode::AuthInvalidRefreshToken)
+    })?;
 
     // Issue new pair
-    let access_token = keys
-        .sign_access(claims.sub)
-        .map_err(|e| (axum::http::StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
-    let refresh_token = keys
-        .sign_refresh(claims.sub)
-        .map_err(|e| (axum::http::StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let sign_failed = |e: anyhow::Error| {
+        error!(error = %e, user_id = %claims.sub, "jwt sign failed");
+        ApiError::Internal("Failed to issue tokens".into())
+    };
+    let access_token = keys.sign_access(claims.sub).map_err(sign_failed)?;
+    let refresh_token = keys.sign_refresh(claims.sub).map_err(sign_failed)?;
 
     // Load public user
-    let user = sqlx::query_as::<_, User>(
-        r#"SELECT id, email, password_hash, created_at FROM users WHERE id = $1"#,
-    )
-    .bind(claims.sub)
-    .fetch_one(&state.db)
-    .await
-    .map_err(|_| {
-        (
-            axum::http::StatusCode::UNAUTHORIZED,
-            "User not found".into(),
-        )
-    })?;
+    let user = users_repo::find_by_id(&state.db, claims.sub)
+        .await
+        .map_err(|e| {
+            error!(error = %e, user_id = %claims.sub, "load user failed");
+            ApiError::Internal("Failed to issue tokens".into())
+        })?
+        .ok_or_else(|| {
+            ApiError::Unauthorized("User not found".into()).with_code(ErrorCode::AuthUserNotFound)
+        })?;
     Ok(Json(AuthResponse {
         access_token,
         refresh_token,
@@ -0,0 +1,67 @@
+use axum::{
+    body::Bytes,
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    routing::{get, post},
+    Json, Router,
+};
+use tracing::{error, instrument, warn};
+
+use crate::{
+    auth::jwt::AuthUser,
+    billing::{
+        dto::PlanResponse,
+        services::{self, QuotaError, StripeWebhookError},
+        stripe::SIGNATURE_HEADER,
+    },
+    db::AppState,
+    error::ApiError,
+};
+
+pub fn billing_routes() -> Router<AppState> {
+    Router::new()
+        .route("/me/plan", get(get_plan))
+        .route("/webhooks/stripe", post(stripe_webhook))
+}
+
+/// The user's plan and this month's photo analyses.
+#[instrument(skip(state))]
+pub async fn get_plan(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+) -> Result<Json<PlanResponse>, ApiError> {
+    services::get_plan(&state, user_id)
+        .await
+        .map(Json)
+        .map_err(|e| match e {
+            QuotaError::Other(source) => {
+                error!(error = %source, user_id = %user_id, "load plan failed");
+                ApiError::internal(&source, "Failed to load plan")
+            }
+            e => ApiError::from_status(e.status(), e.to_string()).with_code(e.code()),
+        })
+}
+
+/// Stripe's webhook endpoint; authenticated by `Stripe-Signature` rather
+/// than a token. Non-2xx answers make Stripe retry the delivery.
+#[instrument(skip(state, headers, body))]
+pub async fn stripe_webhook(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<StatusCode, ApiError> {
+    let signature = headers.get(SIGNATURE_HEADER).and_then(|v| v.to_str().ok());
+    services::handle_stripe_webhook(&state, signature, &body)
+        .await
+        .map(|()| StatusCode::OK)
+        .map_err(|e| match e {
+            StripeWebhookError::Other(source) => {
+                error!(error = %source, "process Stripe event failed");
+                ApiError::internal(&source, "Failed to process Stripe event")
+            }
+            e => {
+                warn!(error = %e, "Stripe webhook rejected");
+                ApiError::from_status(e.status(), e.to_string()).with_code(e.code())
+            }
+        })
+}
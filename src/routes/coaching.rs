@@ -0,0 +1,135 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    routing::{delete, get, post},
+    Json, Router,
+};
+use tracing::{error, instrument};
+use uuid::Uuid;
+
+use crate::{
+    auth::jwt::AuthUser,
+    coaching::{
+        dto::{Coach, CoachClient, InviteClientRequest},
+        services::{self, CoachingError},
+    },
+    db::AppState,
+    error::ApiError,
+    meals::dto::{ListMealsQuery, MealDetails, MealResponse},
+};
+
+pub fn coaching_routes() -> Router<AppState> {
+    Router::new()
+        .route("/coach/clients", get(list_clients).post(invite_client))
+        .route("/coach/clients/:id", delete(remove_client))
+        .route("/coach/clients/:id/meals", get(list_client_meals))
+        .route("/coach/clients/:id/meals/:meal_id", get(get_client_meal))
+        .route("/me/coaches", get(list_coaches))
+        .route("/me/coaches/:coach_id", delete(remove_coach))
+        .route("/me/coaches/:coach_id/accept", post(accept_coach))
+}
+
+fn coaching_error(e: CoachingError, user_id: Uuid) -> ApiError {
+    if let CoachingError::Other(source) = &e {
+        error!(error = %source, user_id = %user_id, "coaching request failed");
+        return ApiError::internal(source, "Failed to access coaching");
+    }
+    ApiError::from_status(e.status(), e.to_string()).with_code(e.code())
+}
+
+#[instrument(skip(state, payload))]
+pub async fn invite_client(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Json(payload): Json<InviteClientRequest>,
+) -> Result<(StatusCode, Json<CoachClient>), ApiError> {
+    services::invite_client(&state, user_id, payload)
+        .await
+        .map(|client| (StatusCode::CREATED, Json(client)))
+        .map_err(|e| coaching_error(e, user_id))
+}
+
+#[instrument(skip(state))]
+pub async fn list_clients(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+) -> Result<Json<Vec<CoachClient>>, ApiError> {
+    services::list_clients(&state, user_id)
+        .await
+        .map(Json)
+        .map_err(|e| coaching_error(e, user_id))
+}
+
+#[instrument(skip(state))]
+pub async fn remove_client(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path(client_id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    services::remove_client(&state, user_id, client_id)
+        .await
+        .map(|()| StatusCode::NO_CONTENT)
+        .map_err(|e| coaching_error(e, user_id))
+}
+
+/// Read-only view of a client's meals, same shape as `GET /meals`.
+#[instrument(skip(state))]
+pub async fn list_client_meals(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path(client_id): Path<Uuid>,
+    Query(query): Query<ListMealsQuery>,
+) -> Result<Json<Vec<MealResponse>>, ApiError> {
+    services::list_client_meals(&state, user_id, client_id, &query)
+        .await
+        .map(Json)
+        .map_err(|e| coaching_error(e, user_id))
+}
+
+#[instrument(skip(state))]
+pub async fn get_client_meal(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path((client_id, meal_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<MealDetails>, ApiError> {
+    services::get_client_meal(&state, user_id, client_id, meal_id)
+        .await
+        .map(Json)
+        .map_err(|e| coaching_error(e, user_id))
+}
+
+/// Coaches of the current user, including invites awaiting consent.
+#[instrument(skip(state))]
+pub async fn list_coaches(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+) -> Result<Json<Vec<Coach>>, ApiError> {
+    services::list_coaches(&state, user_id)
+        .await
+        .map(Json)
+        .map_err(|e| coaching_error(e, user_id))
+}
+
+#[instrument(skip(state))]
+pub async fn accept_coach(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path(coach_id): Path<Uuid>,
+) -> Result<Json<Coach>, ApiError> {
+    services::accept_coach(&state, user_id, coach_id)
+        .await
+        .map(Json)
+        .map_err(|e| coaching_error(e, user_id))
+}
+
+#[instrument(skip(state))]
+pub async fn remove_coach(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path(coach_id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    services::remove_coach(&state, user_id, coach_id)
+        .await
+        .map(|()| StatusCode::NO_CONTENT)
+        .map_err(|e| coaching_error(e, user_id))
+}
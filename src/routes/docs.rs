@@ -0,0 +1,149 @@
+use axum::{
+    http::header::CONTENT_TYPE,
+    response::{Html, IntoResponse},
+    routing::get,
+    Json, Router,
+};
+use utoipa::{
+    openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme},
+    Modify, OpenApi,
+};
+
+use crate::{
+    auth::dto::{AuthResponse, LoginRequest, PublicUser, RefreshRequest, RegisterRequest},
+    db::AppState,
+    error::ErrorResponse,
+    goals::dto::{GoalProgress, NutrientProgress},
+    images::dto::{ImageInput, PresignedPhoto},
+    meals::dto::{
+        BulkItemResult, BulkMealRequest, BulkMealResponse, BulkOperation, CreatedMealRequest,
+        ManualNutritionRequest, MealDetails, MealNutrition, MealResponse, MealStatus,
+        MealStatusResponse, MealType, NutritionSnippet, NutritionSource, QuickAdd,
+    },
+    photos::dto::PhotoMetadata,
+    routes::{auth, meals},
+};
+
+/// Generated from the handler annotations and the DTOs themselves, so the
+/// document can't drift from what the API actually accepts.
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "MealMind API"),
+    paths(
+        auth::register,
+        auth::login,
+        auth::refresh,
+        meals::list_meals,
+        meals::create_meal,
+        meals::create_meal_multipart,
+        meals::bulk_meals,
+        meals::get_meal,
+        meals::get_meal_status,
+        meals::put_meal_nutrition,
+        meals::delete_meal_nutrition,
+        meals::list_meal_photos,
+        meals::delete_meal_photo,
+    ),
+    components(schemas(
+        ErrorResponse,
+        RegisterRequest,
+        LoginRequest,
+        RefreshRequest,
+        AuthResponse,
+        PublicUser,
+        CreatedMealRequest,
+        QuickAdd,
+        ImageInput,
+        meals::MealForm,
+        MealType,
+        MealStatus,
+        MealResponse,
+        MealDetails,
+        MealStatusResponse,
+        MealNutrition,
+        NutritionSnippet,
+        NutritionSource,
+        ManualNutritionRequest,
+        GoalProgress,
+        NutrientProgress,
+        PresignedPhoto,
+        PhotoMetadata,
+        BulkMealRequest,
+        BulkOperation,
+        BulkMealResponse,
+        BulkItemResult,
+    )),
+    modifiers(&BearerAuth),
+    tags(
+        (name = "auth", description = "Registration and tokens"),
+        (name = "meals", description = "Meals, their nutrition and photos"),
+    )
+)]
+pub struct ApiDoc;
+
+struct BearerAuth;
+
+impl Modify for BearerAuth {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer_auth",
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .bearer_format("JWT")
+                    .build(),
+            ),
+        );
+    }
+}
+
+/// Swagger UI assets come from a CDN so the build doesn't have to download
+/// and embed them. The page has no inline script, so its
+/// `Content-Security-Policy` doesn't need `'unsafe-inline'` for scripts.
+const SWAGGER_UI: &str = r##"<!doctype html>
+<html lang="en">
+<head>
+  <meta charset="utf-8">
+  <title>MealMind API</title>
+  <link rel="stylesheet" href="https://unpkg.com/swagger-ui-dist@5/swagger-ui.css">
+</head>
+<body>
+  <div id="swagger-ui"></div>
+  <script src="https://unpkg.com/swagger-ui-dist@5/swagger-ui-bundle.js"></script>
+  <script src="/api/v1/docs/init.js"></script>
+</body>
+</html>
+"##;
+
+const SWAGGER_INIT: &str = r##"window.ui = SwaggerUIBundle({ url: "/api/v1/openapi.json", dom_id: "#swagger-ui" });
+"##;
+
+pub fn docs_routes() -> Router<AppState> {
+    Router::new()
+        .route("/api/v1/openapi.json", get(openapi_json))
+        .route("/api/v1/docs", get(|| async { Html(SWAGGER_UI) }))
+        .route(
+            "/api/v1/docs/init.js",
+            get(|| async { ([(CONTENT_TYPE, "text/javascript")], SWAGGER_INIT).into_response() }),
+        )
+}
+
+async fn openapi_json() -> Json<utoipa::openapi::OpenApi> {
+    Json(ApiDoc::openapi())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn document_covers_auth_and_meals() {
+        let doc = serde_json::to_value(ApiDoc::openapi()).unwrap();
+        assert!(doc["paths"]["/auth/login"]["post"].is_object());
+        assert!(doc["paths"]["/meals/{id}"]["get"].is_object());
+        let meal = &doc["components"]["schemas"]["MealDetails"]["properties"];
+        assert!(meal["nutrition"].is_object());
+        assert!(doc["components"]["securitySchemes"]["bearer_auth"].is_object());
+    }
+}
@@ -0,0 +1,50 @@
+use std::{convert::Infallible, time::Duration};
+
+use axum::{
+    extract::State,
+    response::sse::{Event, KeepAlive, Sse},
+    routing::get,
+    Router,
+};
+use futures::{stream, Stream};
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{instrument, warn};
+
+use crate::{auth::jwt::AuthUser, db::AppState};
+
+pub fn event_routes() -> Router<AppState> {
+    Router::new().route("/meals/events", get(meal_events))
+}
+
+/// Streams the caller's meal events as Server-Sent Events. A `lagged` event
+/// tells the client it missed updates and should refetch.
+#[instrument(skip(state))]
+pub async fn meal_events(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let rx = state.events.subscribe();
+    let events = stream::unfold(rx, move |mut rx| async move {
+        loop {
+            match rx.recv().await {
+                Ok(msg) if msg.user_id == user_id => {
+                    let event = Event::default()
+                        .event(msg.event.name())
+                        .json_data(&msg.event)
+                        .unwrap_or_else(|_| Event::default().event(msg.event.name()));
+                    return Some((Ok(event), rx));
+                }
+                Ok(_) => continue,
+                Err(RecvError::Lagged(missed)) => {
+                    warn!(user_id = %user_id, missed, "event stream lagged");
+                    return Some((
+                        Ok(Event::default().event("lagged").data(missed.to_string())),
+                        rx,
+                    ));
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    });
+    Sse::new(events).keep_alive(KeepAlive::new().interval(Duration::from_secs(15)))
+}
@@ -0,0 +1,100 @@
+use axum::{
+    body::Body,
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use futures::TryStreamExt;
+use time::OffsetDateTime;
+use tracing::{error, instrument};
+use uuid::Uuid;
+
+use crate::{
+    auth::jwt::AuthUser,
+    db::AppState,
+    error::{ApiError, ErrorCode},
+    export::{
+        dto::{DataExportResponse, ExportQuery},
+        services,
+    },
+};
+
+pub fn export_routes() -> Router<AppState> {
+    Router::new()
+        .route("/export/meals", get(export_meals))
+        .route("/me/export", post(request_data_export))
+        .route("/me/export/:id", get(get_data_export))
+}
+
+fn export_failed(e: anyhow::Error, user_id: Uuid, context: &str) -> ApiError {
+    error!(error = %e, user_id = %user_id, "{}", context);
+    ApiError::internal(&e, "Failed to load data export")
+}
+
+/// Streams the user's meals with nutrition as a CSV or JSON download.
+#[instrument(skip(state))]
+pub async fn export_meals(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Query(query): Query<ExportQuery>,
+) -> Result<Response, ApiError> {
+    if let (Some(from), Some(to)) = (query.from, query.to) {
+        if from >= to {
+            return Err(ApiError::validation("from must be before to"));
+        }
+    }
+    let format = query.format;
+    let filename = format!(
+        "mealmind-meals-{}.{}",
+        OffsetDateTime::now_utc().date(),
+        format.extension()
+    );
+    // Headers are already sent once streaming starts; failures can only be
+    // logged and end the body early.
+    let body = services::meals_export(state.db.clone(), user_id, query).inspect_err(move |e| {
+        error!(error = %e, user_id = %user_id, "meal export failed mid-stream");
+    });
+    Ok((
+        [
+            (header::CONTENT_TYPE, format.content_type().to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{filename}\""),
+            ),
+            (header::CACHE_CONTROL, "no-store".to_string()),
+        ],
+        Body::from_stream(body),
+    )
+        .into_response())
+}
+
+/// Queues a ZIP of all the user's data; poll `GET /me/export/:id` for the
+/// download link.
+#[instrument(skip(state))]
+pub async fn request_data_export(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+) -> Result<(StatusCode, Json<DataExportResponse>), ApiError> {
+    let export = services::request_data_export(&state, user_id)
+        .await
+        .map_err(|e| export_failed(e, user_id, "request data export failed"))?;
+    Ok((StatusCode::ACCEPTED, Json(export)))
+}
+
+#[instrument(skip(state))]
+pub async fn get_data_export(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path(export_id): Path<Uuid>,
+) -> Result<Json<DataExportResponse>, ApiError> {
+    match services::get_data_export(&state, user_id, export_id).await {
+        Ok(Some(export)) => Ok(Json(export)),
+        Ok(None) => {
+            Err(ApiError::NotFound("Export not found".to_string())
+                .with_code(ErrorCode::ExportNotFound))
+        }
+        Err(e) => Err(export_failed(e, user_id, "get data export failed")),
+    }
+}
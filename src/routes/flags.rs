@@ -0,0 +1,19 @@
+use std::collections::BTreeMap;
+
+use axum::{extract::State, routing::get, Json, Router};
+use tracing::instrument;
+
+use crate::{auth::jwt::AuthUser, db::AppState};
+
+pub fn flag_routes() -> Router<AppState> {
+    Router::new().route("/me/flags", get(get_flags))
+}
+
+/// Every feature flag as it applies to the current user.
+#[instrument(skip(state))]
+pub async fn get_flags(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+) -> Json<BTreeMap<String, bool>> {
+    Json(state.flags.for_user(user_id))
+}
@@ -0,0 +1,183 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    routing::get,
+    Json, Router,
+};
+use tracing::{error, instrument};
+use uuid::Uuid;
+
+use crate::{
+    auth::jwt::AuthUser,
+    db::AppState,
+    error::{ApiError, ErrorCode},
+    foods::{
+        dto::{
+            is_valid_gtin, CustomFood, CustomFoodRequest, Food, FoodResponse, FoodSearchQuery,
+            FoodSearchResponse,
+        },
+        services::{self, SourceUnavailable},
+    },
+    preferences::services as preferences,
+    units::{Units, UnitsQuery},
+};
+
+pub fn food_routes() -> Router<AppState> {
+    Router::new()
+        .route("/foods/search", get(search_foods))
+        .route("/foods/barcode/:ean", get(get_food_by_barcode))
+        .route("/foods", get(list_custom_foods).post(create_custom_food))
+        .route(
+            "/foods/:id",
+            get(get_custom_food)
+                .put(replace_custom_food)
+                .delete(delete_custom_food),
+        )
+}
+
+fn custom_food_error(e: &anyhow::Error, user_id: Uuid, msg: &str) -> ApiError {
+    error!(error = %e, user_id = %user_id, "{}", msg);
+    ApiError::internal(e, "Failed to access custom foods")
+}
+
+async fn units(
+    state: &AppState,
+    user_id: Uuid,
+    requested: Option<Units>,
+) -> Result<Units, ApiError> {
+    preferences::units(state, user_id, requested)
+        .await
+        .map_err(|e| custom_food_error(&e, user_id, "load unit preference failed"))
+}
+
+fn food_not_found() -> ApiError {
+    ApiError::NotFound("Food not found".to_string()).with_code(ErrorCode::FoodNotFound)
+}
+
+#[instrument(skip(state, payload))]
+pub async fn create_custom_food(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Query(query): Query<UnitsQuery>,
+    Json(payload): Json<CustomFoodRequest>,
+) -> Result<(StatusCode, Json<FoodResponse<CustomFood>>), ApiError> {
+    let input = payload.normalized().map_err(ApiError::validation)?;
+    let units = units(&state, user_id, query.units).await?;
+    let food = services::create_custom_food(&state, user_id, &input)
+        .await
+        .map_err(|e| custom_food_error(&e, user_id, "create custom food failed"))?;
+    Ok((StatusCode::CREATED, Json(FoodResponse::new(food, units))))
+}
+
+#[instrument(skip(state))]
+pub async fn list_custom_foods(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Query(query): Query<UnitsQuery>,
+) -> Result<Json<Vec<FoodResponse<CustomFood>>>, ApiError> {
+    let units = units(&state, user_id, query.units).await?;
+    let foods = services::list_custom_foods(&state, user_id)
+        .await
+        .map_err(|e| custom_food_error(&e, user_id, "list custom foods failed"))?;
+    Ok(Json(
+        foods
+            .into_iter()
+            .map(|food| FoodResponse::new(food, units))
+            .collect(),
+    ))
+}
+
+#[instrument(skip(state))]
+pub async fn get_custom_food(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path(food_id): Path<Uuid>,
+    Query(query): Query<UnitsQuery>,
+) -> Result<Json<FoodResponse<CustomFood>>, ApiError> {
+    let units = units(&state, user_id, query.units).await?;
+    services::get_custom_food(&state, user_id, food_id)
+        .await
+        .map_err(|e| custom_food_error(&e, user_id, "get custom food failed"))?
+        .map(|food| Json(FoodResponse::new(food, units)))
+        .ok_or_else(food_not_found)
+}
+
+#[instrument(skip(state, payload))]
+pub async fn replace_custom_food(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path(food_id): Path<Uuid>,
+    Query(query): Query<UnitsQuery>,
+    Json(payload): Json<CustomFoodRequest>,
+) -> Result<Json<FoodResponse<CustomFood>>, ApiError> {
+    let input = payload.normalized().map_err(ApiError::validation)?;
+    let units = units(&state, user_id, query.units).await?;
+    services::update_custom_food(&state, user_id, food_id, &input)
+        .await
+        .map_err(|e| custom_food_error(&e, user_id, "update custom food failed"))?
+        .map(|food| Json(FoodResponse::new(food, units)))
+        .ok_or_else(food_not_found)
+}
+
+#[instrument(skip(state))]
+pub async fn delete_custom_food(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path(food_id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    match services::delete_custom_food(&state, user_id, food_id).await {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Err(food_not_found()),
+        Err(e) => Err(custom_food_error(&e, user_id, "delete custom food failed")),
+    }
+}
+
+#[instrument(skip(state))]
+pub async fn search_foods(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Query(query): Query<FoodSearchQuery>,
+) -> Result<Json<FoodSearchResponse>, ApiError> {
+    let q = query.normalized().map_err(ApiError::validation)?;
+    let units = units(&state, user_id, query.units).await?;
+    let (foods, partial) = services::search(&state, user_id, &q)
+        .await
+        .map_err(|e| custom_food_error(&e, user_id, "food search failed"))?;
+    Ok(Json(FoodSearchResponse {
+        query: q,
+        foods: foods
+            .into_iter()
+            .map(|food| FoodResponse::new(food, units))
+            .collect(),
+        partial,
+    }))
+}
+
+#[instrument(skip(state))]
+pub async fn get_food_by_barcode(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path(ean): Path<String>,
+    Query(query): Query<UnitsQuery>,
+) -> Result<Json<FoodResponse<Food>>, ApiError> {
+    let ean = ean.trim();
+    if !is_valid_gtin(ean) {
+        return Err(ApiError::validation("Invalid barcode"));
+    }
+    let units = units(&state, user_id, query.units).await?;
+    match services::lookup_barcode(&state, ean).await {
+        Ok(Some(food)) => Ok(Json(FoodResponse::new(food, units))),
+        Ok(None) => Err(food_not_found()),
+        Err(e) if e.is::<SourceUnavailable>() => {
+            error!(error = %e, user_id = %user_id, ean, "barcode lookup failed");
+            Err(
+                ApiError::BadGateway("Food database unavailable".to_string())
+                    .with_code(ErrorCode::FoodSourceUnavailable),
+            )
+        }
+        Err(e) => {
+            error!(error = %e, user_id = %user_id, ean, "barcode lookup failed");
+            Err(ApiError::internal(&e, "Failed to look up barcode"))
+        }
+    }
+}
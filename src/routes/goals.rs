@@ -0,0 +1,45 @@
+use axum::{extract::State, routing::get, Json, Router};
+use tracing::{error, instrument};
+
+use crate::{
+    auth::jwt::AuthUser,
+    db::AppState,
+    error::ApiError,
+    goals::{
+        dto::{Goals, GoalsResponse},
+        services,
+    },
+};
+
+pub fn goal_routes() -> Router<AppState> {
+    Router::new().route("/me/goals", get(get_goals).put(put_goals))
+}
+
+#[instrument(skip(state))]
+pub async fn get_goals(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+) -> Result<Json<GoalsResponse>, ApiError> {
+    let goals = services::get_goals(&state, user_id).await.map_err(|e| {
+        error!(error = %e, user_id = %user_id, "load goals failed");
+        ApiError::Internal("Failed to load goals".to_string())
+    })?;
+    Ok(Json(GoalsResponse::from(&goals)))
+}
+
+#[instrument(skip(state, payload))]
+pub async fn put_goals(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Json(payload): Json<Goals>,
+) -> Result<Json<GoalsResponse>, ApiError> {
+    payload.validate().map_err(ApiError::validation)?;
+    let goals = services::save_goals(&state, user_id, &payload)
+        .await
+        .map_err(|e| {
+            error!(error = %e, user_id = %user_id, "save goals failed");
+            ApiError::Internal("Failed to save goals".to_string())
+        })?;
+    state.cache.invalidate_summaries(user_id).await;
+    Ok(Json(GoalsResponse::from(&goals)))
+}
@@ -0,0 +1,42 @@
+use std::collections::BTreeMap;
+
+use axum::{extract::State, http::StatusCode, routing::get, Json, Router};
+use serde::Serialize;
+use tracing::warn;
+
+use crate::{db::AppState, tasks::TaskReport};
+
+pub fn health_routes() -> Router<AppState> {
+    Router::new().route("/health/ready", get(ready))
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReadyResponse {
+    pub ready: bool,
+    pub database: bool,
+    pub tasks: BTreeMap<String, TaskReport>,
+}
+
+/// `200` when the database answers and no background task is failing or
+/// stopped, `503` otherwise. The body reports each check either way.
+pub async fn ready(State(state): State<AppState>) -> (StatusCode, Json<ReadyResponse>) {
+    let database = match sqlx::query("SELECT 1").execute(&state.db).await {
+        Ok(_) => true,
+        Err(e) => {
+            warn!(error = %e, "readiness check: database unavailable");
+            false
+        }
+    };
+    let ready = database && state.tasks.is_ready();
+    let status = if ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    let body = ReadyResponse {
+        ready,
+        database,
+        tasks: state.tasks.snapshot(),
+    };
+    (status, Json(body))
+}
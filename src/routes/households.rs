@@ -0,0 +1,201 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    routing::{delete, get, post, put},
+    Json, Router,
+};
+use serde_json::json;
+use tracing::{error, instrument};
+use uuid::Uuid;
+
+use crate::{
+    auth::jwt::AuthUser,
+    db::AppState,
+    error::ApiError,
+    households::{
+        dto::{
+            HouseholdDetails, HouseholdInvite, HouseholdRequest, HouseholdSummary, InviteRequest,
+            MealHouseholdRequest, ReceivedInvite,
+        },
+        repo,
+        services::{self, HouseholdError},
+    },
+    meals::dto::{ListMealsQuery, MealResponse},
+};
+
+pub fn household_routes() -> Router<AppState> {
+    Router::new()
+        .route("/households", get(list_households).post(create_household))
+        .route("/households/invites", get(list_received_invites))
+        .route("/households/invites/:invite_id/accept", post(accept_invite))
+        .route(
+            "/households/invites/:invite_id/decline",
+            post(decline_invite),
+        )
+        .route(
+            "/households/:id",
+            get(get_household).delete(delete_household),
+        )
+        .route("/households/:id/meals", get(list_household_meals))
+        .route("/households/:id/invites", post(invite_member))
+        .route("/households/:id/invites/:invite_id", delete(cancel_invite))
+        .route("/households/:id/members/:user_id", delete(remove_member))
+        .route("/meals/:id/household", put(set_meal_household))
+}
+
+pub(crate) fn household_error(e: HouseholdError, user_id: Uuid) -> ApiError {
+    if let HouseholdError::Other(source) = &e {
+        error!(error = %source, user_id = %user_id, "household request failed");
+        return ApiError::internal(source, "Failed to access households");
+    }
+    ApiError::from_status(e.status(), e.to_string()).with_code(e.code())
+}
+
+#[instrument(skip(state))]
+pub async fn list_households(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+) -> Result<Json<Vec<HouseholdSummary>>, ApiError> {
+    repo::list_households(&state.db, user_id)
+        .await
+        .map(Json)
+        .map_err(|e| household_error(e.into(), user_id))
+}
+
+#[instrument(skip(state, payload))]
+pub async fn create_household(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Json(payload): Json<HouseholdRequest>,
+) -> Result<(StatusCode, Json<HouseholdSummary>), ApiError> {
+    services::create_household(&state, user_id, payload)
+        .await
+        .map(|household| (StatusCode::CREATED, Json(household)))
+        .map_err(|e| household_error(e, user_id))
+}
+
+#[instrument(skip(state))]
+pub async fn get_household(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path(household_id): Path<Uuid>,
+) -> Result<Json<HouseholdDetails>, ApiError> {
+    services::get_household(&state, user_id, household_id)
+        .await
+        .map(Json)
+        .map_err(|e| household_error(e, user_id))
+}
+
+#[instrument(skip(state))]
+pub async fn delete_household(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path(household_id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    services::delete_household(&state, user_id, household_id)
+        .await
+        .map(|()| StatusCode::NO_CONTENT)
+        .map_err(|e| household_error(e, user_id))
+}
+
+/// Meals every member logged into the household, newest first.
+#[instrument(skip(state))]
+pub async fn list_household_meals(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path(household_id): Path<Uuid>,
+    Query(query): Query<ListMealsQuery>,
+) -> Result<Json<Vec<MealResponse>>, ApiError> {
+    services::list_meals(&state, user_id, household_id, &query)
+        .await
+        .map(Json)
+        .map_err(|e| household_error(e, user_id))
+}
+
+#[instrument(skip(state, payload))]
+pub async fn invite_member(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path(household_id): Path<Uuid>,
+    Json(payload): Json<InviteRequest>,
+) -> Result<(StatusCode, Json<HouseholdInvite>), ApiError> {
+    services::invite(&state, user_id, household_id, payload)
+        .await
+        .map(|invite| (StatusCode::CREATED, Json(invite)))
+        .map_err(|e| household_error(e, user_id))
+}
+
+#[instrument(skip(state))]
+pub async fn cancel_invite(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path((household_id, invite_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, ApiError> {
+    services::cancel_invite(&state, user_id, household_id, invite_id)
+        .await
+        .map(|()| StatusCode::NO_CONTENT)
+        .map_err(|e| household_error(e, user_id))
+}
+
+#[instrument(skip(state))]
+pub async fn remove_member(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path((household_id, member_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, ApiError> {
+    services::remove_member(&state, user_id, household_id, member_id)
+        .await
+        .map(|()| StatusCode::NO_CONTENT)
+        .map_err(|e| household_error(e, user_id))
+}
+
+/// Invites addressed to the current user's email.
+#[instrument(skip(state))]
+pub async fn list_received_invites(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+) -> Result<Json<Vec<ReceivedInvite>>, ApiError> {
+    services::received_invites(&state, user_id)
+        .await
+        .map(Json)
+        .map_err(|e| household_error(e, user_id))
+}
+
+#[instrument(skip(state))]
+pub async fn accept_invite(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path(invite_id): Path<Uuid>,
+) -> Result<Json<HouseholdSummary>, ApiError> {
+    services::accept_invite(&state, user_id, invite_id)
+        .await
+        .map(Json)
+        .map_err(|e| household_error(e, user_id))
+}
+
+#[instrument(skip(state))]
+pub async fn decline_invite(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path(invite_id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    services::decline_invite(&state, user_id, invite_id)
+        .await
+        .map(|()| StatusCode::NO_CONTENT)
+        .map_err(|e| household_error(e, user_id))
+}
+
+/// Shares one of the user's meals with a household, or makes it private
+/// again with `{"household_id": null}`.
+#[instrument(skip(state, payload))]
+pub async fn set_meal_household(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path(meal_id): Path<Uuid>,
+    Json(payload): Json<MealHouseholdRequest>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    services::set_meal_household(&state, user_id, meal_id, payload.household_id)
+        .await
+        .map(|household_id| Json(json!({ "id": meal_id, "household_id": household_id })))
+        .map_err(|e| household_error(e, user_id))
+}
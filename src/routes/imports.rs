@@ -0,0 +1,51 @@
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::post,
+    Json, Router,
+};
+use tracing::{error, instrument};
+
+use crate::{
+    app::body_limit,
+    auth::jwt::AuthUser,
+    db::AppState,
+    error::ApiError,
+    imports::{
+        dto::{ImportQuery, MAX_IMPORT_BYTES},
+        services::{self, ImportError},
+    },
+};
+
+pub fn import_routes() -> Router<AppState> {
+    Router::new().route(
+        "/import/meals",
+        post(import_meals).layer(body_limit(MAX_IMPORT_BYTES)),
+    )
+}
+
+/// Imports a MyFitnessPal or Cronometer CSV export sent as the request body.
+/// Dry runs answer 200 with the report; imports answer 201, or 422 with the
+/// report when rows are invalid.
+#[instrument(skip(state, body))]
+pub async fn import_meals(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Query(query): Query<ImportQuery>,
+    body: String,
+) -> Result<Response, ApiError> {
+    let dry_run = query.dry_run;
+    match services::import_meals(&state, user_id, query, &body).await {
+        Ok(report) if dry_run => Ok((StatusCode::OK, Json(report)).into_response()),
+        Ok(report) => Ok((StatusCode::CREATED, Json(report)).into_response()),
+        Err(ImportError::Rejected(report)) => {
+            Ok((StatusCode::UNPROCESSABLE_ENTITY, Json(report)).into_response())
+        }
+        Err(ImportError::Other(e)) => {
+            error!(error = %e, user_id = %user_id, "meal import failed");
+            Err(ApiError::Internal("Failed to import meals".to_string()))
+        }
+        Err(e) => Err(ApiError::from_status(e.status(), e.to_string()).with_code(e.code())),
+    }
+}
@@ -0,0 +1,122 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    routing::{delete, get, post},
+    Json, Router,
+};
+use tracing::{error, info, instrument};
+use uuid::Uuid;
+
+use crate::{
+    activity::dto::{ImportActivityRequest, ImportActivityResponse},
+    auth::jwt::AuthUser,
+    db::AppState,
+    error::ApiError,
+    integrations::{
+        dto::{CallbackQuery, ConnectResponse, IntegrationStatus, SyncResponse},
+        services::{self, IntegrationError},
+        Provider,
+    },
+};
+
+pub fn integration_routes() -> Router<AppState> {
+    Router::new()
+        .route("/integrations", get(list_integrations))
+        .route("/integrations/activity", post(import_activity))
+        .route("/integrations/:provider", delete(disconnect))
+        .route("/integrations/:provider/connect", get(connect))
+        .route("/integrations/:provider/callback", get(callback))
+        .route("/integrations/:provider/sync", post(sync))
+}
+
+fn integration_error(e: IntegrationError, user_id: Option<Uuid>) -> ApiError {
+    match &e {
+        IntegrationError::Other(source) => {
+            error!(error = %source, user_id = ?user_id, "integration request failed");
+            return ApiError::internal(source, "Integration request failed");
+        }
+        IntegrationError::Upstream(source) => {
+            error!(error = %source, user_id = ?user_id, "integration provider failed");
+        }
+        _ => {}
+    }
+    ApiError::from_status(e.status(), e.to_string()).with_code(e.code())
+}
+
+#[instrument(skip(state, payload))]
+pub async fn import_activity(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Json(payload): Json<ImportActivityRequest>,
+) -> Result<Json<ImportActivityResponse>, ApiError> {
+    let imported = services::import_activity(&state, user_id, payload)
+        .await
+        .map_err(|e| integration_error(e, Some(user_id)))?;
+    info!(user_id = %user_id, imported, "activity imported");
+    Ok(Json(ImportActivityResponse { imported }))
+}
+
+#[instrument(skip(state))]
+pub async fn list_integrations(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+) -> Result<Json<Vec<IntegrationStatus>>, ApiError> {
+    let integrations = services::list_integrations(&state, user_id)
+        .await
+        .map_err(|e| {
+            error!(error = %e, user_id = %user_id, "list integrations failed");
+            ApiError::Internal("Failed to list integrations".to_string())
+        })?;
+    Ok(Json(integrations))
+}
+
+#[instrument(skip(state))]
+pub async fn connect(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path(provider): Path<Provider>,
+) -> Result<Json<ConnectResponse>, ApiError> {
+    services::connect(&state, user_id, provider)
+        .await
+        .map(Json)
+        .map_err(|e| integration_error(e, Some(user_id)))
+}
+
+/// Reached from the provider's redirect, so it carries no bearer token;
+/// `state` names the user.
+#[instrument(skip(state, query))]
+pub async fn callback(
+    State(state): State<AppState>,
+    Path(provider): Path<Provider>,
+    Query(query): Query<CallbackQuery>,
+) -> Result<Json<IntegrationStatus>, ApiError> {
+    services::complete(&state, provider, query)
+        .await
+        .map(Json)
+        .map_err(|e| integration_error(e, None))
+}
+
+#[instrument(skip(state))]
+pub async fn sync(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path(provider): Path<Provider>,
+) -> Result<Json<SyncResponse>, ApiError> {
+    services::sync(&state, user_id, provider)
+        .await
+        .map(Json)
+        .map_err(|e| integration_error(e, Some(user_id)))
+}
+
+#[instrument(skip(state))]
+pub async fn disconnect(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path(provider): Path<Provider>,
+) -> Result<StatusCode, ApiError> {
+    services::disconnect(&state, user_id, provider)
+        .await
+        .map_err(|e| integration_error(e, Some(user_id)))?;
+    info!(user_id = %user_id, provider = ?provider, "integration disconnected");
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -1,13 +1,14 @@
 use axum::{extract::State, Json};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use tracing::{error, instrument};
 
 use crate::{
-    auth::jwt::AuthUser,
-    db::{AppState, User},
+    auth::{jwt::AuthUser, repo as users_repo},
+    cache::{self, keys},
+    db::AppState,
 };
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct MeResponse {
     pub id: uuid::Uuid,
     pub email: String,
@@ -18,24 +19,33 @@ pub async fn me_route(
     State(state): State<AppState>,
     AuthUser(user_id): AuthUser,
 ) -> Result<Json<MeResponse>, (axum::http::StatusCode, String)> {
-    let user = sqlx::query_as::<_, User>(
-        r#"SELECT id, email, password_hash, created_at FROM users WHERE id = $1"#,
-    )
-    .bind(user_id)
-    .fetch_one(&state.db)
-    .await
-    .map_err(|e| {
-        error!(error = %e, user_id = %user_id, "user not found");
-        (
-            axum::http::StatusCode::UNAUTHORIZED,
-            "User not found".into(),
-        )
-    })?;
-
-    Ok(Json(MeResponse {
+    let key = keys::me(user_id);
+    if let Some(me) = state.cache.get::<MeResponse>(&key).await {
+        return Ok(Json(me));
+    }
+
+    let user = users_repo::find_by_id(&state.db, user_id)
+        .await
+        .map_err(|e| {
+            error!(error = %e, user_id = %user_id, "load user failed");
+            (
+                axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to load user".into(),
+            )
+        })?
+        .ok_or_else(|| {
+            (
+                axum::http::StatusCode::UNAUTHORIZED,
+                "User not found".to_string(),
+            )
+        })?;
+
+    let me = MeResponse {
         id: user.id,
         email: user.email,
-    }))
+    };
+    state.cache.put(&key, &me, cache::ME_TTL).await;
+    Ok(Json(me))
 }
 
 #[cfg(test)]
@@ -0,0 +1,111 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    routing::{get, put},
+    Json, Router,
+};
+use tracing::{error, instrument};
+use uuid::Uuid;
+
+use crate::{
+    auth::jwt::AuthUser,
+    db::AppState,
+    error::ApiError,
+    meal_items::{
+        dto::{MealItem, MealItemRequest},
+        services::{self, ItemError},
+    },
+    preferences::services as preferences,
+    units::UnitsQuery,
+};
+
+pub fn meal_item_routes() -> Router<AppState> {
+    Router::new()
+        .route("/meals/:id/items", get(list_items).post(create_item))
+        .route(
+            "/meals/:id/items/:item_id",
+            put(replace_item).delete(delete_item),
+        )
+}
+
+/// Logs server-side failures; client errors pass through with their message.
+fn item_error(e: ItemError, user_id: Uuid, meal_id: Uuid) -> ApiError {
+    let status = e.status();
+    match &e {
+        ItemError::Upstream(source) => {
+            error!(error = %source, user_id = %user_id, meal_id = %meal_id, "food lookup failed")
+        }
+        ItemError::Other(source) => {
+            error!(error = %source, user_id = %user_id, meal_id = %meal_id, "meal item request failed");
+            return ApiError::internal(source, "Failed to update meal items");
+        }
+        _ => {}
+    }
+    ApiError::from_status(status, e.to_string()).with_code(e.code())
+}
+
+#[instrument(skip(state))]
+pub async fn list_items(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path(meal_id): Path<Uuid>,
+    Query(query): Query<UnitsQuery>,
+) -> Result<Json<Vec<MealItem>>, ApiError> {
+    let units = preferences::units(&state, user_id, query.units)
+        .await
+        .map_err(|e| item_error(e.into(), user_id, meal_id))?;
+    let items = services::list_items(&state, user_id, meal_id)
+        .await
+        .map_err(|e| item_error(e, user_id, meal_id))?;
+    Ok(Json(
+        items.into_iter().map(|item| item.in_units(units)).collect(),
+    ))
+}
+
+#[instrument(skip(state, payload))]
+pub async fn create_item(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path(meal_id): Path<Uuid>,
+    Query(query): Query<UnitsQuery>,
+    Json(payload): Json<MealItemRequest>,
+) -> Result<(StatusCode, Json<MealItem>), ApiError> {
+    let input = payload.normalized().map_err(ApiError::validation)?;
+    let units = preferences::units(&state, user_id, query.units)
+        .await
+        .map_err(|e| item_error(e.into(), user_id, meal_id))?;
+    services::add_item(&state, user_id, meal_id, input)
+        .await
+        .map(|item| (StatusCode::CREATED, Json(item.in_units(units))))
+        .map_err(|e| item_error(e, user_id, meal_id))
+}
+
+#[instrument(skip(state, payload))]
+pub async fn replace_item(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path((meal_id, item_id)): Path<(Uuid, Uuid)>,
+    Query(query): Query<UnitsQuery>,
+    Json(payload): Json<MealItemRequest>,
+) -> Result<Json<MealItem>, ApiError> {
+    let input = payload.normalized().map_err(ApiError::validation)?;
+    let units = preferences::units(&state, user_id, query.units)
+        .await
+        .map_err(|e| item_error(e.into(), user_id, meal_id))?;
+    services::replace_item(&state, user_id, meal_id, item_id, input)
+        .await
+        .map(|item| Json(item.in_units(units)))
+        .map_err(|e| item_error(e, user_id, meal_id))
+}
+
+#[instrument(skip(state))]
+pub async fn delete_item(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path((meal_id, item_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, ApiError> {
+    services::delete_item(&state, user_id, meal_id, item_id)
+        .await
+        .map(|()| StatusCode::NO_CONTENT)
+        .map_err(|e| item_error(e, user_id, meal_id))
+}
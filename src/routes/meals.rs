@@ -0,0 +1,608 @@
+use axum::{
+    extract::{DefaultBodyLimit, Multipart, Path, Query, State},
+    http::{HeaderMap, HeaderName, HeaderValue, StatusCode},
+    middleware::from_fn_with_state,
+    routing::{delete, get, post, put},
+    Json, Router,
+};
+use time::macros::date;
+use tracing::{error, info, instrument, warn};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::{
+    app::body_limit,
+    auth::jwt::AuthUser,
+    billing,
+    config::UploadConfig,
+    db::AppState,
+    deprecation::{self, Deprecation},
+    error::{ApiError, ErrorCode},
+    households::services as household_services,
+    idempotency,
+    images::{
+        dto::NormalizedImage,
+        services::{
+            self as image_services, normalize_image, normalize_images, DuplicatePhoto, ImageError,
+            MAX_IMAGES_PER_MEAL,
+        },
+    },
+    meals::{
+        dto::{
+            BulkMealRequest, BulkMealResponse, CreatedMealRequest, ListMealsQuery,
+            ManualNutritionRequest, MealDetails, MealNutrition, MealResponse, MealStatusResponse,
+            MealType, NewMeal,
+        },
+        services,
+    },
+    photos::dto::PhotoMetadata,
+    routes::{households::household_error, uploads::upload_session_error},
+    uploads::services as uploads_services,
+};
+
+/// Room for text fields and multipart/JSON framing on top of image bytes.
+pub(crate) const BODY_OVERHEAD_BYTES: usize = 1024 * 1024;
+
+pub fn meal_routes() -> Router<AppState> {
+    Router::new()
+        .route("/meals", get(list_meals))
+        .route("/meals/bulk", post(bulk_meals))
+        .route("/meals/:id", get(get_meal))
+        .route("/meals/:id/status", get(get_meal_status))
+        .route(
+            "/meals/:id/nutrition",
+            put(put_meal_nutrition).delete(delete_meal_nutrition),
+        )
+        .route("/meals/:id/photos", get(list_meal_photos))
+        .route("/meals/:id/photos/:photo_id", delete(delete_meal_photo))
+}
+
+/// Meal creation with images, sized from [`UploadConfig`] rather than the
+/// default body limit. `POST /meals` honours `Idempotency-Key`; photo
+/// uploads count against the user's plan.
+pub fn meal_upload_routes(state: &AppState) -> Router<AppState> {
+    let uploads = &state.config.uploads;
+    let multipart_limit = uploads.max_request_bytes + BODY_OVERHEAD_BYTES;
+    Router::new()
+        .route(
+            "/meals",
+            // `DefaultBodyLimit` rather than `body_limit` so the replay
+            // middleware reads the body under the same cap as the handler.
+            post(create_meal).layer((
+                DefaultBodyLimit::max(json_body_limit(uploads)),
+                from_fn_with_state(state.clone(), idempotency::replay),
+                // Inside the replay so replayed responses aren't checked.
+                from_fn_with_state(state.clone(), billing::enforce_plan),
+            )),
+        )
+        .route(
+            "/meals/multipart",
+            post(create_meal_multipart)
+                .layer(body_limit(multipart_limit))
+                .layer(from_fn_with_state(state.clone(), billing::enforce_plan))
+                .layer(from_fn_with_state(MULTIPART_DEPRECATION, deprecation::mark)),
+        )
+}
+
+/// Replaced by `POST /api/v2/meals`, which takes multipart bodies too.
+const MULTIPART_DEPRECATION: Deprecation = Deprecation {
+    since: date!(2026 - 10 - 16),
+    sunset: date!(2027 - 04 - 01),
+    successor: "/api/v2/meals",
+};
+
+/// Body cap of JSON meal creation. Base64 inflates images by 4/3, so it is
+/// larger than the multipart one.
+pub fn json_body_limit(uploads: &UploadConfig) -> usize {
+    uploads.max_request_bytes / 3 * 4 + BODY_OVERHEAD_BYTES
+}
+
+static X_TOTAL_COUNT: HeaderName = HeaderName::from_static("x-total-count");
+static X_TOTAL_CALORIES: HeaderName = HeaderName::from_static("x-total-calories-kcal");
+static X_TOTAL_PROTEIN: HeaderName = HeaderName::from_static("x-total-protein-g");
+
+#[utoipa::path(
+    get,
+    path = "/meals",
+    tag = "meals",
+    params(ListMealsQuery),
+    security(("bearer_auth" = [])),
+    responses((
+        status = 200,
+        body = Vec<MealResponse>,
+        headers(
+            ("X-Total-Count" = i64, description = "With include=totals: meals matching the filters"),
+            ("X-Total-Calories-Kcal" = f64, description = "With include=totals: their calories"),
+            ("X-Total-Protein-G" = f64, description = "With include=totals: their protein"),
+        )
+    ))
+)]
+#[instrument(skip(state))]
+pub async fn list_meals(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Query(query): Query<ListMealsQuery>,
+) -> Result<(HeaderMap, Json<Vec<MealResponse>>), ApiError> {
+    let near = query.near().map_err(ApiError::validation)?;
+    let includes = query.includes().map_err(ApiError::validation)?;
+    let list = services::list_meals(&state, user_id, &query, near, includes)
+        .await
+        .map_err(|e| {
+            error!(error = %e, user_id = %user_id, "list meals failed");
+            ApiError::internal(&e, "Failed to list meals")
+        })?;
+    let mut headers = HeaderMap::new();
+    if let Some(totals) = list.totals {
+        headers.insert(X_TOTAL_COUNT.clone(), HeaderValue::from(totals.count));
+        for (name, value) in [
+            (&X_TOTAL_CALORIES, totals.total_calories_kcal),
+            (&X_TOTAL_PROTEIN, totals.protein_g),
+        ] {
+            let value = format!("{:.1}", value);
+            headers.insert(
+                name.clone(),
+                HeaderValue::from_str(&value).expect("a number"),
+            );
+        }
+    }
+    Ok((headers, Json(list.meals)))
+}
+
+fn meal_not_found() -> ApiError {
+    ApiError::NotFound("Meal not found".to_string()).with_code(ErrorCode::MealNotFound)
+}
+
+/// Rejects logging into a household the user doesn't belong to before any
+/// image is uploaded.
+async fn check_household(state: &AppState, user_id: Uuid, meal: &NewMeal) -> Result<(), ApiError> {
+    match meal.household_id {
+        Some(household_id) => household_services::ensure_member(state, user_id, household_id)
+            .await
+            .map_err(|e| household_error(e, user_id)),
+        None => Ok(()),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/meals",
+    tag = "meals",
+    request_body = CreatedMealRequest,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 201, body = MealDetails),
+        (status = 400, body = ErrorResponse),
+        (status = 404, description = "Household not found", body = ErrorResponse),
+        (status = 413, description = "Image too large", body = ErrorResponse),
+        (status = 422, description = "Not a supported image", body = ErrorResponse),
+    )
+)]
+#[instrument(skip(state, payload))]
+pub async fn create_meal(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Json(mut payload): Json<CreatedMealRequest>,
+) -> Result<(StatusCode, Json<MealDetails>), ApiError> {
+    let nutrition = payload.take_nutrition().map_err(ApiError::validation)?;
+    let meal = NewMeal {
+        title: payload.title,
+        notes: payload.notes,
+        meal_type: payload.meal_type,
+        household_id: payload.household_id,
+        latitude: payload.latitude,
+        longitude: payload.longitude,
+        place_name: payload.place_name,
+    }
+    .normalized()
+    .map_err(ApiError::validation)?;
+    check_household(&state, user_id, &meal).await?;
+
+    if let Some(session_id) = payload.upload_session_id {
+        if !payload.images.is_empty() || nutrition.is_some() {
+            return Err(ApiError::validation(
+                "upload_session_id can't be combined with images, nutrition or quick_add",
+            ));
+        }
+        let details = uploads_services::create_meal(&state, user_id, session_id, meal)
+            .await
+            .map_err(|e| upload_session_error(e, user_id))?;
+        return Ok((StatusCode::CREATED, Json(details)));
+    }
+
+    let images = normalize_images(payload.images, &state.config.uploads).map_err(|e| {
+        warn!(user_id = %user_id, error = %e, "invalid meal images");
+        ApiError::from(e)
+    })?;
+    if images.is_empty() {
+        if nutrition.is_none() && meal.title.is_none() {
+            return Err(ApiError::BadRequest(
+                "Provide images, nutrition, quick_add or a title".to_string(),
+            )
+            .with_code(ErrorCode::MealEmpty));
+        }
+        let details = services::create_quick_meal(&state, user_id, meal, nutrition)
+            .await
+            .map_err(|e| {
+                error!(error = %e, user_id = %user_id, "create quick meal failed");
+                ApiError::internal(&e, "Failed to create meal")
+            })?;
+        return Ok((StatusCode::CREATED, Json(details)));
+    }
+    if nutrition.is_some() {
+        // Photos get analyzed; hand-entered values go through PUT /nutrition.
+        return Err(ApiError::validation(
+            "nutrition and quick_add are only accepted without images",
+        ));
+    }
+
+    let details = services::create_meal_with_images(&state, user_id, meal, images)
+        .await
+        .map_err(|e| {
+            if let Some(duplicate) = e.downcast_ref::<DuplicatePhoto>() {
+                return ApiError::from(*duplicate);
+            }
+            error!(error = %e, user_id = %user_id, "create meal failed");
+            ApiError::internal(&e, "Failed to create meal")
+        })?;
+    Ok((StatusCode::CREATED, Json(details)))
+}
+
+/// `multipart/form-data` variant of [`create_meal`]: text fields `title`,
+/// `notes`, `meal_type`, `household_id`, `latitude`, `longitude`,
+/// `place_name` and one file part per image.
+#[utoipa::path(
+    post,
+    path = "/meals/multipart",
+    tag = "meals",
+    request_body(content = MealForm, content_type = "multipart/form-data"),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 201, body = MealDetails),
+        (status = 400, body = ErrorResponse),
+        (status = 404, description = "Household not found", body = ErrorResponse),
+        (status = 413, description = "Image too large", body = ErrorResponse),
+        (status = 422, description = "Not a supported image", body = ErrorResponse),
+    )
+)]
+#[instrument(skip(state, multipart))]
+pub async fn create_meal_multipart(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    multipart: Multipart,
+) -> Result<(StatusCode, Json<MealDetails>), ApiError> {
+    let (meal, images) = read_meal_form(multipart, &state.config.uploads)
+        .await
+        .inspect_err(|e| {
+            warn!(user_id = %user_id, status = %e.status(), msg = %e.message(), "invalid multipart meal");
+        })?;
+    let meal = meal.normalized().map_err(ApiError::validation)?;
+    if images.is_empty() {
+        return Err(ApiError::BadRequest("No images provided".to_string())
+            .with_code(ErrorCode::UploadNoImages));
+    }
+    check_household(&state, user_id, &meal).await?;
+
+    let details = services::create_meal_with_images(&state, user_id, meal, images)
+        .await
+        .map_err(|e| {
+            if let Some(duplicate) = e.downcast_ref::<DuplicatePhoto>() {
+                return ApiError::from(*duplicate);
+            }
+            error!(error = %e, user_id = %user_id, "create meal failed");
+            ApiError::internal(&e, "Failed to create meal")
+        })?;
+    Ok((StatusCode::CREATED, Json(details)))
+}
+
+// Only describes the form read by `read_meal_form` in the OpenAPI document.
+/// Text fields plus one file part per image.
+#[derive(ToSchema)]
+#[allow(dead_code)]
+pub struct MealForm {
+    title: Option<String>,
+    notes: Option<String>,
+    meal_type: Option<MealType>,
+    household_id: Option<Uuid>,
+    latitude: Option<f64>,
+    longitude: Option<f64>,
+    place_name: Option<String>,
+    /// One file part per image.
+    #[schema(value_type = Vec<String>, format = Binary)]
+    images: Vec<Vec<u8>>,
+}
+
+/// Reads the form, streaming each file part in chunks so an oversized image
+/// is rejected as soon as it crosses the limit.
+async fn read_meal_form(
+    mut multipart: Multipart,
+    limits: &UploadConfig,
+) -> Result<(NewMeal, Vec<NormalizedImage>), ApiError> {
+    let mut meal = NewMeal::default();
+    let mut images = Vec::new();
+    let mut total = 0usize;
+
+    while let Some(mut field) = multipart.next_field().await.map_err(ApiError::from)? {
+        let name = field.name().unwrap_or_default().to_string();
+        if field.file_name().is_some() || name == "images" || name == "image" {
+            let index = images.len();
+            if index >= MAX_IMAGES_PER_MEAL {
+                return Err(ImageError::TooMany {
+                    max: MAX_IMAGES_PER_MEAL,
+                }
+                .into());
+            }
+            let content_type = field.content_type().unwrap_or_default().to_string();
+            let mut bytes = Vec::new();
+            while let Some(chunk) = field.chunk().await.map_err(ApiError::from)? {
+                if bytes.len() + chunk.len() > limits.max_image_bytes {
+                    return Err(ImageError::TooLarge {
+                        index,
+                        max: limits.max_image_bytes,
+                    }
+                    .into());
+                }
+                total += chunk.len();
+                if total > limits.max_request_bytes {
+                    return Err(ImageError::RequestTooLarge {
+                        max: limits.max_request_bytes,
+                    }
+                    .into());
+                }
+                bytes.extend_from_slice(&chunk);
+            }
+            let image = normalize_image(index, &content_type, bytes, None, limits)
+                .map_err(ApiError::from)?;
+            images.push(image);
+            continue;
+        }
+
+        let value = field.text().await.map_err(ApiError::from)?;
+        match name.as_str() {
+            "title" => meal.title = Some(value),
+            "notes" => meal.notes = Some(value),
+            "household_id" if !value.trim().is_empty() => {
+                meal.household_id = Some(
+                    value
+                        .trim()
+                        .parse()
+                        .map_err(|_| ApiError::validation("household_id must be a UUID"))?,
+                );
+            }
+            "meal_type" if !value.trim().is_empty() => {
+                meal.meal_type = Some(value.parse().map_err(ApiError::validation)?);
+            }
+            "latitude" | "longitude" if !value.trim().is_empty() => {
+                let coordinate = value
+                    .trim()
+                    .parse()
+                    .map_err(|_| ApiError::validation(format!("{} must be a number", name)))?;
+                if name == "latitude" {
+                    meal.latitude = Some(coordinate);
+                } else {
+                    meal.longitude = Some(coordinate);
+                }
+            }
+            "place_name" => meal.place_name = Some(value),
+            _ => {}
+        }
+    }
+    Ok((meal, images))
+}
+
+#[utoipa::path(
+    get,
+    path = "/meals/{id}",
+    tag = "meals",
+    params(("id" = Uuid, Path, description = "Meal id")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, body = MealDetails),
+        (status = 404, body = ErrorResponse),
+    )
+)]
+#[instrument(skip(state))]
+pub async fn get_meal(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path(meal_id): Path<Uuid>,
+) -> Result<Json<MealDetails>, ApiError> {
+    match services::get_meal_details(&state, user_id, meal_id).await {
+        Ok(Some(details)) => Ok(Json(details)),
+        Ok(None) => Err(meal_not_found()),
+        Err(e) => {
+            error!(error = %e, user_id = %user_id, meal_id = %meal_id, "get meal failed");
+            Err(ApiError::internal(&e, "Failed to load meal"))
+        }
+    }
+}
+
+/// Lightweight poll target while analysis runs.
+#[utoipa::path(
+    get,
+    path = "/meals/{id}/status",
+    tag = "meals",
+    params(("id" = Uuid, Path, description = "Meal id")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, body = MealStatusResponse),
+        (status = 404, body = ErrorResponse),
+    )
+)]
+#[instrument(skip(state))]
+pub async fn get_meal_status(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path(meal_id): Path<Uuid>,
+) -> Result<Json<MealStatusResponse>, ApiError> {
+    match services::meal_status(&state, user_id, meal_id).await {
+        Ok(Some(status)) => Ok(Json(MealStatusResponse {
+            id: meal_id,
+            status,
+        })),
+        Ok(None) => Err(meal_not_found()),
+        Err(e) => {
+            error!(error = %e, user_id = %user_id, meal_id = %meal_id, "get meal status failed");
+            Err(ApiError::Internal("Failed to load meal status".to_string()))
+        }
+    }
+}
+
+#[utoipa::path(
+    put,
+    path = "/meals/{id}/nutrition",
+    tag = "meals",
+    params(("id" = Uuid, Path, description = "Meal id")),
+    request_body = ManualNutritionRequest,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, body = MealNutrition),
+        (status = 400, body = ErrorResponse),
+        (status = 404, body = ErrorResponse),
+    )
+)]
+#[instrument(skip(state, payload))]
+pub async fn put_meal_nutrition(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path(meal_id): Path<Uuid>,
+    Json(payload): Json<ManualNutritionRequest>,
+) -> Result<Json<MealNutrition>, ApiError> {
+    payload.validate().map_err(ApiError::validation)?;
+    match services::set_manual_nutrition(&state, user_id, meal_id, &payload).await {
+        Ok(Some(nutrition)) => {
+            info!(user_id = %user_id, meal_id = %meal_id, "manual nutrition saved");
+            Ok(Json(nutrition))
+        }
+        Ok(None) => Err(meal_not_found()),
+        Err(e) => {
+            error!(error = %e, user_id = %user_id, meal_id = %meal_id, "save nutrition failed");
+            Err(ApiError::Internal("Failed to save nutrition".to_string()))
+        }
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/meals/{id}/nutrition",
+    tag = "meals",
+    params(("id" = Uuid, Path, description = "Meal id")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 204),
+        (status = 404, body = ErrorResponse),
+    )
+)]
+#[instrument(skip(state))]
+pub async fn delete_meal_nutrition(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path(meal_id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    match services::clear_nutrition(&state, user_id, meal_id).await {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Err(ApiError::NotFound("Nutrition not found".to_string())
+            .with_code(ErrorCode::NutritionNotFound)),
+        Err(e) => {
+            error!(error = %e, user_id = %user_id, meal_id = %meal_id, "delete nutrition failed");
+            Err(ApiError::Internal("Failed to delete nutrition".to_string()))
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/meals/{id}/photos",
+    tag = "meals",
+    params(("id" = Uuid, Path, description = "Meal id")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, body = Vec<PhotoMetadata>),
+        (status = 404, body = ErrorResponse),
+    )
+)]
+#[instrument(skip(state))]
+pub async fn list_meal_photos(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path(meal_id): Path<Uuid>,
+) -> Result<Json<Vec<PhotoMetadata>>, ApiError> {
+    match services::list_meal_photos(&state, user_id, meal_id).await {
+        Ok(Some(photos)) => Ok(Json(photos)),
+        Ok(None) => Err(meal_not_found()),
+        Err(e) => {
+            error!(error = %e, user_id = %user_id, meal_id = %meal_id, "list photos failed");
+            Err(ApiError::internal(&e, "Failed to list photos"))
+        }
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/meals/{id}/photos/{photo_id}",
+    tag = "meals",
+    params(("id" = Uuid, Path, description = "Meal id"), ("photo_id" = Uuid, Path, description = "Photo id")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 204),
+        (status = 404, body = ErrorResponse),
+    )
+)]
+#[instrument(skip(state))]
+pub async fn delete_meal_photo(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path((meal_id, photo_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, ApiError> {
+    match image_services::delete_meal_photo(&state, user_id, meal_id, photo_id).await {
+        Ok(true) => {
+            info!(user_id = %user_id, meal_id = %meal_id, photo_id = %photo_id, "photo deleted");
+            Ok(StatusCode::NO_CONTENT)
+        }
+        Ok(false) => {
+            Err(ApiError::NotFound("Photo not found".to_string())
+                .with_code(ErrorCode::PhotoNotFound))
+        }
+        Err(e) => {
+            error!(error = %e, user_id = %user_id, photo_id = %photo_id, "delete photo failed");
+            Err(ApiError::internal(&e, "Failed to delete photo"))
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/meals/bulk",
+    tag = "meals",
+    request_body = BulkMealRequest,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, body = BulkMealResponse),
+        (status = 400, body = ErrorResponse),
+    )
+)]
+#[instrument(skip(state, payload))]
+pub async fn bulk_meals(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Json(mut payload): Json<BulkMealRequest>,
+) -> Result<Json<BulkMealResponse>, ApiError> {
+    if let Err(msg) = payload.normalize() {
+        warn!(user_id = %user_id, %msg, "invalid bulk request");
+        return Err(ApiError::validation(msg));
+    }
+
+    let results = services::apply_bulk(&state, user_id, &payload.operations)
+        .await
+        .map_err(|e| {
+            error!(error = %e, user_id = %user_id, "bulk meal operation failed");
+            ApiError::Internal("Bulk operation failed".to_string())
+        })?;
+
+    info!(
+        user_id = %user_id,
+        items = results.len(),
+        failed = results.iter().filter(|r| !r.ok).count(),
+        "bulk meal operation applied"
+    );
+    Ok(Json(BulkMealResponse { results }))
+}
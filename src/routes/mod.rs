@@ -1,2 +1,39 @@
+pub mod achievements;
+pub mod admin;
+pub mod analytics;
 pub mod auth;
+pub mod billing;
+pub mod coaching;
+pub mod docs;
+pub mod events;
+pub mod export;
+pub mod flags;
+pub mod foods;
+pub mod goals;
+pub mod health;
+pub mod households;
+pub mod imports;
+pub mod integrations;
 pub mod me;
+pub mod meal_items;
+pub mod meals;
+pub mod notifications;
+pub mod orgs;
+pub mod photos;
+pub mod plans;
+pub mod preferences;
+pub mod progress;
+pub mod push;
+pub mod reactions;
+pub mod recipes;
+pub mod recommendations;
+pub mod shares;
+pub mod stats;
+pub mod summary;
+pub mod sync;
+pub mod templates;
+pub mod uploads;
+pub mod v2;
+pub mod webhooks;
+pub mod weights;
+pub mod ws;
@@ -0,0 +1,56 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    routing::{get, post},
+    Json, Router,
+};
+use tracing::{error, instrument};
+use uuid::Uuid;
+
+use crate::{
+    auth::jwt::AuthUser,
+    db::AppState,
+    error::{ApiError, ErrorCode},
+    notifications::{
+        dto::{ListNotificationsQuery, NotificationsResponse},
+        services,
+    },
+};
+
+pub fn notification_routes() -> Router<AppState> {
+    Router::new()
+        .route("/me/notifications", get(list_notifications))
+        .route("/me/notifications/:id/read", post(mark_notification_read))
+}
+
+#[instrument(skip(state))]
+pub async fn list_notifications(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Query(query): Query<ListNotificationsQuery>,
+) -> Result<Json<NotificationsResponse>, ApiError> {
+    let notifications = services::list_notifications(&state, user_id, &query)
+        .await
+        .map_err(|e| {
+            error!(error = %e, user_id = %user_id, "list notifications failed");
+            ApiError::internal(&e, "Failed to list notifications")
+        })?;
+    Ok(Json(notifications))
+}
+
+#[instrument(skip(state))]
+pub async fn mark_notification_read(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path(notification_id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    match services::mark_read(&state, user_id, notification_id).await {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Err(ApiError::NotFound("Notification not found".to_string())
+            .with_code(ErrorCode::NotificationNotFound)),
+        Err(e) => {
+            error!(error = %e, user_id = %user_id, notification_id = %notification_id, "mark notification read failed");
+            Err(ApiError::internal(&e, "Failed to mark notification read"))
+        }
+    }
+}
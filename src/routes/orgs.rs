@@ -0,0 +1,138 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    routing::{get, put},
+    Json, Router,
+};
+use tracing::{error, instrument};
+use uuid::Uuid;
+
+use crate::{
+    admin::{
+        dto::{AdminStats, AdminStatsQuery},
+        services as admin_services,
+    },
+    auth::jwt::{AdminUser, OrgAdmin},
+    db::AppState,
+    error::ApiError,
+    orgs::{
+        dto::{OrgMember, OrgMembershipRequest, OrgRequest, Organization},
+        repo,
+        services::{self, OrgError},
+    },
+};
+
+pub fn org_routes() -> Router<AppState> {
+    Router::new()
+        .route("/admin/orgs", get(list_orgs).post(create_org))
+        .route("/admin/orgs/:id", get(get_org))
+        .route("/admin/orgs/:id/members", get(list_org_members))
+        .route("/admin/users/:id/org", put(set_user_org))
+        .route("/org", get(get_own_org))
+        .route("/org/members", get(list_own_members))
+        .route("/org/stats", get(get_own_stats))
+}
+
+fn org_error(e: OrgError, user_id: Uuid) -> ApiError {
+    if let OrgError::Other(source) = &e {
+        error!(error = %source, user_id = %user_id, "organization request failed");
+        return ApiError::internal(source, "Failed to access organizations");
+    }
+    ApiError::from_status(e.status(), e.to_string()).with_code(e.code())
+}
+
+#[instrument(skip(state))]
+pub async fn list_orgs(
+    State(state): State<AppState>,
+    AdminUser(user_id): AdminUser,
+) -> Result<Json<Vec<Organization>>, ApiError> {
+    repo::list_orgs(&state.db)
+        .await
+        .map(Json)
+        .map_err(|e| org_error(e.into(), user_id))
+}
+
+#[instrument(skip(state, payload))]
+pub async fn create_org(
+    State(state): State<AppState>,
+    AdminUser(user_id): AdminUser,
+    Json(payload): Json<OrgRequest>,
+) -> Result<(StatusCode, Json<Organization>), ApiError> {
+    services::create_org(&state, payload)
+        .await
+        .map(|org| (StatusCode::CREATED, Json(org)))
+        .map_err(|e| org_error(e, user_id))
+}
+
+#[instrument(skip(state))]
+pub async fn get_org(
+    State(state): State<AppState>,
+    AdminUser(user_id): AdminUser,
+    Path(org_id): Path<Uuid>,
+) -> Result<Json<Organization>, ApiError> {
+    services::get_org(&state, org_id)
+        .await
+        .map(Json)
+        .map_err(|e| org_error(e, user_id))
+}
+
+#[instrument(skip(state))]
+pub async fn list_org_members(
+    State(state): State<AppState>,
+    AdminUser(user_id): AdminUser,
+    Path(org_id): Path<Uuid>,
+) -> Result<Json<Vec<OrgMember>>, ApiError> {
+    services::members(&state, org_id)
+        .await
+        .map(Json)
+        .map_err(|e| org_error(e, user_id))
+}
+
+#[instrument(skip(state, payload))]
+pub async fn set_user_org(
+    State(state): State<AppState>,
+    AdminUser(admin_id): AdminUser,
+    Path(user_id): Path<Uuid>,
+    Json(payload): Json<OrgMembershipRequest>,
+) -> Result<StatusCode, ApiError> {
+    services::set_membership(&state, user_id, payload)
+        .await
+        .map(|()| StatusCode::NO_CONTENT)
+        .map_err(|e| org_error(e, admin_id))
+}
+
+#[instrument(skip(state, admin), fields(user_id = %admin.user_id))]
+pub async fn get_own_org(
+    State(state): State<AppState>,
+    admin: OrgAdmin,
+) -> Result<Json<Organization>, ApiError> {
+    services::get_org(&state, admin.org_id)
+        .await
+        .map(Json)
+        .map_err(|e| org_error(e, admin.user_id))
+}
+
+#[instrument(skip(state, admin), fields(user_id = %admin.user_id))]
+pub async fn list_own_members(
+    State(state): State<AppState>,
+    admin: OrgAdmin,
+) -> Result<Json<Vec<OrgMember>>, ApiError> {
+    services::members(&state, admin.org_id)
+        .await
+        .map(Json)
+        .map_err(|e| org_error(e, admin.user_id))
+}
+
+/// The same numbers as `/admin/stats`, for the organization's members.
+#[instrument(skip(state, admin), fields(user_id = %admin.user_id))]
+pub async fn get_own_stats(
+    State(state): State<AppState>,
+    admin: OrgAdmin,
+    Query(query): Query<AdminStatsQuery>,
+) -> Result<Json<AdminStats>, ApiError> {
+    let days = admin_services::window_days(&query).map_err(ApiError::validation)?;
+    services::org_stats(&state, admin.org_id, days)
+        .await
+        .map(Json)
+        .map_err(|e| org_error(e, admin.user_id))
+}
@@ -0,0 +1,61 @@
+use axum::{
+    body::Body,
+    extract::{Path, State},
+    http::header,
+    response::{IntoResponse, Response},
+    routing::get,
+    Router,
+};
+use tracing::{error, instrument};
+use uuid::Uuid;
+
+use crate::{
+    auth::jwt::AuthUser,
+    db::AppState,
+    error::{ApiError, ErrorCode},
+    images::services,
+};
+
+pub fn photo_routes() -> Router<AppState> {
+    Router::new().route("/photos/:id/content", get(get_photo_content))
+}
+
+/// Streams a photo through the API for clients that can't reach presigned
+/// storage URLs directly.
+#[instrument(skip(state))]
+pub async fn get_photo_content(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path(photo_id): Path<Uuid>,
+) -> Result<Response, ApiError> {
+    let object = match services::open_photo_content(&state, user_id, photo_id).await {
+        Ok(Some(object)) => object,
+        Ok(None) => {
+            return Err(ApiError::NotFound("Photo not found".to_string())
+                .with_code(ErrorCode::PhotoNotFound))
+        }
+        Err(e) => {
+            error!(error = %e, user_id = %user_id, photo_id = %photo_id, "open photo failed");
+            return Err(ApiError::internal(&e, "Failed to load photo"));
+        }
+    };
+
+    let content_type = object
+        .content_type
+        .unwrap_or_else(|| "application/octet-stream".to_string());
+    let mut response = (
+        [
+            (header::CONTENT_TYPE, content_type),
+            (header::CACHE_CONTROL, "private, max-age=300".to_string()),
+            (header::X_CONTENT_TYPE_OPTIONS, "nosniff".to_string()),
+        ],
+        Body::from_stream(object.body),
+    )
+        .into_response();
+    if let Some(len) = object.content_length {
+        response
+            .headers_mut()
+            .insert(header::CONTENT_LENGTH, len.into());
+    }
+    Ok(response)
+}
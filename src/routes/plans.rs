@@ -0,0 +1,111 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    routing::{delete, get, post, put},
+    Json, Router,
+};
+use time::OffsetDateTime;
+use tracing::{error, instrument};
+use uuid::Uuid;
+
+use crate::{
+    auth::jwt::AuthUser,
+    db::AppState,
+    error::ApiError,
+    plans::{
+        dto::{CreatePlanRequest, MealPlan, SlotRequest},
+        services::{self, PlanError},
+    },
+};
+
+pub fn plan_routes() -> Router<AppState> {
+    Router::new()
+        .route("/plans", post(create_plan))
+        .route("/plans/current", get(get_current_plan))
+        .route("/plans/:id", get(get_plan).delete(delete_plan))
+        .route("/plans/:id/slots", put(set_slot))
+        .route("/plans/:id/slots/:slot_id", delete(delete_slot))
+}
+
+fn plan_error(e: PlanError, user_id: Uuid) -> ApiError {
+    if let PlanError::Other(source) = &e {
+        error!(error = %source, user_id = %user_id, "meal plan request failed");
+        return ApiError::internal(source, "Failed to access meal plans");
+    }
+    ApiError::from_status(e.status(), e.to_string()).with_code(e.code())
+}
+
+#[instrument(skip(state, payload))]
+pub async fn create_plan(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Json(payload): Json<CreatePlanRequest>,
+) -> Result<(StatusCode, Json<MealPlan>), ApiError> {
+    services::create_plan(&state, user_id, payload.week_start())
+        .await
+        .map(|plan| (StatusCode::CREATED, Json(plan)))
+        .map_err(|e| plan_error(e, user_id))
+}
+
+#[instrument(skip(state))]
+pub async fn get_current_plan(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+) -> Result<Json<MealPlan>, ApiError> {
+    let today = OffsetDateTime::now_utc().date();
+    services::plan_for_week_of(&state, user_id, today)
+        .await
+        .map_err(|e| plan_error(e.into(), user_id))?
+        .map(Json)
+        .ok_or_else(|| plan_error(PlanError::NotFound, user_id))
+}
+
+#[instrument(skip(state))]
+pub async fn get_plan(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path(plan_id): Path<Uuid>,
+) -> Result<Json<MealPlan>, ApiError> {
+    services::get_plan(&state, user_id, plan_id)
+        .await
+        .map(Json)
+        .map_err(|e| plan_error(e, user_id))
+}
+
+#[instrument(skip(state))]
+pub async fn delete_plan(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path(plan_id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    match services::delete_plan(&state, user_id, plan_id).await {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Err(plan_error(PlanError::NotFound, user_id)),
+        Err(e) => Err(plan_error(e.into(), user_id)),
+    }
+}
+
+#[instrument(skip(state, payload))]
+pub async fn set_slot(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path(plan_id): Path<Uuid>,
+    Json(payload): Json<SlotRequest>,
+) -> Result<Json<MealPlan>, ApiError> {
+    services::set_slot(&state, user_id, plan_id, payload)
+        .await
+        .map(Json)
+        .map_err(|e| plan_error(e, user_id))
+}
+
+#[instrument(skip(state))]
+pub async fn delete_slot(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path((plan_id, slot_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, ApiError> {
+    services::delete_slot(&state, user_id, plan_id, slot_id)
+        .await
+        .map(|()| StatusCode::NO_CONTENT)
+        .map_err(|e| plan_error(e, user_id))
+}
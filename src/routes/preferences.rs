@@ -0,0 +1,51 @@
+use axum::{extract::State, routing::get, Json, Router};
+use tracing::{error, instrument};
+use uuid::Uuid;
+
+use crate::{
+    auth::jwt::AuthUser,
+    db::AppState,
+    error::ApiError,
+    preferences::{
+        dto::{PreferencesResponse, UpdatePreferencesRequest},
+        services::{self, PreferencesError},
+    },
+};
+
+pub fn preference_routes() -> Router<AppState> {
+    Router::new().route(
+        "/me/preferences",
+        get(get_preferences).patch(update_preferences),
+    )
+}
+
+fn preferences_error(e: PreferencesError, user_id: Uuid) -> ApiError {
+    if let PreferencesError::Other(source) = &e {
+        error!(error = %source, user_id = %user_id, "preferences request failed");
+        return ApiError::internal(source, "Failed to access preferences");
+    }
+    ApiError::from_status(e.status(), e.to_string()).with_code(e.code())
+}
+
+#[instrument(skip(state))]
+pub async fn get_preferences(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+) -> Result<Json<PreferencesResponse>, ApiError> {
+    services::get_preferences(&state, user_id)
+        .await
+        .map(Json)
+        .map_err(|e| preferences_error(e, user_id))
+}
+
+#[instrument(skip(state, payload))]
+pub async fn update_preferences(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Json(payload): Json<UpdatePreferencesRequest>,
+) -> Result<Json<PreferencesResponse>, ApiError> {
+    services::update_preferences(&state, user_id, payload)
+        .await
+        .map(Json)
+        .map_err(|e| preferences_error(e, user_id))
+}
@@ -0,0 +1,237 @@
+use axum::{
+    extract::{Multipart, Path, Query, State},
+    http::StatusCode,
+    routing::{delete, get, post},
+    Json, Router,
+};
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+use tracing::{error, info, instrument, warn};
+use uuid::Uuid;
+
+use crate::{
+    app::body_limit,
+    auth::jwt::AuthUser,
+    config::UploadConfig,
+    db::AppState,
+    error::{ApiError, ErrorCode},
+    images::{
+        dto::NormalizedImage,
+        services::{normalize_image, ImageError},
+    },
+    progress::{
+        dto::{
+            MeasurementEntry, MeasurementsResponse, NewMeasurementRequest, NewProgressPhoto,
+            ProgressPhotoResponse, ProgressPhotoUrl, ProgressRangeQuery,
+        },
+        services,
+    },
+    routes::meals::BODY_OVERHEAD_BYTES,
+};
+
+pub fn progress_routes() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/measurements",
+            get(list_measurements).post(create_measurement),
+        )
+        .route("/measurements/:id", delete(delete_measurement))
+        .route("/progress-photos", get(list_progress_photos))
+        .route("/progress-photos/:id", delete(delete_progress_photo))
+        .route("/progress-photos/:id/url", get(progress_photo_url))
+}
+
+/// Progress photo upload, limited to one image of
+/// [`UploadConfig::max_image_bytes`].
+pub fn progress_upload_routes(state: &AppState) -> Router<AppState> {
+    let limit = state.config.uploads.max_image_bytes + BODY_OVERHEAD_BYTES;
+    Router::new().route(
+        "/progress-photos",
+        post(upload_progress_photo).layer(body_limit(limit)),
+    )
+}
+
+fn photo_not_found() -> ApiError {
+    ApiError::NotFound("Progress photo not found".to_string())
+        .with_code(ErrorCode::ProgressPhotoNotFound)
+}
+
+#[instrument(skip(state, payload))]
+pub async fn create_measurement(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Json(payload): Json<NewMeasurementRequest>,
+) -> Result<(StatusCode, Json<MeasurementEntry>), ApiError> {
+    let input = payload.normalized().map_err(ApiError::validation)?;
+    let entry = services::create_measurement(&state, user_id, &input)
+        .await
+        .map_err(|e| {
+            error!(error = %e, user_id = %user_id, "create measurement failed");
+            ApiError::Internal("Failed to save measurement".to_string())
+        })?;
+    Ok((StatusCode::CREATED, Json(entry)))
+}
+
+#[instrument(skip(state))]
+pub async fn list_measurements(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Query(query): Query<ProgressRangeQuery>,
+) -> Result<Json<MeasurementsResponse>, ApiError> {
+    query.validate().map_err(ApiError::validation)?;
+    let measurements = services::list_measurements(&state, user_id, &query)
+        .await
+        .map_err(|e| {
+            error!(error = %e, user_id = %user_id, "list measurements failed");
+            ApiError::Internal("Failed to list measurements".to_string())
+        })?;
+    Ok(Json(measurements))
+}
+
+#[instrument(skip(state))]
+pub async fn delete_measurement(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path(measurement_id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    match services::delete_measurement(&state, user_id, measurement_id).await {
+        Ok(true) => {
+            info!(user_id = %user_id, measurement_id = %measurement_id, "measurement deleted");
+            Ok(StatusCode::NO_CONTENT)
+        }
+        Ok(false) => Err(ApiError::NotFound("Measurement not found".to_string())
+            .with_code(ErrorCode::MeasurementNotFound)),
+        Err(e) => {
+            error!(error = %e, user_id = %user_id, measurement_id = %measurement_id, "delete measurement failed");
+            Err(ApiError::Internal(
+                "Failed to delete measurement".to_string(),
+            ))
+        }
+    }
+}
+
+/// `multipart/form-data` with one file part `photo` and optional text fields
+/// `taken_at` (RFC 3339) and `note`.
+#[instrument(skip(state, multipart))]
+pub async fn upload_progress_photo(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    multipart: Multipart,
+) -> Result<(StatusCode, Json<ProgressPhotoResponse>), ApiError> {
+    let (input, image) = read_photo_form(multipart, &state.config.uploads)
+        .await
+        .inspect_err(|e| {
+            warn!(user_id = %user_id, status = %e.status(), msg = %e.message(), "invalid progress photo upload");
+        })?;
+    let input = input.normalized().map_err(ApiError::validation)?;
+    let Some(image) = image else {
+        return Err(ApiError::BadRequest("No photo provided".to_string())
+            .with_code(ErrorCode::UploadNoImages));
+    };
+    let photo = services::upload_photo(&state, user_id, image, input)
+        .await
+        .map_err(|e| {
+            error!(error = %e, user_id = %user_id, "upload progress photo failed");
+            ApiError::internal(&e, "Failed to save progress photo")
+        })?;
+    info!(user_id = %user_id, photo_id = %photo.id, "progress photo uploaded");
+    Ok((StatusCode::CREATED, Json(photo)))
+}
+
+/// Reads the form, streaming the file part in chunks so an oversized image is
+/// rejected as soon as it crosses the limit.
+async fn read_photo_form(
+    mut multipart: Multipart,
+    limits: &UploadConfig,
+) -> Result<(NewProgressPhoto, Option<NormalizedImage>), ApiError> {
+    let mut input = NewProgressPhoto::default();
+    let mut image = None;
+
+    while let Some(mut field) = multipart.next_field().await.map_err(ApiError::from)? {
+        let name = field.name().unwrap_or_default().to_string();
+        if field.file_name().is_some() || name == "photo" {
+            if image.is_some() {
+                return Err(ImageError::TooMany { max: 1 }.into());
+            }
+            let content_type = field.content_type().unwrap_or_default().to_string();
+            let mut bytes = Vec::new();
+            while let Some(chunk) = field.chunk().await.map_err(ApiError::from)? {
+                if bytes.len() + chunk.len() > limits.max_image_bytes {
+                    return Err(ImageError::TooLarge {
+                        index: 0,
+                        max: limits.max_image_bytes,
+                    }
+                    .into());
+                }
+                bytes.extend_from_slice(&chunk);
+            }
+            image = Some(normalize_image(0, &content_type, bytes, None, limits)?);
+            continue;
+        }
+
+        let value = field.text().await.map_err(ApiError::from)?;
+        match name.as_str() {
+            "note" => input.note = Some(value),
+            "taken_at" if !value.trim().is_empty() => {
+                input.taken_at = Some(
+                    OffsetDateTime::parse(value.trim(), &Rfc3339)
+                        .map_err(|_| ApiError::validation("taken_at must be RFC 3339"))?,
+                );
+            }
+            _ => {}
+        }
+    }
+    Ok((input, image))
+}
+
+#[instrument(skip(state))]
+pub async fn list_progress_photos(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Query(query): Query<ProgressRangeQuery>,
+) -> Result<Json<Vec<ProgressPhotoResponse>>, ApiError> {
+    query.validate().map_err(ApiError::validation)?;
+    let photos = services::list_photos(&state, user_id, &query)
+        .await
+        .map_err(|e| {
+            error!(error = %e, user_id = %user_id, "list progress photos failed");
+            ApiError::internal(&e, "Failed to list progress photos")
+        })?;
+    Ok(Json(photos))
+}
+
+#[instrument(skip(state))]
+pub async fn progress_photo_url(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path(photo_id): Path<Uuid>,
+) -> Result<Json<ProgressPhotoUrl>, ApiError> {
+    services::photo_url(&state, user_id, photo_id)
+        .await
+        .map_err(|e| {
+            error!(error = %e, user_id = %user_id, photo_id = %photo_id, "presign progress photo failed");
+            ApiError::internal(&e, "Failed to presign progress photo")
+        })?
+        .map(Json)
+        .ok_or_else(photo_not_found)
+}
+
+#[instrument(skip(state))]
+pub async fn delete_progress_photo(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path(photo_id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    match services::delete_photo(&state, user_id, photo_id).await {
+        Ok(true) => {
+            info!(user_id = %user_id, photo_id = %photo_id, "progress photo deleted");
+            Ok(StatusCode::NO_CONTENT)
+        }
+        Ok(false) => Err(photo_not_found()),
+        Err(e) => {
+            error!(error = %e, user_id = %user_id, photo_id = %photo_id, "delete progress photo failed");
+            Err(ApiError::Internal(
+                "Failed to delete progress photo".to_string(),
+            ))
+        }
+    }
+}
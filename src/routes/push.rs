@@ -0,0 +1,99 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    routing::{delete, get},
+    Json, Router,
+};
+use tracing::{error, instrument};
+use uuid::Uuid;
+
+use crate::{
+    auth::jwt::AuthUser,
+    db::AppState,
+    error::ApiError,
+    push::{
+        dto::{
+            DeviceResponse, NotificationSettingsResponse, RegisterDeviceRequest,
+            UpdateNotificationSettingsRequest,
+        },
+        services::{self, NotificationError},
+    },
+};
+
+pub fn push_routes() -> Router<AppState> {
+    Router::new()
+        .route("/me/devices", get(list_devices).post(register_device))
+        .route("/me/devices/:id", delete(delete_device))
+        .route(
+            "/me/notification-preferences",
+            get(get_preferences).patch(update_preferences),
+        )
+}
+
+fn notification_error(e: NotificationError, user_id: Uuid) -> ApiError {
+    if let NotificationError::Other(source) = &e {
+        error!(error = %source, user_id = %user_id, "notification request failed");
+        return ApiError::internal(source, "Failed to access notification settings");
+    }
+    ApiError::from_status(e.status(), e.to_string()).with_code(e.code())
+}
+
+/// Registers the push token of an app install. Registering a known token
+/// again refreshes it, and moves it over if another account had it.
+#[instrument(skip(state, payload))]
+pub async fn register_device(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Json(payload): Json<RegisterDeviceRequest>,
+) -> Result<(StatusCode, Json<DeviceResponse>), ApiError> {
+    services::register_device(&state, user_id, payload)
+        .await
+        .map(|device| (StatusCode::CREATED, Json(device)))
+        .map_err(|e| notification_error(e, user_id))
+}
+
+#[instrument(skip(state))]
+pub async fn list_devices(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+) -> Result<Json<Vec<DeviceResponse>>, ApiError> {
+    services::list_devices(&state, user_id)
+        .await
+        .map(Json)
+        .map_err(|e| notification_error(e, user_id))
+}
+
+#[instrument(skip(state))]
+pub async fn delete_device(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path(device_id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    services::delete_device(&state, user_id, device_id)
+        .await
+        .map(|()| StatusCode::NO_CONTENT)
+        .map_err(|e| notification_error(e, user_id))
+}
+
+#[instrument(skip(state))]
+pub async fn get_preferences(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+) -> Result<Json<NotificationSettingsResponse>, ApiError> {
+    services::get_preferences(&state, user_id)
+        .await
+        .map(Json)
+        .map_err(|e| notification_error(e, user_id))
+}
+
+#[instrument(skip(state, payload))]
+pub async fn update_preferences(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Json(payload): Json<UpdateNotificationSettingsRequest>,
+) -> Result<Json<NotificationSettingsResponse>, ApiError> {
+    services::update_preferences(&state, user_id, payload)
+        .await
+        .map(Json)
+        .map_err(|e| notification_error(e, user_id))
+}
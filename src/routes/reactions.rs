@@ -0,0 +1,59 @@
+use axum::{
+    extract::{Path, State},
+    routing::{delete, post},
+    Json, Router,
+};
+use tracing::{error, instrument};
+use uuid::Uuid;
+
+use crate::{
+    auth::jwt::AuthUser,
+    db::AppState,
+    error::ApiError,
+    reactions::{
+        dto::{AddReactionRequest, MealReactions, Reaction},
+        services::{self, ReactionError},
+    },
+};
+
+pub fn reaction_routes() -> Router<AppState> {
+    Router::new()
+        .route("/meals/:id/reactions", post(add_reaction))
+        .route("/meals/:id/reactions/:reaction", delete(remove_reaction))
+}
+
+fn reaction_error(e: ReactionError, user_id: Uuid) -> ApiError {
+    if let ReactionError::Other(source) = &e {
+        error!(error = %source, user_id = %user_id, "meal reaction request failed");
+        return ApiError::internal(source, "Failed to update reactions");
+    }
+    ApiError::from_status(e.status(), e.to_string()).with_code(e.code())
+}
+
+/// Reacts to an own or household meal; reacting twice with the same emoji
+/// is a no-op.
+#[instrument(skip(state, payload))]
+pub async fn add_reaction(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path(meal_id): Path<Uuid>,
+    Json(payload): Json<AddReactionRequest>,
+) -> Result<Json<MealReactions>, ApiError> {
+    services::add_reaction(&state, user_id, meal_id, payload.emoji)
+        .await
+        .map(Json)
+        .map_err(|e| reaction_error(e, user_id))
+}
+
+#[instrument(skip(state))]
+pub async fn remove_reaction(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path((meal_id, reaction)): Path<(Uuid, String)>,
+) -> Result<Json<MealReactions>, ApiError> {
+    let reaction: Reaction = reaction.parse().map_err(ApiError::validation)?;
+    services::remove_reaction(&state, user_id, meal_id, reaction)
+        .await
+        .map(Json)
+        .map_err(|e| reaction_error(e, user_id))
+}
@@ -0,0 +1,137 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    routing::{get, post},
+    Json, Router,
+};
+use tracing::{error, instrument};
+use uuid::Uuid;
+
+use crate::{
+    auth::jwt::AuthUser,
+    db::AppState,
+    error::ApiError,
+    meal_items::services::ItemError,
+    meals::dto::MealDetails,
+    preferences::services as preferences,
+    recipes::{
+        dto::{CookRecipeRequest, Recipe, RecipeRequest, RecipeSummary},
+        services::{self, RecipeError},
+    },
+    units::UnitsQuery,
+};
+
+pub fn recipe_routes() -> Router<AppState> {
+    Router::new()
+        .route("/recipes", get(list_recipes).post(create_recipe))
+        .route(
+            "/recipes/:id",
+            get(get_recipe).put(replace_recipe).delete(delete_recipe),
+        )
+        .route("/recipes/:id/meals", post(cook_recipe))
+}
+
+/// Logs server-side failures; client errors pass through with their message.
+fn recipe_error(e: RecipeError, user_id: Uuid) -> ApiError {
+    let status = e.status();
+    match &e {
+        RecipeError::Ingredient(ItemError::Upstream(source)) => {
+            error!(error = %source, user_id = %user_id, "ingredient lookup failed")
+        }
+        RecipeError::Other(source) | RecipeError::Ingredient(ItemError::Other(source)) => {
+            error!(error = %source, user_id = %user_id, "recipe request failed");
+            return ApiError::internal(source, "Failed to access recipes");
+        }
+        _ => {}
+    }
+    ApiError::from_status(status, e.to_string()).with_code(e.code())
+}
+
+#[instrument(skip(state))]
+pub async fn list_recipes(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+) -> Result<Json<Vec<RecipeSummary>>, ApiError> {
+    services::list_recipes(&state, user_id)
+        .await
+        .map(Json)
+        .map_err(|e| recipe_error(e.into(), user_id))
+}
+
+#[instrument(skip(state, payload))]
+pub async fn create_recipe(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Query(query): Query<UnitsQuery>,
+    Json(payload): Json<RecipeRequest>,
+) -> Result<(StatusCode, Json<Recipe>), ApiError> {
+    let input = payload.normalized().map_err(ApiError::validation)?;
+    let units = preferences::units(&state, user_id, query.units)
+        .await
+        .map_err(|e| recipe_error(e.into(), user_id))?;
+    services::create_recipe(&state, user_id, input)
+        .await
+        .map(|recipe| (StatusCode::CREATED, Json(recipe.in_units(units))))
+        .map_err(|e| recipe_error(e, user_id))
+}
+
+#[instrument(skip(state))]
+pub async fn get_recipe(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path(recipe_id): Path<Uuid>,
+    Query(query): Query<UnitsQuery>,
+) -> Result<Json<Recipe>, ApiError> {
+    let units = preferences::units(&state, user_id, query.units)
+        .await
+        .map_err(|e| recipe_error(e.into(), user_id))?;
+    services::get_recipe(&state, user_id, recipe_id)
+        .await
+        .map(|recipe| Json(recipe.in_units(units)))
+        .map_err(|e| recipe_error(e, user_id))
+}
+
+#[instrument(skip(state, payload))]
+pub async fn replace_recipe(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path(recipe_id): Path<Uuid>,
+    Query(query): Query<UnitsQuery>,
+    Json(payload): Json<RecipeRequest>,
+) -> Result<Json<Recipe>, ApiError> {
+    let input = payload.normalized().map_err(ApiError::validation)?;
+    let units = preferences::units(&state, user_id, query.units)
+        .await
+        .map_err(|e| recipe_error(e.into(), user_id))?;
+    services::replace_recipe(&state, user_id, recipe_id, input)
+        .await
+        .map(|recipe| Json(recipe.in_units(units)))
+        .map_err(|e| recipe_error(e, user_id))
+}
+
+#[instrument(skip(state))]
+pub async fn delete_recipe(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path(recipe_id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    match services::delete_recipe(&state, user_id, recipe_id).await {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Err(recipe_error(RecipeError::NotFound, user_id)),
+        Err(e) => Err(recipe_error(e.into(), user_id)),
+    }
+}
+
+#[instrument(skip(state, payload))]
+pub async fn cook_recipe(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path(recipe_id): Path<Uuid>,
+    Json(payload): Json<CookRecipeRequest>,
+) -> Result<(StatusCode, Json<MealDetails>), ApiError> {
+    let (servings, meal) = payload.normalized().map_err(ApiError::validation)?;
+    services::cook_recipe(&state, user_id, recipe_id, servings, meal)
+        .await
+        .map(|meal| (StatusCode::CREATED, Json(meal)))
+        .map_err(|e| recipe_error(e, user_id))
+}
@@ -0,0 +1,35 @@
+use axum::{
+    extract::{Query, State},
+    routing::get,
+    Json, Router,
+};
+use tracing::{error, instrument};
+
+use crate::{
+    auth::jwt::AuthUser,
+    db::AppState,
+    error::ApiError,
+    recommendations::{
+        dto::{RecommendationsQuery, RecommendationsResponse},
+        services,
+    },
+};
+
+pub fn recommendation_routes() -> Router<AppState> {
+    Router::new().route("/recommendations", get(get_recommendations))
+}
+
+#[instrument(skip(state))]
+pub async fn get_recommendations(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Query(query): Query<RecommendationsQuery>,
+) -> Result<Json<RecommendationsResponse>, ApiError> {
+    let recommendations = services::recommend(&state, user_id, &query)
+        .await
+        .map_err(|e| {
+            error!(error = %e, user_id = %user_id, "load recommendations failed");
+            ApiError::Internal("Failed to load recommendations".to_string())
+        })?;
+    Ok(Json(recommendations))
+}
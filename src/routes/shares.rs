@@ -0,0 +1,131 @@
+use axum::{
+    body::Body,
+    extract::{Path, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{delete, get, post},
+    Json, Router,
+};
+use tracing::{error, instrument};
+use uuid::Uuid;
+
+use crate::{
+    auth::jwt::AuthUser,
+    db::AppState,
+    error::{ApiError, ErrorCode},
+    shares::{
+        dto::{CreateShareRequest, ShareResponse, SharedMeal},
+        services::{self, ShareError},
+    },
+};
+
+pub fn share_routes() -> Router<AppState> {
+    Router::new()
+        .route("/meals/:id/share", post(create_share))
+        .route("/meals/:id/shares", get(list_shares))
+        .route("/meals/:id/shares/:share_id", delete(revoke_share))
+        .route("/shared/:token", get(get_shared_meal))
+        .route("/shared/:token/photos/:index", get(get_shared_photo))
+}
+
+fn share_error(e: ShareError, user_id: Uuid) -> ApiError {
+    if let ShareError::Other(source) = &e {
+        error!(error = %source, user_id = %user_id, "meal share request failed");
+        return ApiError::internal(source, "Failed to access meal shares");
+    }
+    ApiError::from_status(e.status(), e.to_string()).with_code(e.code())
+}
+
+/// Creates a revocable public link to a meal.
+#[instrument(skip(state, payload))]
+pub async fn create_share(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path(meal_id): Path<Uuid>,
+    payload: Option<Json<CreateShareRequest>>,
+) -> Result<(StatusCode, Json<ShareResponse>), ApiError> {
+    let input = payload.map(|Json(p)| p).unwrap_or_default();
+    services::create_share(&state, user_id, meal_id, input)
+        .await
+        .map(|share| (StatusCode::CREATED, Json(share)))
+        .map_err(|e| share_error(e, user_id))
+}
+
+#[instrument(skip(state))]
+pub async fn list_shares(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path(meal_id): Path<Uuid>,
+) -> Result<Json<Vec<ShareResponse>>, ApiError> {
+    services::list_shares(&state, user_id, meal_id)
+        .await
+        .map(Json)
+        .map_err(|e| share_error(e, user_id))
+}
+
+#[instrument(skip(state))]
+pub async fn revoke_share(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path((meal_id, share_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, ApiError> {
+    services::revoke_share(&state, user_id, meal_id, share_id)
+        .await
+        .map(|()| StatusCode::NO_CONTENT)
+        .map_err(|e| share_error(e, user_id))
+}
+
+/// Public, unauthenticated view of a shared meal.
+#[instrument(skip(state, token))]
+pub async fn get_shared_meal(
+    State(state): State<AppState>,
+    Path(token): Path<String>,
+) -> Result<Json<SharedMeal>, ApiError> {
+    match services::shared_meal(&state, &token).await {
+        Ok(Some(meal)) => Ok(Json(meal)),
+        Ok(None) => Err(ApiError::NotFound("Shared meal not found".to_string())
+            .with_code(ErrorCode::ShareNotFound)),
+        Err(e) => {
+            error!(error = %e, "load shared meal failed");
+            Err(ApiError::Internal("Failed to load shared meal".to_string()))
+        }
+    }
+}
+
+/// Streams a shared meal's photo, so the owner's storage keys stay private.
+#[instrument(skip(state, token))]
+pub async fn get_shared_photo(
+    State(state): State<AppState>,
+    Path((token, index)): Path<(String, usize)>,
+) -> Result<Response, ApiError> {
+    let object = match services::open_shared_photo(&state, &token, index).await {
+        Ok(Some(object)) => object,
+        Ok(None) => {
+            return Err(ApiError::NotFound("Photo not found".to_string())
+                .with_code(ErrorCode::PhotoNotFound))
+        }
+        Err(e) => {
+            error!(error = %e, "open shared photo failed");
+            return Err(ApiError::internal(&e, "Failed to load photo"));
+        }
+    };
+
+    let content_type = object
+        .content_type
+        .unwrap_or_else(|| "application/octet-stream".to_string());
+    let mut response = (
+        [
+            (header::CONTENT_TYPE, content_type),
+            (header::CACHE_CONTROL, "public, max-age=300".to_string()),
+            (header::X_CONTENT_TYPE_OPTIONS, "nosniff".to_string()),
+        ],
+        Body::from_stream(object.body),
+    )
+        .into_response();
+    if let Some(len) = object.content_length {
+        response
+            .headers_mut()
+            .insert(header::CONTENT_LENGTH, len.into());
+    }
+    Ok(response)
+}
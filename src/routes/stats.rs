@@ -0,0 +1,44 @@
+use axum::{
+    extract::{Query, State},
+    routing::get,
+    Json, Router,
+};
+use tracing::{error, instrument};
+
+use crate::{
+    auth::jwt::AuthUser,
+    db::AppState,
+    error::ApiError,
+    preferences::services as preferences,
+    stats::{
+        dto::{StatsQuery, UserStats},
+        services,
+    },
+};
+
+pub fn stats_routes() -> Router<AppState> {
+    Router::new().route("/me/stats", get(get_stats))
+}
+
+#[instrument(skip(state))]
+pub async fn get_stats(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Query(query): Query<StatsQuery>,
+) -> Result<Json<UserStats>, ApiError> {
+    let tz = match query.tz {
+        Some(tz) => tz.trim().to_string(),
+        None => preferences::timezone(&state, user_id).await.map_err(|e| {
+            error!(error = %e, user_id = %user_id, "load time zone failed");
+            ApiError::Internal("Failed to load stats".to_string())
+        })?,
+    };
+    match services::user_stats(&state, user_id, &tz).await {
+        Ok(Some(stats)) => Ok(Json(stats)),
+        Ok(None) => Err(ApiError::validation(format!("Unknown time zone: {}", tz))),
+        Err(e) => {
+            error!(error = %e, user_id = %user_id, "load stats failed");
+            Err(ApiError::Internal("Failed to load stats".to_string()))
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use axum::{
+    extract::{Query, State},
+    routing::get,
+    Json, Router,
+};
+use tracing::{error, instrument};
+
+use crate::{
+    auth::jwt::AuthUser,
+    db::AppState,
+    error::ApiError,
+    summary::{
+        dto::{
+            CalendarQuery, CalendarResponse, DailyQuery, DailySummary, TrendsQuery, TrendsResponse,
+        },
+        services,
+    },
+};
+
+pub fn summary_routes() -> Router<AppState> {
+    Router::new()
+        .route("/summary/daily", get(get_daily_summary))
+        .route("/summary/trends", get(get_trends))
+        .route("/summary/calendar", get(get_calendar))
+}
+
+#[instrument(skip(state))]
+pub async fn get_daily_summary(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Query(query): Query<DailyQuery>,
+) -> Result<Json<DailySummary>, ApiError> {
+    let summary = services::daily(&state, user_id, query.date)
+        .await
+        .map_err(|e| {
+            error!(error = %e, user_id = %user_id, "load daily summary failed");
+            ApiError::Internal("Failed to load daily summary".to_string())
+        })?;
+    Ok(Json(summary))
+}
+
+#[instrument(skip(state))]
+pub async fn get_trends(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Query(query): Query<TrendsQuery>,
+) -> Result<Json<TrendsResponse>, ApiError> {
+    let trends = services::trends(&state, user_id, query.range)
+        .await
+        .map_err(|e| {
+            error!(error = %e, user_id = %user_id, "load trends failed");
+            ApiError::Internal("Failed to load trends".to_string())
+        })?;
+    Ok(Json(trends))
+}
+
+#[instrument(skip(state))]
+pub async fn get_calendar(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Query(query): Query<CalendarQuery>,
+) -> Result<Json<CalendarResponse>, ApiError> {
+    let month = query.first_day().map_err(ApiError::validation)?;
+    let calendar = services::calendar(&state, user_id, month)
+        .await
+        .map_err(|e| {
+            error!(error = %e, user_id = %user_id, "load calendar failed");
+            ApiError::Internal("Failed to load calendar".to_string())
+        })?;
+    Ok(Json(calendar))
+}
@@ -0,0 +1,53 @@
+use axum::{
+    extract::{Query, State},
+    routing::get,
+    Json, Router,
+};
+use tracing::{error, instrument};
+use uuid::Uuid;
+
+use crate::{
+    auth::jwt::AuthUser,
+    db::AppState,
+    error::ApiError,
+    sync::{
+        dto::{PullQuery, PullResponse, PushRequest, PushResponse},
+        services::{self, SyncError},
+    },
+};
+
+pub fn sync_routes() -> Router<AppState> {
+    Router::new().route("/sync", get(pull).post(push))
+}
+
+fn sync_error(e: SyncError, user_id: Uuid) -> ApiError {
+    if let SyncError::Other(source) = &e {
+        error!(error = %source, user_id = %user_id, "sync failed");
+        return ApiError::internal(source, "Failed to sync meals");
+    }
+    ApiError::from_status(e.status(), e.to_string()).with_code(e.code())
+}
+
+#[instrument(skip(state))]
+pub async fn pull(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Query(query): Query<PullQuery>,
+) -> Result<Json<PullResponse>, ApiError> {
+    services::pull(&state, user_id, &query)
+        .await
+        .map(Json)
+        .map_err(|e| sync_error(e, user_id))
+}
+
+#[instrument(skip(state, payload))]
+pub async fn push(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Json(payload): Json<PushRequest>,
+) -> Result<Json<PushResponse>, ApiError> {
+    services::push(&state, user_id, payload)
+        .await
+        .map(Json)
+        .map_err(|e| sync_error(e, user_id))
+}
@@ -0,0 +1,84 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    routing::{delete, get, post},
+    Json, Router,
+};
+use tracing::{error, instrument};
+use uuid::Uuid;
+
+use crate::{
+    auth::jwt::AuthUser,
+    db::AppState,
+    error::ApiError,
+    meals::dto::MealDetails,
+    templates::{
+        dto::{MealTemplate, SaveTemplateRequest},
+        services::{self, TemplateError},
+    },
+};
+
+pub fn template_routes() -> Router<AppState> {
+    Router::new()
+        .route("/meals/templates", get(list_templates))
+        .route("/meals/templates/:template_id", delete(delete_template))
+        .route("/meals/:id/save-as-template", post(save_as_template))
+        .route("/meals/from-template/:template_id", post(log_from_template))
+}
+
+fn template_error(e: TemplateError, user_id: Uuid) -> ApiError {
+    if let TemplateError::Other(source) = &e {
+        error!(error = %source, user_id = %user_id, "meal template request failed");
+        return ApiError::internal(source, "Failed to access meal templates");
+    }
+    ApiError::from_status(e.status(), e.to_string()).with_code(e.code())
+}
+
+#[instrument(skip(state))]
+pub async fn list_templates(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+) -> Result<Json<Vec<MealTemplate>>, ApiError> {
+    services::list_templates(&state, user_id)
+        .await
+        .map(Json)
+        .map_err(|e| template_error(e.into(), user_id))
+}
+
+#[instrument(skip(state, payload))]
+pub async fn save_as_template(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path(meal_id): Path<Uuid>,
+    payload: Option<Json<SaveTemplateRequest>>,
+) -> Result<(StatusCode, Json<MealTemplate>), ApiError> {
+    let input = payload.map(|Json(p)| p).unwrap_or_default();
+    services::save_template(&state, user_id, meal_id, input)
+        .await
+        .map(|template| (StatusCode::CREATED, Json(template)))
+        .map_err(|e| template_error(e, user_id))
+}
+
+#[instrument(skip(state))]
+pub async fn log_from_template(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path(template_id): Path<Uuid>,
+) -> Result<(StatusCode, Json<MealDetails>), ApiError> {
+    services::log_from_template(&state, user_id, template_id)
+        .await
+        .map(|meal| (StatusCode::CREATED, Json(meal)))
+        .map_err(|e| template_error(e, user_id))
+}
+
+#[instrument(skip(state))]
+pub async fn delete_template(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path(template_id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    services::delete_template(&state, user_id, template_id)
+        .await
+        .map(|()| StatusCode::NO_CONTENT)
+        .map_err(|e| template_error(e, user_id))
+}
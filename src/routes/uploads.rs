@@ -0,0 +1,195 @@
+use axum::{
+    body::Bytes,
+    extract::{Path, Query, State},
+    http::{header::CONTENT_TYPE, HeaderMap, StatusCode},
+    routing::{delete, get, post, put},
+    Json, Router,
+};
+use tracing::{error, info, instrument, warn};
+use uuid::Uuid;
+
+use crate::{
+    app::body_limit,
+    auth::jwt::AuthUser,
+    db::AppState,
+    error::{ApiError, ErrorCode},
+    routes::meals::BODY_OVERHEAD_BYTES,
+    uploads::{
+        dto::{
+            ChunkedUploadResponse, PhotoUploadQuery, SessionPhoto, StartChunkedUploadRequest,
+            UploadSessionResponse, UploadedPart,
+        },
+        services::{self, UploadSessionError, MAX_PART_BYTES},
+    },
+};
+
+pub fn upload_routes() -> Router<AppState> {
+    Router::new()
+        .route("/uploads/sessions", post(create_session))
+        .route("/uploads/sessions/:id", get(get_session))
+        .route(
+            "/uploads/sessions/:id/photos/:position",
+            delete(delete_session_photo),
+        )
+        .route(
+            "/uploads/sessions/:id/photos/:position/chunked",
+            post(start_chunked_upload),
+        )
+        .route("/uploads/chunked/:id", get(get_chunked_upload))
+        .route(
+            "/uploads/chunked/:id/complete",
+            post(complete_chunked_upload),
+        )
+}
+
+/// Session photo upload, limited to one image of
+/// [`crate::config::UploadConfig::max_image_bytes`], and chunked upload
+/// parts, limited to [`MAX_PART_BYTES`].
+pub fn upload_photo_routes(state: &AppState) -> Router<AppState> {
+    let limit = state.config.uploads.max_image_bytes + BODY_OVERHEAD_BYTES;
+    Router::new()
+        .route(
+            "/uploads/sessions/:id/photos/:position",
+            put(upload_session_photo).layer(body_limit(limit)),
+        )
+        .route(
+            "/uploads/chunked/:id/parts/:number",
+            put(append_chunked_part).layer(body_limit(MAX_PART_BYTES + BODY_OVERHEAD_BYTES)),
+        )
+}
+
+pub(crate) fn upload_session_error(e: UploadSessionError, user_id: Uuid) -> ApiError {
+    match e {
+        UploadSessionError::Other(source) => {
+            error!(error = %source, user_id = %user_id, "upload session request failed");
+            ApiError::internal(&source, "Failed to process upload session")
+        }
+        UploadSessionError::Duplicate(duplicate) => duplicate.into(),
+        e => {
+            warn!(user_id = %user_id, error = %e, "upload session request rejected");
+            ApiError::from_status(e.status(), e.to_string()).with_code(e.code())
+        }
+    }
+}
+
+#[instrument(skip(state))]
+pub async fn create_session(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+) -> Result<(StatusCode, Json<UploadSessionResponse>), ApiError> {
+    let session = services::create_session(&state, user_id)
+        .await
+        .map_err(|e| {
+            error!(error = %e, user_id = %user_id, "create upload session failed");
+            ApiError::internal(&e, "Failed to create upload session")
+        })?;
+    Ok((StatusCode::CREATED, Json(session)))
+}
+
+#[instrument(skip(state))]
+pub async fn get_session(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path(session_id): Path<Uuid>,
+) -> Result<Json<UploadSessionResponse>, ApiError> {
+    services::get_session(&state, user_id, session_id)
+        .await
+        .map(Json)
+        .map_err(|e| upload_session_error(e, user_id))
+}
+
+/// The raw image is the request body, typed by its `Content-Type`.
+#[instrument(skip(state, headers, body))]
+pub async fn upload_session_photo(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path((session_id, position)): Path<(Uuid, i32)>,
+    Query(query): Query<PhotoUploadQuery>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Json<SessionPhoto>, ApiError> {
+    let content_type = headers
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default();
+    let photo = services::upload_photo(
+        &state,
+        user_id,
+        session_id,
+        position,
+        content_type,
+        body.to_vec(),
+        query.taken_at,
+    )
+    .await
+    .map_err(|e| upload_session_error(e, user_id))?;
+    info!(user_id = %user_id, session_id = %session_id, position, "session photo uploaded");
+    Ok(Json(photo))
+}
+
+#[instrument(skip(state))]
+pub async fn delete_session_photo(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path((session_id, position)): Path<(Uuid, i32)>,
+) -> Result<StatusCode, ApiError> {
+    match services::delete_photo(&state, user_id, session_id, position).await {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => {
+            Err(ApiError::NotFound("Photo not found".to_string())
+                .with_code(ErrorCode::PhotoNotFound))
+        }
+        Err(e) => Err(upload_session_error(e, user_id)),
+    }
+}
+
+#[instrument(skip(state, payload))]
+pub async fn start_chunked_upload(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path((session_id, position)): Path<(Uuid, i32)>,
+    Json(payload): Json<StartChunkedUploadRequest>,
+) -> Result<(StatusCode, Json<ChunkedUploadResponse>), ApiError> {
+    let upload = services::start_chunked_upload(&state, user_id, session_id, position, &payload)
+        .await
+        .map_err(|e| upload_session_error(e, user_id))?;
+    Ok((StatusCode::CREATED, Json(upload)))
+}
+
+#[instrument(skip(state))]
+pub async fn get_chunked_upload(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path(upload_id): Path<Uuid>,
+) -> Result<Json<ChunkedUploadResponse>, ApiError> {
+    services::get_chunked_upload(&state, user_id, upload_id)
+        .await
+        .map(Json)
+        .map_err(|e| upload_session_error(e, user_id))
+}
+
+/// The raw part bytes are the request body.
+#[instrument(skip(state, body))]
+pub async fn append_chunked_part(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path((upload_id, number)): Path<(Uuid, i32)>,
+    body: Bytes,
+) -> Result<Json<UploadedPart>, ApiError> {
+    services::append_part(&state, user_id, upload_id, number, body.to_vec())
+        .await
+        .map(Json)
+        .map_err(|e| upload_session_error(e, user_id))
+}
+
+#[instrument(skip(state))]
+pub async fn complete_chunked_upload(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path(upload_id): Path<Uuid>,
+) -> Result<Json<SessionPhoto>, ApiError> {
+    services::complete_chunked_upload(&state, user_id, upload_id)
+        .await
+        .map(Json)
+        .map_err(|e| upload_session_error(e, user_id))
+}
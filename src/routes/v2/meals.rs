@@ -0,0 +1,73 @@
+//! Meals in v2: one `POST /meals` for JSON and multipart bodies, picked by
+//! `Content-Type`, instead of the separate v1 `/meals/multipart`.
+
+use axum::{
+    extract::{DefaultBodyLimit, FromRequest, Multipart, Request, State},
+    http::{header::CONTENT_TYPE, StatusCode},
+    middleware::from_fn_with_state,
+    routing::{delete, get, post, put},
+    Json, Router,
+};
+
+use crate::{
+    auth::jwt::AuthUser,
+    billing,
+    db::AppState,
+    error::ApiError,
+    idempotency,
+    meals::dto::MealDetails,
+    routes::meals::{
+        self as v1, bulk_meals, delete_meal_nutrition, delete_meal_photo, get_meal,
+        get_meal_status, list_meal_photos, list_meals, put_meal_nutrition,
+    },
+};
+
+pub fn meal_routes() -> Router<AppState> {
+    Router::new()
+        .route("/meals", get(list_meals))
+        .route("/meals/bulk", post(bulk_meals))
+        .route("/meals/:id", get(get_meal))
+        .route("/meals/:id/status", get(get_meal_status))
+        .route(
+            "/meals/:id/nutrition",
+            put(put_meal_nutrition).delete(delete_meal_nutrition),
+        )
+        .route("/meals/:id/photos", get(list_meal_photos))
+        .route("/meals/:id/photos/:photo_id", delete(delete_meal_photo))
+}
+
+/// `POST /meals` under the larger of the two v1 upload limits; the image
+/// size limits still apply per image and per request.
+pub fn meal_upload_routes(state: &AppState) -> Router<AppState> {
+    Router::new().route(
+        "/meals",
+        post(create_meal).layer((
+            DefaultBodyLimit::max(v1::json_body_limit(&state.config.uploads)),
+            from_fn_with_state(state.clone(), idempotency::replay),
+            from_fn_with_state(state.clone(), billing::enforce_plan),
+        )),
+    )
+}
+
+pub async fn create_meal(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    req: Request,
+) -> Result<(StatusCode, Json<MealDetails>), ApiError> {
+    let is_multipart = req
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("multipart/form-data"));
+    if is_multipart {
+        let multipart = Multipart::from_request(req, &state)
+            .await
+            .map_err(|e| ApiError::from_status(e.status(), e.body_text()))?;
+        v1::create_meal_multipart(State(state), AuthUser(user_id), multipart).await
+    } else {
+        let payload = Json::from_request(req, &state)
+            .await
+            .map_err(|e| ApiError::from_status(e.status(), e.body_text()))?;
+        v1::create_meal(State(state), AuthUser(user_id), payload).await
+    }
+}
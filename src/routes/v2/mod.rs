@@ -0,0 +1,18 @@
+//! `/api/v2`: resource-oriented routes for changes v1 clients can't take.
+//! Routes move here one area at a time; the rest of the API stays on v1.
+
+pub mod meals;
+
+use axum::Router;
+
+use crate::db::AppState;
+
+/// Routes under the default body limit and request timeout.
+pub fn v2_routes() -> Router<AppState> {
+    Router::new().merge(meals::meal_routes())
+}
+
+/// Routes setting their own body limits.
+pub fn v2_upload_routes(state: &AppState) -> Router<AppState> {
+    Router::new().merge(meals::meal_upload_routes(state))
+}
@@ -0,0 +1,69 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    routing::{delete, get},
+    Json, Router,
+};
+use tracing::{error, instrument};
+use uuid::Uuid;
+
+use crate::{
+    auth::jwt::AuthUser,
+    db::AppState,
+    error::ApiError,
+    webhooks::{
+        dto::{CreateWebhookRequest, WebhookResponse},
+        services::{self, WebhookError},
+    },
+};
+
+pub fn webhook_routes() -> Router<AppState> {
+    Router::new()
+        .route("/me/webhooks", get(list_webhooks).post(create_webhook))
+        .route("/me/webhooks/:id", delete(delete_webhook))
+}
+
+fn webhook_error(e: WebhookError, user_id: Uuid) -> ApiError {
+    if let WebhookError::Other(source) = &e {
+        error!(error = %source, user_id = %user_id, "webhook request failed");
+        return ApiError::internal(source, "Failed to access webhooks");
+    }
+    ApiError::from_status(e.status(), e.to_string()).with_code(e.code())
+}
+
+/// Registers a URL to be notified of the user's events. The signing secret
+/// is only returned here.
+#[instrument(skip(state, payload))]
+pub async fn create_webhook(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Json(payload): Json<CreateWebhookRequest>,
+) -> Result<(StatusCode, Json<WebhookResponse>), ApiError> {
+    services::create_webhook(&state, user_id, payload)
+        .await
+        .map(|webhook| (StatusCode::CREATED, Json(webhook)))
+        .map_err(|e| webhook_error(e, user_id))
+}
+
+#[instrument(skip(state))]
+pub async fn list_webhooks(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+) -> Result<Json<Vec<WebhookResponse>>, ApiError> {
+    services::list_webhooks(&state, user_id)
+        .await
+        .map(Json)
+        .map_err(|e| webhook_error(e, user_id))
+}
+
+#[instrument(skip(state))]
+pub async fn delete_webhook(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path(webhook_id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    services::delete_webhook(&state, user_id, webhook_id)
+        .await
+        .map(|()| StatusCode::NO_CONTENT)
+        .map_err(|e| webhook_error(e, user_id))
+}
@@ -0,0 +1,82 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    routing::{delete, get},
+    Json, Router,
+};
+use tracing::{error, info, instrument};
+use uuid::Uuid;
+
+use crate::{
+    auth::jwt::AuthUser,
+    db::AppState,
+    error::{ApiError, ErrorCode},
+    weights::{
+        dto::{ListWeightsQuery, NewWeightRequest, WeightEntry, WeightsResponse},
+        services,
+    },
+};
+
+pub fn weight_routes() -> Router<AppState> {
+    Router::new()
+        .route("/weights", get(list_weights).post(create_weight))
+        .route("/weights/:id", delete(delete_weight))
+}
+
+#[instrument(skip(state, payload))]
+pub async fn create_weight(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Json(payload): Json<NewWeightRequest>,
+) -> Result<(StatusCode, Json<WeightEntry>), ApiError> {
+    let input = payload.normalized().map_err(ApiError::validation)?;
+    let entry = services::create_weight(&state, user_id, &input)
+        .await
+        .map_err(|e| {
+            error!(error = %e, user_id = %user_id, "create weight failed");
+            ApiError::Internal("Failed to save weight".to_string())
+        })?;
+    Ok((StatusCode::CREATED, Json(entry)))
+}
+
+#[instrument(skip(state))]
+pub async fn list_weights(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Query(query): Query<ListWeightsQuery>,
+) -> Result<Json<WeightsResponse>, ApiError> {
+    if let (Some(from), Some(to)) = (query.from, query.to) {
+        if from >= to {
+            return Err(ApiError::validation("from must be before to"));
+        }
+    }
+    let weights = services::list_weights(&state, user_id, &query)
+        .await
+        .map_err(|e| {
+            error!(error = %e, user_id = %user_id, "list weights failed");
+            ApiError::Internal("Failed to list weights".to_string())
+        })?;
+    Ok(Json(weights))
+}
+
+#[instrument(skip(state))]
+pub async fn delete_weight(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path(weight_id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    match services::delete_weight(&state, user_id, weight_id).await {
+        Ok(true) => {
+            info!(user_id = %user_id, weight_id = %weight_id, "weight deleted");
+            Ok(StatusCode::NO_CONTENT)
+        }
+        Ok(false) => {
+            Err(ApiError::NotFound("Weight not found".to_string())
+                .with_code(ErrorCode::WeightNotFound))
+        }
+        Err(e) => {
+            error!(error = %e, user_id = %user_id, weight_id = %weight_id, "delete weight failed");
+            Err(ApiError::Internal("Failed to delete weight".to_string()))
+        }
+    }
+}
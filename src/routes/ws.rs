@@ -0,0 +1,169 @@
+use std::time::Duration;
+
+use axum::{
+    extract::{
+        ws::{CloseFrame, Message, WebSocket, WebSocketUpgrade},
+        FromRef, Query, State,
+    },
+    response::{IntoResponse, Response},
+    routing::get,
+    Router,
+};
+use serde::Deserialize;
+use serde_json::json;
+use time::OffsetDateTime;
+use tokio::{sync::broadcast::error::RecvError, time::Instant};
+use tracing::{debug, instrument, warn};
+
+use crate::{
+    auth::jwt::{Claims, JwtKeys},
+    db::AppState,
+};
+
+use crate::error::{ApiError, ErrorCode};
+
+/// How long an unauthenticated socket may wait before sending its token.
+pub(crate) const AUTH_TIMEOUT: Duration = Duration::from_secs(10);
+/// Close code for policy violations (RFC 6455), used for auth failures.
+const CLOSE_POLICY: u16 = 1008;
+
+pub fn ws_routes() -> Router<AppState> {
+    Router::new().route("/ws", get(ws_handler))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WsQuery {
+    pub token: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ClientMessage {
+    Auth { token: String },
+    Ping,
+}
+
+/// Realtime meal updates over WebSocket. The access token comes either as
+/// `?token=` or as a first `{"type":"auth","token":"..."}` message. The
+/// socket is closed when the token expires, unless the client sends a fresh
+/// one in another auth message first.
+#[instrument(skip_all)]
+pub async fn ws_handler(
+    ws: WebSocketUpgrade,
+    State(state): State<AppState>,
+    Query(query): Query<WsQuery>,
+) -> Response {
+    let keys = JwtKeys::from_ref(&state);
+    // A bad query token is rejected before the upgrade so clients get a 401.
+    let claims = match query.token.as_deref().map(|t| keys.verify_access(t)) {
+        Some(Ok(claims)) => Some(claims),
+        Some(Err(_)) => {
+            warn!("invalid or expired websocket token");
+            return ApiError::Unauthorized("Invalid or expired token".to_string())
+                .with_code(ErrorCode::AuthTokenInvalid)
+                .into_response();
+        }
+        None => None,
+    };
+    ws.on_upgrade(move |socket| handle_socket(socket, state, keys, claims))
+}
+
+/// When the socket has to close because the token's `exp` has passed.
+fn expiry(claims: &Claims) -> Instant {
+    let left = claims.exp as i64 - OffsetDateTime::now_utc().unix_timestamp();
+    Instant::now() + Duration::from_secs(left.max(0) as u64)
+}
+
+async fn close(mut socket: WebSocket, reason: &'static str) {
+    let _ = socket
+        .send(Message::Close(Some(CloseFrame {
+            code: CLOSE_POLICY,
+            reason: reason.into(),
+        })))
+        .await;
+}
+
+async fn handle_socket(
+    mut socket: WebSocket,
+    state: AppState,
+    keys: JwtKeys,
+    claims: Option<Claims>,
+) {
+    let claims = match claims {
+        Some(claims) => claims,
+        None => match tokio::time::timeout(AUTH_TIMEOUT, authenticate(&mut socket, &keys)).await {
+            Ok(Some(claims)) => claims,
+            _ => return close(socket, "authentication required").await,
+        },
+    };
+    let user_id = claims.sub;
+    let mut expires = expiry(&claims);
+    debug!(user_id = %user_id, "websocket connected");
+    let mut events = state.events.subscribe();
+    if send_json(&mut socket, json!({"type": "ready"}))
+        .await
+        .is_err()
+    {
+        return;
+    }
+
+    loop {
+        tokio::select! {
+            received = events.recv() => {
+                let sent = match received {
+                    Ok(msg) if msg.user_id == user_id => send_json(&mut socket, json!(msg.event)).await,
+                    Ok(_) => continue,
+                    Err(RecvError::Lagged(missed)) => {
+                        warn!(user_id = %user_id, missed, "websocket lagged");
+                        send_json(&mut socket, json!({"type": "lagged", "missed": missed})).await
+                    }
+                    Err(RecvError::Closed) => break,
+                };
+                if sent.is_err() {
+                    break;
+                }
+            }
+            () = tokio::time::sleep_until(expires) => {
+                debug!(user_id = %user_id, "websocket token expired");
+                return close(socket, "token expired").await;
+            }
+            incoming = socket.recv() => match incoming {
+                Some(Ok(Message::Text(text))) => match serde_json::from_str(&text) {
+                    Ok(ClientMessage::Ping) => {
+                        if send_json(&mut socket, json!({"type": "pong"})).await.is_err() {
+                            break;
+                        }
+                    }
+                    Ok(ClientMessage::Auth { token }) => match keys.verify_access(&token) {
+                        Ok(claims) if claims.sub == user_id => expires = expiry(&claims),
+                        _ => return close(socket, "invalid token").await,
+                    },
+                    Err(_) => {}
+                },
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+    debug!(user_id = %user_id, "websocket disconnected");
+}
+
+/// Waits for the first text message and verifies it as an auth message.
+async fn authenticate(socket: &mut WebSocket, keys: &JwtKeys) -> Option<Claims> {
+    loop {
+        match socket.recv().await? {
+            Ok(Message::Text(text)) => {
+                let Ok(ClientMessage::Auth { token }) = serde_json::from_str(&text) else {
+                    return None;
+                };
+                return keys.verify_access(&token).ok();
+            }
+            Ok(Message::Ping(_) | Message::Pong(_)) => continue,
+            _ => return None,
+        }
+    }
+}
+
+async fn send_json(socket: &mut WebSocket, value: serde_json::Value) -> Result<(), axum::Error> {
+    socket.send(Message::Text(value.to_string())).await
+}
@@ -0,0 +1,134 @@
+//! Security headers on every response: `Strict-Transport-Security`,
+//! `X-Content-Type-Options` and `Referrer-Policy`, plus a
+//! `Content-Security-Policy` on HTML pages, the only responses a browser
+//! renders. Headers a handler set itself are kept.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Request, State},
+    http::{
+        header::{
+            CONTENT_SECURITY_POLICY, CONTENT_TYPE, REFERRER_POLICY, STRICT_TRANSPORT_SECURITY,
+            X_CONTENT_TYPE_OPTIONS,
+        },
+        HeaderMap, HeaderName, HeaderValue,
+    },
+    middleware::Next,
+    response::Response,
+};
+
+use crate::config::SecurityHeadersConfig;
+
+/// The configured headers, turned into header values once at startup.
+#[derive(Debug, Default)]
+pub struct SecurityHeaders {
+    always: Vec<(HeaderName, HeaderValue)>,
+    html_csp: Option<HeaderValue>,
+}
+
+impl SecurityHeaders {
+    pub fn new(config: &SecurityHeadersConfig) -> Arc<Self> {
+        let mut always = vec![(X_CONTENT_TYPE_OPTIONS, HeaderValue::from_static("nosniff"))];
+        if config.hsts_max_age_secs > 0 {
+            let hsts = format!("max-age={}", config.hsts_max_age_secs);
+            always.push((STRICT_TRANSPORT_SECURITY, header_value(&hsts)));
+        }
+        if let Some(policy) = &config.referrer_policy {
+            always.push((REFERRER_POLICY, header_value(policy)));
+        }
+        Arc::new(Self {
+            always,
+            html_csp: config.content_security_policy.as_deref().map(header_value),
+        })
+    }
+
+    fn apply(&self, headers: &mut HeaderMap) {
+        for (name, value) in &self.always {
+            headers.entry(name).or_insert_with(|| value.clone());
+        }
+        let is_html = headers
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.starts_with("text/html"));
+        if let (true, Some(csp)) = (is_html, &self.html_csp) {
+            headers
+                .entry(CONTENT_SECURITY_POLICY)
+                .or_insert_with(|| csp.clone());
+        }
+    }
+}
+
+/// Configured values were validated when the configuration was loaded.
+fn header_value(value: &str) -> HeaderValue {
+    HeaderValue::from_str(value).expect("validated header value")
+}
+
+/// Middleware for [`build_app`](crate::app::build_app), e.g.
+/// `from_fn_with_state(SecurityHeaders::new(&config), security_headers::set)`.
+pub async fn set(
+    State(headers): State<Arc<SecurityHeaders>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let mut response = next.run(req).await;
+    headers.apply(response.headers_mut());
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::Body,
+        middleware::from_fn_with_state,
+        response::{Html, IntoResponse},
+        routing::get,
+        Router,
+    };
+    use tower::ServiceExt;
+
+    use super::*;
+
+    async fn call(config: &SecurityHeadersConfig, path: &str) -> HeaderMap {
+        let app = Router::new()
+            .route("/page", get(|| async { Html("<p>hi</p>") }))
+            .route("/json", get(|| async { axum::Json(serde_json::json!({})) }))
+            .route(
+                "/framed",
+                get(|| async { ([(REFERRER_POLICY, "same-origin")], Html("")).into_response() }),
+            )
+            .layer(from_fn_with_state(SecurityHeaders::new(config), set));
+        let req = Request::get(path).body(Body::empty()).unwrap();
+        app.oneshot(req).await.unwrap().headers().clone()
+    }
+
+    #[tokio::test]
+    async fn csp_is_only_sent_with_html() {
+        let config = SecurityHeadersConfig::default();
+        let page = call(&config, "/page").await;
+        assert_eq!(page[X_CONTENT_TYPE_OPTIONS], "nosniff");
+        assert_eq!(page[STRICT_TRANSPORT_SECURITY], "max-age=31536000");
+        assert_eq!(page[REFERRER_POLICY], "no-referrer");
+        assert!(page[CONTENT_SECURITY_POLICY]
+            .to_str()
+            .unwrap()
+            .starts_with("default-src 'none';"));
+
+        let json = call(&config, "/json").await;
+        assert_eq!(json[X_CONTENT_TYPE_OPTIONS], "nosniff");
+        assert!(json.get(CONTENT_SECURITY_POLICY).is_none());
+    }
+
+    #[tokio::test]
+    async fn keeps_handler_headers_and_disabled_ones_stay_off() {
+        let config = SecurityHeadersConfig {
+            hsts_max_age_secs: 0,
+            referrer_policy: Some("no-referrer".into()),
+            content_security_policy: None,
+        };
+        let headers = call(&config, "/framed").await;
+        assert_eq!(headers[REFERRER_POLICY], "same-origin");
+        assert!(headers.get(STRICT_TRANSPORT_SECURITY).is_none());
+        assert!(headers.get(CONTENT_SECURITY_POLICY).is_none());
+    }
+}
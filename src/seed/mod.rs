@@ -0,0 +1,237 @@
+//! Demo data for development. `mealmind --seed` (re)creates a few users with
+//! up to two weeks of meals, nutrition and placeholder photos before the
+//! server starts. Photos go through the configured storage client; with the
+//! default in-memory `FakeStorage` they only live as long as the process,
+//! which is why seeding is part of the server rather than the admin CLI.
+
+mod png;
+pub mod repo;
+
+use time::{Duration, OffsetDateTime, Time};
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    analysis::NutritionEstimate,
+    auth::{password, repo as users_repo},
+    db::AppState,
+    goals::{dto::Goals, repo as goals_repo},
+    images::{
+        dto::NormalizedImage,
+        services::{release_objects, upload_images},
+        sniff::ImageFormat,
+    },
+    meals::{
+        dto::MealType::{self, Breakfast, Dinner, Lunch, Snack},
+        repo as meals_repo, services as meals_services,
+    },
+    photos::repo as photos_repo,
+};
+
+/// Password of every demo user.
+pub const DEMO_PASSWORD: &str = "demo-password";
+
+const PHOTO_WIDTH: u32 = 64;
+const PHOTO_HEIGHT: u32 = 48;
+
+struct DemoUser {
+    email: &'static str,
+    /// Days of meals, today included.
+    days: i64,
+    goals: bool,
+}
+
+const DEMO_USERS: &[DemoUser] = &[
+    DemoUser {
+        email: "demo@example.com",
+        days: 14,
+        goals: true,
+    },
+    DemoUser {
+        email: "demo.friend@example.com",
+        days: 5,
+        goals: false,
+    },
+];
+
+struct DemoMeal {
+    title: &'static str,
+    meal_type: MealType,
+    /// UTC time of day.
+    at: (u8, u8),
+    /// kcal, protein, fat, carbs, fiber, sugar (g) and sodium (mg).
+    nutrition: [f64; 7],
+    /// Colour of the placeholder photo; snacks are logged without one.
+    photo: Option<[u8; 3]>,
+}
+
+impl DemoMeal {
+    const fn new(
+        title: &'static str,
+        meal_type: MealType,
+        at: (u8, u8),
+        nutrition: [f64; 7],
+        photo: Option<[u8; 3]>,
+    ) -> Self {
+        Self {
+            title,
+            meal_type,
+            at,
+            nutrition,
+            photo,
+        }
+    }
+
+    fn estimate(&self) -> NutritionEstimate {
+        let [kcal, protein, fat, carbs, fiber, sugar, sodium] = self.nutrition;
+        NutritionEstimate {
+            total_calories_kcal: Some(kcal),
+            protein_g: Some(protein),
+            fat_g: Some(fat),
+            carbs_g: Some(carbs),
+            sodium_mg: Some(sodium),
+            sugar_g: Some(sugar),
+            fiber_g: Some(fiber),
+            caffeine_mg: None,
+            alcohol_g: None,
+            micros: None,
+            description: Some(self.title.to_string()),
+            title: None,
+            items: Vec::new(),
+            contains: Vec::new(),
+        }
+    }
+}
+
+#[rustfmt::skip]
+const BREAKFASTS: &[DemoMeal] = &[
+    DemoMeal::new("Greek yogurt with berries and granola", Breakfast, (7, 40), [380.0, 22.0, 12.0, 48.0, 6.0, 24.0, 110.0], Some([235, 225, 240])),
+    DemoMeal::new("Oatmeal with banana and peanut butter", Breakfast, (8, 5), [450.0, 14.0, 15.0, 68.0, 9.0, 20.0, 150.0], Some([214, 180, 120])),
+    DemoMeal::new("Scrambled eggs on whole-grain toast", Breakfast, (7, 55), [420.0, 24.0, 22.0, 30.0, 5.0, 4.0, 620.0], Some([250, 210, 80])),
+    DemoMeal::new("Avocado toast with a poached egg", Breakfast, (8, 20), [390.0, 15.0, 22.0, 32.0, 9.0, 3.0, 480.0], Some([150, 190, 90])),
+];
+
+#[rustfmt::skip]
+const LUNCHES: &[DemoMeal] = &[
+    DemoMeal::new("Chicken Caesar salad", Lunch, (12, 30), [520.0, 38.0, 32.0, 18.0, 4.0, 4.0, 980.0], Some([170, 210, 120])),
+    DemoMeal::new("Turkey and hummus wrap", Lunch, (13, 0), [560.0, 34.0, 20.0, 58.0, 8.0, 6.0, 1150.0], Some([220, 190, 150])),
+    DemoMeal::new("Lentil soup with sourdough", Lunch, (12, 45), [470.0, 24.0, 10.0, 70.0, 16.0, 8.0, 890.0], Some([190, 120, 60])),
+    DemoMeal::new("Salmon poke bowl", Lunch, (13, 15), [640.0, 36.0, 22.0, 72.0, 6.0, 10.0, 1240.0], Some([240, 140, 110])),
+];
+
+#[rustfmt::skip]
+const DINNERS: &[DemoMeal] = &[
+    DemoMeal::new("Spaghetti bolognese", Dinner, (19, 0), [720.0, 36.0, 24.0, 86.0, 8.0, 12.0, 980.0], Some([200, 70, 50])),
+    DemoMeal::new("Grilled salmon with quinoa and broccoli", Dinner, (19, 30), [610.0, 42.0, 24.0, 52.0, 9.0, 4.0, 420.0], Some([230, 150, 120])),
+    DemoMeal::new("Chicken stir-fry with rice", Dinner, (18, 45), [650.0, 40.0, 16.0, 82.0, 5.0, 14.0, 1320.0], Some([210, 160, 70])),
+    DemoMeal::new("Margherita pizza", Dinner, (20, 15), [800.0, 32.0, 30.0, 96.0, 5.0, 9.0, 1600.0], Some([220, 90, 60])),
+];
+
+#[rustfmt::skip]
+const SNACKS: &[DemoMeal] = &[
+    DemoMeal::new("Apple with almonds", Snack, (16, 0), [250.0, 7.0, 15.0, 25.0, 6.0, 19.0, 0.0], None),
+    DemoMeal::new("Protein shake", Snack, (17, 30), [180.0, 25.0, 3.0, 12.0, 2.0, 6.0, 220.0], None),
+    DemoMeal::new("Dark chocolate", Snack, (21, 0), [170.0, 2.0, 12.0, 13.0, 3.0, 7.0, 5.0], None),
+];
+
+/// What gets eaten on demo day `day`: three meals that rotate at different
+/// paces, and a snack on two days out of three.
+fn menu(day: usize) -> Vec<&'static DemoMeal> {
+    let mut meals = vec![
+        &BREAKFASTS[day % BREAKFASTS.len()],
+        &LUNCHES[(day * 3 + 1) % LUNCHES.len()],
+        &DINNERS[(day * 5 + 2) % DINNERS.len()],
+    ];
+    if day % 3 != 2 {
+        meals.push(&SNACKS[day % SNACKS.len()]);
+    }
+    meals
+}
+
+/// Replaces the demo users and their data. Meals later today than now are
+/// left out so today looks like a day in progress.
+pub async fn run(state: &AppState) -> anyhow::Result<()> {
+    let emails: Vec<String> = DEMO_USERS.iter().map(|u| u.email.to_string()).collect();
+    let keys = repo::photo_keys(&state.db, &emails).await?;
+    let removed = repo::delete_users(&state.db, &emails).await?;
+    release_objects(state, keys).await?;
+    if removed > 0 {
+        info!(users = removed, "removed previous demo users");
+    }
+
+    let hash = password::hash_password(DEMO_PASSWORD)?;
+    let now = OffsetDateTime::now_utc();
+    for (index, demo) in DEMO_USERS.iter().enumerate() {
+        let user = users_repo::create(&state.db, demo.email, &hash).await?;
+        if demo.goals {
+            let goals = Goals {
+                daily_calories_kcal: Some(2200.0),
+                protein_g: Some(120.0),
+                fiber_g: Some(30.0),
+                sugar_max_g: Some(50.0),
+                sodium_max_mg: Some(2300.0),
+                ..Goals::default()
+            };
+            goals_repo::upsert_goals(&state.db, user.id, &goals).await?;
+        }
+        let mut meals = 0;
+        for days_ago in 0..demo.days {
+            let date = now.date() - Duration::days(days_ago);
+            // Each user gets their own rotation.
+            for meal in menu(days_ago as usize + index * 7) {
+                let (hour, minute) = meal.at;
+                let eaten_at = date
+                    .with_time(Time::from_hms(hour, minute, 0)?)
+                    .assume_utc();
+                if eaten_at > now {
+                    continue;
+                }
+                seed_meal(state, user.id, meal, eaten_at).await?;
+                meals += 1;
+            }
+        }
+        info!(email = demo.email, meals, "seeded demo user");
+    }
+    info!(password = DEMO_PASSWORD, "demo users ready");
+    Ok(())
+}
+
+async fn seed_meal(
+    state: &AppState,
+    user_id: Uuid,
+    meal: &DemoMeal,
+    eaten_at: OffsetDateTime,
+) -> anyhow::Result<()> {
+    let meal_id = Uuid::new_v4();
+    let mut tx = state.db.begin().await?;
+    let photos = match meal.photo {
+        Some(rgb) => {
+            let image = NormalizedImage {
+                content_type: ImageFormat::Png.content_type().to_string(),
+                bytes: png::solid(PHOTO_WIDTH, PHOTO_HEIGHT, rgb),
+                taken_at: Some(eaten_at),
+            };
+            // Repeated meals reuse the object of their first photo.
+            upload_images(state, &mut tx, user_id, meal_id, vec![image])
+                .await?
+                .photos
+        }
+        None => Vec::new(),
+    };
+    let estimate = meal.estimate();
+    let raw = serde_json::to_value(&estimate)?;
+    repo::insert_meal(
+        &mut *tx,
+        meal_id,
+        user_id,
+        meal.title,
+        meal.meal_type,
+        eaten_at,
+    )
+    .await?;
+    photos_repo::insert_many(&mut *tx, user_id, meal_id, &photos).await?;
+    meals_repo::upsert_nutrition(&mut *tx, meal_id, &estimate, &raw).await?;
+    tx.commit().await?;
+    meals_services::refresh_nutrition(state, user_id, meal_id).await?;
+    Ok(())
+}
@@ -0,0 +1,87 @@
+//! Tiny solid-colour PNGs standing in for meal photos.
+
+const SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+/// Largest stored (uncompressed) deflate block.
+const MAX_STORED_BLOCK: usize = 0xFFFF;
+
+/// An RGB PNG of `width` × `height` pixels in `rgb`. The image data is
+/// stored without compression, which is fine at placeholder sizes.
+pub fn solid(width: u32, height: u32, rgb: [u8; 3]) -> Vec<u8> {
+    // Each row starts with filter type `None`.
+    let row: Vec<u8> = std::iter::once(0)
+        .chain(rgb.iter().copied().cycle().take(3 * width as usize))
+        .collect();
+    let pixels = row.repeat(height as usize);
+
+    let mut zlib = vec![0x78, 0x01];
+    let blocks = pixels.chunks(MAX_STORED_BLOCK).count();
+    for (i, block) in pixels.chunks(MAX_STORED_BLOCK).enumerate() {
+        zlib.push(u8::from(i + 1 == blocks));
+        let len = block.len() as u16;
+        zlib.extend_from_slice(&len.to_le_bytes());
+        zlib.extend_from_slice(&(!len).to_le_bytes());
+        zlib.extend_from_slice(block);
+    }
+    zlib.extend_from_slice(&adler32(&pixels).to_be_bytes());
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&width.to_be_bytes());
+    header.extend_from_slice(&height.to_be_bytes());
+    // 8-bit truecolour, default compression, filtering and no interlace.
+    header.extend_from_slice(&[8, 2, 0, 0, 0]);
+
+    let mut png = SIGNATURE.to_vec();
+    push_chunk(&mut png, b"IHDR", &header);
+    push_chunk(&mut png, b"IDAT", &zlib);
+    push_chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+fn push_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+    let crc = crc32(&png[start..]);
+    png.extend_from_slice(&crc.to_be_bytes());
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in bytes {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+fn adler32(bytes: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in bytes {
+        a = (a + u32::from(byte)) % 65521;
+        b = (b + a) % 65521;
+    }
+    (b << 16) | a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::images::sniff::{detect, dimensions, ImageFormat};
+
+    #[test]
+    fn writes_a_png_the_upload_checks_accept() {
+        let png = solid(64, 48, [200, 120, 40]);
+        assert_eq!(detect(&png), Some(ImageFormat::Png));
+        assert_eq!(dimensions(ImageFormat::Png, &png), Some((64, 48)));
+        // Every PNG ends with the same IEND chunk and checksum.
+        assert!(png.ends_with(b"IEND\xae\x42\x60\x82"));
+        assert_eq!(adler32(b"Wikipedia"), 0x11E6_0398);
+    }
+}
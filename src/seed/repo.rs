@@ -0,0 +1,57 @@
+use sqlx::{PgExecutor, PgPool};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::meals::dto::MealType;
+
+/// Storage keys of the photos (and kept originals) of the users with these
+/// emails.
+pub async fn photo_keys(db: &PgPool, emails: &[String]) -> anyhow::Result<Vec<String>> {
+    let keys = sqlx::query_scalar::<_, String>(
+        r#"
+        SELECT key
+        FROM photos p
+        JOIN users u ON u.id = p.user_id
+        CROSS JOIN LATERAL (VALUES (p.s3_key), (p.original_s3_key)) AS k(key)
+        WHERE u.email = ANY($1) AND key IS NOT NULL
+        "#,
+    )
+    .bind(emails)
+    .fetch_all(db)
+    .await?;
+    Ok(keys)
+}
+
+/// Deletes the users with these emails and, by cascade, all their data.
+pub async fn delete_users(db: &PgPool, emails: &[String]) -> anyhow::Result<u64> {
+    let result = sqlx::query(r#"DELETE FROM users WHERE email = ANY($1)"#)
+        .bind(emails)
+        .execute(db)
+        .await?;
+    Ok(result.rows_affected())
+}
+
+/// A finished meal eaten at `eaten_at`.
+pub async fn insert_meal(
+    db: impl PgExecutor<'_>,
+    meal_id: Uuid,
+    user_id: Uuid,
+    title: &str,
+    meal_type: MealType,
+    eaten_at: OffsetDateTime,
+) -> anyhow::Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO meals (id, user_id, title, meal_type, tags, status, created_at)
+        VALUES ($1, $2, $3, $4, ARRAY['demo'], 'done', $5)
+        "#,
+    )
+    .bind(meal_id)
+    .bind(user_id)
+    .bind(title)
+    .bind(meal_type)
+    .bind(eaten_at)
+    .execute(db)
+    .await?;
+    Ok(())
+}
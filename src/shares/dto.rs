@@ -0,0 +1,139 @@
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::{
+    foods::dto::FoodNutrition,
+    meal_items::dto::{ItemUnit, MealItem},
+    meals::dto::{MealNutrition, MealType},
+    reactions::dto::ReactionCount,
+};
+
+/// Longest lifetime a share link can be given.
+pub const MAX_SHARE_HOURS: u32 = 24 * 365;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct CreateShareRequest {
+    /// The link never expires when omitted.
+    pub expires_in_hours: Option<u32>,
+}
+
+impl CreateShareRequest {
+    pub fn validate(&self) -> Result<(), String> {
+        match self.expires_in_hours {
+            Some(hours) if hours == 0 || hours > MAX_SHARE_HOURS => Err(format!(
+                "expires_in_hours must be between 1 and {}",
+                MAX_SHARE_HOURS
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct MealShare {
+    pub id: Uuid,
+    pub meal_id: Uuid,
+    pub created_at: OffsetDateTime,
+    pub expires_at: Option<OffsetDateTime>,
+    pub revoked_at: Option<OffsetDateTime>,
+}
+
+/// A share link as seen by the meal's owner.
+#[derive(Debug, Serialize)]
+pub struct ShareResponse {
+    pub id: Uuid,
+    pub meal_id: Uuid,
+    pub token: String,
+    /// Path of the public endpoint, relative to the API base URL.
+    pub url: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub expires_at: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub revoked_at: Option<OffsetDateTime>,
+}
+
+impl ShareResponse {
+    pub fn new(share: MealShare, token: String) -> Self {
+        Self {
+            id: share.id,
+            meal_id: share.meal_id,
+            url: format!("/shared/{token}"),
+            token,
+            created_at: share.created_at,
+            expires_at: share.expires_at,
+            revoked_at: share.revoked_at,
+        }
+    }
+}
+
+/// Public view of a shared meal. Carries no ids, so nothing links it back to
+/// the owner's account.
+#[derive(Debug, Serialize)]
+pub struct SharedMeal {
+    pub title: Option<String>,
+    pub notes: Option<String>,
+    pub meal_type: Option<MealType>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    pub nutrition: Option<SharedNutrition>,
+    pub items: Vec<SharedItem>,
+    pub photos: Vec<SharedPhoto>,
+    pub reactions: Vec<ReactionCount>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SharedNutrition {
+    #[serde(flatten)]
+    pub totals: FoodNutrition,
+    pub global_score: Option<f64>,
+}
+
+impl From<&MealNutrition> for SharedNutrition {
+    fn from(nutrition: &MealNutrition) -> Self {
+        Self {
+            totals: FoodNutrition {
+                total_calories_kcal: nutrition.total_calories_kcal,
+                protein_g: nutrition.protein_g,
+                fat_g: nutrition.fat_g,
+                carbs_g: nutrition.carbs_g,
+                sodium_mg: nutrition.sodium_mg,
+                sugar_g: nutrition.sugar_g,
+                fiber_g: nutrition.fiber_g,
+            },
+            global_score: nutrition.global_score,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct SharedItem {
+    pub name: String,
+    pub quantity: f64,
+    pub unit: ItemUnit,
+    #[serde(flatten)]
+    pub nutrition: FoodNutrition,
+}
+
+impl From<MealItem> for SharedItem {
+    fn from(item: MealItem) -> Self {
+        Self {
+            name: item.name,
+            quantity: item.quantity,
+            unit: item.unit,
+            nutrition: item.nutrition,
+        }
+    }
+}
+
+/// Photo served through the share link; `url` is relative to the API base
+/// URL.
+#[derive(Debug, Serialize)]
+pub struct SharedPhoto {
+    pub url: String,
+    pub content_type: Option<String>,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+}
@@ -0,0 +1,110 @@
+use sqlx::PgPool;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::shares::dto::MealShare;
+
+/// Creates a share for a meal owned by `user_id`; `None` if the meal doesn't
+/// exist or belongs to someone else.
+pub async fn insert_share(
+    db: &PgPool,
+    user_id: Uuid,
+    meal_id: Uuid,
+    expires_at: Option<OffsetDateTime>,
+) -> anyhow::Result<Option<MealShare>> {
+    let share = sqlx::query_as::<_, MealShare>(
+        r#"
+        INSERT INTO meal_shares (meal_id, expires_at)
+        SELECT m.id, $3 FROM meals m WHERE m.id = $1 AND m.user_id = $2
+        RETURNING id, meal_id, created_at, expires_at, revoked_at
+        "#,
+    )
+    .bind(meal_id)
+    .bind(user_id)
+    .bind(expires_at)
+    .fetch_optional(db)
+    .await?;
+    Ok(share)
+}
+
+/// Shares of a meal owned by `user_id`, newest first, including revoked ones.
+pub async fn list_shares(
+    db: &PgPool,
+    user_id: Uuid,
+    meal_id: Uuid,
+) -> anyhow::Result<Vec<MealShare>> {
+    let shares = sqlx::query_as::<_, MealShare>(
+        r#"
+        SELECT s.id, s.meal_id, s.created_at, s.expires_at, s.revoked_at
+        FROM meal_shares s
+        JOIN meals m ON m.id = s.meal_id
+        WHERE s.meal_id = $1 AND m.user_id = $2
+        ORDER BY s.created_at DESC, s.id
+        "#,
+    )
+    .bind(meal_id)
+    .bind(user_id)
+    .fetch_all(db)
+    .await?;
+    Ok(shares)
+}
+
+/// Revokes a share of a meal owned by `user_id`. Revoking twice is a no-op
+/// that still reports the share as found.
+pub async fn revoke_share(
+    db: &PgPool,
+    user_id: Uuid,
+    meal_id: Uuid,
+    share_id: Uuid,
+) -> anyhow::Result<bool> {
+    let result = sqlx::query(
+        r#"
+        UPDATE meal_shares s
+        SET revoked_at = COALESCE(s.revoked_at, NOW())
+        FROM meals m
+        WHERE s.id = $1 AND s.meal_id = $2 AND m.id = s.meal_id AND m.user_id = $3
+        "#,
+    )
+    .bind(share_id)
+    .bind(meal_id)
+    .bind(user_id)
+    .execute(db)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// `(meal_id, owner_id)` of a share that is neither revoked nor expired.
+pub async fn find_active_share(
+    db: &PgPool,
+    share_id: Uuid,
+) -> anyhow::Result<Option<(Uuid, Uuid)>> {
+    let share = sqlx::query_as::<_, (Uuid, Uuid)>(
+        r#"
+        SELECT m.id, m.user_id
+        FROM meal_shares s
+        JOIN meals m ON m.id = s.meal_id
+        WHERE s.id = $1
+          AND s.revoked_at IS NULL
+          AND (s.expires_at IS NULL OR s.expires_at > NOW())
+        "#,
+    )
+    .bind(share_id)
+    .fetch_optional(db)
+    .await?;
+    Ok(share)
+}
+
+/// Drops share links that expired or were revoked more than `retention_days`
+/// ago. Returns how many were removed.
+pub async fn prune_inactive(db: &PgPool, retention_days: i32) -> anyhow::Result<u64> {
+    let result = sqlx::query(
+        r#"
+        DELETE FROM meal_shares
+        WHERE LEAST(expires_at, revoked_at) < NOW() - make_interval(days => $1)
+        "#,
+    )
+    .bind(retention_days)
+    .execute(db)
+    .await?;
+    Ok(result.rows_affected())
+}
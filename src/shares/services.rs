@@ -0,0 +1,228 @@
+use axum::http::StatusCode;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use time::{Duration, OffsetDateTime};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::{
+    db::{AppState, RepoError},
+    error::ErrorCode,
+    meal_items::repo as items_repo,
+    meals::repo as meals_repo,
+    photos::repo as photos_repo,
+    reactions::repo as reactions_repo,
+    shares::{
+        dto::{
+            CreateShareRequest, MealShare, ShareResponse, SharedMeal, SharedNutrition, SharedPhoto,
+        },
+        repo,
+    },
+    storage::ObjectStream,
+};
+
+type HmacSha256 = Hmac<Sha256>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ShareError {
+    #[error("Meal not found")]
+    MealNotFound,
+    #[error("Share not found")]
+    ShareNotFound,
+    #[error("{0}")]
+    Invalid(String),
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+impl From<RepoError> for ShareError {
+    fn from(e: RepoError) -> Self {
+        ShareError::Other(e.into())
+    }
+}
+
+impl ShareError {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            ShareError::MealNotFound | ShareError::ShareNotFound => StatusCode::NOT_FOUND,
+            ShareError::Invalid(_) => StatusCode::BAD_REQUEST,
+            ShareError::Other(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            ShareError::MealNotFound => ErrorCode::MealNotFound,
+            ShareError::ShareNotFound => ErrorCode::ShareNotFound,
+            ShareError::Invalid(_) => ErrorCode::ValidationFailed,
+            ShareError::Other(_) => ErrorCode::Internal,
+        }
+    }
+}
+
+fn mac(secret: &str, share_id: Uuid) -> HmacSha256 {
+    // HMAC accepts keys of any length.
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).expect("any key length");
+    mac.update(b"meal-share:");
+    mac.update(share_id.as_bytes());
+    mac
+}
+
+/// Share id plus its HMAC under `secret`, URL-safe base64. Tokens can't be
+/// forged or enumerated, and verifying one needs no database lookup.
+pub fn sign_token(secret: &str, share_id: Uuid) -> String {
+    let mut bytes = share_id.as_bytes().to_vec();
+    bytes.extend(mac(secret, share_id).finalize().into_bytes());
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+/// The share id of a token signed with `secret`.
+pub fn verify_token(secret: &str, token: &str) -> Option<Uuid> {
+    let bytes = URL_SAFE_NO_PAD.decode(token).ok()?;
+    if bytes.len() != 48 {
+        return None;
+    }
+    let (id, tag) = bytes.split_at(16);
+    let share_id = Uuid::from_slice(id).ok()?;
+    mac(secret, share_id).verify_slice(tag).ok()?;
+    Some(share_id)
+}
+
+fn response(state: &AppState, share: MealShare) -> ShareResponse {
+    let token = sign_token(&state.config.jwt.secret, share.id);
+    ShareResponse::new(share, token)
+}
+
+pub async fn create_share(
+    state: &AppState,
+    user_id: Uuid,
+    meal_id: Uuid,
+    input: CreateShareRequest,
+) -> Result<ShareResponse, ShareError> {
+    input.validate().map_err(ShareError::Invalid)?;
+    let expires_at = input
+        .expires_in_hours
+        .map(|hours| OffsetDateTime::now_utc() + Duration::hours(hours.into()));
+    let share = repo::insert_share(&state.db, user_id, meal_id, expires_at)
+        .await?
+        .ok_or(ShareError::MealNotFound)?;
+    info!(user_id = %user_id, meal_id = %meal_id, share_id = %share.id, "meal shared");
+    Ok(response(state, share))
+}
+
+pub async fn list_shares(
+    state: &AppState,
+    user_id: Uuid,
+    meal_id: Uuid,
+) -> Result<Vec<ShareResponse>, ShareError> {
+    if meals_repo::find_meal(&state.db, user_id, meal_id)
+        .await?
+        .is_none()
+    {
+        return Err(ShareError::MealNotFound);
+    }
+    let shares = repo::list_shares(&state.db, user_id, meal_id).await?;
+    Ok(shares.into_iter().map(|s| response(state, s)).collect())
+}
+
+pub async fn revoke_share(
+    state: &AppState,
+    user_id: Uuid,
+    meal_id: Uuid,
+    share_id: Uuid,
+) -> Result<(), ShareError> {
+    if !repo::revoke_share(&state.db, user_id, meal_id, share_id).await? {
+        return Err(ShareError::ShareNotFound);
+    }
+    info!(user_id = %user_id, meal_id = %meal_id, share_id = %share_id, "meal share revoked");
+    Ok(())
+}
+
+/// `(meal_id, owner_id)` behind a valid, live token.
+async fn resolve(state: &AppState, token: &str) -> anyhow::Result<Option<(Uuid, Uuid)>> {
+    match verify_token(&state.config.jwt.secret, token) {
+        Some(share_id) => repo::find_active_share(&state.db, share_id).await,
+        None => Ok(None),
+    }
+}
+
+/// The read-only meal behind a share token; `None` for unknown, revoked or
+/// expired tokens.
+pub async fn shared_meal(state: &AppState, token: &str) -> anyhow::Result<Option<SharedMeal>> {
+    let Some((meal_id, owner_id)) = resolve(state, token).await? else {
+        return Ok(None);
+    };
+    let Some(meal) = meals_repo::find_meal(&state.db, owner_id, meal_id).await? else {
+        return Ok(None);
+    };
+    let nutrition = meals_repo::find_nutrition(&state.db, meal_id).await?;
+    let items = items_repo::list_for_meal(&state.db, meal_id).await?;
+    let photos = photos_repo::list_for_meal(&state.db, owner_id, meal_id).await?;
+    let reactions = reactions_repo::counts_for_meals(&state.db, &[meal_id])
+        .await?
+        .remove(&meal_id)
+        .unwrap_or_default();
+    Ok(Some(SharedMeal {
+        title: meal.title,
+        notes: meal.notes,
+        meal_type: meal.meal_type,
+        created_at: meal.created_at,
+        nutrition: nutrition.as_ref().map(SharedNutrition::from),
+        items: items.into_iter().map(Into::into).collect(),
+        photos: photos
+            .into_iter()
+            .enumerate()
+            .map(|(i, photo)| SharedPhoto {
+                url: format!("/shared/{token}/photos/{i}"),
+                content_type: photo.content_type,
+                width: photo.width,
+                height: photo.height,
+            })
+            .collect(),
+        reactions,
+    }))
+}
+
+/// Streams the `index`-th photo of a shared meal.
+pub async fn open_shared_photo(
+    state: &AppState,
+    token: &str,
+    index: usize,
+) -> anyhow::Result<Option<ObjectStream>> {
+    let Some((meal_id, owner_id)) = resolve(state, token).await? else {
+        return Ok(None);
+    };
+    let photos = photos_repo::list_for_meal(&state.db, owner_id, meal_id).await?;
+    let Some(photo) = photos.get(index) else {
+        return Ok(None);
+    };
+    let object = state.storage.get_object_stream(&photo.s3_key).await?;
+    if object.is_none() {
+        warn!(photo_id = %photo.id, "shared photo object missing from storage");
+    }
+    Ok(object)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokens_verify_only_with_the_signing_secret() {
+        let id = Uuid::new_v4();
+        let token = sign_token("secret", id);
+        assert_eq!(verify_token("secret", &token), Some(id));
+        assert_eq!(verify_token("other", &token), None);
+    }
+
+    #[test]
+    fn tampered_tokens_are_rejected() {
+        let token = sign_token("secret", Uuid::new_v4());
+        let mut bytes = URL_SAFE_NO_PAD.decode(&token).unwrap();
+        bytes[0] ^= 1;
+        assert_eq!(verify_token("secret", &URL_SAFE_NO_PAD.encode(bytes)), None);
+        assert_eq!(verify_token("secret", "not-a-token"), None);
+        assert_eq!(verify_token("secret", &token[..20]), None);
+    }
+}
@@ -0,0 +1,126 @@
+//! Per-instance cache of computed stats. Entries expire after a TTL and are
+//! dropped when the user's meals change on this instance; expired entries
+//! are pruned once the cache reaches [`MAX_ENTRIES`].
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use uuid::Uuid;
+
+use crate::stats::dto::UserStats;
+
+/// Entries kept before expired ones are pruned, and everything is dropped if
+/// that isn't enough.
+pub const MAX_ENTRIES: usize = 10_000;
+
+/// Keyed by user and time zone, with the time each entry was computed.
+type Entries = HashMap<(Uuid, String), (Instant, UserStats)>;
+
+#[derive(Clone, Default)]
+pub struct StatsCache {
+    entries: Arc<Mutex<Entries>>,
+}
+
+impl StatsCache {
+    pub fn get(&self, user_id: Uuid, tz: &str, ttl: Duration) -> Option<UserStats> {
+        let mut entries = self.entries.lock().unwrap();
+        let key = (user_id, tz.to_string());
+        match entries.get(&key) {
+            Some((at, stats)) if at.elapsed() < ttl => Some(stats.clone()),
+            Some(_) => {
+                entries.remove(&key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Caches `stats`; `ttl` decides which entries are expired when the
+    /// cache is full.
+    pub fn put(&self, user_id: Uuid, tz: &str, stats: UserStats, ttl: Duration) {
+        let mut entries = self.entries.lock().unwrap();
+        let key = (user_id, tz.to_string());
+        if entries.len() >= MAX_ENTRIES && !entries.contains_key(&key) {
+            entries.retain(|_, (at, _)| at.elapsed() < ttl);
+            if entries.len() >= MAX_ENTRIES {
+                entries.clear();
+            }
+        }
+        entries.insert(key, (Instant::now(), stats));
+    }
+
+    /// Forgets every cached time zone of the user.
+    pub fn invalidate(&self, user_id: Uuid) {
+        self.entries
+            .lock()
+            .unwrap()
+            .retain(|(cached_user, _), _| *cached_user != user_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(current: u32) -> UserStats {
+        UserStats {
+            timezone: "UTC".into(),
+            current_streak_days: current,
+            longest_streak_days: current,
+            days_logged: 1,
+            total_meals: 1,
+            last_logged_on: None,
+        }
+    }
+
+    #[test]
+    fn entries_expire_after_ttl() {
+        let cache = StatsCache::default();
+        let user = Uuid::new_v4();
+        cache.put(user, "UTC", stats(3), Duration::from_secs(60));
+        let hit = cache.get(user, "UTC", Duration::from_secs(60)).unwrap();
+        assert_eq!(hit.current_streak_days, 3);
+        assert!(cache.get(user, "UTC", Duration::ZERO).is_none());
+        assert!(cache.get(user, "UTC", Duration::from_secs(60)).is_none());
+    }
+
+    #[test]
+    fn invalidate_drops_all_time_zones_of_one_user() {
+        let cache = StatsCache::default();
+        let (user, other) = (Uuid::new_v4(), Uuid::new_v4());
+        let ttl = Duration::from_secs(60);
+        cache.put(user, "UTC", stats(1), ttl);
+        cache.put(user, "Europe/Berlin", stats(1), ttl);
+        cache.put(other, "UTC", stats(2), ttl);
+        cache.invalidate(user);
+        assert!(cache.get(user, "UTC", ttl).is_none());
+        assert!(cache.get(user, "Europe/Berlin", ttl).is_none());
+        assert!(cache.get(other, "UTC", ttl).is_some());
+    }
+
+    #[test]
+    fn full_caches_prune_expired_entries() {
+        let cache = StatsCache::default();
+        let ttl = Duration::from_secs(60);
+        for _ in 0..MAX_ENTRIES {
+            cache.put(Uuid::new_v4(), "UTC", stats(1), Duration::ZERO);
+        }
+        let user = Uuid::new_v4();
+        cache.put(user, "UTC", stats(2), Duration::ZERO);
+        assert_eq!(cache.entries.lock().unwrap().len(), 1);
+        assert!(cache.get(user, "UTC", ttl).is_some());
+
+        for _ in 1..MAX_ENTRIES {
+            cache.put(Uuid::new_v4(), "UTC", stats(1), ttl);
+        }
+        cache.put(Uuid::new_v4(), "UTC", stats(1), ttl);
+        assert_eq!(
+            cache.entries.lock().unwrap().len(),
+            1,
+            "fresh entries are dropped too"
+        );
+    }
+}
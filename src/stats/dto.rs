@@ -0,0 +1,30 @@
+use serde::{Deserialize, Serialize};
+use time::Date;
+
+time::serde::format_description!(iso_date, Date, "[year]-[month]-[day]");
+
+#[derive(Debug, Deserialize)]
+pub struct StatsQuery {
+    /// IANA time zone deciding where days start; the time zone of the
+    /// user's preferences when omitted.
+    pub tz: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Streaks {
+    /// Consecutive days with a meal ending today, or yesterday while today
+    /// has nothing logged yet.
+    pub current: u32,
+    pub longest: u32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UserStats {
+    pub timezone: String,
+    pub current_streak_days: u32,
+    pub longest_streak_days: u32,
+    pub days_logged: i64,
+    pub total_meals: i64,
+    #[serde(with = "iso_date::option")]
+    pub last_logged_on: Option<Date>,
+}
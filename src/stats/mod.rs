@@ -0,0 +1,4 @@
+pub mod cache;
+pub mod dto;
+pub mod repo;
+pub mod services;
@@ -0,0 +1,46 @@
+use sqlx::PgPool;
+use time::Date;
+use uuid::Uuid;
+
+pub async fn timezone_exists(db: &PgPool, tz: &str) -> anyhow::Result<bool> {
+    let exists = sqlx::query_scalar::<_, bool>(
+        r#"SELECT EXISTS (SELECT 1 FROM pg_timezone_names WHERE name = $1)"#,
+    )
+    .bind(tz)
+    .fetch_one(db)
+    .await?;
+    Ok(exists)
+}
+
+pub async fn today_in(db: &PgPool, tz: &str) -> anyhow::Result<Date> {
+    let today = sqlx::query_scalar::<_, Date>(r#"SELECT (NOW() AT TIME ZONE $1)::date"#)
+        .bind(tz)
+        .fetch_one(db)
+        .await?;
+    Ok(today)
+}
+
+/// Distinct local days with at least one meal, oldest first.
+pub async fn logged_days(db: &PgPool, user_id: Uuid, tz: &str) -> anyhow::Result<Vec<Date>> {
+    let days = sqlx::query_scalar::<_, Date>(
+        r#"
+        SELECT DISTINCT (created_at AT TIME ZONE $2)::date AS day
+        FROM meals
+        WHERE user_id = $1
+        ORDER BY day
+        "#,
+    )
+    .bind(user_id)
+    .bind(tz)
+    .fetch_all(db)
+    .await?;
+    Ok(days)
+}
+
+pub async fn count_meals(db: &PgPool, user_id: Uuid) -> anyhow::Result<i64> {
+    let count = sqlx::query_scalar::<_, i64>(r#"SELECT COUNT(*) FROM meals WHERE user_id = $1"#)
+        .bind(user_id)
+        .fetch_one(db)
+        .await?;
+    Ok(count)
+}
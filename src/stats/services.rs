@@ -0,0 +1,103 @@
+use std::time::Duration;
+
+use time::Date;
+use uuid::Uuid;
+
+use crate::{
+    db::AppState,
+    stats::{
+        dto::{Streaks, UserStats},
+        repo,
+    },
+};
+
+/// Current and longest runs of consecutive days in `days` (sorted, distinct).
+pub fn streaks(days: &[Date], today: Date) -> Streaks {
+    let mut longest = 0;
+    let mut run = 0;
+    let mut previous: Option<Date> = None;
+    for &day in days {
+        run = match previous {
+            Some(prev) if prev.next_day() == Some(day) => run + 1,
+            _ => 1,
+        };
+        longest = longest.max(run);
+        previous = Some(day);
+    }
+    let current = match previous {
+        Some(last) if last == today || last.next_day() == Some(today) => run,
+        _ => 0,
+    };
+    Streaks { current, longest }
+}
+
+/// Stats for the user's days in `tz`, served from cache when fresh. Returns
+/// `None` for an unknown time zone.
+pub async fn user_stats(
+    state: &AppState,
+    user_id: Uuid,
+    tz: &str,
+) -> anyhow::Result<Option<UserStats>> {
+    let ttl = Duration::from_secs(state.config.stats.cache_ttl_secs);
+    if let Some(stats) = state.stats_cache.get(user_id, tz, ttl) {
+        return Ok(Some(stats));
+    }
+    if !repo::timezone_exists(&state.db, tz).await? {
+        return Ok(None);
+    }
+
+    let today = repo::today_in(&state.db, tz).await?;
+    let days = repo::logged_days(&state.db, user_id, tz).await?;
+    let total_meals = repo::count_meals(&state.db, user_id).await?;
+    let streaks = streaks(&days, today);
+    let stats = UserStats {
+        timezone: tz.to_string(),
+        current_streak_days: streaks.current,
+        longest_streak_days: streaks.longest,
+        days_logged: days.len() as i64,
+        total_meals,
+        last_logged_on: days.last().copied(),
+    };
+    if !ttl.is_zero() {
+        state.stats_cache.put(user_id, tz, stats.clone(), ttl);
+    }
+    Ok(Some(stats))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::date;
+
+    #[test]
+    fn no_days_means_no_streaks() {
+        assert_eq!(streaks(&[], date!(2024 - 03 - 10)), Streaks::default());
+    }
+
+    #[test]
+    fn current_streak_survives_until_today_is_over() {
+        let days = [
+            date!(2024 - 03 - 07),
+            date!(2024 - 03 - 08),
+            date!(2024 - 03 - 09),
+        ];
+        assert_eq!(streaks(&days, date!(2024 - 03 - 09)).current, 3);
+        assert_eq!(streaks(&days, date!(2024 - 03 - 10)).current, 3);
+        assert_eq!(streaks(&days, date!(2024 - 03 - 11)).current, 0);
+    }
+
+    #[test]
+    fn longest_streak_spans_gaps() {
+        let days = [
+            date!(2024 - 02 - 27),
+            date!(2024 - 02 - 28),
+            date!(2024 - 02 - 29),
+            date!(2024 - 03 - 01),
+            date!(2024 - 03 - 05),
+            date!(2024 - 03 - 06),
+        ];
+        let streaks = streaks(&days, date!(2024 - 03 - 06));
+        assert_eq!(streaks.longest, 4);
+        assert_eq!(streaks.current, 2);
+    }
+}
@@ -0,0 +1,160 @@
+use std::{sync::Arc, time::Duration};
+
+use crate::{
+    breaker::CircuitBreaker,
+    config::CircuitBreakerConfig,
+    storage::{CompletedPart, ObjectStream, StorageClient, StorageError, StorageResult},
+};
+
+/// Fails calls with [`StorageError::Unavailable`] right away while the store
+/// keeps failing, so an outage answers `503` instead of tying up requests
+/// until their timeouts. Wraps [`RetryingStorage`](super::RetryingStorage):
+/// a call that exhausted its retries counts as one failure.
+pub struct CircuitBreakingStorage {
+    inner: Arc<dyn StorageClient>,
+    breaker: CircuitBreaker,
+}
+
+impl CircuitBreakingStorage {
+    pub fn new(inner: Arc<dyn StorageClient>, config: &CircuitBreakerConfig) -> Self {
+        Self {
+            inner,
+            breaker: CircuitBreaker::new("storage", config),
+        }
+    }
+
+    async fn guard<T, Fut>(&self, call: impl FnOnce() -> Fut) -> StorageResult<T>
+    where
+        Fut: std::future::Future<Output = StorageResult<T>>,
+    {
+        self.breaker
+            .call(StorageError::is_transient, call)
+            .await
+            .unwrap_or_else(|open| Err(StorageError::Unavailable(open.to_string())))
+    }
+}
+
+#[axum::async_trait]
+impl StorageClient for CircuitBreakingStorage {
+    async fn put_object(&self, key: &str, bytes: Vec<u8>, content_type: &str) -> StorageResult<()> {
+        self.guard(|| self.inner.put_object(key, bytes, content_type))
+            .await
+    }
+
+    async fn delete_object(&self, key: &str) -> StorageResult<()> {
+        self.guard(|| self.inner.delete_object(key)).await
+    }
+
+    async fn get_object_stream(&self, key: &str) -> StorageResult<Option<ObjectStream>> {
+        self.guard(|| self.inner.get_object_stream(key)).await
+    }
+
+    async fn presign_get(&self, key: &str, ttl: Duration) -> StorageResult<String> {
+        self.guard(|| self.inner.presign_get(key, ttl)).await
+    }
+
+    async fn create_multipart(&self, key: &str, content_type: &str) -> StorageResult<String> {
+        self.guard(|| self.inner.create_multipart(key, content_type))
+            .await
+    }
+
+    async fn upload_part(
+        &self,
+        key: &str,
+        upload_id: &str,
+        number: i32,
+        bytes: Vec<u8>,
+    ) -> StorageResult<String> {
+        self.guard(|| self.inner.upload_part(key, upload_id, number, bytes))
+            .await
+    }
+
+    async fn complete_multipart(
+        &self,
+        key: &str,
+        upload_id: &str,
+        parts: &[CompletedPart],
+    ) -> StorageResult<()> {
+        self.guard(|| self.inner.complete_multipart(key, upload_id, parts))
+            .await
+    }
+
+    async fn abort_multipart(&self, key: &str, upload_id: &str) -> StorageResult<()> {
+        self.guard(|| self.inner.abort_multipart(key, upload_id))
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use super::*;
+
+    /// Every call times out.
+    #[derive(Default)]
+    struct Down {
+        calls: AtomicU32,
+    }
+
+    #[axum::async_trait]
+    impl StorageClient for Down {
+        async fn put_object(&self, _: &str, _: Vec<u8>, _: &str) -> StorageResult<()> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Err(StorageError::Unavailable("timeout".into()))
+        }
+
+        async fn delete_object(&self, _: &str) -> StorageResult<()> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Err(StorageError::Other("403".into()))
+        }
+
+        async fn get_object_stream(&self, _: &str) -> StorageResult<Option<ObjectStream>> {
+            Ok(None)
+        }
+
+        async fn presign_get(&self, key: &str, _: Duration) -> StorageResult<String> {
+            Ok(key.to_string())
+        }
+
+        async fn create_multipart(&self, _: &str, _: &str) -> StorageResult<String> {
+            Ok("upload".into())
+        }
+
+        async fn upload_part(&self, _: &str, _: &str, _: i32, _: Vec<u8>) -> StorageResult<String> {
+            Ok("etag".into())
+        }
+
+        async fn complete_multipart(
+            &self,
+            _: &str,
+            _: &str,
+            _: &[CompletedPart],
+        ) -> StorageResult<()> {
+            Ok(())
+        }
+
+        async fn abort_multipart(&self, _: &str, _: &str) -> StorageResult<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn open_circuit_fails_fast_as_unavailable() {
+        let inner = Arc::new(Down::default());
+        let config = CircuitBreakerConfig {
+            failure_threshold: 2,
+            open_secs: 60,
+        };
+        let storage = CircuitBreakingStorage::new(inner.clone(), &config);
+        // Permanent errors don't count.
+        for _ in 0..3 {
+            assert!(!storage.delete_object("k").await.unwrap_err().is_transient());
+        }
+        for _ in 0..4 {
+            let err = storage.put_object("k", vec![1], "image/png").await;
+            assert!(err.unwrap_err().is_transient());
+        }
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 5);
+    }
+}
@@ -0,0 +1,135 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use axum::body::Bytes;
+use futures::{stream, StreamExt};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::storage::{CompletedPart, ObjectStream, StorageClient, StorageError, StorageResult};
+
+/// Object bytes and content type, keyed by object key.
+type Objects = HashMap<String, (Vec<u8>, String)>;
+
+/// A multipart upload in progress: its key, content type and parts by number.
+struct Upload {
+    key: String,
+    content_type: String,
+    parts: BTreeMap<i32, Vec<u8>>,
+}
+
+/// In-memory object store. Presigned URLs use a `memory://` scheme and are
+/// only meaningful to tests and local tooling.
+#[derive(Clone, Default)]
+pub struct FakeStorage {
+    objects: Arc<RwLock<Objects>>,
+    uploads: Arc<RwLock<HashMap<String, Upload>>>,
+}
+
+fn poisoned<T>(_: T) -> StorageError {
+    StorageError::Other("fake storage lock poisoned".into())
+}
+
+/// ETags only have to tell part contents apart within one upload.
+fn part_etag(bytes: &[u8]) -> String {
+    format!("\"{}\"", hex::encode(Sha256::digest(bytes)))
+}
+
+#[axum::async_trait]
+impl StorageClient for FakeStorage {
+    async fn put_object(&self, key: &str, bytes: Vec<u8>, content_type: &str) -> StorageResult<()> {
+        self.objects
+            .write()
+            .map_err(poisoned)?
+            .insert(key.to_string(), (bytes, content_type.to_string()));
+        Ok(())
+    }
+
+    async fn delete_object(&self, key: &str) -> StorageResult<()> {
+        self.objects.write().map_err(poisoned)?.remove(key);
+        Ok(())
+    }
+
+    async fn get_object_stream(&self, key: &str) -> StorageResult<Option<ObjectStream>> {
+        let objects = self.objects.read().map_err(poisoned)?;
+        Ok(objects.get(key).map(|(bytes, content_type)| ObjectStream {
+            content_type: Some(content_type.clone()),
+            content_length: Some(bytes.len() as u64),
+            body: stream::once(std::future::ready(Ok(Bytes::from(bytes.clone())))).boxed(),
+        }))
+    }
+
+    async fn presign_get(&self, key: &str, ttl: Duration) -> StorageResult<String> {
+        Ok(format!("memory://{}?expires_in={}", key, ttl.as_secs()))
+    }
+
+    async fn create_multipart(&self, key: &str, content_type: &str) -> StorageResult<String> {
+        let upload_id = Uuid::new_v4().to_string();
+        let upload = Upload {
+            key: key.to_string(),
+            content_type: content_type.to_string(),
+            parts: BTreeMap::new(),
+        };
+        self.uploads
+            .write()
+            .map_err(poisoned)?
+            .insert(upload_id.clone(), upload);
+        Ok(upload_id)
+    }
+
+    async fn upload_part(
+        &self,
+        key: &str,
+        upload_id: &str,
+        number: i32,
+        bytes: Vec<u8>,
+    ) -> StorageResult<String> {
+        let mut uploads = self.uploads.write().map_err(poisoned)?;
+        let upload = uploads
+            .get_mut(upload_id)
+            .filter(|upload| upload.key == key)
+            .ok_or_else(|| StorageError::Other(format!("upload part {}: no such upload", key)))?;
+        let etag = part_etag(&bytes);
+        upload.parts.insert(number, bytes);
+        Ok(etag)
+    }
+
+    async fn complete_multipart(
+        &self,
+        key: &str,
+        upload_id: &str,
+        parts: &[CompletedPart],
+    ) -> StorageResult<()> {
+        let mut uploads = self.uploads.write().map_err(poisoned)?;
+        let upload = uploads
+            .get(upload_id)
+            .filter(|upload| upload.key == key)
+            .ok_or_else(|| StorageError::Other(format!("complete {}: no such upload", key)))?;
+        let mut bytes = Vec::new();
+        for part in parts {
+            match upload.parts.get(&part.number) {
+                Some(stored) if part_etag(stored) == part.etag => bytes.extend_from_slice(stored),
+                _ => {
+                    return Err(StorageError::Other(format!(
+                        "complete {}: invalid part {}",
+                        key, part.number
+                    )))
+                }
+            }
+        }
+        let upload = uploads.remove(upload_id).expect("upload checked above");
+        self.objects
+            .write()
+            .map_err(poisoned)?
+            .insert(upload.key, (bytes, upload.content_type));
+        Ok(())
+    }
+
+    async fn abort_multipart(&self, _key: &str, upload_id: &str) -> StorageResult<()> {
+        self.uploads.write().map_err(poisoned)?.remove(upload_id);
+        Ok(())
+    }
+}
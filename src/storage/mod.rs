@@ -0,0 +1,119 @@
+use std::{sync::Arc, time::Duration};
+
+use axum::body::Bytes;
+use futures::stream::BoxStream;
+
+use crate::config::{AppConfig, StorageBackend};
+
+pub mod breaker;
+pub mod fake;
+pub mod retry;
+pub mod s3;
+
+pub use breaker::CircuitBreakingStorage;
+pub use fake::FakeStorage;
+pub use retry::RetryingStorage;
+pub use s3::S3Storage;
+
+#[derive(Debug, thiserror::Error)]
+pub enum StorageError {
+    /// Transient failure (timeouts, connection errors, 5xx/throttling) that
+    /// may succeed on retry.
+    #[error("storage unavailable: {0}")]
+    Unavailable(String),
+    #[error("storage error: {0}")]
+    Other(String),
+}
+
+impl StorageError {
+    pub fn is_transient(&self) -> bool {
+        matches!(self, StorageError::Unavailable(_))
+    }
+}
+
+pub type StorageResult<T> = Result<T, StorageError>;
+
+/// Object body streamed from storage, with the metadata needed to serve it.
+pub struct ObjectStream {
+    pub content_type: Option<String>,
+    pub content_length: Option<u64>,
+    pub body: BoxStream<'static, StorageResult<Bytes>>,
+}
+
+/// A part written by [`StorageClient::upload_part`], as needed to complete
+/// the multipart upload.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompletedPart {
+    pub number: i32,
+    pub etag: String,
+}
+
+/// True when `err` (anywhere in its chain) is a transient storage failure, so
+/// handlers can answer 503 instead of 500.
+pub fn is_unavailable(err: &anyhow::Error) -> bool {
+    err.chain().any(|cause| {
+        cause
+            .downcast_ref::<StorageError>()
+            .is_some_and(StorageError::is_transient)
+    })
+}
+
+/// Object storage used for meal photos. Handlers and services only talk to
+/// this trait so the S3 client can be swapped for [`FakeStorage`] locally.
+#[axum::async_trait]
+pub trait StorageClient: Send + Sync {
+    async fn put_object(&self, key: &str, bytes: Vec<u8>, content_type: &str) -> StorageResult<()>;
+
+    /// Deleting a missing key is not an error.
+    async fn delete_object(&self, key: &str) -> StorageResult<()>;
+
+    /// Streams the object at `key`, or `None` if it does not exist.
+    async fn get_object_stream(&self, key: &str) -> StorageResult<Option<ObjectStream>>;
+
+    /// Returns a time-limited GET URL for `key`.
+    async fn presign_get(&self, key: &str, ttl: Duration) -> StorageResult<String>;
+
+    /// Starts a multipart upload to `key`, returning its upload id. Parts
+    /// stay invisible until [`complete_multipart`](Self::complete_multipart).
+    async fn create_multipart(&self, key: &str, content_type: &str) -> StorageResult<String>;
+
+    /// Writes part `number` (1-based) and returns its ETag. Writing a number
+    /// again replaces the part. All parts but the last must be at least
+    /// 5 MiB.
+    async fn upload_part(
+        &self,
+        key: &str,
+        upload_id: &str,
+        number: i32,
+        bytes: Vec<u8>,
+    ) -> StorageResult<String>;
+
+    /// Joins `parts`, in ascending order, into the object at `key`.
+    async fn complete_multipart(
+        &self,
+        key: &str,
+        upload_id: &str,
+        parts: &[CompletedPart],
+    ) -> StorageResult<()>;
+
+    /// Drops the upload and its parts. Aborting an unknown upload is not an
+    /// error.
+    async fn abort_multipart(&self, key: &str, upload_id: &str) -> StorageResult<()>;
+}
+
+pub async fn from_config(config: &AppConfig) -> anyhow::Result<Arc<dyn StorageClient>> {
+    let storage: Arc<dyn StorageClient> = match config.storage_backend {
+        StorageBackend::S3 => Arc::new(CircuitBreakingStorage::new(
+            Arc::new(RetryingStorage::new(
+                Arc::new(S3Storage::new(&config.s3)),
+                config.storage_retry.clone(),
+            )),
+            &config.storage_breaker,
+        )),
+        StorageBackend::Memory => {
+            tracing::warn!("using in-memory storage backend; objects are not persisted");
+            Arc::new(FakeStorage::default())
+        }
+    };
+    Ok(storage)
+}
@@ -0,0 +1,265 @@
+use std::{sync::Arc, time::Duration};
+
+use rand_core::{OsRng, RngCore};
+use tracing::warn;
+
+use crate::{
+    config::StorageRetryConfig,
+    storage::{CompletedPart, ObjectStream, StorageClient, StorageResult},
+};
+
+/// Retries transient failures of the wrapped client with exponential backoff
+/// and jitter. Permanent errors are returned immediately.
+pub struct RetryingStorage {
+    inner: Arc<dyn StorageClient>,
+    config: StorageRetryConfig,
+}
+
+impl RetryingStorage {
+    pub fn new(inner: Arc<dyn StorageClient>, config: StorageRetryConfig) -> Self {
+        Self { inner, config }
+    }
+
+    async fn retry<T, F, Fut>(&self, op: &str, key: &str, mut call: F) -> StorageResult<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: std::future::Future<Output = StorageResult<T>>,
+    {
+        let mut attempt = 0;
+        loop {
+            match call().await {
+                Err(e) if e.is_transient() && attempt + 1 < self.config.max_attempts => {
+                    let delay = backoff_delay(&self.config, attempt, random_unit());
+                    warn!(error = %e, op, key, attempt = attempt + 1, ?delay, "retrying storage call");
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+/// Delay before retry number `attempt + 1`: `base * 2^attempt` capped at the
+/// configured max, with up to `jitter` of it replaced by a random amount.
+/// `unit` is a random number in `[0, 1)`.
+pub fn backoff_delay(config: &StorageRetryConfig, attempt: u32, unit: f64) -> Duration {
+    let exp = config
+        .base_delay_ms
+        .saturating_mul(1u64 << attempt.min(32))
+        .min(config.max_delay_ms);
+    let jitter = config.jitter.clamp(0.0, 1.0);
+    let ms = exp as f64 * (1.0 - jitter + jitter * unit);
+    Duration::from_millis(ms as u64)
+}
+
+fn random_unit() -> f64 {
+    OsRng.next_u32() as f64 / (u32::MAX as f64 + 1.0)
+}
+
+#[axum::async_trait]
+impl StorageClient for RetryingStorage {
+    async fn put_object(&self, key: &str, bytes: Vec<u8>, content_type: &str) -> StorageResult<()> {
+        self.retry("put_object", key, || {
+            self.inner.put_object(key, bytes.clone(), content_type)
+        })
+        .await
+    }
+
+    async fn delete_object(&self, key: &str) -> StorageResult<()> {
+        self.retry("delete_object", key, || self.inner.delete_object(key))
+            .await
+    }
+
+    /// Only opening the stream is retried; a failure mid-body surfaces to the
+    /// caller as-is.
+    async fn get_object_stream(&self, key: &str) -> StorageResult<Option<ObjectStream>> {
+        self.retry("get_object", key, || self.inner.get_object_stream(key))
+            .await
+    }
+
+    async fn presign_get(&self, key: &str, ttl: Duration) -> StorageResult<String> {
+        self.retry("presign_get", key, || self.inner.presign_get(key, ttl))
+            .await
+    }
+
+    async fn create_multipart(&self, key: &str, content_type: &str) -> StorageResult<String> {
+        self.retry("create_multipart", key, || {
+            self.inner.create_multipart(key, content_type)
+        })
+        .await
+    }
+
+    async fn upload_part(
+        &self,
+        key: &str,
+        upload_id: &str,
+        number: i32,
+        bytes: Vec<u8>,
+    ) -> StorageResult<String> {
+        self.retry("upload_part", key, || {
+            self.inner
+                .upload_part(key, upload_id, number, bytes.clone())
+        })
+        .await
+    }
+
+    async fn complete_multipart(
+        &self,
+        key: &str,
+        upload_id: &str,
+        parts: &[CompletedPart],
+    ) -> StorageResult<()> {
+        self.retry("complete_multipart", key, || {
+            self.inner.complete_multipart(key, upload_id, parts)
+        })
+        .await
+    }
+
+    async fn abort_multipart(&self, key: &str, upload_id: &str) -> StorageResult<()> {
+        self.retry("abort_multipart", key, || {
+            self.inner.abort_multipart(key, upload_id)
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use super::*;
+    use crate::storage::StorageError;
+
+    fn config() -> StorageRetryConfig {
+        StorageRetryConfig {
+            max_attempts: 3,
+            base_delay_ms: 1,
+            max_delay_ms: 5,
+            jitter: 0.0,
+        }
+    }
+
+    #[test]
+    fn backoff_grows_and_caps() {
+        let config = StorageRetryConfig {
+            max_attempts: 5,
+            base_delay_ms: 100,
+            max_delay_ms: 500,
+            jitter: 0.0,
+        };
+        let delays: Vec<_> = (0..5).map(|a| backoff_delay(&config, a, 0.5)).collect();
+        assert_eq!(
+            delays,
+            [100, 200, 400, 500, 500]
+                .map(Duration::from_millis)
+                .to_vec()
+        );
+    }
+
+    #[test]
+    fn jitter_stays_within_fraction() {
+        let config = StorageRetryConfig {
+            jitter: 0.5,
+            base_delay_ms: 1000,
+            max_delay_ms: 1000,
+            ..config()
+        };
+        assert_eq!(backoff_delay(&config, 0, 0.0), Duration::from_millis(500));
+        assert!(backoff_delay(&config, 0, 0.999) <= Duration::from_millis(1000));
+    }
+
+    /// Fails with the given error for the first `failures` calls.
+    struct Flaky {
+        calls: AtomicU32,
+        failures: u32,
+        transient: bool,
+    }
+
+    #[axum::async_trait]
+    impl StorageClient for Flaky {
+        async fn put_object(&self, _: &str, _: Vec<u8>, _: &str) -> StorageResult<()> {
+            let n = self.calls.fetch_add(1, Ordering::SeqCst);
+            match (n < self.failures, self.transient) {
+                (false, _) => Ok(()),
+                (true, true) => Err(StorageError::Unavailable("503".into())),
+                (true, false) => Err(StorageError::Other("403".into())),
+            }
+        }
+
+        async fn delete_object(&self, _: &str) -> StorageResult<()> {
+            Ok(())
+        }
+
+        async fn get_object_stream(&self, _: &str) -> StorageResult<Option<ObjectStream>> {
+            Ok(None)
+        }
+
+        async fn presign_get(&self, key: &str, _: Duration) -> StorageResult<String> {
+            Ok(key.to_string())
+        }
+
+        async fn create_multipart(&self, _: &str, _: &str) -> StorageResult<String> {
+            Ok("upload".into())
+        }
+
+        async fn upload_part(&self, _: &str, _: &str, _: i32, _: Vec<u8>) -> StorageResult<String> {
+            Ok("etag".into())
+        }
+
+        async fn complete_multipart(
+            &self,
+            _: &str,
+            _: &str,
+            _: &[CompletedPart],
+        ) -> StorageResult<()> {
+            Ok(())
+        }
+
+        async fn abort_multipart(&self, _: &str, _: &str) -> StorageResult<()> {
+            Ok(())
+        }
+    }
+
+    fn flaky(failures: u32, transient: bool) -> Arc<Flaky> {
+        Arc::new(Flaky {
+            calls: AtomicU32::new(0),
+            failures,
+            transient,
+        })
+    }
+
+    #[tokio::test]
+    async fn retries_transient_errors() {
+        let inner = flaky(2, true);
+        let storage = RetryingStorage::new(inner.clone(), config());
+        storage
+            .put_object("k", vec![1], "image/jpeg")
+            .await
+            .unwrap();
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn gives_up_after_max_attempts() {
+        let inner = flaky(5, true);
+        let storage = RetryingStorage::new(inner.clone(), config());
+        let err = storage
+            .put_object("k", vec![1], "image/jpeg")
+            .await
+            .unwrap_err();
+        assert!(err.is_transient());
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn does_not_retry_permanent_errors() {
+        let inner = flaky(1, false);
+        let storage = RetryingStorage::new(inner.clone(), config());
+        assert!(storage
+            .put_object("k", vec![1], "image/jpeg")
+            .await
+            .is_err());
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,261 @@
+use std::time::Duration;
+
+use aws_sdk_s3::{
+    config::{http::HttpResponse, retry::RetryConfig, Credentials, Region},
+    error::{ProvideErrorMetadata, SdkError},
+    presigning::PresigningConfig,
+    primitives::ByteStream,
+    types::{CompletedMultipartUpload, CompletedPart as S3CompletedPart},
+    Client,
+};
+
+use futures::{stream, StreamExt};
+
+use crate::{
+    config::S3Config,
+    storage::{CompletedPart, ObjectStream, StorageClient, StorageError, StorageResult},
+};
+
+#[derive(Clone)]
+pub struct S3Storage {
+    client: Client,
+    bucket: String,
+}
+
+impl S3Storage {
+    pub fn new(config: &S3Config) -> Self {
+        let credentials = Credentials::new(
+            &config.access_key,
+            &config.secret_key,
+            None,
+            None,
+            "mealmind-env",
+        );
+        let mut builder = aws_sdk_s3::Config::builder()
+            .behavior_version_latest()
+            .region(Region::new(config.region.clone()))
+            .credentials_provider(credentials)
+            .force_path_style(config.use_path_style)
+            // Retries are handled by `RetryingStorage` so attempts don't multiply.
+            .retry_config(RetryConfig::disabled());
+        if let Some(endpoint) = &config.endpoint {
+            builder = builder.endpoint_url(endpoint);
+        }
+        Self {
+            client: Client::from_conf(builder.build()),
+            bucket: config.bucket.clone(),
+        }
+    }
+
+    /// Creates the configured bucket; the harness starts from an empty MinIO.
+    #[cfg(all(test, feature = "test-support"))]
+    pub async fn create_bucket(&self) -> anyhow::Result<()> {
+        self.client
+            .create_bucket()
+            .bucket(&self.bucket)
+            .send()
+            .await?;
+        Ok(())
+    }
+}
+
+/// Connection problems, timeouts, throttling and 5xx responses are transient;
+/// everything else (auth, missing bucket, bad request) is not.
+fn classify<E>(op: &str, key: &str, err: SdkError<E, HttpResponse>) -> StorageError
+where
+    E: ProvideErrorMetadata + std::error::Error + 'static,
+{
+    let transient = match &err {
+        SdkError::TimeoutError(_) | SdkError::DispatchFailure(_) | SdkError::ResponseError(_) => {
+            true
+        }
+        SdkError::ServiceError(service) => {
+            let status = service.raw().status().as_u16();
+            status >= 500
+                || status == 429
+                || matches!(
+                    service.err().code(),
+                    Some("SlowDown" | "InternalError" | "ServiceUnavailable")
+                )
+        }
+        _ => false,
+    };
+    let msg = format!(
+        "{} {}: {}",
+        op,
+        key,
+        aws_sdk_s3::error::DisplayErrorContext(&err)
+    );
+    if transient {
+        StorageError::Unavailable(msg)
+    } else {
+        StorageError::Other(msg)
+    }
+}
+
+#[axum::async_trait]
+impl StorageClient for S3Storage {
+    async fn put_object(&self, key: &str, bytes: Vec<u8>, content_type: &str) -> StorageResult<()> {
+        self.client
+            .put_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .content_type(content_type)
+            .body(ByteStream::from(bytes))
+            .send()
+            .await
+            .map_err(|e| classify("put object", key, e))?;
+        Ok(())
+    }
+
+    async fn delete_object(&self, key: &str) -> StorageResult<()> {
+        self.client
+            .delete_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .send()
+            .await
+            .map_err(|e| classify("delete object", key, e))?;
+        Ok(())
+    }
+
+    async fn get_object_stream(&self, key: &str) -> StorageResult<Option<ObjectStream>> {
+        let output = match self
+            .client
+            .get_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .send()
+            .await
+        {
+            Ok(output) => output,
+            Err(e) if e.as_service_error().is_some_and(|se| se.is_no_such_key()) => {
+                return Ok(None)
+            }
+            Err(e) => return Err(classify("get object", key, e)),
+        };
+        let key = key.to_string();
+        let body = stream::try_unfold(output.body, move |mut body| {
+            let key = key.clone();
+            async move {
+                match body.try_next().await {
+                    Ok(chunk) => Ok(chunk.map(|c| (c, body))),
+                    Err(e) => Err(StorageError::Unavailable(format!(
+                        "read object {}: {}",
+                        key, e
+                    ))),
+                }
+            }
+        })
+        .boxed();
+        Ok(Some(ObjectStream {
+            content_type: output.content_type,
+            content_length: output.content_length.and_then(|l| u64::try_from(l).ok()),
+            body,
+        }))
+    }
+
+    async fn presign_get(&self, key: &str, ttl: Duration) -> StorageResult<String> {
+        let presigning = PresigningConfig::expires_in(ttl)
+            .map_err(|e| StorageError::Other(format!("presign ttl: {}", e)))?;
+        let request = self
+            .client
+            .get_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .presigned(presigning)
+            .await
+            .map_err(|e| classify("presign get", key, e))?;
+        Ok(request.uri().to_string())
+    }
+
+    async fn create_multipart(&self, key: &str, content_type: &str) -> StorageResult<String> {
+        let output = self
+            .client
+            .create_multipart_upload()
+            .bucket(&self.bucket)
+            .key(key)
+            .content_type(content_type)
+            .send()
+            .await
+            .map_err(|e| classify("create multipart upload", key, e))?;
+        output
+            .upload_id
+            .ok_or_else(|| StorageError::Other(format!("create multipart upload {}: no id", key)))
+    }
+
+    async fn upload_part(
+        &self,
+        key: &str,
+        upload_id: &str,
+        number: i32,
+        bytes: Vec<u8>,
+    ) -> StorageResult<String> {
+        let output = self
+            .client
+            .upload_part()
+            .bucket(&self.bucket)
+            .key(key)
+            .upload_id(upload_id)
+            .part_number(number)
+            .body(ByteStream::from(bytes))
+            .send()
+            .await
+            .map_err(|e| classify("upload part", key, e))?;
+        output
+            .e_tag
+            .ok_or_else(|| StorageError::Other(format!("upload part {}: no ETag", key)))
+    }
+
+    async fn complete_multipart(
+        &self,
+        key: &str,
+        upload_id: &str,
+        parts: &[CompletedPart],
+    ) -> StorageResult<()> {
+        let parts = parts
+            .iter()
+            .map(|part| {
+                S3CompletedPart::builder()
+                    .part_number(part.number)
+                    .e_tag(&part.etag)
+                    .build()
+            })
+            .collect();
+        self.client
+            .complete_multipart_upload()
+            .bucket(&self.bucket)
+            .key(key)
+            .upload_id(upload_id)
+            .multipart_upload(
+                CompletedMultipartUpload::builder()
+                    .set_parts(Some(parts))
+                    .build(),
+            )
+            .send()
+            .await
+            .map_err(|e| classify("complete multipart upload", key, e))?;
+        Ok(())
+    }
+
+    async fn abort_multipart(&self, key: &str, upload_id: &str) -> StorageResult<()> {
+        match self
+            .client
+            .abort_multipart_upload()
+            .bucket(&self.bucket)
+            .key(key)
+            .upload_id(upload_id)
+            .send()
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(e)
+                if e.as_service_error()
+                    .is_some_and(|se| se.is_no_such_upload()) =>
+            {
+                Ok(())
+            }
+            Err(e) => Err(classify("abort multipart upload", key, e)),
+        }
+    }
+}
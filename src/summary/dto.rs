@@ -0,0 +1,216 @@
+use serde::{Deserialize, Serialize};
+use time::{Date, Month};
+
+use crate::{
+    activity::dto::{ActivityTotals, CalorieBudget},
+    goals::dto::{GoalProgress, GoalsResponse, Intake, LimitWarning},
+    meals::{compliance::DietCompliance, warnings::Ingredient},
+    plans::dto::PlanComparison,
+};
+
+/// Days averaged by the rolling columns of a trend.
+pub const ROLLING_WINDOW_DAYS: i32 = 7;
+
+time::serde::format_description!(iso_date, Date, "[year]-[month]-[day]");
+time::serde::format_description!(iso_month, Date, "[year]-[month]");
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TrendRange {
+    #[default]
+    Week,
+    Month,
+}
+
+impl TrendRange {
+    /// Number of days covered, ending today.
+    pub fn days(self) -> i64 {
+        match self {
+            TrendRange::Week => 7,
+            TrendRange::Month => 30,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TrendsQuery {
+    #[serde(default)]
+    pub range: TrendRange,
+}
+
+/// Totals of one UTC day. Days without meals are included with zeros so
+/// charts get a continuous axis.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct TrendDay {
+    #[serde(with = "iso_date")]
+    pub date: Date,
+    pub meals: i64,
+    /// Meals that have nutrition; the others don't count towards totals.
+    pub analyzed_meals: i64,
+    pub calories_kcal: f64,
+    pub protein_g: f64,
+    pub fat_g: f64,
+    pub carbs_g: f64,
+    pub sugar_g: f64,
+    pub sodium_mg: f64,
+    pub fiber_g: f64,
+    pub caffeine_mg: f64,
+    pub alcohol_g: f64,
+    /// Mean global score of the day's meals.
+    pub avg_score: Option<f64>,
+    /// Mean daily calories over the trailing window, skipping days without
+    /// analyzed meals.
+    pub rolling_calories_kcal: Option<f64>,
+    pub rolling_score: Option<f64>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct DailyAverages {
+    /// Days with at least one analyzed meal.
+    pub days_logged: usize,
+    pub calories_kcal: Option<f64>,
+    pub protein_g: Option<f64>,
+    pub fat_g: Option<f64>,
+    pub carbs_g: Option<f64>,
+    pub sugar_g: Option<f64>,
+    pub sodium_mg: Option<f64>,
+    pub fiber_g: Option<f64>,
+    pub caffeine_mg: Option<f64>,
+    pub alcohol_g: Option<f64>,
+    pub score: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DayHighlight {
+    #[serde(with = "iso_date")]
+    pub date: Date,
+    pub avg_score: f64,
+    pub calories_kcal: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TrendsResponse {
+    pub range: TrendRange,
+    #[serde(with = "iso_date")]
+    pub from: Date,
+    #[serde(with = "iso_date")]
+    pub to: Date,
+    pub rolling_window_days: i32,
+    pub days: Vec<TrendDay>,
+    pub averages: DailyAverages,
+    /// Days with the highest and lowest mean global score.
+    pub best_day: Option<DayHighlight>,
+    pub worst_day: Option<DayHighlight>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DailyQuery {
+    /// UTC day to summarize; today when omitted.
+    #[serde(default, with = "iso_date::option")]
+    pub date: Option<Date>,
+}
+
+/// Meal totals of one UTC day.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct DayTotals {
+    pub meals: i64,
+    pub analyzed_meals: i64,
+    #[sqlx(flatten)]
+    pub intake: Intake,
+    pub avg_score: Option<f64>,
+    /// Everything the day's meals likely contain.
+    pub contains: Vec<Ingredient>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DailySummary {
+    #[serde(with = "iso_date")]
+    pub date: Date,
+    pub meals: i64,
+    pub analyzed_meals: i64,
+    pub totals: Intake,
+    pub avg_score: Option<f64>,
+    /// How well the day fits the user's diet profile; `null` without a
+    /// profile or analyzed meals.
+    pub diet_compliance: Option<DietCompliance>,
+    pub goals: GoalsResponse,
+    pub progress: GoalProgress,
+    /// Daily limits (sugar, sodium, caffeine, alcohol) the day went over.
+    pub limit_warnings: Vec<LimitWarning>,
+    /// Burned calories and workouts from connected health apps.
+    pub activity: ActivityTotals,
+    pub calorie_budget: CalorieBudget,
+    /// Planned intake from the week's meal plan; `null` without one.
+    pub plan: Option<PlanComparison>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CalendarQuery {
+    /// `YYYY-MM`; the current month when omitted.
+    pub month: Option<String>,
+}
+
+impl CalendarQuery {
+    /// First day of the requested month.
+    pub fn first_day(&self) -> Result<Option<Date>, String> {
+        let Some(month) = &self.month else {
+            return Ok(None);
+        };
+        let invalid = || format!("month must be YYYY-MM, got {month:?}");
+        let (year, number) = month.trim().split_once('-').ok_or_else(invalid)?;
+        if year.len() != 4 || number.len() != 2 {
+            return Err(invalid());
+        }
+        let year: i32 = year.parse().map_err(|_| invalid())?;
+        let number: u8 = number.parse().map_err(|_| invalid())?;
+        let month = Month::try_from(number).map_err(|_| invalid())?;
+        Date::from_calendar_date(year, month, 1)
+            .map(Some)
+            .map_err(|_| invalid())
+    }
+}
+
+/// One day of the month view.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct CalendarDay {
+    #[serde(with = "iso_date")]
+    pub date: Date,
+    pub meals: i64,
+    pub calories_kcal: f64,
+    /// Calories as a percentage of the daily calorie goal; `null` on days
+    /// without analyzed meals.
+    pub goal_percent: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CalendarResponse {
+    #[serde(with = "iso_month")]
+    pub month: Date,
+    pub calorie_goal_kcal: f64,
+    /// Every day of the month, in order.
+    pub days: Vec<CalendarDay>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::date;
+
+    fn query(month: &str) -> CalendarQuery {
+        CalendarQuery {
+            month: Some(month.into()),
+        }
+    }
+
+    #[test]
+    fn calendar_month_parses_to_its_first_day() {
+        assert_eq!(
+            query("2024-02").first_day(),
+            Ok(Some(date!(2024 - 02 - 01)))
+        );
+        assert_eq!(CalendarQuery { month: None }.first_day(), Ok(None));
+        for invalid in ["2024-13", "2024-2", "24-02", "2024-02-01", "february"] {
+            assert!(query(invalid).first_day().is_err(), "{invalid}");
+        }
+    }
+}
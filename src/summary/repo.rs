@@ -0,0 +1,170 @@
+use sqlx::PgPool;
+use time::Date;
+use uuid::Uuid;
+
+use crate::summary::dto::{CalendarDay, DayTotals, TrendDay, ROLLING_WINDOW_DAYS};
+
+/// Totals of the meals logged on `date` in time zone `tz`.
+pub async fn day_totals(
+    db: &PgPool,
+    user_id: Uuid,
+    date: Date,
+    tz: &str,
+) -> anyhow::Result<DayTotals> {
+    let totals = sqlx::query_as::<_, DayTotals>(
+        r#"
+        SELECT COUNT(*) AS meals,
+               COUNT(n.meal_id) AS analyzed_meals,
+               COALESCE(SUM(n.total_calories_kcal), 0)::float8 AS calories_kcal,
+               COALESCE(SUM(n.protein_g), 0)::float8 AS protein_g,
+               COALESCE(SUM(n.fat_g), 0)::float8 AS fat_g,
+               COALESCE(SUM(n.carbs_g), 0)::float8 AS carbs_g,
+               COALESCE(SUM(n.fiber_g), 0)::float8 AS fiber_g,
+               COALESCE(SUM(n.sugar_g), 0)::float8 AS sugar_g,
+               COALESCE(SUM(n.sodium_mg), 0)::float8 AS sodium_mg,
+               COALESCE(SUM(n.caffeine_mg), 0)::float8 AS caffeine_mg,
+               COALESCE(SUM(n.alcohol_g), 0)::float8 AS alcohol_g,
+               ROUND(AVG(n.global_score), 1)::float8 AS avg_score,
+               ARRAY(
+                   SELECT DISTINCT c
+                   FROM meals d, unnest(d.contains) AS c
+                   WHERE d.user_id = $1
+                     AND d.created_at >= $2::date::timestamp AT TIME ZONE $3
+                     AND d.created_at < ($2::date + 1)::timestamp AT TIME ZONE $3
+               ) AS contains
+        FROM meals m
+        LEFT JOIN meal_nutrition n ON n.meal_id = m.id
+        WHERE m.user_id = $1
+          AND m.created_at >= $2::date::timestamp AT TIME ZONE $3
+          AND m.created_at < ($2::date + 1)::timestamp AT TIME ZONE $3
+        "#,
+    )
+    .bind(user_id)
+    .bind(date)
+    .bind(tz)
+    .fetch_one(db)
+    .await?;
+    Ok(totals)
+}
+
+/// Per-day totals for `from..=to`, days starting at midnight in `tz`. Meals
+/// before `from` are read as well so the rolling averages of the first days
+/// are complete.
+pub async fn daily_trend(
+    db: &PgPool,
+    user_id: Uuid,
+    from: Date,
+    to: Date,
+    tz: &str,
+) -> anyhow::Result<Vec<TrendDay>> {
+    let days = sqlx::query_as::<_, TrendDay>(
+        r#"
+        WITH series AS (
+            SELECT d::date AS day
+            FROM generate_series(($2::date - ($4 - 1))::timestamp, $3::date::timestamp,
+                                 interval '1 day') AS d
+        ),
+        daily AS (
+            SELECT (m.created_at AT TIME ZONE $5)::date AS day,
+                   COUNT(*) AS meals,
+                   COUNT(n.meal_id) AS analyzed_meals,
+                   SUM(n.total_calories_kcal) AS calories_kcal,
+                   SUM(n.protein_g) AS protein_g,
+                   SUM(n.fat_g) AS fat_g,
+                   SUM(n.carbs_g) AS carbs_g,
+                   SUM(n.sugar_g) AS sugar_g,
+                   SUM(n.sodium_mg) AS sodium_mg,
+                   SUM(n.fiber_g) AS fiber_g,
+                   SUM(n.caffeine_mg) AS caffeine_mg,
+                   SUM(n.alcohol_g) AS alcohol_g,
+                   AVG(n.global_score) AS avg_score
+            FROM meals m
+            LEFT JOIN meal_nutrition n ON n.meal_id = m.id
+            WHERE m.user_id = $1
+              AND m.created_at >= ($2::date - ($4 - 1))::timestamp AT TIME ZONE $5
+              AND m.created_at < ($3::date + 1)::timestamp AT TIME ZONE $5
+            GROUP BY 1
+        ),
+        rolling AS (
+            SELECT s.day,
+                   COALESCE(d.meals, 0) AS meals,
+                   COALESCE(d.analyzed_meals, 0) AS analyzed_meals,
+                   d.calories_kcal, d.protein_g, d.fat_g, d.carbs_g,
+                   d.sugar_g, d.sodium_mg, d.fiber_g, d.caffeine_mg, d.alcohol_g,
+                   d.avg_score,
+                   AVG(CASE WHEN d.analyzed_meals > 0 THEN COALESCE(d.calories_kcal, 0) END)
+                       OVER w AS rolling_calories_kcal,
+                   AVG(d.avg_score) OVER w AS rolling_score
+            FROM series s
+            LEFT JOIN daily d ON d.day = s.day
+            WINDOW w AS (ORDER BY s.day ROWS BETWEEN $4 - 1 PRECEDING AND CURRENT ROW)
+        )
+        SELECT day AS date,
+               meals,
+               analyzed_meals,
+               COALESCE(calories_kcal, 0)::float8 AS calories_kcal,
+               COALESCE(protein_g, 0)::float8 AS protein_g,
+               COALESCE(fat_g, 0)::float8 AS fat_g,
+               COALESCE(carbs_g, 0)::float8 AS carbs_g,
+               COALESCE(sugar_g, 0)::float8 AS sugar_g,
+               COALESCE(sodium_mg, 0)::float8 AS sodium_mg,
+               COALESCE(fiber_g, 0)::float8 AS fiber_g,
+               COALESCE(caffeine_mg, 0)::float8 AS caffeine_mg,
+               COALESCE(alcohol_g, 0)::float8 AS alcohol_g,
+               ROUND(avg_score, 1)::float8 AS avg_score,
+               ROUND(rolling_calories_kcal, 1)::float8 AS rolling_calories_kcal,
+               ROUND(rolling_score, 1)::float8 AS rolling_score
+        FROM rolling
+        WHERE day >= $2
+        ORDER BY day
+        "#,
+    )
+    .bind(user_id)
+    .bind(from)
+    .bind(to)
+    .bind(ROLLING_WINDOW_DAYS)
+    .bind(tz)
+    .fetch_all(db)
+    .await?;
+    Ok(days)
+}
+
+/// Meal count and calories of every day of the month starting at `month`,
+/// days starting at midnight in `tz`, with calories as a percentage of
+/// `calorie_goal_kcal`.
+pub async fn calendar(
+    db: &PgPool,
+    user_id: Uuid,
+    month: Date,
+    tz: &str,
+    calorie_goal_kcal: f64,
+) -> anyhow::Result<Vec<CalendarDay>> {
+    let days = sqlx::query_as::<_, CalendarDay>(
+        r#"
+        SELECT s.day::date AS date,
+               COUNT(m.id) AS meals,
+               COALESCE(SUM(n.total_calories_kcal), 0)::float8 AS calories_kcal,
+               CASE WHEN COUNT(n.meal_id) > 0
+                    THEN ROUND(COALESCE(SUM(n.total_calories_kcal), 0) * 100
+                               / $4::float8::numeric, 1)::float8
+               END AS goal_percent
+        FROM generate_series($2::date::timestamp,
+                             ($2::date + interval '1 month' - interval '1 day'),
+                             interval '1 day') AS s(day)
+        LEFT JOIN meals m
+               ON m.user_id = $1
+              AND m.created_at >= s.day AT TIME ZONE $3
+              AND m.created_at < (s.day + interval '1 day') AT TIME ZONE $3
+        LEFT JOIN meal_nutrition n ON n.meal_id = m.id
+        GROUP BY s.day
+        ORDER BY s.day
+        "#,
+    )
+    .bind(user_id)
+    .bind(month)
+    .bind(tz)
+    .bind(calorie_goal_kcal)
+    .fetch_all(db)
+    .await?;
+    Ok(days)
+}
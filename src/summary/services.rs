@@ -0,0 +1,290 @@
+use std::collections::HashMap;
+
+use time::{Date, Duration};
+use uuid::Uuid;
+
+use crate::{
+    activity::{dto::CalorieBudget, repo as activity_repo},
+    cache::{keys, SUMMARY_TTL},
+    db::AppState,
+    goals::{
+        dto::{GoalProgress, GoalsResponse},
+        repo as goals_repo,
+    },
+    plans::{dto::PlanComparison, services as plan_services},
+    preferences::services as preferences_services,
+    stats::repo as stats_repo,
+    summary::{
+        dto::{
+            CalendarResponse, DailyAverages, DailySummary, DayHighlight, TrendDay, TrendRange,
+            TrendsResponse, ROLLING_WINDOW_DAYS,
+        },
+        repo,
+    },
+};
+
+/// Days kept in a user's summary cache entry before it starts over.
+const MAX_CACHED_DAYS: usize = 31;
+
+/// Totals of one day (by default today) in the time zone of the user's
+/// preferences, progress towards the user's goals and, when the day is
+/// planned, planned vs actual intake. Served from the cache until the user's
+/// meals, goals, plans or preferences change.
+pub async fn daily(
+    state: &AppState,
+    user_id: Uuid,
+    date: Option<Date>,
+) -> anyhow::Result<DailySummary> {
+    let tz = preferences_services::timezone(state, user_id).await?;
+    let date = match date {
+        Some(date) => date,
+        None => stats_repo::today_in(&state.db, &tz).await?,
+    };
+    let generation = state.cache.summary_generation(user_id).await;
+    let key = keys::daily_summaries(user_id, &generation);
+    let mut cached: HashMap<String, DailySummary> = state.cache.get(&key).await.unwrap_or_default();
+    let day = date.to_string();
+    if let Some(summary) = cached.remove(&day) {
+        return Ok(summary);
+    }
+
+    let summary = compute_daily(state, user_id, date, &tz).await?;
+    if cached.len() >= MAX_CACHED_DAYS {
+        cached.clear();
+    }
+    cached.insert(day.clone(), summary);
+    state.cache.put(&key, &cached, SUMMARY_TTL).await;
+    Ok(cached.remove(&day).expect("summary was just inserted"))
+}
+
+async fn compute_daily(
+    state: &AppState,
+    user_id: Uuid,
+    date: Date,
+    tz: &str,
+) -> anyhow::Result<DailySummary> {
+    let totals = repo::day_totals(&state.db, user_id, date, tz).await?;
+    let goals = goals_repo::find_goals(&state.db, user_id).await?;
+    let activity = activity_repo::day_totals(&state.db, user_id, date, tz).await?;
+    let prefs = preferences_services::preferences(state, user_id).await?;
+    let targets = goals.targets();
+    let calorie_budget = CalorieBudget::new(
+        targets.calories_kcal,
+        activity.burned_kcal,
+        prefs.activity_eat_back_percent,
+        totals.intake.calories_kcal.unwrap_or(0.0),
+    );
+    let diet_compliance = prefs
+        .diet_profile
+        .filter(|_| totals.analyzed_meals > 0)
+        .and_then(|profile| profile.compliance(&totals.intake, &totals.contains, 1.0));
+    let plan = plan_services::planned_day(state, user_id, date)
+        .await?
+        .map(|day| PlanComparison::new(&day, &totals.intake));
+    let progress = GoalProgress::new(&totals.intake, &targets);
+    Ok(DailySummary {
+        date,
+        meals: totals.meals,
+        analyzed_meals: totals.analyzed_meals,
+        limit_warnings: progress.exceeded_limits(),
+        progress,
+        totals: totals.intake,
+        avg_score: totals.avg_score,
+        diet_compliance,
+        goals: GoalsResponse::from(&goals),
+        activity,
+        calorie_budget,
+        plan,
+    })
+}
+
+/// Trend over the last 7 or 30 days in the user's time zone, today included.
+pub async fn trends(
+    state: &AppState,
+    user_id: Uuid,
+    range: TrendRange,
+) -> anyhow::Result<TrendsResponse> {
+    let tz = preferences_services::timezone(state, user_id).await?;
+    let to = stats_repo::today_in(&state.db, &tz).await?;
+    let from = to - Duration::days(range.days() - 1);
+    let days = repo::daily_trend(&state.db, user_id, from, to, &tz).await?;
+    let (best_day, worst_day) = best_and_worst(&days);
+    Ok(TrendsResponse {
+        range,
+        from,
+        to,
+        rolling_window_days: ROLLING_WINDOW_DAYS,
+        averages: averages(&days),
+        best_day,
+        worst_day,
+        days,
+    })
+}
+
+/// Meals, calories and calorie goal adherence of each day of `month` (by
+/// default the current one) in the user's time zone, for a month view.
+pub async fn calendar(
+    state: &AppState,
+    user_id: Uuid,
+    month: Option<Date>,
+) -> anyhow::Result<CalendarResponse> {
+    let tz = preferences_services::timezone(state, user_id).await?;
+    let month = match month {
+        Some(month) => month,
+        None => stats_repo::today_in(&state.db, &tz).await?.replace_day(1)?,
+    };
+    let goals = goals_repo::find_goals(&state.db, user_id).await?;
+    let calorie_goal_kcal = goals.targets().calories_kcal;
+    let days = repo::calendar(&state.db, user_id, month, &tz, calorie_goal_kcal).await?;
+    Ok(CalendarResponse {
+        month,
+        calorie_goal_kcal,
+        days,
+    })
+}
+
+fn round1(value: f64) -> f64 {
+    (value * 10.0).round() / 10.0
+}
+
+fn mean(values: impl Iterator<Item = f64>) -> Option<f64> {
+    let (sum, count) = values.fold((0.0, 0usize), |(sum, count), v| (sum + v, count + 1));
+    (count > 0).then(|| round1(sum / count as f64))
+}
+
+/// Averages per logged day; days without analyzed meals are left out.
+pub fn averages(days: &[TrendDay]) -> DailyAverages {
+    let logged: Vec<&TrendDay> = days.iter().filter(|d| d.analyzed_meals > 0).collect();
+    let avg = |field: fn(&TrendDay) -> f64| mean(logged.iter().map(|d| field(d)));
+    DailyAverages {
+        days_logged: logged.len(),
+        calories_kcal: avg(|d| d.calories_kcal),
+        protein_g: avg(|d| d.protein_g),
+        fat_g: avg(|d| d.fat_g),
+        carbs_g: avg(|d| d.carbs_g),
+        sugar_g: avg(|d| d.sugar_g),
+        sodium_mg: avg(|d| d.sodium_mg),
+        fiber_g: avg(|d| d.fiber_g),
+        caffeine_mg: avg(|d| d.caffeine_mg),
+        alcohol_g: avg(|d| d.alcohol_g),
+        score: mean(logged.iter().filter_map(|d| d.avg_score)),
+    }
+}
+
+/// Highest and lowest scoring days; ties go to the earlier day.
+pub fn best_and_worst(days: &[TrendDay]) -> (Option<DayHighlight>, Option<DayHighlight>) {
+    let mut best: Option<DayHighlight> = None;
+    let mut worst: Option<DayHighlight> = None;
+    for day in days {
+        let Some(score) = day.avg_score else {
+            continue;
+        };
+        let highlight = DayHighlight {
+            date: day.date,
+            avg_score: score,
+            calories_kcal: day.calories_kcal,
+        };
+        if best.as_ref().is_none_or(|b| score > b.avg_score) {
+            best = Some(highlight.clone());
+        }
+        if worst.as_ref().is_none_or(|w| score < w.avg_score) {
+            worst = Some(highlight);
+        }
+    }
+    (best, worst)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        activity::dto::ActivityTotals,
+        goals::dto::{Goals, Intake},
+        meals::compliance::{DietCompliance, DietProfile},
+    };
+    use time::macros::date;
+
+    fn day(date: time::Date, analyzed: i64, calories: f64, score: Option<f64>) -> TrendDay {
+        TrendDay {
+            date,
+            meals: analyzed,
+            analyzed_meals: analyzed,
+            calories_kcal: calories,
+            protein_g: 0.0,
+            fat_g: 0.0,
+            carbs_g: 0.0,
+            sugar_g: 0.0,
+            sodium_mg: 0.0,
+            fiber_g: 0.0,
+            caffeine_mg: 0.0,
+            alcohol_g: 0.0,
+            avg_score: score,
+            rolling_calories_kcal: None,
+            rolling_score: None,
+        }
+    }
+
+    #[test]
+    fn averages_skip_days_without_analyzed_meals() {
+        let days = [
+            day(date!(2024 - 03 - 01), 2, 1800.0, Some(70.0)),
+            day(date!(2024 - 03 - 02), 0, 0.0, None),
+            day(date!(2024 - 03 - 03), 1, 2200.0, Some(80.0)),
+        ];
+        let averages = averages(&days);
+        assert_eq!(averages.days_logged, 2);
+        assert_eq!(averages.calories_kcal, Some(2000.0));
+        assert_eq!(averages.score, Some(75.0));
+    }
+
+    #[test]
+    fn best_and_worst_use_scores_and_prefer_earlier_days() {
+        let days = [
+            day(date!(2024 - 03 - 01), 1, 1500.0, Some(60.0)),
+            day(date!(2024 - 03 - 02), 1, 2500.0, Some(90.0)),
+            day(date!(2024 - 03 - 03), 1, 2000.0, Some(60.0)),
+            day(date!(2024 - 03 - 04), 0, 0.0, None),
+        ];
+        let (best, worst) = best_and_worst(&days);
+        assert_eq!(best.unwrap().date, date!(2024 - 03 - 02));
+        assert_eq!(worst.unwrap().date, date!(2024 - 03 - 01));
+    }
+
+    #[test]
+    fn empty_trend_has_no_highlights() {
+        assert_eq!(best_and_worst(&[]), (None, None));
+        assert_eq!(averages(&[]), DailyAverages::default());
+    }
+
+    #[test]
+    fn daily_summary_survives_the_cache_round_trip() {
+        let intake = Intake {
+            calories_kcal: Some(650.0),
+            ..Intake::default()
+        };
+        let goals = Goals::default();
+        let summary = DailySummary {
+            date: date!(2024 - 03 - 01),
+            meals: 2,
+            analyzed_meals: 1,
+            progress: GoalProgress::new(&intake, &goals.targets()),
+            limit_warnings: Vec::new(),
+            totals: intake,
+            avg_score: Some(71.5),
+            diet_compliance: Some(DietCompliance {
+                profile: DietProfile::LowSodium,
+                score: 80.0,
+            }),
+            goals: GoalsResponse::from(&goals),
+            activity: ActivityTotals {
+                burned_kcal: 300.0,
+                entries: 1,
+            },
+            calorie_budget: CalorieBudget::new(2000.0, 300.0, 50, 650.0),
+            plan: None,
+        };
+        let json = serde_json::to_string(&summary).unwrap();
+        let cached: DailySummary = serde_json::from_str(&json).unwrap();
+        assert_eq!(serde_json::to_string(&cached).unwrap(), json);
+    }
+}
@@ -0,0 +1,197 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::meals::dto::{MealNutrition, MealResponse, MealType, NewMeal, MAX_TAG_LEN};
+
+pub const DEFAULT_PULL_LIMIT: i64 = 100;
+pub const MAX_PULL_LIMIT: i64 = 500;
+pub const MAX_PUSH_CHANGES: usize = 100;
+pub const MAX_TAGS: usize = 20;
+
+#[derive(Debug, Deserialize)]
+pub struct PullQuery {
+    /// `cursor` of the previous pull; 0 (the default) starts from scratch.
+    #[serde(default)]
+    pub cursor: i64,
+    pub limit: Option<i64>,
+}
+
+impl PullQuery {
+    pub fn limit(&self) -> i64 {
+        self.limit
+            .unwrap_or(DEFAULT_PULL_LIMIT)
+            .clamp(1, MAX_PULL_LIMIT)
+    }
+}
+
+/// A meal as a pull returns it: the list fields plus what an offline copy
+/// needs to show it.
+#[derive(Debug, Serialize)]
+pub struct SyncedMeal {
+    #[serde(flatten)]
+    pub meal: MealResponse,
+    pub nutrition: Option<MealNutrition>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub updated_at: OffsetDateTime,
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct Tombstone {
+    pub id: Uuid,
+    #[serde(with = "time::serde::rfc3339")]
+    pub deleted_at: OffsetDateTime,
+    #[serde(skip)]
+    pub sync_version: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PullResponse {
+    pub meals: Vec<SyncedMeal>,
+    pub deleted: Vec<Tombstone>,
+    /// Pass to the next pull.
+    pub cursor: i64,
+    /// More changes are waiting; pull again right away.
+    pub has_more: bool,
+}
+
+/// A meal created, edited or deleted on the device. `id` is generated by
+/// the client for new meals.
+#[derive(Debug, Deserialize)]
+pub struct MealChange {
+    pub id: Uuid,
+    pub title: Option<String>,
+    pub notes: Option<String>,
+    pub meal_type: Option<MealType>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// When the meal was eaten; defaults to `updated_at` for new meals.
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub created_at: Option<OffsetDateTime>,
+    /// When the change was made on the device. Times ahead of the server's
+    /// clock count as now, so a device with a fast clock can't win every
+    /// later conflict.
+    #[serde(with = "time::serde::rfc3339")]
+    pub updated_at: OffsetDateTime,
+    #[serde(default)]
+    pub deleted: bool,
+}
+
+impl MealChange {
+    /// Applies the limits of the other meal endpoints; tags are trimmed,
+    /// lowercased and deduplicated like bulk tagging does, and `updated_at`
+    /// is clamped to now.
+    pub fn normalize(&mut self) -> Result<(), String> {
+        self.updated_at = self.updated_at.min(OffsetDateTime::now_utc());
+        let fields = NewMeal {
+            title: self.title.take(),
+            notes: self.notes.take(),
+            meal_type: self.meal_type,
+            ..NewMeal::default()
+        }
+        .normalized()?;
+        self.title = fields.title;
+        self.notes = fields.notes;
+        let mut tags: Vec<String> = self
+            .tags
+            .iter()
+            .map(|t| t.trim().to_lowercase())
+            .filter(|t| !t.is_empty())
+            .collect();
+        tags.sort();
+        tags.dedup();
+        if tags.len() > MAX_TAGS {
+            return Err(format!("At most {} tags per meal", MAX_TAGS));
+        }
+        if tags.iter().any(|t| t.chars().count() > MAX_TAG_LEN) {
+            return Err(format!("Tags must be at most {} characters", MAX_TAG_LEN));
+        }
+        self.tags = tags;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PushRequest {
+    pub meals: Vec<MealChange>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeOutcome {
+    Applied,
+    /// The server has a later edit, or the meal was deleted; the client
+    /// should take `meal` (or drop its copy when `deleted`).
+    Conflict,
+    /// Invalid, or the id belongs to someone else's meal.
+    Rejected,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ChangeResult {
+    pub id: Uuid,
+    pub outcome: ChangeOutcome,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// The server's copy after the change, or the one that won a conflict.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub meal: Option<SyncedMeal>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub deleted: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PushResponse {
+    pub results: Vec<ChangeResult>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn change(tags: &[&str]) -> MealChange {
+        MealChange {
+            id: Uuid::nil(),
+            title: Some("  Oatmeal ".into()),
+            notes: Some(" ".into()),
+            meal_type: None,
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            created_at: None,
+            updated_at: OffsetDateTime::UNIX_EPOCH,
+            deleted: false,
+        }
+    }
+
+    #[test]
+    fn changes_are_normalized_like_other_meal_writes() {
+        let mut c = change(&[" Breakfast", "breakfast", "", "oats"]);
+        c.normalize().unwrap();
+        assert_eq!(c.title.as_deref(), Some("Oatmeal"));
+        assert_eq!(c.notes, None);
+        assert_eq!(c.tags, ["breakfast", "oats"]);
+
+        let long = "x".repeat(MAX_TAG_LEN + 1);
+        assert!(change(&[&long]).normalize().is_err());
+    }
+
+    #[test]
+    fn future_change_times_are_clamped_to_now() {
+        let mut c = change(&[]);
+        c.updated_at = OffsetDateTime::now_utc() + time::Duration::days(365);
+        c.normalize().unwrap();
+        assert!(c.updated_at <= OffsetDateTime::now_utc());
+
+        let mut past = change(&[]);
+        past.normalize().unwrap();
+        assert_eq!(past.updated_at, OffsetDateTime::UNIX_EPOCH);
+    }
+
+    #[test]
+    fn pull_limits_are_clamped() {
+        let query = |limit| PullQuery { cursor: 0, limit };
+        assert_eq!(query(None).limit(), DEFAULT_PULL_LIMIT);
+        assert_eq!(query(Some(0)).limit(), 1);
+        assert_eq!(query(Some(10_000)).limit(), MAX_PULL_LIMIT);
+    }
+}
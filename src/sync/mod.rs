@@ -0,0 +1,11 @@
+//! Offline-first sync for the mobile app. A pull returns the user's meals
+//! changed since a cursor, plus tombstones of deleted ones; a push applies
+//! meals created, edited or deleted offline. Conflicting edits are settled
+//! by `updated_at`: the later edit wins, and the loser gets the winner back.
+//!
+//! Change tracking lives in the database (migration 39), so meals changed
+//! through any other endpoint or job show up in the next pull as well.
+
+pub mod dto;
+pub mod repo;
+pub mod services;
@@ -0,0 +1,303 @@
+use sqlx::{types::Json, FromRow, PgConnection, PgPool};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::{
+    meals::{
+        dto::{DetectedItem, MealNutrition, MealStatus, MealType},
+        repo::Meal,
+        warnings::Ingredient,
+    },
+    sync::dto::{MealChange, Tombstone},
+};
+
+/// A meal with its change tracking columns.
+#[derive(Debug, FromRow)]
+pub struct SyncRow {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub household_id: Option<Uuid>,
+    pub title: Option<String>,
+    pub notes: Option<String>,
+    pub meal_type: Option<MealType>,
+    pub tags: Vec<String>,
+    pub status: MealStatus,
+    pub created_at: OffsetDateTime,
+    pub updated_at: OffsetDateTime,
+    pub sync_version: i64,
+    pub title_generated: bool,
+    pub detected_items: Option<Json<Vec<DetectedItem>>>,
+    pub contains: Vec<Ingredient>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub place_name: Option<String>,
+}
+
+impl From<&SyncRow> for Meal {
+    fn from(row: &SyncRow) -> Self {
+        Meal {
+            id: row.id,
+            user_id: row.user_id,
+            household_id: row.household_id,
+            title: row.title.clone(),
+            notes: row.notes.clone(),
+            meal_type: row.meal_type,
+            tags: row.tags.clone(),
+            status: row.status,
+            created_at: row.created_at,
+            title_generated: row.title_generated,
+            detected_items: row.detected_items.clone(),
+            contains: row.contains.clone(),
+            latitude: row.latitude,
+            longitude: row.longitude,
+            place_name: row.place_name.clone(),
+        }
+    }
+}
+
+#[derive(Debug, FromRow)]
+pub struct NutritionRow {
+    pub meal_id: Uuid,
+    #[sqlx(flatten)]
+    pub nutrition: MealNutrition,
+}
+
+/// Owner and last edit of a meal, or of its tombstone.
+#[derive(Debug, FromRow)]
+pub struct ChangeTarget {
+    pub user_id: Uuid,
+    pub changed_at: OffsetDateTime,
+}
+
+const SYNC_COLUMNS: &str = "id, user_id, household_id, title, notes, meal_type, tags, status, \
+                            created_at, updated_at, sync_version, title_generated, \
+                            detected_items, contains, latitude, longitude, place_name";
+
+/// Waits until every transaction that drew a version for the user's meals
+/// has ended; see migration 39. Held until the pull's transaction ends.
+pub async fn lock_for_pull(conn: &mut PgConnection, user_id: Uuid) -> anyhow::Result<()> {
+    sqlx::query("SELECT pg_advisory_xact_lock(1907, hashtext($1::text))")
+        .bind(user_id)
+        .execute(conn)
+        .await?;
+    Ok(())
+}
+
+/// Highest tombstone version pruned; cursors below it may have missed
+/// deletions.
+pub async fn pruned_through(db: &PgPool) -> anyhow::Result<i64> {
+    let version = sqlx::query_scalar::<_, i64>("SELECT pruned_through FROM meal_sync_horizon")
+        .fetch_optional(db)
+        .await?;
+    Ok(version.unwrap_or(0))
+}
+
+/// Up to `limit` of the user's meals changed after `cursor`, in change order.
+pub async fn changed_meals(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    cursor: i64,
+    limit: i64,
+) -> anyhow::Result<Vec<SyncRow>> {
+    let rows = sqlx::query_as::<_, SyncRow>(&format!(
+        r#"
+        SELECT {SYNC_COLUMNS}
+        FROM meals
+        WHERE user_id = $1 AND sync_version > $2
+        ORDER BY sync_version
+        LIMIT $3
+        "#
+    ))
+    .bind(user_id)
+    .bind(cursor)
+    .bind(limit)
+    .fetch_all(conn)
+    .await?;
+    Ok(rows)
+}
+
+/// Up to `limit` of the user's meals deleted after `cursor`, in change order.
+pub async fn tombstones(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    cursor: i64,
+    limit: i64,
+) -> anyhow::Result<Vec<Tombstone>> {
+    let rows = sqlx::query_as::<_, Tombstone>(
+        r#"
+        SELECT meal_id AS id, deleted_at, sync_version
+        FROM meal_tombstones
+        WHERE user_id = $1 AND sync_version > $2
+        ORDER BY sync_version
+        LIMIT $3
+        "#,
+    )
+    .bind(user_id)
+    .bind(cursor)
+    .bind(limit)
+    .fetch_all(conn)
+    .await?;
+    Ok(rows)
+}
+
+pub async fn find_meals(
+    db: &PgPool,
+    user_id: Uuid,
+    meal_ids: &[Uuid],
+) -> anyhow::Result<Vec<SyncRow>> {
+    let rows = sqlx::query_as::<_, SyncRow>(&format!(
+        "SELECT {SYNC_COLUMNS} FROM meals WHERE user_id = $1 AND id = ANY($2)"
+    ))
+    .bind(user_id)
+    .bind(meal_ids)
+    .fetch_all(db)
+    .await?;
+    Ok(rows)
+}
+
+pub async fn nutrition_for(db: &PgPool, meal_ids: &[Uuid]) -> anyhow::Result<Vec<NutritionRow>> {
+    let rows = sqlx::query_as::<_, NutritionRow>(
+        r#"
+        SELECT meal_id,
+               total_calories_kcal::float8 AS total_calories_kcal,
+               protein_g::float8 AS protein_g,
+               fat_g::float8 AS fat_g,
+               carbs_g::float8 AS carbs_g,
+               sodium_mg::float8 AS sodium_mg,
+               sugar_g::float8 AS sugar_g,
+               fiber_g::float8 AS fiber_g,
+               caffeine_mg::float8 AS caffeine_mg,
+               alcohol_g::float8 AS alcohol_g,
+               micros,
+               global_score::float8 AS global_score,
+               source
+        FROM meal_nutrition
+        WHERE meal_id = ANY($1)
+        "#,
+    )
+    .bind(meal_ids)
+    .fetch_all(db)
+    .await?;
+    Ok(rows)
+}
+
+/// Locks the meal for the rest of the transaction.
+pub async fn lock_meal(
+    conn: &mut PgConnection,
+    meal_id: Uuid,
+) -> anyhow::Result<Option<ChangeTarget>> {
+    let target = sqlx::query_as::<_, ChangeTarget>(
+        "SELECT user_id, updated_at AS changed_at FROM meals WHERE id = $1 FOR UPDATE",
+    )
+    .bind(meal_id)
+    .fetch_optional(conn)
+    .await?;
+    Ok(target)
+}
+
+pub async fn find_tombstone(
+    conn: &mut PgConnection,
+    meal_id: Uuid,
+) -> anyhow::Result<Option<ChangeTarget>> {
+    let target = sqlx::query_as::<_, ChangeTarget>(
+        "SELECT user_id, deleted_at AS changed_at FROM meal_tombstones WHERE meal_id = $1",
+    )
+    .bind(meal_id)
+    .fetch_optional(conn)
+    .await?;
+    Ok(target)
+}
+
+/// Inserts a meal logged offline. Meals from sync have no photos, so there
+/// is nothing to analyze. `false` if the id is taken after all.
+pub async fn insert_meal(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    change: &MealChange,
+) -> anyhow::Result<bool> {
+    let inserted = sqlx::query(
+        r#"
+        INSERT INTO meals (id, user_id, title, notes, meal_type, tags, status,
+                           created_at, updated_at)
+        VALUES ($1, $2, $3, $4, $5, $6, 'done', COALESCE($7, $8), $8)
+        ON CONFLICT (id) DO NOTHING
+        "#,
+    )
+    .bind(change.id)
+    .bind(user_id)
+    .bind(&change.title)
+    .bind(&change.notes)
+    .bind(change.meal_type)
+    .bind(&change.tags)
+    .bind(change.created_at)
+    .bind(change.updated_at)
+    .execute(conn)
+    .await?
+    .rows_affected();
+    Ok(inserted > 0)
+}
+
+/// Replaces the synced fields of a meal the caller locked. `created_at`
+/// only changes when the client sends it; a new title replaces an AI one.
+pub async fn update_meal(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    change: &MealChange,
+) -> anyhow::Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE meals
+        SET title = $3, notes = $4, meal_type = $5, tags = $6,
+            created_at = COALESCE($7, created_at), updated_at = $8,
+            title_generated = title_generated AND title IS NOT DISTINCT FROM $3
+        WHERE id = $1 AND user_id = $2
+        "#,
+    )
+    .bind(change.id)
+    .bind(user_id)
+    .bind(&change.title)
+    .bind(&change.notes)
+    .bind(change.meal_type)
+    .bind(&change.tags)
+    .bind(change.created_at)
+    .bind(change.updated_at)
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+/// Deletes a meal; the trigger leaves its tombstone.
+pub async fn delete_meal(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    meal_id: Uuid,
+) -> anyhow::Result<()> {
+    sqlx::query("DELETE FROM meals WHERE id = $1 AND user_id = $2")
+        .bind(meal_id)
+        .bind(user_id)
+        .execute(conn)
+        .await?;
+    Ok(())
+}
+
+/// Deletes tombstones older than `retention_days` and moves the horizon
+/// past them. Returns how many were deleted.
+pub async fn prune_tombstones(db: &PgPool, retention_days: i32) -> anyhow::Result<u64> {
+    let pruned = sqlx::query_scalar::<_, i64>(
+        r#"
+        WITH pruned AS (
+            DELETE FROM meal_tombstones
+            WHERE deleted_at < NOW() - make_interval(days => $1)
+            RETURNING sync_version
+        ), horizon AS (
+            UPDATE meal_sync_horizon
+            SET pruned_through = GREATEST(pruned_through, (SELECT MAX(sync_version) FROM pruned))
+        )
+        SELECT COUNT(*) FROM pruned
+        "#,
+    )
+    .bind(retention_days)
+    .fetch_one(db)
+    .await?;
+    Ok(pruned as u64)
+}
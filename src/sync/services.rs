@@ -0,0 +1,262 @@
+use std::collections::HashMap;
+
+use axum::http::StatusCode;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    db::AppState,
+    error::ErrorCode,
+    events::{repo as events_repo, DomainEvent},
+    meals::{repo::Meal, services as meals_services},
+    sync::{
+        dto::{
+            ChangeOutcome, ChangeResult, MealChange, PullQuery, PullResponse, PushRequest,
+            PushResponse, SyncedMeal, MAX_PUSH_CHANGES,
+        },
+        repo::{self, SyncRow},
+    },
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum SyncError {
+    #[error("Sync cursor expired; pull again from cursor 0")]
+    CursorExpired,
+    #[error("{0}")]
+    Invalid(String),
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+impl SyncError {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            SyncError::CursorExpired => StatusCode::CONFLICT,
+            SyncError::Invalid(_) => StatusCode::BAD_REQUEST,
+            SyncError::Other(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            SyncError::CursorExpired => ErrorCode::SyncCursorExpired,
+            SyncError::Invalid(_) => ErrorCode::ValidationFailed,
+            SyncError::Other(_) => ErrorCode::Internal,
+        }
+    }
+}
+
+/// Meals and tombstones changed after `query.cursor`, oldest change first.
+pub async fn pull(
+    state: &AppState,
+    user_id: Uuid,
+    query: &PullQuery,
+) -> Result<PullResponse, SyncError> {
+    if query.cursor < 0 {
+        return Err(SyncError::Invalid("cursor must not be negative".into()));
+    }
+    if query.cursor > 0 && query.cursor < repo::pruned_through(&state.db).await? {
+        return Err(SyncError::CursorExpired);
+    }
+    let limit = query.limit();
+    let mut tx = state.begin_as(user_id).await.map_err(anyhow::Error::from)?;
+    repo::lock_for_pull(&mut tx, user_id).await?;
+    let mut rows = repo::changed_meals(&mut tx, user_id, query.cursor, limit + 1).await?;
+    let mut deleted = repo::tombstones(&mut tx, user_id, query.cursor, limit + 1).await?;
+    tx.commit().await.map_err(anyhow::Error::from)?;
+
+    let (cursor, has_more) = page_end(
+        rows.iter().map(|r| r.sync_version),
+        deleted.iter().map(|t| t.sync_version),
+        limit,
+    )
+    .unwrap_or((query.cursor, false));
+    rows.retain(|r| r.sync_version <= cursor);
+    deleted.retain(|t| t.sync_version <= cursor);
+    let order: Vec<Uuid> = rows.iter().map(|r| r.id).collect();
+    let mut meals = synced_meals(state, rows).await?;
+    Ok(PullResponse {
+        meals: order.iter().filter_map(|id| meals.remove(id)).collect(),
+        deleted,
+        cursor,
+        has_more,
+    })
+}
+
+/// Version of the `limit`-th change of the two ascending version lists,
+/// and whether more follow; `None` without changes.
+fn page_end(
+    meals: impl Iterator<Item = i64>,
+    tombstones: impl Iterator<Item = i64>,
+    limit: i64,
+) -> Option<(i64, bool)> {
+    let mut versions: Vec<i64> = meals.chain(tombstones).collect();
+    versions.sort_unstable();
+    let limit = limit as usize;
+    let last = *versions.get(limit - 1).or(versions.last())?;
+    Some((last, versions.len() > limit))
+}
+
+/// Applies the changes one by one, each in its own transaction, so one
+/// conflict doesn't hold back the rest.
+pub async fn push(
+    state: &AppState,
+    user_id: Uuid,
+    request: PushRequest,
+) -> Result<PushResponse, SyncError> {
+    if request.meals.len() > MAX_PUSH_CHANGES {
+        return Err(SyncError::Invalid(format!(
+            "At most {} changes per push",
+            MAX_PUSH_CHANGES
+        )));
+    }
+    let mut results = Vec::with_capacity(request.meals.len());
+    let mut changed = false;
+    for mut change in request.meals {
+        if let Err(msg) = change.normalize() {
+            results.push(rejected(change.id, msg));
+            continue;
+        }
+        let result = apply(state, user_id, &change).await?;
+        changed |= result.outcome == ChangeOutcome::Applied;
+        results.push(result);
+    }
+    if changed {
+        state.stats_cache.invalidate(user_id);
+        state.cache.invalidate_summaries(user_id).await;
+    }
+
+    let ids: Vec<Uuid> = results
+        .iter()
+        .filter(|r| r.outcome != ChangeOutcome::Rejected && !r.deleted)
+        .map(|r| r.id)
+        .collect();
+    let rows = repo::find_meals(&state.db, user_id, &ids).await?;
+    let mut meals = synced_meals(state, rows).await?;
+    for result in &mut results {
+        result.meal = meals.remove(&result.id);
+    }
+    info!(
+        user_id = %user_id,
+        changes = results.len(),
+        conflicts = results.iter().filter(|r| r.outcome == ChangeOutcome::Conflict).count(),
+        "sync push applied"
+    );
+    Ok(PushResponse { results })
+}
+
+async fn apply(
+    state: &AppState,
+    user_id: Uuid,
+    change: &MealChange,
+) -> anyhow::Result<ChangeResult> {
+    let mut tx = state.begin_as(user_id).await?;
+    let outcome = match repo::lock_meal(&mut tx, change.id).await? {
+        Some(meal) if meal.user_id != user_id => return Ok(rejected(change.id, not_found())),
+        Some(meal) if change.updated_at < meal.changed_at => ChangeOutcome::Conflict,
+        // The same change pushed again, e.g. after a lost response.
+        Some(meal) if change.updated_at == meal.changed_at && !change.deleted => {
+            ChangeOutcome::Applied
+        }
+        Some(_) if change.deleted => {
+            repo::delete_meal(&mut tx, user_id, change.id).await?;
+            ChangeOutcome::Applied
+        }
+        Some(_) => {
+            repo::update_meal(&mut tx, user_id, change).await?;
+            ChangeOutcome::Applied
+        }
+        None => match repo::find_tombstone(&mut tx, change.id).await? {
+            Some(tombstone) if tombstone.user_id != user_id => {
+                return Ok(rejected(change.id, not_found()))
+            }
+            // Deleting wins over edits from other devices, whenever made.
+            Some(_) if !change.deleted => {
+                return Ok(ChangeResult {
+                    deleted: true,
+                    ..result(change.id, ChangeOutcome::Conflict)
+                })
+            }
+            Some(_) => ChangeOutcome::Applied,
+            None if change.deleted => ChangeOutcome::Applied,
+            None => {
+                if !repo::insert_meal(&mut tx, user_id, change).await? {
+                    // Someone else's meal, hidden by row-level security.
+                    return Ok(rejected(change.id, not_found()));
+                }
+                let created = DomainEvent::MealCreated { meal_id: change.id };
+                events_repo::record(&mut *tx, user_id, &created).await?;
+                ChangeOutcome::Applied
+            }
+        },
+    };
+    tx.commit().await?;
+    Ok(ChangeResult {
+        deleted: change.deleted && outcome == ChangeOutcome::Applied,
+        ..result(change.id, outcome)
+    })
+}
+
+fn result(id: Uuid, outcome: ChangeOutcome) -> ChangeResult {
+    ChangeResult {
+        id,
+        outcome,
+        error: None,
+        meal: None,
+        deleted: false,
+    }
+}
+
+fn rejected(id: Uuid, error: String) -> ChangeResult {
+    ChangeResult {
+        error: Some(error),
+        ..result(id, ChangeOutcome::Rejected)
+    }
+}
+
+fn not_found() -> String {
+    "Meal not found".to_string()
+}
+
+/// Pull and push views of `rows`, by meal id.
+async fn synced_meals(
+    state: &AppState,
+    rows: Vec<SyncRow>,
+) -> anyhow::Result<HashMap<Uuid, SyncedMeal>> {
+    let ids: Vec<Uuid> = rows.iter().map(|r| r.id).collect();
+    let mut nutrition: HashMap<Uuid, _> = repo::nutrition_for(&state.db, &ids)
+        .await?
+        .into_iter()
+        .map(|n| (n.meal_id, n.nutrition))
+        .collect();
+    let updated: HashMap<Uuid, _> = rows.iter().map(|r| (r.id, r.updated_at)).collect();
+    let meals = rows.iter().map(Meal::from).collect();
+    Ok(meals_services::meal_responses(state, meals)
+        .await?
+        .into_iter()
+        .map(|meal| {
+            let synced = SyncedMeal {
+                nutrition: nutrition.remove(&meal.id),
+                updated_at: updated[&meal.id],
+                meal,
+            };
+            (synced.meal.id, synced)
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pages_end_at_the_limit_across_meals_and_tombstones() {
+        let page = |meals: &[i64], tombstones: &[i64], limit| {
+            page_end(meals.iter().copied(), tombstones.iter().copied(), limit)
+        };
+        assert_eq!(page(&[], &[], 10), None);
+        assert_eq!(page(&[3, 7], &[5], 10), Some((7, false)));
+        assert_eq!(page(&[3, 7, 9], &[5], 2), Some((5, true)));
+        assert_eq!(page(&[3, 4], &[], 2), Some((4, false)));
+    }
+}
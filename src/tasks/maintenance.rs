@@ -0,0 +1,88 @@
+//! Housekeeping jobs, run on their cron schedules (see [`crate::cron`]).
+
+use tracing::info;
+
+use crate::{
+    db::AppState,
+    events::repo as events_repo,
+    idempotency::repo as idempotency_repo,
+    images::services::release_objects,
+    photos::repo as photos_repo,
+    shares::repo as shares_repo,
+    sync::repo as sync_repo,
+    uploads::{repo as uploads_repo, services as uploads_services},
+    webhooks::repo as webhooks_repo,
+};
+
+/// Orphaned photos deleted per batch, so one run never holds a long lock.
+const ORPHAN_BATCH: i64 = 100;
+
+/// Deletes photos whose meal is gone, and their objects unless a template or
+/// a deduplicated copy still uses them. Also aborts chunked uploads that can
+/// no longer complete, dropping their stored parts.
+pub async fn cleanup_orphan_photos(state: &AppState) -> anyhow::Result<()> {
+    let grace_hours = state.config.tasks.orphan_photo_grace_hours;
+    let mut deleted = 0;
+    loop {
+        let keys = photos_repo::delete_orphans(&state.db, grace_hours, ORPHAN_BATCH).await?;
+        if keys.is_empty() {
+            break;
+        }
+        deleted += keys.len();
+        release_objects(state, keys).await?;
+    }
+    if deleted > 0 {
+        info!(objects = deleted, "cleaned up orphaned photos");
+    }
+    let aborted = uploads_services::abort_stale_uploads(state).await?;
+    if aborted > 0 {
+        info!(uploads = aborted, "aborted stale chunked uploads");
+    }
+    Ok(())
+}
+
+/// Removes share links, idempotency keys and upload sessions that can no
+/// longer be used.
+pub async fn prune_tokens(state: &AppState) -> anyhow::Result<()> {
+    let shares =
+        shares_repo::prune_inactive(&state.db, state.config.tasks.share_retention_days).await?;
+    let keys = idempotency_repo::purge_all_expired(&state.db).await?;
+    let sessions = uploads_repo::prune_expired(&state.db).await?;
+    if shares + keys + sessions > 0 {
+        info!(
+            shares,
+            idempotency_keys = keys,
+            upload_sessions = sessions,
+            "pruned expired tokens"
+        );
+    }
+    Ok(())
+}
+
+/// Deletes outbox events delivered, and webhook deliveries finished, more
+/// than `EVENTS_RETENTION_DAYS` ago.
+pub async fn prune_outbox(state: &AppState) -> anyhow::Result<()> {
+    let pruned =
+        events_repo::prune_dispatched(&state.db, state.config.events.retention_days).await?;
+    if pruned > 0 {
+        info!(events = pruned, "pruned delivered events");
+    }
+    let pruned =
+        webhooks_repo::prune_finished(&state.db, state.config.events.retention_days).await?;
+    if pruned > 0 {
+        info!(deliveries = pruned, "pruned finished webhook deliveries");
+    }
+    Ok(())
+}
+
+/// Drops tombstones older than `SYNC_TOMBSTONE_RETENTION_DAYS`. Clients
+/// whose cursor predates them have to resync from scratch.
+pub async fn prune_sync_tombstones(state: &AppState) -> anyhow::Result<()> {
+    let pruned =
+        sync_repo::prune_tombstones(&state.db, state.config.tasks.sync_tombstone_retention_days)
+            .await?;
+    if pruned > 0 {
+        info!(tombstones = pruned, "pruned sync tombstones");
+    }
+    Ok(())
+}
@@ -0,0 +1,235 @@
+//! Background task runtime: long-running tasks started next to the HTTP
+//! server, stopped together with it, each reporting its health for
+//! `/health/ready`.
+
+pub mod maintenance;
+
+use std::{
+    collections::BTreeMap,
+    future::Future,
+    panic::AssertUnwindSafe,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use futures::FutureExt;
+use serde::Serialize;
+use time::OffsetDateTime;
+use tokio::{sync::watch, task::JoinHandle};
+use tracing::{error, info, warn};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskState {
+    /// Started but hasn't finished a round of work yet.
+    Starting,
+    Healthy,
+    /// The last round of work failed; the task keeps retrying.
+    Failing,
+    /// Exited or panicked outside of shutdown and won't come back.
+    Stopped,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskReport {
+    pub state: TaskState,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub last_success: Option<OffsetDateTime>,
+    pub last_error: Option<String>,
+}
+
+/// Latest report of every background task, by name. Shared between the
+/// tasks writing it and the readiness check reading it.
+#[derive(Clone, Default)]
+pub struct TaskHealth {
+    tasks: Arc<Mutex<BTreeMap<String, TaskReport>>>,
+}
+
+impl TaskHealth {
+    pub fn snapshot(&self) -> BTreeMap<String, TaskReport> {
+        self.tasks.lock().unwrap().clone()
+    }
+
+    /// Ready unless a task is failing or has stopped.
+    pub fn is_ready(&self) -> bool {
+        self.tasks
+            .lock()
+            .unwrap()
+            .values()
+            .all(|task| matches!(task.state, TaskState::Starting | TaskState::Healthy))
+    }
+
+    fn update(&self, name: &str, apply: impl FnOnce(&mut TaskReport)) {
+        let mut tasks = self.tasks.lock().unwrap();
+        let report = tasks.entry(name.to_string()).or_insert(TaskReport {
+            state: TaskState::Starting,
+            last_success: None,
+            last_error: None,
+        });
+        apply(report);
+    }
+}
+
+/// Handle passed to a running task to report health and watch for shutdown.
+pub struct TaskContext {
+    name: String,
+    health: TaskHealth,
+    shutdown: watch::Receiver<bool>,
+}
+
+impl TaskContext {
+    pub fn succeeded(&self) {
+        self.health.update(&self.name, |report| {
+            report.state = TaskState::Healthy;
+            report.last_success = Some(OffsetDateTime::now_utc());
+            report.last_error = None;
+        });
+    }
+
+    pub fn failed(&self, error: &anyhow::Error) {
+        self.health.update(&self.name, |report| {
+            report.state = TaskState::Failing;
+            report.last_error = Some(format!("{:#}", error));
+        });
+    }
+
+    pub fn is_shutting_down(&self) -> bool {
+        *self.shutdown.borrow()
+    }
+
+    /// Sleeps for `duration`, waking early on shutdown. Returns `false` once
+    /// the task should stop.
+    pub async fn sleep(&mut self, duration: Duration) -> bool {
+        if self.is_shutting_down() {
+            return false;
+        }
+        tokio::select! {
+            _ = tokio::time::sleep(duration) => true,
+            _ = self.shutdown.changed() => false,
+        }
+    }
+}
+
+/// Owns the spawned tasks. Work already in progress is allowed to finish
+/// on shutdown; tasks still running after the grace period are aborted.
+pub struct BackgroundTasks {
+    health: TaskHealth,
+    shutdown: watch::Sender<bool>,
+    handles: Vec<(String, JoinHandle<()>)>,
+}
+
+impl BackgroundTasks {
+    pub fn new(health: TaskHealth) -> Self {
+        let (shutdown, _) = watch::channel(false);
+        Self {
+            health,
+            shutdown,
+            handles: Vec::new(),
+        }
+    }
+
+    /// Spawns a task that runs until it returns. Returning before shutdown,
+    /// or panicking, marks it stopped.
+    pub fn spawn<F, Fut>(&mut self, name: impl Into<String>, task: F)
+    where
+        F: FnOnce(TaskContext) -> Fut,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let name = name.into();
+        self.health.update(&name, |_| {});
+        let ctx = TaskContext {
+            name: name.clone(),
+            health: self.health.clone(),
+            shutdown: self.shutdown.subscribe(),
+        };
+        let shutdown = self.shutdown.subscribe();
+        let health = self.health.clone();
+        let task_name = name.clone();
+        let future = task(ctx);
+        let handle = tokio::spawn(async move {
+            let outcome = AssertUnwindSafe(future).catch_unwind().await;
+            if *shutdown.borrow() {
+                return;
+            }
+            let reason = if outcome.is_ok() {
+                "exited"
+            } else {
+                "panicked"
+            };
+            error!(task = %task_name, %reason, "background task stopped");
+            health.update(&task_name, |report| {
+                report.state = TaskState::Stopped;
+                report.last_error = Some(reason.to_string());
+            });
+        });
+        self.handles.push((name, handle));
+    }
+
+    /// Signals every task to stop and waits up to `grace` for them.
+    pub async fn shutdown(self, grace: Duration) {
+        self.shutdown.send_replace(true);
+        let deadline = tokio::time::Instant::now() + grace;
+        for (name, mut handle) in self.handles {
+            if tokio::time::timeout_at(deadline, &mut handle)
+                .await
+                .is_err()
+            {
+                warn!(task = %name, "background task did not stop in time; aborting");
+                handle.abort();
+            }
+        }
+        info!("background tasks stopped");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn task_reports_health_and_stops() {
+        let health = TaskHealth::default();
+        let mut tasks = BackgroundTasks::new(health.clone());
+        let runs = Arc::new(AtomicU32::new(0));
+        let counter = runs.clone();
+        tasks.spawn("flaky", move |mut ctx| async move {
+            loop {
+                match counter.fetch_add(1, Ordering::SeqCst) {
+                    0 => ctx.failed(&anyhow::anyhow!("boom")),
+                    _ => ctx.succeeded(),
+                }
+                if !ctx.sleep(Duration::from_millis(5)).await {
+                    break;
+                }
+            }
+        });
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let report = &health.snapshot()["flaky"];
+        assert_eq!(report.state, TaskState::Healthy);
+        assert!(report.last_error.is_none());
+        assert!(health.is_ready());
+
+        tasks.shutdown(Duration::from_secs(1)).await;
+        let stopped_at = runs.load(Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(runs.load(Ordering::SeqCst), stopped_at);
+    }
+
+    #[tokio::test]
+    async fn task_exiting_early_is_not_ready() {
+        let health = TaskHealth::default();
+        let mut tasks = BackgroundTasks::new(health.clone());
+        tasks.spawn("oneshot", |_ctx| async {});
+        tasks.spawn("panics", |_ctx| async { panic!("bug") });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+
+        let snapshot = health.snapshot();
+        assert_eq!(snapshot["oneshot"].state, TaskState::Stopped);
+        assert_eq!(snapshot["panics"].state, TaskState::Stopped);
+        assert!(!health.is_ready());
+        tasks.shutdown(Duration::from_secs(1)).await;
+    }
+}
@@ -0,0 +1,99 @@
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::{foods::dto::FoodNutrition, meal_items::dto::NewMealItem, meals::dto::MealType};
+
+pub const MAX_TEMPLATE_NAME_LEN: usize = 200;
+
+/// Body of `POST /meals/:id/save-as-template`; may be omitted.
+#[derive(Debug, Default, Deserialize)]
+pub struct SaveTemplateRequest {
+    /// Defaults to the meal title.
+    pub name: Option<String>,
+    #[serde(default)]
+    pub include_photos: bool,
+}
+
+impl SaveTemplateRequest {
+    /// The template name, falling back to `title`.
+    pub fn name(&self, title: Option<&str>) -> Result<String, String> {
+        let name = self
+            .name
+            .as_deref()
+            .or(title)
+            .map(str::trim)
+            .filter(|n| !n.is_empty())
+            .ok_or("name is required for meals without a title")?;
+        if name.chars().count() > MAX_TEMPLATE_NAME_LEN {
+            return Err(format!(
+                "name must be at most {} characters",
+                MAX_TEMPLATE_NAME_LEN
+            ));
+        }
+        Ok(name.to_string())
+    }
+}
+
+/// Everything copied from a meal into a template.
+#[derive(Debug, Clone)]
+pub struct NewTemplate {
+    pub name: String,
+    pub title: Option<String>,
+    pub notes: Option<String>,
+    pub meal_type: Option<MealType>,
+    pub tags: Vec<String>,
+    pub nutrition: Option<FoodNutrition>,
+    pub micros: Option<serde_json::Value>,
+    pub items: Vec<NewMealItem>,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct MealTemplate {
+    pub id: Uuid,
+    pub name: String,
+    pub title: Option<String>,
+    pub notes: Option<String>,
+    pub meal_type: Option<MealType>,
+    pub tags: Vec<String>,
+    /// AI or manual nutrition of the source meal, without its items.
+    pub nutrition: Option<Json<FoodNutrition>>,
+    #[serde(skip)]
+    pub micros: Option<serde_json::Value>,
+    pub items: Json<Vec<NewMealItem>>,
+    pub photo_count: i64,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+impl MealTemplate {
+    /// Nutrition of a meal logged from this template: base plus items.
+    pub fn total_nutrition(&self) -> FoodNutrition {
+        let base = self
+            .nutrition
+            .as_ref()
+            .map(|n| n.0.clone())
+            .unwrap_or_default();
+        self.items
+            .iter()
+            .fold(base, |sum, i| sum.plus(&i.nutrition))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn name_falls_back_to_title() {
+        let request = SaveTemplateRequest::default();
+        assert_eq!(request.name(Some(" Oatmeal ")).unwrap(), "Oatmeal");
+        assert!(request.name(None).is_err());
+        let request = SaveTemplateRequest {
+            name: Some("My usual breakfast".into()),
+            include_photos: false,
+        };
+        assert_eq!(request.name(Some("Oatmeal")).unwrap(), "My usual breakfast");
+    }
+}
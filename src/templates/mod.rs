@@ -0,0 +1,3 @@
+pub mod dto;
+pub mod repo;
+pub mod services;
@@ -0,0 +1,146 @@
+use sqlx::{types::Json, PgConnection, PgExecutor};
+use uuid::Uuid;
+
+use crate::{
+    photos::repo::{NewPhoto, Photo},
+    templates::dto::{MealTemplate, NewTemplate},
+};
+
+const TEMPLATE_COLUMNS: &str = r#"
+    t.id, t.name, t.title, t.notes, t.meal_type, t.tags, t.nutrition, t.micros, t.items,
+    (SELECT COUNT(*) FROM template_photos p WHERE p.template_id = t.id) AS photo_count,
+    t.created_at
+"#;
+
+/// Stores a template and its photo references; call in a transaction.
+pub async fn insert_template(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    template: &NewTemplate,
+    photos: &[Photo],
+) -> anyhow::Result<Uuid> {
+    let id = sqlx::query_scalar::<_, Uuid>(
+        r#"
+        INSERT INTO meal_templates (user_id, name, title, notes, meal_type, tags, nutrition,
+                                    micros, items)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+        RETURNING id
+        "#,
+    )
+    .bind(user_id)
+    .bind(&template.name)
+    .bind(&template.title)
+    .bind(&template.notes)
+    .bind(template.meal_type)
+    .bind(&template.tags)
+    .bind(template.nutrition.as_ref().map(Json))
+    .bind(&template.micros)
+    .bind(Json(&template.items))
+    .fetch_one(&mut *conn)
+    .await?;
+    for (position, photo) in photos.iter().enumerate() {
+        sqlx::query(
+            r#"
+            INSERT INTO template_photos (template_id, s3_key, original_s3_key, content_type,
+                                         size_bytes, width, height, content_hash, taken_at,
+                                         position)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            "#,
+        )
+        .bind(id)
+        .bind(&photo.s3_key)
+        .bind(&photo.original_s3_key)
+        .bind(&photo.content_type)
+        .bind(photo.size_bytes)
+        .bind(photo.width)
+        .bind(photo.height)
+        .bind(&photo.content_hash)
+        .bind(photo.taken_at)
+        .bind(position as i32)
+        .execute(&mut *conn)
+        .await?;
+    }
+    Ok(id)
+}
+
+pub async fn list_templates(
+    db: impl PgExecutor<'_>,
+    user_id: Uuid,
+) -> anyhow::Result<Vec<MealTemplate>> {
+    let templates = sqlx::query_as::<_, MealTemplate>(&format!(
+        r#"
+        SELECT {TEMPLATE_COLUMNS}
+        FROM meal_templates t
+        WHERE t.user_id = $1
+        ORDER BY lower(t.name), t.id
+        "#
+    ))
+    .bind(user_id)
+    .fetch_all(db)
+    .await?;
+    Ok(templates)
+}
+
+pub async fn find_template(
+    db: impl PgExecutor<'_>,
+    user_id: Uuid,
+    template_id: Uuid,
+) -> anyhow::Result<Option<MealTemplate>> {
+    let template = sqlx::query_as::<_, MealTemplate>(&format!(
+        "SELECT {TEMPLATE_COLUMNS} FROM meal_templates t WHERE t.id = $1 AND t.user_id = $2"
+    ))
+    .bind(template_id)
+    .bind(user_id)
+    .fetch_optional(db)
+    .await?;
+    Ok(template)
+}
+
+/// Photos of a template, ready to be linked to a new meal under new ids.
+pub async fn list_photos(
+    db: impl PgExecutor<'_>,
+    template_id: Uuid,
+) -> anyhow::Result<Vec<NewPhoto>> {
+    let photos = sqlx::query_as::<_, NewPhoto>(
+        r#"
+        SELECT gen_random_uuid() AS id, s3_key, original_s3_key,
+               COALESCE(content_type, '') AS content_type,
+               COALESCE(size_bytes, 0) AS size_bytes, width, height,
+               COALESCE(content_hash, '') AS content_hash, taken_at
+        FROM template_photos
+        WHERE template_id = $1
+        ORDER BY position
+        "#,
+    )
+    .bind(template_id)
+    .fetch_all(db)
+    .await?;
+    Ok(photos)
+}
+
+/// Deletes a template of `user_id`, returning the storage keys its photos
+/// referenced, or `None` if there was no such template. Call in a
+/// transaction.
+pub async fn delete_template(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    template_id: Uuid,
+) -> anyhow::Result<Option<Vec<String>>> {
+    let keys = sqlx::query_scalar::<_, String>(
+        r#"
+        SELECT k
+        FROM template_photos p
+        CROSS JOIN LATERAL (VALUES (p.s3_key), (p.original_s3_key)) AS v(k)
+        WHERE p.template_id = $1 AND k IS NOT NULL
+        "#,
+    )
+    .bind(template_id)
+    .fetch_all(&mut *conn)
+    .await?;
+    let deleted = sqlx::query(r#"DELETE FROM meal_templates WHERE id = $1 AND user_id = $2"#)
+        .bind(template_id)
+        .bind(user_id)
+        .execute(&mut *conn)
+        .await?;
+    Ok((deleted.rows_affected() > 0).then_some(keys))
+}
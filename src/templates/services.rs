@@ -0,0 +1,189 @@
+use anyhow::Context;
+use axum::http::StatusCode;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    db::{AppState, RepoError},
+    error::ErrorCode,
+    images::services::release_objects,
+    meal_items::dto::NewMealItem,
+    meal_items::repo as items_repo,
+    meals::{
+        dto::{ManualNutritionRequest, MealDetails, NewMeal},
+        repo as meals_repo, services as meals_services,
+    },
+    photos::repo as photos_repo,
+    templates::{
+        dto::{MealTemplate, NewTemplate, SaveTemplateRequest},
+        repo,
+    },
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum TemplateError {
+    #[error("Meal not found")]
+    MealNotFound,
+    #[error("Template not found")]
+    TemplateNotFound,
+    #[error("{0}")]
+    Invalid(String),
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+impl From<RepoError> for TemplateError {
+    fn from(e: RepoError) -> Self {
+        TemplateError::Other(e.into())
+    }
+}
+
+impl TemplateError {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            TemplateError::MealNotFound | TemplateError::TemplateNotFound => StatusCode::NOT_FOUND,
+            TemplateError::Invalid(_) => StatusCode::BAD_REQUEST,
+            TemplateError::Other(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            TemplateError::MealNotFound => ErrorCode::MealNotFound,
+            TemplateError::TemplateNotFound => ErrorCode::TemplateNotFound,
+            TemplateError::Invalid(_) => ErrorCode::ValidationFailed,
+            TemplateError::Other(_) => ErrorCode::Internal,
+        }
+    }
+}
+
+/// Snapshots a meal's details, base nutrition and items, plus its photos
+/// when asked to.
+pub async fn save_template(
+    state: &AppState,
+    user_id: Uuid,
+    meal_id: Uuid,
+    input: SaveTemplateRequest,
+) -> Result<MealTemplate, TemplateError> {
+    let mut tx = state.begin_as(user_id).await.map_err(anyhow::Error::from)?;
+    let meal = meals_repo::find_meal(&mut *tx, user_id, meal_id)
+        .await?
+        .ok_or(TemplateError::MealNotFound)?;
+    let name = input
+        .name(meal.title.as_deref())
+        .map_err(TemplateError::Invalid)?;
+    let nutrition = meals_repo::find_base_nutrition(&state.db, meal_id).await?;
+    let micros = match &nutrition {
+        Some(_) => meals_repo::find_nutrition(&state.db, meal_id)
+            .await?
+            .and_then(|n| n.micros),
+        None => None,
+    };
+    let items = items_repo::list_for_meal(&state.db, meal_id)
+        .await?
+        .into_iter()
+        .map(|item| NewMealItem {
+            food_source: item.food_source,
+            food_id: item.food_id,
+            name: item.name,
+            quantity: item.quantity,
+            unit: item.unit,
+            nutrition: item.nutrition,
+        })
+        .collect();
+    let photos = if input.include_photos {
+        photos_repo::list_for_meal(&mut *tx, user_id, meal_id).await?
+    } else {
+        Vec::new()
+    };
+    let template = NewTemplate {
+        name,
+        title: meal.title,
+        notes: meal.notes,
+        meal_type: meal.meal_type,
+        tags: meal.tags,
+        nutrition,
+        micros,
+        items,
+    };
+    let id = repo::insert_template(&mut tx, user_id, &template, &photos).await?;
+    let template = repo::find_template(&mut *tx, user_id, id)
+        .await?
+        .context("template disappeared right after creation")?;
+    tx.commit().await.map_err(anyhow::Error::from)?;
+    info!(user_id = %user_id, meal_id = %meal_id, template_id = %id, "meal saved as template");
+    Ok(template)
+}
+
+/// Logs a new meal from a template: same details, nutrition, items and
+/// photos, marked `done` without analysis.
+pub async fn log_from_template(
+    state: &AppState,
+    user_id: Uuid,
+    template_id: Uuid,
+) -> Result<MealDetails, TemplateError> {
+    let mut tx = state.begin_as(user_id).await.map_err(anyhow::Error::from)?;
+    let template = repo::find_template(&mut *tx, user_id, template_id)
+        .await?
+        .ok_or(TemplateError::TemplateNotFound)?;
+    let photos = repo::list_photos(&mut *tx, template_id).await?;
+    tx.commit().await.map_err(anyhow::Error::from)?;
+    let meal = NewMeal {
+        title: template.title,
+        notes: template.notes,
+        meal_type: template.meal_type,
+        ..NewMeal::default()
+    };
+    let nutrition = template.nutrition.map(|n| ManualNutritionRequest {
+        total_calories_kcal: n.total_calories_kcal,
+        protein_g: n.protein_g,
+        fat_g: n.fat_g,
+        carbs_g: n.carbs_g,
+        sodium_mg: n.sodium_mg,
+        sugar_g: n.sugar_g,
+        fiber_g: n.fiber_g,
+        caffeine_mg: None,
+        alcohol_g: None,
+        micros: template.micros,
+    });
+    let created = meals_services::create_quick_meal(state, user_id, meal, nutrition).await?;
+    if !template.tags.is_empty() {
+        meals_repo::set_tags(&state.db, created.id, &template.tags).await?;
+    }
+    for item in template.items.iter() {
+        items_repo::insert_item(&state.db, created.id, item).await?;
+    }
+    if !template.items.is_empty() {
+        meals_services::refresh_nutrition(state, user_id, created.id).await?;
+    }
+    if !photos.is_empty() {
+        let mut tx = state.begin_as(user_id).await.map_err(anyhow::Error::from)?;
+        photos_repo::insert_many(&mut *tx, user_id, created.id, &photos).await?;
+        tx.commit().await.map_err(anyhow::Error::from)?;
+    }
+    let details = meals_services::get_meal_details(state, user_id, created.id)
+        .await?
+        .context("meal disappeared right after creation")?;
+    Ok(details)
+}
+
+pub async fn list_templates(state: &AppState, user_id: Uuid) -> anyhow::Result<Vec<MealTemplate>> {
+    let mut tx = state.begin_as(user_id).await?;
+    let templates = repo::list_templates(&mut *tx, user_id).await?;
+    tx.commit().await?;
+    Ok(templates)
+}
+
+pub async fn delete_template(
+    state: &AppState,
+    user_id: Uuid,
+    template_id: Uuid,
+) -> Result<(), TemplateError> {
+    let mut tx = state.begin_as(user_id).await.map_err(anyhow::Error::from)?;
+    let keys = repo::delete_template(&mut tx, user_id, template_id)
+        .await?
+        .ok_or(TemplateError::TemplateNotFound)?;
+    tx.commit().await.map_err(anyhow::Error::from)?;
+    release_objects(state, keys).await?;
+    Ok(())
+}
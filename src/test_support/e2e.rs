@@ -0,0 +1,933 @@
+//! Register → login → create meal → list against real Postgres and MinIO.
+
+use std::sync::Arc;
+
+use axum::http::{header, Method, StatusCode};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde_json::json;
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+use uuid::Uuid;
+
+use crate::{
+    analysis::{self, mock::MockAnalyzer},
+    db::{bypasses_row_level_security, AppState},
+    jobs::{repo as jobs_repo, Job},
+    retention::repo as retention_repo,
+    tasks::maintenance::cleanup_orphan_photos,
+    test_support::TestApp,
+};
+
+/// PNG signature and header of a 1×1 image; enough for format sniffing.
+const PNG: &[u8] = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR\0\0\0\x01\0\0\0\x01\x08\x02\0\0\0";
+
+#[tokio::test]
+async fn register_login_create_and_list_meals() {
+    let app = AppState::test().await.expect("start test app");
+    app.register("e2e@example.com").await;
+
+    let (status, body) = app
+        .send(
+            Method::POST,
+            "/auth/login",
+            None,
+            Some(json!({"email": "e2e@example.com", "password": "password123"})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    let token = body["access_token"].as_str().unwrap().to_string();
+
+    let (status, created) = app
+        .send(
+            Method::POST,
+            "/meals",
+            Some(&token),
+            Some(json!({
+                "title": "Lunch",
+                "meal_type": "lunch",
+                "images": [{"content_type": "image/png", "data": STANDARD.encode(PNG)}],
+            })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::CREATED, "{created}");
+    assert_eq!(created["images"].as_array().unwrap().len(), 1);
+
+    let (status, meals) = app.send(Method::GET, "/meals", Some(&token), None).await;
+    assert_eq!(status, StatusCode::OK);
+    let meals = meals.as_array().unwrap();
+    assert_eq!(meals.len(), 1);
+    assert_eq!(meals[0]["id"], created["id"]);
+    let url = meals[0]["photos"][0]["url"].as_str().unwrap();
+    assert!(
+        url.contains("mealmind-test"),
+        "presigned URL points at the bucket: {url}"
+    );
+}
+
+#[tokio::test]
+async fn upload_session_photos_become_one_meal() {
+    let app = AppState::test().await.expect("start test app");
+    let token = app.register("session@example.com").await;
+
+    let (status, session) = app
+        .send(Method::POST, "/uploads/sessions", Some(&token), None)
+        .await;
+    assert_eq!(status, StatusCode::CREATED, "{session}");
+    let session_id = session["id"].as_str().unwrap().to_string();
+
+    for position in [1, 0, 1] {
+        let uri = format!("/uploads/sessions/{session_id}/photos/{position}");
+        let (status, photo) = app
+            .send_bytes(Method::PUT, &uri, &token, "image/png", PNG)
+            .await;
+        assert_eq!(status, StatusCode::OK, "{photo}");
+    }
+    let uri = format!("/uploads/sessions/{session_id}");
+    let (_, session) = app.send(Method::GET, &uri, Some(&token), None).await;
+    assert_eq!(session["photos"].as_array().unwrap().len(), 2, "{session}");
+
+    let request = json!({"title": "Dinner", "upload_session_id": session_id});
+    let (status, created) = app
+        .send(Method::POST, "/meals", Some(&token), Some(request.clone()))
+        .await;
+    assert_eq!(status, StatusCode::CREATED, "{created}");
+    assert_eq!(created["images"].as_array().unwrap().len(), 2);
+
+    let (status, body) = app
+        .send(Method::POST, "/meals", Some(&token), Some(request))
+        .await;
+    assert_eq!(status, StatusCode::CONFLICT, "{body}");
+    assert_eq!(body["error_code"], "UPLOAD_SESSION_CLOSED");
+}
+
+#[tokio::test]
+async fn chunked_uploads_complete_into_a_session_photo() {
+    let app = AppState::test().await.expect("start test app");
+    let token = app.register("chunked@example.com").await;
+
+    let (_, session) = app
+        .send(Method::POST, "/uploads/sessions", Some(&token), None)
+        .await;
+    let session_id = session["id"].as_str().unwrap();
+    let (status, upload) = app
+        .send(
+            Method::POST,
+            &format!("/uploads/sessions/{session_id}/photos/0/chunked"),
+            Some(&token),
+            Some(json!({"content_type": "image/png", "size_bytes": PNG.len()})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::CREATED, "{upload}");
+    let upload_id = upload["id"].as_str().unwrap();
+
+    let uri = format!("/uploads/chunked/{upload_id}/parts/1");
+    let (status, part) = app
+        .send_bytes(Method::PUT, &uri, &token, "application/octet-stream", PNG)
+        .await;
+    assert_eq!(status, StatusCode::OK, "{part}");
+
+    let uri = format!("/uploads/chunked/{upload_id}/complete");
+    let (status, photo) = app.send(Method::POST, &uri, Some(&token), None).await;
+    assert_eq!(status, StatusCode::OK, "{photo}");
+    assert_eq!(photo["position"], 0);
+    assert_eq!(photo["width"], 1);
+
+    let (status, _) = app.send(Method::POST, &uri, Some(&token), None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+/// Brand box of a HEIC still; enough for format sniffing.
+const HEIC: &[u8] = b"\0\0\0\x18ftypheic\0\0\0\0mif1heic";
+/// JPEG start-of-image marker; enough for format sniffing.
+const JPEG: &[u8] = b"\xff\xd8\xff\xe0\0\x10JFIF\0";
+
+/// Uploads `bytes` to position 0 of a new session as a one-part chunked
+/// upload; the session id and the completed photo.
+async fn chunked_photo(
+    app: &TestApp,
+    token: &str,
+    content_type: &str,
+    bytes: &'static [u8],
+) -> (String, serde_json::Value) {
+    let (_, session) = app
+        .send(Method::POST, "/uploads/sessions", Some(token), None)
+        .await;
+    let session_id = session["id"].as_str().unwrap().to_string();
+    let (status, upload) = app
+        .send(
+            Method::POST,
+            &format!("/uploads/sessions/{session_id}/photos/0/chunked"),
+            Some(token),
+            Some(json!({"content_type": content_type, "size_bytes": bytes.len()})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::CREATED, "{upload}");
+    let upload_id = upload["id"].as_str().unwrap();
+    let uri = format!("/uploads/chunked/{upload_id}/parts/1");
+    let (status, part) = app
+        .send_bytes(Method::PUT, &uri, token, "application/octet-stream", bytes)
+        .await;
+    assert_eq!(status, StatusCode::OK, "{part}");
+    let uri = format!("/uploads/chunked/{upload_id}/complete");
+    let (status, photo) = app.send(Method::POST, &uri, Some(token), None).await;
+    assert_eq!(status, StatusCode::OK, "{photo}");
+    (session_id, photo)
+}
+
+#[tokio::test]
+async fn chunked_heic_photos_are_transcoded_or_fail_analysis() {
+    let mut app = AppState::test().await.expect("start test app");
+    // Stands in for a converter: writes a fixed JPEG whatever the input.
+    let jpeg = std::env::temp_dir().join(format!("mealmind-test-{}.jpg", Uuid::new_v4()));
+    std::fs::write(&jpeg, JPEG).unwrap();
+    let mut config = (*app.state.config).clone();
+    config.transcode.heic_command = Some(format!("cp {} {{output}}", jpeg.display()));
+    app.state.config = Arc::new(config);
+    app.router = crate::app::build_app(app.state.clone());
+    let token = app.register("chunked-heic@example.com").await;
+
+    let (_, photo) = chunked_photo(&app, &token, "image/heic", HEIC).await;
+    assert_eq!(photo["content_type"], "image/jpeg");
+    assert_eq!(photo["size_bytes"], JPEG.len());
+    let photo_id = Uuid::parse_str(photo["photo_id"].as_str().unwrap()).unwrap();
+    let (key, original): (String, Option<String>) =
+        sqlx::query_as("SELECT s3_key, original_s3_key FROM photos WHERE id = $1")
+            .bind(photo_id)
+            .fetch_one(&app.state.db)
+            .await
+            .unwrap();
+    assert!(key.ends_with(".jpg"), "{key}");
+    assert!(original.is_some_and(|key| key.ends_with(".heic")));
+    std::fs::remove_file(&jpeg).unwrap();
+
+    // Without a converter the HEIC is kept, and analysis can't read it.
+    let mut config = (*app.state.config).clone();
+    config.transcode.heic_command = None;
+    app.state.config = Arc::new(config);
+    app.state.analyzer = Some(Arc::new(MockAnalyzer::default()));
+    app.router = crate::app::build_app(app.state.clone());
+    let (session_id, photo) = chunked_photo(&app, &token, "image/heic", HEIC).await;
+    assert_eq!(photo["content_type"], "image/heic");
+    let request = json!({"title": "Big dinner", "upload_session_id": session_id});
+    let (status, created) = app
+        .send(Method::POST, "/meals", Some(&token), Some(request))
+        .await;
+    assert_eq!(status, StatusCode::CREATED, "{created}");
+    let meal_id = Uuid::parse_str(created["id"].as_str().unwrap()).unwrap();
+    analysis::analyze_meal(&app.state, meal_id, false)
+        .await
+        .unwrap();
+    let uri = format!("/meals/{meal_id}/status");
+    let (_, status) = app.send(Method::GET, &uri, Some(&token), None).await;
+    assert_eq!(status["status"], "failed", "{status}");
+}
+
+#[tokio::test]
+async fn meal_photos_are_presigned_urls_not_storage_keys() {
+    let app = AppState::test().await.expect("start test app");
+    let token = app.register("presigned@example.com").await;
+    let (status, created) = app
+        .send(
+            Method::POST,
+            "/meals",
+            Some(&token),
+            Some(json!({
+                "title": "Breakfast",
+                "images": [{"content_type": "image/png", "data": STANDARD.encode(PNG)}],
+            })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::CREATED, "{created}");
+    let meal_id = Uuid::parse_str(created["id"].as_str().unwrap()).unwrap();
+    let (photo_id, key): (Uuid, String) =
+        sqlx::query_as("SELECT id, s3_key FROM photos WHERE meal_id = $1")
+            .bind(meal_id)
+            .fetch_one(&app.state.db)
+            .await
+            .unwrap();
+
+    let (status, meal) = app
+        .send(
+            Method::GET,
+            &format!("/meals/{meal_id}"),
+            Some(&token),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{meal}");
+    let image = &meal["images"][0];
+    assert_eq!(image["photo_id"], photo_id.to_string());
+    assert!(image.get("s3_key").is_none(), "{image}");
+    let url = image["url"].as_str().unwrap();
+    assert_ne!(url, key);
+    assert!(
+        url.contains(&key) && url.contains("X-Amz-Signature="),
+        "{url}"
+    );
+    let expires_at = image["expires_at"].as_str().unwrap();
+    let expires_at = OffsetDateTime::parse(expires_at, &Rfc3339).unwrap();
+    assert!(expires_at > OffsetDateTime::now_utc());
+}
+
+#[tokio::test]
+async fn photo_content_is_streamed_to_its_owner_only() {
+    let app = AppState::test().await.expect("start test app");
+    let owner = app.register("download@example.com").await;
+    let other = app.register("snooper@example.com").await;
+    let (status, created) = app
+        .send(
+            Method::POST,
+            "/meals",
+            Some(&owner),
+            Some(json!({
+                "title": "Salad",
+                "images": [{"content_type": "image/png", "data": STANDARD.encode(PNG)}],
+            })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::CREATED, "{created}");
+    let photo_id = created["images"][0]["photo_id"].as_str().unwrap();
+    let uri = format!("/photos/{photo_id}/content");
+
+    let (status, headers, body) = app.get_bytes(&uri, &owner).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(headers[header::CONTENT_TYPE], "image/png");
+    assert_eq!(headers[header::CACHE_CONTROL], "private, max-age=300");
+    assert_eq!(&body[..], PNG);
+
+    let (status, _, body) = app.get_bytes(&uri, &other).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let error: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(error["error_code"], "PHOTO_NOT_FOUND");
+    let missing = format!("/photos/{}/content", Uuid::new_v4());
+    let (status, _, _) = app.get_bytes(&missing, &owner).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn quick_meals_show_up_in_the_daily_summary() {
+    let app = AppState::test().await.expect("start test app");
+    let token = app.register("summary@example.com").await;
+
+    let (status, _) = app
+        .send(
+            Method::POST,
+            "/meals",
+            Some(&token),
+            Some(json!({"quick_add": {"calories_kcal": 350}})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let (status, summary) = app
+        .send(Method::GET, "/summary/daily", Some(&token), None)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(summary["meals"], 1);
+    assert_eq!(summary["totals"]["calories_kcal"], 350.0);
+
+    let stored: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM meals")
+        .fetch_one(&app.state.db)
+        .await
+        .unwrap();
+    assert_eq!(stored, 1);
+}
+
+#[tokio::test]
+async fn archived_meals_keep_objects_shared_with_live_photos() {
+    let app = AppState::test().await.expect("start test app");
+    let token = app.register("archive@example.com").await;
+    let mut meal_ids = Vec::new();
+    for title in ["Old lunch", "Same plate again"] {
+        let (status, created) = app
+            .send(
+                Method::POST,
+                "/meals",
+                Some(&token),
+                Some(json!({
+                    "title": title,
+                    "images": [{"content_type": "image/png", "data": STANDARD.encode(PNG)}],
+                })),
+            )
+            .await;
+        assert_eq!(status, StatusCode::CREATED, "{created}");
+        meal_ids.push(Uuid::parse_str(created["id"].as_str().unwrap()).unwrap());
+    }
+    let (old, live) = (meal_ids[0], meal_ids[1]);
+    let db = &app.state.db;
+    let photo = |meal_id: Uuid| {
+        sqlx::query_as::<_, (Uuid, String)>("SELECT id, s3_key FROM photos WHERE meal_id = $1")
+            .bind(meal_id)
+            .fetch_optional(db)
+    };
+    let (_, key) = photo(old).await.unwrap().unwrap();
+    let (live_photo, live_key) = photo(live).await.unwrap().unwrap();
+    assert_eq!(live_key, key, "the same bytes share one object");
+
+    sqlx::query("UPDATE meals SET created_at = NOW() - interval '400 days' WHERE id = $1")
+        .bind(old)
+        .execute(db)
+        .await
+        .unwrap();
+    assert_eq!(retention_repo::archive_batch(db, 365, 10).await.unwrap(), 1);
+    assert!(
+        photo(old).await.unwrap().is_none(),
+        "photo rows are detached"
+    );
+    let archived_keys: Vec<String> =
+        sqlx::query_scalar("SELECT photo_keys FROM meal_archive WHERE id = $1")
+            .bind(old)
+            .fetch_one(db)
+            .await
+            .unwrap();
+    assert_eq!(archived_keys, [key.as_str()]);
+
+    // Neither releasing the live copy nor the orphan sweep may delete the
+    // object the archive still refers to.
+    let (status, _) = app
+        .send(
+            Method::DELETE,
+            &format!("/meals/{live}/photos/{live_photo}"),
+            Some(&token),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    cleanup_orphan_photos(&app.state).await.unwrap();
+    let object = app.state.storage.get_object_stream(&key).await.unwrap();
+    assert!(object.is_some(), "archived object {key} was deleted");
+}
+
+#[tokio::test]
+async fn late_meals_count_toward_the_local_day() {
+    let app = AppState::test().await.expect("start test app");
+    let token = app.register("night-owl@example.com").await;
+
+    // 23:30 in New York is 04:30 UTC the next day.
+    let id = Uuid::new_v4();
+    let (status, pushed) = app
+        .send(
+            Method::POST,
+            "/sync",
+            Some(&token),
+            Some(json!({"meals": [{
+                "id": id,
+                "title": "Late snack",
+                "created_at": "2024-03-01T23:30:00-05:00",
+                "updated_at": "2024-03-01T23:30:00-05:00",
+            }]})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(pushed["results"][0]["outcome"], "applied");
+    let (status, _) = app
+        .send(
+            Method::PUT,
+            &format!("/meals/{id}/nutrition"),
+            Some(&token),
+            Some(json!({"total_calories_kcal": 250})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, stats) = app
+        .send(
+            Method::GET,
+            "/me/stats?tz=America/New_York",
+            Some(&token),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(stats["last_logged_on"], "2024-03-01");
+
+    let (status, _) = app
+        .send(
+            Method::PATCH,
+            "/me/preferences",
+            Some(&token),
+            Some(json!({"timezone": "America/New_York"})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    for (date, meals, calories) in [("2024-03-01", 1, 250.0), ("2024-03-02", 0, 0.0)] {
+        let (status, summary) = app
+            .send(
+                Method::GET,
+                &format!("/summary/daily?date={date}"),
+                Some(&token),
+                None,
+            )
+            .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(summary["meals"], meals, "{date}");
+        assert_eq!(summary["totals"]["calories_kcal"], calories, "{date}");
+    }
+}
+
+#[tokio::test]
+async fn meals_are_private_to_their_owner() {
+    let app = AppState::test().await.expect("start test app");
+    let owner = app.register("owner@example.com").await;
+    let other = app.register("other@example.com").await;
+
+    let (_, created) = app
+        .send(
+            Method::POST,
+            "/meals",
+            Some(&owner),
+            Some(json!({"title": "Soup"})),
+        )
+        .await;
+    let uri = format!("/meals/{}", created["id"].as_str().unwrap());
+
+    let (status, _) = app.send(Method::GET, &uri, Some(&other), None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, meals) = app.send(Method::GET, "/meals", Some(&other), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(meals, json!([]));
+}
+
+#[tokio::test]
+async fn other_users_cannot_change_a_meal() {
+    let app = AppState::test().await.expect("start test app");
+    let owner = app.register("writer@example.com").await;
+    let other = app.register("intruder@example.com").await;
+
+    let (_, created) = app
+        .send(
+            Method::POST,
+            "/meals",
+            Some(&owner),
+            Some(json!({"title": "Stew", "nutrition": {"total_calories_kcal": 500}})),
+        )
+        .await;
+    let id = created["id"].as_str().unwrap();
+
+    let attempts = [
+        (Method::GET, format!("/meals/{id}/status"), None),
+        (
+            Method::PUT,
+            format!("/meals/{id}/nutrition"),
+            Some(json!({"total_calories_kcal": 1})),
+        ),
+        (Method::DELETE, format!("/meals/{id}/nutrition"), None),
+        (Method::GET, format!("/meals/{id}/photos"), None),
+        (
+            Method::POST,
+            format!("/meals/{id}/items"),
+            Some(json!({"name": "Bread", "quantity": 50, "unit": "g"})),
+        ),
+        (Method::POST, format!("/meals/{id}/share"), Some(json!({}))),
+    ];
+    for (method, uri, body) in attempts {
+        let (status, _) = app.send(method.clone(), &uri, Some(&other), body).await;
+        assert_eq!(status, StatusCode::NOT_FOUND, "{method} {uri}");
+    }
+    let (status, bulk) = app
+        .send(
+            Method::POST,
+            "/meals/bulk",
+            Some(&other),
+            Some(json!({"operations": [{"op": "delete", "meal_ids": [id]}]})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(bulk["results"][0]["ok"], false);
+
+    let (status, meal) = app
+        .send(Method::GET, &format!("/meals/{id}"), Some(&owner), None)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(meal["nutrition"]["total_calories_kcal"], 500.0);
+}
+
+#[tokio::test]
+async fn row_level_security_scopes_unfiltered_queries() {
+    let app = AppState::test().await.expect("start test app");
+    for (email, title) in [("alice@example.com", "Soup"), ("bob@example.com", "Stew")] {
+        let token = app.register(email).await;
+        let (status, _) = app
+            .send(
+                Method::POST,
+                "/meals",
+                Some(&token),
+                Some(json!({"title": title})),
+            )
+            .await;
+        assert_eq!(status, StatusCode::CREATED);
+    }
+    let user_id = |email: &'static str| {
+        sqlx::query_scalar::<_, Uuid>("SELECT id FROM users WHERE email = $1")
+            .bind(email)
+            .fetch_one(&app.state.db)
+    };
+    let alice = user_id("alice@example.com").await.unwrap();
+    let bob = user_id("bob@example.com").await.unwrap();
+
+    // The container connects as a superuser, which skips the policies.
+    for statement in [
+        "CREATE ROLE mealmind_app LOGIN PASSWORD 'mealmind_app'",
+        "GRANT SELECT, INSERT, UPDATE, DELETE ON ALL TABLES IN SCHEMA public TO mealmind_app",
+    ] {
+        sqlx::query(statement).execute(&app.state.db).await.unwrap();
+    }
+    let mut config = (*app.state.config).clone();
+    config.row_level_security = true;
+    config.database_url =
+        config
+            .database_url
+            .replacen("postgres:postgres@", "mealmind_app:mealmind_app@", 1);
+    let state = AppState {
+        db: sqlx::PgPool::connect(&config.database_url).await.unwrap(),
+        config: Arc::new(config),
+        ..app.state.clone()
+    };
+    assert!(!bypasses_row_level_security(&state.db).await.unwrap());
+
+    // No user filter in either statement: the policies alone scope them.
+    let mut tx = state.begin_as(alice).await.unwrap();
+    let titles: Vec<String> = sqlx::query_scalar("SELECT title FROM meals")
+        .fetch_all(&mut *tx)
+        .await
+        .unwrap();
+    assert_eq!(titles, ["Soup"]);
+    let renamed = sqlx::query("UPDATE meals SET title = 'Mine now' WHERE user_id = $1")
+        .bind(bob)
+        .execute(&mut *tx)
+        .await
+        .unwrap();
+    assert_eq!(renamed.rows_affected(), 0);
+    tx.commit().await.unwrap();
+}
+
+/// Invites `client_email` as a client of `coach`; the client's id and the
+/// coach's id.
+async fn invite_client(
+    app: &TestApp,
+    coach: &str,
+    client: &str,
+    client_email: &str,
+) -> (String, String) {
+    let (status, invite) = app
+        .send(
+            Method::POST,
+            "/coach/clients",
+            Some(coach),
+            Some(json!({"email": client_email})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::CREATED, "{invite}");
+    let (_, coaches) = app
+        .send(Method::GET, "/me/coaches", Some(client), None)
+        .await;
+    (
+        invite["client_id"].as_str().unwrap().to_string(),
+        coaches[0]["coach_id"].as_str().unwrap().to_string(),
+    )
+}
+
+#[tokio::test]
+async fn coaches_see_client_meals_only_while_consented() {
+    let app = AppState::test().await.expect("start test app");
+    let coach = app.register("coach@example.com").await;
+    let client = app.register("client@example.com").await;
+    let stranger = app.register("stranger@example.com").await;
+    let (_, meal) = app
+        .send(
+            Method::POST,
+            "/meals",
+            Some(&client),
+            Some(json!({"title": "Salad"})),
+        )
+        .await;
+    let (client_id, coach_id) = invite_client(&app, &coach, &client, "client@example.com").await;
+    let meals = format!("/coach/clients/{client_id}/meals");
+    let details = format!("{meals}/{}", meal["id"].as_str().unwrap());
+
+    // Pending until the client accepts.
+    for uri in [&meals, &details] {
+        let (status, _) = app.send(Method::GET, uri, Some(&coach), None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND, "pending {uri}");
+    }
+
+    let accept = format!("/me/coaches/{coach_id}/accept");
+    let (status, _) = app.send(Method::POST, &accept, Some(&client), None).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, listed) = app.send(Method::GET, &meals, Some(&coach), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(listed[0]["id"], meal["id"]);
+    let (status, _) = app.send(Method::GET, &details, Some(&coach), None).await;
+    assert_eq!(status, StatusCode::OK);
+
+    // A coach the client never invited.
+    for uri in [&meals, &details] {
+        let (status, _) = app.send(Method::GET, uri, Some(&stranger), None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND, "unrelated {uri}");
+    }
+
+    let revoke = format!("/me/coaches/{coach_id}");
+    let (status, _) = app.send(Method::DELETE, &revoke, Some(&client), None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    for uri in [&meals, &details] {
+        let (status, _) = app.send(Method::GET, uri, Some(&coach), None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND, "revoked {uri}");
+    }
+}
+
+#[tokio::test]
+async fn coaches_cannot_change_client_meals() {
+    let app = AppState::test().await.expect("start test app");
+    let coach = app.register("trainer@example.com").await;
+    let client = app.register("trainee@example.com").await;
+    let (_, meal) = app
+        .send(
+            Method::POST,
+            "/meals",
+            Some(&client),
+            Some(json!({"title": "Pasta", "nutrition": {"total_calories_kcal": 700}})),
+        )
+        .await;
+    let id = meal["id"].as_str().unwrap();
+    let (client_id, coach_id) = invite_client(&app, &coach, &client, "trainee@example.com").await;
+    let accept = format!("/me/coaches/{coach_id}/accept");
+    let (status, _) = app.send(Method::POST, &accept, Some(&client), None).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let attempts = [
+        (
+            Method::PUT,
+            format!("/meals/{id}/nutrition"),
+            Some(json!({"total_calories_kcal": 1})),
+        ),
+        (Method::DELETE, format!("/meals/{id}/nutrition"), None),
+        (
+            Method::POST,
+            format!("/meals/{id}/items"),
+            Some(json!({"name": "Bread", "quantity": 50, "unit": "g"})),
+        ),
+    ];
+    for (method, uri, body) in attempts {
+        let (status, _) = app.send(method.clone(), &uri, Some(&coach), body).await;
+        assert_eq!(status, StatusCode::NOT_FOUND, "{method} {uri}");
+    }
+    let (status, _) = app
+        .send(
+            Method::DELETE,
+            &format!("/coach/clients/{client_id}/meals/{id}"),
+            Some(&coach),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::METHOD_NOT_ALLOWED);
+    let (status, bulk) = app
+        .send(
+            Method::POST,
+            "/meals/bulk",
+            Some(&coach),
+            Some(json!({"operations": [{"op": "delete", "meal_ids": [id]}]})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(bulk["results"][0]["ok"], false);
+
+    let (status, meal) = app
+        .send(Method::GET, &format!("/meals/{id}"), Some(&client), None)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(meal["nutrition"]["total_calories_kcal"], 700.0);
+}
+
+#[tokio::test]
+async fn concurrent_registrations_of_one_email_conflict() {
+    let app = AppState::test().await.expect("start test app");
+    let register = |email: &'static str| {
+        app.send(
+            Method::POST,
+            "/auth/register",
+            None,
+            Some(json!({"email": email, "password": "password123"})),
+        )
+    };
+    let ((first, _), (second, _)) =
+        tokio::join!(register("race@example.com"), register("Race@Example.com"));
+    let mut statuses = [first, second];
+    statuses.sort();
+    assert_eq!(statuses, [StatusCode::OK, StatusCode::CONFLICT]);
+
+    let (status, body) = register("race@example.com").await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(body["error_code"], "AUTH_EMAIL_TAKEN");
+}
+
+#[tokio::test]
+async fn stale_jobs_are_reclaimed_until_attempts_run_out() {
+    let app = AppState::test().await.expect("start test app");
+    let db = &app.state.db;
+    let job = Job::GeocodeMeal {
+        meal_id: Uuid::new_v4(),
+    };
+    let id = jobs_repo::enqueue(db, &job, 2).await.unwrap();
+
+    // A zero lock timeout makes every running job look abandoned.
+    let first = jobs_repo::claim_next(db, 0).await.unwrap().unwrap();
+    let second = jobs_repo::claim_next(db, 0).await.unwrap().unwrap();
+    assert_eq!((first.id, second.id), (id, id));
+    assert_eq!((first.attempts, second.attempts), (1, 2));
+    assert!(
+        !jobs_repo::mark_done(db, id, first.locked_at).await.unwrap(),
+        "the first worker lost its claim"
+    );
+    assert!(jobs_repo::claim_next(db, 0).await.unwrap().is_none());
+
+    let abandoned = jobs_repo::fail_abandoned(db, 0).await.unwrap();
+    assert_eq!(abandoned.len(), 1);
+    assert!(!jobs_repo::mark_done(db, id, second.locked_at)
+        .await
+        .unwrap());
+    let status: String = sqlx::query_scalar("SELECT status FROM jobs WHERE id = $1")
+        .bind(id)
+        .fetch_one(db)
+        .await
+        .unwrap();
+    assert_eq!(status, "failed");
+}
+
+/// Next text frame of a WebSocket as JSON, or the close code.
+async fn next_ws_frame(
+    socket: &mut tokio_tungstenite::WebSocketStream<
+        tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>,
+    >,
+) -> Result<serde_json::Value, Option<u16>> {
+    use futures::StreamExt;
+    use tokio_tungstenite::tungstenite::Message;
+
+    loop {
+        match socket.next().await {
+            Some(Ok(Message::Text(text))) => return Ok(serde_json::from_str(&text).unwrap()),
+            Some(Ok(Message::Close(frame))) => return Err(frame.map(|f| f.code.into())),
+            Some(Ok(_)) => continue,
+            Some(Err(_)) | None => return Err(None),
+        }
+    }
+}
+
+#[tokio::test]
+async fn websockets_authenticate_by_query_or_first_message() {
+    use futures::SinkExt;
+    use tokio_tungstenite::{connect_async, tungstenite::Message};
+
+    let app = AppState::test().await.expect("start test app");
+    let token = app.register("ws@example.com").await;
+    let addr = app.serve().await;
+
+    let (mut socket, _) = connect_async(format!("ws://{addr}/ws?token={token}"))
+        .await
+        .expect("query token accepted");
+    assert_eq!(
+        next_ws_frame(&mut socket).await,
+        Ok(json!({"type": "ready"}))
+    );
+
+    let (mut socket, _) = connect_async(format!("ws://{addr}/ws")).await.unwrap();
+    let auth = json!({"type": "auth", "token": token}).to_string();
+    socket.send(Message::Text(auth)).await.unwrap();
+    assert_eq!(
+        next_ws_frame(&mut socket).await,
+        Ok(json!({"type": "ready"}))
+    );
+    socket
+        .send(Message::Text(r#"{"type":"ping"}"#.into()))
+        .await
+        .unwrap();
+    assert_eq!(
+        next_ws_frame(&mut socket).await,
+        Ok(json!({"type": "pong"}))
+    );
+}
+
+#[tokio::test]
+async fn websockets_reject_bad_or_missing_tokens() {
+    use futures::SinkExt;
+    use tokio_tungstenite::{
+        connect_async,
+        tungstenite::{Error, Message},
+    };
+
+    let app = AppState::test().await.expect("start test app");
+    let addr = app.serve().await;
+
+    // A bad query token fails the upgrade itself.
+    match connect_async(format!("ws://{addr}/ws?token=not-a-jwt")).await {
+        Err(Error::Http(response)) => assert_eq!(response.status(), StatusCode::UNAUTHORIZED),
+        other => panic!("expected 401, got {other:?}"),
+    }
+
+    let (mut socket, _) = connect_async(format!("ws://{addr}/ws")).await.unwrap();
+    let auth = json!({"type": "auth", "token": "not-a-jwt"}).to_string();
+    socket.send(Message::Text(auth)).await.unwrap();
+    assert_eq!(next_ws_frame(&mut socket).await, Err(Some(1008)));
+
+    // Silent sockets are closed once the auth timeout passes.
+    let (mut socket, _) = connect_async(format!("ws://{addr}/ws")).await.unwrap();
+    let wait = crate::routes::ws::AUTH_TIMEOUT + std::time::Duration::from_secs(5);
+    let closed = tokio::time::timeout(wait, next_ws_frame(&mut socket))
+        .await
+        .expect("closed before the timeout");
+    assert_eq!(closed, Err(Some(1008)));
+}
+
+#[tokio::test]
+async fn websockets_close_when_the_token_expires() {
+    use axum::extract::FromRef;
+    use futures::SinkExt;
+    use tokio_tungstenite::{connect_async, tungstenite::Message};
+
+    use crate::auth::jwt::JwtKeys;
+
+    let app = AppState::test().await.expect("start test app");
+    app.register("ws-expiry@example.com").await;
+    let user_id: Uuid = sqlx::query_scalar("SELECT id FROM users WHERE email = $1")
+        .bind("ws-expiry@example.com")
+        .fetch_one(&app.state.db)
+        .await
+        .unwrap();
+    let mut keys = JwtKeys::from_ref(&app.state);
+    keys.access_ttl = std::time::Duration::from_secs(2);
+    let short = keys.sign_access(user_id).unwrap();
+    let addr = app.serve().await;
+
+    let (mut socket, _) = connect_async(format!("ws://{addr}/ws?token={short}"))
+        .await
+        .unwrap();
+    assert_eq!(
+        next_ws_frame(&mut socket).await,
+        Ok(json!({"type": "ready"}))
+    );
+    let closed = tokio::time::timeout(
+        std::time::Duration::from_secs(10),
+        next_ws_frame(&mut socket),
+    )
+    .await
+    .expect("closed once the token expired");
+    assert_eq!(closed, Err(Some(1008)));
+
+    // A fresh token sent before expiry keeps the socket open.
+    let short = keys.sign_access(user_id).unwrap();
+    let (mut socket, _) = connect_async(format!("ws://{addr}/ws?token={short}"))
+        .await
+        .unwrap();
+    next_ws_frame(&mut socket).await.unwrap();
+    keys.access_ttl = std::time::Duration::from_secs(300);
+    let fresh = keys.sign_access(user_id).unwrap();
+    let auth = json!({"type": "auth", "token": fresh}).to_string();
+    socket.send(Message::Text(auth)).await.unwrap();
+    tokio::time::sleep(std::time::Duration::from_secs(3)).await;
+    socket
+        .send(Message::Text(r#"{"type":"ping"}"#.into()))
+        .await
+        .unwrap();
+    assert_eq!(
+        next_ws_frame(&mut socket).await,
+        Ok(json!({"type": "pong"}))
+    );
+}
@@ -0,0 +1,257 @@
+//! End-to-end test harness (`--features test-support`): [`AppState::test`]
+//! starts Postgres and MinIO in containers, migrates the database and builds
+//! the full router. Requires a running Docker daemon.
+
+mod e2e;
+
+use std::sync::Arc;
+
+use axum::{
+    body::{to_bytes, Body, Bytes},
+    http::{header, HeaderMap, Method, Request, StatusCode},
+    Router,
+};
+use serde_json::Value;
+use testcontainers_modules::{
+    minio::MinIO,
+    postgres::Postgres,
+    testcontainers::{runners::AsyncRunner, ContainerAsync, ImageExt},
+};
+use tower::ServiceExt;
+
+use crate::{
+    app::build_app,
+    cache::Cache,
+    config::{
+        AnalyticsConfig, AnalyzerConfig, AppConfig, BillingConfig, CircuitBreakerConfig,
+        CronConfig, DuplicatesConfig, EventsConfig, FoodsConfig, GeocodingConfig, HttpConfig,
+        IntegrationsConfig, JobsConfig, JwtConfig, PhotoUrlConfig, PushConfig, ResearchConfig,
+        S3Config, ScoreConfig, SecurityHeadersConfig, StatsConfig, StorageBackend,
+        StorageRetryConfig, TasksConfig, TranscodeConfig, UploadConfig, WebhooksConfig,
+    },
+    db::{AppState, MIGRATOR},
+    flags::Flags,
+    foods::FoodSources,
+    images::fingerprint::Fingerprinter,
+    integrations::Integrations,
+    push::PushProviders,
+    realtime::EventHub,
+    recommendations::MacroFitStrategy,
+    stats::cache::StatsCache,
+    storage::{RetryingStorage, S3Storage},
+    tasks::TaskHealth,
+};
+
+const BUCKET: &str = "mealmind-test";
+
+/// A migrated, empty application. The containers live as long as this value.
+pub struct TestApp {
+    pub state: AppState,
+    pub router: Router,
+    _postgres: ContainerAsync<Postgres>,
+    _minio: ContainerAsync<MinIO>,
+}
+
+impl AppState {
+    pub async fn test() -> anyhow::Result<TestApp> {
+        let postgres = Postgres::default().with_tag("16-alpine").start().await?;
+        let database_url = format!(
+            "postgres://postgres:postgres@{}:{}/postgres",
+            postgres.get_host().await?,
+            postgres.get_host_port_ipv4(5432).await?
+        );
+        let minio = MinIO::default().start().await?;
+        let s3 = S3Config {
+            endpoint: Some(format!(
+                "http://{}:{}",
+                minio.get_host().await?,
+                minio.get_host_port_ipv4(9000).await?
+            )),
+            bucket: BUCKET.into(),
+            region: "us-east-1".into(),
+            access_key: "minioadmin".into(),
+            secret_key: "minioadmin".into(),
+            use_path_style: true,
+        };
+
+        let db = sqlx::PgPool::connect(&database_url).await?;
+        MIGRATOR.run(&db).await?;
+        let s3_storage = S3Storage::new(&s3);
+        s3_storage.create_bucket().await?;
+
+        let config = Arc::new(AppConfig {
+            database_url,
+            strict_migrations: true,
+            row_level_security: false,
+            redis_url: None,
+            jwt: JwtConfig {
+                secret: "test-secret".into(),
+                issuer: "mealmind".into(),
+                audience: "mealmind-users".into(),
+                ttl_minutes: 5,
+                refresh_ttl_minutes: 60,
+            },
+            storage_backend: StorageBackend::S3,
+            s3,
+            storage_retry: StorageRetryConfig::default(),
+            storage_breaker: CircuitBreakerConfig::default(),
+            uploads: UploadConfig::default(),
+            photo_urls: PhotoUrlConfig::default(),
+            http: HttpConfig::default(),
+            security_headers: SecurityHeadersConfig::default(),
+            transcode: TranscodeConfig::default(),
+            duplicates: DuplicatesConfig::default(),
+            jobs: JobsConfig::default(),
+            tasks: TasksConfig::default(),
+            cron: CronConfig::default(),
+            events: EventsConfig::default(),
+            webhooks: WebhooksConfig::default(),
+            push: PushConfig::default(),
+            analyzer: AnalyzerConfig::default(),
+            score: ScoreConfig::default(),
+            stats: StatsConfig::default(),
+            foods: FoodsConfig::default(),
+            integrations: IntegrationsConfig::default(),
+            geocoding: GeocodingConfig::default(),
+            analytics: AnalyticsConfig::default(),
+            research: ResearchConfig::default(),
+            billing: BillingConfig::default(),
+        });
+        let state = AppState {
+            db,
+            storage: Arc::new(RetryingStorage::new(
+                Arc::new(s3_storage),
+                config.storage_retry.clone(),
+            )),
+            events: EventHub::default(),
+            analyzer: None,
+            stats_cache: StatsCache::default(),
+            cache: Cache::default(),
+            tasks: TaskHealth::default(),
+            push: PushProviders::default(),
+            flags: Flags::default(),
+            integrations: Integrations::default(),
+            geocoder: None,
+            analytics_sink: None,
+            recommender: Arc::new(MacroFitStrategy),
+            fingerprinter: Fingerprinter::default(),
+            foods: FoodSources::from_config(&config.foods)?,
+            config,
+        };
+        Ok(TestApp {
+            router: build_app(state.clone()),
+            state,
+            _postgres: postgres,
+            _minio: minio,
+        })
+    }
+}
+
+impl TestApp {
+    /// Sends a request through the router; the response body is parsed as
+    /// JSON, or `Null` when empty.
+    pub async fn send(
+        &self,
+        method: Method,
+        uri: &str,
+        token: Option<&str>,
+        body: Option<Value>,
+    ) -> (StatusCode, Value) {
+        let mut request = Request::builder().method(method).uri(uri);
+        if let Some(token) = token {
+            request = request.header(header::AUTHORIZATION, format!("Bearer {token}"));
+        }
+        let body = match body {
+            Some(json) => {
+                request = request.header(header::CONTENT_TYPE, "application/json");
+                Body::from(json.to_string())
+            }
+            None => Body::empty(),
+        };
+        self.dispatch(request.body(body).expect("valid request"))
+            .await
+    }
+
+    /// Sends raw `bytes` of `content_type`, e.g. an image upload.
+    pub async fn send_bytes(
+        &self,
+        method: Method,
+        uri: &str,
+        token: &str,
+        content_type: &str,
+        bytes: &'static [u8],
+    ) -> (StatusCode, Value) {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header(header::AUTHORIZATION, format!("Bearer {token}"))
+            .header(header::CONTENT_TYPE, content_type)
+            .body(Body::from(bytes))
+            .expect("valid request");
+        self.dispatch(request).await
+    }
+
+    /// GETs `uri` and returns the body as is, for responses that aren't
+    /// JSON such as photo downloads.
+    pub async fn get_bytes(&self, uri: &str, token: &str) -> (StatusCode, HeaderMap, Bytes) {
+        let request = Request::builder()
+            .uri(uri)
+            .header(header::AUTHORIZATION, format!("Bearer {token}"))
+            .body(Body::empty())
+            .expect("valid request");
+        let response = self
+            .router
+            .clone()
+            .oneshot(request)
+            .await
+            .expect("router is infallible");
+        let (parts, body) = response.into_parts();
+        let bytes = to_bytes(body, usize::MAX).await.expect("readable body");
+        (parts.status, parts.headers, bytes)
+    }
+
+    async fn dispatch(&self, request: Request<Body>) -> (StatusCode, Value) {
+        let response = self
+            .router
+            .clone()
+            .oneshot(request)
+            .await
+            .expect("router is infallible");
+        let status = response.status();
+        let bytes = to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("readable body");
+        let json = if bytes.is_empty() {
+            Value::Null
+        } else {
+            serde_json::from_slice(&bytes).expect("JSON body")
+        };
+        (status, json)
+    }
+
+    /// Serves the router on a local port, for clients that need a real
+    /// connection such as WebSockets. The server runs until the test ends.
+    pub async fn serve(&self) -> std::net::SocketAddr {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("bind test server");
+        let addr = listener.local_addr().expect("local address");
+        let router = self.router.clone();
+        tokio::spawn(async move { axum::serve(listener, router).await });
+        addr
+    }
+
+    /// Registers `email` and returns its access token.
+    pub async fn register(&self, email: &str) -> String {
+        let (status, body) = self
+            .send(
+                Method::POST,
+                "/auth/register",
+                None,
+                Some(serde_json::json!({"email": email, "password": "password123"})),
+            )
+            .await;
+        assert_eq!(status, StatusCode::OK, "register failed: {body}");
+        body["access_token"].as_str().unwrap().to_string()
+    }
+}
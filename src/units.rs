@@ -0,0 +1,162 @@
+//! Units of measure shared by meal items, recipes and foods. Quantities are
+//! stored in the unit they were entered in and turned into grams or
+//! millilitres to scale nutrition. Responses show them in the unit system
+//! the client asks for; nutrient amounts stay in grams and milligrams in
+//! both, as on nutrition labels.
+
+use serde::{Deserialize, Serialize};
+
+pub const GRAMS_PER_OZ: f64 = 28.349523125;
+pub const GRAMS_PER_LB: f64 = 453.59237;
+/// US customary volumes.
+pub const ML_PER_FL_OZ: f64 = 29.5735295625;
+pub const ML_PER_CUP: f64 = 236.5882365;
+pub const ML_PER_TBSP: f64 = 14.78676478125;
+pub const ML_PER_TSP: f64 = 4.92892159375;
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "text", rename_all = "lowercase")]
+pub enum Units {
+    #[default]
+    Metric,
+    Imperial,
+}
+
+/// `?units=`; without it responses use the unit system of the user's
+/// preferences.
+#[derive(Debug, Default, Deserialize)]
+pub struct UnitsQuery {
+    pub units: Option<Units>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+pub enum ItemUnit {
+    G,
+    /// Treated like grams when scaling per-100 g nutrition.
+    Ml,
+    Serving,
+    Oz,
+    Lb,
+    FlOz,
+    Cup,
+    Tbsp,
+    Tsp,
+}
+
+impl ItemUnit {
+    /// Grams or millilitres in one of this unit; `None` for servings.
+    fn metric_factor(self) -> Option<f64> {
+        match self {
+            ItemUnit::G | ItemUnit::Ml => Some(1.0),
+            ItemUnit::Oz => Some(GRAMS_PER_OZ),
+            ItemUnit::Lb => Some(GRAMS_PER_LB),
+            ItemUnit::FlOz => Some(ML_PER_FL_OZ),
+            ItemUnit::Cup => Some(ML_PER_CUP),
+            ItemUnit::Tbsp => Some(ML_PER_TBSP),
+            ItemUnit::Tsp => Some(ML_PER_TSP),
+            ItemUnit::Serving => None,
+        }
+    }
+
+    fn is_volume(self) -> bool {
+        matches!(
+            self,
+            ItemUnit::Ml | ItemUnit::FlOz | ItemUnit::Cup | ItemUnit::Tbsp | ItemUnit::Tsp
+        )
+    }
+
+    fn system(self) -> Option<Units> {
+        match self {
+            ItemUnit::G | ItemUnit::Ml => Some(Units::Metric),
+            ItemUnit::Serving => None,
+            _ => Some(Units::Imperial),
+        }
+    }
+
+    /// Grams (or millilitres) in `quantity` of this unit; `None` for
+    /// servings.
+    pub fn metric_amount(self, quantity: f64) -> Option<f64> {
+        self.metric_factor().map(|factor| quantity * factor)
+    }
+
+    /// Largest quantity of this unit that stays within `max_metric` grams
+    /// or millilitres, rounded down to two decimals.
+    pub fn max_quantity(self, max_metric: f64, max_servings: f64) -> f64 {
+        match self.metric_factor() {
+            Some(factor) => (max_metric / factor * 100.0).floor() / 100.0,
+            None => max_servings,
+        }
+    }
+
+    /// `quantity` in `units`: kept as is when already in that system (or in
+    /// servings), otherwise converted to grams, millilitres, ounces or
+    /// fluid ounces and rounded to two decimals.
+    pub fn in_units(self, quantity: f64, units: Units) -> (ItemUnit, f64) {
+        let Some(metric) = self.metric_amount(quantity) else {
+            return (self, quantity);
+        };
+        if self.system() == Some(units) {
+            return (self, quantity);
+        }
+        let target = match (units, self.is_volume()) {
+            (Units::Metric, false) => ItemUnit::G,
+            (Units::Metric, true) => ItemUnit::Ml,
+            (Units::Imperial, false) => ItemUnit::Oz,
+            (Units::Imperial, true) => ItemUnit::FlOz,
+        };
+        let factor = target.metric_factor().unwrap_or(1.0);
+        (target, round2(metric / factor))
+    }
+}
+
+/// Serving size in ounces, for imperial responses.
+pub fn grams_to_oz(grams: f64) -> f64 {
+    round2(grams / GRAMS_PER_OZ)
+}
+
+fn round2(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_to_metric_amounts() {
+        assert_eq!(ItemUnit::G.metric_amount(150.0), Some(150.0));
+        assert_eq!(ItemUnit::Lb.metric_amount(0.5), Some(226.796185));
+        assert_eq!(ItemUnit::Cup.metric_amount(2.0), Some(473.176473));
+        assert_eq!(ItemUnit::Serving.metric_amount(2.0), None);
+        assert_eq!(ItemUnit::Oz.max_quantity(10_000.0, 100.0), 352.73);
+    }
+
+    #[test]
+    fn shows_quantities_in_the_requested_system() {
+        assert_eq!(
+            ItemUnit::G.in_units(100.0, Units::Imperial),
+            (ItemUnit::Oz, 3.53)
+        );
+        assert_eq!(
+            ItemUnit::Ml.in_units(250.0, Units::Imperial),
+            (ItemUnit::FlOz, 8.45)
+        );
+        assert_eq!(
+            ItemUnit::Cup.in_units(1.0, Units::Metric),
+            (ItemUnit::Ml, 236.59)
+        );
+        // Entered units of the requested system, and servings, stay.
+        assert_eq!(
+            ItemUnit::Cup.in_units(1.0, Units::Imperial),
+            (ItemUnit::Cup, 1.0)
+        );
+        assert_eq!(
+            ItemUnit::Serving.in_units(2.0, Units::Imperial),
+            (ItemUnit::Serving, 2.0)
+        );
+        assert_eq!(grams_to_oz(40.0), 1.41);
+    }
+}
@@ -0,0 +1,129 @@
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct UploadSession {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    /// The id the meal gets once the session is finalized.
+    pub meal_id: Uuid,
+    pub created_at: OffsetDateTime,
+    pub expires_at: OffsetDateTime,
+    pub finalized_at: Option<OffsetDateTime>,
+}
+
+impl UploadSession {
+    pub fn is_expired(&self, now: OffsetDateTime) -> bool {
+        self.expires_at <= now
+    }
+}
+
+/// A photo uploaded to a session, by position.
+#[derive(Debug, Clone, PartialEq, Serialize, sqlx::FromRow)]
+pub struct SessionPhoto {
+    pub position: i32,
+    pub photo_id: Uuid,
+    pub content_type: Option<String>,
+    pub size_bytes: Option<i64>,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    /// Hex SHA-256 of the bytes as uploaded, so a client resuming a session
+    /// can tell which of its photos already arrived.
+    pub sha256: Option<String>,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub taken_at: Option<OffsetDateTime>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UploadSessionResponse {
+    pub id: Uuid,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub expires_at: OffsetDateTime,
+    /// Positions run from 0 to `max_photos - 1`.
+    pub max_photos: usize,
+    /// The meal created from the session; `null` until finalized.
+    pub meal_id: Option<Uuid>,
+    pub photos: Vec<SessionPhoto>,
+}
+
+impl UploadSessionResponse {
+    pub fn new(session: UploadSession, max_photos: usize, photos: Vec<SessionPhoto>) -> Self {
+        Self {
+            id: session.id,
+            created_at: session.created_at,
+            expires_at: session.expires_at,
+            max_photos,
+            meal_id: session.finalized_at.map(|_| session.meal_id),
+            photos,
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct PhotoUploadQuery {
+    /// When the photo was taken, RFC 3339.
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub taken_at: Option<OffsetDateTime>,
+}
+
+/// A large photo being uploaded in parts to a session position.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct ChunkedUpload {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub session_id: Option<Uuid>,
+    pub position: i32,
+    pub photo_id: Uuid,
+    pub s3_key: String,
+    pub storage_upload_id: String,
+    pub content_type: String,
+    pub size_bytes: i64,
+    pub taken_at: Option<OffsetDateTime>,
+    pub created_at: OffsetDateTime,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, sqlx::FromRow)]
+pub struct UploadedPart {
+    pub number: i32,
+    pub size_bytes: i64,
+    #[serde(skip_serializing)]
+    pub etag: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StartChunkedUploadRequest {
+    pub content_type: String,
+    /// Size of the whole photo; the parts must add up to it.
+    pub size_bytes: i64,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub taken_at: Option<OffsetDateTime>,
+}
+
+impl StartChunkedUploadRequest {
+    pub fn validate(&self, max_bytes: usize) -> Result<(), String> {
+        if self.size_bytes <= 0 {
+            return Err("size_bytes must be positive".into());
+        }
+        if self.size_bytes as u64 > max_bytes as u64 {
+            return Err(format!("size_bytes exceeds {max_bytes} bytes"));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ChunkedUploadResponse {
+    pub id: Uuid,
+    pub session_id: Option<Uuid>,
+    pub position: i32,
+    pub content_type: String,
+    pub size_bytes: i64,
+    /// Every part but the last must be at least this large.
+    pub min_part_bytes: usize,
+    pub max_part_bytes: usize,
+    /// Parts received so far, by number.
+    pub parts: Vec<UploadedPart>,
+}
@@ -0,0 +1,10 @@
+//! Upload sessions for multi-photo meals on flaky networks. `POST
+//! /uploads/sessions` opens a session, each photo is then uploaded on its
+//! own (in parallel, and retried until it lands) to a numbered position,
+//! and `POST /meals` with `upload_session_id` turns the session into a meal.
+//! Photos of sessions that are never finalized are removed by the orphan
+//! photo cleanup once the session expires.
+
+pub mod dto;
+pub mod repo;
+pub mod services;
@@ -0,0 +1,345 @@
+use sqlx::{PgConnection, PgExecutor, PgPool};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::{
+    photos::repo::{NewPhoto, Photo},
+    uploads::dto::{ChunkedUpload, SessionPhoto, UploadSession, UploadedPart},
+};
+
+pub async fn create_session(
+    db: &PgPool,
+    user_id: Uuid,
+    ttl_hours: i32,
+) -> anyhow::Result<UploadSession> {
+    let session = sqlx::query_as::<_, UploadSession>(
+        r#"
+        INSERT INTO upload_sessions (user_id, meal_id, expires_at)
+        VALUES ($1, $2, NOW() + make_interval(hours => $3))
+        RETURNING id, user_id, meal_id, created_at, expires_at, finalized_at
+        "#,
+    )
+    .bind(user_id)
+    .bind(Uuid::new_v4())
+    .bind(ttl_hours)
+    .fetch_one(db)
+    .await?;
+    Ok(session)
+}
+
+/// The user's session, expired or finalized ones included.
+pub async fn find_session(
+    db: impl PgExecutor<'_>,
+    user_id: Uuid,
+    session_id: Uuid,
+) -> anyhow::Result<Option<UploadSession>> {
+    let session = sqlx::query_as::<_, UploadSession>(
+        r#"
+        SELECT id, user_id, meal_id, created_at, expires_at, finalized_at
+        FROM upload_sessions
+        WHERE id = $1 AND user_id = $2
+        "#,
+    )
+    .bind(session_id)
+    .bind(user_id)
+    .fetch_optional(db)
+    .await?;
+    Ok(session)
+}
+
+/// Locks the session if it is still open, serializing photo writes and
+/// finalization of one session.
+pub async fn lock_open_session(
+    db: &mut PgConnection,
+    user_id: Uuid,
+    session_id: Uuid,
+) -> anyhow::Result<Option<UploadSession>> {
+    let session = sqlx::query_as::<_, UploadSession>(
+        r#"
+        SELECT id, user_id, meal_id, created_at, expires_at, finalized_at
+        FROM upload_sessions
+        WHERE id = $1 AND user_id = $2
+          AND finalized_at IS NULL AND expires_at > NOW()
+        FOR UPDATE
+        "#,
+    )
+    .bind(session_id)
+    .bind(user_id)
+    .fetch_optional(db)
+    .await?;
+    Ok(session)
+}
+
+pub async fn list_photos(
+    db: impl PgExecutor<'_>,
+    session_id: Uuid,
+) -> anyhow::Result<Vec<SessionPhoto>> {
+    let photos = sqlx::query_as::<_, SessionPhoto>(
+        r#"
+        SELECT upload_position AS position, id AS photo_id, content_type, size_bytes,
+               width, height, content_hash AS sha256, taken_at
+        FROM photos
+        WHERE upload_session_id = $1 AND meal_id IS NULL
+        ORDER BY upload_position
+        "#,
+    )
+    .bind(session_id)
+    .fetch_all(db)
+    .await?;
+    Ok(photos)
+}
+
+pub async fn find_photo(
+    db: impl PgExecutor<'_>,
+    session_id: Uuid,
+    position: i32,
+) -> anyhow::Result<Option<SessionPhoto>> {
+    let photo = sqlx::query_as::<_, SessionPhoto>(
+        r#"
+        SELECT upload_position AS position, id AS photo_id, content_type, size_bytes,
+               width, height, content_hash AS sha256, taken_at
+        FROM photos
+        WHERE upload_session_id = $1 AND upload_position = $2 AND meal_id IS NULL
+        "#,
+    )
+    .bind(session_id)
+    .bind(position)
+    .fetch_optional(db)
+    .await?;
+    Ok(photo)
+}
+
+/// Removes the photo at `position`, returning the storage keys (photo, kept
+/// original) it used.
+pub async fn delete_photo(
+    db: impl PgExecutor<'_>,
+    session_id: Uuid,
+    position: i32,
+) -> anyhow::Result<Option<(String, Option<String>)>> {
+    let keys = sqlx::query_as::<_, (String, Option<String>)>(
+        r#"
+        DELETE FROM photos
+        WHERE upload_session_id = $1 AND upload_position = $2 AND meal_id IS NULL
+        RETURNING s3_key, original_s3_key
+        "#,
+    )
+    .bind(session_id)
+    .bind(position)
+    .fetch_optional(db)
+    .await?;
+    Ok(keys)
+}
+
+/// Records `photo` at `position` of the session; any photo there must be
+/// deleted first.
+pub async fn insert_photo(
+    db: impl PgExecutor<'_>,
+    user_id: Uuid,
+    session_id: Uuid,
+    position: i32,
+    photo: &NewPhoto,
+) -> anyhow::Result<SessionPhoto> {
+    let photo = sqlx::query_as::<_, SessionPhoto>(
+        r#"
+        INSERT INTO photos (id, user_id, s3_key, original_s3_key, content_type, size_bytes,
+                            width, height, content_hash, taken_at, upload_session_id,
+                            upload_position, perceptual_hash)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+        RETURNING upload_position AS position, id AS photo_id, content_type, size_bytes,
+                  width, height, content_hash AS sha256, taken_at
+        "#,
+    )
+    .bind(photo.id)
+    .bind(user_id)
+    .bind(&photo.s3_key)
+    .bind(&photo.original_s3_key)
+    .bind(&photo.content_type)
+    .bind(photo.size_bytes)
+    .bind(photo.width)
+    .bind(photo.height)
+    .bind(&photo.content_hash)
+    .bind(photo.taken_at)
+    .bind(session_id)
+    .bind(position)
+    .bind(photo.perceptual_hash)
+    .fetch_one(db)
+    .await?;
+    Ok(photo)
+}
+
+/// Fingerprints of the session's photos, see [`crate::images::fingerprint`].
+pub async fn fingerprints(
+    db: impl PgExecutor<'_>,
+    session_id: Uuid,
+) -> anyhow::Result<Vec<Option<i64>>> {
+    let fingerprints = sqlx::query_scalar::<_, Option<i64>>(
+        r#"
+        SELECT perceptual_hash
+        FROM photos
+        WHERE upload_session_id = $1 AND meal_id IS NULL
+        "#,
+    )
+    .bind(session_id)
+    .fetch_all(db)
+    .await?;
+    Ok(fingerprints)
+}
+
+/// Attaches the session's photos to `meal_id` and closes the session. The
+/// session must be locked with [`lock_open_session`].
+pub async fn finalize(
+    db: &mut PgConnection,
+    session_id: Uuid,
+    meal_id: Uuid,
+) -> anyhow::Result<Vec<Photo>> {
+    let photos = sqlx::query_as::<_, Photo>(
+        r#"
+        UPDATE photos
+        SET meal_id = $2
+        WHERE upload_session_id = $1 AND meal_id IS NULL
+        RETURNING id, user_id, meal_id, s3_key, original_s3_key, content_type, size_bytes,
+                  width, height, content_hash, taken_at, status, failure_reason, created_at
+        "#,
+    )
+    .bind(session_id)
+    .bind(meal_id)
+    .fetch_all(&mut *db)
+    .await?;
+    sqlx::query("UPDATE upload_sessions SET finalized_at = NOW() WHERE id = $1")
+        .bind(session_id)
+        .execute(&mut *db)
+        .await?;
+    Ok(photos)
+}
+
+/// Deletes expired sessions. Photos they left behind lose their session and
+/// go with the orphan cleanup.
+pub async fn prune_expired(db: &PgPool) -> anyhow::Result<u64> {
+    let result = sqlx::query("DELETE FROM upload_sessions WHERE expires_at < NOW()")
+        .execute(db)
+        .await?;
+    Ok(result.rows_affected())
+}
+
+/// A chunked upload not recorded yet; see [`insert_chunked`].
+pub struct NewChunkedUpload<'a> {
+    pub session_id: Uuid,
+    pub position: i32,
+    pub photo_id: Uuid,
+    pub s3_key: &'a str,
+    pub storage_upload_id: &'a str,
+    pub content_type: &'a str,
+    pub size_bytes: i64,
+    pub taken_at: Option<OffsetDateTime>,
+}
+
+pub async fn insert_chunked(
+    db: &PgPool,
+    user_id: Uuid,
+    upload: &NewChunkedUpload<'_>,
+) -> anyhow::Result<ChunkedUpload> {
+    let upload = sqlx::query_as::<_, ChunkedUpload>(
+        r#"
+        INSERT INTO chunked_uploads (user_id, session_id, position, photo_id, s3_key,
+                                     storage_upload_id, content_type, size_bytes, taken_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+        RETURNING id, user_id, session_id, position, photo_id, s3_key, storage_upload_id,
+                  content_type, size_bytes, taken_at, created_at
+        "#,
+    )
+    .bind(user_id)
+    .bind(upload.session_id)
+    .bind(upload.position)
+    .bind(upload.photo_id)
+    .bind(upload.s3_key)
+    .bind(upload.storage_upload_id)
+    .bind(upload.content_type)
+    .bind(upload.size_bytes)
+    .bind(upload.taken_at)
+    .fetch_one(db)
+    .await?;
+    Ok(upload)
+}
+
+pub async fn find_chunked(
+    db: &PgPool,
+    user_id: Uuid,
+    upload_id: Uuid,
+) -> anyhow::Result<Option<ChunkedUpload>> {
+    let upload = sqlx::query_as::<_, ChunkedUpload>(
+        r#"
+        SELECT id, user_id, session_id, position, photo_id, s3_key, storage_upload_id,
+               content_type, size_bytes, taken_at, created_at
+        FROM chunked_uploads
+        WHERE id = $1 AND user_id = $2
+        "#,
+    )
+    .bind(upload_id)
+    .bind(user_id)
+    .fetch_optional(db)
+    .await?;
+    Ok(upload)
+}
+
+pub async fn list_parts(db: &PgPool, upload_id: Uuid) -> anyhow::Result<Vec<UploadedPart>> {
+    let parts = sqlx::query_as::<_, UploadedPart>(
+        r#"
+        SELECT part_number AS number, size_bytes, etag
+        FROM chunked_upload_parts
+        WHERE upload_id = $1
+        ORDER BY part_number
+        "#,
+    )
+    .bind(upload_id)
+    .fetch_all(db)
+    .await?;
+    Ok(parts)
+}
+
+/// Records a written part, replacing an earlier write of the same number.
+pub async fn upsert_part(db: &PgPool, upload_id: Uuid, part: &UploadedPart) -> anyhow::Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO chunked_upload_parts (upload_id, part_number, etag, size_bytes)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (upload_id, part_number)
+        DO UPDATE SET etag = EXCLUDED.etag, size_bytes = EXCLUDED.size_bytes
+        "#,
+    )
+    .bind(upload_id)
+    .bind(part.number)
+    .bind(&part.etag)
+    .bind(part.size_bytes)
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+/// Forgets a chunked upload and its parts.
+pub async fn delete_chunked(db: impl PgExecutor<'_>, upload_id: Uuid) -> anyhow::Result<()> {
+    sqlx::query("DELETE FROM chunked_uploads WHERE id = $1")
+        .bind(upload_id)
+        .execute(db)
+        .await?;
+    Ok(())
+}
+
+/// Up to `limit` chunked uploads whose session was finalized, expired or
+/// pruned, so they can never complete.
+pub async fn list_stale_chunked(db: &PgPool, limit: i64) -> anyhow::Result<Vec<ChunkedUpload>> {
+    let uploads = sqlx::query_as::<_, ChunkedUpload>(
+        r#"
+        SELECT c.id, c.user_id, c.session_id, c.position, c.photo_id, c.s3_key,
+               c.storage_upload_id, c.content_type, c.size_bytes, c.taken_at, c.created_at
+        FROM chunked_uploads c
+        LEFT JOIN upload_sessions s ON s.id = c.session_id
+        WHERE s.id IS NULL OR s.finalized_at IS NOT NULL OR s.expires_at <= NOW()
+        ORDER BY c.created_at
+        LIMIT $1
+        "#,
+    )
+    .bind(limit)
+    .fetch_all(db)
+    .await?;
+    Ok(uploads)
+}
@@ -0,0 +1,720 @@
+use std::path::Path;
+
+use axum::http::StatusCode;
+use futures::StreamExt;
+use sha2::{Digest, Sha256};
+use time::OffsetDateTime;
+use tokio::io::AsyncWriteExt;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::{
+    db::AppState,
+    error::ErrorCode,
+    events::{repo as events_repo, DomainEvent},
+    images::{
+        services::{
+            content_hash, discard_objects, find_possible_duplicate, heic_command, normalize_image,
+            object_key, release_objects, upload_images, DuplicatePhoto, ImageError,
+            MAX_IMAGES_PER_MEAL,
+        },
+        sniff::{self, ImageFormat},
+        transcode,
+    },
+    meals::{
+        dto::{MealDetails, NewMeal},
+        repo as meals_repo,
+        services::finish_photo_meal,
+    },
+    photos::repo::NewPhoto,
+    storage::CompletedPart,
+    uploads::{
+        dto::{
+            ChunkedUpload, ChunkedUploadResponse, SessionPhoto, StartChunkedUploadRequest,
+            UploadSession, UploadSessionResponse, UploadedPart,
+        },
+        repo::{self, NewChunkedUpload},
+    },
+};
+
+/// Storage's lower bound for every part of a multipart upload but the last.
+pub const MIN_PART_BYTES: usize = 5 * 1024 * 1024;
+pub const MAX_PART_BYTES: usize = 16 * 1024 * 1024;
+/// Storage's limit on parts per multipart upload.
+const MAX_PARTS: i32 = 10_000;
+/// Leading bytes of a chunked photo kept to read its dimensions; image
+/// headers sit well within them.
+const HEADER_BYTES: usize = 1024 * 1024;
+/// Stale chunked uploads aborted per batch.
+const STALE_BATCH: i64 = 100;
+
+#[derive(Debug, thiserror::Error)]
+pub enum UploadSessionError {
+    #[error("Upload session not found")]
+    NotFound,
+    #[error("Upload session is already finalized or expired")]
+    Closed,
+    #[error("Position must be between 0 and {}", MAX_IMAGES_PER_MEAL - 1)]
+    InvalidPosition,
+    #[error("Upload session has no photos")]
+    Empty,
+    #[error("Chunked upload not found")]
+    UploadNotFound,
+    #[error("{0}")]
+    Invalid(String),
+    #[error(transparent)]
+    Image(#[from] ImageError),
+    #[error(transparent)]
+    Duplicate(#[from] DuplicatePhoto),
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+impl UploadSessionError {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            UploadSessionError::NotFound | UploadSessionError::UploadNotFound => {
+                StatusCode::NOT_FOUND
+            }
+            UploadSessionError::Closed | UploadSessionError::Duplicate(_) => StatusCode::CONFLICT,
+            UploadSessionError::InvalidPosition
+            | UploadSessionError::Empty
+            | UploadSessionError::Invalid(_) => StatusCode::BAD_REQUEST,
+            UploadSessionError::Image(e) => e.status(),
+            UploadSessionError::Other(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            UploadSessionError::NotFound => ErrorCode::UploadSessionNotFound,
+            UploadSessionError::Closed => ErrorCode::UploadSessionClosed,
+            UploadSessionError::InvalidPosition | UploadSessionError::Invalid(_) => {
+                ErrorCode::ValidationFailed
+            }
+            UploadSessionError::Empty => ErrorCode::UploadNoImages,
+            UploadSessionError::UploadNotFound => ErrorCode::ChunkedUploadNotFound,
+            UploadSessionError::Image(e) => e.code(),
+            UploadSessionError::Duplicate(_) => ErrorCode::PhotoDuplicate,
+            UploadSessionError::Other(_) => ErrorCode::Internal,
+        }
+    }
+}
+
+fn check_position(position: i32) -> Result<(), UploadSessionError> {
+    match usize::try_from(position) {
+        Ok(position) if position < MAX_IMAGES_PER_MEAL => Ok(()),
+        _ => Err(UploadSessionError::InvalidPosition),
+    }
+}
+
+/// The user's session if it still accepts photos.
+async fn open_session(
+    state: &AppState,
+    user_id: Uuid,
+    session_id: Uuid,
+) -> Result<UploadSession, UploadSessionError> {
+    let session = repo::find_session(&state.db, user_id, session_id)
+        .await?
+        .ok_or(UploadSessionError::NotFound)?;
+    if session.finalized_at.is_some() || session.is_expired(OffsetDateTime::now_utc()) {
+        return Err(UploadSessionError::Closed);
+    }
+    Ok(session)
+}
+
+pub async fn create_session(
+    state: &AppState,
+    user_id: Uuid,
+) -> anyhow::Result<UploadSessionResponse> {
+    let session =
+        repo::create_session(&state.db, user_id, state.config.uploads.session_ttl_hours).await?;
+    info!(user_id = %user_id, session_id = %session.id, "upload session opened");
+    Ok(UploadSessionResponse::new(
+        session,
+        MAX_IMAGES_PER_MEAL,
+        Vec::new(),
+    ))
+}
+
+/// The session with the photos that arrived so far, for a client resuming
+/// its uploads.
+pub async fn get_session(
+    state: &AppState,
+    user_id: Uuid,
+    session_id: Uuid,
+) -> Result<UploadSessionResponse, UploadSessionError> {
+    let session = repo::find_session(&state.db, user_id, session_id)
+        .await?
+        .ok_or(UploadSessionError::NotFound)?;
+    let photos = if session.finalized_at.is_some() {
+        // Attached to the meal now; the meal lists them.
+        Vec::new()
+    } else {
+        repo::list_photos(&state.db, session.id).await?
+    };
+    Ok(UploadSessionResponse::new(
+        session,
+        MAX_IMAGES_PER_MEAL,
+        photos,
+    ))
+}
+
+/// Stores one photo at `position`, replacing whatever was there. Uploading
+/// the same bytes again is a no-op, so clients can retry freely.
+pub async fn upload_photo(
+    state: &AppState,
+    user_id: Uuid,
+    session_id: Uuid,
+    position: i32,
+    content_type: &str,
+    bytes: Vec<u8>,
+    taken_at: Option<OffsetDateTime>,
+) -> Result<SessionPhoto, UploadSessionError> {
+    check_position(position)?;
+    let session = open_session(state, user_id, session_id).await?;
+    let image = normalize_image(
+        position as usize,
+        content_type,
+        bytes,
+        taken_at,
+        &state.config.uploads,
+    )?;
+    let hash = content_hash(&image.bytes);
+    if let Some(existing) = repo::find_photo(&state.db, session.id, position).await? {
+        if existing.sha256.as_deref() == Some(hash.as_str()) && existing.taken_at == taken_at {
+            return Ok(existing);
+        }
+    }
+
+    let mut tx = state.begin_as(user_id).await.map_err(anyhow::Error::from)?;
+    let staged = upload_images(state, &mut tx, user_id, session.meal_id, vec![image]).await?;
+    let Some(new_photo) = staged.photos.first() else {
+        discard_objects(&state.storage, &staged.new_keys).await;
+        let index = position as usize;
+        return Err(ImageError::NotStored { index }.into());
+    };
+    let stored = async {
+        if repo::lock_open_session(&mut tx, user_id, session.id)
+            .await?
+            .is_none()
+        {
+            return Err(UploadSessionError::Closed);
+        }
+        let replaced = repo::delete_photo(&mut *tx, session.id, position).await?;
+        let photo = repo::insert_photo(&mut *tx, user_id, session.id, position, new_photo).await?;
+        tx.commit().await.map_err(anyhow::Error::from)?;
+        Ok((photo, replaced))
+    }
+    .await;
+    let (photo, replaced) = match stored {
+        Ok(stored) => stored,
+        Err(e) => {
+            discard_objects(&state.storage, &staged.new_keys).await;
+            return Err(e);
+        }
+    };
+    if let Some((key, original_key)) = replaced {
+        // The row is gone; a failed delete only leaves an orphaned object.
+        if let Err(e) = release_objects(state, std::iter::once(key).chain(original_key)).await {
+            warn!(error = %e, session_id = %session.id, position, "failed to release replaced photo");
+        }
+    }
+    Ok(photo)
+}
+
+/// Removes the photo at `position`. Returns `false` when there is none.
+pub async fn delete_photo(
+    state: &AppState,
+    user_id: Uuid,
+    session_id: Uuid,
+    position: i32,
+) -> Result<bool, UploadSessionError> {
+    check_position(position)?;
+    let session = open_session(state, user_id, session_id).await?;
+    let Some((key, original_key)) = repo::delete_photo(&state.db, session.id, position).await?
+    else {
+        return Ok(false);
+    };
+    release_objects(state, std::iter::once(key).chain(original_key)).await?;
+    Ok(true)
+}
+
+/// Creates the meal from a session's photos and closes the session, in one
+/// transaction. A session becomes at most one meal.
+pub async fn create_meal(
+    state: &AppState,
+    user_id: Uuid,
+    session_id: Uuid,
+    meal: NewMeal,
+) -> Result<MealDetails, UploadSessionError> {
+    let mut tx = state.begin_as(user_id).await.map_err(anyhow::Error::from)?;
+    let Some(session) = repo::lock_open_session(&mut tx, user_id, session_id).await? else {
+        return Err(
+            match repo::find_session(&mut *tx, user_id, session_id).await? {
+                Some(_) => UploadSessionError::Closed,
+                None => UploadSessionError::NotFound,
+            },
+        );
+    };
+    let fingerprints = repo::fingerprints(&mut *tx, session.id).await?;
+    let duplicate_of = find_possible_duplicate(state, user_id, fingerprints).await;
+    if let (Some(meal_id), true) = (duplicate_of, state.config.duplicates.block) {
+        // The session stays open, so the photo can be swapped out.
+        return Err(DuplicatePhoto { meal_id }.into());
+    }
+    let meal = meals_repo::create_meal(&mut *tx, session.meal_id, user_id, &meal)
+        .await
+        .map_err(anyhow::Error::from)?;
+    let photos = repo::finalize(&mut tx, session.id, meal.id).await?;
+    if photos.is_empty() {
+        return Err(UploadSessionError::Empty);
+    }
+    let created = DomainEvent::MealCreated { meal_id: meal.id };
+    events_repo::record(&mut *tx, user_id, &created).await?;
+    tx.commit().await.map_err(anyhow::Error::from)?;
+    finish_photo_meal(state, user_id, meal, photos, duplicate_of)
+        .await
+        .map_err(Into::into)
+}
+
+fn chunked_response(upload: ChunkedUpload, parts: Vec<UploadedPart>) -> ChunkedUploadResponse {
+    ChunkedUploadResponse {
+        id: upload.id,
+        session_id: upload.session_id,
+        position: upload.position,
+        content_type: upload.content_type,
+        size_bytes: upload.size_bytes,
+        min_part_bytes: MIN_PART_BYTES,
+        max_part_bytes: MAX_PART_BYTES,
+        parts,
+    }
+}
+
+/// The user's chunked upload, if its session still accepts photos.
+async fn open_chunked(
+    state: &AppState,
+    user_id: Uuid,
+    upload_id: Uuid,
+) -> Result<(ChunkedUpload, UploadSession), UploadSessionError> {
+    let upload = repo::find_chunked(&state.db, user_id, upload_id)
+        .await?
+        .ok_or(UploadSessionError::UploadNotFound)?;
+    let session_id = upload.session_id.ok_or(UploadSessionError::Closed)?;
+    let session = open_session(state, user_id, session_id).await?;
+    Ok((upload, session))
+}
+
+/// Starts uploading a photo too large for one request to `position`, backed
+/// by a storage multipart upload.
+pub async fn start_chunked_upload(
+    state: &AppState,
+    user_id: Uuid,
+    session_id: Uuid,
+    position: i32,
+    input: &StartChunkedUploadRequest,
+) -> Result<ChunkedUploadResponse, UploadSessionError> {
+    check_position(position)?;
+    input
+        .validate(state.config.uploads.max_chunked_image_bytes)
+        .map_err(UploadSessionError::Invalid)?;
+    let format =
+        ImageFormat::from_content_type(&input.content_type).ok_or(ImageError::NotAnImage {
+            index: position as usize,
+        })?;
+    let session = open_session(state, user_id, session_id).await?;
+
+    let photo_id = Uuid::new_v4();
+    let key = object_key(user_id, session.meal_id, photo_id, format.extension());
+    let storage_upload_id = state
+        .storage
+        .create_multipart(&key, format.content_type())
+        .await
+        .map_err(anyhow::Error::from)?;
+    let new_upload = NewChunkedUpload {
+        session_id: session.id,
+        position,
+        photo_id,
+        s3_key: &key,
+        storage_upload_id: &storage_upload_id,
+        content_type: format.content_type(),
+        size_bytes: input.size_bytes,
+        taken_at: input.taken_at,
+    };
+    let upload = match repo::insert_chunked(&state.db, user_id, &new_upload).await {
+        Ok(upload) => upload,
+        Err(e) => {
+            if let Err(e) = state
+                .storage
+                .abort_multipart(&key, &storage_upload_id)
+                .await
+            {
+                warn!(error = %e, key = %key, "failed to abort multipart upload");
+            }
+            return Err(e.into());
+        }
+    };
+    info!(user_id = %user_id, session_id = %session.id, upload_id = %upload.id, "chunked upload started");
+    Ok(chunked_response(upload, Vec::new()))
+}
+
+/// The upload with the parts received so far, for a client resuming it.
+pub async fn get_chunked_upload(
+    state: &AppState,
+    user_id: Uuid,
+    upload_id: Uuid,
+) -> Result<ChunkedUploadResponse, UploadSessionError> {
+    let upload = repo::find_chunked(&state.db, user_id, upload_id)
+        .await?
+        .ok_or(UploadSessionError::UploadNotFound)?;
+    let parts = repo::list_parts(&state.db, upload.id).await?;
+    Ok(chunked_response(upload, parts))
+}
+
+/// Writes part `number` of the upload; sending a number again replaces the
+/// part, so failed parts can simply be retried.
+pub async fn append_part(
+    state: &AppState,
+    user_id: Uuid,
+    upload_id: Uuid,
+    number: i32,
+    bytes: Vec<u8>,
+) -> Result<UploadedPart, UploadSessionError> {
+    if !(1..=MAX_PARTS).contains(&number) {
+        return Err(UploadSessionError::Invalid(format!(
+            "Part number must be between 1 and {MAX_PARTS}"
+        )));
+    }
+    if bytes.is_empty() || bytes.len() > MAX_PART_BYTES {
+        return Err(UploadSessionError::Invalid(format!(
+            "Parts must hold 1 to {MAX_PART_BYTES} bytes"
+        )));
+    }
+    let (upload, _) = open_chunked(state, user_id, upload_id).await?;
+    if number == 1 {
+        check_format(&upload, &bytes)?;
+    }
+    let received: i64 = repo::list_parts(&state.db, upload.id)
+        .await?
+        .iter()
+        .filter(|part| part.number != number)
+        .map(|part| part.size_bytes)
+        .sum();
+    if received + bytes.len() as i64 > upload.size_bytes {
+        return Err(UploadSessionError::Invalid(format!(
+            "Parts exceed the declared size of {} bytes",
+            upload.size_bytes
+        )));
+    }
+
+    let size_bytes = bytes.len() as i64;
+    let etag = state
+        .storage
+        .upload_part(&upload.s3_key, &upload.storage_upload_id, number, bytes)
+        .await
+        .map_err(anyhow::Error::from)?;
+    let part = UploadedPart {
+        number,
+        size_bytes,
+        etag,
+    };
+    repo::upsert_part(&state.db, upload.id, &part).await?;
+    Ok(part)
+}
+
+/// The first part must start with an image of the declared format.
+fn check_format(upload: &ChunkedUpload, bytes: &[u8]) -> Result<(), ImageError> {
+    let index = upload.position as usize;
+    let format = sniff::detect(bytes).ok_or(ImageError::NotAnImage { index })?;
+    if !format.matches_content_type(&upload.content_type) {
+        return Err(ImageError::TypeMismatch {
+            index,
+            declared: upload.content_type.clone(),
+            detected: format.content_type(),
+        });
+    }
+    Ok(())
+}
+
+/// Parts must be numbered 1 to n without gaps, add up to `size_bytes`, and
+/// all but the last be at least [`MIN_PART_BYTES`].
+fn check_parts(parts: &[UploadedPart], size_bytes: i64) -> Result<(), String> {
+    for (expected, part) in (1..).zip(parts) {
+        if part.number != expected {
+            return Err(format!("Part {expected} is missing"));
+        }
+    }
+    let Some((_, leading)) = parts.split_last() else {
+        return Err("No parts uploaded".into());
+    };
+    if let Some(small) = leading
+        .iter()
+        .find(|part| part.size_bytes < MIN_PART_BYTES as i64)
+    {
+        return Err(format!(
+            "Part {} is smaller than {MIN_PART_BYTES} bytes; only the last part may be",
+            small.number
+        ));
+    }
+    let received: i64 = parts.iter().map(|part| part.size_bytes).sum();
+    if received != size_bytes {
+        return Err(format!("Received {received} of {size_bytes} bytes"));
+    }
+    Ok(())
+}
+
+/// SHA-256 of a stored object and its leading [`HEADER_BYTES`], also copying
+/// the object to `spool` when given.
+async fn digest_object(
+    state: &AppState,
+    key: &str,
+    spool: Option<&Path>,
+) -> anyhow::Result<(String, Vec<u8>)> {
+    let object = state
+        .storage
+        .get_object_stream(key)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("completed object {key} is missing"))?;
+    let mut file = match spool {
+        Some(path) => Some(tokio::fs::File::create(path).await?),
+        None => None,
+    };
+    let mut hasher = Sha256::new();
+    let mut header = Vec::new();
+    let mut body = object.body;
+    while let Some(chunk) = body.next().await {
+        let chunk = chunk?;
+        hasher.update(&chunk);
+        let room = HEADER_BYTES.saturating_sub(header.len());
+        header.extend_from_slice(&chunk[..room.min(chunk.len())]);
+        if let Some(file) = &mut file {
+            file.write_all(&chunk).await?;
+        }
+    }
+    if let Some(file) = &mut file {
+        file.flush().await?;
+    }
+    Ok((hex::encode(hasher.finalize()), header))
+}
+
+/// Width and height read from the start of an image, when known.
+fn dimensions(header: &[u8]) -> (Option<i32>, Option<i32>) {
+    sniff::detect(header)
+        .and_then(|format| sniff::dimensions(format, header))
+        .map(|(w, h)| (i32::try_from(w).ok(), i32::try_from(h).ok()))
+        .unwrap_or_default()
+}
+
+/// The photo row for a joined upload. A HEIC is converted to JPEG like a
+/// single upload when transcoding is on for the user, keeping the joined
+/// object as its original; other photos are the joined object itself.
+/// Returns the photo and the objects written for it.
+async fn joined_photo(
+    state: &AppState,
+    user_id: Uuid,
+    meal_id: Uuid,
+    upload: &ChunkedUpload,
+) -> anyhow::Result<(NewPhoto, Vec<String>)> {
+    let command = Some(upload.content_type.as_str())
+        .filter(|content_type| ImageFormat::Heic.matches_content_type(content_type))
+        .and_then(|_| heic_command(state, user_id));
+    // The HEIC goes through a temp file so it isn't held in memory.
+    let spool = command.map(|_| transcode::temp_path("heic"));
+    let digested = async {
+        let (hash, header) = digest_object(state, &upload.s3_key, spool.as_deref()).await?;
+        let jpeg = match (command, &spool) {
+            (Some(command), Some(path)) => transcode::heic_file_to_jpeg(command, path)
+                .await
+                .inspect_err(|e| warn!(error = %e, upload_id = %upload.id, "heic transcode failed; storing original"))
+                .ok(),
+            _ => None,
+        };
+        Ok::<_, anyhow::Error>((hash, header, jpeg))
+    }
+    .await;
+    if let Some(path) = &spool {
+        let _ = tokio::fs::remove_file(path).await;
+    }
+    let (hash, header, jpeg) = digested?;
+
+    let (width, height) = dimensions(&header);
+    let photo = NewPhoto {
+        id: upload.photo_id,
+        s3_key: upload.s3_key.clone(),
+        original_s3_key: None,
+        content_type: upload.content_type.clone(),
+        size_bytes: upload.size_bytes,
+        width,
+        height,
+        content_hash: hash,
+        taken_at: upload.taken_at,
+        // Large photos are not fingerprinted.
+        perceptual_hash: None,
+    };
+    let Some(jpeg) = jpeg else {
+        return Ok((photo, Vec::new()));
+    };
+    let format = ImageFormat::Jpeg;
+    let key = object_key(user_id, meal_id, upload.photo_id, format.extension());
+    let (width, height) = dimensions(&jpeg);
+    let size_bytes = jpeg.len() as i64;
+    state
+        .storage
+        .put_object(&key, jpeg, format.content_type())
+        .await?;
+    let photo = NewPhoto {
+        s3_key: key.clone(),
+        original_s3_key: Some(photo.s3_key),
+        content_type: format.content_type().to_string(),
+        size_bytes,
+        width,
+        height,
+        ..photo
+    };
+    Ok((photo, vec![key]))
+}
+
+/// Joins the parts into the photo at the upload's position, replacing what
+/// was there. HEIC photos are transcoded as single uploads are; others are
+/// stored as uploaded.
+pub async fn complete_chunked_upload(
+    state: &AppState,
+    user_id: Uuid,
+    upload_id: Uuid,
+) -> Result<SessionPhoto, UploadSessionError> {
+    let (upload, session) = open_chunked(state, user_id, upload_id).await?;
+    let parts = repo::list_parts(&state.db, upload.id).await?;
+    check_parts(&parts, upload.size_bytes).map_err(UploadSessionError::Invalid)?;
+    let completed: Vec<CompletedPart> = parts
+        .into_iter()
+        .map(|part| CompletedPart {
+            number: part.number,
+            etag: part.etag,
+        })
+        .collect();
+    state
+        .storage
+        .complete_multipart(&upload.s3_key, &upload.storage_upload_id, &completed)
+        .await
+        .map_err(anyhow::Error::from)?;
+
+    let mut new_keys = vec![upload.s3_key.clone()];
+    let stored = async {
+        let (photo, written) = joined_photo(state, user_id, session.meal_id, &upload).await?;
+        new_keys.extend(written);
+        let mut tx = state.db.begin().await.map_err(anyhow::Error::from)?;
+        if repo::lock_open_session(&mut tx, user_id, session.id)
+            .await?
+            .is_none()
+        {
+            return Err(UploadSessionError::Closed);
+        }
+        let replaced = repo::delete_photo(&mut *tx, session.id, upload.position).await?;
+        let photo =
+            repo::insert_photo(&mut *tx, user_id, session.id, upload.position, &photo).await?;
+        repo::delete_chunked(&mut *tx, upload.id).await?;
+        tx.commit().await.map_err(anyhow::Error::from)?;
+        Ok((photo, replaced))
+    }
+    .await;
+    let (photo, replaced) = match stored {
+        Ok(stored) => stored,
+        Err(e) => {
+            // The parts are gone once joined, so the upload can't be retried.
+            discard_objects(&state.storage, &new_keys).await;
+            if let Err(e) = repo::delete_chunked(&state.db, upload.id).await {
+                warn!(error = %e, upload_id = %upload.id, "failed to forget chunked upload");
+            }
+            return Err(e);
+        }
+    };
+    if let Some((key, original_key)) = replaced {
+        if let Err(e) = release_objects(state, std::iter::once(key).chain(original_key)).await {
+            warn!(error = %e, session_id = %session.id, position = upload.position, "failed to release replaced photo");
+        }
+    }
+    info!(user_id = %user_id, session_id = %session.id, upload_id = %upload.id, "chunked upload completed");
+    Ok(photo)
+}
+
+/// Aborts chunked uploads whose session closed before they completed, so
+/// storage drops their parts. Returns how many were aborted.
+pub async fn abort_stale_uploads(state: &AppState) -> anyhow::Result<usize> {
+    let mut aborted = 0;
+    loop {
+        let uploads = repo::list_stale_chunked(&state.db, STALE_BATCH).await?;
+        if uploads.is_empty() {
+            return Ok(aborted);
+        }
+        for upload in uploads {
+            // A failed abort keeps the row, to be tried again next run.
+            state
+                .storage
+                .abort_multipart(&upload.s3_key, &upload.storage_upload_id)
+                .await?;
+            repo::delete_chunked(&state.db, upload.id).await?;
+            aborted += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn positions_are_bounded_by_the_photo_limit() {
+        assert!(check_position(0).is_ok());
+        assert!(check_position(MAX_IMAGES_PER_MEAL as i32 - 1).is_ok());
+        assert!(matches!(
+            check_position(MAX_IMAGES_PER_MEAL as i32),
+            Err(UploadSessionError::InvalidPosition)
+        ));
+        assert!(matches!(
+            check_position(-1),
+            Err(UploadSessionError::InvalidPosition)
+        ));
+    }
+
+    fn part(number: i32, size_bytes: usize) -> UploadedPart {
+        UploadedPart {
+            number,
+            size_bytes: size_bytes as i64,
+            etag: format!("etag-{number}"),
+        }
+    }
+
+    #[test]
+    fn parts_must_be_contiguous_and_complete() {
+        let size = (2 * MIN_PART_BYTES + 10) as i64;
+        let parts = [
+            part(1, MIN_PART_BYTES),
+            part(2, MIN_PART_BYTES),
+            part(3, 10),
+        ];
+        assert_eq!(check_parts(&parts, size), Ok(()));
+        assert_eq!(
+            check_parts(&[parts[0].clone(), parts[2].clone()], size),
+            Err("Part 2 is missing".to_string())
+        );
+        assert!(check_parts(&parts[..2], size)
+            .unwrap_err()
+            .starts_with("Received"));
+        assert_eq!(check_parts(&[], size), Err("No parts uploaded".to_string()));
+    }
+
+    #[test]
+    fn only_the_last_part_may_be_small() {
+        let parts = [part(1, 10), part(2, MIN_PART_BYTES)];
+        let err = check_parts(&parts, (MIN_PART_BYTES + 10) as i64).unwrap_err();
+        assert!(err.starts_with("Part 1 is smaller"), "{err}");
+    }
+
+    #[test]
+    fn image_errors_keep_their_status_and_code() {
+        let err = UploadSessionError::from(ImageError::TooLarge { index: 2, max: 8 });
+        assert_eq!(err.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(err.code(), ErrorCode::UploadTooLarge);
+        assert_eq!(err.to_string(), "Image 2: exceeds 8 bytes");
+    }
+}
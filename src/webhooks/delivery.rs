@@ -0,0 +1,265 @@
+//! Fans events out to user webhooks and delivers them. The subscriber only
+//! queues a delivery per matching webhook, so one slow or failing endpoint
+//! never holds up the outbox relay or another user's webhooks.
+
+use std::{
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+    time::Duration,
+};
+
+use futures::future::join_all;
+use hmac::{Hmac, Mac};
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use sha2::Sha256;
+use sqlx::PgPool;
+use time::OffsetDateTime;
+use tracing::{error, info, warn};
+
+use crate::{
+    db::AppState,
+    events::{EventSubscriber, OutboxEvent},
+    tasks::{BackgroundTasks, TaskContext},
+    webhooks::{
+        dto::{is_public, WEBHOOK_EVENTS},
+        repo::{self, DeliveryRow},
+    },
+};
+
+type HmacSha256 = Hmac<Sha256>;
+
+pub const SIGNATURE_HEADER: &str = "X-MealMind-Signature";
+pub const EVENT_HEADER: &str = "X-MealMind-Event";
+pub const DELIVERY_HEADER: &str = "X-MealMind-Delivery";
+
+/// Deliveries claimed per round; they are sent concurrently.
+const BATCH_SIZE: i64 = 50;
+const IDLE_INTERVAL: Duration = Duration::from_secs(1);
+/// A worker holding deliveries longer than this is assumed dead.
+const LOCK_TIMEOUT_SECS: i64 = 300;
+
+/// Queues deliveries of the events webhooks can subscribe to.
+pub struct WebhookSubscriber {
+    db: PgPool,
+}
+
+impl WebhookSubscriber {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+}
+
+#[axum::async_trait]
+impl EventSubscriber for WebhookSubscriber {
+    fn name(&self) -> &'static str {
+        "webhooks"
+    }
+
+    async fn handle(&self, event: &OutboxEvent) -> anyhow::Result<()> {
+        let event_type = event.event.name();
+        if !WEBHOOK_EVENTS.contains(&event_type) {
+            return Ok(());
+        }
+        let payload = serde_json::to_value(event)?;
+        repo::enqueue_deliveries(&self.db, event.user_id, event.id, event_type, &payload).await?;
+        Ok(())
+    }
+}
+
+/// `t=<unix seconds>,v1=<hex HMAC-SHA256 of "<t>.<body>">`. Receivers
+/// recompute the HMAC with their secret and reject stale timestamps.
+pub fn signature(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    // HMAC accepts keys of any length.
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).expect("any key length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    format!(
+        "t={},v1={}",
+        timestamp,
+        hex::encode(mac.finalize().into_bytes())
+    )
+}
+
+/// Resolves webhook hosts and refuses any that point at an address that
+/// isn't publicly routable. Registration can only check literal addresses;
+/// this also catches names resolving to internal services, including ones
+/// rebound after they were registered.
+struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let host = name.as_str();
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host, 0)).await?.collect();
+            if let Some(addr) = addrs.iter().find(|addr| !is_public(addr.ip())) {
+                return Err(
+                    format!("{} resolves to non-public address {}", host, addr.ip()).into(),
+                );
+            }
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+pub fn register(tasks: &mut BackgroundTasks, state: &AppState) -> anyhow::Result<()> {
+    let mut http = reqwest::Client::builder()
+        .timeout(Duration::from_secs(state.config.webhooks.timeout_secs))
+        // A redirect would bypass the URL checks done on registration.
+        .redirect(reqwest::redirect::Policy::none());
+    if !state.config.webhooks.allow_insecure {
+        http = http.dns_resolver(Arc::new(PublicResolver));
+    }
+    let http = http.build()?;
+    let state = state.clone();
+    info!("webhook delivery started");
+    tasks.spawn("webhook-delivery", move |ctx| {
+        run_delivery(state, http, ctx)
+    });
+    Ok(())
+}
+
+async fn run_delivery(state: AppState, http: reqwest::Client, mut ctx: TaskContext) {
+    loop {
+        match deliver_batch(&state, &http).await {
+            Ok(claimed) if claimed as i64 == BATCH_SIZE => {
+                ctx.succeeded();
+                if ctx.is_shutting_down() {
+                    break;
+                }
+                continue;
+            }
+            Ok(_) => ctx.succeeded(),
+            Err(e) => {
+                error!(error = %e, "delivering webhooks failed");
+                ctx.failed(&e);
+            }
+        }
+        if !ctx.sleep(IDLE_INTERVAL).await {
+            break;
+        }
+    }
+}
+
+async fn deliver_batch(state: &AppState, http: &reqwest::Client) -> anyhow::Result<usize> {
+    let rows = repo::claim_deliveries(&state.db, BATCH_SIZE, LOCK_TIMEOUT_SECS).await?;
+    let claimed = rows.len();
+    let max_attempts = state.config.webhooks.max_attempts;
+    let allow_insecure = state.config.webhooks.allow_insecure;
+    let results = join_all(rows.into_iter().map(|row| async move {
+        let (id, attempts) = (row.id, row.attempts);
+        match send(http, &row, allow_insecure).await {
+            Ok(code) => repo::mark_delivered(&state.db, id, code).await,
+            Err((code, e)) => {
+                let retry_at = (attempts < max_attempts)
+                    .then(|| OffsetDateTime::now_utc() + retry_delay(attempts));
+                warn!(
+                    delivery_id = %id,
+                    attempts,
+                    status = ?code,
+                    error = %e,
+                    gave_up = retry_at.is_none(),
+                    "webhook delivery failed"
+                );
+                repo::mark_failed(&state.db, id, code, &e, retry_at).await
+            }
+        }
+    }))
+    .await;
+    results.into_iter().collect::<anyhow::Result<Vec<()>>>()?;
+    Ok(claimed)
+}
+
+/// Posts the delivery, returning the status code on a 2xx answer and the
+/// status code (if any) with an error message otherwise.
+async fn send(
+    http: &reqwest::Client,
+    row: &DeliveryRow,
+    allow_insecure: bool,
+) -> Result<i16, (Option<i16>, String)> {
+    if let Some(ip) = literal_ip(&row.url).filter(|ip| !allow_insecure && !is_public(*ip)) {
+        return Err((None, format!("{} is not a public address", ip)));
+    }
+    let body = serde_json::to_vec(&row.payload).map_err(|e| (None, e.to_string()))?;
+    let timestamp = OffsetDateTime::now_utc().unix_timestamp();
+    let response = http
+        .post(&row.url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .header(SIGNATURE_HEADER, signature(&row.secret, timestamp, &body))
+        .header(EVENT_HEADER, &row.event_type)
+        .header(DELIVERY_HEADER, row.id.to_string())
+        .body(body)
+        .send()
+        .await
+        .map_err(|e| (None, format!("{:#}", e)))?;
+    let status = response.status();
+    let code = status.as_u16() as i16;
+    if status.is_success() {
+        Ok(code)
+    } else {
+        Err((Some(code), format!("HTTP {}", status)))
+    }
+}
+
+/// The host of `url` if it is an IP address. Those never reach
+/// [`PublicResolver`], so [`send`] checks them itself.
+fn literal_ip(url: &str) -> Option<IpAddr> {
+    let url = reqwest::Url::parse(url).ok()?;
+    url.host_str()?.trim_matches(['[', ']']).parse().ok()
+}
+
+/// `30s * 2^(attempts - 1)`, capped at one hour; with the default eight
+/// attempts a delivery is retried for about an hour before it's given up on.
+fn retry_delay(attempts: i32) -> Duration {
+    let exp = attempts.saturating_sub(1).clamp(0, 16) as u32;
+    Duration::from_secs((30u64 << exp).min(3600))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signature_covers_timestamp_and_body() {
+        let body = br#"{"type":"meal_created"}"#;
+        let sig = signature("whsec_test", 1700000000, body);
+
+        let (t, v1) = sig.split_once(',').unwrap();
+        assert_eq!(t, "t=1700000000");
+        let mut mac = HmacSha256::new_from_slice(b"whsec_test").unwrap();
+        mac.update(b"1700000000.");
+        mac.update(body);
+        mac.verify_slice(&hex::decode(v1.strip_prefix("v1=").unwrap()).unwrap())
+            .unwrap();
+
+        assert_ne!(sig, signature("whsec_test", 1700000001, body));
+        assert_ne!(sig, signature("whsec_other", 1700000000, body));
+    }
+
+    #[tokio::test]
+    async fn resolver_rejects_non_public_hosts() {
+        let resolve = |host: &str| PublicResolver.resolve(host.parse().unwrap());
+        assert!(resolve("localhost").await.is_err());
+        assert!(resolve("169.254.169.254").await.is_err());
+        assert!(resolve("::ffff:10.0.0.1").await.is_err());
+        let addrs: Vec<_> = resolve("93.184.216.34").await.unwrap().collect();
+        assert_eq!(addrs, ["93.184.216.34:0".parse().unwrap()]);
+    }
+
+    #[test]
+    fn finds_literal_hosts() {
+        let mapped: IpAddr = "::ffff:169.254.169.254".parse().unwrap();
+        assert_eq!(
+            literal_ip("https://[::ffff:169.254.169.254]/hook"),
+            Some(mapped)
+        );
+        assert_eq!(literal_ip("https://example.com/hook"), None);
+    }
+
+    #[test]
+    fn retry_delay_doubles_and_caps() {
+        assert_eq!(retry_delay(1), Duration::from_secs(30));
+        assert_eq!(retry_delay(4), Duration::from_secs(240));
+        assert_eq!(retry_delay(20), Duration::from_secs(3600));
+    }
+}
@@ -0,0 +1,235 @@
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+pub const MAX_WEBHOOKS_PER_USER: i64 = 10;
+
+/// Event types a webhook can subscribe to.
+pub const WEBHOOK_EVENTS: [&str; 3] = ["meal_created", "nutrition_ready", "meal_reaction"];
+
+#[derive(Debug, Deserialize)]
+pub struct CreateWebhookRequest {
+    pub url: String,
+    /// Subset of [`WEBHOOK_EVENTS`]; all of them when omitted or empty.
+    #[serde(default)]
+    pub events: Vec<String>,
+}
+
+impl CreateWebhookRequest {
+    /// Checks the URL and normalizes `events` (deduplicated, all when empty).
+    /// Plain `http` and local or private addresses need `allow_insecure`.
+    pub fn validate(&mut self, allow_insecure: bool) -> Result<(), String> {
+        let url = reqwest::Url::parse(self.url.trim()).map_err(|_| "url is not a valid URL")?;
+        let secure = match url.scheme() {
+            "https" => true,
+            "http" => false,
+            _ => return Err("url must use http or https".into()),
+        };
+        let Some(host) = url.host_str() else {
+            return Err("url must have a host".into());
+        };
+        if !allow_insecure && (!secure || is_local(host)) {
+            return Err("url must use https and a public host".into());
+        }
+        self.url = url.to_string();
+
+        if self.events.is_empty() {
+            self.events = WEBHOOK_EVENTS.iter().map(|e| e.to_string()).collect();
+        }
+        if let Some(unknown) = self
+            .events
+            .iter()
+            .find(|e| !WEBHOOK_EVENTS.contains(&e.as_str()))
+        {
+            return Err(format!(
+                "unknown event {:?}; expected one of {}",
+                unknown,
+                WEBHOOK_EVENTS.join(", ")
+            ));
+        }
+        self.events.sort();
+        self.events.dedup();
+        Ok(())
+    }
+}
+
+/// `localhost` and IP addresses that aren't publicly routable. Other host
+/// names are checked when deliveries resolve them.
+fn is_local(host: &str) -> bool {
+    if host.eq_ignore_ascii_case("localhost") || host.ends_with(".localhost") {
+        return true;
+    }
+    match host.trim_matches(['[', ']']).parse::<IpAddr>() {
+        Ok(ip) => !is_public(ip),
+        Err(_) => false,
+    }
+}
+
+/// Whether `ip` is globally routable, i.e. not loopback, private, CGNAT,
+/// link-local (cloud metadata), multicast, documentation or otherwise
+/// reserved. IPv4-mapped IPv6 addresses are judged by their IPv4 address.
+pub fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_v4(ip),
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(v4) => is_public_v4(v4),
+            None => is_public_v6(ip),
+        },
+    }
+}
+
+fn is_public_v4(ip: Ipv4Addr) -> bool {
+    let [a, b, c, _] = ip.octets();
+    !(a == 0
+        || a == 10
+        || a == 127
+        || a >= 224
+        // CGNAT (100.64.0.0/10).
+        || (a == 100 && (b & 0xc0) == 64)
+        || (a == 169 && b == 254)
+        || (a == 172 && (b & 0xf0) == 16)
+        || (a == 192 && b == 168)
+        // IETF protocol assignments and documentation ranges.
+        || (a == 192 && b == 0 && (c == 0 || c == 2))
+        || (a == 198 && b == 51 && c == 100)
+        || (a == 203 && b == 0 && c == 113)
+        // Benchmarking (198.18.0.0/15).
+        || (a == 198 && (b & 0xfe) == 18))
+}
+
+/// Only global unicast (2000::/3) counts, minus ranges that are reserved or
+/// tunnel to IPv4 addresses that can't be checked here.
+fn is_public_v6(ip: Ipv6Addr) -> bool {
+    let [first, second, ..] = ip.segments();
+    (first & 0xe000) == 0x2000
+        // IETF protocol assignments and Teredo (2001::/23).
+        && !(first == 0x2001 && second < 0x0200)
+        // Documentation (2001:db8::/32).
+        && !(first == 0x2001 && second == 0x0db8)
+        // 6to4 (2002::/16).
+        && first != 0x2002
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct Webhook {
+    pub id: Uuid,
+    pub url: String,
+    pub secret: String,
+    pub events: Vec<String>,
+    pub created_at: OffsetDateTime,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WebhookResponse {
+    pub id: Uuid,
+    pub url: String,
+    pub events: Vec<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    /// Signing secret; only returned when the webhook is created.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+}
+
+impl WebhookResponse {
+    pub fn new(webhook: Webhook, with_secret: bool) -> Self {
+        Self {
+            id: webhook.id,
+            url: webhook.url,
+            events: webhook.events,
+            created_at: webhook.created_at,
+            secret: with_secret.then_some(webhook.secret),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(url: &str, events: &[&str]) -> CreateWebhookRequest {
+        CreateWebhookRequest {
+            url: url.into(),
+            events: events.iter().map(|e| e.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn validate_normalizes_events() {
+        let mut all = request("https://example.com/hook", &[]);
+        all.validate(false).unwrap();
+        assert_eq!(
+            all.events,
+            vec!["meal_created", "meal_reaction", "nutrition_ready"]
+        );
+
+        let mut one = request(
+            "https://example.com/hook",
+            &["meal_created", "meal_created"],
+        );
+        one.validate(false).unwrap();
+        assert_eq!(one.events, vec!["meal_created"]);
+
+        let mut unknown = request("https://example.com/hook", &["meal_deleted"]);
+        assert!(unknown.validate(false).is_err());
+    }
+
+    #[test]
+    fn validate_rejects_insecure_urls_unless_allowed() {
+        for url in [
+            "http://example.com/hook",
+            "https://localhost/hook",
+            "https://127.0.0.1/hook",
+            "https://10.1.2.3/hook",
+            "https://[::1]/hook",
+            "https://[fd00::1]/hook",
+            "https://[::ffff:169.254.169.254]/hook",
+            "https://[::ffff:10.0.0.1]/hook",
+            "https://100.64.0.1/hook",
+            "https://169.254.169.254/latest/meta-data",
+            "https://[fe80::1]/hook",
+            "https://[2001:db8::1]/hook",
+            "ftp://example.com/hook",
+            "not a url",
+        ] {
+            assert!(request(url, &[]).validate(false).is_err(), "{url}");
+        }
+        assert!(request("http://localhost:9000/hook", &[])
+            .validate(true)
+            .is_ok());
+        assert!(request("https://93.184.216.34/hook", &[])
+            .validate(false)
+            .is_ok());
+    }
+
+    #[test]
+    fn only_global_addresses_are_public() {
+        for ip in [
+            "93.184.216.34",
+            "100.128.0.1",
+            "2606:4700::1111",
+            "::ffff:93.184.216.34",
+        ] {
+            assert!(is_public(ip.parse().unwrap()), "{ip}");
+        }
+        for ip in [
+            "0.0.0.0",
+            "100.127.255.255",
+            "172.31.0.1",
+            "192.0.2.1",
+            "198.19.0.1",
+            "224.0.0.1",
+            "255.255.255.255",
+            "::",
+            "::1",
+            "::ffff:127.0.0.1",
+            "fc00::1",
+            "ff02::1",
+            "2002:a00:1::1",
+        ] {
+            assert!(!is_public(ip.parse().unwrap()), "{ip}");
+        }
+    }
+}
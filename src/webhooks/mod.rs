@@ -0,0 +1,4 @@
+pub mod delivery;
+pub mod dto;
+pub mod repo;
+pub mod services;
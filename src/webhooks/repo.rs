@@ -0,0 +1,186 @@
+use sqlx::{FromRow, PgExecutor, PgPool};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::webhooks::dto::Webhook;
+
+/// A claimed delivery with what's needed to send it.
+#[derive(Debug, Clone, FromRow)]
+pub struct DeliveryRow {
+    pub id: Uuid,
+    pub event_type: String,
+    pub payload: serde_json::Value,
+    pub attempts: i32,
+    pub url: String,
+    pub secret: String,
+}
+
+/// Inserts the webhook unless the user already has `max` of them, in which
+/// case it returns `None`.
+pub async fn create(
+    db: impl PgExecutor<'_>,
+    user_id: Uuid,
+    url: &str,
+    secret: &str,
+    events: &[String],
+    max: i64,
+) -> anyhow::Result<Option<Webhook>> {
+    let webhook = sqlx::query_as::<_, Webhook>(
+        r#"
+        INSERT INTO webhooks (user_id, url, secret, events)
+        SELECT $1, $2, $3, $4
+        WHERE (SELECT COUNT(*) FROM webhooks WHERE user_id = $1) < $5
+        RETURNING id, url, secret, events, created_at
+        "#,
+    )
+    .bind(user_id)
+    .bind(url)
+    .bind(secret)
+    .bind(events)
+    .bind(max)
+    .fetch_optional(db)
+    .await?;
+    Ok(webhook)
+}
+
+pub async fn list_by_user(db: impl PgExecutor<'_>, user_id: Uuid) -> anyhow::Result<Vec<Webhook>> {
+    let webhooks = sqlx::query_as::<_, Webhook>(
+        r#"
+        SELECT id, url, secret, events, created_at
+        FROM webhooks
+        WHERE user_id = $1
+        ORDER BY created_at
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(db)
+    .await?;
+    Ok(webhooks)
+}
+
+/// Deletes the webhook with its pending deliveries; false if the user has no
+/// such webhook.
+pub async fn delete(db: impl PgExecutor<'_>, user_id: Uuid, id: Uuid) -> anyhow::Result<bool> {
+    let result = sqlx::query("DELETE FROM webhooks WHERE id = $1 AND user_id = $2")
+        .bind(id)
+        .bind(user_id)
+        .execute(db)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Queues a delivery of the event to each of the user's webhooks subscribed
+/// to it. Queuing the same event again is a no-op.
+pub async fn enqueue_deliveries(
+    db: &PgPool,
+    user_id: Uuid,
+    event_id: i64,
+    event_type: &str,
+    payload: &serde_json::Value,
+) -> anyhow::Result<u64> {
+    let result = sqlx::query(
+        r#"
+        INSERT INTO webhook_deliveries (webhook_id, event_id, event_type, payload)
+        SELECT id, $2, $3, $4
+        FROM webhooks
+        WHERE user_id = $1 AND $3 = ANY(events)
+        ON CONFLICT (webhook_id, event_id) DO NOTHING
+        "#,
+    )
+    .bind(user_id)
+    .bind(event_id)
+    .bind(event_type)
+    .bind(payload)
+    .execute(db)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+/// Locks up to `limit` due deliveries, like the outbox relay does for events.
+pub async fn claim_deliveries(
+    db: &PgPool,
+    limit: i64,
+    lock_timeout_secs: i64,
+) -> anyhow::Result<Vec<DeliveryRow>> {
+    let rows = sqlx::query_as::<_, DeliveryRow>(
+        r#"
+        UPDATE webhook_deliveries d
+        SET locked_at = NOW(), attempts = d.attempts + 1
+        FROM webhooks w
+        WHERE w.id = d.webhook_id
+          AND d.id IN (
+            SELECT id FROM webhook_deliveries
+            WHERE status = 'pending'
+              AND next_attempt_at <= NOW()
+              AND (locked_at IS NULL OR locked_at < NOW() - make_interval(secs => $2))
+            ORDER BY next_attempt_at
+            FOR UPDATE SKIP LOCKED
+            LIMIT $1
+          )
+        RETURNING d.id, d.event_type, d.payload, d.attempts, w.url, w.secret
+        "#,
+    )
+    .bind(limit)
+    .bind(lock_timeout_secs as f64)
+    .fetch_all(db)
+    .await?;
+    Ok(rows)
+}
+
+pub async fn mark_delivered(db: &PgPool, id: Uuid, status_code: i16) -> anyhow::Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE webhook_deliveries
+        SET status = 'delivered', locked_at = NULL, last_status_code = $2,
+            last_error = NULL, finished_at = NOW()
+        WHERE id = $1
+        "#,
+    )
+    .bind(id)
+    .bind(status_code)
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+/// Schedules another attempt at `retry_at`, or gives up on the delivery when
+/// that is `None`.
+pub async fn mark_failed(
+    db: &PgPool,
+    id: Uuid,
+    status_code: Option<i16>,
+    error: &str,
+    retry_at: Option<OffsetDateTime>,
+) -> anyhow::Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE webhook_deliveries
+        SET locked_at = NULL, last_status_code = $2, last_error = $3,
+            status = CASE WHEN $4::timestamptz IS NULL THEN 'failed' ELSE 'pending' END,
+            next_attempt_at = COALESCE($4, next_attempt_at),
+            finished_at = CASE WHEN $4::timestamptz IS NULL THEN NOW() END
+        WHERE id = $1
+        "#,
+    )
+    .bind(id)
+    .bind(status_code)
+    .bind(error)
+    .bind(retry_at)
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+/// Deletes deliveries that finished more than `retention_days` ago.
+pub async fn prune_finished(db: &PgPool, retention_days: i32) -> anyhow::Result<u64> {
+    let result = sqlx::query(
+        r#"
+        DELETE FROM webhook_deliveries
+        WHERE finished_at < NOW() - make_interval(days => $1)
+        "#,
+    )
+    .bind(retention_days)
+    .execute(db)
+    .await?;
+    Ok(result.rows_affected())
+}
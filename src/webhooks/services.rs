@@ -0,0 +1,104 @@
+use axum::http::StatusCode;
+use rand_core::{OsRng, RngCore};
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    db::AppState,
+    error::ErrorCode,
+    webhooks::{
+        dto::{CreateWebhookRequest, WebhookResponse, MAX_WEBHOOKS_PER_USER},
+        repo,
+    },
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum WebhookError {
+    #[error("Webhook not found")]
+    NotFound,
+    #[error("{0}")]
+    Invalid(String),
+    #[error("At most {MAX_WEBHOOKS_PER_USER} webhooks per user")]
+    LimitReached,
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+impl WebhookError {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            WebhookError::NotFound => StatusCode::NOT_FOUND,
+            WebhookError::Invalid(_) => StatusCode::BAD_REQUEST,
+            WebhookError::LimitReached => StatusCode::CONFLICT,
+            WebhookError::Other(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            WebhookError::NotFound => ErrorCode::WebhookNotFound,
+            WebhookError::Invalid(_) => ErrorCode::ValidationFailed,
+            WebhookError::LimitReached => ErrorCode::WebhookLimitReached,
+            WebhookError::Other(_) => ErrorCode::Internal,
+        }
+    }
+}
+
+/// `whsec_` followed by 32 random bytes in hex.
+fn generate_secret() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    format!("whsec_{}", hex::encode(bytes))
+}
+
+/// Registers a webhook; the response is the only one carrying its secret.
+pub async fn create_webhook(
+    state: &AppState,
+    user_id: Uuid,
+    mut input: CreateWebhookRequest,
+) -> Result<WebhookResponse, WebhookError> {
+    input
+        .validate(state.config.webhooks.allow_insecure)
+        .map_err(WebhookError::Invalid)?;
+    let mut tx = state.begin_as(user_id).await.map_err(anyhow::Error::from)?;
+    let webhook = repo::create(
+        &mut *tx,
+        user_id,
+        &input.url,
+        &generate_secret(),
+        &input.events,
+        MAX_WEBHOOKS_PER_USER,
+    )
+    .await?
+    .ok_or(WebhookError::LimitReached)?;
+    tx.commit().await.map_err(anyhow::Error::from)?;
+    info!(user_id = %user_id, webhook_id = %webhook.id, "webhook created");
+    Ok(WebhookResponse::new(webhook, true))
+}
+
+pub async fn list_webhooks(
+    state: &AppState,
+    user_id: Uuid,
+) -> Result<Vec<WebhookResponse>, WebhookError> {
+    let mut tx = state.begin_as(user_id).await.map_err(anyhow::Error::from)?;
+    let webhooks = repo::list_by_user(&mut *tx, user_id).await?;
+    tx.commit().await.map_err(anyhow::Error::from)?;
+    Ok(webhooks
+        .into_iter()
+        .map(|w| WebhookResponse::new(w, false))
+        .collect())
+}
+
+pub async fn delete_webhook(
+    state: &AppState,
+    user_id: Uuid,
+    webhook_id: Uuid,
+) -> Result<(), WebhookError> {
+    let mut tx = state.begin_as(user_id).await.map_err(anyhow::Error::from)?;
+    if !repo::delete(&mut *tx, user_id, webhook_id).await? {
+        return Err(WebhookError::NotFound);
+    }
+    tx.commit().await.map_err(anyhow::Error::from)?;
+    info!(user_id = %user_id, webhook_id = %webhook_id, "webhook deleted");
+    Ok(())
+}
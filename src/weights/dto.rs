@@ -0,0 +1,147 @@
+use serde::{Deserialize, Serialize};
+use time::{Date, Duration, OffsetDateTime};
+use uuid::Uuid;
+
+pub const MIN_WEIGHT_KG: f64 = 20.0;
+pub const MAX_WEIGHT_KG: f64 = 500.0;
+pub const MAX_NOTE_LEN: usize = 500;
+/// Range returned when `from` is omitted.
+pub const DEFAULT_RANGE_DAYS: i64 = 90;
+/// Days averaged by `rolling_avg_kg`.
+pub const ROLLING_WINDOW_DAYS: i64 = 7;
+
+time::serde::format_description!(iso_date, Date, "[year]-[month]-[day]");
+
+#[derive(Debug, Deserialize)]
+pub struct NewWeightRequest {
+    pub weight_kg: f64,
+    /// Defaults to now.
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub measured_at: Option<OffsetDateTime>,
+    pub note: Option<String>,
+}
+
+impl NewWeightRequest {
+    /// Trims the note and checks ranges; weigh-ins can't be in the future.
+    pub fn normalized(self) -> Result<Self, String> {
+        if !self.weight_kg.is_finite() || !(MIN_WEIGHT_KG..=MAX_WEIGHT_KG).contains(&self.weight_kg)
+        {
+            return Err(format!(
+                "weight_kg must be between {} and {}",
+                MIN_WEIGHT_KG, MAX_WEIGHT_KG
+            ));
+        }
+        if self
+            .measured_at
+            .is_some_and(|at| at > OffsetDateTime::now_utc() + Duration::minutes(5))
+        {
+            return Err("measured_at can't be in the future".into());
+        }
+        let note = self
+            .note
+            .map(|n| n.trim().to_string())
+            .filter(|n| !n.is_empty());
+        if note
+            .as_ref()
+            .is_some_and(|n| n.chars().count() > MAX_NOTE_LEN)
+        {
+            return Err(format!("note must be at most {} characters", MAX_NOTE_LEN));
+        }
+        Ok(Self { note, ..self })
+    }
+}
+
+/// A weigh-in pulled from a connected service.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedWeight {
+    /// The service's id of the weigh-in.
+    pub external_id: String,
+    pub weight_kg: f64,
+    pub measured_at: OffsetDateTime,
+}
+
+impl ImportedWeight {
+    pub fn is_valid(&self) -> bool {
+        self.weight_kg.is_finite() && (MIN_WEIGHT_KG..=MAX_WEIGHT_KG).contains(&self.weight_kg)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListWeightsQuery {
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub from: Option<OffsetDateTime>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub to: Option<OffsetDateTime>,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct WeightEntry {
+    pub id: Uuid,
+    pub weight_kg: f64,
+    #[serde(with = "time::serde::rfc3339")]
+    pub measured_at: OffsetDateTime,
+    pub note: Option<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+/// One UTC day with at least one weigh-in.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WeightDay {
+    #[serde(with = "iso_date")]
+    pub date: Date,
+    /// Mean of the day's weigh-ins.
+    pub weight_kg: f64,
+    /// Mean of the daily weights within the trailing window.
+    pub rolling_avg_kg: f64,
+    /// Calories logged that day, for correlating intake with weight.
+    pub calories_kcal: Option<f64>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct WeightTrend {
+    pub rolling_window_days: i64,
+    pub days: Vec<WeightDay>,
+    /// Last minus first rolling average.
+    pub change_kg: Option<f64>,
+    /// Least-squares slope of daily weights, per week.
+    pub weekly_rate_kg: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WeightsResponse {
+    pub entries: Vec<WeightEntry>,
+    pub trend: WeightTrend,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(weight_kg: f64) -> NewWeightRequest {
+        NewWeightRequest {
+            weight_kg,
+            measured_at: None,
+            note: Some("  after run ".into()),
+        }
+    }
+
+    #[test]
+    fn normalized_trims_note_and_checks_range() {
+        assert_eq!(
+            request(72.5).normalized().unwrap().note.as_deref(),
+            Some("after run")
+        );
+        assert!(request(5.0).normalized().is_err());
+        assert!(request(f64::NAN).normalized().is_err());
+    }
+
+    #[test]
+    fn normalized_rejects_future_weigh_ins() {
+        let req = NewWeightRequest {
+            measured_at: Some(OffsetDateTime::now_utc() + Duration::days(1)),
+            ..request(70.0)
+        };
+        assert!(req.normalized().is_err());
+    }
+}
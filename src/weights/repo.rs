@@ -0,0 +1,125 @@
+use std::collections::HashMap;
+
+use sqlx::PgExecutor;
+use time::{Date, OffsetDateTime};
+use uuid::Uuid;
+
+use crate::{
+    integrations::Provider,
+    weights::dto::{ImportedWeight, NewWeightRequest, WeightEntry},
+};
+
+pub async fn insert_weight(
+    db: impl PgExecutor<'_>,
+    user_id: Uuid,
+    input: &NewWeightRequest,
+) -> anyhow::Result<WeightEntry> {
+    let entry = sqlx::query_as::<_, WeightEntry>(
+        r#"
+        INSERT INTO weights (user_id, weight_kg, measured_at, note)
+        VALUES ($1, $2, COALESCE($3, NOW()), $4)
+        RETURNING id, weight_kg::float8 AS weight_kg, measured_at, note, created_at
+        "#,
+    )
+    .bind(user_id)
+    .bind(input.weight_kg)
+    .bind(input.measured_at)
+    .bind(&input.note)
+    .fetch_one(db)
+    .await?;
+    Ok(entry)
+}
+
+/// Inserts a pulled weigh-in, or updates the one pulled before with the same
+/// id.
+pub async fn upsert_imported_weight(
+    db: impl PgExecutor<'_>,
+    user_id: Uuid,
+    source: Provider,
+    weight: &ImportedWeight,
+) -> anyhow::Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO weights (user_id, weight_kg, measured_at, source, external_id)
+        VALUES ($1, $2, $3, $4, $5)
+        ON CONFLICT (user_id, source, external_id) DO UPDATE
+        SET weight_kg = EXCLUDED.weight_kg,
+            measured_at = EXCLUDED.measured_at
+        "#,
+    )
+    .bind(user_id)
+    .bind(weight.weight_kg)
+    .bind(weight.measured_at)
+    .bind(source)
+    .bind(&weight.external_id)
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+/// Weigh-ins from `from` up to (excluding) `to`, oldest first.
+pub async fn list_weights(
+    db: impl PgExecutor<'_>,
+    user_id: Uuid,
+    from: OffsetDateTime,
+    to: Option<OffsetDateTime>,
+) -> anyhow::Result<Vec<WeightEntry>> {
+    let entries = sqlx::query_as::<_, WeightEntry>(
+        r#"
+        SELECT id, weight_kg::float8 AS weight_kg, measured_at, note, created_at
+        FROM weights
+        WHERE user_id = $1
+          AND measured_at >= $2
+          AND ($3::timestamptz IS NULL OR measured_at < $3)
+        ORDER BY measured_at, id
+        "#,
+    )
+    .bind(user_id)
+    .bind(from)
+    .bind(to)
+    .fetch_all(db)
+    .await?;
+    Ok(entries)
+}
+
+/// Logged calories per UTC day of the meals between `from` and `to`; days
+/// without nutrition are absent.
+pub async fn daily_calories(
+    db: impl PgExecutor<'_>,
+    user_id: Uuid,
+    from: OffsetDateTime,
+    to: Option<OffsetDateTime>,
+) -> anyhow::Result<HashMap<Date, f64>> {
+    let rows = sqlx::query_as::<_, (Date, f64)>(
+        r#"
+        SELECT date_trunc('day', m.created_at AT TIME ZONE 'UTC')::date AS day,
+               SUM(n.total_calories_kcal)::float8 AS calories_kcal
+        FROM meals m
+        JOIN meal_nutrition n ON n.meal_id = m.id
+        WHERE m.user_id = $1
+          AND m.created_at >= $2
+          AND ($3::timestamptz IS NULL OR m.created_at < $3)
+          AND n.total_calories_kcal IS NOT NULL
+        GROUP BY 1
+        "#,
+    )
+    .bind(user_id)
+    .bind(from)
+    .bind(to)
+    .fetch_all(db)
+    .await?;
+    Ok(rows.into_iter().collect())
+}
+
+pub async fn delete_weight(
+    db: impl PgExecutor<'_>,
+    user_id: Uuid,
+    weight_id: Uuid,
+) -> anyhow::Result<bool> {
+    let result = sqlx::query(r#"DELETE FROM weights WHERE id = $1 AND user_id = $2"#)
+        .bind(weight_id)
+        .bind(user_id)
+        .execute(db)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
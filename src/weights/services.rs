@@ -0,0 +1,190 @@
+use std::collections::{BTreeMap, HashMap};
+
+use time::{Date, Duration, OffsetDateTime};
+use uuid::Uuid;
+
+use crate::{
+    db::AppState,
+    weights::{
+        dto::{
+            ListWeightsQuery, NewWeightRequest, WeightDay, WeightEntry, WeightTrend,
+            WeightsResponse, DEFAULT_RANGE_DAYS, ROLLING_WINDOW_DAYS,
+        },
+        repo,
+    },
+};
+
+pub async fn create_weight(
+    state: &AppState,
+    user_id: Uuid,
+    input: &NewWeightRequest,
+) -> anyhow::Result<WeightEntry> {
+    let mut tx = state.begin_as(user_id).await?;
+    let entry = repo::insert_weight(&mut *tx, user_id, input).await?;
+    tx.commit().await?;
+    Ok(entry)
+}
+
+/// `false` if the user has no such weigh-in.
+pub async fn delete_weight(
+    state: &AppState,
+    user_id: Uuid,
+    weight_id: Uuid,
+) -> anyhow::Result<bool> {
+    let mut tx = state.begin_as(user_id).await?;
+    let deleted = repo::delete_weight(&mut *tx, user_id, weight_id).await?;
+    tx.commit().await?;
+    Ok(deleted)
+}
+
+/// Weigh-ins in the requested range (the last 90 days by default) with
+/// their trend.
+pub async fn list_weights(
+    state: &AppState,
+    user_id: Uuid,
+    query: &ListWeightsQuery,
+) -> anyhow::Result<WeightsResponse> {
+    let from = query.from.unwrap_or_else(|| {
+        query.to.unwrap_or_else(OffsetDateTime::now_utc) - Duration::days(DEFAULT_RANGE_DAYS)
+    });
+    let mut tx = state.begin_as(user_id).await?;
+    let entries = repo::list_weights(&mut *tx, user_id, from, query.to).await?;
+    let calories = repo::daily_calories(&mut *tx, user_id, from, query.to).await?;
+    tx.commit().await?;
+    let trend = trend(&entries, &calories);
+    Ok(WeightsResponse { entries, trend })
+}
+
+fn round2(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+/// Daily means, trailing rolling averages over calendar days, and the overall
+/// change and weekly rate. `entries` must be sorted by `measured_at`.
+pub fn trend(entries: &[WeightEntry], calories: &HashMap<Date, f64>) -> WeightTrend {
+    let mut by_day: BTreeMap<Date, (f64, u32)> = BTreeMap::new();
+    for entry in entries {
+        let slot = by_day
+            .entry(entry.measured_at.to_offset(time::UtcOffset::UTC).date())
+            .or_default();
+        slot.0 += entry.weight_kg;
+        slot.1 += 1;
+    }
+    let daily: Vec<(Date, f64)> = by_day
+        .into_iter()
+        .map(|(date, (sum, count))| (date, sum / count as f64))
+        .collect();
+
+    let days: Vec<WeightDay> = daily
+        .iter()
+        .enumerate()
+        .map(|(i, &(date, weight))| {
+            let window_start = date - Duration::days(ROLLING_WINDOW_DAYS - 1);
+            let window: Vec<f64> = daily[..=i]
+                .iter()
+                .filter(|(d, _)| *d >= window_start)
+                .map(|(_, w)| *w)
+                .collect();
+            WeightDay {
+                date,
+                weight_kg: round2(weight),
+                rolling_avg_kg: round2(window.iter().sum::<f64>() / window.len() as f64),
+                calories_kcal: calories.get(&date).copied(),
+            }
+        })
+        .collect();
+
+    let change_kg = match (days.first(), days.last()) {
+        (Some(first), Some(last)) if days.len() > 1 => {
+            Some(round2(last.rolling_avg_kg - first.rolling_avg_kg))
+        }
+        _ => None,
+    };
+    WeightTrend {
+        rolling_window_days: ROLLING_WINDOW_DAYS,
+        change_kg,
+        weekly_rate_kg: weekly_rate(&daily),
+        days,
+    }
+}
+
+/// Least-squares slope in kg per week; `None` with fewer than two days.
+fn weekly_rate(daily: &[(Date, f64)]) -> Option<f64> {
+    let (origin, _) = *daily.first()?;
+    if daily.len() < 2 {
+        return None;
+    }
+    let points: Vec<(f64, f64)> = daily
+        .iter()
+        .map(|(date, w)| ((*date - origin).whole_days() as f64, *w))
+        .collect();
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / n;
+    let covariance: f64 = points
+        .iter()
+        .map(|(x, y)| (x - mean_x) * (y - mean_y))
+        .sum();
+    let variance: f64 = points.iter().map(|(x, _)| (x - mean_x).powi(2)).sum();
+    Some(round2(covariance / variance * 7.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::datetime;
+
+    fn entry(measured_at: OffsetDateTime, weight_kg: f64) -> WeightEntry {
+        WeightEntry {
+            id: Uuid::new_v4(),
+            weight_kg,
+            measured_at,
+            note: None,
+            created_at: measured_at,
+        }
+    }
+
+    #[test]
+    fn empty_history_has_no_trend() {
+        let trend = trend(&[], &HashMap::new());
+        assert!(trend.days.is_empty());
+        assert_eq!(trend.change_kg, None);
+        assert_eq!(trend.weekly_rate_kg, None);
+    }
+
+    #[test]
+    fn same_day_weigh_ins_are_averaged() {
+        let entries = [
+            entry(datetime!(2024-03-01 07:00 UTC), 80.0),
+            entry(datetime!(2024-03-01 21:00 UTC), 81.0),
+        ];
+        let trend = trend(&entries, &HashMap::new());
+        assert_eq!(trend.days.len(), 1);
+        assert_eq!(trend.days[0].weight_kg, 80.5);
+    }
+
+    #[test]
+    fn rolling_average_only_covers_the_window() {
+        let entries = [
+            entry(datetime!(2024-03-01 07:00 UTC), 90.0),
+            entry(datetime!(2024-03-07 07:00 UTC), 80.0),
+            entry(datetime!(2024-03-08 07:00 UTC), 79.0),
+        ];
+        let calories = HashMap::from([(time::macros::date!(2024 - 03 - 08), 2100.0)]);
+        let trend = trend(&entries, &calories);
+        assert_eq!(trend.days[1].rolling_avg_kg, 85.0);
+        assert_eq!(trend.days[2].rolling_avg_kg, 79.5);
+        assert_eq!(trend.days[2].calories_kcal, Some(2100.0));
+        assert_eq!(trend.change_kg, Some(-10.5));
+    }
+
+    #[test]
+    fn weekly_rate_follows_a_linear_loss() {
+        let entries = [
+            entry(datetime!(2024-03-01 07:00 UTC), 80.0),
+            entry(datetime!(2024-03-08 07:00 UTC), 79.5),
+            entry(datetime!(2024-03-15 07:00 UTC), 79.0),
+        ];
+        assert_eq!(trend(&entries, &HashMap::new()).weekly_rate_kg, Some(-0.5));
+    }
+}